@@ -0,0 +1,1116 @@
+//! Reading and writing values in AMF, the format that shared objects, `ByteArray.writeObject`
+//! and local connections serialize values to.
+//!
+//! Both versions of AMF refer back to objects that were already written by their index in a
+//! table, rather than writing them again. AMF3 does the same for strings and for the traits of
+//! classes. Objects that are referred to are kept as `AmfValue::Reference`, which lets an
+//! object contain itself.
+
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// Identifies an array or object among the values that are written or read together.
+pub type ObjectId = usize;
+
+/// How deeply values can be nested in each other when they're read. Deeper data is refused,
+/// rather than overflowing the stack here or when the values are turned into objects.
+const MAX_DEPTH: usize = 128;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AmfVersion {
+    Amf0,
+    Amf3,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum AmfValue {
+    Undefined,
+    Null,
+    Bool(bool),
+    Number(f64),
+    /// An integer, which is written as a number if it doesn't fit into 29 bits, or in AMF0.
+    Integer(i32),
+    String(String),
+    /// A date, in milliseconds since the epoch.
+    Date(f64),
+    Xml(String),
+    ByteArray(Vec<u8>),
+    Array {
+        id: ObjectId,
+        /// The elements up to the first hole.
+        dense: Vec<AmfValue>,
+        /// Every other property, by name.
+        associative: Vec<(String, AmfValue)>,
+        length: u32,
+    },
+    Object {
+        id: ObjectId,
+        traits: Traits,
+        /// The values of the sealed fields, in the order of their names in `traits`.
+        sealed: Vec<AmfValue>,
+        /// The dynamic properties, if the traits are dynamic.
+        dynamic: Vec<(String, AmfValue)>,
+    },
+    /// An array or object that was written before.
+    Reference(ObjectId),
+    /// A value that can be read, but not turned into anything, such as a vector.
+    Unsupported,
+}
+
+/// The class of an object.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Traits {
+    /// The alias the class was registered with, or empty for a plain `Object`.
+    pub name: String,
+    pub is_dynamic: bool,
+    /// The names of the fields that every instance of the class has.
+    pub sealed: Vec<String>,
+}
+
+impl Traits {
+    /// The traits of a plain `Object`, which only has dynamic properties.
+    pub fn anonymous() -> Self {
+        Self {
+            name: String::new(),
+            is_dynamic: true,
+            sealed: Vec::new(),
+        }
+    }
+}
+
+/// The contents of a `.sol` file, which stores the data of a shared object.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Lso {
+    pub name: String,
+    pub version: AmfVersion,
+    pub body: Vec<(String, AmfValue)>,
+}
+
+#[derive(Debug, Error)]
+pub enum AmfError {
+    #[error("Unexpected end of AMF data")]
+    UnexpectedEnd,
+
+    #[error("Invalid AMF marker {0:#04x}")]
+    InvalidMarker(u8),
+
+    #[error("Reference to an AMF value that wasn't read yet")]
+    InvalidReference,
+
+    #[error("Externalizable objects can't be read")]
+    Externalizable,
+
+    #[error("Invalid .sol header")]
+    InvalidHeader,
+
+    #[error("AMF values are nested too deeply")]
+    TooDeep,
+}
+
+mod amf0 {
+    pub const NUMBER: u8 = 0x00;
+    pub const BOOLEAN: u8 = 0x01;
+    pub const STRING: u8 = 0x02;
+    pub const OBJECT: u8 = 0x03;
+    pub const MOVIE_CLIP: u8 = 0x04;
+    pub const NULL: u8 = 0x05;
+    pub const UNDEFINED: u8 = 0x06;
+    pub const REFERENCE: u8 = 0x07;
+    pub const ECMA_ARRAY: u8 = 0x08;
+    pub const OBJECT_END: u8 = 0x09;
+    pub const STRICT_ARRAY: u8 = 0x0A;
+    pub const DATE: u8 = 0x0B;
+    pub const LONG_STRING: u8 = 0x0C;
+    pub const UNSUPPORTED: u8 = 0x0D;
+    pub const RECORD_SET: u8 = 0x0E;
+    pub const XML: u8 = 0x0F;
+    pub const TYPED_OBJECT: u8 = 0x10;
+    pub const AVMPLUS: u8 = 0x11;
+}
+
+mod amf3 {
+    pub const UNDEFINED: u8 = 0x00;
+    pub const NULL: u8 = 0x01;
+    pub const FALSE: u8 = 0x02;
+    pub const TRUE: u8 = 0x03;
+    pub const INTEGER: u8 = 0x04;
+    pub const DOUBLE: u8 = 0x05;
+    pub const STRING: u8 = 0x06;
+    pub const XML_DOC: u8 = 0x07;
+    pub const DATE: u8 = 0x08;
+    pub const ARRAY: u8 = 0x09;
+    pub const OBJECT: u8 = 0x0A;
+    pub const XML: u8 = 0x0B;
+    pub const BYTE_ARRAY: u8 = 0x0C;
+    pub const VECTOR_INT: u8 = 0x0D;
+    pub const VECTOR_UINT: u8 = 0x0E;
+    pub const VECTOR_DOUBLE: u8 = 0x0F;
+    pub const VECTOR_OBJECT: u8 = 0x10;
+    pub const DICTIONARY: u8 = 0x11;
+
+    /// The range of integers that fit into 29 bits.
+    pub const INTEGER_RANGE: std::ops::Range<i32> = -(1 << 28)..(1 << 28);
+}
+
+const LSO_SIGNATURE: &[u8] = b"TCSO\x00\x04\x00\x00\x00\x00";
+
+/// Writes a single value, as `ByteArray.writeObject` does.
+pub fn write_value(version: AmfVersion, value: &AmfValue) -> Vec<u8> {
+    let mut writer = Writer::default();
+    match version {
+        AmfVersion::Amf0 => writer.write_amf0_value(value),
+        AmfVersion::Amf3 => writer.write_amf3_value(value),
+    }
+    writer.out
+}
+
+/// Reads a single value from the start of `data`, returning it along with how many bytes of
+/// `data` it took up.
+pub fn read_value(version: AmfVersion, data: &[u8]) -> Result<(AmfValue, usize), AmfError> {
+    let mut reader = Reader::new(data);
+    let value = match version {
+        AmfVersion::Amf0 => reader.read_amf0_value()?,
+        AmfVersion::Amf3 => reader.read_amf3_value()?,
+    };
+    Ok((value, reader.position))
+}
+
+/// Writes a `.sol` file. All of its properties share the same reference tables.
+pub fn write_lso(lso: &Lso) -> Vec<u8> {
+    let mut writer = Writer::default();
+    writer.out.extend_from_slice(LSO_SIGNATURE);
+    writer.write_amf0_string(&lso.name);
+    let version: u32 = match lso.version {
+        AmfVersion::Amf0 => 0,
+        AmfVersion::Amf3 => 3,
+    };
+    writer.out.extend_from_slice(&version.to_be_bytes());
+    for (name, value) in &lso.body {
+        match lso.version {
+            AmfVersion::Amf0 => {
+                writer.write_amf0_string(name);
+                writer.write_amf0_value(value);
+            }
+            AmfVersion::Amf3 => {
+                writer.write_amf3_string(name);
+                writer.write_amf3_value(value);
+            }
+        }
+        writer.out.push(0);
+    }
+
+    let mut out = vec![0x00, 0xBF];
+    out.extend_from_slice(&(writer.out.len() as u32).to_be_bytes());
+    out.extend_from_slice(&writer.out);
+    out
+}
+
+/// Reads a `.sol` file.
+pub fn read_lso(data: &[u8]) -> Result<Lso, AmfError> {
+    let mut reader = Reader::new(data);
+    if reader.read_bytes(2)? != [0x00, 0xBF] {
+        return Err(AmfError::InvalidHeader);
+    }
+    let _length = reader.read_u32()?;
+    if reader.read_bytes(LSO_SIGNATURE.len())? != LSO_SIGNATURE {
+        return Err(AmfError::InvalidHeader);
+    }
+    let name = reader.read_amf0_string()?;
+    let version = match reader.read_u32()? {
+        3 => AmfVersion::Amf3,
+        _ => AmfVersion::Amf0,
+    };
+
+    let mut body = Vec::new();
+    while reader.position < data.len() {
+        let property = match version {
+            AmfVersion::Amf0 => (reader.read_amf0_string()?, reader.read_amf0_value()?),
+            AmfVersion::Amf3 => (reader.read_amf3_string()?, reader.read_amf3_value()?),
+        };
+        body.push(property);
+        // Every property is followed by a padding byte
+        reader.read_u8()?;
+    }
+    Ok(Lso {
+        name,
+        version,
+        body,
+    })
+}
+
+/// The reference tables of AMF3, which start out empty again whenever AMF0 switches to it.
+#[derive(Default)]
+struct Amf3WriteTables {
+    strings: HashMap<String, usize>,
+    traits: Vec<Traits>,
+    /// The marker and index of each array and object written so far.
+    objects: HashMap<ObjectId, (u8, usize)>,
+    /// How many entries the object table has, which includes dates, XML and byte arrays.
+    object_count: usize,
+}
+
+#[derive(Default)]
+struct Writer {
+    out: Vec<u8>,
+    /// The index of each array and object written as AMF0 so far.
+    amf0_objects: HashMap<ObjectId, usize>,
+    amf3: Amf3WriteTables,
+}
+
+impl Writer {
+    fn write_f64(&mut self, value: f64) {
+        self.out.extend_from_slice(&value.to_be_bytes());
+    }
+
+    /// Writes a string prefixed by its length in 16 bits, as names are in AMF0.
+    fn write_amf0_string(&mut self, value: &str) {
+        let bytes = &value.as_bytes()[..value.len().min(u16::MAX.into())];
+        self.out
+            .extend_from_slice(&(bytes.len() as u16).to_be_bytes());
+        self.out.extend_from_slice(bytes);
+    }
+
+    fn write_amf0_properties<'a>(
+        &mut self,
+        properties: impl IntoIterator<Item = (&'a str, &'a AmfValue)>,
+    ) {
+        for (name, value) in properties {
+            self.write_amf0_string(name);
+            self.write_amf0_value(value);
+        }
+        self.write_amf0_string("");
+        self.out.push(amf0::OBJECT_END);
+    }
+
+    fn write_amf0_value(&mut self, value: &AmfValue) {
+        match value {
+            AmfValue::Undefined | AmfValue::Unsupported => self.out.push(amf0::UNDEFINED),
+            AmfValue::Null => self.out.push(amf0::NULL),
+            AmfValue::Bool(value) => self.out.extend_from_slice(&[amf0::BOOLEAN, *value as u8]),
+            AmfValue::Number(value) => {
+                self.out.push(amf0::NUMBER);
+                self.write_f64(*value);
+            }
+            AmfValue::Integer(value) => {
+                self.out.push(amf0::NUMBER);
+                self.write_f64((*value).into());
+            }
+            AmfValue::String(value) => {
+                if value.len() <= u16::MAX.into() {
+                    self.out.push(amf0::STRING);
+                    self.write_amf0_string(value);
+                } else {
+                    self.out.push(amf0::LONG_STRING);
+                    self.out
+                        .extend_from_slice(&(value.len() as u32).to_be_bytes());
+                    self.out.extend_from_slice(value.as_bytes());
+                }
+            }
+            AmfValue::Date(time) => {
+                self.out.push(amf0::DATE);
+                self.write_f64(*time);
+                // The time zone, which is always written as 0
+                self.out.extend_from_slice(&[0, 0]);
+            }
+            AmfValue::Xml(value) => {
+                self.out.push(amf0::XML);
+                self.out
+                    .extend_from_slice(&(value.len() as u32).to_be_bytes());
+                self.out.extend_from_slice(value.as_bytes());
+            }
+            AmfValue::ByteArray(_) => {
+                // AMF0 has no byte arrays, so Flash switches to AMF3 for them
+                self.out.push(amf0::AVMPLUS);
+                let tables = std::mem::take(&mut self.amf3);
+                self.write_amf3_value(value);
+                self.amf3 = tables;
+            }
+            AmfValue::Array {
+                id,
+                dense,
+                associative,
+                length,
+            } => {
+                self.amf0_objects.insert(*id, self.amf0_objects.len());
+                if associative.is_empty() && !dense.is_empty() {
+                    self.out.push(amf0::STRICT_ARRAY);
+                    self.out
+                        .extend_from_slice(&(dense.len() as u32).to_be_bytes());
+                    for value in dense {
+                        self.write_amf0_value(value);
+                    }
+                } else {
+                    self.out.push(amf0::ECMA_ARRAY);
+                    self.out.extend_from_slice(&length.to_be_bytes());
+                    let indices: Vec<_> = (0..dense.len()).map(|i| i.to_string()).collect();
+                    let dense = indices.iter().map(String::as_str).zip(dense);
+                    let associative = associative
+                        .iter()
+                        .map(|(name, value)| (name.as_str(), value));
+                    self.write_amf0_properties(dense.chain(associative));
+                }
+            }
+            AmfValue::Object {
+                id,
+                traits,
+                sealed,
+                dynamic,
+            } => {
+                self.amf0_objects.insert(*id, self.amf0_objects.len());
+                if traits.name.is_empty() {
+                    self.out.push(amf0::OBJECT);
+                } else {
+                    self.out.push(amf0::TYPED_OBJECT);
+                    self.write_amf0_string(&traits.name);
+                }
+                let sealed = traits.sealed.iter().map(String::as_str).zip(sealed);
+                let dynamic = dynamic.iter().map(|(name, value)| (name.as_str(), value));
+                self.write_amf0_properties(sealed.chain(dynamic));
+            }
+            AmfValue::Reference(id) => match self.amf0_objects.get(id) {
+                Some(&index) if index <= u16::MAX.into() => {
+                    self.out.push(amf0::REFERENCE);
+                    self.out.extend_from_slice(&(index as u16).to_be_bytes());
+                }
+                _ => self.out.push(amf0::NULL),
+            },
+        }
+    }
+
+    /// Writes an unsigned integer of up to 29 bits, in 1 to 4 bytes.
+    fn write_u29(&mut self, value: u32) {
+        let value = value & 0x1FFF_FFFF;
+        if value < 0x80 {
+            self.out.push(value as u8);
+        } else if value < 0x4000 {
+            self.out
+                .extend_from_slice(&[(value >> 7) as u8 | 0x80, (value & 0x7F) as u8]);
+        } else if value < 0x20_0000 {
+            self.out.extend_from_slice(&[
+                (value >> 14) as u8 | 0x80,
+                (value >> 7) as u8 | 0x80,
+                (value & 0x7F) as u8,
+            ]);
+        } else {
+            self.out.extend_from_slice(&[
+                (value >> 22) as u8 | 0x80,
+                (value >> 15) as u8 | 0x80,
+                (value >> 8) as u8 | 0x80,
+                value as u8,
+            ]);
+        }
+    }
+
+    /// Writes the length of a value that isn't a reference, followed by a flag saying so.
+    fn write_amf3_length(&mut self, length: usize) {
+        self.write_u29(((length as u32) << 1) | 1);
+    }
+
+    fn write_amf3_string(&mut self, value: &str) {
+        if let Some(&index) = self.amf3.strings.get(value) {
+            self.write_u29((index as u32) << 1);
+            return;
+        }
+        // The empty string is never referred to
+        if !value.is_empty() {
+            let index = self.amf3.strings.len();
+            self.amf3.strings.insert(value.to_string(), index);
+        }
+        self.write_amf3_length(value.len());
+        self.out.extend_from_slice(value.as_bytes());
+    }
+
+    /// Adds an entry to the object table, for a value that can't be referred to from here.
+    fn add_amf3_object(&mut self) {
+        self.amf3.object_count += 1;
+    }
+
+    fn add_amf3_referable_object(&mut self, id: ObjectId, marker: u8) {
+        self.amf3
+            .objects
+            .insert(id, (marker, self.amf3.object_count));
+        self.amf3.object_count += 1;
+    }
+
+    fn write_amf3_value(&mut self, value: &AmfValue) {
+        match value {
+            AmfValue::Undefined | AmfValue::Unsupported => self.out.push(amf3::UNDEFINED),
+            AmfValue::Null => self.out.push(amf3::NULL),
+            AmfValue::Bool(false) => self.out.push(amf3::FALSE),
+            AmfValue::Bool(true) => self.out.push(amf3::TRUE),
+            AmfValue::Integer(value) if amf3::INTEGER_RANGE.contains(value) => {
+                self.out.push(amf3::INTEGER);
+                self.write_u29(*value as u32);
+            }
+            AmfValue::Integer(value) => {
+                self.out.push(amf3::DOUBLE);
+                self.write_f64((*value).into());
+            }
+            AmfValue::Number(value) => {
+                self.out.push(amf3::DOUBLE);
+                self.write_f64(*value);
+            }
+            AmfValue::String(value) => {
+                self.out.push(amf3::STRING);
+                self.write_amf3_string(value);
+            }
+            AmfValue::Date(time) => {
+                self.out.push(amf3::DATE);
+                self.add_amf3_object();
+                self.write_u29(1);
+                self.write_f64(*time);
+            }
+            AmfValue::Xml(value) => {
+                self.out.push(amf3::XML);
+                self.add_amf3_object();
+                self.write_amf3_length(value.len());
+                self.out.extend_from_slice(value.as_bytes());
+            }
+            AmfValue::ByteArray(bytes) => {
+                self.out.push(amf3::BYTE_ARRAY);
+                self.add_amf3_object();
+                self.write_amf3_length(bytes.len());
+                self.out.extend_from_slice(bytes);
+            }
+            AmfValue::Array {
+                id,
+                dense,
+                associative,
+                ..
+            } => {
+                self.out.push(amf3::ARRAY);
+                self.add_amf3_referable_object(*id, amf3::ARRAY);
+                self.write_amf3_length(dense.len());
+                for (name, value) in associative {
+                    self.write_amf3_string(name);
+                    self.write_amf3_value(value);
+                }
+                self.write_amf3_string("");
+                for value in dense {
+                    self.write_amf3_value(value);
+                }
+            }
+            AmfValue::Object {
+                id,
+                traits,
+                sealed,
+                dynamic,
+            } => {
+                self.out.push(amf3::OBJECT);
+                self.add_amf3_referable_object(*id, amf3::OBJECT);
+                if let Some(index) = self.amf3.traits.iter().position(|other| other == traits) {
+                    self.write_u29(((index as u32) << 2) | 0b01);
+                } else {
+                    self.amf3.traits.push(traits.clone());
+                    let dynamic_flag = if traits.is_dynamic { 0b1000 } else { 0 };
+                    self.write_u29(((traits.sealed.len() as u32) << 4) | dynamic_flag | 0b011);
+                    self.write_amf3_string(&traits.name);
+                    for name in &traits.sealed {
+                        self.write_amf3_string(name);
+                    }
+                }
+                for value in sealed {
+                    self.write_amf3_value(value);
+                }
+                if traits.is_dynamic {
+                    for (name, value) in dynamic {
+                        self.write_amf3_string(name);
+                        self.write_amf3_value(value);
+                    }
+                    self.write_amf3_string("");
+                }
+            }
+            AmfValue::Reference(id) => match self.amf3.objects.get(id) {
+                Some(&(marker, index)) => {
+                    self.out.push(marker);
+                    self.write_u29((index as u32) << 1);
+                }
+                None => self.out.push(amf3::NULL),
+            },
+        }
+    }
+}
+
+/// An entry in the object table of AMF3.
+#[derive(Clone)]
+enum Amf3Object {
+    /// An array or object, which is referred to by its id.
+    Referable(ObjectId),
+    /// Anything else, which is copied wherever it's referred to.
+    Value(AmfValue),
+}
+
+#[derive(Default)]
+struct Amf3ReadTables {
+    strings: Vec<String>,
+    traits: Vec<Traits>,
+    objects: Vec<Amf3Object>,
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+    /// The id of each array and object read as AMF0 so far, by index.
+    amf0_objects: Vec<ObjectId>,
+    amf3: Amf3ReadTables,
+    /// The id that the next array or object gets, which is unique across both versions.
+    next_id: ObjectId,
+    /// How many values are being read around the current one, including itself.
+    depth: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            position: 0,
+            amf0_objects: Vec::new(),
+            amf3: Amf3ReadTables::default(),
+            next_id: 0,
+            depth: 0,
+        }
+    }
+
+    /// Reads a value inside of the values being read, unless that would nest them too deeply.
+    fn read_nested(
+        &mut self,
+        read: impl FnOnce(&mut Self) -> Result<AmfValue, AmfError>,
+    ) -> Result<AmfValue, AmfError> {
+        if self.depth >= MAX_DEPTH {
+            return Err(AmfError::TooDeep);
+        }
+        self.depth += 1;
+        let value = read(self);
+        self.depth -= 1;
+        value
+    }
+
+    fn new_id(&mut self) -> ObjectId {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    fn read_bytes(&mut self, length: usize) -> Result<&'a [u8], AmfError> {
+        let bytes = self
+            .data
+            .get(self.position..)
+            .and_then(|data| data.get(..length))
+            .ok_or(AmfError::UnexpectedEnd)?;
+        self.position += length;
+        Ok(bytes)
+    }
+
+    fn read_u8(&mut self) -> Result<u8, AmfError> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    fn read_u16(&mut self) -> Result<u16, AmfError> {
+        let bytes = self.read_bytes(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn read_u32(&mut self) -> Result<u32, AmfError> {
+        let bytes = self.read_bytes(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn read_f64(&mut self) -> Result<f64, AmfError> {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(self.read_bytes(8)?);
+        Ok(f64::from_be_bytes(bytes))
+    }
+
+    fn read_utf8(&mut self, length: usize) -> Result<String, AmfError> {
+        Ok(String::from_utf8_lossy(self.read_bytes(length)?).into_owned())
+    }
+
+    fn read_amf0_string(&mut self) -> Result<String, AmfError> {
+        let length = self.read_u16()?;
+        self.read_utf8(length.into())
+    }
+
+    fn read_amf0_properties(&mut self) -> Result<Vec<(String, AmfValue)>, AmfError> {
+        let mut properties = Vec::new();
+        loop {
+            let name = self.read_amf0_string()?;
+            if name.is_empty() {
+                match self.read_u8()? {
+                    amf0::OBJECT_END => return Ok(properties),
+                    marker => return Err(AmfError::InvalidMarker(marker)),
+                }
+            }
+            properties.push((name, self.read_amf0_value()?));
+        }
+    }
+
+    fn add_amf0_object(&mut self) -> ObjectId {
+        let id = self.new_id();
+        self.amf0_objects.push(id);
+        id
+    }
+
+    fn read_amf0_value(&mut self) -> Result<AmfValue, AmfError> {
+        self.read_nested(Self::read_amf0_contents)
+    }
+
+    fn read_amf0_contents(&mut self) -> Result<AmfValue, AmfError> {
+        Ok(match self.read_u8()? {
+            amf0::NUMBER => AmfValue::Number(self.read_f64()?),
+            amf0::BOOLEAN => AmfValue::Bool(self.read_u8()? != 0),
+            amf0::STRING => AmfValue::String(self.read_amf0_string()?),
+            amf0::OBJECT => {
+                let id = self.add_amf0_object();
+                AmfValue::Object {
+                    id,
+                    traits: Traits::anonymous(),
+                    sealed: Vec::new(),
+                    dynamic: self.read_amf0_properties()?,
+                }
+            }
+            amf0::TYPED_OBJECT => {
+                let name = self.read_amf0_string()?;
+                let id = self.add_amf0_object();
+                AmfValue::Object {
+                    id,
+                    traits: Traits {
+                        name,
+                        ..Traits::anonymous()
+                    },
+                    sealed: Vec::new(),
+                    dynamic: self.read_amf0_properties()?,
+                }
+            }
+            amf0::NULL => AmfValue::Null,
+            amf0::UNDEFINED => AmfValue::Undefined,
+            amf0::MOVIE_CLIP | amf0::UNSUPPORTED | amf0::RECORD_SET => AmfValue::Unsupported,
+            amf0::REFERENCE => {
+                let index = self.read_u16()?;
+                let id = self.amf0_objects.get(usize::from(index));
+                AmfValue::Reference(*id.ok_or(AmfError::InvalidReference)?)
+            }
+            amf0::ECMA_ARRAY => {
+                let length = self.read_u32()?;
+                let id = self.add_amf0_object();
+                AmfValue::Array {
+                    id,
+                    dense: Vec::new(),
+                    associative: self.read_amf0_properties()?,
+                    length,
+                }
+            }
+            amf0::STRICT_ARRAY => {
+                let length = self.read_u32()?;
+                let id = self.add_amf0_object();
+                let dense = (0..length)
+                    .map(|_| self.read_amf0_value())
+                    .collect::<Result<_, _>>()?;
+                AmfValue::Array {
+                    id,
+                    dense,
+                    associative: Vec::new(),
+                    length,
+                }
+            }
+            amf0::DATE => {
+                let time = self.read_f64()?;
+                let _time_zone = self.read_u16()?;
+                AmfValue::Date(time)
+            }
+            amf0::LONG_STRING => {
+                let length = self.read_u32()?;
+                AmfValue::String(self.read_utf8(length as usize)?)
+            }
+            amf0::XML => {
+                let length = self.read_u32()?;
+                AmfValue::Xml(self.read_utf8(length as usize)?)
+            }
+            amf0::AVMPLUS => {
+                let tables = std::mem::take(&mut self.amf3);
+                let value = self.read_amf3_value();
+                self.amf3 = tables;
+                value?
+            }
+            marker => return Err(AmfError::InvalidMarker(marker)),
+        })
+    }
+
+    fn read_u29(&mut self) -> Result<u32, AmfError> {
+        let mut value = 0;
+        for _ in 0..3 {
+            let byte = self.read_u8()?;
+            value = (value << 7) | u32::from(byte & 0x7F);
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        // The last byte has all 8 bits to itself
+        Ok((value << 8) | u32::from(self.read_u8()?))
+    }
+
+    /// Reads the header of a value that may be in the object table, which is either a reference
+    /// to the table or the length of a new value.
+    fn read_amf3_header(&mut self) -> Result<Result<usize, AmfValue>, AmfError> {
+        let header = self.read_u29()?;
+        if header & 1 == 0 {
+            return Ok(Err(self.amf3_object(header >> 1)?));
+        }
+        Ok(Ok((header >> 1) as usize))
+    }
+
+    fn amf3_object(&self, index: u32) -> Result<AmfValue, AmfError> {
+        match self.amf3.objects.get(index as usize) {
+            Some(Amf3Object::Referable(id)) => Ok(AmfValue::Reference(*id)),
+            Some(Amf3Object::Value(value)) => Ok(value.clone()),
+            None => Err(AmfError::InvalidReference),
+        }
+    }
+
+    fn read_amf3_string(&mut self) -> Result<String, AmfError> {
+        let header = self.read_u29()?;
+        if header & 1 == 0 {
+            return self
+                .amf3
+                .strings
+                .get((header >> 1) as usize)
+                .cloned()
+                .ok_or(AmfError::InvalidReference);
+        }
+        let value = self.read_utf8((header >> 1) as usize)?;
+        if !value.is_empty() {
+            self.amf3.strings.push(value.clone());
+        }
+        Ok(value)
+    }
+
+    fn read_amf3_properties(&mut self) -> Result<Vec<(String, AmfValue)>, AmfError> {
+        let mut properties = Vec::new();
+        loop {
+            let name = self.read_amf3_string()?;
+            if name.is_empty() {
+                return Ok(properties);
+            }
+            properties.push((name, self.read_amf3_value()?));
+        }
+    }
+
+    fn add_amf3_object(&mut self, value: AmfValue) -> AmfValue {
+        self.amf3.objects.push(Amf3Object::Value(value.clone()));
+        value
+    }
+
+    fn add_amf3_referable_object(&mut self) -> ObjectId {
+        let id = self.new_id();
+        self.amf3.objects.push(Amf3Object::Referable(id));
+        id
+    }
+
+    fn read_amf3_traits(&mut self, header: u32) -> Result<Traits, AmfError> {
+        if header & 0b10 == 0 {
+            return self
+                .amf3
+                .traits
+                .get((header >> 2) as usize)
+                .cloned()
+                .ok_or(AmfError::InvalidReference);
+        }
+        if header & 0b100 != 0 {
+            return Err(AmfError::Externalizable);
+        }
+        let name = self.read_amf3_string()?;
+        let sealed = (0..header >> 4)
+            .map(|_| self.read_amf3_string())
+            .collect::<Result<_, _>>()?;
+        let traits = Traits {
+            name,
+            is_dynamic: header & 0b1000 != 0,
+            sealed,
+        };
+        self.amf3.traits.push(traits.clone());
+        Ok(traits)
+    }
+
+    /// Reads a vector of `length` elements of `size` bytes each, which aren't supported.
+    fn skip_amf3_vector(&mut self, size: usize) -> Result<AmfValue, AmfError> {
+        let length = match self.read_amf3_header()? {
+            Ok(length) => length,
+            Err(value) => return Ok(value),
+        };
+        self.add_amf3_object(AmfValue::Unsupported);
+        let _fixed = self.read_u8()?;
+        self.read_bytes(length * size)?;
+        Ok(AmfValue::Unsupported)
+    }
+
+    fn read_amf3_value(&mut self) -> Result<AmfValue, AmfError> {
+        self.read_nested(Self::read_amf3_contents)
+    }
+
+    fn read_amf3_contents(&mut self) -> Result<AmfValue, AmfError> {
+        Ok(match self.read_u8()? {
+            amf3::UNDEFINED => AmfValue::Undefined,
+            amf3::NULL => AmfValue::Null,
+            amf3::FALSE => AmfValue::Bool(false),
+            amf3::TRUE => AmfValue::Bool(true),
+            amf3::INTEGER => {
+                // Sign extend from 29 bits
+                let value = self.read_u29()?;
+                AmfValue::Integer(((value << 3) as i32) >> 3)
+            }
+            amf3::DOUBLE => AmfValue::Number(self.read_f64()?),
+            amf3::STRING => AmfValue::String(self.read_amf3_string()?),
+            amf3::XML_DOC | amf3::XML => match self.read_amf3_header()? {
+                Ok(length) => {
+                    let value = AmfValue::Xml(self.read_utf8(length)?);
+                    self.add_amf3_object(value)
+                }
+                Err(value) => value,
+            },
+            amf3::DATE => match self.read_amf3_header()? {
+                Ok(_) => {
+                    let value = AmfValue::Date(self.read_f64()?);
+                    self.add_amf3_object(value)
+                }
+                Err(value) => value,
+            },
+            amf3::BYTE_ARRAY => match self.read_amf3_header()? {
+                Ok(length) => {
+                    let value = AmfValue::ByteArray(self.read_bytes(length)?.to_vec());
+                    self.add_amf3_object(value)
+                }
+                Err(value) => value,
+            },
+            amf3::ARRAY => match self.read_amf3_header()? {
+                Ok(length) => {
+                    let id = self.add_amf3_referable_object();
+                    let associative = self.read_amf3_properties()?;
+                    let dense = (0..length)
+                        .map(|_| self.read_amf3_value())
+                        .collect::<Result<_, _>>()?;
+                    AmfValue::Array {
+                        id,
+                        dense,
+                        associative,
+                        length: length as u32,
+                    }
+                }
+                Err(value) => value,
+            },
+            amf3::OBJECT => {
+                let header = self.read_u29()?;
+                if header & 1 == 0 {
+                    return self.amf3_object(header >> 1);
+                }
+                let id = self.add_amf3_referable_object();
+                let traits = self.read_amf3_traits(header)?;
+                let sealed = (0..traits.sealed.len())
+                    .map(|_| self.read_amf3_value())
+                    .collect::<Result<_, _>>()?;
+                let dynamic = if traits.is_dynamic {
+                    self.read_amf3_properties()?
+                } else {
+                    Vec::new()
+                };
+                AmfValue::Object {
+                    id,
+                    traits,
+                    sealed,
+                    dynamic,
+                }
+            }
+            amf3::VECTOR_INT | amf3::VECTOR_UINT => self.skip_amf3_vector(4)?,
+            amf3::VECTOR_DOUBLE => self.skip_amf3_vector(8)?,
+            amf3::VECTOR_OBJECT => match self.read_amf3_header()? {
+                Ok(length) => {
+                    self.add_amf3_object(AmfValue::Unsupported);
+                    let _fixed = self.read_u8()?;
+                    let _type_name = self.read_amf3_string()?;
+                    for _ in 0..length {
+                        self.read_amf3_value()?;
+                    }
+                    AmfValue::Unsupported
+                }
+                Err(value) => value,
+            },
+            amf3::DICTIONARY => match self.read_amf3_header()? {
+                Ok(length) => {
+                    self.add_amf3_object(AmfValue::Unsupported);
+                    let _weak_keys = self.read_u8()?;
+                    for _ in 0..length * 2 {
+                        self.read_amf3_value()?;
+                    }
+                    AmfValue::Unsupported
+                }
+                Err(value) => value,
+            },
+            marker => return Err(AmfError::InvalidMarker(marker)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AVM1_SOL: &[u8] =
+        include_bytes!("../../tests/tests/swfs/avm1/shared_object/RuffleTest.sol");
+    const AVM2_SOL: &[u8] =
+        include_bytes!("../../tests/tests/swfs/avm2/shared_object/RuffleTest.sol");
+
+    /// An object with a single dynamic property.
+    fn object(id: ObjectId, name: &str, value: AmfValue) -> AmfValue {
+        AmfValue::Object {
+            id,
+            traits: Traits::anonymous(),
+            sealed: Vec::new(),
+            dynamic: vec![(name.to_string(), value)],
+        }
+    }
+
+    /// Checks that `value` is written as `bytes`, and read back the same.
+    fn check_round_trip(version: AmfVersion, value: AmfValue, bytes: &[u8]) {
+        assert_eq!(write_value(version, &value), bytes);
+        let (read, length) = read_value(version, bytes).unwrap();
+        assert_eq!(read, value);
+        assert_eq!(length, bytes.len());
+    }
+
+    #[test]
+    fn shared_objects_written_by_flash_round_trip() {
+        for sol in [AVM1_SOL, AVM2_SOL] {
+            let lso = read_lso(sol).unwrap();
+            assert_eq!(lso.name, "RuffleTest");
+            assert_eq!(write_lso(&lso), sol);
+        }
+    }
+
+    #[test]
+    fn amf0_circular_reference() {
+        let value = object(0, "self", AmfValue::Reference(0));
+        check_round_trip(
+            AmfVersion::Amf0,
+            value,
+            b"\x03\x00\x04self\x07\x00\x00\x00\x00\x09",
+        );
+    }
+
+    #[test]
+    fn amf3_circular_reference() {
+        let value = object(0, "self", AmfValue::Reference(0));
+        check_round_trip(AmfVersion::Amf3, value, b"\x0a\x0b\x01\x09self\x0a\x00\x01");
+    }
+
+    #[test]
+    fn amf3_string_references() {
+        let value = AmfValue::Object {
+            id: 0,
+            traits: Traits::anonymous(),
+            sealed: Vec::new(),
+            dynamic: vec![
+                ("a".to_string(), AmfValue::String("b".to_string())),
+                ("b".to_string(), AmfValue::String("b".to_string())),
+            ],
+        };
+        check_round_trip(
+            AmfVersion::Amf3,
+            value,
+            b"\x0a\x0b\x01\x03a\x06\x03b\x02\x06\x02\x01",
+        );
+    }
+
+    #[test]
+    fn amf3_trait_references() {
+        let traits = Traits {
+            name: "Point".to_string(),
+            is_dynamic: false,
+            sealed: vec!["x".to_string()],
+        };
+        let point = |id, x| AmfValue::Object {
+            id,
+            traits: traits.clone(),
+            sealed: vec![AmfValue::Integer(x)],
+            dynamic: Vec::new(),
+        };
+        let value = AmfValue::Array {
+            id: 0,
+            dense: vec![point(1, 1), point(2, 2)],
+            associative: Vec::new(),
+            length: 2,
+        };
+        check_round_trip(
+            AmfVersion::Amf3,
+            value,
+            b"\x09\x05\x01\x0a\x13\x0bPoint\x03x\x04\x01\x0a\x01\x04\x02",
+        );
+    }
+
+    #[test]
+    fn amf3_references_count_dates() {
+        // The array is the first entry of the object table and the date the second,
+        // so the object is the third.
+        let value = AmfValue::Array {
+            id: 0,
+            dense: vec![
+                AmfValue::Date(0.0),
+                AmfValue::Object {
+                    id: 1,
+                    traits: Traits::anonymous(),
+                    sealed: Vec::new(),
+                    dynamic: Vec::new(),
+                },
+                AmfValue::Reference(1),
+            ],
+            associative: Vec::new(),
+            length: 3,
+        };
+        check_round_trip(
+            AmfVersion::Amf3,
+            value,
+            b"\x09\x07\x01\x08\x01\x00\x00\x00\x00\x00\x00\x00\x00\x0a\x0b\x01\x01\x0a\x04",
+        );
+    }
+
+    #[test]
+    fn circular_shared_object_round_trips() {
+        let lso = Lso {
+            name: "Circular".to_string(),
+            version: AmfVersion::Amf0,
+            body: vec![
+                (
+                    "parent".to_string(),
+                    object(0, "child", object(1, "parent", AmfValue::Reference(0))),
+                ),
+                ("again".to_string(), AmfValue::Reference(1)),
+            ],
+        };
+        let bytes = write_lso(&lso);
+        assert_eq!(read_lso(&bytes).unwrap(), lso);
+    }
+
+    /// `depth` values nested in each other: arrays of a single element, around a null.
+    fn nested_arrays(version: AmfVersion, depth: usize) -> Vec<u8> {
+        let (array, null): (&[u8], u8) = match version {
+            // A strict array of length 1
+            AmfVersion::Amf0 => (&[amf0::STRICT_ARRAY, 0, 0, 0, 1], amf0::NULL),
+            // A dense array of length 1, with no associative part
+            AmfVersion::Amf3 => (&[amf3::ARRAY, 0x03, 0x01], amf3::NULL),
+        };
+        let mut bytes = array.repeat(depth - 1);
+        bytes.push(null);
+        bytes
+    }
+
+    #[test]
+    fn deeply_nested_values_are_refused() {
+        for version in [AmfVersion::Amf0, AmfVersion::Amf3] {
+            let bytes = nested_arrays(version, MAX_DEPTH);
+            assert_eq!(read_value(version, &bytes).unwrap().1, bytes.len());
+
+            let bytes = nested_arrays(version, MAX_DEPTH + 1);
+            assert!(matches!(
+                read_value(version, &bytes),
+                Err(AmfError::TooDeep)
+            ));
+        }
+    }
+}
@@ -11,6 +11,7 @@ mod activation;
 mod callable_value;
 mod debug;
 mod error;
+mod filters;
 mod fscommand;
 pub(crate) mod globals;
 mod object;
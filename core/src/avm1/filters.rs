@@ -0,0 +1,73 @@
+//! Conversion of `flash.filters` objects into filters that can be rendered
+
+use crate::avm1::object::NativeObject;
+use crate::avm1::{Object, TObject};
+use ruffle_render::filters::{
+    BlurFilter, ColorMatrixFilter, ConvolutionFilter, DropShadowFilter, Filter, GlowFilter,
+};
+
+/// Converts an instance of one of the `flash.filters` classes into a `Filter`.
+///
+/// Returns `None` if the filter isn't supported yet, or if `object` isn't a filter at all.
+pub fn object_to_filter(object: Object<'_>) -> Option<Filter> {
+    if let NativeObject::BlurFilter(blur_filter) = object.native() {
+        return Some(Filter::BlurFilter(blur_filter.read().filter()));
+    }
+
+    if let Some(filter) = object.as_drop_shadow_filter_object() {
+        return Some(Filter::DropShadowFilter(DropShadowFilter {
+            color: swf::Color::from_rgb(
+                filter.color(),
+                (filter.alpha().clamp(0.0, 1.0) * 255.0) as u8,
+            ),
+            angle: filter.angle().to_radians() as f32,
+            distance: filter.distance() as f32,
+            blur_x: filter.blur_x() as f32,
+            blur_y: filter.blur_y() as f32,
+            strength: filter.strength() as f32,
+            quality: filter.quality().clamp(1, 15) as u8,
+            inner: filter.inner(),
+            knockout: filter.knockout(),
+            hide_object: filter.hide_object(),
+        }));
+    }
+
+    if let Some(filter) = object.as_glow_filter_object() {
+        return Some(Filter::GlowFilter(GlowFilter {
+            color: swf::Color::from_rgb(
+                filter.color() as u32,
+                (filter.alpha().clamp(0.0, 1.0) * 255.0) as u8,
+            ),
+            blur_x: filter.blur_x() as f32,
+            blur_y: filter.blur_y() as f32,
+            strength: filter.strength() as f32,
+            quality: filter.quality().clamp(1, 15) as u8,
+            inner: filter.inner(),
+            knockout: filter.knockout(),
+        }));
+    }
+
+    if let Some(filter) = object.as_color_matrix_filter_object() {
+        return Some(Filter::ColorMatrixFilter(ColorMatrixFilter {
+            matrix: filter.matrix().map(|value| value as f32),
+        }));
+    }
+
+    if let Some(filter) = object.as_convolution_filter_object() {
+        return Some(Filter::ConvolutionFilter(ConvolutionFilter {
+            matrix_x: filter.matrix_x().min(15),
+            matrix_y: filter.matrix_y().min(15),
+            matrix: filter.matrix().iter().map(|&value| value as f32).collect(),
+            divisor: filter.divisor() as f32,
+            bias: filter.bias() as f32,
+            preserve_alpha: filter.preserve_alpha(),
+            clamp: filter.clamp(),
+            color: swf::Color::from_rgb(
+                filter.color(),
+                (filter.alpha().clamp(0.0, 1.0) * 255.0) as u8,
+            ),
+        }));
+    }
+
+    None
+}
@@ -5,7 +5,7 @@ use crate::avm1::property::Attribute;
 use crate::avm1::property_decl::{define_properties_on, Declaration};
 use crate::avm1::{Object, ScriptObject, TObject, Value};
 use crate::display_object::{DisplayObject, TDisplayObject, TDisplayObjectContainer};
-use crate::string::{AvmString, WStr, WString};
+use crate::string::{AvmString, WStr};
 use gc_arena::Collect;
 use gc_arena::MutationContext;
 use std::str;
@@ -51,6 +51,7 @@ pub(crate) mod shared_object;
 pub(crate) mod sound;
 mod stage;
 pub(crate) mod string;
+mod style_sheet;
 pub(crate) mod system;
 pub(crate) mod system_capabilities;
 pub(crate) mod system_ime;
@@ -61,6 +62,7 @@ mod transform;
 mod video;
 mod xml;
 mod xml_node;
+pub(crate) mod xml_socket;
 
 const GLOBAL_DECLS: &[Declaration] = declare_properties! {
     "trace" => method(trace; DONT_ENUM);
@@ -384,24 +386,34 @@ pub fn escape<'gc>(
         return Ok(Value::Undefined);
     };
 
-    let mut buffer = Vec::<u8>::new();
     // TODO: unpaired surrogates will be lost; this is incorrect:
     // - `\u{DC00}` should become "%ED%B0%80";
     // - `\u{DFFF}` should become "%ED%BF%BF".
-    for c in s.to_utf8_lossy().bytes() {
+    let escaped = escape_str(&s.to_utf8_lossy());
+    Ok(AvmString::new_utf8(activation.context.gc_context, escaped).into())
+}
+
+/// Percent-encodes every byte of the UTF-8 form of `s`, other than letters and digits.
+pub(crate) fn escape_str(s: &str) -> String {
+    let mut buffer = String::with_capacity(s.len());
+    for c in s.bytes() {
         match c {
             // ECMA-262 violation: @*_+-./ are not unescaped chars.
             b'0'..=b'9' | b'A'..=b'Z' | b'a'..=b'z' => {
-                buffer.push(c);
+                buffer.push(c.into());
             }
             // ECMA-262 violation: Avm1 does not support unicode escapes.
             _ => {
                 const DIGITS: &[u8; 16] = b"0123456789ABCDEF";
-                buffer.extend([b'%', DIGITS[(c / 16) as usize], DIGITS[(c % 16) as usize]]);
+                buffer.extend([
+                    '%',
+                    DIGITS[(c / 16) as usize].into(),
+                    DIGITS[(c % 16) as usize].into(),
+                ]);
             }
         };
     }
-    Ok(AvmString::new(activation.context.gc_context, WString::from_buf(buffer)).into())
+    buffer
 }
 
 pub fn unescape<'gc>(
@@ -415,15 +427,23 @@ pub fn unescape<'gc>(
         return Ok(Value::Undefined);
     };
 
-    let s = s.to_utf8_lossy();
+    // TODO: unpaired surrogates will be lost; this is incorrect:
+    // - "%ED%B0%80" should become `\u{DC00}`;
+    // - "%ED%BF%BF" should become `\u{DFFF}`.
+    let unescaped = unescape_str(&s.to_utf8_lossy());
+    Ok(AvmString::new_utf8(activation.context.gc_context, unescaped).into())
+}
+
+/// Decodes the percent-encoded bytes of `s` as UTF-8.
+///
+/// Like in Flash, a `%` that isn't followed by two hex digits is dropped, along with any
+/// hex digit after it and the character that ended the escape.
+pub(crate) fn unescape_str(s: &str) -> String {
     let mut out_bytes = Vec::<u8>::with_capacity(s.len());
 
     let mut remain = 0;
     let mut hex_chars = Vec::<u8>::with_capacity(2);
 
-    // TODO: unpaired surrogates will be lost; this is incorrect:
-    // - "%ED%B0%80" should become `\u{DC00}`;
-    // - "%ED%BF%BF" should become `\u{DFFF}`.
     for c in s.bytes() {
         match c {
             b'%' => {
@@ -452,11 +472,7 @@ pub fn unescape<'gc>(
             }
         }
     }
-    Ok(AvmString::new_utf8(
-        activation.context.gc_context,
-        String::from_utf8_lossy(&out_bytes),
-    )
-    .into())
+    String::from_utf8_lossy(&out_bytes).into_owned()
 }
 
 /// This structure represents all system builtins that are used regardless of
@@ -540,6 +556,7 @@ pub fn create_globals<'gc>(
 
     let text_field_proto = text_field::create_proto(gc_context, object_proto, function_proto);
     let text_format_proto = text_format::create_proto(gc_context, object_proto, function_proto);
+    let style_sheet_proto = style_sheet::create_proto(gc_context, object_proto, function_proto);
 
     let array_proto = array::create_proto(gc_context, object_proto, function_proto);
 
@@ -551,6 +568,8 @@ pub fn create_globals<'gc>(
 
     let xml_proto = xml::create_proto(gc_context, xmlnode_proto, function_proto);
 
+    let xml_socket_proto = xml_socket::create_proto(gc_context, object_proto, function_proto);
+
     let string_proto = string::create_proto(gc_context, object_proto, function_proto);
     let number_proto = number::create_proto(gc_context, object_proto, function_proto);
     let boolean_proto = boolean::create_proto(gc_context, object_proto, function_proto);
@@ -658,6 +677,19 @@ pub fn create_globals<'gc>(
         function_proto,
         text_field_proto,
     );
+    let style_sheet = FunctionObject::constructor(
+        gc_context,
+        Executable::Native(style_sheet::constructor),
+        constructor_to_fn!(style_sheet::constructor),
+        function_proto,
+        style_sheet_proto,
+    );
+    text_field.define_value(
+        gc_context,
+        "StyleSheet",
+        style_sheet.into(),
+        Attribute::empty(),
+    );
     let text_format = FunctionObject::constructor(
         gc_context,
         Executable::Native(text_format::constructor),
@@ -680,6 +712,13 @@ pub fn create_globals<'gc>(
         function_proto,
         xml_proto,
     );
+    let xml_socket = FunctionObject::constructor(
+        gc_context,
+        Executable::Native(xml_socket::constructor),
+        constructor_to_fn!(xml_socket::constructor),
+        function_proto,
+        xml_socket_proto,
+    );
     let string = string::create_string_object(gc_context, string_proto, function_proto);
     let number = number::create_number_object(gc_context, number_proto, function_proto);
     let boolean = boolean::create_boolean_object(gc_context, boolean_proto, function_proto);
@@ -971,6 +1010,12 @@ pub fn create_globals<'gc>(
     );
     globals.define_value(gc_context, "XMLNode", xmlnode.into(), Attribute::DONT_ENUM);
     globals.define_value(gc_context, "XML", xml.into(), Attribute::DONT_ENUM);
+    globals.define_value(
+        gc_context,
+        "XMLSocket",
+        xml_socket.into(),
+        Attribute::DONT_ENUM,
+    );
     globals.define_value(gc_context, "String", string.into(), Attribute::DONT_ENUM);
     globals.define_value(gc_context, "Number", number.into(), Attribute::DONT_ENUM);
     globals.define_value(gc_context, "Boolean", boolean.into(), Attribute::DONT_ENUM);
@@ -1382,4 +1427,25 @@ mod tests {
             [Value::Null] => f64::NAN
         }
     );
+
+    test_method!(escape_function, "escape", setup,
+        [19] => {
+            ["abc123"] => "abc123",
+            ["a b+c&d=e"] => "a%20b%2Bc%26d%3De",
+            ["\u{e9}"] => "%C3%A9",
+            [] => Value::Undefined
+        }
+    );
+
+    test_method!(unescape_function, "unescape", setup,
+        [19] => {
+            ["a%20b%2Bc"] => "a b+c",
+            ["a+b"] => "a+b",
+            ["%C3%A9"] => "\u{e9}",
+            ["%zz"] => "z",
+            ["%4z1"] => "1",
+            ["100%"] => "100",
+            [] => Value::Undefined
+        }
+    );
 }
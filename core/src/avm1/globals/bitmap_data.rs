@@ -1,6 +1,7 @@
 //! flash.display.BitmapData object
 
 use super::matrix::object_to_matrix;
+use crate::avm1::filters::object_to_filter;
 use crate::avm1::function::{Executable, FunctionObject};
 use crate::avm1::globals::color_transform::ColorTransformObject;
 use crate::avm1::object::bitmap_data::BitmapDataObject;
@@ -11,9 +12,10 @@ use crate::bitmap::bitmap_data::{BitmapData, ChannelOptions, Color};
 use crate::bitmap::is_size_valid;
 use crate::character::Character;
 use crate::display_object::TDisplayObject;
-use crate::swf::BlendMode;
+use crate::swf::{BlendMode, Rectangle, Twips};
 use crate::{avm1_stub, avm_error};
 use gc_arena::{GcCell, MutationContext};
+use ruffle_render::filters::Filter;
 use ruffle_render::transform::Transform;
 use std::str::FromStr;
 
@@ -351,14 +353,14 @@ pub fn fill_rect<'gc>(
             if let Some(color_val) = args.get(1) {
                 let color = color_val.coerce_to_i32(activation)?;
 
-                let x = rectangle.get("x", activation)?.coerce_to_u32(activation)?;
-                let y = rectangle.get("y", activation)?.coerce_to_u32(activation)?;
+                let x = rectangle.get("x", activation)?.coerce_to_f64(activation)? as i32;
+                let y = rectangle.get("y", activation)?.coerce_to_f64(activation)? as i32;
                 let width = rectangle
                     .get("width", activation)?
-                    .coerce_to_u32(activation)?;
+                    .coerce_to_f64(activation)? as i32;
                 let height = rectangle
                     .get("height", activation)?
-                    .coerce_to_u32(activation)?;
+                    .coerce_to_f64(activation)? as i32;
 
                 bitmap_data
                     .bitmap_data()
@@ -556,6 +558,7 @@ pub fn draw<'gc>(
                 Transform {
                     matrix,
                     color_transform,
+                    matrix3d: None,
                 },
                 smoothing,
                 blend_mode,
@@ -570,24 +573,214 @@ pub fn draw<'gc>(
     Ok((-1).into())
 }
 
+/// Whether a filter can only be applied to a transparent bitmap, as it cuts out the object itself.
+fn needs_transparency(filter: &Filter) -> bool {
+    match filter {
+        Filter::DropShadowFilter(filter) => filter.knockout || filter.hide_object,
+        Filter::GlowFilter(filter) => filter.knockout,
+        _ => false,
+    }
+}
+
 pub fn apply_filter<'gc>(
     activation: &mut Activation<'_, 'gc>,
-    _this: Object<'gc>,
-    _args: &[Value<'gc>],
+    this: Object<'gc>,
+    args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    avm1_stub!(activation, "BitmapData", "applyFilter");
+    if let Some(bitmap_data) = this.as_bitmap_data_object() {
+        if !bitmap_data.disposed() {
+            let source_bitmap = args
+                .get(0)
+                .unwrap_or(&Value::Undefined)
+                .coerce_to_object(activation);
+            let source_bitmap = match source_bitmap.as_bitmap_data_object() {
+                Some(source_bitmap) if !source_bitmap.disposed() => source_bitmap,
+                _ => return Ok((-1).into()),
+            };
+
+            let source_rect = args
+                .get(1)
+                .unwrap_or(&Value::Undefined)
+                .coerce_to_object(activation);
+            let mut src_min_x = source_rect
+                .get("x", activation)?
+                .coerce_to_f64(activation)? as i32;
+            let mut src_min_y = source_rect
+                .get("y", activation)?
+                .coerce_to_f64(activation)? as i32;
+            let mut src_width = source_rect
+                .get("width", activation)?
+                .coerce_to_f64(activation)? as i32;
+            let mut src_height = source_rect
+                .get("height", activation)?
+                .coerce_to_f64(activation)? as i32;
+
+            let dest_point = args
+                .get(2)
+                .unwrap_or(&Value::Undefined)
+                .coerce_to_object(activation);
+            let mut dest_x = dest_point.get("x", activation)?.coerce_to_f64(activation)? as i32;
+            let mut dest_y = dest_point.get("y", activation)?.coerce_to_f64(activation)? as i32;
+
+            let filter = args
+                .get(3)
+                .unwrap_or(&Value::Undefined)
+                .coerce_to_object(activation);
+            let filter = match object_to_filter(filter) {
+                Some(filter) => filter,
+                None => {
+                    avm1_stub!(activation, "BitmapData", "applyFilter", "with this filter");
+                    return Ok((-1).into());
+                }
+            };
+            if needs_transparency(&filter) && !bitmap_data.transparency() {
+                return Ok((-2).into());
+            }
+
+            // Only the part of the area that is inside of both bitmaps is filtered
+            if src_min_x < 0 {
+                dest_x -= src_min_x;
+                src_width += src_min_x;
+                src_min_x = 0;
+            }
+            if src_min_y < 0 {
+                dest_y -= src_min_y;
+                src_height += src_min_y;
+                src_min_y = 0;
+            }
+            if dest_x < 0 {
+                src_min_x -= dest_x;
+                src_width += dest_x;
+                dest_x = 0;
+            }
+            if dest_y < 0 {
+                src_min_y -= dest_y;
+                src_height += dest_y;
+                dest_y = 0;
+            }
+            src_width = src_width
+                .min(source_bitmap.width() as i32 - src_min_x)
+                .min(bitmap_data.width() as i32 - dest_x);
+            src_height = src_height
+                .min(source_bitmap.height() as i32 - src_min_y)
+                .min(bitmap_data.height() as i32 - dest_y);
+            if src_width <= 0 || src_height <= 0 {
+                return Ok(0.into());
+            }
+            let source_point = (src_min_x as u32, src_min_y as u32);
+            let source_size = (src_width as u32, src_height as u32);
+            let dest_point = (dest_x as u32, dest_y as u32);
+
+            if activation.context.renderer.is_filter_supported(&filter) {
+                let source_handle = match source_bitmap
+                    .bitmap_data()
+                    .write(activation.context.gc_context)
+                    .bitmap_handle(activation.context.renderer)
+                {
+                    Some(handle) => handle,
+                    None => return Ok((-1).into()),
+                };
+                let bmd = bitmap_data
+                    .bitmap_data_wrapper()
+                    .overwrite_cpu_pixels_from_gpu(&mut activation.context);
+                let mut write = bmd.write(activation.context.gc_context);
+                write.apply_filter(
+                    &mut activation.context,
+                    source_handle,
+                    source_point,
+                    source_size,
+                    dest_point,
+                    filter,
+                );
+                return Ok(0.into());
+            }
+
+            // dealing with object aliasing...
+            let src_bitmap_clone: BitmapData; // only initialized if source is the same object as self
+            let src_bitmap_data_cell = source_bitmap.bitmap_data();
+            let src_bitmap_gc_ref; // only initialized if source is a different object than self
+            let source_bitmap_ref = // holds the reference to either of the ones above
+                if GcCell::ptr_eq(src_bitmap_data_cell, bitmap_data.bitmap_data()) {
+                    src_bitmap_clone = src_bitmap_data_cell.read().clone();
+                    &src_bitmap_clone
+                } else {
+                    src_bitmap_gc_ref = src_bitmap_data_cell.read();
+                    &src_bitmap_gc_ref
+                };
+
+            let applied = bitmap_data
+                .bitmap_data()
+                .write(activation.context.gc_context)
+                .apply_filter_cpu(
+                    source_bitmap_ref,
+                    source_point,
+                    source_size,
+                    dest_point,
+                    &filter,
+                );
+            if applied {
+                return Ok(0.into());
+            }
+            avm1_stub!(activation, "BitmapData", "applyFilter", "with this filter");
+        }
+    }
+
     Ok((-1).into())
 }
 
 pub fn generate_filter_rect<'gc>(
     activation: &mut Activation<'_, 'gc>,
     this: Object<'gc>,
-    _args: &[Value<'gc>],
+    args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     if let Some(bitmap_data) = this.as_bitmap_data_object() {
         if !bitmap_data.disposed() {
-            avm1_stub!(activation, "BitmapData", "generateFilterRect");
-            return Ok(Value::Undefined);
+            let source_rect = args
+                .get(0)
+                .unwrap_or(&Value::Undefined)
+                .coerce_to_object(activation);
+            let x = source_rect
+                .get("x", activation)?
+                .coerce_to_f64(activation)?;
+            let y = source_rect
+                .get("y", activation)?
+                .coerce_to_f64(activation)?;
+            let width = source_rect
+                .get("width", activation)?
+                .coerce_to_f64(activation)?;
+            let height = source_rect
+                .get("height", activation)?
+                .coerce_to_f64(activation)?;
+            let source_rect = Rectangle {
+                x_min: Twips::from_pixels(x),
+                y_min: Twips::from_pixels(y),
+                x_max: Twips::from_pixels(x + width),
+                y_max: Twips::from_pixels(y + height),
+            };
+
+            let filter = args
+                .get(1)
+                .unwrap_or(&Value::Undefined)
+                .coerce_to_object(activation);
+            // A filter that isn't supported yet is treated as one that stays inside the source
+            let rect = match object_to_filter(filter) {
+                Some(filter) => {
+                    crate::bitmap::bitmap_data::generate_filter_rect(&filter, source_rect)
+                }
+                None => source_rect,
+            };
+
+            let proto = activation.context.avm1.prototypes().rectangle_constructor;
+            let rect = proto.construct(
+                activation,
+                &[
+                    rect.x_min.to_pixels().into(),
+                    rect.y_min.to_pixels().into(),
+                    rect.width().to_pixels().into(),
+                    rect.height().to_pixels().into(),
+                ],
+            )?;
+            return Ok(rect);
         }
     }
 
@@ -614,9 +807,9 @@ pub fn color_transform<'gc>(
                     .coerce_to_f64(activation)? as i32;
 
                 let x_min = x.max(0) as u32;
-                let x_max = (x + width) as u32;
+                let x_max = x.saturating_add(width).max(0) as u32;
                 let y_min = y.max(0) as u32;
-                let y_max = (y + height) as u32;
+                let y_max = y.saturating_add(height).max(0) as u32;
 
                 let color_transform = match ColorTransformObject::cast(*color_transform) {
                     Some(color_transform) => color_transform.read().clone(),
@@ -628,6 +821,7 @@ pub fn color_transform<'gc>(
                     .write(activation.context.gc_context)
                     .color_transform(x_min, y_min, x_max, y_max, color_transform.into());
             }
+            return Ok(Value::Undefined);
         }
     }
 
@@ -746,12 +940,98 @@ pub fn perlin_noise<'gc>(
 pub fn hit_test<'gc>(
     activation: &mut Activation<'_, 'gc>,
     this: Object<'gc>,
-    _args: &[Value<'gc>],
+    args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     if let Some(bitmap_data) = this.as_bitmap_data_object() {
         if !bitmap_data.disposed() {
-            avm1_stub!(activation, "BitmapData", "hitTest");
-            return Ok(Value::Undefined);
+            let first_point = args
+                .get(0)
+                .unwrap_or(&Value::Undefined)
+                .coerce_to_object(activation);
+            let top_left = (
+                first_point
+                    .get("x", activation)?
+                    .coerce_to_f64(activation)? as i64,
+                first_point
+                    .get("y", activation)?
+                    .coerce_to_f64(activation)? as i64,
+            );
+            let alpha_threshold = args
+                .get(1)
+                .unwrap_or(&Value::Undefined)
+                .coerce_to_f64(activation)? as u32;
+
+            let compare_object = args
+                .get(2)
+                .unwrap_or(&Value::Undefined)
+                .coerce_to_object(activation);
+
+            // Everything is tested relative to the top left of this bitmap
+            if let Some(other_bitmap) = compare_object.as_bitmap_data_object() {
+                if other_bitmap.disposed() {
+                    return Ok((-1).into());
+                }
+                let second_point = args
+                    .get(3)
+                    .unwrap_or(&Value::Undefined)
+                    .coerce_to_object(activation);
+                let second_point = (
+                    second_point
+                        .get("x", activation)?
+                        .coerce_to_f64(activation)? as i64,
+                    second_point
+                        .get("y", activation)?
+                        .coerce_to_f64(activation)? as i64,
+                );
+                let second_alpha_threshold =
+                    args.get(4).unwrap_or(&1.into()).coerce_to_f64(activation)? as u32;
+
+                // Both bitmaps can be read at once, even if they're the same one
+                let result = bitmap_data.bitmap_data().read().hit_test_bitmapdata(
+                    alpha_threshold,
+                    &other_bitmap.bitmap_data().read(),
+                    (
+                        second_point.0.saturating_sub(top_left.0),
+                        second_point.1.saturating_sub(top_left.1),
+                    ),
+                    second_alpha_threshold,
+                );
+                return Ok(result.into());
+            }
+
+            // Otherwise it's either a Rectangle, or a Point that's tested as a single pixel
+            let x = compare_object
+                .get("x", activation)?
+                .coerce_to_f64(activation)? as i64;
+            let y = compare_object
+                .get("y", activation)?
+                .coerce_to_f64(activation)? as i64;
+            let rectangle = activation.context.avm1.prototypes().rectangle;
+            let rectangle_constructor = activation.context.avm1.prototypes().rectangle_constructor;
+            let (width, height) =
+                if compare_object.is_instance_of(activation, rectangle_constructor, rectangle)? {
+                    (
+                        compare_object
+                            .get("width", activation)?
+                            .coerce_to_f64(activation)? as i64,
+                        compare_object
+                            .get("height", activation)?
+                            .coerce_to_f64(activation)? as i64,
+                    )
+                } else {
+                    (1, 1)
+                };
+
+            let result = bitmap_data.bitmap_data().read().hit_test_rectangle(
+                alpha_threshold,
+                (
+                    x.saturating_sub(top_left.0),
+                    y.saturating_sub(top_left.1),
+                    width,
+                    height,
+                ),
+            );
+            return Ok(result.into());
         }
     }
 
@@ -1047,21 +1327,23 @@ pub fn palette_map<'gc>(
             if let Some(src_bitmap) = source_bitmap.as_bitmap_data_object() {
                 if !src_bitmap.disposed() {
                     // dealing with object aliasing...
+                    let src_bitmap_clone: BitmapData; // only initialized if source is the same object as self
                     let src_bitmap_data_cell = src_bitmap.bitmap_data();
-                    let read;
-                    let source: Option<&BitmapData> =
+                    let src_bitmap_gc_ref; // only initialized if source is a different object than self
+                    let source_bitmap_ref = // holds the reference to either of the ones above
                         if GcCell::ptr_eq(src_bitmap_data_cell, bitmap_data.bitmap_data()) {
-                            None
+                            src_bitmap_clone = src_bitmap_data_cell.read().clone();
+                            &src_bitmap_clone
                         } else {
-                            read = src_bitmap_data_cell.read();
-                            Some(&read)
+                            src_bitmap_gc_ref = src_bitmap_data_cell.read();
+                            &src_bitmap_gc_ref
                         };
 
                     bitmap_data
                         .bitmap_data()
                         .write(activation.context.gc_context)
                         .palette_map(
-                            source,
+                            source_bitmap_ref,
                             (src_min_x, src_min_y, src_width, src_height),
                             (dest_x, dest_y),
                             (red_array, green_array, blue_array, alpha_array),
@@ -1350,3 +1632,116 @@ pub fn create_bitmap_data_object<'gc>(
     define_properties_on(OBJECT_DECLS, gc_context, object, fn_proto);
     bitmap_data
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::avm1::test_utils::with_avm;
+
+    const BLACK: i32 = 0xFF000000u32 as i32;
+    const RED: i32 = 0xFFFF0000u32 as i32;
+    const GREEN: i32 = 0xFF00FF00u32 as i32;
+
+    /// A new opaque black `BitmapData`, as `new BitmapData(width, height, false, 0xFF000000)`.
+    fn bitmap_data<'gc>(
+        activation: &mut Activation<'_, 'gc>,
+        width: u32,
+        height: u32,
+    ) -> Result<Object<'gc>, Error<'gc>> {
+        let constructor = activation.context.avm1.prototypes().bitmap_data_constructor;
+        let args = [width.into(), height.into(), false.into(), BLACK.into()];
+        Ok(constructor
+            .construct(activation, &args)?
+            .coerce_to_object(activation))
+    }
+
+    fn rectangle<'gc>(
+        activation: &mut Activation<'_, 'gc>,
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+    ) -> Result<Value<'gc>, Error<'gc>> {
+        let constructor = activation.context.avm1.prototypes().rectangle_constructor;
+        let args = [x.into(), y.into(), width.into(), height.into()];
+        constructor.construct(activation, &args)
+    }
+
+    /// Checks every pixel of a 4x4 bitmap against the colors expected for its coordinates.
+    fn check_pixels<'gc>(
+        activation: &mut Activation<'_, 'gc>,
+        bitmap_data: Object<'gc>,
+        expected: impl Fn(i32, i32) -> i32,
+    ) -> Result<(), Error<'gc>> {
+        for y in 0..4 {
+            for x in 0..4 {
+                let pixel = get_pixel32(activation, bitmap_data, &[x.into(), y.into()])?;
+                assert_eq!(pixel, expected(x, y).into(), "Pixel at ({x}, {y})");
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn fill_rect_clips_to_bitmap() {
+        with_avm(8, |activation, _root| {
+            let bitmap_data = bitmap_data(activation, 4, 4)?;
+
+            // Coordinates are truncated, and only the part inside of the bitmap is filled
+            let rect = rectangle(activation, -2.5, 1.5, 4.0, 10.0)?;
+            let result = fill_rect(activation, bitmap_data, &[rect, RED.into()])?;
+            assert_eq!(result, Value::Undefined);
+            check_pixels(activation, bitmap_data, |x, y| {
+                if x < 2 && y >= 1 {
+                    RED
+                } else {
+                    BLACK
+                }
+            })?;
+
+            // A rectangle entirely outside of the bitmap fills nothing
+            let rect = rectangle(activation, 4.0, -8.0, 4.0, 4.0)?;
+            fill_rect(activation, bitmap_data, &[rect, GREEN.into()])?;
+            check_pixels(activation, bitmap_data, |x, y| {
+                if x < 2 && y >= 1 {
+                    RED
+                } else {
+                    BLACK
+                }
+            })
+        });
+    }
+
+    #[test]
+    fn color_transform_returns_undefined() {
+        with_avm(8, |activation, _root| {
+            let bitmap_data = bitmap_data(activation, 4, 4)?;
+            let constructor = activation
+                .context
+                .avm1
+                .prototypes()
+                .color_transform_constructor;
+            let args = [1, 1, 1, 1, 0, 255, 0, 0].map(Value::from);
+            let green = constructor.construct(activation, &args)?;
+
+            // The end of the rectangle is past the largest integer, which used to overflow
+            let max = f64::from(i32::MAX);
+            let rect = rectangle(activation, 1.0, 2.0, max, max)?;
+            let result = color_transform(activation, bitmap_data, &[rect, green])?;
+            assert_eq!(result, Value::Undefined);
+            check_pixels(activation, bitmap_data, |x, y| {
+                if x >= 1 && y >= 2 {
+                    GREEN
+                } else {
+                    BLACK
+                }
+            })?;
+
+            // Anything but a ColorTransform is still rejected
+            let rect = rectangle(activation, 0.0, 0.0, 4.0, 4.0)?;
+            let result = color_transform(activation, bitmap_data, &[rect, Value::Null])?;
+            assert_eq!(result, (-3).into());
+            Ok(())
+        });
+    }
+}
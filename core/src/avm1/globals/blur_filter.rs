@@ -5,6 +5,7 @@ use crate::avm1::object::NativeObject;
 use crate::avm1::property_decl::{define_properties_on, Declaration};
 use crate::avm1::{Activation, Error, Object, ScriptObject, TObject, Value};
 use gc_arena::{Collect, GcCell, MutationContext};
+use ruffle_render::filters::BlurFilter;
 
 #[derive(Clone, Debug, Collect)]
 #[collect(require_static)]
@@ -14,6 +15,17 @@ pub struct BlurFilterObject {
     quality: i32,
 }
 
+impl BlurFilterObject {
+    /// The filter that this object describes.
+    pub fn filter(&self) -> BlurFilter {
+        BlurFilter {
+            blur_x: self.blur_x as f32,
+            blur_y: self.blur_y as f32,
+            quality: self.quality.clamp(1, 15) as u8,
+        }
+    }
+}
+
 macro_rules! blur_filter_method {
     ($index:literal) => {
         |activation, this, args| method(activation, this, args, $index)
@@ -145,6 +145,8 @@ fn set_rgb<'gc>(
         color_transform.r_add = r.into();
         color_transform.g_add = g.into();
         color_transform.b_add = b.into();
+        drop(base);
+        target.invalidate_parent_cached_bitmap(activation.context.gc_context);
     }
     Ok(Value::Undefined)
 }
@@ -204,6 +206,8 @@ fn set_transform<'gc>(
         set_color_add(activation, transform, "gb", &mut color_transform.g_add)?;
         set_color_add(activation, transform, "bb", &mut color_transform.b_add)?;
         set_color_add(activation, transform, "ab", &mut color_transform.a_add)?;
+        drop(base);
+        target.invalidate_parent_cached_bitmap(activation.context.gc_context);
     }
 
     Ok(Value::Undefined)
@@ -1,16 +1,52 @@
 //! AVM1 LoadVars object
-//! TODO: bytesLoaded, bytesTotal, contentType, addRequestHeader
+//! TODO: Update bytesLoaded while the data is still being transferred
 
 use crate::avm1::activation::Activation;
 use crate::avm1::error::Error;
 use crate::avm1::function::ExecutionReason;
+use crate::avm1::globals::{escape_str, unescape_str};
 use crate::avm1::property::Attribute;
 use crate::avm1::property_decl::{define_properties_on, Declaration};
-use crate::avm1::{Object, ScriptObject, TObject, Value};
-use crate::avm1_stub;
+use crate::avm1::{ArrayObject, Object, ScriptObject, TObject, Value};
 use crate::backend::navigator::{NavigationMethod, Request};
 use crate::string::AvmString;
 use gc_arena::MutationContext;
+use indexmap::IndexMap;
+
+/// Headers that Flash doesn't allow `addRequestHeader` to set.
+const DISALLOWED_HEADERS: &[&str] = &[
+    "Accept-Charset",
+    "Accept-Encoding",
+    "Accept-Ranges",
+    "Age",
+    "Allow",
+    "Allowed",
+    "Connection",
+    "Content-Length",
+    "Content-Location",
+    "Content-Range",
+    "ETag",
+    "Host",
+    "Last-Modified",
+    "Location",
+    "Max-Forwards",
+    "Proxy-Authenticate",
+    "Proxy-Authorization",
+    "Public",
+    "Range",
+    "Retry-After",
+    "Server",
+    "TE",
+    "Trailer",
+    "Transfer-Encoding",
+    "Upgrade",
+    "URI",
+    "Vary",
+    "Via",
+    "Warning",
+    "WWW-Authenticate",
+    "x-flash-version",
+];
 
 const PROTO_DECLS: &[Declaration] = declare_properties! {
     "load" => method(load; DONT_ENUM | DONT_DELETE);
@@ -48,10 +84,55 @@ pub fn create_proto<'gc>(
 
 fn add_request_header<'gc>(
     activation: &mut Activation<'_, 'gc>,
-    _this: Object<'gc>,
-    _args: &[Value<'gc>],
+    this: Object<'gc>,
+    args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    avm1_stub!(activation, "LoadVars", "addRequestHeader");
+    // Headers are either a name and a value, or an array of alternating names and values.
+    let mut new_headers = vec![];
+    match args {
+        [Value::Object(array), ..] => {
+            for index in 0..array.length(activation)? / 2 {
+                new_headers.push((
+                    array.get_element(activation, index * 2),
+                    array.get_element(activation, index * 2 + 1),
+                ));
+            }
+        }
+        [name, value, ..] => new_headers.push((*name, *value)),
+        _ => return Ok(Value::Undefined),
+    }
+
+    // Flash keeps the added headers in an undocumented array on the object.
+    let headers = match this.get("_customHeaders", activation)? {
+        Value::Object(headers) => headers,
+        _ => {
+            let headers: Object<'gc> = ArrayObject::empty(activation).into();
+            this.define_value(
+                activation.context.gc_context,
+                "_customHeaders",
+                headers.into(),
+                Attribute::DONT_ENUM,
+            );
+            headers
+        }
+    };
+    for (name, value) in new_headers {
+        // Only strings are accepted as headers.
+        let (Value::String(name), Value::String(_)) = (name, value) else {
+            continue;
+        };
+        let name_utf8 = name.to_utf8_lossy();
+        if DISALLOWED_HEADERS
+            .iter()
+            .any(|disallowed| name_utf8.eq_ignore_ascii_case(disallowed))
+        {
+            continue;
+        }
+        let length = headers.length(activation)?;
+        headers.set_element(activation, length, name.into())?;
+        headers.set_element(activation, length + 1, value)?;
+    }
+
     Ok(Value::Undefined)
 }
 
@@ -64,7 +145,16 @@ fn decode<'gc>(
     // Decode the query string into properties on this object.
     if let Some(data) = args.get(0) {
         let data = data.coerce_to_string(activation)?;
-        for (k, v) in url::form_urlencoded::parse(data.to_utf8_lossy().as_bytes()) {
+        for pair in data
+            .to_utf8_lossy()
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+        {
+            let (k, v) = pair.split_once('=').unwrap_or((pair, ""));
+            // Spaces may also be encoded as `+`, which has to be replaced before unescaping
+            // so that an escaped `%2B` is left as a `+`
+            let k = unescape_str(&k.replace('+', " "));
+            let v = unescape_str(&v.replace('+', " "));
             let k = AvmString::new_utf8(activation.context.gc_context, k);
             let v = AvmString::new_utf8(activation.context.gc_context, v);
             this.set(k, v.into(), activation)?;
@@ -168,8 +258,6 @@ fn send<'gc>(
         .coerce_to_string(activation)?;
     let method = NavigationMethod::from_method_str(&method_name).unwrap_or(NavigationMethod::Post);
 
-    use indexmap::IndexMap;
-
     let mut form_values = IndexMap::new();
     let keys = this.get_keys(activation);
 
@@ -223,8 +311,6 @@ fn to_string<'gc>(
     this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    use indexmap::IndexMap;
-
     let mut form_values = IndexMap::new();
     let keys = this.get_keys(activation);
 
@@ -242,26 +328,59 @@ fn to_string<'gc>(
         );
     }
 
-    let query_string = url::form_urlencoded::Serializer::new(String::new())
-        .extend_pairs(form_values.iter())
-        .finish();
+    // Unlike an HTML form, spaces are escaped as `%20` rather than `+`.
+    let query_string = form_values
+        .iter()
+        .map(|(k, v)| format!("{}={}", escape_str(k), escape_str(v)))
+        .collect::<Vec<_>>()
+        .join("&");
 
     Ok(AvmString::new_utf8(activation.context.gc_context, query_string).into())
 }
 
+/// The headers that were added to `object` with `addRequestHeader`.
+fn custom_headers<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    object: Object<'gc>,
+) -> Result<IndexMap<String, String>, Error<'gc>> {
+    let mut headers = IndexMap::new();
+    if let Value::Object(array) = object.get("_customHeaders", activation)? {
+        for index in 0..array.length(activation)? / 2 {
+            let name = array.get_element(activation, index * 2);
+            let value = array.get_element(activation, index * 2 + 1);
+            headers.insert(
+                name.coerce_to_string(activation)?.to_string(),
+                value.coerce_to_string(activation)?.to_string(),
+            );
+        }
+    }
+    Ok(headers)
+}
+
 fn spawn_load_var_fetch<'gc>(
     activation: &mut Activation<'_, 'gc>,
     loader_object: Object<'gc>,
     url: AvmString<'gc>,
     send_object: Option<(Object<'gc>, NavigationMethod)>,
 ) -> Result<Value<'gc>, Error<'gc>> {
-    let request = if let Some((send_object, method)) = send_object {
+    let (mut request, header_object) = if let Some((send_object, method)) = send_object {
         // Send properties from `send_object`.
-        activation.object_into_request(send_object, url, Some(method))
+        let mut request = activation.object_into_request(send_object, url, Some(method));
+        if let Some((body, _)) = request.body().clone() {
+            let content_type = send_object
+                .get("contentType", activation)?
+                .coerce_to_string(activation)?;
+            request.set_body((body, content_type.to_string()));
+        }
+        (request, send_object)
     } else {
         // Not sending any parameters.
-        Request::get(url.to_utf8_lossy().into_owned())
+        (
+            Request::get(url.to_utf8_lossy().into_owned()),
+            loader_object,
+        )
     };
+    request.set_headers(custom_headers(activation, header_object)?);
 
     let future = activation.context.load_manager.load_form_into_load_vars(
         activation.context.player.clone(),
@@ -1,44 +1,115 @@
 //! LocalConnection class
 
+use crate::amf::AmfValue;
 use crate::avm1::activation::Activation;
 use crate::avm1::error::Error;
+use crate::avm1::globals::shared_object::{serialize_value, ObjectTable};
 use crate::avm1::property_decl::{define_properties_on, Declaration};
 use crate::avm1::{Object, ScriptObject, Value};
 use crate::display_object::TDisplayObject;
+use crate::local_connection::{LocalConnectionObject, LocalConnections};
 use crate::string::AvmString;
 use gc_arena::MutationContext;
 
 const PROTO_DECLS: &[Declaration] = declare_properties! {
     "domain" => method(domain; DONT_DELETE | READ_ONLY);
+    "connect" => method(connect; DONT_ENUM | DONT_DELETE);
+    "send" => method(send; DONT_ENUM | DONT_DELETE);
+    "close" => method(close; DONT_ENUM | DONT_DELETE);
 };
 
+/// Methods of `LocalConnection` that can't be called through `send`.
+const RESERVED_METHODS: &[&str] = &[
+    "send",
+    "connect",
+    "close",
+    "domain",
+    "allowDomain",
+    "allowInsecureDomain",
+];
+
+/// The domain of the movie that's running this code.
+fn movie_domain(activation: &mut Activation<'_, '_>) -> String {
+    LocalConnections::domain(activation.base_clip().movie().url())
+}
+
 pub fn domain<'gc>(
     activation: &mut Activation<'_, 'gc>,
     _this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    let movie = activation.base_clip().movie();
-
-    let domain = if let Some(url) = movie.url() {
-        if let Ok(url) = url::Url::parse(url) {
-            if url.scheme() == "file" {
-                "localhost".into()
-            } else if let Some(domain) = url.domain() {
-                AvmString::new_utf8(activation.context.gc_context, domain)
-            } else {
-                // no domain?
-                "localhost".into()
-            }
-        } else {
-            tracing::error!("LocalConnection::domain: Unable to parse movie URL");
-            return Ok(Value::Null);
-        }
-    } else {
-        // No URL (loading local data).
-        "localhost".into()
+    let domain = movie_domain(activation);
+    Ok(AvmString::new_utf8(activation.context.gc_context, domain).into())
+}
+
+pub fn connect<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let Some(Value::String(name)) = args.get(0) else {
+        return Ok(false.into());
     };
+    // Connections can only listen within their own domain.
+    let name = name.to_utf8_lossy();
+    if name.is_empty() || name.contains(':') {
+        return Ok(false.into());
+    }
 
-    Ok(Value::String(domain))
+    let domain = movie_domain(activation);
+    let full_name = LocalConnections::full_name(&domain, &name);
+    let connected = activation.context.local_connections.connect(
+        LocalConnectionObject::Avm1(this),
+        full_name,
+        domain,
+    );
+    Ok(connected.into())
+}
+
+pub fn send<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let [Value::String(name), Value::String(method_name), arguments @ ..] = args else {
+        return Ok(false.into());
+    };
+    let name = name.to_utf8_lossy();
+    let method_name = method_name.to_utf8_lossy().into_owned();
+    if name.is_empty() || method_name.is_empty() || RESERVED_METHODS.contains(&&*method_name) {
+        return Ok(false.into());
+    }
+
+    // The arguments are written together, so they may refer to each other's objects
+    let mut objects = ObjectTable::new();
+    let arguments = arguments
+        .iter()
+        .map(|argument| {
+            serialize_value(activation, *argument, &mut objects).unwrap_or(AmfValue::Undefined)
+        })
+        .collect();
+    let domain = movie_domain(activation);
+    let full_name = LocalConnections::full_name(&domain, &name);
+    activation.context.local_connections.send(
+        LocalConnectionObject::Avm1(this),
+        domain,
+        full_name,
+        method_name,
+        arguments,
+    );
+    Ok(true.into())
+}
+
+pub fn close<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    activation
+        .context
+        .local_connections
+        .close(LocalConnectionObject::Avm1(this));
+    Ok(Value::Undefined)
 }
 
 pub fn constructor<'gc>(
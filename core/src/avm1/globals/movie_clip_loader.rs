@@ -135,16 +135,26 @@ fn get_progress<'gc>(
         };
         let result = ScriptObject::new(activation.context.gc_context, None);
         if let Some(target) = target {
+            let (bytes_loaded, bytes_total) = match target.as_movie_clip() {
+                // A clip that a movie is loaded into reports how much of it has arrived so far
+                Some(mc) if mc.is_root() => {
+                    (mc.compressed_loaded_bytes(), mc.compressed_total_bytes())
+                }
+                _ => {
+                    let len = target.movie().compressed_len() as u32;
+                    (len, len)
+                }
+            };
             result.define_value(
                 activation.context.gc_context,
                 "bytesLoaded",
-                target.movie().compressed_len().into(),
+                bytes_loaded.into(),
                 Attribute::empty(),
             );
             result.define_value(
                 activation.context.gc_context,
                 "bytesTotal",
-                target.movie().compressed_len().into(),
+                bytes_total.into(),
                 Attribute::empty(),
             );
         }
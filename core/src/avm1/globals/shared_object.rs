@@ -1,3 +1,4 @@
+use crate::amf::{AmfValue, AmfVersion, Lso, ObjectId, Traits};
 use crate::avm1::activation::Activation;
 use crate::avm1::error::Error;
 use crate::avm1::function::{Executable, FunctionObject};
@@ -5,14 +6,17 @@ use crate::avm1::object::shared_object::SharedObject;
 use crate::avm1::object::NativeObject;
 use crate::avm1::property::Attribute;
 use crate::avm1::property_decl::{define_properties_on, Declaration};
-use crate::avm1::{Object, ScriptObject, TObject, Value};
+use crate::avm1::{Object, ObjectPtr, ScriptObject, TObject, Value};
 use crate::avm1_stub;
+use crate::context::ActionType;
 use crate::display_object::TDisplayObject;
 use crate::string::AvmString;
-use flash_lso::types::Value as AmfValue;
-use flash_lso::types::{AMFVersion, Element, Lso};
 use gc_arena::MutationContext;
 use std::borrow::Cow;
+use std::collections::HashMap;
+
+/// Flash asks the user before letting a shared object grow larger than this.
+const DEFAULT_SIZE_LIMIT: usize = 100 * 1024;
 
 const PROTO_DECLS: &[Declaration] = declare_properties! {
     "clear" => method(clear; DONT_ENUM | DONT_DELETE);
@@ -54,10 +58,15 @@ pub fn get_disk_usage<'gc>(
     Ok(Value::Undefined)
 }
 
+/// The objects that were serialized so far, which are written as references when they're
+/// serialized again.
+pub type ObjectTable = HashMap<*const ObjectPtr, ObjectId>;
+
 /// Serialize a Value to an AmfValue
-fn serialize_value<'gc>(
+pub fn serialize_value<'gc>(
     activation: &mut Activation<'_, 'gc>,
     elem: Value<'gc>,
+    objects: &mut ObjectTable,
 ) -> Option<AmfValue> {
     match elem {
         Value::Undefined => Some(AmfValue::Undefined),
@@ -72,66 +81,142 @@ fn serialize_value<'gc>(
                 None
             } else if o.as_display_object().is_some() {
                 Some(AmfValue::Undefined)
-            } else if o.as_array_object().is_some() {
-                let mut values = Vec::new();
-                recursive_serialize(activation, o, &mut values);
-
-                // TODO: What happens if an exception is thrown here?
-                let length = o.length(activation).unwrap();
-                Some(AmfValue::ECMAArray(vec![], values, length as u32))
-            } else if let Some(xml_node) = o.as_xml_node() {
-                // TODO: What happens if an exception is thrown here?
-                let string = xml_node.into_string(activation).unwrap();
-                Some(AmfValue::XML(string.to_utf8_lossy().into_owned(), true))
-            } else if let NativeObject::Date(date) = o.native() {
-                Some(AmfValue::Date(date.read().time(), None))
+            } else if let Some(&id) = objects.get(&o.as_ptr()) {
+                Some(AmfValue::Reference(id))
             } else {
-                let mut object_body = Vec::new();
-                recursive_serialize(activation, o, &mut object_body);
-                Some(AmfValue::Object(object_body, None))
+                Some(serialize_object(activation, o, objects))
+            }
+        }
+    }
+}
+
+/// Serialize an object that isn't in `objects` yet to an AmfValue
+fn serialize_object<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    o: Object<'gc>,
+    objects: &mut ObjectTable,
+) -> AmfValue {
+    if o.as_array_object().is_some() {
+        // Arrays and objects are added before their children, which may refer back to them.
+        let id = objects.len();
+        objects.insert(o.as_ptr(), id);
+        let mut values = Vec::new();
+        recursive_serialize(activation, o, &mut values, objects);
+
+        // TODO: What happens if an exception is thrown here?
+        let length = o.length(activation).unwrap();
+        AmfValue::Array {
+            id,
+            dense: Vec::new(),
+            associative: values,
+            length: length as u32,
+        }
+    } else if let Some(xml_node) = o.as_xml_node() {
+        // TODO: What happens if an exception is thrown here?
+        let string = xml_node.into_string(activation).unwrap();
+        AmfValue::Xml(string.to_utf8_lossy().into_owned())
+    } else if let NativeObject::Date(date) = o.native() {
+        AmfValue::Date(date.read().time())
+    } else {
+        let id = objects.len();
+        objects.insert(o.as_ptr(), id);
+        let mut object_body = Vec::new();
+        recursive_serialize(activation, o, &mut object_body, objects);
+        // Instances of a class registered with `Object.registerClass` are typed objects.
+        let traits = Traits {
+            name: registered_class_name(activation, o).unwrap_or_default(),
+            ..Traits::anonymous()
+        };
+        AmfValue::Object {
+            id,
+            traits,
+            sealed: Vec::new(),
+            dynamic: object_body,
+        }
+    }
+}
+
+/// The symbol of the constructor registered with `Object.registerClass` that made this object.
+fn registered_class_name<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    o: Object<'gc>,
+) -> Option<String> {
+    let Value::Object(proto) = o.proto(activation) else {
+        return None;
+    };
+    let constructors: Vec<_> = activation
+        .context
+        .avm1
+        .registered_constructors(activation.swf_version())
+        .collect();
+    for (symbol, constructor) in constructors {
+        let constructor = Object::FunctionObject(constructor);
+        if let Ok(Value::Object(prototype)) = constructor.get("prototype", activation) {
+            if Object::ptr_eq(prototype, proto) {
+                return Some(symbol.to_string());
             }
         }
     }
+    None
 }
 
 /// Serialize an Object and any children to a JSON object
 fn recursive_serialize<'gc>(
     activation: &mut Activation<'_, 'gc>,
     obj: Object<'gc>,
-    elements: &mut Vec<Element>,
+    elements: &mut Vec<(String, AmfValue)>,
+    objects: &mut ObjectTable,
 ) {
     // Reversed to match flash player ordering
     for element_name in obj.get_keys(activation).into_iter().rev() {
         if let Ok(elem) = obj.get(element_name, activation) {
-            if let Some(v) = serialize_value(activation, elem) {
-                elements.push(Element::new(element_name.to_utf8_lossy(), v));
+            if let Some(v) = serialize_value(activation, elem, objects) {
+                elements.push((element_name.to_utf8_lossy().into_owned(), v));
             }
         }
     }
 }
 
 /// Deserialize a AmfValue to a Value
-fn deserialize_value<'gc>(activation: &mut Activation<'_, 'gc>, val: &AmfValue) -> Value<'gc> {
+///
+/// `objects` are the arrays and objects that were deserialized so far, which references refer to.
+pub fn deserialize_value<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    val: &AmfValue,
+    objects: &mut HashMap<ObjectId, Object<'gc>>,
+) -> Value<'gc> {
     match val {
         AmfValue::Null => Value::Null,
         AmfValue::Undefined => Value::Undefined,
         AmfValue::Number(f) => (*f).into(),
+        AmfValue::Integer(num) => (*num).into(),
         AmfValue::String(s) => Value::String(AvmString::new_utf8(activation.context.gc_context, s)),
         AmfValue::Bool(b) => (*b).into(),
-        AmfValue::ECMAArray(_, associative, len) => {
+        AmfValue::Array {
+            id,
+            dense,
+            associative,
+            length,
+        } => {
             let array_constructor = activation.context.avm1.prototypes().array_constructor;
             if let Ok(Value::Object(obj)) =
-                array_constructor.construct(activation, &[(*len).into()])
+                array_constructor.construct(activation, &[(*length).into()])
             {
-                for entry in associative {
-                    let value = deserialize_value(activation, entry.value());
+                objects.insert(*id, obj);
+                for (i, value) in dense.iter().enumerate() {
+                    let value = deserialize_value(activation, value, objects);
+                    obj.set_element(activation, i as i32, value).unwrap();
+                }
+
+                for (name, value) in associative {
+                    let value = deserialize_value(activation, value, objects);
 
-                    if let Ok(i) = entry.name().parse::<i32>() {
+                    if let Ok(i) = name.parse::<i32>() {
                         obj.set_element(activation, i, value).unwrap();
                     } else {
                         obj.define_value(
                             activation.context.gc_context,
-                            AvmString::new_utf8(activation.context.gc_context, &entry.name),
+                            AvmString::new_utf8(activation.context.gc_context, name),
                             value,
                             Attribute::empty(),
                         );
@@ -143,15 +228,45 @@ fn deserialize_value<'gc>(activation: &mut Activation<'_, 'gc>, val: &AmfValue)
                 Value::Undefined
             }
         }
-        AmfValue::Object(elements, _) => {
-            // Deserialize Object
-            let obj = ScriptObject::new(
-                activation.context.gc_context,
-                Some(activation.context.avm1.prototypes().object),
-            );
-            for entry in elements {
-                let value = deserialize_value(activation, entry.value());
-                let name = AvmString::new_utf8(activation.context.gc_context, &entry.name);
+        AmfValue::Object {
+            id,
+            traits,
+            sealed,
+            dynamic,
+        } => {
+            // Typed objects are constructed by the class registered for them, if there's one.
+            let constructor = (!traits.name.is_empty())
+                .then(|| {
+                    let name = AvmString::new_utf8(activation.context.gc_context, &traits.name);
+                    activation
+                        .context
+                        .avm1
+                        .get_registered_constructor(activation.swf_version(), name)
+                        .copied()
+                })
+                .flatten();
+            let obj = match constructor
+                .map(|constructor| Object::FunctionObject(constructor).construct(activation, &[]))
+            {
+                Some(Ok(Value::Object(obj))) => Some(obj),
+                _ => None,
+            };
+            let obj = obj.unwrap_or_else(|| {
+                ScriptObject::new(
+                    activation.context.gc_context,
+                    Some(activation.context.avm1.prototypes().object),
+                )
+                .into()
+            });
+            objects.insert(*id, obj);
+            let properties = traits
+                .sealed
+                .iter()
+                .zip(sealed)
+                .chain(dynamic.iter().map(|(name, value)| (name, value)));
+            for (name, value) in properties {
+                let value = deserialize_value(activation, value, objects);
+                let name = AvmString::new_utf8(activation.context.gc_context, name);
                 obj.define_value(
                     activation.context.gc_context,
                     name,
@@ -161,7 +276,10 @@ fn deserialize_value<'gc>(activation: &mut Activation<'_, 'gc>, val: &AmfValue)
             }
             obj.into()
         }
-        AmfValue::Date(time, _) => {
+        AmfValue::Reference(id) => objects
+            .get(id)
+            .map_or(Value::Undefined, |obj| (*obj).into()),
+        AmfValue::Date(time) => {
             let date_proto = activation.context.avm1.prototypes().date_constructor;
 
             if let Ok(Value::Object(obj)) = date_proto.construct(activation, &[(*time).into()]) {
@@ -170,7 +288,7 @@ fn deserialize_value<'gc>(activation: &mut Activation<'_, 'gc>, val: &AmfValue)
                 Value::Undefined
             }
         }
-        AmfValue::XML(content, _) => {
+        AmfValue::Xml(content) => {
             let xml_proto = activation.context.avm1.prototypes().xml_constructor;
 
             if let Ok(Value::Object(obj)) = xml_proto.construct(
@@ -186,7 +304,7 @@ fn deserialize_value<'gc>(activation: &mut Activation<'_, 'gc>, val: &AmfValue)
             }
         }
 
-        _ => Value::Undefined,
+        AmfValue::ByteArray(_) | AmfValue::Unsupported => Value::Undefined,
     }
 }
 
@@ -200,11 +318,12 @@ fn deserialize_lso<'gc>(
         Some(activation.context.avm1.prototypes().object),
     );
 
-    for child in &lso.body {
+    let mut objects = HashMap::new();
+    for (name, value) in &lso.body {
         obj.define_value(
             activation.context.gc_context,
-            AvmString::new_utf8(activation.context.gc_context, &child.name),
-            deserialize_value(activation, child.value()),
+            AvmString::new_utf8(activation.context.gc_context, name),
+            deserialize_value(activation, value, &mut objects),
             Attribute::empty(),
         );
     }
@@ -354,7 +473,7 @@ pub fn get_local<'gc>(
 
     // Load the data object from storage if it existed prior
     if let Some(saved) = activation.context.storage.get(&full_name) {
-        if let Ok(lso) = flash_lso::read::Reader::default().parse(&saved) {
+        if let Ok(lso) = crate::amf::read_lso(&saved) {
             data = deserialize_lso(activation, &lso)?.into();
         }
     }
@@ -474,38 +593,89 @@ pub fn connect<'gc>(
 }
 
 pub fn flush<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let (name, bytes) = serialize_data(activation, this)?;
+    let min_disk_space = args
+        .get(0)
+        .unwrap_or(&Value::Undefined)
+        .coerce_to_i32(activation)?;
+    let success = activation.context.storage.put(&name, &bytes);
+
+    // Flash asks the user before storing more than the default limit,
+    // and calls `onStatus` with their answer. There's no one to ask here.
+    if bytes.len().max(min_disk_space.max(0) as usize) > DEFAULT_SIZE_LIMIT {
+        let (code, level) = if success {
+            ("SharedObject.Flush.Success", "status")
+        } else {
+            ("SharedObject.Flush.Failed", "error")
+        };
+        let info = ScriptObject::new(
+            activation.context.gc_context,
+            Some(activation.context.avm1.prototypes().object),
+        );
+        info.define_value(
+            activation.context.gc_context,
+            "code",
+            code.into(),
+            Attribute::empty(),
+        );
+        info.define_value(
+            activation.context.gc_context,
+            "level",
+            level.into(),
+            Attribute::empty(),
+        );
+        activation.context.action_queue.queue_action(
+            activation.base_clip(),
+            ActionType::Method {
+                object: this,
+                name: "onStatus",
+                args: vec![info.into()],
+            },
+            false,
+        );
+        return Ok("pending".into());
+    }
+
+    Ok(success.into())
+}
+
+pub fn get_size<'gc>(
     activation: &mut Activation<'_, 'gc>,
     this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
+    let (_, bytes) = serialize_data(activation, this)?;
+    Ok(bytes.len().into())
+}
+
+/// Serializes the `data` of a shared object, returning its name along with the `.sol` file.
+fn serialize_data<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+) -> Result<(String, Vec<u8>), Error<'gc>> {
     let data = this.get("data", activation)?.coerce_to_object(activation);
 
     let this_obj = this.as_shared_object().unwrap();
     let name = this_obj.get_name();
 
-    let mut elements = Vec::new();
-    recursive_serialize(activation, data, &mut elements);
-    let mut lso = Lso::new(
-        elements,
-        name.split('/')
+    let mut body = Vec::new();
+    recursive_serialize(activation, data, &mut body, &mut ObjectTable::new());
+    let lso = Lso {
+        name: name
+            .split('/')
             .last()
             .map(|e| e.to_string())
             .unwrap_or_else(|| "<unknown>".to_string()),
-        AMFVersion::AMF0,
-    );
-
-    let bytes = flash_lso::write::write_to_bytes(&mut lso).unwrap_or_default();
-
-    Ok(activation.context.storage.put(&name, &bytes).into())
-}
+        version: AmfVersion::Amf0,
+        body,
+    };
 
-pub fn get_size<'gc>(
-    activation: &mut Activation<'_, 'gc>,
-    _this: Object<'gc>,
-    _args: &[Value<'gc>],
-) -> Result<Value<'gc>, Error<'gc>> {
-    avm1_stub!(activation, "SharedObject", "getSize");
-    Ok(Value::Undefined)
+    let bytes = crate::amf::write_lso(&lso);
+    Ok((name, bytes))
 }
 
 pub fn send<'gc>(
@@ -0,0 +1,187 @@
+//! `TextField.StyleSheet` impl
+
+use crate::avm1::object::NativeObject;
+use crate::avm1::property_decl::{define_properties_on, Declaration};
+use crate::avm1::{Activation, ArrayObject, Error, Object, ScriptObject, TObject, Value};
+use crate::html::{Style, StyleSheet};
+use crate::string::{AvmString, WString};
+use gc_arena::{GcCell, MutationContext};
+
+macro_rules! method {
+    ($name:ident) => {
+        |activation, this, args| {
+            if let NativeObject::StyleSheet(style_sheet) = this.native() {
+                return $name(activation, style_sheet, args);
+            }
+            Ok(Value::Undefined)
+        }
+    };
+}
+
+const PROTO_DECLS: &[Declaration] = declare_properties! {
+    "getStyle" => method(method!(get_style); DONT_ENUM | DONT_DELETE);
+    "setStyle" => method(method!(set_style); DONT_ENUM | DONT_DELETE);
+    "getStyleNames" => method(method!(get_style_names); DONT_ENUM | DONT_DELETE);
+    "clear" => method(method!(clear); DONT_ENUM | DONT_DELETE);
+    "parseCSS" => method(method!(parse_css); DONT_ENUM | DONT_DELETE);
+    "transform" => method(transform; DONT_ENUM | DONT_DELETE);
+};
+
+/// Implements `TextField.StyleSheet`
+pub fn constructor<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    this.set_native(
+        activation.context.gc_context,
+        NativeObject::StyleSheet(GcCell::allocate(
+            activation.context.gc_context,
+            StyleSheet::new(),
+        )),
+    );
+    Ok(this.into())
+}
+
+pub fn create_proto<'gc>(
+    gc_context: MutationContext<'gc, '_>,
+    proto: Object<'gc>,
+    fn_proto: Object<'gc>,
+) -> Object<'gc> {
+    let object = ScriptObject::new(gc_context, Some(proto));
+    define_properties_on(PROTO_DECLS, gc_context, object, fn_proto);
+    object.into()
+}
+
+/// Reads the properties of a style object, as the strings they're kept as.
+fn style_from_object<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    object: Object<'gc>,
+) -> Result<Style, Error<'gc>> {
+    let mut properties = Vec::new();
+    for name in object.get_keys(activation) {
+        let value = object.get(name, activation)?.coerce_to_string(activation)?;
+        properties.push((
+            WString::from(name.as_wstr()),
+            WString::from(value.as_wstr()),
+        ));
+    }
+    Ok(Style::from_properties(properties))
+}
+
+fn get_style<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    style_sheet: GcCell<'gc, StyleSheet>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let name = args
+        .get(0)
+        .unwrap_or(&Value::Undefined)
+        .coerce_to_string(activation)?;
+
+    // Movies get a copy, which doesn't change the style sheet when it's changed.
+    let properties = style_sheet
+        .read()
+        .style(&name)
+        .map(|style| style.properties().to_vec());
+    let Some(properties) = properties else {
+        return Ok(Value::Null);
+    };
+    let object = ScriptObject::new(
+        activation.context.gc_context,
+        Some(activation.context.avm1.prototypes().object),
+    );
+    for (name, value) in properties {
+        let name = AvmString::new(activation.context.gc_context, name);
+        let value = AvmString::new(activation.context.gc_context, value);
+        object.set(name, value.into(), activation)?;
+    }
+    Ok(object.into())
+}
+
+fn set_style<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    style_sheet: GcCell<'gc, StyleSheet>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let name = args
+        .get(0)
+        .unwrap_or(&Value::Undefined)
+        .coerce_to_string(activation)?;
+
+    // Anything other than an object removes the style.
+    let style = match args.get(1) {
+        Some(Value::Object(object)) => Some(style_from_object(activation, *object)?),
+        _ => None,
+    };
+    style_sheet
+        .write(activation.context.gc_context)
+        .set_style(&name, style);
+    Ok(Value::Undefined)
+}
+
+fn get_style_names<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    style_sheet: GcCell<'gc, StyleSheet>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let gc_context = activation.context.gc_context;
+    let names: Vec<_> = style_sheet
+        .read()
+        .selectors()
+        .map(|name| Value::from(AvmString::new(gc_context, name)))
+        .collect();
+    Ok(ArrayObject::new(
+        gc_context,
+        activation.context.avm1.prototypes().array,
+        names,
+    )
+    .into())
+}
+
+fn clear<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    style_sheet: GcCell<'gc, StyleSheet>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    style_sheet.write(activation.context.gc_context).clear();
+    Ok(Value::Undefined)
+}
+
+fn parse_css<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    style_sheet: GcCell<'gc, StyleSheet>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let css = args
+        .get(0)
+        .unwrap_or(&Value::Undefined)
+        .coerce_to_string(activation)?;
+    let parsed = style_sheet
+        .write(activation.context.gc_context)
+        .parse_css(&css);
+    Ok(parsed.into())
+}
+
+/// Turns a style object into the `TextFormat` that it applies.
+fn transform<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    _this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let Some(Value::Object(object)) = args.get(0) else {
+        return Ok(Value::Null);
+    };
+    let style = style_from_object(activation, *object)?;
+
+    let proto = activation.context.avm1.prototypes().text_format;
+    let text_format = ScriptObject::new(activation.context.gc_context, Some(proto));
+    text_format.set_native(
+        activation.context.gc_context,
+        NativeObject::TextFormat(GcCell::allocate(
+            activation.context.gc_context,
+            style.text_format().clone(),
+        )),
+    );
+    Ok(text_format.into())
+}
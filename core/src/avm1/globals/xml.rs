@@ -13,10 +13,10 @@ use crate::xml::{XmlNode, ELEMENT_NODE, TEXT_NODE};
 use gc_arena::MutationContext;
 
 const PROTO_DECLS: &[Declaration] = declare_properties! {
-    "docTypeDecl" => property(doc_type_decl; READ_ONLY);
+    "docTypeDecl" => property(doc_type_decl, set_doc_type_decl);
     "ignoreWhite" => bool(false);
-    "contentType" => string("application/x-www-form-urlencoded"; READ_ONLY);
-    "xmlDecl" => property(xml_decl);
+    "contentType" => string("application/x-www-form-urlencoded");
+    "xmlDecl" => property(xml_decl, set_xml_decl);
     "idMap" => property(id_map);
     "status" => property(status);
     "createElement" => method(create_element);
@@ -134,7 +134,7 @@ fn send_and_load<'gc>(
 
     if let Some(document) = this.as_xml() {
         let url = url_val.coerce_to_string(activation)?;
-        spawn_xml_fetch(activation, this, target, url, Some(document.as_node()))?;
+        spawn_xml_fetch(activation, this, target, url, Some(document))?;
     }
     Ok(Value::Undefined)
 }
@@ -210,6 +210,19 @@ fn doc_type_decl<'gc>(
     Ok(Value::Undefined)
 }
 
+fn set_doc_type_decl<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let (Some(document), Some(doctype)) = (this.as_xml(), args.get(0)) {
+        let doctype = doctype.coerce_to_string(activation)?;
+        document.set_doctype(activation.context.gc_context, doctype);
+    }
+
+    Ok(Value::Undefined)
+}
+
 fn xml_decl<'gc>(
     _activation: &mut Activation<'_, 'gc>,
     this: Object<'gc>,
@@ -224,6 +237,19 @@ fn xml_decl<'gc>(
     Ok(Value::Undefined)
 }
 
+fn set_xml_decl<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let (Some(document), Some(xml_decl)) = (this.as_xml(), args.get(0)) {
+        let xml_decl = xml_decl.coerce_to_string(activation)?;
+        document.set_xml_decl(activation.context.gc_context, xml_decl);
+    }
+
+    Ok(Value::Undefined)
+}
+
 fn id_map<'gc>(
     _activation: &mut Activation<'_, 'gc>,
     this: Object<'gc>,
@@ -253,18 +279,21 @@ fn spawn_xml_fetch<'gc>(
     this: Object<'gc>,
     loader_object: Object<'gc>,
     url: AvmString<'gc>,
-    send_object: Option<XmlNode<'gc>>,
+    send_object: Option<XmlObject<'gc>>,
 ) -> Result<Value<'gc>, Error<'gc>> {
     let url = url.to_utf8_lossy().into_owned();
 
-    let request = if let Some(node) = send_object {
-        // Send `node` as string.
-        let string = node.into_string(activation)?;
+    let request = if let Some(document) = send_object {
+        // Send `document` as string.
+        let string = document.into_string(activation)?;
+        let content_type = this
+            .get("contentType", activation)?
+            .coerce_to_string(activation)?;
         Request::post(
             url,
             Some((
                 string.to_utf8_lossy().into_owned().into_bytes(),
-                "application/x-www-form-urlencoded".to_string(),
+                content_type.to_string(),
             )),
         )
     } else {
@@ -181,12 +181,16 @@ fn to_string<'gc>(
     this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    if let Some(node) = this.as_xml_node() {
-        let string = node.into_string(activation)?;
-        return Ok(AvmString::new(activation.context.gc_context, string).into());
-    }
+    // Documents also include their XML and DOCTYPE declarations.
+    let string = if let Some(document) = this.as_xml() {
+        document.into_string(activation)?
+    } else if let Some(node) = this.as_xml_node() {
+        node.into_string(activation)?
+    } else {
+        return Ok("".into());
+    };
 
-    Ok("".into())
+    Ok(AvmString::new(activation.context.gc_context, string).into())
 }
 
 fn local_name<'gc>(
@@ -0,0 +1,202 @@
+//! XMLSocket class
+
+use crate::avm1::activation::Activation;
+use crate::avm1::error::Error;
+use crate::avm1::function::ExecutionReason;
+use crate::avm1::object::NativeObject;
+use crate::avm1::property_decl::{define_properties_on, Declaration};
+use crate::avm1::{Object, ScriptObject, TObject, Value};
+use crate::backend::navigator::SocketHandle;
+use crate::socket::Sockets;
+use gc_arena::{Collect, GcCell, MutationContext};
+use std::time::Duration;
+
+/// Flash doesn't allow connecting to any of the well-known ports.
+const MIN_PORT: u16 = 1024;
+
+const PROTO_DECLS: &[Declaration] = declare_properties! {
+    "connect" => method(connect; DONT_ENUM | DONT_DELETE);
+    "send" => method(send; DONT_ENUM | DONT_DELETE);
+    "close" => method(close; DONT_ENUM | DONT_DELETE);
+    "timeout" => property(timeout, set_timeout; DONT_ENUM | DONT_DELETE);
+    "onData" => method(on_data; DONT_ENUM | DONT_DELETE);
+};
+
+/// The native state of an `XMLSocket`.
+#[derive(Clone, Debug, Collect)]
+#[collect(require_static)]
+pub struct XmlSocket {
+    /// The socket that was last opened, which may have since been closed.
+    handle: Option<SocketHandle>,
+
+    /// How long to wait for a connection, in milliseconds.
+    timeout: u32,
+}
+
+impl Default for XmlSocket {
+    fn default() -> Self {
+        Self {
+            handle: None,
+            timeout: 20000,
+        }
+    }
+}
+
+/// Implements `XMLSocket`
+pub fn constructor<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    this.set_native(
+        activation.context.gc_context,
+        NativeObject::XmlSocket(GcCell::allocate(
+            activation.context.gc_context,
+            XmlSocket::default(),
+        )),
+    );
+    Ok(this.into())
+}
+
+pub fn create_proto<'gc>(
+    gc_context: MutationContext<'gc, '_>,
+    proto: Object<'gc>,
+    fn_proto: Object<'gc>,
+) -> Object<'gc> {
+    let object = ScriptObject::new(gc_context, Some(proto));
+    define_properties_on(PROTO_DECLS, gc_context, object, fn_proto);
+    object.into()
+}
+
+fn connect<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let NativeObject::XmlSocket(xml_socket) = this.native() else {
+        return Ok(false.into());
+    };
+
+    // A missing host means the host that the movie was loaded from.
+    let host = match args.get(0).unwrap_or(&Value::Undefined) {
+        Value::Undefined | Value::Null => Sockets::default_host(&activation.context),
+        host => host.coerce_to_string(activation)?.to_string(),
+    };
+    let port = args
+        .get(1)
+        .unwrap_or(&Value::Undefined)
+        .coerce_to_i32(activation)?;
+    let port = match u16::try_from(port) {
+        Ok(port) if port >= MIN_PORT => port,
+        _ => return Ok(false.into()),
+    };
+
+    // Only one connection can be open at a time.
+    let old_handle = xml_socket.read().handle;
+    if let Some(handle) = old_handle {
+        activation.context.sockets.close(handle);
+    }
+
+    let timeout = Duration::from_millis(xml_socket.read().timeout.into());
+    let handle = activation.context.sockets.connect_avm1(
+        activation.context.navigator,
+        this,
+        host,
+        port,
+        timeout,
+    );
+    xml_socket.write(activation.context.gc_context).handle = Some(handle);
+
+    Ok(true.into())
+}
+
+fn send<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let NativeObject::XmlSocket(xml_socket) = this.native() else {
+        return Ok(Value::Undefined);
+    };
+    let Some(handle) = xml_socket.read().handle else {
+        return Ok(Value::Undefined);
+    };
+
+    // Each message is terminated by a null byte.
+    let data = args
+        .get(0)
+        .unwrap_or(&Value::Undefined)
+        .coerce_to_string(activation)?;
+    let mut data = data.to_utf8_lossy().into_owned().into_bytes();
+    data.push(0);
+    activation.context.sockets.send(handle, data);
+
+    Ok(Value::Undefined)
+}
+
+fn close<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let NativeObject::XmlSocket(xml_socket) = this.native() {
+        // `onClose` is only called when the server closes the connection.
+        let handle = xml_socket
+            .write(activation.context.gc_context)
+            .handle
+            .take();
+        if let Some(handle) = handle {
+            activation.context.sockets.close(handle);
+        }
+    }
+
+    Ok(Value::Undefined)
+}
+
+fn timeout<'gc>(
+    _activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let NativeObject::XmlSocket(xml_socket) = this.native() {
+        return Ok(xml_socket.read().timeout.into());
+    }
+
+    Ok(Value::Undefined)
+}
+
+fn set_timeout<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let NativeObject::XmlSocket(xml_socket) = this.native() {
+        let timeout = args
+            .get(0)
+            .unwrap_or(&Value::Undefined)
+            .coerce_to_u32(activation)?;
+        xml_socket.write(activation.context.gc_context).timeout = timeout;
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// The default `onData` parses each message as XML, and passes it on to `onXML`.
+fn on_data<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let src = args.get(0).copied().unwrap_or(Value::Undefined);
+    let xml_constructor = activation.context.avm1.prototypes().xml_constructor;
+    let xml = xml_constructor.construct(activation, &[src])?;
+
+    this.call_method(
+        "onXML".into(),
+        &[xml],
+        activation,
+        ExecutionReason::FunctionCall,
+    )?;
+
+    Ok(Value::Undefined)
+}
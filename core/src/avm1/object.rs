@@ -5,6 +5,7 @@ use crate::avm1::globals::bevel_filter::BevelFilterObject;
 use crate::avm1::globals::blur_filter::BlurFilterObject;
 use crate::avm1::globals::color_transform::ColorTransformObject;
 use crate::avm1::globals::date::Date;
+use crate::avm1::globals::xml_socket::XmlSocket;
 use crate::avm1::object::array_object::ArrayObject;
 use crate::avm1::object::bitmap_data::BitmapDataObject;
 use crate::avm1::object::color_matrix_filter::ColorMatrixFilterObject;
@@ -22,7 +23,7 @@ use crate::avm1::object::xml_node_object::XmlNodeObject;
 use crate::avm1::object::xml_object::XmlObject;
 use crate::avm1::{Activation, Attribute, Error, ScriptObject, SoundObject, StageObject, Value};
 use crate::display_object::DisplayObject;
-use crate::html::TextFormat;
+use crate::html::{StyleSheet, TextFormat};
 use crate::string::AvmString;
 use crate::xml::XmlNode;
 use gc_arena::{Collect, GcCell, MutationContext};
@@ -58,6 +59,8 @@ pub enum NativeObject<'gc> {
     BevelFilter(GcCell<'gc, BevelFilterObject>),
     ColorTransform(GcCell<'gc, ColorTransformObject>),
     TextFormat(GcCell<'gc, TextFormat>),
+    StyleSheet(GcCell<'gc, StyleSheet>),
+    XmlSocket(GcCell<'gc, XmlSocket>),
 }
 
 /// Represents an object that can be directly interacted with by the AVM
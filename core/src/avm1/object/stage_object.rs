@@ -205,11 +205,18 @@ impl<'gc> TObject<'gc> for StageObject<'gc> {
             }
         }
 
-        // 3) Child display objects with the given instance name
+        // 3) Child display objects with the given instance name, including the images
+        // of text fields
+        let case_sensitive = activation.is_case_sensitive();
         if let Some(child) = obj
             .display_object
             .as_container()
-            .and_then(|o| o.child_by_name(&name, activation.is_case_sensitive()))
+            .and_then(|o| o.child_by_name(&name, case_sensitive))
+            .or_else(|| {
+                obj.display_object
+                    .as_edit_text()
+                    .and_then(|t| t.image_by_id(&name, case_sensitive))
+            })
         {
             return Some(child.object());
         }
@@ -300,6 +307,11 @@ impl<'gc> TObject<'gc> for StageObject<'gc> {
             .display_object
             .as_container()
             .and_then(|o| o.child_by_name(&name, case_sensitive))
+            .or_else(|| {
+                obj.display_object
+                    .as_edit_text()
+                    .and_then(|t| t.image_by_id(&name, case_sensitive))
+            })
             .is_some()
         {
             return true;
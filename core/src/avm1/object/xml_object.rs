@@ -8,6 +8,7 @@ use crate::string::{AvmString, WStr, WString};
 use crate::xml::{XmlNode, ELEMENT_NODE, TEXT_NODE};
 use gc_arena::{Collect, GcCell, MutationContext};
 use quick_xml::{events::Event, Reader};
+use std::borrow::Cow;
 use std::fmt;
 
 #[derive(Clone, Copy, Collect)]
@@ -17,18 +18,15 @@ pub enum XmlStatus {
     NoError = 0,
 
     /// A CDATA section was not properly terminated.
-    #[allow(dead_code)]
     CdataNotTerminated = -2,
 
     /// The XML declaration was not properly terminated.
     DeclNotTerminated = -3,
 
     /// The DOCTYPE declaration was not properly terminated.
-    #[allow(dead_code)]
     DoctypeNotTerminated = -4,
 
     /// A comment was not properly terminated.
-    #[allow(dead_code)]
     CommentNotTerminated = -5,
 
     /// An XML element was malformed.
@@ -41,7 +39,6 @@ pub enum XmlStatus {
     AttributeNotTerminated = -8,
 
     /// A start-tag was not matched with an end-tag.
-    #[allow(dead_code)]
     MismatchedStart = -9,
 
     /// An end-tag was encountered without a matching start-tag.
@@ -107,11 +104,35 @@ impl<'gc> XmlObject<'gc> {
         self.0.read().xml_decl
     }
 
+    /// Replace the XML declaration of this document.
+    pub fn set_xml_decl(self, gc_context: MutationContext<'gc, '_>, xml_decl: AvmString<'gc>) {
+        self.0.write(gc_context).xml_decl = Some(xml_decl);
+    }
+
     /// Retrieve the first DocType node in the document.
     pub fn doctype(self) -> Option<AvmString<'gc>> {
         self.0.read().doctype
     }
 
+    /// Replace the DOCTYPE declaration of this document.
+    pub fn set_doctype(self, gc_context: MutationContext<'gc, '_>, doctype: AvmString<'gc>) {
+        self.0.write(gc_context).doctype = Some(doctype);
+    }
+
+    /// Convert the document to a string of XML, starting with its XML declaration and
+    /// DOCTYPE declaration, if it has them.
+    pub fn into_string(self, activation: &mut Activation<'_, 'gc>) -> Result<WString, Error<'gc>> {
+        let mut result = WString::new();
+        if let Some(xml_decl) = self.xml_decl() {
+            result.push_str(&xml_decl);
+        }
+        if let Some(doctype) = self.doctype() {
+            result.push_str(&doctype);
+        }
+        result.push_str(&self.as_node().into_string(activation)?);
+        Ok(result)
+    }
+
     /// Replace the contents of this document with the result of parsing a string.
     ///
     /// This method does not yet actually remove existing node contents.
@@ -131,8 +152,15 @@ impl<'gc> XmlObject<'gc> {
         loop {
             let event = parser.read_event(&mut buf).map_err(|error| {
                 self.0.write(activation.context.gc_context).status = match error {
-                    quick_xml::Error::UnexpectedEof(_)
-                    | quick_xml::Error::NameWithQuote(_)
+                    // `quick-xml` names what it was in the middle of reading when the data ran out.
+                    quick_xml::Error::UnexpectedEof(ref what) => match what.as_str() {
+                        "CData" => XmlStatus::CdataNotTerminated,
+                        "Comment" => XmlStatus::CommentNotTerminated,
+                        "DOCTYPE" => XmlStatus::DoctypeNotTerminated,
+                        "XmlDecl" => XmlStatus::DeclNotTerminated,
+                        _ => XmlStatus::ElementMalformed,
+                    },
+                    quick_xml::Error::NameWithQuote(_)
                     | quick_xml::Error::NoEqAfterName(_)
                     | quick_xml::Error::DuplicatedAttribute(_, _) => XmlStatus::ElementMalformed,
                     quick_xml::Error::EndEventMismatch { .. } => XmlStatus::MismatchedEnd,
@@ -147,6 +175,7 @@ impl<'gc> XmlObject<'gc> {
                 };
                 error
             })?;
+            let is_cdata = matches!(event, Event::CData(_));
 
             match event {
                 Event::Start(bs) => {
@@ -168,7 +197,12 @@ impl<'gc> XmlObject<'gc> {
                     open_tags.pop();
                 }
                 Event::Text(bt) | Event::CData(bt) => {
-                    let text = bt.unescaped()?;
+                    // Entities within CDATA are left as they are.
+                    let text = if is_cdata {
+                        Cow::Borrowed(bt.escaped())
+                    } else {
+                        bt.unescaped()?
+                    };
                     let is_whitespace_char = |c: &u8| matches!(*c, b'\t' | b'\n' | b'\r' | b' ');
                     let is_whitespace_text = text.iter().all(is_whitespace_char);
                     if !(text.is_empty() || ignore_white && is_whitespace_text) {
@@ -204,6 +238,11 @@ impl<'gc> XmlObject<'gc> {
             }
         }
 
+        // Flash keeps everything that was parsed, even if some elements weren't closed.
+        if open_tags.len() > 1 {
+            self.0.write(activation.context.gc_context).status = XmlStatus::MismatchedStart;
+        }
+
         Ok(())
     }
 
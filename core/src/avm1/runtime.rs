@@ -525,6 +525,21 @@ impl<'gc> Avm1<'gc> {
         }
     }
 
+    /// All of the constructors registered with `Object.registerClass()`, along with their symbol.
+    pub fn registered_constructors(
+        &self,
+        swf_version: u8,
+    ) -> impl Iterator<Item = (AvmString<'gc>, FunctionObject<'gc>)> + '_ {
+        let registry = if swf_version >= 7 {
+            &self.constructor_registry_case_sensitive
+        } else {
+            &self.constructor_registry_case_insensitive
+        };
+        registry
+            .iter()
+            .map(|(symbol, constructor)| (symbol, *constructor))
+    }
+
     #[cfg(feature = "avm_debug")]
     #[inline]
     pub fn show_debug_output(&self) -> bool {
@@ -30,6 +30,7 @@ mod class;
 mod domain;
 pub mod error;
 mod events;
+pub mod filters;
 mod function;
 pub mod globals;
 mod method;
@@ -97,6 +98,7 @@ pub struct Avm2<'gc> {
     pub flash_display_internal: Namespace<'gc>,
     pub flash_utils_internal: Namespace<'gc>,
     pub flash_geom_internal: Namespace<'gc>,
+    pub flash_net_internal: Namespace<'gc>,
 
     #[collect(require_static)]
     native_method_table: &'static [Option<(&'static str, NativeMethodImpl)>],
@@ -117,6 +119,12 @@ pub struct Avm2<'gc> {
     /// collector does not support weak references.
     broadcast_list: FnvHashMap<AvmString<'gc>, Vec<Object<'gc>>>,
 
+    /// The classes registered with `registerClassAlias`, by their alias.
+    ///
+    /// AMF serialization writes instances of these classes with their alias,
+    /// so that they can be read back as the same class.
+    class_aliases: FnvHashMap<AvmString<'gc>, ClassObject<'gc>>,
+
     #[cfg(feature = "avm_debug")]
     pub debug_output: bool,
 }
@@ -146,11 +154,13 @@ impl<'gc> Avm2<'gc> {
             flash_display_internal: Namespace::internal("flash.display", mc),
             flash_utils_internal: Namespace::internal("flash.utils", mc),
             flash_geom_internal: Namespace::internal("flash.geom", mc),
+            flash_net_internal: Namespace::internal("flash.net", mc),
 
             native_method_table: Default::default(),
             native_instance_allocator_table: Default::default(),
             native_instance_init_table: Default::default(),
             broadcast_list: Default::default(),
+            class_aliases: Default::default(),
 
             #[cfg(feature = "avm_debug")]
             debug_output: false,
@@ -224,6 +234,24 @@ impl<'gc> Avm2<'gc> {
         dispatch_event(&mut activation, target, event)
     }
 
+    /// Registers the alias that instances of a class are serialized with.
+    pub fn register_class_alias(&mut self, alias: AvmString<'gc>, class: ClassObject<'gc>) {
+        self.class_aliases.insert(alias, class);
+    }
+
+    /// Looks up the class that was registered with an alias.
+    pub fn get_class_by_alias(&self, alias: AvmString<'gc>) -> Option<ClassObject<'gc>> {
+        self.class_aliases.get(&alias).copied()
+    }
+
+    /// Looks up the alias that a class was registered with, if any.
+    pub fn get_alias_by_class(&self, class: ClassObject<'gc>) -> Option<AvmString<'gc>> {
+        self.class_aliases
+            .iter()
+            .find(|(_, c)| Object::ptr_eq(**c, class))
+            .map(|(alias, _)| *alias)
+    }
+
     /// Add an object to the broadcast list.
     ///
     /// Each broadcastable event contains it's own broadcast list. You must
@@ -1,35 +1,31 @@
+use crate::amf::{AmfValue, Lso, ObjectId, Traits};
 use crate::avm2::bytearray::ByteArrayStorage;
-use crate::avm2::object::{ByteArrayObject, TObject};
+use crate::avm2::object::{ByteArrayObject, ClassObject, ObjectPtr, TObject};
+use crate::avm2::traits::TraitKind;
 use crate::avm2::ArrayObject;
 use crate::avm2::ArrayStorage;
 use crate::avm2::{Activation, Error, Object, Value};
 use crate::string::AvmString;
-use enumset::EnumSet;
-use flash_lso::types::{AMFVersion, Element, Lso};
-use flash_lso::types::{Attribute, ClassDefinition, Value as AmfValue};
+use std::collections::HashMap;
+
+/// The objects that were serialized so far, which are written as references when they're
+/// serialized again.
+pub type ObjectTable = HashMap<*const ObjectPtr, ObjectId>;
 
 /// Serialize a Value to an AmfValue
 pub fn serialize_value<'gc>(
     activation: &mut Activation<'_, 'gc>,
     elem: Value<'gc>,
-    amf_version: AMFVersion,
+    objects: &mut ObjectTable,
 ) -> Option<AmfValue> {
     match elem {
         Value::Undefined => Some(AmfValue::Undefined),
         Value::Null => Some(AmfValue::Null),
         Value::Bool(b) => Some(AmfValue::Bool(b)),
         Value::Number(f) => Some(AmfValue::Number(f)),
-        Value::Integer(num) => {
-            // NOTE - we should really be converting `Value::Integer` to `Value::Number`
-            // whenever it's outside this range, instead of performing this during AMF serialization.
-            // Integers are unsupported in AMF0, and must be converted to Number regardless of whether
-            // it can be represented as an integer.
-            if amf_version == AMFVersion::AMF0 || num >= (1 << 28) || num < -(1 << 28) {
-                Some(AmfValue::Number(num as f64))
-            } else {
-                Some(AmfValue::Integer(num))
-            }
-        }
+        // Integers that don't fit into AMF3's 29 bits, and all integers in AMF0,
+        // are written as numbers.
+        Value::Integer(num) => Some(AmfValue::Integer(num)),
         Value::String(s) => Some(AmfValue::String(s.to_string())),
         Value::Object(o) => {
             // TODO: Find a more general rule for which object types should be skipped,
@@ -38,62 +34,140 @@ pub fn serialize_value<'gc>(
                 None
             } else if o.as_display_object().is_some() {
                 Some(AmfValue::Undefined)
-            } else if let Some(array) = o.as_array_storage() {
-                let mut values = Vec::new();
-                recursive_serialize(activation, o, &mut values, amf_version).unwrap();
-
-                let mut dense = vec![];
-                let mut sparse = vec![];
-                for (i, elem) in (0..array.length()).zip(values.into_iter()) {
-                    if elem.name == i.to_string() {
-                        dense.push(elem.value.clone());
-                    } else {
-                        sparse.push(elem);
-                    }
-                }
+            } else if let Some(&id) = objects.get(&o.as_ptr()) {
+                Some(AmfValue::Reference(id))
+            } else {
+                serialize_object(activation, o, objects)
+            }
+        }
+    }
+}
 
-                if sparse.is_empty() {
-                    Some(AmfValue::StrictArray(dense))
-                } else {
-                    let len = sparse.len() as u32;
-                    Some(AmfValue::ECMAArray(dense, sparse, len))
-                }
-            } else if let Some(date) = o.as_date_object() {
-                date.date_time()
-                    .map(|date_time| AmfValue::Date(date_time.timestamp_millis() as f64, None))
+/// Serialize an object that isn't in `objects` yet to an AmfValue
+fn serialize_object<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    o: Object<'gc>,
+    objects: &mut ObjectTable,
+) -> Option<AmfValue> {
+    if let Some(length) = o.as_array_storage().map(|array| array.length()) {
+        // Arrays and objects are added before their children, which may refer back to them.
+        let id = objects.len();
+        objects.insert(o.as_ptr(), id);
+        let mut values = Vec::new();
+        recursive_serialize(activation, o, &mut values, objects).unwrap();
+
+        // Everything from the first hole onwards is written by name.
+        let mut dense = vec![];
+        let mut associative = vec![];
+        for (i, (name, value)) in (0..length).zip(values.into_iter()) {
+            if associative.is_empty() && name == i.to_string() {
+                dense.push(value);
             } else {
-                let is_object = o
-                    .instance_of()
-                    .map_or(false, |c| c == activation.avm2().classes().object);
-                if is_object {
-                    let mut object_body = Vec::new();
-                    recursive_serialize(activation, o, &mut object_body, amf_version).unwrap();
-                    Some(AmfValue::Object(
-                        object_body,
-                        Some(ClassDefinition {
-                            name: "".to_string(),
-                            attributes: EnumSet::only(Attribute::Dynamic),
-                            static_properties: Vec::new(),
-                        }),
-                    ))
-                } else {
-                    tracing::warn!(
-                        "Serialization is not implemented for class other than Object: {:?}",
-                        o
-                    );
-                    None
-                }
+                associative.push((name, value));
+            }
+        }
+
+        Some(AmfValue::Array {
+            id,
+            dense,
+            associative,
+            length: length as u32,
+        })
+    } else if let Some(date) = o.as_date_object() {
+        date.date_time()
+            .map(|date_time| AmfValue::Date(date_time.timestamp_millis() as f64))
+    } else if let Some(bytearray) = o.as_bytearray() {
+        Some(AmfValue::ByteArray(bytearray.bytes().to_vec()))
+    } else if o.as_vector_storage().is_some() {
+        tracing::warn!("Serialization is not implemented for Vector: {:?}", o);
+        None
+    } else {
+        let id = objects.len();
+        objects.insert(o.as_ptr(), id);
+
+        // Instances are written with the public fields of their class, along with their
+        // dynamic properties. Without an alias for the class, they're read back as an `Object`.
+        let class = o.instance_of();
+        let name = class
+            .and_then(|class| activation.avm2().get_alias_by_class(class))
+            .map(|alias| alias.to_string())
+            .unwrap_or_default();
+        let is_dynamic = class.map_or(true, |class| {
+            !class.inner_class_definition().read().is_sealed()
+        });
+        let static_properties = class.map(sealed_properties).unwrap_or_default();
+
+        let mut sealed = Vec::new();
+        for property in &static_properties {
+            let value = o
+                .get_public_property(
+                    AvmString::new_utf8(activation.context.gc_context, property),
+                    activation,
+                )
+                .unwrap_or(Value::Undefined);
+            let value = serialize_value(activation, value, objects).unwrap_or(AmfValue::Undefined);
+            sealed.push(value);
+        }
+        let mut dynamic = Vec::new();
+        if is_dynamic {
+            recursive_serialize(activation, o, &mut dynamic, objects).unwrap();
+        }
+
+        Some(AmfValue::Object {
+            id,
+            traits: Traits {
+                name,
+                is_dynamic,
+                sealed: static_properties,
+            },
+            sealed,
+            dynamic,
+        })
+    }
+}
+
+/// The public fields of a class and its superclasses that AMF serializes:
+/// variables, and properties with both a getter and a setter.
+fn sealed_properties(class: ClassObject<'_>) -> Vec<String> {
+    let mut classes = Vec::new();
+    let mut current = Some(class);
+    while let Some(class) = current {
+        classes.push(class);
+        current = class.superclass_object();
+    }
+
+    let mut properties = Vec::new();
+    for class in classes.into_iter().rev() {
+        let class = class.inner_class_definition();
+        let class = class.read();
+        let traits = class.instance_traits();
+        for instance_trait in traits {
+            let name = instance_trait.name();
+            if !name.namespace().is_public() {
+                continue;
+            }
+            let is_field = match instance_trait.kind() {
+                TraitKind::Slot { .. } => true,
+                TraitKind::Getter { .. } => traits.iter().any(|other| {
+                    other.name() == name && matches!(other.kind(), TraitKind::Setter { .. })
+                }),
+                _ => false,
+            };
+            let name = name.local_name().to_string();
+            if is_field && !properties.contains(&name) {
+                properties.push(name);
             }
         }
     }
+    properties
 }
 
 /// Serialize an Object and any children to a AMF object
 pub fn recursive_serialize<'gc>(
     activation: &mut Activation<'_, 'gc>,
     obj: Object<'gc>,
-    elements: &mut Vec<Element>,
-    amf_version: AMFVersion,
+    elements: &mut Vec<(String, AmfValue)>,
+    objects: &mut ObjectTable,
 ) -> Result<(), Error<'gc>> {
     let mut last_index = obj.get_next_enumerant(0, activation)?;
     while let Some(index) = last_index {
@@ -102,8 +176,8 @@ pub fn recursive_serialize<'gc>(
             .coerce_to_string(activation)?;
         let value = obj.get_public_property(name, activation)?;
 
-        if let Some(value) = serialize_value(activation, value, amf_version) {
-            elements.push(Element::new(name.to_utf8_lossy(), value));
+        if let Some(value) = serialize_value(activation, value, objects) {
+            elements.push((name.to_utf8_lossy().into_owned(), value));
         }
         last_index = obj.get_next_enumerant(index, activation)?;
     }
@@ -111,9 +185,12 @@ pub fn recursive_serialize<'gc>(
 }
 
 /// Deserialize a AmfValue to a Value
+///
+/// `objects` are the arrays and objects that were deserialized so far, which references refer to.
 pub fn deserialize_value<'gc>(
     activation: &mut Activation<'_, 'gc>,
     val: &AmfValue,
+    objects: &mut HashMap<ObjectId, Object<'gc>>,
 ) -> Result<Value<'gc>, Error<'gc>> {
     Ok(match val {
         AmfValue::Null => Value::Null,
@@ -127,62 +204,77 @@ pub fn deserialize_value<'gc>(
             let bytearray = ByteArrayObject::from_storage(activation, storage)?;
             bytearray.into()
         }
-        AmfValue::ECMAArray(values, elements, _) => {
-            // First let's create an array out of `values` (dense portion), then we add the elements onto it.
-            let mut arr: Vec<Option<Value<'gc>>> = Vec::with_capacity(values.len());
-            for value in values {
-                arr.push(Some(deserialize_value(activation, value)?));
-            }
-            let storage = ArrayStorage::from_storage(arr);
+        AmfValue::Array {
+            id,
+            dense,
+            associative,
+            ..
+        } => {
+            // The array is registered before its elements are read, which may refer back to it.
+            let storage = ArrayStorage::from_storage(Vec::with_capacity(dense.len()));
             let mut array = ArrayObject::from_storage(activation, storage)?;
+            objects.insert(*id, array);
+            for value in dense {
+                let value = deserialize_value(activation, value, objects)?;
+                if let Some(mut storage) = array.as_array_storage_mut(activation.context.gc_context)
+                {
+                    storage.push(value);
+                }
+            }
             // Now let's add each element as a property
-            for element in elements {
+            for (name, value) in associative {
+                let value = deserialize_value(activation, value, objects)?;
                 array.set_public_property(
-                    AvmString::new_utf8(activation.context.gc_context, element.name()),
-                    deserialize_value(activation, element.value())?,
+                    AvmString::new_utf8(activation.context.gc_context, name),
+                    value,
                     activation,
                 )?;
             }
             array.into()
         }
-        AmfValue::StrictArray(values) => {
-            let mut arr: Vec<Option<Value<'gc>>> = Vec::with_capacity(values.len());
-            for value in values {
-                arr.push(Some(deserialize_value(activation, value)?));
-            }
-            let storage = ArrayStorage::from_storage(arr);
-            let array = ArrayObject::from_storage(activation, storage)?;
-            array.into()
-        }
-        AmfValue::Object(elements, class) => {
-            if let Some(class) = class {
-                if !class.name.is_empty() && class.name != "Object" {
-                    tracing::warn!("Deserializing class {:?} is not supported!", class);
+        AmfValue::Object {
+            id,
+            traits,
+            sealed,
+            dynamic,
+        } => {
+            // Objects of a class with a registered alias are read back as that class.
+            let mut class_object = activation.avm2().classes().object;
+            if !traits.name.is_empty() && traits.name != "Object" {
+                let alias = AvmString::new_utf8(activation.context.gc_context, &traits.name);
+                match activation.avm2().get_class_by_alias(alias) {
+                    Some(aliased) => class_object = aliased,
+                    None => tracing::warn!("No class is registered for alias {:?}", traits.name),
                 }
             }
 
-            let mut obj = activation
-                .avm2()
-                .classes()
-                .object
-                .construct(activation, &[])?;
-            for entry in elements {
-                let value = deserialize_value(activation, entry.value())?;
-                obj.set_public_property(
-                    AvmString::new_utf8(activation.context.gc_context, entry.name()),
-                    value,
-                    activation,
-                )?;
+            let mut obj = class_object.construct(activation, &[])?;
+            objects.insert(*id, obj);
+            let properties = traits
+                .sealed
+                .iter()
+                .zip(sealed)
+                .chain(dynamic.iter().map(|(name, value)| (name, value)));
+            for (name, value) in properties {
+                let value = deserialize_value(activation, value, objects)?;
+                // A class may have changed since it was saved, which shouldn't fail the whole load.
+                let name = AvmString::new_utf8(activation.context.gc_context, name);
+                if let Err(e) = obj.set_public_property(name, value, activation) {
+                    tracing::warn!("Unable to set deserialized property {}: {}", name, e);
+                }
             }
             obj.into()
         }
-        AmfValue::Date(time, _) => activation
+        AmfValue::Reference(id) => objects
+            .get(id)
+            .map_or(Value::Undefined, |obj| (*obj).into()),
+        AmfValue::Date(time) => activation
             .avm2()
             .classes()
             .date
             .construct(activation, &[(*time).into()])?
             .into(),
-        AmfValue::XML(content, _) => activation
+        AmfValue::Xml(content) => activation
             .avm2()
             .classes()
             .xml
@@ -194,17 +286,10 @@ pub fn deserialize_value<'gc>(
                 ))],
             )?
             .into(),
-        AmfValue::VectorDouble(..)
-        | AmfValue::VectorUInt(..)
-        | AmfValue::VectorInt(..)
-        | AmfValue::VectorObject(..)
-        | AmfValue::Dictionary(..)
-        | AmfValue::Custom(..) => {
-            tracing::error!("Deserialization not yet implemented: {:?}", val);
+        AmfValue::Unsupported => {
+            tracing::error!("Deserialization not yet implemented for vectors and dictionaries");
             Value::Undefined
         }
-        AmfValue::AMF3(val) => deserialize_value(activation, val)?,
-        AmfValue::Unsupported => Value::Undefined,
     })
 }
 
@@ -219,10 +304,12 @@ pub fn deserialize_lso<'gc>(
         .object
         .construct(activation, &[])?;
 
-    for child in &lso.body {
+    let mut objects = HashMap::new();
+    for (name, value) in &lso.body {
+        let value = deserialize_value(activation, value, &mut objects)?;
         obj.set_public_property(
-            AvmString::new_utf8(activation.context.gc_context, &child.name),
-            deserialize_value(activation, child.value())?,
+            AvmString::new_utf8(activation.context.gc_context, name),
+            value,
             activation,
         )?;
     }
@@ -369,6 +369,13 @@ impl ByteArrayStorage {
     pub fn bytes_available(&self) -> usize {
         self.len().saturating_sub(self.position.get())
     }
+
+    /// Removes the bytes before the current position, which becomes the start of the ByteArray.
+    pub fn discard_read(&mut self) {
+        let position = self.position.get().min(self.len());
+        self.bytes.drain(..position);
+        self.position.set(0);
+    }
 }
 
 impl Write for ByteArrayStorage {
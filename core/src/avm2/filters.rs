@@ -0,0 +1,330 @@
+//! Conversion of `flash.filters` objects into filters that can be rendered
+
+use crate::avm2::activation::Activation;
+use crate::avm2::object::{Object, TObject};
+use crate::avm2::Error;
+use crate::avm2::Multiname;
+use crate::avm2::Namespace;
+use crate::avm2::Value;
+use ruffle_render::filters::{
+    BevelFilter, BevelFilterType, BlurFilter, ColorMatrixFilter, ConvolutionFilter,
+    DisplacementMapFilter, DisplacementMapFilterMode, DropShadowFilter, Filter, GlowFilter,
+};
+
+/// Converts an instance of one of the `flash.filters` classes into a `Filter`.
+///
+/// Returns `None` if the filter isn't supported yet, or if it wouldn't have any effect.
+pub fn object_to_filter<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    object: Object<'gc>,
+) -> Result<Option<Filter>, Error<'gc>> {
+    let filters_namespace = Namespace::package("flash.filters", activation.context.gc_context);
+    let bevel_filter =
+        activation.resolve_class(&Multiname::new(filters_namespace, "BevelFilter"))?;
+    let blur_filter = activation.resolve_class(&Multiname::new(filters_namespace, "BlurFilter"))?;
+    let color_matrix_filter =
+        activation.resolve_class(&Multiname::new(filters_namespace, "ColorMatrixFilter"))?;
+    let convolution_filter =
+        activation.resolve_class(&Multiname::new(filters_namespace, "ConvolutionFilter"))?;
+    let displacement_map_filter =
+        activation.resolve_class(&Multiname::new(filters_namespace, "DisplacementMapFilter"))?;
+    let drop_shadow_filter =
+        activation.resolve_class(&Multiname::new(filters_namespace, "DropShadowFilter"))?;
+    let glow_filter = activation.resolve_class(&Multiname::new(filters_namespace, "GlowFilter"))?;
+
+    let filter = if object.is_of_type(bevel_filter, activation) {
+        let distance = object
+            .get_public_property("distance", activation)?
+            .coerce_to_number(activation)?;
+        let angle = object
+            .get_public_property("angle", activation)?
+            .coerce_to_number(activation)?;
+        let highlight_color = object
+            .get_public_property("highlightColor", activation)?
+            .coerce_to_u32(activation)?;
+        let highlight_alpha = object
+            .get_public_property("highlightAlpha", activation)?
+            .coerce_to_number(activation)?;
+        let shadow_color = object
+            .get_public_property("shadowColor", activation)?
+            .coerce_to_u32(activation)?;
+        let shadow_alpha = object
+            .get_public_property("shadowAlpha", activation)?
+            .coerce_to_number(activation)?;
+        let blur_x = object
+            .get_public_property("blurX", activation)?
+            .coerce_to_number(activation)?;
+        let blur_y = object
+            .get_public_property("blurY", activation)?
+            .coerce_to_number(activation)?;
+        let strength = object
+            .get_public_property("strength", activation)?
+            .coerce_to_number(activation)?;
+        let quality = object
+            .get_public_property("quality", activation)?
+            .coerce_to_u32(activation)?;
+        let bevel_type = object
+            .get_public_property("type", activation)?
+            .coerce_to_string(activation)?;
+        let bevel_type = if &bevel_type == b"outer" {
+            BevelFilterType::Outer
+        } else if &bevel_type == b"full" {
+            BevelFilterType::Full
+        } else {
+            BevelFilterType::Inner
+        };
+        let knockout = object
+            .get_public_property("knockout", activation)?
+            .coerce_to_boolean();
+        Some(Filter::BevelFilter(BevelFilter {
+            highlight_color: swf::Color::from_rgb(
+                highlight_color,
+                (highlight_alpha.clamp(0.0, 1.0) * 255.0) as u8,
+            ),
+            shadow_color: swf::Color::from_rgb(
+                shadow_color,
+                (shadow_alpha.clamp(0.0, 1.0) * 255.0) as u8,
+            ),
+            angle: angle.to_radians() as f32,
+            distance: distance as f32,
+            blur_x: blur_x as f32,
+            blur_y: blur_y as f32,
+            strength: strength as f32,
+            quality: quality.clamp(1, 15) as u8,
+            bevel_type,
+            knockout,
+        }))
+    } else if object.is_of_type(color_matrix_filter, activation) {
+        let mut matrix = [0.0; 20];
+        if let Some(matrix_object) = object
+            .get_public_property("matrix", activation)?
+            .as_object()
+        {
+            if let Some(array) = matrix_object.as_array_storage() {
+                for i in 0..matrix.len().min(array.length()) {
+                    matrix[i] = array
+                        .get(i)
+                        .expect("Length was already checked at this point")
+                        .coerce_to_number(activation)? as f32;
+                }
+            }
+        }
+        Some(Filter::ColorMatrixFilter(ColorMatrixFilter { matrix }))
+    } else if object.is_of_type(blur_filter, activation) {
+        let blur_x = object
+            .get_public_property("blurX", activation)?
+            .coerce_to_number(activation)?;
+        let blur_y = object
+            .get_public_property("blurY", activation)?
+            .coerce_to_number(activation)?;
+        let quality = object
+            .get_public_property("quality", activation)?
+            .coerce_to_u32(activation)?;
+        Some(Filter::BlurFilter(BlurFilter {
+            blur_x: blur_x as f32,
+            blur_y: blur_y as f32,
+            quality: quality.clamp(1, 15) as u8,
+        }))
+    } else if object.is_of_type(convolution_filter, activation) {
+        let matrix_x = object
+            .get_public_property("matrixX", activation)?
+            .coerce_to_u32(activation)?;
+        let matrix_y = object
+            .get_public_property("matrixY", activation)?
+            .coerce_to_u32(activation)?;
+        let mut matrix = vec![];
+        if let Some(matrix_object) = object
+            .get_public_property("matrix", activation)?
+            .as_object()
+        {
+            if let Some(array) = matrix_object.as_array_storage() {
+                for value in array.iter() {
+                    let value = value.unwrap_or(Value::Undefined);
+                    matrix.push(value.coerce_to_number(activation)? as f32);
+                }
+            }
+        }
+        let divisor = object
+            .get_public_property("divisor", activation)?
+            .coerce_to_number(activation)?;
+        let bias = object
+            .get_public_property("bias", activation)?
+            .coerce_to_number(activation)?;
+        let preserve_alpha = object
+            .get_public_property("preserveAlpha", activation)?
+            .coerce_to_boolean();
+        let clamp = object
+            .get_public_property("clamp", activation)?
+            .coerce_to_boolean();
+        let color = object
+            .get_public_property("color", activation)?
+            .coerce_to_u32(activation)?;
+        let alpha = object
+            .get_public_property("alpha", activation)?
+            .coerce_to_number(activation)?;
+        Some(Filter::ConvolutionFilter(ConvolutionFilter {
+            matrix_x: matrix_x.min(15) as u8,
+            matrix_y: matrix_y.min(15) as u8,
+            matrix,
+            divisor: divisor as f32,
+            bias: bias as f32,
+            preserve_alpha,
+            clamp,
+            color: swf::Color::from_rgb(color, (alpha.clamp(0.0, 1.0) * 255.0) as u8),
+        }))
+    } else if object.is_of_type(displacement_map_filter, activation) {
+        let map = object
+            .get_public_property("mapBitmap", activation)?
+            .as_object()
+            .and_then(|o| o.as_bitmap_data())
+            .and_then(|map| {
+                let mut map = map.write(activation.context.gc_context);
+                map.update_dirty_texture(activation.context.renderer);
+                map.bitmap_handle(activation.context.renderer)
+            });
+        match map {
+            Some(map) => {
+                let map_point = match object
+                    .get_public_property("mapPoint", activation)?
+                    .as_object()
+                {
+                    Some(point) => (
+                        point
+                            .get_public_property("x", activation)?
+                            .coerce_to_i32(activation)?,
+                        point
+                            .get_public_property("y", activation)?
+                            .coerce_to_i32(activation)?,
+                    ),
+                    None => (0, 0),
+                };
+                let component_x = object
+                    .get_public_property("componentX", activation)?
+                    .coerce_to_u32(activation)?;
+                let component_y = object
+                    .get_public_property("componentY", activation)?
+                    .coerce_to_u32(activation)?;
+                let scale_x = object
+                    .get_public_property("scaleX", activation)?
+                    .coerce_to_number(activation)?;
+                let scale_y = object
+                    .get_public_property("scaleY", activation)?
+                    .coerce_to_number(activation)?;
+                let mode = object
+                    .get_public_property("mode", activation)?
+                    .coerce_to_string(activation)?;
+                let mode = if &mode == b"clamp" {
+                    DisplacementMapFilterMode::Clamp
+                } else if &mode == b"ignore" {
+                    DisplacementMapFilterMode::Ignore
+                } else if &mode == b"color" {
+                    DisplacementMapFilterMode::Color
+                } else {
+                    DisplacementMapFilterMode::Wrap
+                };
+                let color = object
+                    .get_public_property("color", activation)?
+                    .coerce_to_u32(activation)?;
+                let alpha = object
+                    .get_public_property("alpha", activation)?
+                    .coerce_to_number(activation)?;
+                Some(Filter::DisplacementMapFilter(DisplacementMapFilter {
+                    map,
+                    map_point,
+                    component_x: component_x as u8,
+                    component_y: component_y as u8,
+                    scale_x: scale_x as f32,
+                    scale_y: scale_y as f32,
+                    mode,
+                    color: swf::Color::from_rgb(color, (alpha.clamp(0.0, 1.0) * 255.0) as u8),
+                }))
+            }
+            // Without a map there's nothing to displace by
+            None => None,
+        }
+    } else if object.is_of_type(drop_shadow_filter, activation) {
+        let distance = object
+            .get_public_property("distance", activation)?
+            .coerce_to_number(activation)?;
+        let angle = object
+            .get_public_property("angle", activation)?
+            .coerce_to_number(activation)?;
+        let color = object
+            .get_public_property("color", activation)?
+            .coerce_to_u32(activation)?;
+        let alpha = object
+            .get_public_property("alpha", activation)?
+            .coerce_to_number(activation)?;
+        let blur_x = object
+            .get_public_property("blurX", activation)?
+            .coerce_to_number(activation)?;
+        let blur_y = object
+            .get_public_property("blurY", activation)?
+            .coerce_to_number(activation)?;
+        let strength = object
+            .get_public_property("strength", activation)?
+            .coerce_to_number(activation)?;
+        let quality = object
+            .get_public_property("quality", activation)?
+            .coerce_to_u32(activation)?;
+        let inner = object
+            .get_public_property("inner", activation)?
+            .coerce_to_boolean();
+        let knockout = object
+            .get_public_property("knockout", activation)?
+            .coerce_to_boolean();
+        let hide_object = object
+            .get_public_property("hideObject", activation)?
+            .coerce_to_boolean();
+        Some(Filter::DropShadowFilter(DropShadowFilter {
+            color: swf::Color::from_rgb(color, (alpha.clamp(0.0, 1.0) * 255.0) as u8),
+            angle: angle.to_radians() as f32,
+            distance: distance as f32,
+            blur_x: blur_x as f32,
+            blur_y: blur_y as f32,
+            strength: strength as f32,
+            quality: quality.clamp(1, 15) as u8,
+            inner,
+            knockout,
+            hide_object,
+        }))
+    } else if object.is_of_type(glow_filter, activation) {
+        let color = object
+            .get_public_property("color", activation)?
+            .coerce_to_u32(activation)?;
+        let alpha = object
+            .get_public_property("alpha", activation)?
+            .coerce_to_number(activation)?;
+        let blur_x = object
+            .get_public_property("blurX", activation)?
+            .coerce_to_number(activation)?;
+        let blur_y = object
+            .get_public_property("blurY", activation)?
+            .coerce_to_number(activation)?;
+        let strength = object
+            .get_public_property("strength", activation)?
+            .coerce_to_number(activation)?;
+        let quality = object
+            .get_public_property("quality", activation)?
+            .coerce_to_u32(activation)?;
+        let inner = object
+            .get_public_property("inner", activation)?
+            .coerce_to_boolean();
+        let knockout = object
+            .get_public_property("knockout", activation)?
+            .coerce_to_boolean();
+        Some(Filter::GlowFilter(GlowFilter {
+            color: swf::Color::from_rgb(color, (alpha.clamp(0.0, 1.0) * 255.0) as u8),
+            blur_x: blur_x as f32,
+            blur_y: blur_y as f32,
+            strength: strength as f32,
+            quality: quality.clamp(1, 15) as u8,
+            inner,
+            knockout,
+        }))
+    } else {
+        None
+    };
+
+    Ok(filter)
+}
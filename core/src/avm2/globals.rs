@@ -87,19 +87,24 @@ pub struct SystemClasses<'gc> {
     pub errorevent: ClassObject<'gc>,
     pub ioerrorevent: ClassObject<'gc>,
     pub securityerrorevent: ClassObject<'gc>,
+    pub netstatusevent: ClassObject<'gc>,
+    pub statusevent: ClassObject<'gc>,
     pub transform: ClassObject<'gc>,
     pub colortransform: ClassObject<'gc>,
     pub matrix: ClassObject<'gc>,
+    pub matrix3d: ClassObject<'gc>,
     pub illegaloperationerror: ClassObject<'gc>,
     pub eventdispatcher: ClassObject<'gc>,
     pub rectangle: ClassObject<'gc>,
     pub keyboardevent: ClassObject<'gc>,
     pub point: ClassObject<'gc>,
+    pub error: ClassObject<'gc>,
     pub rangeerror: ClassObject<'gc>,
     pub referenceerror: ClassObject<'gc>,
     pub argumenterror: ClassObject<'gc>,
     pub typeerror: ClassObject<'gc>,
     pub verifyerror: ClassObject<'gc>,
+    pub securityerror: ClassObject<'gc>,
     pub ioerror: ClassObject<'gc>,
     pub eoferror: ClassObject<'gc>,
     pub uncaughterrorevents: ClassObject<'gc>,
@@ -173,19 +178,24 @@ impl<'gc> SystemClasses<'gc> {
             errorevent: object,
             ioerrorevent: object,
             securityerrorevent: object,
+            netstatusevent: object,
+            statusevent: object,
             transform: object,
             colortransform: object,
             matrix: object,
+            matrix3d: object,
             illegaloperationerror: object,
             eventdispatcher: object,
             rectangle: object,
             keyboardevent: object,
             point: object,
+            error: object,
             rangeerror: object,
             referenceerror: object,
             argumenterror: object,
             typeerror: object,
             verifyerror: object,
+            securityerror: object,
             ioerror: object,
             eoferror: object,
             uncaughterrorevents: object,
@@ -679,8 +689,10 @@ fn load_playerglobal<'gc>(
         script,
         [
             ("", "ArgumentError", argumenterror),
+            ("", "Error", error),
             ("", "RangeError", rangeerror),
             ("", "ReferenceError", referenceerror),
+            ("", "SecurityError", securityerror),
             ("", "TypeError", typeerror),
             ("", "VerifyError", verifyerror),
             ("", "XML", xml),
@@ -706,10 +718,13 @@ fn load_playerglobal<'gc>(
             ("flash.events", "ProgressEvent", progressevent),
             ("flash.events", "SecurityErrorEvent", securityerrorevent),
             ("flash.events", "IOErrorEvent", ioerrorevent),
+            ("flash.events", "NetStatusEvent", netstatusevent),
+            ("flash.events", "StatusEvent", statusevent),
             ("flash.events", "MouseEvent", mouseevent),
             ("flash.events", "FullScreenEvent", fullscreenevent),
             ("flash.events", "UncaughtErrorEvents", uncaughterrorevents),
             ("flash.geom", "Matrix", matrix),
+            ("flash.geom", "Matrix3D", matrix3d),
             ("flash.geom", "Point", point),
             ("flash.geom", "Rectangle", rectangle),
             ("flash.geom", "Transform", transform),
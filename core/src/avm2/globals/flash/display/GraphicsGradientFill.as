@@ -0,0 +1,26 @@
+package flash.display {
+    import flash.geom.Matrix;
+
+    public final class GraphicsGradientFill implements IGraphicsFill, IGraphicsData {
+        public var type: String;
+        public var colors: Array;
+        public var alphas: Array;
+        public var ratios: Array;
+        public var matrix: Matrix;
+        public var spreadMethod: String;
+        public var interpolationMethod: String;
+        public var focalPointRatio: Number;
+
+        public function GraphicsGradientFill(type: String = "linear", colors: Array = null, alphas: Array = null, ratios: Array = null,
+                                             matrix: * = null, spreadMethod: * = "pad", interpolationMethod: String = "rgb", focalPointRatio: Number = 0.0) {
+            this.type = type;
+            this.colors = colors;
+            this.alphas = alphas;
+            this.ratios = ratios;
+            this.matrix = matrix;
+            this.spreadMethod = spreadMethod;
+            this.interpolationMethod = interpolationMethod;
+            this.focalPointRatio = focalPointRatio;
+        }
+    }
+}
@@ -0,0 +1,11 @@
+package flash.display {
+    public final class GraphicsSolidFill implements IGraphicsFill, IGraphicsData {
+        public var color: uint = 0;
+        public var alpha: Number = 1.0;
+
+        public function GraphicsSolidFill(color: uint = 0, alpha: Number = 1.0) {
+            this.color = color;
+            this.alpha = alpha;
+        }
+    }
+}
@@ -2,7 +2,8 @@
 
 use crate::avm2::activation::Activation;
 use crate::avm2::class::{Class, ClassAttributes};
-use crate::avm2::error::argument_error;
+use crate::avm2::error::{argument_error, type_error};
+use crate::avm2::filters::object_to_filter;
 use crate::avm2::method::{Method, NativeMethodImpl};
 use crate::avm2::object::{
     bitmapdata_allocator, BitmapDataObject, ByteArrayObject, Object, TObject,
@@ -20,7 +21,7 @@ use crate::character::Character;
 use crate::display_object::Bitmap;
 use crate::swf::BlendMode;
 use gc_arena::GcCell;
-use ruffle_render::filters::{BlurFilter, ColorMatrixFilter, Filter};
+use ruffle_render::filters::Filter;
 use ruffle_render::transform::Transform;
 use std::str::FromStr;
 
@@ -591,25 +592,196 @@ pub fn copy_channel<'gc>(
     Ok(Value::Undefined)
 }
 
-pub fn flood_fill<'gc>(
+/// Implements `BitmapData.threshold`.
+pub fn threshold<'gc>(
     activation: &mut Activation<'_, 'gc>,
     this: Option<Object<'gc>>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     if let Some(bitmap_data) = this.and_then(|t| t.as_bitmap_data()) {
-        let mut bitmap_data = bitmap_data.write(activation.context.gc_context);
-        if !bitmap_data.disposed() {
-            if let (Some(x_val), Some(y_val), Some(color_val)) =
-                (args.get(0), args.get(1), args.get(2))
-            {
-                let x = x_val.coerce_to_u32(activation)?;
-                let y = y_val.coerce_to_u32(activation)?;
-                let color = color_val.coerce_to_i32(activation)?;
+        bitmap_data.read().check_valid(activation)?;
+        let source_bitmap = args
+            .get(0)
+            .unwrap_or(&Value::Undefined)
+            .coerce_to_object(activation)?;
 
-                let color: Color = color.into();
-                let color: Color = color.to_premultiplied_alpha(bitmap_data.transparency());
+        let source_rect = args
+            .get(1)
+            .unwrap_or(&Value::Undefined)
+            .coerce_to_object(activation)?;
 
-                bitmap_data.flood_fill(x, y, color);
+        let src_min_x = source_rect
+            .get_public_property("x", activation)?
+            .coerce_to_i32(activation)?;
+        let src_min_y = source_rect
+            .get_public_property("y", activation)?
+            .coerce_to_i32(activation)?;
+        let src_width = source_rect
+            .get_public_property("width", activation)?
+            .coerce_to_i32(activation)?;
+        let src_height = source_rect
+            .get_public_property("height", activation)?
+            .coerce_to_i32(activation)?;
+
+        let dest_point = args
+            .get(2)
+            .unwrap_or(&Value::Undefined)
+            .coerce_to_object(activation)?;
+
+        let dest_x = dest_point
+            .get_public_property("x", activation)?
+            .coerce_to_i32(activation)?;
+        let dest_y = dest_point
+            .get_public_property("y", activation)?
+            .coerce_to_i32(activation)?;
+
+        let operation = args
+            .get(3)
+            .unwrap_or(&Value::Undefined)
+            .coerce_to_string(activation)?;
+
+        let threshold = args
+            .get(4)
+            .unwrap_or(&Value::Undefined)
+            .coerce_to_u32(activation)?;
+
+        let colour = args.get(5).unwrap_or(&0.into()).coerce_to_u32(activation)?;
+
+        let mask = args
+            .get(6)
+            .unwrap_or(&0xFFFFFFFFu32.into())
+            .coerce_to_u32(activation)?;
+
+        let copy_source = args.get(7).unwrap_or(&false.into()).coerce_to_boolean();
+
+        if let Some(source_bitmap) = source_bitmap.as_bitmap_data() {
+            source_bitmap.read().check_valid(activation)?;
+            let src_rect = (src_min_x, src_min_y, src_width, src_height);
+            let modified_count = if GcCell::ptr_eq(bitmap_data, source_bitmap) {
+                let src_bitmap_data_clone = source_bitmap.read().clone();
+                bitmap_data.write(activation.context.gc_context).threshold(
+                    &src_bitmap_data_clone,
+                    src_rect,
+                    (dest_x, dest_y),
+                    &operation,
+                    threshold,
+                    colour,
+                    mask,
+                    copy_source,
+                )
+            } else {
+                bitmap_data.write(activation.context.gc_context).threshold(
+                    &source_bitmap.read(),
+                    src_rect,
+                    (dest_x, dest_y),
+                    &operation,
+                    threshold,
+                    colour,
+                    mask,
+                    copy_source,
+                )
+            };
+
+            return Ok(modified_count.into());
+        }
+    }
+
+    Ok(0.into())
+}
+
+/// Implements `BitmapData.paletteMap`.
+pub fn palette_map<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(bitmap_data) = this.and_then(|t| t.as_bitmap_data()) {
+        bitmap_data.read().check_valid(activation)?;
+        let source_bitmap = args
+            .get(0)
+            .unwrap_or(&Value::Undefined)
+            .coerce_to_object(activation)?;
+
+        let source_rect = args
+            .get(1)
+            .unwrap_or(&Value::Undefined)
+            .coerce_to_object(activation)?;
+
+        let src_min_x = source_rect
+            .get_public_property("x", activation)?
+            .coerce_to_i32(activation)?;
+        let src_min_y = source_rect
+            .get_public_property("y", activation)?
+            .coerce_to_i32(activation)?;
+        let src_width = source_rect
+            .get_public_property("width", activation)?
+            .coerce_to_i32(activation)?;
+        let src_height = source_rect
+            .get_public_property("height", activation)?
+            .coerce_to_i32(activation)?;
+
+        let dest_point = args
+            .get(2)
+            .unwrap_or(&Value::Undefined)
+            .coerce_to_object(activation)?;
+
+        let dest_x = dest_point
+            .get_public_property("x", activation)?
+            .coerce_to_i32(activation)?;
+        let dest_y = dest_point
+            .get_public_property("y", activation)?
+            .coerce_to_i32(activation)?;
+
+        let mut get_channel = |index: usize, shift: usize| -> Result<[u32; 256], Error<'gc>> {
+            let array: Option<Vec<_>> = args
+                .get(index)
+                .and_then(|arg| arg.as_object())
+                .and_then(|arg| arg.as_array_storage().map(|array| array.iter().collect()));
+            let mut channel = [0_u32; 256];
+            for (i, item) in channel.iter_mut().enumerate() {
+                *item = if let Some(array) = &array {
+                    array
+                        .get(i)
+                        .copied()
+                        .flatten()
+                        .unwrap_or(Value::Undefined)
+                        .coerce_to_u32(activation)?
+                } else {
+                    // A null array maps the channel to itself
+                    (i << shift) as u32
+                }
+            }
+            Ok(channel)
+        };
+
+        let red_array = get_channel(3, 16)?;
+        let green_array = get_channel(4, 8)?;
+        let blue_array = get_channel(5, 0)?;
+        let alpha_array = get_channel(6, 24)?;
+
+        if let Some(source_bitmap) = source_bitmap.as_bitmap_data() {
+            source_bitmap.read().check_valid(activation)?;
+            let src_rect = (src_min_x, src_min_y, src_width, src_height);
+            let channel_arrays = (red_array, green_array, blue_array, alpha_array);
+            if GcCell::ptr_eq(bitmap_data, source_bitmap) {
+                let src_bitmap_data_clone = source_bitmap.read().clone();
+                bitmap_data
+                    .write(activation.context.gc_context)
+                    .palette_map(
+                        &src_bitmap_data_clone,
+                        src_rect,
+                        (dest_x, dest_y),
+                        channel_arrays,
+                    );
+            } else {
+                bitmap_data
+                    .write(activation.context.gc_context)
+                    .palette_map(
+                        &source_bitmap.read(),
+                        src_rect,
+                        (dest_x, dest_y),
+                        channel_arrays,
+                    );
             }
         }
     }
@@ -617,6 +789,30 @@ pub fn flood_fill<'gc>(
     Ok(Value::Undefined)
 }
 
+pub fn flood_fill<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(bitmap_data) = this.and_then(|t| t.as_bitmap_data()) {
+        bitmap_data.read().check_valid(activation)?;
+        if let (Some(x_val), Some(y_val), Some(color_val)) = (args.get(0), args.get(1), args.get(2))
+        {
+            let x = x_val.coerce_to_u32(activation)?;
+            let y = y_val.coerce_to_u32(activation)?;
+            let color = color_val.coerce_to_i32(activation)?;
+
+            let mut bitmap_data = bitmap_data.write(activation.context.gc_context);
+            let color: Color = color.into();
+            let color: Color = color.to_premultiplied_alpha(bitmap_data.transparency());
+
+            bitmap_data.flood_fill(x, y, color);
+        }
+    }
+
+    Ok(Value::Undefined)
+}
+
 pub fn noise<'gc>(
     activation: &mut Activation<'_, 'gc>,
     this: Option<Object<'gc>>,
@@ -698,24 +894,24 @@ pub fn get_color_bounds_rect<'gc>(
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     if let Some(bitmap_data) = this.and_then(|t| t.as_bitmap_data()) {
-        let bitmap_data = bitmap_data.read();
-        if !bitmap_data.disposed() {
-            let find_color = args.get(2).unwrap_or(&true.into()).coerce_to_boolean();
-
-            if let (Some(mask_val), Some(color_val)) = (args.get(0), args.get(1)) {
-                let mask = mask_val.coerce_to_i32(activation)?;
-                let color = color_val.coerce_to_i32(activation)?;
-
-                let (x, y, w, h) = bitmap_data.color_bounds_rect(find_color, mask, color);
-
-                let rect = activation
-                    .avm2()
-                    .classes()
-                    .rectangle
-                    .construct(activation, &[x.into(), y.into(), w.into(), h.into()])?
-                    .into();
-                return Ok(rect);
-            }
+        bitmap_data.read().check_valid(activation)?;
+        let find_color = args.get(2).unwrap_or(&true.into()).coerce_to_boolean();
+
+        if let (Some(mask_val), Some(color_val)) = (args.get(0), args.get(1)) {
+            let mask = mask_val.coerce_to_i32(activation)?;
+            let color = color_val.coerce_to_i32(activation)?;
+
+            let (x, y, w, h) = bitmap_data
+                .read()
+                .color_bounds_rect(find_color, mask, color);
+
+            let rect = activation
+                .avm2()
+                .classes()
+                .rectangle
+                .construct(activation, &[x.into(), y.into(), w.into(), h.into()])?
+                .into();
+            return Ok(rect);
         }
     }
 
@@ -928,16 +1124,16 @@ pub fn fill_rect<'gc>(
     if let Some(bitmap_data) = this.and_then(|this| this.as_bitmap_data()) {
         let x = rectangle
             .get_public_property("x", activation)?
-            .coerce_to_u32(activation)?;
+            .coerce_to_i32(activation)?;
         let y = rectangle
             .get_public_property("y", activation)?
-            .coerce_to_u32(activation)?;
+            .coerce_to_i32(activation)?;
         let width = rectangle
             .get_public_property("width", activation)?
-            .coerce_to_u32(activation)?;
+            .coerce_to_i32(activation)?;
         let height = rectangle
             .get_public_property("height", activation)?
-            .coerce_to_u32(activation)?;
+            .coerce_to_i32(activation)?;
 
         bitmap_data.write(activation.context.gc_context).fill_rect(
             x,
@@ -1010,16 +1206,6 @@ pub fn apply_filter<'gc>(
             .ok_or_else(|| {
                 Error::from(format!("TypeError: Error #1034: Type Coercion failed: cannot convert {} to flash.display.BitmapData.", args[0].coerce_to_string(activation).unwrap_or_default()))
             })?;
-        let source_handle = match source_bitmap
-            .write(activation.context.gc_context)
-            .bitmap_handle(activation.context.renderer)
-        {
-            Some(handle) => handle,
-            None => {
-                tracing::warn!("Ignoring BitmapData.apply_filter() with an undrawable source");
-                return Ok(Value::Undefined);
-            }
-        };
         let source_rect = args[1]
             .as_object()
             .and_then(|o| super::displayobject::object_to_rectangle(activation, o).ok())
@@ -1044,7 +1230,7 @@ pub fn apply_filter<'gc>(
                 .get_public_property("x", activation)?
                 .coerce_to_u32(activation)?,
             dest_point
-                .get_public_property("x", activation)?
+                .get_public_property("y", activation)?
                 .coerce_to_u32(activation)?,
         );
         let filter = args[3]
@@ -1054,22 +1240,10 @@ pub fn apply_filter<'gc>(
             })?;
 
         let filters_namespace = Namespace::package("flash.filters", activation.context.gc_context);
-        let bevel_filter =
-            activation.resolve_class(&Multiname::new(filters_namespace, "BevelFilter"))?;
         let bitmap_filter =
             activation.resolve_class(&Multiname::new(filters_namespace, "BitmapFilter"))?;
-        let blur_filter =
-            activation.resolve_class(&Multiname::new(filters_namespace, "BlurFilter"))?;
-        let color_matrix_filter =
-            activation.resolve_class(&Multiname::new(filters_namespace, "ColorMatrixFilter"))?;
-        let convolution_filter =
-            activation.resolve_class(&Multiname::new(filters_namespace, "ConvolutionFilter"))?;
         let displacement_map_filter = activation
             .resolve_class(&Multiname::new(filters_namespace, "DisplacementMapFilter"))?;
-        let drop_shadow_filter =
-            activation.resolve_class(&Multiname::new(filters_namespace, "DropShadowFilter"))?;
-        let glow_filter =
-            activation.resolve_class(&Multiname::new(filters_namespace, "GlowFilter"))?;
         let gradient_bevel_filter =
             activation.resolve_class(&Multiname::new(filters_namespace, "GradientBevelFilter"))?;
         let gradient_glow_filter =
@@ -1078,101 +1252,10 @@ pub fn apply_filter<'gc>(
         //     Namespace::package("flash.filters"),
         //     "ShaderFilter",
         // ))?;
-        let filter = if filter.is_of_type(color_matrix_filter, activation) {
-            let mut matrix = [0.0; 20];
-            if let Some(object) = filter
-                .get_public_property("matrix", activation)?
-                .as_object()
-            {
-                if let Some(array) = object.as_array_storage() {
-                    for i in 0..matrix.len().min(array.length()) {
-                        matrix[i] = array
-                            .get(i)
-                            .expect("Length was already checked at this point")
-                            .coerce_to_number(activation)?
-                            as f32;
-                    }
-                }
-            }
-            Filter::ColorMatrixFilter(ColorMatrixFilter { matrix })
-        } else if filter.is_of_type(blur_filter, activation) {
-            let blur_x = filter
-                .get_public_property("blurX", activation)?
-                .coerce_to_number(activation)?;
-            let blur_y = filter
-                .get_public_property("blurY", activation)?
-                .coerce_to_number(activation)?;
-            let quality = filter
-                .get_public_property("quality", activation)?
-                .coerce_to_u32(activation)?;
-            Filter::BlurFilter(BlurFilter {
-                blur_x: blur_x as f32,
-                blur_y: blur_y as f32,
-                quality: quality.clamp(1, 15) as u8,
-            })
-        } else if filter.is_of_type(bevel_filter, activation) {
-            avm2_stub_method!(
-                activation,
-                "flash.display.BitmapData",
-                "applyFilter",
-                "with bevel filter"
-            );
-            Filter::default()
-        } else if filter.is_of_type(bitmap_filter, activation) {
-            avm2_stub_method!(
-                activation,
-                "flash.display.BitmapData",
-                "applyFilter",
-                "with bitmap filter"
-            );
-            Filter::default()
-        } else if filter.is_of_type(blur_filter, activation) {
-            avm2_stub_method!(
-                activation,
-                "flash.display.BitmapData",
-                "applyFilter",
-                "with blur filter"
-            );
-            Filter::default()
-        } else if filter.is_of_type(color_matrix_filter, activation) {
-            avm2_stub_method!(
-                activation,
-                "flash.display.BitmapData",
-                "applyFilter",
-                "with color matrix filter"
-            );
-            Filter::default()
-        } else if filter.is_of_type(convolution_filter, activation) {
-            avm2_stub_method!(
-                activation,
-                "flash.display.BitmapData",
-                "applyFilter",
-                "with convolution filter"
-            );
-            Filter::default()
+        let filter = if let Some(filter) = object_to_filter(activation, filter)? {
+            filter
         } else if filter.is_of_type(displacement_map_filter, activation) {
-            avm2_stub_method!(
-                activation,
-                "flash.display.BitmapData",
-                "applyFilter",
-                "with displacement map filter"
-            );
-            Filter::default()
-        } else if filter.is_of_type(drop_shadow_filter, activation) {
-            avm2_stub_method!(
-                activation,
-                "flash.display.BitmapData",
-                "applyFilter",
-                "with drop shadow filter"
-            );
-            Filter::default()
-        } else if filter.is_of_type(glow_filter, activation) {
-            avm2_stub_method!(
-                activation,
-                "flash.display.BitmapData",
-                "applyFilter",
-                "with glow filter"
-            );
+            // Without a map there's nothing to displace by
             Filter::default()
         } else if filter.is_of_type(gradient_bevel_filter, activation) {
             avm2_stub_method!(
@@ -1198,23 +1281,229 @@ pub fn apply_filter<'gc>(
         //         "with shader filter"
         //     );
         //     Filter::default()
+        } else if filter.is_of_type(bitmap_filter, activation) {
+            avm2_stub_method!(
+                activation,
+                "flash.display.BitmapData",
+                "applyFilter",
+                "with bitmap filter"
+            );
+            Filter::default()
         } else {
             tracing::error!("BitmapData.applyFilter received unknown filter");
             Filter::default()
         };
-        let mut dest_bitmap_data = dest_bitmap_data.write(activation.context.gc_context);
-        dest_bitmap_data.apply_filter(
-            &mut activation.context,
-            source_handle,
-            source_point,
-            source_size,
-            dest_point,
-            filter,
-        )
+        if activation.context.renderer.is_filter_supported(&filter) {
+            let source_handle = match source_bitmap
+                .write(activation.context.gc_context)
+                .bitmap_handle(activation.context.renderer)
+            {
+                Some(handle) => handle,
+                None => {
+                    tracing::warn!("Ignoring BitmapData.apply_filter() with an undrawable source");
+                    return Ok(Value::Undefined);
+                }
+            };
+            let mut dest_bitmap_data = dest_bitmap_data.write(activation.context.gc_context);
+            dest_bitmap_data.apply_filter(
+                &mut activation.context,
+                source_handle,
+                source_point,
+                source_size,
+                dest_point,
+                filter,
+            )
+        } else {
+            // Only a renderer that applies filters itself can leave pixels waiting on the GPU,
+            // so the pixels of both bitmaps are already up to date here
+            let source_bitmap_clone;
+            let source_bitmap_ref;
+            let source: &BitmapData = if GcCell::ptr_eq(source_bitmap, dest_bitmap_data) {
+                source_bitmap_clone = source_bitmap.read().clone();
+                &source_bitmap_clone
+            } else {
+                source_bitmap_ref = source_bitmap.read();
+                &source_bitmap_ref
+            };
+            let applied = dest_bitmap_data
+                .write(activation.context.gc_context)
+                .apply_filter_cpu(source, source_point, source_size, dest_point, &filter);
+            if !applied {
+                tracing::warn!("BitmapData.apply_filter: Filter not yet implemented on the CPU")
+            }
+        }
+    }
+    Ok(Value::Undefined)
+}
+
+/// Implement `BitmapData.generateFilterRect`
+pub fn generate_filter_rect<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(bitmap_data) = this.and_then(|this| this.as_bitmap_data()) {
+        bitmap_data.read().check_valid(activation)?;
+        let source_rect = args
+            .get(0)
+            .and_then(|v| v.as_object())
+            .and_then(|o| super::displayobject::object_to_rectangle(activation, o).ok())
+            .ok_or_else(|| {
+                Error::from(format!("TypeError: Error #1034: Type Coercion failed: cannot convert {} to flash.geom.Rectangle.", args.get(0).unwrap_or(&Value::Undefined).coerce_to_string(activation).unwrap_or_default()))
+            })?;
+        let filter = args
+            .get(1)
+            .and_then(|v| v.as_object())
+            .ok_or_else(|| {
+                Error::from(format!("TypeError: Error #1034: Type Coercion failed: cannot convert {} to flash.filters.BitmapFilter.", args.get(1).unwrap_or(&Value::Undefined).coerce_to_string(activation).unwrap_or_default()))
+            })?;
+
+        // A filter that isn't supported yet is treated as one that stays inside the source
+        let rect = match object_to_filter(activation, filter)? {
+            Some(filter) => crate::bitmap::bitmap_data::generate_filter_rect(&filter, source_rect),
+            None => source_rect,
+        };
+        return Ok(activation
+            .avm2()
+            .classes()
+            .rectangle
+            .construct(
+                activation,
+                &[
+                    rect.x_min.to_pixels().into(),
+                    rect.y_min.to_pixels().into(),
+                    rect.width().to_pixels().into(),
+                    rect.height().to_pixels().into(),
+                ],
+            )?
+            .into());
     }
     Ok(Value::Undefined)
 }
 
+/// Reads the integer coordinates of a `Point`, which must not be null.
+fn point_coordinates<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    point: Option<&Value<'gc>>,
+    name: &str,
+) -> Result<(i64, i64), Error<'gc>> {
+    let Some(point) = point.and_then(|point| point.as_object()) else {
+        return Err(Error::AvmError(type_error(
+            activation,
+            &format!("Error #2007: Parameter {name} must be non-null."),
+            2007,
+        )?));
+    };
+    Ok((
+        point
+            .get_public_property("x", activation)?
+            .coerce_to_i32(activation)?
+            .into(),
+        point
+            .get_public_property("y", activation)?
+            .coerce_to_i32(activation)?
+            .into(),
+    ))
+}
+
+/// Implements `BitmapData.hitTest`
+pub fn hit_test<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(bitmap_data) = this.and_then(|t| t.as_bitmap_data()) {
+        bitmap_data.read().check_valid(activation)?;
+        let top_left = point_coordinates(activation, args.get(0), "firstPoint")?;
+        let alpha_threshold = args
+            .get(1)
+            .unwrap_or(&Value::Undefined)
+            .coerce_to_u32(activation)?;
+        let Some(compare_object) = args.get(2).and_then(|o| o.as_object()) else {
+            return Err(Error::AvmError(type_error(
+                activation,
+                "Error #2007: Parameter secondObject must be non-null.",
+                2007,
+            )?));
+        };
+
+        // Everything is tested relative to the top left of this bitmap
+        let other_bitmap = if let Some(bitmap) = compare_object
+            .as_display_object()
+            .and_then(|dobj| dobj.as_bitmap())
+        {
+            Some(bitmap.bitmap_data())
+        } else {
+            compare_object.as_bitmap_data()
+        };
+        if let Some(other_bitmap) = other_bitmap {
+            other_bitmap.read().check_valid(activation)?;
+            let second_point = point_coordinates(activation, args.get(3), "secondBitmapDataPoint")?;
+            let second_alpha_threshold =
+                args.get(4).unwrap_or(&1.into()).coerce_to_u32(activation)?;
+
+            // Both bitmaps can be read at once, even if they're the same one
+            let result = bitmap_data.read().hit_test_bitmapdata(
+                alpha_threshold,
+                &other_bitmap.read(),
+                (second_point.0 - top_left.0, second_point.1 - top_left.1),
+                second_alpha_threshold,
+            );
+            return Ok(result.into());
+        }
+
+        let rectangle_class = activation.avm2().classes().rectangle;
+        let point_class = activation.avm2().classes().point;
+        let rect = if compare_object.is_of_type(rectangle_class, activation) {
+            (
+                compare_object
+                    .get_public_property("x", activation)?
+                    .coerce_to_i32(activation)?,
+                compare_object
+                    .get_public_property("y", activation)?
+                    .coerce_to_i32(activation)?,
+                compare_object
+                    .get_public_property("width", activation)?
+                    .coerce_to_i32(activation)?,
+                compare_object
+                    .get_public_property("height", activation)?
+                    .coerce_to_i32(activation)?,
+            )
+        } else if compare_object.is_of_type(point_class, activation) {
+            // A point is tested as a single pixel
+            (
+                compare_object
+                    .get_public_property("x", activation)?
+                    .coerce_to_i32(activation)?,
+                compare_object
+                    .get_public_property("y", activation)?
+                    .coerce_to_i32(activation)?,
+                1,
+                1,
+            )
+        } else {
+            return Err(Error::AvmError(type_error(
+                activation,
+                "Error #2005: Parameter 0 is of the incorrect type. Should be type BitmapData, Bitmap, Rectangle or Point.",
+                2005,
+            )?));
+        };
+
+        let result = bitmap_data.read().hit_test_rectangle(
+            alpha_threshold,
+            (
+                i64::from(rect.0) - top_left.0,
+                i64::from(rect.1) - top_left.1,
+                rect.2.into(),
+                rect.3.into(),
+            ),
+        );
+        return Ok(result.into());
+    }
+
+    Ok(false.into())
+}
+
 /// Implement `BitmapData.clone`
 pub fn clone<'gc>(
     activation: &mut Activation<'_, 'gc>,
@@ -1369,6 +1658,8 @@ pub fn create_class<'gc>(activation: &mut Activation<'_, 'gc>) -> GcCell<'gc, Cl
         ("setPixel32", set_pixel32),
         ("setPixels", set_pixels),
         ("copyChannel", copy_channel),
+        ("threshold", threshold),
+        ("paletteMap", palette_map),
         ("floodFill", flood_fill),
         ("noise", noise),
         ("colorTransform", color_transform),
@@ -1382,6 +1673,8 @@ pub fn create_class<'gc>(activation: &mut Activation<'_, 'gc>) -> GcCell<'gc, Cl
         ("fillRect", fill_rect),
         ("dispose", dispose),
         ("applyFilter", apply_filter),
+        ("generateFilterRect", generate_filter_rect),
+        ("hitTest", hit_test),
         ("clone", clone),
         ("perlinNoise", perlin_noise),
     ];
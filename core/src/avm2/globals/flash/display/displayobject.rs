@@ -2,6 +2,7 @@
 
 use crate::avm2::activation::Activation;
 use crate::avm2::class::Class;
+use crate::avm2::filters::object_to_filter;
 use crate::avm2::method::{Method, NativeMethodImpl};
 use crate::avm2::object::{stage_allocator, Object, TObject};
 use crate::avm2::value::Value;
@@ -10,7 +11,7 @@ use crate::avm2::Multiname;
 use crate::avm2::Namespace;
 use crate::avm2::QName;
 use crate::avm2::{ArrayObject, ArrayStorage};
-use crate::display_object::{DisplayObject, HitTestOptions, TDisplayObject};
+use crate::display_object::{DisplayObject, HitTestOptions, TDisplayObject, Transform3D};
 use crate::ecma_conversions::round_to_even;
 use crate::frame_lifecycle::catchup_display_object_to_frame;
 use crate::prelude::*;
@@ -288,6 +289,7 @@ pub fn set_filters<'gc>(
         if matches!(new_filters, Value::Undefined | Value::Null) {
             let new_storage = ArrayStorage::new(0);
             dobj.set_filters(activation.context.gc_context, new_storage);
+            dobj.set_render_filters(activation.context.gc_context, Vec::new());
         } else {
             let new_filters = new_filters.coerce_to_object(activation)?;
 
@@ -299,6 +301,7 @@ pub fn set_filters<'gc>(
 
                     let filter_class_object = activation.resolve_class(&filter_class)?;
 
+                    let mut render_filters = Vec::new();
                     for filter in filters_storage.iter().flatten() {
                         if matches!(filter, Value::Undefined | Value::Null) {
                             return build_argument_type_error(activation);
@@ -308,11 +311,20 @@ pub fn set_filters<'gc>(
                             if !filter_object.is_of_type(filter_class_object, activation) {
                                 return build_argument_type_error(activation);
                             }
+
+                            // Later changes to the filter objects don't affect the display object,
+                            // so they're converted once here.
+                            if let Some(render_filter) =
+                                object_to_filter(activation, filter_object)?
+                            {
+                                render_filters.push(render_filter);
+                            }
                         }
                     }
                     let new_storage = ArrayStorage::from_storage(filters_storage.iter().collect());
 
                     dobj.set_filters(activation.context.gc_context, new_storage);
+                    dobj.set_render_filters(activation.context.gc_context, render_filters);
                 }
             }
         }
@@ -385,93 +397,163 @@ pub fn set_y<'gc>(
     Ok(Value::Undefined)
 }
 
+/// Implements `z`'s getter.
 pub fn z<'gc>(
-    activation: &mut Activation<'_, 'gc>,
-    _this: Option<Object<'gc>>,
+    _activation: &mut Activation<'_, 'gc>,
+    this: Option<Object<'gc>>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    avm2_stub_getter!(activation, "flash.display.DisplayObject", "z");
-    Ok(0.into())
+    if let Some(dobj) = this.and_then(|this| this.as_display_object()) {
+        return Ok(dobj.transform_3d().z.into());
+    }
+
+    Ok(Value::Undefined)
 }
 
+/// Implements `z`'s setter.
 pub fn set_z<'gc>(
     activation: &mut Activation<'_, 'gc>,
-    _this: Option<Object<'gc>>,
-    _args: &[Value<'gc>],
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    avm2_stub_setter!(activation, "flash.display.DisplayObject", "z");
+    if let Some(dobj) = this.and_then(|this| this.as_display_object()) {
+        let z = args
+            .get(0)
+            .cloned()
+            .unwrap_or(Value::Undefined)
+            .coerce_to_number(activation)?;
+        let transform_3d = Transform3D {
+            z,
+            ..dobj.transform_3d()
+        };
+        dobj.set_transform_3d(activation.context.gc_context, transform_3d);
+    }
+
     Ok(Value::Undefined)
 }
 
+/// Implements `rotationX`'s getter.
 pub fn rotation_x<'gc>(
-    activation: &mut Activation<'_, 'gc>,
-    _this: Option<Object<'gc>>,
+    _activation: &mut Activation<'_, 'gc>,
+    this: Option<Object<'gc>>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    avm2_stub_getter!(activation, "flash.display.DisplayObject", "rotationX");
-    Ok(0.into())
+    if let Some(dobj) = this.and_then(|this| this.as_display_object()) {
+        let degrees: f64 = dobj.transform_3d().rotation_x.into();
+        return Ok(degrees.into());
+    }
+
+    Ok(Value::Undefined)
 }
 
+/// Implements `rotationX`'s setter.
 pub fn set_rotation_x<'gc>(
     activation: &mut Activation<'_, 'gc>,
-    _this: Option<Object<'gc>>,
-    _args: &[Value<'gc>],
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    avm2_stub_setter!(activation, "flash.display.DisplayObject", "rotationX");
+    if let Some(dobj) = this.and_then(|this| this.as_display_object()) {
+        let degrees = args
+            .get(0)
+            .cloned()
+            .unwrap_or(Value::Undefined)
+            .coerce_to_number(activation)?;
+        let transform_3d = Transform3D {
+            rotation_x: Degrees::from(degrees),
+            ..dobj.transform_3d()
+        };
+        dobj.set_transform_3d(activation.context.gc_context, transform_3d);
+    }
+
     Ok(Value::Undefined)
 }
 
+/// Implements `rotationY`'s getter.
 pub fn rotation_y<'gc>(
-    activation: &mut Activation<'_, 'gc>,
-    _this: Option<Object<'gc>>,
+    _activation: &mut Activation<'_, 'gc>,
+    this: Option<Object<'gc>>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    avm2_stub_getter!(activation, "flash.display.DisplayObject", "rotationY");
-    Ok(0.into())
+    if let Some(dobj) = this.and_then(|this| this.as_display_object()) {
+        let degrees: f64 = dobj.transform_3d().rotation_y.into();
+        return Ok(degrees.into());
+    }
+
+    Ok(Value::Undefined)
 }
 
+/// Implements `rotationY`'s setter.
 pub fn set_rotation_y<'gc>(
     activation: &mut Activation<'_, 'gc>,
-    _this: Option<Object<'gc>>,
-    _args: &[Value<'gc>],
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    avm2_stub_setter!(activation, "flash.display.DisplayObject", "rotationY");
+    if let Some(dobj) = this.and_then(|this| this.as_display_object()) {
+        let degrees = args
+            .get(0)
+            .cloned()
+            .unwrap_or(Value::Undefined)
+            .coerce_to_number(activation)?;
+        let transform_3d = Transform3D {
+            rotation_y: Degrees::from(degrees),
+            ..dobj.transform_3d()
+        };
+        dobj.set_transform_3d(activation.context.gc_context, transform_3d);
+    }
+
     Ok(Value::Undefined)
 }
 
+/// Implements `rotationZ`'s getter, which is the same as `rotation`.
 pub fn rotation_z<'gc>(
     activation: &mut Activation<'_, 'gc>,
-    _this: Option<Object<'gc>>,
-    _args: &[Value<'gc>],
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    avm2_stub_getter!(activation, "flash.display.DisplayObject", "rotationZ");
-    Ok(0.into())
+    rotation(activation, this, args)
 }
 
+/// Implements `rotationZ`'s setter, which is the same as `rotation`.
 pub fn set_rotation_z<'gc>(
     activation: &mut Activation<'_, 'gc>,
-    _this: Option<Object<'gc>>,
-    _args: &[Value<'gc>],
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    avm2_stub_setter!(activation, "flash.display.DisplayObject", "rotationZ");
-    Ok(Value::Undefined)
+    set_rotation(activation, this, args)
 }
 
+/// Implements `scaleZ`'s getter.
 pub fn scale_z<'gc>(
-    activation: &mut Activation<'_, 'gc>,
-    _this: Option<Object<'gc>>,
+    _activation: &mut Activation<'_, 'gc>,
+    this: Option<Object<'gc>>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    avm2_stub_getter!(activation, "flash.display.DisplayObject", "scaleZ");
-    Ok(1.into())
+    if let Some(dobj) = this.and_then(|this| this.as_display_object()) {
+        return Ok(dobj.transform_3d().scale_z.into());
+    }
+
+    Ok(Value::Undefined)
 }
 
+/// Implements `scaleZ`'s setter.
 pub fn set_scale_z<'gc>(
     activation: &mut Activation<'_, 'gc>,
-    _this: Option<Object<'gc>>,
-    _args: &[Value<'gc>],
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    avm2_stub_setter!(activation, "flash.display.DisplayObject", "scaleZ");
+    if let Some(dobj) = this.and_then(|this| this.as_display_object()) {
+        let scale_z = args
+            .get(0)
+            .cloned()
+            .unwrap_or(Value::Undefined)
+            .coerce_to_number(activation)?;
+        let transform_3d = Transform3D {
+            scale_z,
+            ..dobj.transform_3d()
+        };
+        dobj.set_transform_3d(activation.context.gc_context, transform_3d);
+    }
+
     Ok(Value::Undefined)
 }
 
@@ -793,9 +875,8 @@ pub fn set_transform<'gc>(
             )?;
 
         let dobj = this.as_display_object().unwrap();
-        let mut write = dobj.base_mut(activation.context.gc_context);
-        write.set_matrix(matrix);
-        write.set_color_transform(color_transform);
+        dobj.set_matrix(activation.context.gc_context, matrix);
+        dobj.set_color_transform(activation.context.gc_context, color_transform);
     }
     Ok(Value::Undefined)
 }
@@ -2,6 +2,7 @@
 
 use crate::avm2::activation::Activation;
 use crate::avm2::class::{Class, ClassAttributes};
+use crate::avm2::globals::flash::geom::transform::object_to_matrix;
 use crate::avm2::method::{Method, NativeMethodImpl};
 use crate::avm2::object::{stage_allocator, Object, TObject};
 use crate::avm2::value::Value;
@@ -11,12 +12,17 @@ use crate::avm2::Namespace;
 use crate::avm2::QName;
 use crate::avm2_stub_method;
 use crate::display_object::TDisplayObject;
-use crate::drawing::Drawing;
+use crate::drawing::{Drawing, TriangleCulling};
 use crate::string::WStr;
 use gc_arena::GcCell;
-use ruffle_render::shape_utils::DrawCommand;
+use ruffle_render::bitmap::BitmapInfo;
+use ruffle_render::matrix::Matrix;
+use ruffle_render::shape_utils::{DrawCommand, FillRule, TriangleList};
 use std::f64::consts::FRAC_1_SQRT_2;
-use swf::{Color, FillStyle, Fixed8, LineCapStyle, LineJoinStyle, LineStyle, Twips};
+use swf::{
+    Color, FillStyle, Fixed8, Gradient, GradientInterpolation, GradientRecord, GradientSpread,
+    LineCapStyle, LineJoinStyle, LineStyle, Twips,
+};
 
 /// Implements `flash.display.Graphics`'s instance constructor.
 fn instance_init<'gc>(
@@ -81,23 +87,190 @@ fn begin_fill<'gc>(
     Ok(Value::Undefined)
 }
 
+/// Reads the elements of an `Array` or `Vector` argument, which may also be `null`.
+fn list_values<'gc>(value: Value<'gc>) -> Vec<Value<'gc>> {
+    let Some(object) = value.as_object() else {
+        return Vec::new();
+    };
+    if let Some(array) = object.as_array_storage() {
+        array
+            .iter()
+            .map(|value| value.unwrap_or(Value::Undefined))
+            .collect()
+    } else if let Some(vector) = object.as_vector_storage() {
+        vector.iter().collect()
+    } else {
+        Vec::new()
+    }
+}
+
+/// Reads the elements of an `Array` or `Vector` argument as numbers.
+fn list_numbers<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    value: Value<'gc>,
+) -> Result<Vec<f64>, Error<'gc>> {
+    list_values(value)
+        .into_iter()
+        .map(|value| value.coerce_to_number(activation))
+        .collect()
+}
+
 /// Implements `Graphics.beginBitmapFill`.
 fn begin_bitmap_fill<'gc>(
     activation: &mut Activation<'_, 'gc>,
-    _this: Option<Object<'gc>>,
-    _args: &[Value<'gc>],
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    avm2_stub_method!(activation, "flash.display.Graphics", "beginBitmapFill");
+    if let Some(this) = this.and_then(|t| t.as_display_object()) {
+        let bitmap_data = args
+            .get(0)
+            .cloned()
+            .unwrap_or(Value::Undefined)
+            .as_object()
+            .and_then(|o| o.as_bitmap_data())
+            .ok_or("TypeError: Error #2007: Parameter bitmap must be non-null.")?;
+        let bitmap = {
+            let mut bitmap_data = bitmap_data.write(activation.context.gc_context);
+            bitmap_data.update_dirty_texture(activation.context.renderer);
+            bitmap_data
+                .bitmap_handle(activation.context.renderer)
+                .map(|handle| BitmapInfo {
+                    handle,
+                    width: bitmap_data.width() as u16,
+                    height: bitmap_data.height() as u16,
+                })
+        };
+        let Some(bitmap) = bitmap else {
+            tracing::warn!("Ignoring Graphics.beginBitmapFill() with an undrawable bitmap");
+            return Ok(Value::Undefined);
+        };
+
+        let mut matrix = match args.get(1).and_then(|matrix| matrix.as_object()) {
+            Some(matrix) => object_to_matrix(matrix, activation)?,
+            None => Matrix::IDENTITY,
+        };
+        // The matrix is in pixels, and the bitmap is positioned in twips.
+        matrix *= Matrix::scale(Twips::TWIPS_PER_PIXEL as f32, Twips::TWIPS_PER_PIXEL as f32);
+        let is_repeating = args
+            .get(2)
+            .cloned()
+            .unwrap_or_else(|| true.into())
+            .coerce_to_boolean();
+        let is_smoothed = args
+            .get(3)
+            .cloned()
+            .unwrap_or_else(|| false.into())
+            .coerce_to_boolean();
+
+        if let Some(mut draw) = this.as_drawing(activation.context.gc_context) {
+            let id = draw.add_bitmap(bitmap);
+            draw.set_fill_style(Some(FillStyle::Bitmap {
+                id,
+                matrix: matrix.into(),
+                is_smoothed,
+                is_repeating,
+            }));
+        }
+    }
+
     Ok(Value::Undefined)
 }
 
 /// Implements `Graphics.beginGradientFill`.
 fn begin_gradient_fill<'gc>(
     activation: &mut Activation<'_, 'gc>,
-    _this: Option<Object<'gc>>,
-    _args: &[Value<'gc>],
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    avm2_stub_method!(activation, "flash.display.Graphics", "beginGradientFill");
+    if let Some(this) = this.and_then(|t| t.as_display_object()) {
+        let gradient_type = args
+            .get(0)
+            .cloned()
+            .unwrap_or(Value::Undefined)
+            .coerce_to_string(activation)?;
+        let colors = list_values(args.get(1).cloned().unwrap_or(Value::Null));
+        let alphas = list_values(args.get(2).cloned().unwrap_or(Value::Null));
+        let ratios = list_values(args.get(3).cloned().unwrap_or(Value::Null));
+        if colors.len() != alphas.len() || colors.len() != ratios.len() {
+            tracing::warn!(
+                "Graphics.beginGradientFill() received different sized arrays for colors, alphas and ratios"
+            );
+            return Ok(Value::Undefined);
+        }
+
+        let mut records = Vec::with_capacity(colors.len());
+        for ((color, alpha), ratio) in colors.into_iter().zip(alphas).zip(ratios) {
+            let color = color.coerce_to_u32(activation)?;
+            let alpha = alpha.coerce_to_number(activation)?.clamp(0.0, 1.0);
+            let ratio = ratio.coerce_to_number(activation)?.clamp(0.0, 255.0) as u8;
+            records.push(GradientRecord {
+                ratio,
+                color: color_from_args(color, alpha),
+            });
+        }
+
+        let matrix = match args.get(4).and_then(|matrix| matrix.as_object()) {
+            Some(matrix) => object_to_matrix(matrix, activation)?,
+            None => Matrix::IDENTITY,
+        };
+        let spread_method = args
+            .get(5)
+            .cloned()
+            .unwrap_or_else(|| "pad".into())
+            .coerce_to_string(activation)?;
+        let spread = if &spread_method == b"reflect" {
+            GradientSpread::Reflect
+        } else if &spread_method == b"repeat" {
+            GradientSpread::Repeat
+        } else {
+            GradientSpread::Pad
+        };
+        let interpolation_method = args
+            .get(6)
+            .cloned()
+            .unwrap_or_else(|| "rgb".into())
+            .coerce_to_string(activation)?;
+        let interpolation = if &interpolation_method == b"linearRGB" {
+            GradientInterpolation::LinearRgb
+        } else {
+            GradientInterpolation::Rgb
+        };
+        let focal_point_ratio = args
+            .get(7)
+            .cloned()
+            .unwrap_or_else(|| 0.0.into())
+            .coerce_to_number(activation)?
+            .clamp(-1.0, 1.0);
+
+        let gradient = Gradient {
+            matrix: matrix.into(),
+            spread,
+            interpolation,
+            records,
+        };
+        let style = if &gradient_type == b"linear" {
+            FillStyle::LinearGradient(gradient)
+        } else if &gradient_type == b"radial" {
+            if focal_point_ratio == 0.0 {
+                FillStyle::RadialGradient(gradient)
+            } else {
+                FillStyle::FocalGradient {
+                    gradient,
+                    focal_point: Fixed8::from_f64(focal_point_ratio),
+                }
+            }
+        } else {
+            return Err(
+                "ArgumentError: Error #2008: Parameter type must be one of the accepted values."
+                    .into(),
+            );
+        };
+
+        if let Some(mut draw) = this.as_drawing(activation.context.gc_context) {
+            draw.set_fill_style(Some(style));
+        }
+    }
+
     Ok(Value::Undefined)
 }
 
@@ -156,6 +329,32 @@ fn curve_to<'gc>(
     Ok(Value::Undefined)
 }
 
+/// Implements `Graphics.cubicCurveTo`.
+fn cubic_curve_to<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(this) = this.and_then(|t| t.as_display_object()) {
+        let mut points = [Twips::ZERO; 6];
+        for (index, point) in points.iter_mut().enumerate() {
+            *point = Twips::from_pixels(
+                args.get(index)
+                    .cloned()
+                    .unwrap_or(Value::Undefined)
+                    .coerce_to_number(activation)?,
+            );
+        }
+        let [x1, y1, x2, y2, x3, y3] = points;
+
+        if let Some(mut draw) = this.as_drawing(activation.context.gc_context) {
+            draw.draw_cubic_curve((x1, y1), (x2, y2), (x3, y3));
+        }
+    }
+
+    Ok(Value::Undefined)
+}
+
 /// Implements `Graphics.endFill`.
 fn end_fill<'gc>(
     activation: &mut Activation<'_, 'gc>,
@@ -347,6 +546,266 @@ fn move_to<'gc>(
     Ok(Value::Undefined)
 }
 
+/// Implements `Graphics.drawPath`.
+fn draw_path<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    // The values of `GraphicsPathCommand`
+    const MOVE_TO: i32 = 1;
+    const LINE_TO: i32 = 2;
+    const CURVE_TO: i32 = 3;
+    const WIDE_MOVE_TO: i32 = 4;
+    const WIDE_LINE_TO: i32 = 5;
+    const CUBIC_CURVE_TO: i32 = 6;
+
+    if let Some(this) = this.and_then(|t| t.as_display_object()) {
+        let commands = list_values(args.get(0).cloned().unwrap_or(Value::Null))
+            .into_iter()
+            .map(|command| command.coerce_to_i32(activation))
+            .collect::<Result<Vec<_>, _>>()?;
+        let data = list_numbers(activation, args.get(1).cloned().unwrap_or(Value::Null))?;
+        let winding = args
+            .get(2)
+            .cloned()
+            .unwrap_or_else(|| "evenOdd".into())
+            .coerce_to_string(activation)?;
+        let winding_rule = if &winding == b"evenOdd" {
+            FillRule::EvenOdd
+        } else if &winding == b"nonZero" {
+            FillRule::NonZero
+        } else {
+            return Err(
+                "ArgumentError: Error #2008: Parameter winding must be one of the accepted values."
+                    .into(),
+            );
+        };
+
+        if let Some(mut draw) = this.as_drawing(activation.context.gc_context) {
+            draw.set_winding_rule(winding_rule);
+
+            let mut data = data.as_slice();
+            for command in commands {
+                // How many numbers the command skips over, and then how many it uses
+                let (skipped, used) = match command {
+                    MOVE_TO | LINE_TO => (0, 2),
+                    CURVE_TO => (0, 4),
+                    WIDE_MOVE_TO | WIDE_LINE_TO => (2, 2),
+                    CUBIC_CURVE_TO => (0, 6),
+                    _ => continue,
+                };
+                if data.len() < skipped + used {
+                    // Drawing stops at the first command that runs out of data
+                    break;
+                }
+                let points: Vec<_> = data[skipped..skipped + used]
+                    .iter()
+                    .map(|&value| Twips::from_pixels(value))
+                    .collect();
+                data = &data[skipped + used..];
+
+                match command {
+                    MOVE_TO | WIDE_MOVE_TO => draw.draw_command(DrawCommand::MoveTo {
+                        x: points[0],
+                        y: points[1],
+                    }),
+                    LINE_TO | WIDE_LINE_TO => draw.draw_command(DrawCommand::LineTo {
+                        x: points[0],
+                        y: points[1],
+                    }),
+                    CURVE_TO => draw.draw_command(DrawCommand::CurveTo {
+                        x1: points[0],
+                        y1: points[1],
+                        x2: points[2],
+                        y2: points[3],
+                    }),
+                    _ => draw.draw_cubic_curve(
+                        (points[0], points[1]),
+                        (points[2], points[3]),
+                        (points[4], points[5]),
+                    ),
+                }
+            }
+        }
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `Graphics.drawTriangles`.
+fn draw_triangles<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(this) = this.and_then(|t| t.as_display_object()) {
+        let vertices: Vec<_> =
+            list_numbers(activation, args.get(0).cloned().unwrap_or(Value::Null))?
+                .chunks_exact(2)
+                .map(|point| (Twips::from_pixels(point[0]), Twips::from_pixels(point[1])))
+                .collect();
+        let indices = match args.get(1).cloned().unwrap_or(Value::Null) {
+            Value::Null | Value::Undefined => (0..vertices.len() as u32).collect(),
+            indices => list_values(indices)
+                .into_iter()
+                .map(|index| Ok(index.coerce_to_i32(activation)? as u32))
+                .collect::<Result<Vec<_>, Error<'gc>>>()?,
+        };
+        let uvt_data = list_numbers(activation, args.get(2).cloned().unwrap_or(Value::Null))?;
+        let uvs = if uvt_data.is_empty() {
+            None
+        } else {
+            // There's either a U and V for each vertex, or a U, V and T.
+            // T is for perspective correct texturing, which isn't supported yet.
+            let stride = if uvt_data.len() >= vertices.len() * 3 {
+                3
+            } else {
+                2
+            };
+            Some(
+                uvt_data
+                    .chunks_exact(stride)
+                    .map(|uvt| (uvt[0] as f32, uvt[1] as f32))
+                    .collect(),
+            )
+        };
+        let culling = args
+            .get(3)
+            .cloned()
+            .unwrap_or_else(|| "none".into())
+            .coerce_to_string(activation)?;
+        let culling = if &culling == b"none" {
+            TriangleCulling::None
+        } else if &culling == b"positive" {
+            TriangleCulling::Positive
+        } else if &culling == b"negative" {
+            TriangleCulling::Negative
+        } else {
+            return Err(
+                "ArgumentError: Error #2008: Parameter culling must be one of the accepted values."
+                    .into(),
+            );
+        };
+
+        if let Some(mut draw) = this.as_drawing(activation.context.gc_context) {
+            draw.draw_triangles(
+                TriangleList {
+                    vertices,
+                    indices,
+                    uvs,
+                },
+                culling,
+            );
+        }
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `Graphics.drawGraphicsData`.
+fn draw_graphics_data<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let display_namespace = Namespace::package("flash.display", activation.context.gc_context);
+    let bitmap_fill =
+        activation.resolve_class(&Multiname::new(display_namespace, "GraphicsBitmapFill"))?;
+    let end_fill_class =
+        activation.resolve_class(&Multiname::new(display_namespace, "GraphicsEndFill"))?;
+    let gradient_fill =
+        activation.resolve_class(&Multiname::new(display_namespace, "GraphicsGradientFill"))?;
+    let path = activation.resolve_class(&Multiname::new(display_namespace, "GraphicsPath"))?;
+    let solid_fill =
+        activation.resolve_class(&Multiname::new(display_namespace, "GraphicsSolidFill"))?;
+    let stroke = activation.resolve_class(&Multiname::new(display_namespace, "GraphicsStroke"))?;
+    let triangle_path =
+        activation.resolve_class(&Multiname::new(display_namespace, "GraphicsTrianglePath"))?;
+
+    for data in list_values(args.get(0).cloned().unwrap_or(Value::Null)) {
+        let Some(data) = data.as_object() else {
+            continue;
+        };
+        // Each kind of graphics data is drawn by the method that takes the same parameters
+        let (properties, method): (&[&str], NativeMethodImpl) =
+            if data.is_of_type(solid_fill, activation) {
+                (&["color", "alpha"], begin_fill)
+            } else if data.is_of_type(bitmap_fill, activation) {
+                (
+                    &["bitmapData", "matrix", "repeat", "smooth"],
+                    begin_bitmap_fill,
+                )
+            } else if data.is_of_type(gradient_fill, activation) {
+                (
+                    &[
+                        "type",
+                        "colors",
+                        "alphas",
+                        "ratios",
+                        "matrix",
+                        "spreadMethod",
+                        "interpolationMethod",
+                        "focalPointRatio",
+                    ],
+                    begin_gradient_fill,
+                )
+            } else if data.is_of_type(end_fill_class, activation) {
+                (&[], end_fill)
+            } else if data.is_of_type(path, activation) {
+                (&["commands", "data", "winding"], draw_path)
+            } else if data.is_of_type(triangle_path, activation) {
+                (
+                    &["vertices", "indices", "uvtData", "culling"],
+                    draw_triangles,
+                )
+            } else if data.is_of_type(stroke, activation) {
+                let mut args = vec![data.get_public_property("thickness", activation)?];
+                match data.get_public_property("fill", activation)?.as_object() {
+                    Some(fill) if fill.is_of_type(solid_fill, activation) => {
+                        args.push(fill.get_public_property("color", activation)?);
+                        args.push(fill.get_public_property("alpha", activation)?);
+                    }
+                    Some(_) => {
+                        avm2_stub_method!(
+                            activation,
+                            "flash.display.Graphics",
+                            "drawGraphicsData",
+                            "with a bitmap or gradient GraphicsStroke fill"
+                        );
+                        args.push(0.into());
+                        args.push(1.0.into());
+                    }
+                    None => {
+                        args.push(0.into());
+                        args.push(1.0.into());
+                    }
+                }
+                for name in ["pixelHinting", "scaleMode", "caps", "joints", "miterLimit"] {
+                    args.push(data.get_public_property(name, activation)?);
+                }
+                line_style(activation, this, &args)?;
+                continue;
+            } else {
+                avm2_stub_method!(
+                    activation,
+                    "flash.display.Graphics",
+                    "drawGraphicsData",
+                    "with shader fills"
+                );
+                continue;
+            };
+
+        let args = properties
+            .iter()
+            .map(|&name| data.get_public_property(name, activation))
+            .collect::<Result<Vec<_>, _>>()?;
+        method(activation, this, &args)?;
+    }
+
+    Ok(Value::Undefined)
+}
+
 /// Implements `Graphics.drawRect`.
 fn draw_rect<'gc>(
     activation: &mut Activation<'_, 'gc>,
@@ -814,10 +1273,14 @@ pub fn create_class<'gc>(activation: &mut Activation<'_, 'gc>) -> GcCell<'gc, Cl
         ("beginGradientFill", begin_gradient_fill),
         ("clear", clear),
         ("curveTo", curve_to),
+        ("cubicCurveTo", cubic_curve_to),
         ("endFill", end_fill),
         ("lineStyle", line_style),
         ("lineTo", line_to),
         ("moveTo", move_to),
+        ("drawPath", draw_path),
+        ("drawTriangles", draw_triangles),
+        ("drawGraphicsData", draw_graphics_data),
         ("drawRect", draw_rect),
         ("drawRoundRect", draw_round_rect),
         ("drawCircle", draw_circle),
@@ -224,6 +224,66 @@ pub fn set_focus_rect<'gc>(
     Ok(Value::Null)
 }
 
+/// Implements `InteractiveObject.needsSoftKeyboard`'s getter.
+pub fn needs_soft_keyboard<'gc>(
+    _activation: &mut Activation<'_, 'gc>,
+    this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(int) = this
+        .and_then(|t| t.as_display_object())
+        .and_then(|dobj| dobj.as_interactive())
+    {
+        return Ok(int.needs_soft_keyboard().into());
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `InteractiveObject.needsSoftKeyboard`'s setter.
+pub fn set_needs_soft_keyboard<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(int) = this
+        .and_then(|t| t.as_display_object())
+        .and_then(|dobj| dobj.as_interactive())
+    {
+        let value = args
+            .get(0)
+            .cloned()
+            .unwrap_or(Value::Undefined)
+            .coerce_to_boolean();
+        int.set_needs_soft_keyboard(activation.context.gc_context, value);
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `InteractiveObject.requestSoftKeyboard`.
+///
+/// This focuses the object, and shows the on-screen keyboard if it needs one.
+pub fn request_soft_keyboard<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(int) = this
+        .and_then(|t| t.as_display_object())
+        .and_then(|dobj| dobj.as_interactive())
+    {
+        if !int.needs_soft_keyboard() {
+            return Ok(false.into());
+        }
+        let tracker = activation.context.focus_tracker;
+        tracker.set(Some(int.as_displayobject()), &mut activation.context);
+        return Ok(activation.context.ui.request_soft_keyboard().into());
+    }
+
+    Ok(false.into())
+}
+
 /// Construct `InteractiveObject`'s class.
 pub fn create_class<'gc>(activation: &mut Activation<'_, 'gc>) -> GcCell<'gc, Class<'gc>> {
     let mc = activation.context.gc_context;
@@ -265,6 +325,11 @@ pub fn create_class<'gc>(activation: &mut Activation<'_, 'gc>) -> GcCell<'gc, Cl
         ("tabEnabled", Some(tab_enabled), Some(set_tab_enabled)),
         ("tabIndex", Some(tab_index), Some(set_tab_index)),
         ("focusRect", Some(focus_rect), Some(set_focus_rect)),
+        (
+            "needsSoftKeyboard",
+            Some(needs_soft_keyboard),
+            Some(set_needs_soft_keyboard),
+        ),
     ];
     write.define_builtin_instance_properties(
         mc,
@@ -272,5 +337,13 @@ pub fn create_class<'gc>(activation: &mut Activation<'_, 'gc>) -> GcCell<'gc, Cl
         PUBLIC_INSTANCE_PROPERTIES,
     );
 
+    const PUBLIC_INSTANCE_METHODS: &[(&str, NativeMethodImpl)] =
+        &[("requestSoftKeyboard", request_soft_keyboard)];
+    write.define_builtin_instance_methods(
+        mc,
+        activation.avm2().public_namespace,
+        PUBLIC_INSTANCE_METHODS,
+    );
+
     class
 }
@@ -1,10 +1,13 @@
 #![allow(non_snake_case)]
 
+use crate::avm2::object::VectorObject;
+use crate::avm2::vector::VectorStorage;
 use crate::avm2::Multiname;
 use crate::avm2::{Activation, Error, Object, TObject, Value};
 use crate::avm2_stub_getter;
 use crate::display_object::TDisplayObject;
 use crate::prelude::{ColorTransform, DisplayObject, Matrix, Twips};
+use ruffle_render::matrix3d::Matrix3D;
 use ruffle_render::quality::StageQuality;
 use swf::Fixed8;
 
@@ -55,9 +58,7 @@ pub fn set_color_transform<'gc>(
 ) -> Result<Value<'gc>, Error<'gc>> {
     let this = this.unwrap();
     let ct = object_to_color_transform(args[0].coerce_to_object(activation)?, activation)?;
-    get_display_object(this, activation)?
-        .base_mut(activation.context.gc_context)
-        .set_color_transform(ct);
+    get_display_object(this, activation)?.set_color_transform(activation.context.gc_context, ct);
     Ok(Value::Undefined)
 }
 
@@ -78,9 +79,36 @@ pub fn set_matrix<'gc>(
 ) -> Result<Value<'gc>, Error<'gc>> {
     let this = this.unwrap();
     let matrix = object_to_matrix(args[0].coerce_to_object(activation)?, activation)?;
-    get_display_object(this, activation)?
-        .base_mut(activation.context.gc_context)
-        .set_matrix(matrix);
+    get_display_object(this, activation)?.set_matrix(activation.context.gc_context, matrix);
+    Ok(Value::Undefined)
+}
+
+pub fn get_matrix_3d<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let this = this.unwrap();
+    match get_display_object(this, activation)?.matrix3d() {
+        Some(matrix) => matrix3d_to_object(matrix, activation),
+        None => Ok(Value::Null),
+    }
+}
+
+pub fn set_matrix_3d<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let this = this.unwrap();
+    let matrix = match args[0] {
+        Value::Null | Value::Undefined => None,
+        value => Some(object_to_matrix3d(
+            value.coerce_to_object(activation)?,
+            activation,
+        )?),
+    };
+    get_display_object(this, activation)?.set_matrix3d(activation.context.gc_context, matrix);
     Ok(Value::Undefined)
 }
 
@@ -177,6 +205,7 @@ pub fn object_to_color_transform<'gc>(
         g_add: green_offset as i16,
         b_add: blue_offset as i16,
         a_add: alpha_offset as i16,
+        luminance_mix: Fixed8::ZERO,
     })
 }
 
@@ -248,3 +277,42 @@ pub fn object_to_matrix<'gc>(
 
     Ok(Matrix { a, b, c, d, tx, ty })
 }
+
+pub fn matrix3d_to_object<'gc>(
+    matrix: Matrix3D,
+    activation: &mut Activation<'_, 'gc>,
+) -> Result<Value<'gc>, Error<'gc>> {
+    let number = activation.avm2().classes().number;
+    let raw_data = VectorStorage::from_values(
+        matrix.raw_data.iter().map(|&value| value.into()).collect(),
+        false,
+        number,
+    );
+    let raw_data = VectorObject::from_vector(raw_data, activation)?;
+    let object = activation
+        .avm2()
+        .classes()
+        .matrix3d
+        .construct(activation, &[raw_data.into()])?;
+    Ok(object.into())
+}
+
+pub fn object_to_matrix3d<'gc>(
+    object: Object<'gc>,
+    activation: &mut Activation<'_, 'gc>,
+) -> Result<Matrix3D, Error<'gc>> {
+    let raw_data = object
+        .get_public_property("rawData", activation)?
+        .coerce_to_object(activation)?;
+    let raw_data = raw_data
+        .as_vector_storage()
+        .ok_or("Matrix3D.rawData must be a Vector")?
+        .clone();
+    let mut matrix = Matrix3D::IDENTITY;
+    for (index, value) in matrix.raw_data.iter_mut().enumerate() {
+        *value = raw_data
+            .get(index, activation)?
+            .coerce_to_number(activation)?;
+    }
+    Ok(matrix)
+}
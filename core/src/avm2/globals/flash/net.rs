@@ -1,10 +1,13 @@
 //! `flash.net` namespace
 
+use crate::avm2::error::{reference_error, type_error};
 use crate::avm2::object::TObject;
 use crate::avm2::{Activation, Error, Object, Value};
 
+pub mod local_connection;
 pub mod object_encoding;
 pub mod shared_object;
+pub mod socket;
 pub mod url_loader;
 
 /// Implements `flash.net.navigateToURL`
@@ -34,3 +37,66 @@ pub fn navigate_to_url<'gc>(
 
     Ok(Value::Undefined)
 }
+
+/// Implements `flash.net.registerClassAlias`
+pub fn register_class_alias<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    _this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let alias = match args.get(0).unwrap_or(&Value::Null) {
+        Value::Undefined | Value::Null => {
+            return Err(Error::AvmError(type_error(
+                activation,
+                "Error #2007: Parameter aliasName must be non-null.",
+                2007,
+            )?));
+        }
+        alias => alias.coerce_to_string(activation)?,
+    };
+    let Some(class) = args
+        .get(1)
+        .and_then(|class| class.as_object())
+        .and_then(|class| class.as_class_object())
+    else {
+        return Err(Error::AvmError(type_error(
+            activation,
+            "Error #2007: Parameter classObject must be non-null.",
+            2007,
+        )?));
+    };
+
+    activation.context.avm2.register_class_alias(alias, class);
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `flash.net.getClassByAlias`
+pub fn get_class_by_alias<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    _this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let alias = match args.get(0).unwrap_or(&Value::Null) {
+        Value::Undefined | Value::Null => {
+            return Err(Error::AvmError(type_error(
+                activation,
+                "Error #2007: Parameter aliasName must be non-null.",
+                2007,
+            )?));
+        }
+        alias => alias.coerce_to_string(activation)?,
+    };
+
+    match activation.avm2().get_class_by_alias(alias) {
+        Some(class) => Ok(class.into()),
+        None => Err(Error::AvmError(reference_error(
+            activation,
+            &format!(
+                "Error #1014: Class {} could not be found.",
+                alias.to_utf8_lossy()
+            ),
+            1014,
+        )?)),
+    }
+}
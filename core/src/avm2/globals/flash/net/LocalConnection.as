@@ -1,42 +1,33 @@
 package flash.net {
     import flash.events.EventDispatcher;
-    import __ruffle__.stub_method;
-    import __ruffle__.stub_getter;
 
-    // NOTE: this entire class is a stub.
-    // Thankfully (hopefully) a lot of code like Mochicrypt doesn't actually require this to... well do anything.
     public class LocalConnection extends EventDispatcher {
 
         public var client: Object;
 
+        // Domains that may send messages to this connection, from `allowDomain`.
+        internal var _allowedDomains: Array = [];
+
         public function LocalConnection() {
             this.client = this;
         }
 
-        public function get domain():String {
-            // FIXME - implement this - this is unrelated to the messaging functionality.
-            stub_getter("flash.net.LocalConnection", "domain");
-            return "localhost";
-        }
+        public native function get domain():String;
 
-        public function close(): void {
-            stub_method("flash.net.LocalConnection", "close");
-        }
+        public native function close(): void;
 
-        public function connect(connectionName:String): void {
-            stub_method("flash.net.LocalConnection", "connect");
-        }
+        public native function connect(connectionName:String): void;
 
-        public function send(connectionName: String, methodName: String, ... arguments): void {
-            stub_method("flash.net.LocalConnection", "send");
-        }
+        public native function send(connectionName: String, methodName: String, ... arguments): void;
 
         public function allowDomain(... domains): void {
-            stub_method("flash.net.LocalConnection", "allowDomain");
+            for each (var domain in domains) {
+                this._allowedDomains.push(String(domain));
+            }
         }
 
         public function allowInsecureDomain(... domains): void {
-            stub_method("flash.net.LocalConnection", "allowInsecureDomain");
+            this.allowDomain.apply(this, domains);
         }
     }
-}
\ No newline at end of file
+}
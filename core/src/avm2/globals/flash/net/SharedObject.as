@@ -26,8 +26,8 @@ package flash.net
       // note: this is supposed to be a read-only property
       public var data: Object;
       
-      // note: this is supposed to be a read-only property
-      public var size: uint;
+      // The size of `data` once it's serialized, in bytes.
+      native public function get size() : uint;
 
       ruffle var _ruffleName: String;
    }
@@ -0,0 +1,214 @@
+package flash.net {
+	import flash.errors.IOError;
+	import flash.events.EventDispatcher;
+	import flash.utils.ByteArray;
+	import flash.utils.IDataInput;
+	import flash.utils.IDataOutput;
+
+	public class Socket extends EventDispatcher implements IDataInput, IDataOutput {
+		// Bytes received from the server. Read bytes are discarded as new ones arrive.
+		internal var _input: ByteArray = new ByteArray();
+		// Bytes written by the movie, which are only sent on `flush`.
+		internal var _output: ByteArray = new ByteArray();
+		internal var _connected: Boolean = false;
+		internal var _timeout: uint = 20000;
+
+		public function Socket(host:String = null, port:int = 0) {
+			if (host != null) {
+				this.connect(host, port);
+			}
+		}
+
+		public native function connect(host:String, port:int):void;
+		private native function closeSocket():void;
+		private native function sendBytes(bytes:ByteArray):void;
+
+		public function close():void {
+			this.checkConnected();
+			this.closeSocket();
+			this._connected = false;
+		}
+
+		public function flush():void {
+			this.checkConnected();
+			if (this._output.length > 0) {
+				this.sendBytes(this._output);
+				this._output.clear();
+			}
+		}
+
+		private function checkConnected():void {
+			if (!this._connected) {
+				throw new IOError("Error #2002: Operation attempted on invalid socket.", 2002);
+			}
+		}
+
+		public function get bytesAvailable():uint {
+			return this._input.bytesAvailable;
+		}
+
+		public function get bytesPending():uint {
+			return this._output.length;
+		}
+
+		public function get connected():Boolean {
+			return this._connected;
+		}
+
+		public function get endian():String {
+			return this._input.endian;
+		}
+
+		public function set endian(value:String):void {
+			this._input.endian = value;
+			this._output.endian = value;
+		}
+
+		public function get objectEncoding():uint {
+			return this._input.objectEncoding;
+		}
+
+		public function set objectEncoding(value:uint):void {
+			this._input.objectEncoding = value;
+			this._output.objectEncoding = value;
+		}
+
+		public function get timeout():uint {
+			return this._timeout;
+		}
+
+		public function set timeout(value:uint):void {
+			this._timeout = value;
+		}
+
+		public function readBoolean():Boolean {
+			this.checkConnected();
+			return this._input.readBoolean();
+		}
+
+		public function readByte():int {
+			this.checkConnected();
+			return this._input.readByte();
+		}
+
+		public function readBytes(bytes:ByteArray, offset:uint = 0, length:uint = 0):void {
+			this.checkConnected();
+			this._input.readBytes(bytes, offset, length);
+		}
+
+		public function readDouble():Number {
+			this.checkConnected();
+			return this._input.readDouble();
+		}
+
+		public function readFloat():Number {
+			this.checkConnected();
+			return this._input.readFloat();
+		}
+
+		public function readInt():int {
+			this.checkConnected();
+			return this._input.readInt();
+		}
+
+		public function readMultiByte(length:uint, charSet:String):String {
+			this.checkConnected();
+			return this._input.readMultiByte(length, charSet);
+		}
+
+		public function readObject():* {
+			this.checkConnected();
+			return this._input.readObject();
+		}
+
+		public function readShort():int {
+			this.checkConnected();
+			return this._input.readShort();
+		}
+
+		public function readUnsignedByte():uint {
+			this.checkConnected();
+			return this._input.readUnsignedByte();
+		}
+
+		public function readUnsignedInt():uint {
+			this.checkConnected();
+			return this._input.readUnsignedInt();
+		}
+
+		public function readUnsignedShort():uint {
+			this.checkConnected();
+			return this._input.readUnsignedShort();
+		}
+
+		public function readUTF():String {
+			this.checkConnected();
+			return this._input.readUTF();
+		}
+
+		public function readUTFBytes(length:uint):String {
+			this.checkConnected();
+			return this._input.readUTFBytes(length);
+		}
+
+		public function writeBoolean(value:Boolean):void {
+			this.checkConnected();
+			this._output.writeBoolean(value);
+		}
+
+		public function writeByte(value:int):void {
+			this.checkConnected();
+			this._output.writeByte(value);
+		}
+
+		public function writeBytes(bytes:ByteArray, offset:uint = 0, length:uint = 0):void {
+			this.checkConnected();
+			this._output.writeBytes(bytes, offset, length);
+		}
+
+		public function writeDouble(value:Number):void {
+			this.checkConnected();
+			this._output.writeDouble(value);
+		}
+
+		public function writeFloat(value:Number):void {
+			this.checkConnected();
+			this._output.writeFloat(value);
+		}
+
+		public function writeInt(value:int):void {
+			this.checkConnected();
+			this._output.writeInt(value);
+		}
+
+		public function writeMultiByte(value:String, charSet:String):void {
+			this.checkConnected();
+			this._output.writeMultiByte(value, charSet);
+		}
+
+		public function writeObject(object:*):void {
+			this.checkConnected();
+			this._output.writeObject(object);
+		}
+
+		public function writeShort(value:int):void {
+			this.checkConnected();
+			this._output.writeShort(value);
+		}
+
+		public function writeUnsignedInt(value:uint):void {
+			this.checkConnected();
+			this._output.writeUnsignedInt(value);
+		}
+
+		public function writeUTF(value:String):void {
+			this.checkConnected();
+			this._output.writeUTF(value);
+		}
+
+		public function writeUTFBytes(value:String):void {
+			this.checkConnected();
+			this._output.writeUTFBytes(value);
+		}
+	}
+}
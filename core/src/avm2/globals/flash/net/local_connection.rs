@@ -0,0 +1,134 @@
+//! `flash.net.LocalConnection` native function definitions
+
+use crate::amf::AmfValue;
+use crate::avm2::activation::Activation;
+use crate::avm2::amf::{serialize_value, ObjectTable};
+use crate::avm2::error::argument_error;
+use crate::avm2::value::Value;
+use crate::avm2::{Error, Object};
+use crate::local_connection::{LocalConnectionObject, LocalConnections};
+use crate::string::AvmString;
+
+/// The domain of the movie that's running this code.
+fn movie_domain(activation: &Activation<'_, '_>) -> String {
+    LocalConnections::domain(activation.context.swf.url())
+}
+
+/// Coerces a connection or method name, which must not be empty.
+fn non_empty_string<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    value: Option<&Value<'gc>>,
+    parameter: &str,
+) -> Result<String, Error<'gc>> {
+    let string = match value.unwrap_or(&Value::Null) {
+        Value::Undefined | Value::Null => String::new(),
+        value => value.coerce_to_string(activation)?.to_string(),
+    };
+    if string.is_empty() {
+        return Err(Error::AvmError(argument_error(
+            activation,
+            &format!("Error #2085: Parameter {parameter} must be non-empty string."),
+            2085,
+        )?));
+    }
+    Ok(string)
+}
+
+/// Implements `LocalConnection.domain`
+pub fn get_domain<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    _this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let domain = movie_domain(activation);
+    Ok(AvmString::new_utf8(activation.context.gc_context, domain).into())
+}
+
+/// Implements `LocalConnection.connect`
+pub fn connect<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let Some(this) = this else {
+        return Ok(Value::Undefined);
+    };
+
+    let name = non_empty_string(activation, args.get(0), "connectionName")?;
+    let domain = movie_domain(activation);
+    let full_name = LocalConnections::full_name(&domain, &name);
+    let connected = activation.context.local_connections.connect(
+        LocalConnectionObject::Avm2(this),
+        full_name,
+        domain,
+    );
+    if !connected {
+        return Err(Error::AvmError(argument_error(
+            activation,
+            "Error #2082: Connect failed because the object is already connected.",
+            2082,
+        )?));
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `LocalConnection.close`
+pub fn close<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let Some(this) = this else {
+        return Ok(Value::Undefined);
+    };
+
+    let closed = activation
+        .context
+        .local_connections
+        .close(LocalConnectionObject::Avm2(this));
+    if !closed {
+        return Err(Error::AvmError(argument_error(
+            activation,
+            "Error #2083: Close failed because the object is not connected.",
+            2083,
+        )?));
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `LocalConnection.send`
+pub fn send<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let Some(this) = this else {
+        return Ok(Value::Undefined);
+    };
+
+    let name = non_empty_string(activation, args.get(0), "connectionName")?;
+    let method_name = non_empty_string(activation, args.get(1), "methodName")?;
+    // The arguments are written together, so they may refer to each other's objects
+    let mut objects = ObjectTable::new();
+    let arguments = args
+        .iter()
+        .skip(2)
+        .map(|argument| {
+            serialize_value(activation, *argument, &mut objects).unwrap_or(AmfValue::Undefined)
+        })
+        .collect();
+
+    let domain = movie_domain(activation);
+    let full_name = LocalConnections::full_name(&domain, &name);
+    activation.context.local_connections.send(
+        LocalConnectionObject::Avm2(this),
+        domain,
+        full_name,
+        method_name,
+        arguments,
+    );
+
+    Ok(Value::Undefined)
+}
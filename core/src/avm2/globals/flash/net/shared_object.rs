@@ -1,15 +1,21 @@
 //! `flash.net.SharedObject` builtin/prototype
 
+use crate::amf::{AmfVersion, Lso};
+use crate::avm2::amf::ObjectTable;
+use crate::avm2::error::error;
 use crate::avm2::object::TObject;
 use crate::avm2::Multiname;
 use crate::avm2::{Activation, Error, Namespace, Object, Value};
 use crate::avm2_stub_method;
+use crate::context::ActionType;
 use crate::display_object::DisplayObject;
 use crate::display_object::TDisplayObject;
 use crate::string::AvmString;
-use flash_lso::types::{AMFVersion, Lso};
 use std::borrow::Cow;
 
+/// Flash asks the user before letting a shared object grow larger than this.
+const DEFAULT_SIZE_LIMIT: usize = 100 * 1024;
+
 pub fn get_local<'gc>(
     activation: &mut Activation<'_, 'gc>,
     this: Option<Object<'gc>>,
@@ -155,7 +161,7 @@ pub fn get_local<'gc>(
 
     // Load the data object from storage if it existed prior
     if let Some(saved) = activation.context.storage.get(&full_name) {
-        if let Ok(lso) = flash_lso::read::Reader::default().parse(&saved) {
+        if let Ok(lso) = crate::amf::read_lso(&saved) {
             data = crate::avm2::amf::deserialize_lso(activation, &lso)?.into();
         }
     }
@@ -182,40 +188,118 @@ pub fn get_local<'gc>(
 pub fn flush<'gc>(
     activation: &mut Activation<'_, 'gc>,
     this: Option<Object<'gc>>,
-    _args: &[Value<'gc>],
+    args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     if let Some(this) = this {
-        let data = this
-            .get_public_property("data", activation)?
-            .coerce_to_object(activation)?;
-
-        let ruffle_name = Multiname::new(
-            Namespace::package("__ruffle__", activation.context.gc_context),
-            "_ruffleName",
-        );
-        let name = this
-            .get_property(&ruffle_name, activation)?
-            .coerce_to_string(activation)?;
-        let name = name.to_utf8_lossy();
-
-        let mut elements = Vec::new();
-        crate::avm2::amf::recursive_serialize(activation, data, &mut elements, AMFVersion::AMF3)?;
-        let mut lso = Lso::new(
-            elements,
-            name.split('/')
-                .last()
-                .map(|e| e.to_string())
-                .unwrap_or_else(|| "<unknown>".to_string()),
-            AMFVersion::AMF3,
-        );
+        let (name, bytes) = serialize_data(activation, this)?;
+        let min_disk_space = args
+            .get(0)
+            .unwrap_or(&Value::Integer(0))
+            .coerce_to_i32(activation)?;
+        let success = activation.context.storage.put(&name, &bytes);
 
-        let bytes = flash_lso::write::write_to_bytes(&mut lso).unwrap_or_default();
+        // Flash asks the user before storing more than the default limit,
+        // and dispatches a `netStatus` event with their answer. There's no one to ask here.
+        if bytes.len().max(min_disk_space.max(0) as usize) > DEFAULT_SIZE_LIMIT {
+            queue_flush_status(activation, this, success)?;
+            return Ok("pending".into());
+        }
+        if !success {
+            return Err(Error::AvmError(error(
+                activation,
+                "Error #2130: Unable to flush SharedObject.",
+                2130,
+            )?));
+        }
+        return Ok("flushed".into());
+    }
+    Ok(Value::Undefined)
+}
 
-        return Ok(activation.context.storage.put(&name, &bytes).into());
+pub fn get_size<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(this) = this {
+        let (_, bytes) = serialize_data(activation, this)?;
+        return Ok(bytes.len().into());
     }
     Ok(Value::Undefined)
 }
 
+/// Serializes the `data` of a shared object, returning its name along with the `.sol` file.
+fn serialize_data<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+) -> Result<(String, Vec<u8>), Error<'gc>> {
+    let data = this
+        .get_public_property("data", activation)?
+        .coerce_to_object(activation)?;
+
+    let ruffle_name = Multiname::new(
+        Namespace::package("__ruffle__", activation.context.gc_context),
+        "_ruffleName",
+    );
+    let name = this
+        .get_property(&ruffle_name, activation)?
+        .coerce_to_string(activation)?;
+    let name = name.to_utf8_lossy().into_owned();
+
+    let mut body = Vec::new();
+    crate::avm2::amf::recursive_serialize(activation, data, &mut body, &mut ObjectTable::new())?;
+    let lso = Lso {
+        name: name
+            .split('/')
+            .last()
+            .map(|e| e.to_string())
+            .unwrap_or_else(|| "<unknown>".to_string()),
+        version: AmfVersion::Amf3,
+        body,
+    };
+
+    let bytes = crate::amf::write_lso(&lso);
+    Ok((name, bytes))
+}
+
+/// Queues the `netStatus` event that tells the movie whether a pending flush succeeded.
+fn queue_flush_status<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    success: bool,
+) -> Result<(), Error<'gc>> {
+    let (code, level) = if success {
+        ("SharedObject.Flush.Success", "status")
+    } else {
+        ("SharedObject.Flush.Failed", "error")
+    };
+    let mut info = activation
+        .avm2()
+        .classes()
+        .object
+        .construct(activation, &[])?;
+    info.set_public_property("code", code.into(), activation)?;
+    info.set_public_property("level", level.into(), activation)?;
+
+    let event = activation.avm2().classes().netstatusevent.construct(
+        activation,
+        &["netStatus".into(), false.into(), false.into(), info.into()],
+    )?;
+    let dispatch_event = this
+        .get_public_property("dispatchEvent", activation)?
+        .coerce_to_object(activation)?;
+    activation.context.action_queue.queue_action(
+        activation.context.stage.root_clip(),
+        ActionType::Callable2 {
+            callable: dispatch_event,
+            reciever: Some(this),
+            args: vec![event.into()],
+        },
+        false,
+    );
+    Ok(())
+}
+
 pub fn close<'gc>(
     activation: &mut Activation<'_, 'gc>,
     _this: Option<Object<'gc>>,
@@ -0,0 +1,108 @@
+//! `flash.net.Socket` native function definitions
+
+use crate::avm2::activation::Activation;
+use crate::avm2::error::security_error;
+use crate::avm2::object::TObject;
+use crate::avm2::value::Value;
+use crate::avm2::Multiname;
+use crate::avm2::{Error, Object};
+use crate::socket::Sockets;
+use std::time::Duration;
+
+/// Implements `Socket.connect`
+pub fn connect<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let Some(mut this) = this else {
+        return Ok(Value::Undefined);
+    };
+
+    // A missing host means the host that the movie was loaded from.
+    let host = match args.get(0).unwrap_or(&Value::Null) {
+        Value::Undefined | Value::Null => Sockets::default_host(&activation.context),
+        host => host.coerce_to_string(activation)?.to_string(),
+    };
+    let port = args
+        .get(1)
+        .unwrap_or(&Value::Undefined)
+        .coerce_to_i32(activation)?;
+    let port = match u16::try_from(port) {
+        Ok(port) if port != 0 => port,
+        _ => {
+            return Err(Error::AvmError(security_error(
+                activation,
+                "Error #2003: Invalid socket port number specified.",
+                2003,
+            )?));
+        }
+    };
+
+    // Connecting again drops the old connection, without a `close` event.
+    if let Some(handle) = activation.context.sockets.avm2_handle(this) {
+        activation.context.sockets.close(handle);
+    }
+    this.set_property(
+        &Multiname::new(activation.avm2().flash_net_internal, "_connected"),
+        false.into(),
+        activation,
+    )?;
+
+    let timeout = this
+        .get_property(
+            &Multiname::new(activation.avm2().flash_net_internal, "_timeout"),
+            activation,
+        )?
+        .coerce_to_u32(activation)?;
+    activation.context.sockets.connect_avm2(
+        activation.context.navigator,
+        this,
+        host,
+        port,
+        Duration::from_millis(timeout.into()),
+    );
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `Socket.closeSocket`
+pub fn close_socket<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(this) = this {
+        if let Some(handle) = activation.context.sockets.avm2_handle(this) {
+            activation.context.sockets.close(handle);
+        }
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `Socket.sendBytes`
+pub fn send_bytes<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let Some(this) = this else {
+        return Ok(Value::Undefined);
+    };
+    let Some(handle) = activation.context.sockets.avm2_handle(this) else {
+        return Ok(Value::Undefined);
+    };
+
+    let bytes = args
+        .get(0)
+        .unwrap_or(&Value::Undefined)
+        .coerce_to_object(activation)?;
+    let data = bytes
+        .as_bytearray()
+        .map(|bytearray| bytearray.bytes().to_vec())
+        .unwrap_or_default();
+    activation.context.sockets.send(handle, data);
+
+    Ok(Value::Undefined)
+}
@@ -1123,6 +1123,70 @@ pub fn get_line_metrics<'gc>(
     Ok(Value::Undefined)
 }
 
+/// Implements `TextField.getCharBoundaries`, where an image is a single character.
+pub fn get_char_boundaries<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(this) = this
+        .and_then(|this| this.as_display_object())
+        .and_then(|this| this.as_edit_text())
+    {
+        let index = args
+            .get(0)
+            .cloned()
+            .unwrap_or(Value::Undefined)
+            .coerce_to_i32(activation)?;
+        let bounds = usize::try_from(index)
+            .ok()
+            .and_then(|index| this.char_boundaries(index));
+
+        if let Some(bounds) = bounds {
+            let rectangle_class = activation.avm2().classes().rectangle;
+            return Ok(rectangle_class
+                .construct(
+                    activation,
+                    &[
+                        bounds.offset_x().to_pixels().into(),
+                        bounds.offset_y().to_pixels().into(),
+                        bounds.width().to_pixels().into(),
+                        bounds.height().to_pixels().into(),
+                    ],
+                )?
+                .into());
+        }
+
+        return Ok(Value::Null);
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `TextField.getImageReference`, which gives the clip of an `<img>` by its `id`.
+pub fn get_image_reference<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(this) = this
+        .and_then(|this| this.as_display_object())
+        .and_then(|this| this.as_edit_text())
+    {
+        let id = args
+            .get(0)
+            .cloned()
+            .unwrap_or(Value::Undefined)
+            .coerce_to_string(activation)?;
+
+        return Ok(this
+            .image_by_id(&id, true)
+            .map_or(Value::Null, |clip| clip.object2()));
+    }
+
+    Ok(Value::Undefined)
+}
+
 pub fn bottom_scroll_v<'gc>(
     _activation: &mut Activation<'_, 'gc>,
     this: Option<Object<'gc>>,
@@ -1379,6 +1443,8 @@ pub fn create_class<'gc>(activation: &mut Activation<'_, 'gc>) -> GcCell<'gc, Cl
         ("setSelection", set_selection),
         ("setTextFormat", set_text_format),
         ("getLineMetrics", get_line_metrics),
+        ("getCharBoundaries", get_char_boundaries),
+        ("getImageReference", get_image_reference),
     ];
     write.define_builtin_instance_methods(
         mc,
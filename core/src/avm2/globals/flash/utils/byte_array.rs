@@ -1,4 +1,6 @@
+use crate::amf::AmfVersion;
 use crate::avm2::activation::Activation;
+use crate::avm2::amf::ObjectTable;
 use crate::avm2::bytearray::{Endian, ObjectEncoding};
 pub use crate::avm2::object::byte_array_allocator;
 use crate::avm2::object::{Object, TObject};
@@ -8,9 +10,7 @@ use crate::character::Character;
 use crate::string::AvmString;
 use encoding_rs::Encoding;
 use encoding_rs::UTF_8;
-use flash_lso::amf0::read::AMF0Decoder;
-use flash_lso::amf3::read::AMF3Decoder;
-use flash_lso::types::{AMFVersion, Element};
+use std::collections::HashMap;
 
 /// Implements `flash.utils.ByteArray`'s instance constructor.
 pub fn init<'gc>(
@@ -798,31 +798,16 @@ pub fn read_object<'gc>(
             let bytes = bytearray
                 .read_at(bytearray.bytes_available(), bytearray.position())
                 .map_err(|e| e.to_avm(activation))?;
-            let (bytes_left, value) = match bytearray.object_encoding() {
-                ObjectEncoding::Amf0 => {
-                    let mut decoder = AMF0Decoder::default();
-                    let (extra, amf) = decoder
-                        .parse_single_element(bytes)
-                        .map_err(|_| "Error: Invalid object")?;
-                    (
-                        extra.len(),
-                        crate::avm2::amf::deserialize_value(activation, &amf)?,
-                    )
-                }
-                ObjectEncoding::Amf3 => {
-                    let mut decoder = AMF3Decoder::default();
-                    let (extra, amf) = decoder
-                        .parse_single_element(bytes)
-                        .map_err(|_| "Error: Invalid object")?;
-                    (
-                        extra.len(),
-                        crate::avm2::amf::deserialize_value(activation, &amf)?,
-                    )
-                }
+            let amf_version = match bytearray.object_encoding() {
+                ObjectEncoding::Amf0 => AmfVersion::Amf0,
+                ObjectEncoding::Amf3 => AmfVersion::Amf3,
             };
+            let (amf, length) =
+                crate::amf::read_value(amf_version, bytes).map_err(|_| "Error: Invalid object")?;
+            bytearray.set_position(bytearray.position() + length);
+            drop(bytearray);
 
-            bytearray.set_position(bytearray.len() - bytes_left);
-            return Ok(value);
+            return crate::avm2::amf::deserialize_value(activation, &amf, &mut HashMap::new());
         }
     }
 
@@ -835,27 +820,24 @@ pub fn write_object<'gc>(
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     if let Some(this) = this {
-        if let Some(mut bytearray) = this.as_bytearray_mut(activation.context.gc_context) {
-            let obj = args.get(0).cloned().unwrap_or(Value::Undefined);
-            let amf_version = match bytearray.object_encoding() {
-                ObjectEncoding::Amf0 => AMFVersion::AMF0,
-                ObjectEncoding::Amf3 => AMFVersion::AMF3,
-            };
-            if let Some(amf) = crate::avm2::amf::serialize_value(activation, obj, amf_version) {
-                let element = Element::new("", amf);
-                let mut lso = flash_lso::types::Lso::new(vec![element], "", amf_version);
-                let bytes = flash_lso::write::write_to_bytes(&mut lso)
-                    .map_err(|_| "Failed to serialize object")?;
-                // This is kind of hacky: We need to strip out the header and any padding so that we only write
-                // the value. In the future, there should be a method to do this in the flash_lso crate.
-                let element_padding = match amf_version {
-                    AMFVersion::AMF0 => 8,
-                    AMFVersion::AMF3 => 7,
-                };
-                bytearray.write_bytes(
-                    &bytes[flash_lso::write::header_length(&lso.header) + element_padding
-                        ..bytes.len() - 1],
-                )?;
+        let Some(object_encoding) = this
+            .as_bytearray()
+            .map(|bytearray| bytearray.object_encoding())
+        else {
+            return Ok(Value::Undefined);
+        };
+        let obj = args.get(0).cloned().unwrap_or(Value::Undefined);
+        let amf_version = match object_encoding {
+            ObjectEncoding::Amf0 => AmfVersion::Amf0,
+            ObjectEncoding::Amf3 => AmfVersion::Amf3,
+        };
+        // The byte array isn't borrowed while serializing, as it may be written into itself.
+        if let Some(amf) =
+            crate::avm2::amf::serialize_value(activation, obj, &mut ObjectTable::new())
+        {
+            let bytes = crate::amf::write_value(amf_version, &amf);
+            if let Some(mut bytearray) = this.as_bytearray_mut(activation.context.gc_context) {
+                bytearray.write_bytes(&bytes)?;
             }
         }
     }
@@ -34,8 +34,19 @@ include "flash/display/ColorCorrectionSupport.as"
 include "flash/display/FocusDirection.as"
 include "flash/display/FrameLabel.as"
 include "flash/display/GradientType.as"
+include "flash/display/IGraphicsData.as"
+include "flash/display/IGraphicsFill.as"
+include "flash/display/IGraphicsPath.as"
+include "flash/display/IGraphicsStroke.as"
+include "flash/display/GraphicsBitmapFill.as"
+include "flash/display/GraphicsEndFill.as"
+include "flash/display/GraphicsGradientFill.as"
+include "flash/display/GraphicsPath.as"
 include "flash/display/GraphicsPathCommand.as"
 include "flash/display/GraphicsPathWinding.as"
+include "flash/display/GraphicsSolidFill.as"
+include "flash/display/GraphicsStroke.as"
+include "flash/display/GraphicsTrianglePath.as"
 include "flash/display/InterpolationMethod.as"
 include "flash/display/JointStyle.as"
 include "flash/display/JPEGEncoderOptions.as"
@@ -188,6 +199,7 @@ include "flash/net/NetGroupSendResult.as"
 include "flash/net/ObjectEncoding.as"
 include "flash/net/SharedObject.as"
 include "flash/net/SharedObjectFlushStatus.as"
+include "flash/net/Socket.as"
 include "flash/net/URLLoader.as"
 include "flash/net/URLLoaderDataFormat.as"
 include "flash/net/URLRequest.as"
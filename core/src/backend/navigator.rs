@@ -6,6 +6,7 @@ use indexmap::IndexMap;
 use std::future::Future;
 use std::path::{Path, PathBuf};
 use std::pin::Pin;
+use std::time::Duration;
 use swf::avm1::types::SendVarsMethod;
 use url::Url;
 
@@ -53,6 +54,10 @@ pub struct Request {
     ///
     /// The body consists of data and a mime type.
     body: Option<(Vec<u8>, String)>,
+
+    /// Additional HTTP headers to send with the request, such as those added with
+    /// `LoadVars.addRequestHeader`.
+    headers: IndexMap<String, String>,
 }
 
 impl Request {
@@ -62,6 +67,7 @@ impl Request {
             url,
             method: NavigationMethod::Get,
             body: None,
+            headers: IndexMap::new(),
         }
     }
 
@@ -71,13 +77,19 @@ impl Request {
             url,
             method: NavigationMethod::Post,
             body,
+            headers: IndexMap::new(),
         }
     }
 
     /// Construct a request with the given method and data
     #[allow(clippy::self_named_constructors)]
     pub fn request(method: NavigationMethod, url: String, body: Option<(Vec<u8>, String)>) -> Self {
-        Self { url, method, body }
+        Self {
+            url,
+            method,
+            body,
+            headers: IndexMap::new(),
+        }
     }
 
     /// Retrieve the URL of this request.
@@ -98,6 +110,15 @@ impl Request {
     pub fn set_body(&mut self, body: (Vec<u8>, String)) {
         self.body = Some(body);
     }
+
+    /// Retrieve the additional HTTP headers of this request.
+    pub fn headers(&self) -> &IndexMap<String, String> {
+        &self.headers
+    }
+
+    pub fn set_headers(&mut self, headers: IndexMap<String, String>) {
+        self.headers = headers;
+    }
 }
 
 /// A response to a fetch request.
@@ -107,12 +128,44 @@ pub struct Response {
 
     /// The contents of the response body.
     pub body: Vec<u8>,
+
+    /// The HTTP status code of the response, or 0 if it wasn't fetched over HTTP.
+    pub status: u16,
 }
 
 /// Type alias for pinned, boxed, and owned futures that output a falliable
 /// result of type `Result<T, E>`.
 pub type OwnedFuture<T, E> = Pin<Box<dyn Future<Output = Result<T, E>> + 'static>>;
 
+/// A handle identifying a socket opened through the `NavigatorBackend`.
+pub type SocketHandle = generational_arena::Index;
+
+/// The outcome of an attempt to open a socket.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ConnectionState {
+    /// The socket is open, and data may now be sent over it.
+    Connected,
+
+    /// The connection was refused, or could not be made at all.
+    Failed,
+
+    /// The connection was not made within the timeout.
+    TimedOut,
+}
+
+/// Something that happened to a socket, sent from the backend back to the player.
+#[derive(Debug)]
+pub enum SocketAction {
+    /// The connection attempt for a socket has finished.
+    Connect(SocketHandle, ConnectionState),
+
+    /// Raw bytes were received from the other end of the socket.
+    Data(SocketHandle, Vec<u8>),
+
+    /// The socket was closed, either by the other end or because of an error.
+    Close(SocketHandle),
+}
+
 /// A backend interacting with a browser environment.
 pub trait NavigatorBackend {
     /// Cause a browser navigation to a given URL.
@@ -162,6 +215,25 @@ pub trait NavigatorBackend {
     /// Changing http -> https for example. This function may alter any part of the
     /// URL (generally only if configured to do so by the user).
     fn pre_process_url(&self, url: Url) -> Url;
+
+    /// Open a socket to the given host and port.
+    ///
+    /// The outcome of the connection attempt, any data received and the
+    /// closing of the socket are all reported back through `sender`, tagged
+    /// with `handle`. Bytes to be written to the socket are received from
+    /// `receiver`, and the socket should be closed once it is closed.
+    ///
+    /// Only raw bytes are passed through; any framing of messages is left to
+    /// the caller.
+    fn connect_socket(
+        &mut self,
+        host: String,
+        port: u16,
+        timeout: Duration,
+        handle: SocketHandle,
+        receiver: async_channel::Receiver<Vec<u8>>,
+        sender: async_channel::Sender<SocketAction>,
+    );
 }
 
 #[cfg(not(target_family = "wasm"))]
@@ -297,7 +369,11 @@ impl NavigatorBackend for NullNavigatorBackend {
 
             let body = std::fs::read(path).map_err(|e| Error::FetchError(e.to_string()))?;
 
-            Ok(Response { url, body })
+            Ok(Response {
+                url,
+                body,
+                status: 0,
+            })
         })
     }
 
@@ -308,4 +384,16 @@ impl NavigatorBackend for NullNavigatorBackend {
     fn pre_process_url(&self, url: Url) -> Url {
         url
     }
+
+    fn connect_socket(
+        &mut self,
+        _host: String,
+        _port: u16,
+        _timeout: Duration,
+        handle: SocketHandle,
+        _receiver: async_channel::Receiver<Vec<u8>>,
+        sender: async_channel::Sender<SocketAction>,
+    ) {
+        let _ = sender.try_send(SocketAction::Connect(handle, ConnectionState::Failed));
+    }
 }
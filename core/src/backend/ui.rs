@@ -1,4 +1,5 @@
 use crate::events::{KeyCode, PlayerEvent};
+use ruffle_render::bounding_box::BoundingBox;
 use std::borrow::Cow;
 use std::collections::HashSet;
 
@@ -28,6 +29,46 @@ pub trait UiBackend {
 
     // Unused, but kept in case we need it later.
     fn message(&self, message: &str);
+
+    /// Looks up a device font on the system, for text fields that don't embed their fonts.
+    ///
+    /// `name` is either a font family, or one of the `_sans`, `_serif` and `_typewriter`
+    /// names that Flash maps to the system's default fonts of that kind. The font that
+    /// best matches is registered first. Any other fonts that are registered after it
+    /// are used in order for the characters that it has no glyphs for.
+    fn load_device_font(
+        &self,
+        name: &str,
+        is_bold: bool,
+        is_italic: bool,
+        register: &mut dyn FnMut(FontDefinition),
+    );
+
+    /// Tells the UI where the caret of the text field that's being typed into is, so that
+    /// input methods can show what they're composing next to it, or `None` once there's
+    /// no editable text field with focus.
+    ///
+    /// The area is in the viewport pixels that mouse events are given in, as twips.
+    fn set_text_input_area(&mut self, area: Option<BoundingBox>);
+
+    /// Shows an on-screen keyboard, for a focused object that asked for one.
+    /// Returns `false` if there's no on-screen keyboard to show.
+    fn request_soft_keyboard(&mut self) -> bool;
+}
+
+/// A font that's given to the player to draw device fonts with.
+#[derive(Clone)]
+pub enum FontDefinition {
+    /// A TrueType or OpenType font file, or a collection of them.
+    FontFile {
+        /// The family name that movies refer to the font by.
+        name: String,
+        is_bold: bool,
+        is_italic: bool,
+        data: Vec<u8>,
+        /// Which font of a collection this is, or 0 for a font file with a single font.
+        index: u32,
+    },
 }
 
 /// A mouse cursor icon displayed by the Flash Player.
@@ -148,6 +189,21 @@ impl UiBackend for NullUiBackend {
     fn display_root_movie_download_failed_message(&self) {}
 
     fn message(&self, _message: &str) {}
+
+    fn load_device_font(
+        &self,
+        _name: &str,
+        _is_bold: bool,
+        _is_italic: bool,
+        _register: &mut dyn FnMut(FontDefinition),
+    ) {
+    }
+
+    fn set_text_input_area(&mut self, _area: Option<BoundingBox>) {}
+
+    fn request_soft_keyboard(&mut self) -> bool {
+        false
+    }
 }
 
 impl Default for NullUiBackend {
@@ -9,8 +9,10 @@ use core::fmt;
 use gc_arena::Collect;
 use ruffle_render::backend::RenderBackend;
 use ruffle_render::bitmap::{Bitmap, BitmapFormat, BitmapHandle, SyncHandle};
+use ruffle_render::bounding_box::BoundingBox;
 use ruffle_render::color_transform::ColorTransform;
 use ruffle_render::commands::{CommandHandler, CommandList};
+use ruffle_render::filters::cpu::{self as filter_cpu, FilterImage};
 use ruffle_render::filters::Filter;
 use ruffle_render::matrix::Matrix;
 use ruffle_render::quality::StageQuality;
@@ -189,6 +191,10 @@ pub struct BitmapData<'gc> {
     avm2_object: Option<Avm2Object<'gc>>,
 
     dirty_state: DirtyState,
+
+    /// Incremented whenever the pixels change, so that anything rendered from them
+    /// (such as a cached display object) can tell when it's out of date.
+    generation: u32,
 }
 
 #[derive(Clone, Collect, Default, Debug)]
@@ -327,6 +333,9 @@ mod wrapper {
                 let handle = inner_bitmap_data
                     .bitmap_handle(context.renderer)
                     .expect("Missing bitmap handle");
+                if let Some(cache_dependencies) = &mut context.cache_dependencies {
+                    cache_dependencies.add_bitmap(self.0, inner_bitmap_data.generation());
+                }
 
                 context.commands.render_bitmap(
                     handle,
@@ -371,6 +380,7 @@ impl<'gc> BitmapData<'gc> {
             bitmap_handle: None,
             avm2_object: None,
             dirty_state: DirtyState::Clean,
+            generation: 0,
         }
     }
 
@@ -413,6 +423,7 @@ impl<'gc> BitmapData<'gc> {
         // There's no longer a handle to update
         self.dirty_state = DirtyState::Clean;
         self.disposed = true;
+        self.generation = self.generation.wrapping_add(1);
     }
 
     pub fn bitmap_handle(&mut self, renderer: &mut dyn RenderBackend) -> Option<BitmapHandle> {
@@ -441,8 +452,14 @@ impl<'gc> BitmapData<'gc> {
         self.transparency = transparency;
     }
 
+    /// A counter that changes whenever the pixels of this bitmap change.
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
     pub fn set_cpu_dirty(&mut self, dirty: bool) {
         let new_state = if dirty {
+            self.generation = self.generation.wrapping_add(1);
             DirtyState::CpuModified
         } else {
             DirtyState::Clean
@@ -563,16 +580,32 @@ impl<'gc> BitmapData<'gc> {
         }
     }
 
-    pub fn fill_rect(&mut self, x: u32, y: u32, width: u32, height: u32, color: Color) {
-        for x_offset in 0..width {
-            for y_offset in 0..height {
-                self.set_pixel32((x + x_offset) as i32, (y + y_offset) as i32, color)
-            }
+    /// Fills the part of the rectangle that is inside of the bitmap with the given color.
+    pub fn fill_rect(&mut self, x: i32, y: i32, width: i32, height: i32, color: Color) {
+        let x_min = x.clamp(0, self.width() as i32) as u32;
+        let y_min = y.clamp(0, self.height() as i32) as u32;
+        let x_max = x.saturating_add(width).clamp(0, self.width() as i32) as u32;
+        let y_max = y.saturating_add(height).clamp(0, self.height() as i32) as u32;
+        if x_min >= x_max || y_min >= y_max {
+            return;
         }
+
+        let color = color.to_premultiplied_alpha(self.transparency());
+        let width = self.width();
+        for y in y_min..y_max {
+            self.pixels[(x_min + y * width) as usize..(x_max + y * width) as usize].fill(color);
+        }
+        self.set_cpu_dirty(true);
     }
 
+    /// Replaces the color of the pixel at `(x, y)` with `replace_color`, along with every pixel of
+    /// exactly the same color that's connected to it horizontally or vertically.
     pub fn flood_fill(&mut self, x: u32, y: u32, replace_color: Color) {
         let expected_color = self.get_pixel_raw(x, y).unwrap_or_else(|| 0.into());
+        // Filled pixels would still match, and be filled again forever
+        if expected_color == replace_color {
+            return;
+        }
 
         let mut pending = vec![(x, y)];
 
@@ -737,6 +770,10 @@ impl<'gc> BitmapData<'gc> {
         }
     }
 
+    /// Finds the smallest rectangle around the pixels whose color matches `color` once it's
+    /// masked by `mask`, or that doesn't match if `find_color` is false.
+    ///
+    /// Pixels are compared by the same ARGB value that `getPixel32` returns for them.
     pub fn color_bounds_rect(
         &self,
         find_color: bool,
@@ -748,9 +785,13 @@ impl<'gc> BitmapData<'gc> {
         let mut min_y = self.height();
         let mut max_y = 0;
 
-        for x in 0..self.width() {
-            for y in 0..self.height() {
-                let pixel_raw: i32 = self.get_pixel_raw(x, y).unwrap().into();
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                let pixel_raw: i32 = self
+                    .get_pixel_raw(x, y)
+                    .unwrap()
+                    .to_un_multiplied_alpha()
+                    .into();
                 let color_matches = if find_color {
                     (pixel_raw & mask) == color
                 } else {
@@ -778,6 +819,65 @@ impl<'gc> BitmapData<'gc> {
         }
     }
 
+    /// Whether the pixel at `(x, y)` counts as solid for `BitmapData.hitTest`: its alpha
+    /// must be at least `alpha_threshold`, unless the bitmap has no transparency at all.
+    fn is_pixel_solid(&self, x: u32, y: u32, alpha_threshold: u32) -> bool {
+        !self.transparency
+            || u32::from(self.pixels[(x + y * self.width) as usize].alpha()) >= alpha_threshold
+    }
+
+    /// Clips a rectangle in this bitmap's coordinates to the bitmap,
+    /// returning the ranges of columns and rows that it covers.
+    fn clip_rect(&self, x: i64, y: i64, width: i64, height: i64) -> (Range<u32>, Range<u32>) {
+        let clip = |start: i64, length: i64, size: u32| {
+            let min = start.clamp(0, size.into()) as u32;
+            let max = start.saturating_add(length.max(0)).clamp(0, size.into()) as u32;
+            min..max
+        };
+        (clip(x, width, self.width), clip(y, height, self.height))
+    }
+
+    /// Tests whether any solid pixel of this bitmap lies within the given rectangle,
+    /// which is in this bitmap's coordinates. A point is tested as a 1x1 rectangle.
+    pub fn hit_test_rectangle(&self, alpha_threshold: u32, rect: (i64, i64, i64, i64)) -> bool {
+        let (columns, mut rows) = self.clip_rect(rect.0, rect.1, rect.2, rect.3);
+        rows.any(|y| {
+            columns
+                .clone()
+                .any(|x| self.is_pixel_solid(x, y, alpha_threshold))
+        })
+    }
+
+    /// Tests whether a solid pixel of this bitmap overlaps a solid pixel of `other`,
+    /// which has its top left corner at `other_point` in this bitmap's coordinates.
+    ///
+    /// Only the area where the two bitmaps overlap is visited, and this stops at the first hit.
+    pub fn hit_test_bitmapdata(
+        &self,
+        alpha_threshold: u32,
+        other: &Self,
+        other_point: (i64, i64),
+        other_alpha_threshold: u32,
+    ) -> bool {
+        let (columns, mut rows) = self.clip_rect(
+            other_point.0,
+            other_point.1,
+            other.width.into(),
+            other.height.into(),
+        );
+        if !self.transparency && !other.transparency {
+            return !columns.is_empty() && !rows.is_empty();
+        }
+        rows.any(|y| {
+            let other_y = (i64::from(y) - other_point.1) as u32;
+            columns.clone().any(|x| {
+                let other_x = (i64::from(x) - other_point.0) as u32;
+                self.is_pixel_solid(x, y, alpha_threshold)
+                    && other.is_pixel_solid(other_x, other_y, other_alpha_threshold)
+            })
+        })
+    }
+
     pub fn copy_pixels(
         &mut self,
         source_bitmap: &Self,
@@ -928,14 +1028,14 @@ impl<'gc> BitmapData<'gc> {
         }
     }
 
-    // Unlike `copy_channel` and `copy_pixels`, this function seems to
-    // operate "in-place" if the source bitmap is the same object as `self`.
-    // This means that we can't resolve this aliasing issue in Rust by a
-    // simple clone in the caller. Instead, if the `source_bitmap` parameter
-    // is `None`, it means that `self` should be used as source as well.
+    /// Replaces every channel of each pixel with the value that its array maps it to,
+    /// and sums the results of all four channels together.
+    ///
+    /// If `source_bitmap` is the same object as `self`, the caller must pass a copy of it,
+    /// as Flash reads from a snapshot of the source even when the rectangles overlap.
     pub fn palette_map(
         &mut self,
-        source_bitmap: Option<&Self>,
+        source_bitmap: &Self,
         src_rect: (i32, i32, i32, i32),
         dest_point: (i32, i32),
         channel_arrays: ([u32; 256], [u32; 256], [u32; 256], [u32; 256]),
@@ -949,15 +1049,12 @@ impl<'gc> BitmapData<'gc> {
                 let dest_y = src_y - src_min_y + dest_min_y;
 
                 if !self.is_point_in_bounds(dest_x, dest_y)
-                    || !source_bitmap
-                        .unwrap_or(self)
-                        .is_point_in_bounds(src_x, src_y)
+                    || !source_bitmap.is_point_in_bounds(src_x, src_y)
                 {
                     continue;
                 }
 
                 let source_color = source_bitmap
-                    .unwrap_or(self)
                     .get_pixel_raw(src_x as u32, src_y as u32)
                     .unwrap()
                     .to_un_multiplied_alpha();
@@ -968,7 +1065,7 @@ impl<'gc> BitmapData<'gc> {
                 let a = channel_arrays.3[source_color.alpha() as usize];
 
                 let sum = u32::wrapping_add(u32::wrapping_add(r, g), u32::wrapping_add(b, a));
-                let mix_color = Color(sum as i32).to_premultiplied_alpha(true);
+                let mix_color = Color(sum as i32).to_premultiplied_alpha(self.transparency());
 
                 self.set_pixel32_raw(dest_x as u32, dest_y as u32, mix_color);
             }
@@ -1081,50 +1178,50 @@ impl<'gc> BitmapData<'gc> {
         }
     }
 
+    /// Shifts the pixels by `x` and `y` in place. The pixels that are scrolled out of are left as
+    /// they were, rather than being cleared.
     pub fn scroll(&mut self, x: i32, y: i32) {
         let width = self.width() as i32;
         let height = self.height() as i32;
 
-        if (x == 0 && y == 0) || x.abs() >= width || y.abs() >= height {
+        if (x == 0 && y == 0)
+            || x.unsigned_abs() >= width as u32
+            || y.unsigned_abs() >= height as u32
+        {
             return; // no-op
         }
 
-        // since this is an "in-place copy", we have to iterate from bottom to top
-        // when scrolling downwards - so if y is positive
-        let reverse_y = y > 0;
-        // and if only scrolling horizontally, we have to iterate from right to left
-        // when scrolling right - so if x is positive
-        let reverse_x = y == 0 && x > 0;
-
-        // iteration ranges to use as source for the copy, from is inclusive, to is exclusive
-        let y_from = if reverse_y { height - y - 1 } else { -y };
-        let y_to = if reverse_y { -1 } else { height };
-        let dy = if reverse_y { -1 } else { 1 };
-
-        let x_from = if reverse_x {
-            // we know x > 0
-            width - x - 1
-        } else {
-            // x can be any sign
-            (-x).max(0)
+        // The same part of every row is copied, which `copy_within` does correctly even when
+        // it overlaps itself. Whole rows can overlap too, so when scrolling downwards they're
+        // copied from bottom to top, to read each row before it's overwritten.
+        let src_x = (-x).max(0) as usize;
+        let dest_x = x.max(0) as usize;
+        let row_length = (width - x.abs()) as usize;
+        let stride = width as usize;
+        let copy_row = |pixels: &mut [Color], dest_y: i32| {
+            let src_start = (dest_y - y) as usize * stride + src_x;
+            pixels.copy_within(
+                src_start..src_start + row_length,
+                dest_y as usize * stride + dest_x,
+            );
         };
-        let x_to = if reverse_x { -1 } else { width.min(width - x) };
-        let dx = if reverse_x { -1 } else { 1 };
-
-        let mut src_y = y_from;
-        while src_y != y_to {
-            let mut src_x = x_from;
-            while src_x != x_to {
-                let color = self.get_pixel_raw(src_x as u32, src_y as u32).unwrap();
-                self.set_pixel32_raw((src_x + x) as u32, (src_y + y) as u32, color);
-                src_x += dx;
-            }
-            src_y += dy;
+
+        let dest_rows = y.max(0)..height + y.min(0);
+        if y > 0 {
+            dest_rows
+                .rev()
+                .for_each(|dest_y| copy_row(&mut self.pixels, dest_y));
+        } else {
+            dest_rows.for_each(|dest_y| copy_row(&mut self.pixels, dest_y));
         }
+        self.set_cpu_dirty(true);
     }
 
     /// This implements the threshold operation generically over the test operation performed for each pixel
     /// Returns the number of pixels modified
+    ///
+    /// If `source_bitmap` is the same object as `self`, the caller must pass a copy of it,
+    /// as Flash reads from a snapshot of the source even when the rectangles overlap.
     #[allow(clippy::too_many_arguments)]
     fn threshold_internal<Op: Fn(u32, u32) -> bool>(
         &mut self,
@@ -1137,8 +1234,9 @@ impl<'gc> BitmapData<'gc> {
         mask: u32,
         copy_source: bool,
     ) -> u32 {
-        // Pre-compute the masked threshold
+        // Pre-compute the masked threshold, and the colour as it's stored in this bitmap
         let masked_threshold = threshold & mask;
+        let colour = Color(colour as i32).to_premultiplied_alpha(self.transparency());
 
         // Extract coords
         let (src_min_x, src_min_y, src_width, src_height) = src_rect;
@@ -1169,17 +1267,14 @@ impl<'gc> BitmapData<'gc> {
                 // If the test, as defined by the operation pass then set to input colour
                 if operation(source_color.0 as u32 & mask, masked_threshold) {
                     modified_count += 1;
-                    self.set_pixel32_raw(dest_x as u32, dest_y as u32, Color(colour as _));
-                } else {
+                    self.set_pixel32_raw(dest_x as u32, dest_y as u32, colour);
+                } else if copy_source {
                     // If the test fails, but copy_source is true then take the colour from the source
-                    if copy_source {
-                        let new_color = source_bitmap
-                            .get_pixel_raw(dest_x as u32, dest_y as u32)
-                            .unwrap()
-                            .to_un_multiplied_alpha();
-
-                        self.set_pixel32_raw(dest_x as u32, dest_y as u32, new_color);
-                    }
+                    self.set_pixel32_raw(
+                        dest_x as u32,
+                        dest_y as u32,
+                        source_color.to_premultiplied_alpha(self.transparency()),
+                    );
                 }
             }
         }
@@ -1331,7 +1426,10 @@ impl<'gc> BitmapData<'gc> {
         );
         match sync_handle {
             Some(sync_handle) => match self.dirty_state {
-                DirtyState::Clean => self.dirty_state = DirtyState::GpuModified(sync_handle),
+                DirtyState::Clean => {
+                    self.dirty_state = DirtyState::GpuModified(sync_handle);
+                    self.generation = self.generation.wrapping_add(1);
+                }
                 DirtyState::CpuModified | DirtyState::GpuModified(_) => panic!(
                     "Called BitmapData.render while already dirty: {:?}",
                     self.dirty_state
@@ -1343,6 +1441,47 @@ impl<'gc> BitmapData<'gc> {
         }
     }
 
+    /// Applies a filter to the pixels of `source` directly, for when the renderer can't.
+    ///
+    /// If `source` is this same bitmap, the caller must pass a copy of it instead.
+    /// Returns `false` if this filter can't be applied on the CPU yet.
+    pub fn apply_filter_cpu(
+        &mut self,
+        source: &Self,
+        source_point: (u32, u32),
+        source_size: (u32, u32),
+        dest_point: (u32, u32),
+        filter: &Filter,
+    ) -> bool {
+        let image = FilterImage::from_rgba(source.width(), source.height(), &source.pixels_rgba());
+        let Some(result) = filter_cpu::apply_filter(filter, &image, source_point, source_size)
+        else {
+            return false;
+        };
+
+        let rgba = result.to_rgba();
+        for y in 0..result.height() {
+            for x in 0..result.width() {
+                let dest_x = dest_point.0 + x;
+                let dest_y = dest_point.1 + y;
+                if !self.is_point_in_bounds(dest_x as i32, dest_y as i32) {
+                    continue;
+                }
+                let index = ((y * result.width() + x) * 4) as usize;
+                // Like a result read back from the GPU, an opaque bitmap keeps the colors
+                // as they would look over black
+                let a = if self.transparency() {
+                    rgba[index + 3]
+                } else {
+                    255
+                };
+                let color = Color::argb(a, rgba[index], rgba[index + 1], rgba[index + 2]);
+                self.set_pixel32_raw(dest_x, dest_y, color);
+            }
+        }
+        true
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn draw(
         &mut self,
@@ -1357,6 +1496,12 @@ impl<'gc> BitmapData<'gc> {
         let bitmapdata_width = self.width();
         let bitmapdata_height = self.height();
 
+        if let IBitmapDrawable::DisplayObject(object) = &source {
+            if object.as_stage().is_some() {
+                context.stage.set_always_redraw(context.gc_context);
+            }
+        }
+
         let mut transform_stack = ruffle_render::transform::TransformStack::new();
         transform_stack.push(&transform);
         let handle = self.bitmap_handle(context.renderer).unwrap();
@@ -1372,6 +1517,8 @@ impl<'gc> BitmapData<'gc> {
             stage: context.stage,
             clip_depth_stack: vec![],
             allow_mask: true,
+            cache_dependencies: None,
+            culling_stats: Default::default(),
         };
 
         // Make the screen opacity match the opacity of this bitmap
@@ -1425,7 +1572,11 @@ impl<'gc> BitmapData<'gc> {
             render_context.commands
         } else {
             let mut commands = CommandList::new();
-            commands.blend(render_context.commands, blend_mode);
+            commands.blend(
+                render_context.commands,
+                blend_mode,
+                ColorTransform::IDENTITY,
+            );
             commands
         };
 
@@ -1439,7 +1590,10 @@ impl<'gc> BitmapData<'gc> {
 
         match image {
             Some(sync_handle) => match self.dirty_state {
-                DirtyState::Clean => self.dirty_state = DirtyState::GpuModified(sync_handle),
+                DirtyState::Clean => {
+                    self.dirty_state = DirtyState::GpuModified(sync_handle);
+                    self.generation = self.generation.wrapping_add(1);
+                }
                 DirtyState::CpuModified | DirtyState::GpuModified(_) => panic!(
                     "Called BitmapData.render while already dirty: {:?}",
                     self.dirty_state
@@ -1457,6 +1611,18 @@ pub enum IBitmapDrawable<'gc> {
     DisplayObject(DisplayObject<'gc>),
 }
 
+/// Calculates the area of a bitmap that `filter` changes when applied to `source_rect`,
+/// rounded out to whole pixels. This is what `BitmapData.generateFilterRect` returns.
+pub fn generate_filter_rect(filter: &Filter, source_rect: Rectangle<Twips>) -> Rectangle<Twips> {
+    let rect = filter.calculate_dest_rect(BoundingBox::from(source_rect));
+    Rectangle {
+        x_min: Twips::from_pixels(rect.x_min.to_pixels().floor()),
+        y_min: Twips::from_pixels(rect.y_min.to_pixels().floor()),
+        x_max: Twips::from_pixels(rect.x_max.to_pixels().ceil()),
+        y_max: Twips::from_pixels(rect.y_max.to_pixels().ceil()),
+    }
+}
+
 #[instrument(level = "debug", skip_all)]
 fn copy_pixels_to_bitmapdata(write: &mut BitmapData, bytes: &[u8]) {
     let height = write.height();
@@ -1486,3 +1652,175 @@ fn copy_pixels_to_bitmapdata(write: &mut BitmapData, bytes: &[u8]) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A transparent bitmap, with the given pixels set to white at the given alpha.
+    fn transparent_bitmap(
+        width: u32,
+        height: u32,
+        pixels: &[(i32, i32, u8)],
+    ) -> BitmapData<'static> {
+        let mut bitmap = BitmapData::dummy();
+        bitmap.init_pixels(width, height, true, 0);
+        for &(x, y, alpha) in pixels {
+            bitmap.set_pixel32(x, y, Color::argb(alpha, 255, 255, 255));
+        }
+        bitmap
+    }
+
+    fn opaque_bitmap(width: u32, height: u32) -> BitmapData<'static> {
+        let mut bitmap = BitmapData::dummy();
+        bitmap.init_pixels(width, height, false, 0);
+        bitmap
+    }
+
+    /// An opaque bitmap where every pixel has a different color, from its index.
+    fn numbered_bitmap(width: u32, height: u32) -> BitmapData<'static> {
+        let mut bitmap = opaque_bitmap(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                bitmap.set_pixel32_raw(x, y, Color(0xFF000000u32 as i32 | (x + y * width) as i32));
+            }
+        }
+        bitmap
+    }
+
+    /// The indices of the pixels that were in each place of a `numbered_bitmap`, row by row.
+    fn numbers(bitmap: &BitmapData) -> Vec<i32> {
+        bitmap
+            .pixels()
+            .iter()
+            .map(|color| color.0 & 0xFFFFFF)
+            .collect()
+    }
+
+    #[test]
+    fn scroll_keeps_vacated_pixels() {
+        let mut bitmap = numbered_bitmap(3, 3);
+        bitmap.scroll(1, 1);
+        assert_eq!(numbers(&bitmap), [0, 1, 2, 3, 0, 1, 6, 3, 4]);
+
+        let mut bitmap = numbered_bitmap(3, 3);
+        bitmap.scroll(-2, 0);
+        assert_eq!(numbers(&bitmap), [2, 1, 2, 5, 4, 5, 8, 7, 8]);
+
+        let mut bitmap = numbered_bitmap(3, 3);
+        bitmap.scroll(1, -1);
+        assert_eq!(numbers(&bitmap), [0, 3, 4, 3, 6, 7, 6, 7, 8]);
+
+        // Scrolling everything out of the bitmap leaves all of it as it was
+        let mut bitmap = numbered_bitmap(3, 3);
+        bitmap.scroll(3, 0);
+        bitmap.scroll(0, i32::MIN);
+        assert_eq!(numbers(&bitmap), [0, 1, 2, 3, 4, 5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn flood_fill_is_four_connected() {
+        let black = Color(0xFF000000u32 as i32);
+        let white = Color(0xFFFFFFFFu32 as i32);
+        let red = Color(0xFFFF0000u32 as i32);
+        let mut bitmap = opaque_bitmap(3, 3);
+        // A white cross, with a diagonal neighbour in the corner
+        for (x, y) in [(1, 0), (0, 1), (1, 1), (2, 1), (1, 2)] {
+            bitmap.set_pixel32_raw(x, y, white);
+        }
+        bitmap.flood_fill(0, 0, red);
+        let expected = [red, white, black, white, white, white, black, white, black];
+        assert_eq!(bitmap.pixels(), expected);
+
+        // Filling with the color that's already there does nothing
+        bitmap.flood_fill(1, 1, white);
+        bitmap.flood_fill(1, 1, red);
+        let expected = [red, red, black, red, red, red, black, red, black];
+        assert_eq!(bitmap.pixels(), expected);
+    }
+
+    #[test]
+    fn flood_fill_matches_exact_color() {
+        let mut bitmap = transparent_bitmap(3, 1, &[(0, 0, 0xFF), (1, 0, 0xFF), (2, 0, 0xFE)]);
+        bitmap.set_pixel32(1, 0, Color::argb(0xFF, 0xFF, 0xFF, 0xFE));
+        bitmap.flood_fill(0, 0, Color(0));
+        assert_eq!(bitmap.get_pixel32(0, 0), Color(0));
+        assert_ne!(bitmap.get_pixel32(1, 0), Color(0));
+    }
+
+    #[test]
+    fn color_bounds_rect() {
+        let mut bitmap = transparent_bitmap(5, 5, &[(1, 3, 0x80), (3, 1, 0xFF)]);
+        assert_eq!(
+            bitmap.color_bounds_rect(false, 0xFF000000u32 as i32, 0),
+            (1, 1, 3, 3)
+        );
+        assert_eq!(
+            bitmap.color_bounds_rect(true, 0xFF000000u32 as i32, 0x80000000u32 as i32),
+            (1, 3, 1, 1)
+        );
+        // The color is compared without its alpha multiplied in
+        assert_eq!(
+            bitmap.color_bounds_rect(true, -1, 0x80FFFFFFu32 as i32),
+            (1, 3, 1, 1)
+        );
+        assert_eq!(bitmap.color_bounds_rect(true, -1, 0x12345678), (0, 0, 0, 0));
+
+        bitmap.fill_rect(0, 0, 5, 5, Color(0));
+        assert_eq!(bitmap.color_bounds_rect(true, -1, 0), (0, 0, 5, 5));
+    }
+
+    #[test]
+    fn hit_test_point() {
+        let bitmap = transparent_bitmap(4, 4, &[(1, 2, 0x80)]);
+        assert!(bitmap.hit_test_rectangle(0x80, (1, 2, 1, 1)));
+        assert!(!bitmap.hit_test_rectangle(0x81, (1, 2, 1, 1)));
+        assert!(!bitmap.hit_test_rectangle(1, (2, 1, 1, 1)));
+        // Everything counts as solid at a threshold of 0, but only inside the bitmap
+        assert!(bitmap.hit_test_rectangle(0, (0, 0, 1, 1)));
+        assert!(!bitmap.hit_test_rectangle(0, (-1, 0, 1, 1)));
+        assert!(!bitmap.hit_test_rectangle(0, (4, 4, 1, 1)));
+    }
+
+    #[test]
+    fn hit_test_rectangle() {
+        let bitmap = transparent_bitmap(4, 4, &[(3, 3, 0xFF)]);
+        assert!(bitmap.hit_test_rectangle(1, (2, 2, 10, 10)));
+        assert!(bitmap.hit_test_rectangle(1, (-10, -10, 14, 14)));
+        assert!(!bitmap.hit_test_rectangle(1, (-10, -10, 13, 13)));
+        assert!(!bitmap.hit_test_rectangle(1, (0, 0, 4, -4)));
+        assert!(!bitmap.hit_test_rectangle(1, (i64::MAX, 0, i64::MAX, 4)));
+    }
+
+    #[test]
+    fn hit_test_opaque_is_always_solid() {
+        let bitmap = opaque_bitmap(4, 4);
+        assert!(bitmap.hit_test_rectangle(0xFF, (0, 0, 1, 1)));
+        assert!(bitmap.hit_test_rectangle(0x100, (3, 3, 1, 1)));
+        assert!(!bitmap.hit_test_rectangle(0x100, (4, 0, 1, 1)));
+
+        let other = opaque_bitmap(2, 2);
+        assert!(bitmap.hit_test_bitmapdata(0x100, &other, (-1, -1), 0x100));
+        assert!(!bitmap.hit_test_bitmapdata(0x100, &other, (-2, -1), 0x100));
+
+        let transparent = transparent_bitmap(2, 2, &[(1, 1, 0x10)]);
+        assert!(bitmap.hit_test_bitmapdata(0xFF, &transparent, (2, 2), 0x10));
+        assert!(!bitmap.hit_test_bitmapdata(0xFF, &transparent, (2, 2), 0x11));
+        assert!(!bitmap.hit_test_bitmapdata(0xFF, &transparent, (3, 3), 0x10));
+    }
+
+    #[test]
+    fn hit_test_bitmapdata_thresholds() {
+        let first = transparent_bitmap(4, 4, &[(2, 2, 0x80)]);
+        let second = transparent_bitmap(4, 4, &[(0, 0, 0x40), (3, 3, 0x80)]);
+        assert!(first.hit_test_bitmapdata(0x80, &second, (2, 2), 0x40));
+        assert!(!first.hit_test_bitmapdata(0x81, &second, (2, 2), 0x40));
+        assert!(!first.hit_test_bitmapdata(0x80, &second, (2, 2), 0x41));
+        // The second bitmap's other pixel only lines up from the opposite side
+        assert!(first.hit_test_bitmapdata(0x80, &second, (-1, -1), 0x80));
+        assert!(!first.hit_test_bitmapdata(0x80, &second, (-1, -1), 0x81));
+        // Bitmaps that don't overlap never hit, whatever the thresholds
+        assert!(!first.hit_test_bitmapdata(0, &second, (4, 0), 0));
+        assert!(first.hit_test_bitmapdata(0, &second, (3, 0), 0));
+    }
+}
@@ -12,14 +12,18 @@ use crate::backend::{
     ui::{InputManager, UiBackend},
 };
 use crate::context_menu::ContextMenuState;
-use crate::display_object::{EditText, InteractiveObject, MovieClip, SoundTransform, Stage};
+use crate::display_object::{
+    CacheDependencies, EditText, InteractiveObject, MovieClip, SoundTransform, Stage,
+};
 use crate::external::ExternalInterface;
 use crate::focus_tracker::FocusTracker;
 use crate::frame_lifecycle::FramePhase;
 use crate::library::Library;
 use crate::loader::LoadManager;
+use crate::local_connection::LocalConnections;
 use crate::player::Player;
 use crate::prelude::*;
+use crate::socket::Sockets;
 use crate::stub::StubCollection;
 use crate::tag_utils::{SwfMovie, SwfSlice};
 use crate::timer::Timers;
@@ -143,6 +147,12 @@ pub struct UpdateContext<'a, 'gc> {
     /// Timed callbacks created with `setInterval`/`setTimeout`.
     pub timers: &'a mut Timers<'gc>,
 
+    /// Sockets opened by `XMLSocket`.
+    pub sockets: &'a mut Sockets<'gc>,
+
+    /// Connections made with `LocalConnection`.
+    pub local_connections: &'a mut LocalConnections<'gc>,
+
     pub current_context_menu: &'a mut Option<ContextMenuState<'gc>>,
 
     /// The AVM1 global state.
@@ -332,6 +342,8 @@ impl<'a, 'gc> UpdateContext<'a, 'gc> {
             avm2_shared_objects: self.avm2_shared_objects,
             unbound_text_fields: self.unbound_text_fields,
             timers: self.timers,
+            sockets: self.sockets,
+            local_connections: self.local_connections,
             current_context_menu: self.current_context_menu,
             avm1: self.avm1,
             avm2: self.avm2,
@@ -458,6 +470,37 @@ pub struct RenderContext<'a, 'gc> {
     /// Whether to allow pushing a new mask. A masker-inside-a-masker does not work in Flash, instead
     /// causing the inner mask to be included as part of the outer mask. Maskee-inside-a-maskee works as one expects.
     pub allow_mask: bool,
+
+    /// What the current cached bitmap rendering depends on, if we're rendering into one.
+    pub cache_dependencies: Option<CacheDependencies<'gc>>,
+
+    /// How many display objects have been drawn or culled so far.
+    pub culling_stats: CullingStats,
+}
+
+impl<'a, 'gc> RenderContext<'a, 'gc> {
+    /// Whether a display object can be skipped, because it lies entirely outside of the stage.
+    /// Nothing is culled while rendering offscreen, so its bounds are only calculated when needed.
+    pub fn is_culled(&mut self, world_bounds: impl FnOnce() -> BoundingBox) -> bool {
+        if self.is_offscreen {
+            return false;
+        }
+        let culled = !world_bounds().intersects(&self.stage.view_bounds());
+        if culled {
+            self.culling_stats.culled += 1;
+        } else {
+            self.culling_stats.drawn += 1;
+        }
+        culled
+    }
+}
+
+/// The number of display objects that were drawn onto the stage while rendering a frame,
+/// and how many were skipped for being off-screen.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CullingStats {
+    pub drawn: usize,
+    pub culled: usize,
 }
 
 /// The type of action being run.
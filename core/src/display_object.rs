@@ -15,6 +15,7 @@ use crate::vminterface::Instantiator;
 use bitflags::bitflags;
 use gc_arena::{Collect, MutationContext};
 use ruffle_macros::enum_trait_object;
+use ruffle_render::matrix3d::Matrix3D;
 use ruffle_render::transform::Transform;
 use std::cell::{Ref, RefMut};
 use std::fmt::Debug;
@@ -24,6 +25,7 @@ use swf::{BlendMode, Fixed8, Rectangle};
 mod avm1_button;
 mod avm2_button;
 mod bitmap;
+mod bitmap_cache;
 mod container;
 mod edit_text;
 mod graphic;
@@ -43,17 +45,45 @@ pub use crate::display_object::container::{
 pub use avm1_button::{Avm1Button, ButtonState, ButtonTracking};
 pub use avm2_button::Avm2Button;
 pub use bitmap::Bitmap;
+pub use bitmap_cache::CacheDependencies;
+use bitmap_cache::{BitmapCache, MAX_CACHE_PIXELS, MAX_CACHE_SIZE};
 pub use edit_text::{AutoSizeMode, EditText, TextSelection};
 pub use graphic::Graphic;
 pub use interactive::{Avm2MousePick, InteractiveObject, TInteractiveObject};
 pub use loader_display::LoaderDisplay;
 pub use morph_shape::{MorphShape, MorphShapeStatic};
 pub use movie_clip::{MovieClip, Scene};
-use ruffle_render::commands::CommandHandler;
+use ruffle_render::commands::{CommandHandler, CommandList};
+use ruffle_render::filters::Filter;
 pub use stage::{Stage, StageAlign, StageDisplayState, StageScaleMode, WindowMode};
 pub use text::Text;
 pub use video::Video;
 
+/// The 3D properties of a display object, which it only has once any of them have been set.
+#[derive(Clone, Copy, Debug)]
+pub struct Transform3D {
+    pub z: f64,
+    pub rotation_x: Degrees,
+    pub rotation_y: Degrees,
+    pub scale_z: f64,
+
+    /// A matrix set through `transform.matrix3D`, which is used as is instead of the properties
+    /// above (except for the position, which is kept in sync with `x`, `y` and `z`).
+    pub matrix: Option<Matrix3D>,
+}
+
+impl Default for Transform3D {
+    fn default() -> Self {
+        Self {
+            z: 0.0,
+            rotation_x: Degrees::from(0.0),
+            rotation_y: Degrees::from(0.0),
+            scale_z: 1.0,
+            matrix: None,
+        }
+    }
+}
+
 #[derive(Clone, Collect)]
 #[collect(no_drop)]
 pub struct DisplayObjectBase<'gc> {
@@ -64,6 +94,12 @@ pub struct DisplayObjectBase<'gc> {
     transform: Transform,
     name: AvmString<'gc>,
     filters: Avm2ArrayStorage<'gc>,
+
+    /// The filters applied when rendering this display object, in order.
+    /// Filters that aren't supported yet are left out.
+    #[collect(require_static)]
+    render_filters: Vec<Filter>,
+
     clip_depth: Depth,
 
     // Cached transform properties `_xscale`, `_yscale`, `_rotation`.
@@ -78,6 +114,10 @@ pub struct DisplayObjectBase<'gc> {
 
     skew: f64,
 
+    /// The 3D properties of this display object, if it's been given any.
+    #[collect(require_static)]
+    transform_3d: Option<Transform3D>,
+
     /// The next display object in order of execution.
     ///
     /// `None` in an AVM2 movie.
@@ -117,6 +157,10 @@ pub struct DisplayObjectBase<'gc> {
     /// changes immediately (without needing wait for a render)
     #[collect(require_static)]
     next_scroll_rect: Rectangle<Twips>,
+
+    /// The cached bitmap rendering of this display object, if it's cached as a bitmap
+    /// (either explicitly with `cacheAsBitmap`, or implicitly by having filters).
+    bitmap_cache: Option<BitmapCache<'gc>>,
 }
 
 impl<'gc> Default for DisplayObjectBase<'gc> {
@@ -128,11 +172,13 @@ impl<'gc> Default for DisplayObjectBase<'gc> {
             transform: Default::default(),
             name: Default::default(),
             filters: Avm2ArrayStorage::new(0),
+            render_filters: Vec::new(),
             clip_depth: Default::default(),
             rotation: Degrees::from_radians(0.0),
             scale_x: Percent::from_unit(1.0),
             scale_y: Percent::from_unit(1.0),
             skew: 0.0,
+            transform_3d: None,
             next_avm1_clip: None,
             masker: None,
             maskee: None,
@@ -142,6 +188,7 @@ impl<'gc> Default for DisplayObjectBase<'gc> {
             flags: DisplayObjectFlags::VISIBLE,
             scroll_rect: None,
             next_scroll_rect: Default::default(),
+            bitmap_cache: None,
         }
     }
 }
@@ -151,6 +198,7 @@ impl<'gc> DisplayObjectBase<'gc> {
     fn reset_for_movie_load(&mut self) {
         let flags_to_keep = self.flags & DisplayObjectFlags::LOCK_ROOT;
         self.flags = flags_to_keep | DisplayObjectFlags::VISIBLE;
+        self.update_bitmap_cache();
     }
 
     fn depth(&self) -> Depth {
@@ -169,8 +217,12 @@ impl<'gc> DisplayObjectBase<'gc> {
         self.place_frame = frame;
     }
 
-    fn transform(&self) -> &Transform {
-        &self.transform
+    /// The transform to render this object with, including its 3D transform if it has one.
+    fn transform(&self) -> Transform {
+        Transform {
+            matrix3d: self.matrix3d(),
+            ..self.transform.clone()
+        }
     }
 
     pub fn matrix(&self) -> &Matrix {
@@ -204,7 +256,10 @@ impl<'gc> DisplayObjectBase<'gc> {
 
     fn set_x(&mut self, value: f64) {
         self.set_transformed_by_script(true);
-        self.transform.matrix.tx = Twips::from_pixels(value)
+        self.transform.matrix.tx = Twips::from_pixels(value);
+        if let Some(matrix) = self.transform_3d.as_mut().and_then(|t| t.matrix.as_mut()) {
+            matrix.raw_data[12] = value;
+        }
     }
 
     fn y(&self) -> f64 {
@@ -213,7 +268,64 @@ impl<'gc> DisplayObjectBase<'gc> {
 
     fn set_y(&mut self, value: f64) {
         self.set_transformed_by_script(true);
-        self.transform.matrix.ty = Twips::from_pixels(value)
+        self.transform.matrix.ty = Twips::from_pixels(value);
+        if let Some(matrix) = self.transform_3d.as_mut().and_then(|t| t.matrix.as_mut()) {
+            matrix.raw_data[13] = value;
+        }
+    }
+
+    fn transform_3d(&self) -> Transform3D {
+        self.transform_3d.unwrap_or_default()
+    }
+
+    /// Changes the 3D properties, which stop following any matrix set through
+    /// `transform.matrix3D`.
+    fn set_transform_3d(&mut self, transform_3d: Transform3D) {
+        self.set_transformed_by_script(true);
+        self.transform_3d = Some(Transform3D {
+            matrix: None,
+            ..transform_3d
+        });
+    }
+
+    /// The 3D transform of this object relative to its parent, if it has any 3D properties.
+    fn matrix3d(&self) -> Option<Matrix3D> {
+        let transform_3d = self.transform_3d.as_ref()?;
+        if let Some(matrix) = transform_3d.matrix {
+            return Some(matrix);
+        }
+        // Like Flash, the object is scaled, then rotated around X, Y and Z in that order,
+        // and then moved. Any skew of the 2D matrix is lost.
+        let Matrix { a, b, c, d, .. } = self.transform.matrix;
+        let (a, b, c, d) = (f64::from(a), f64::from(b), f64::from(c), f64::from(d));
+        Some(
+            Matrix3D::translate(self.x(), self.y(), transform_3d.z)
+                * Matrix3D::rotate_z(f64::atan2(b, a))
+                * Matrix3D::rotate_y(transform_3d.rotation_y.into_radians())
+                * Matrix3D::rotate_x(transform_3d.rotation_x.into_radians())
+                * Matrix3D::scale(
+                    f64::sqrt(a * a + b * b),
+                    f64::sqrt(c * c + d * d),
+                    transform_3d.scale_z,
+                ),
+        )
+    }
+
+    /// Sets the 3D transform of this object, or makes it 2D again if `None`.
+    /// The 2D matrix becomes the closest approximation of the 3D one.
+    fn set_matrix3d(&mut self, matrix: Option<Matrix3D>) {
+        self.set_transformed_by_script(true);
+        match matrix {
+            Some(matrix) => {
+                self.set_matrix(matrix.to_affine());
+                self.transform_3d = Some(Transform3D {
+                    z: matrix.raw_data[14],
+                    matrix: Some(matrix),
+                    ..self.transform_3d()
+                });
+            }
+            None => self.transform_3d = None,
+        }
     }
 
     /// Caches the scale and rotation factors for this display object, if necessary.
@@ -320,6 +432,15 @@ impl<'gc> DisplayObjectBase<'gc> {
         self.filters = filters;
     }
 
+    fn render_filters(&self) -> &[Filter] {
+        &self.render_filters
+    }
+
+    fn set_render_filters(&mut self, filters: Vec<Filter>) {
+        self.render_filters = filters;
+        self.update_bitmap_cache();
+    }
+
     fn alpha(&self) -> f64 {
         f64::from(self.color_transform().a_mult)
     }
@@ -455,6 +576,18 @@ impl<'gc> DisplayObjectBase<'gc> {
 
     fn set_is_bitmap_cached(&mut self, value: bool) {
         self.flags.set(DisplayObjectFlags::CACHE_AS_BITMAP, value);
+        self.update_bitmap_cache();
+    }
+
+    /// Creates or removes the cached bitmap rendering, depending on whether it's now needed.
+    fn update_bitmap_cache(&mut self) {
+        if self.is_bitmap_cached() || !self.render_filters.is_empty() {
+            if self.bitmap_cache.is_none() {
+                self.bitmap_cache = Some(BitmapCache::default());
+            }
+        } else {
+            self.bitmap_cache = None;
+        }
     }
 
     fn instantiated_by_timeline(&self) -> bool {
@@ -501,18 +634,81 @@ impl<'gc> DisplayObjectBase<'gc> {
 }
 
 pub fn render_base<'gc>(this: DisplayObject<'gc>, context: &mut RenderContext<'_, 'gc>) {
-    if this.maskee().is_some() {
+    if this.maskee().is_some() && context.allow_mask {
+        // A mask is only drawn as part of the object that it masks
         return;
     }
-    context.transform_stack.push(this.base().transform());
-    let blend_mode = this.blend_mode();
-    let original_commands = if blend_mode != BlendMode::Normal {
+    render_object(this, context);
+}
+
+/// Renders a display object along with its transform, filters, blend mode, scroll rect and mask,
+/// regardless of whether it's a mask itself.
+fn render_object<'gc>(this: DisplayObject<'gc>, context: &mut RenderContext<'_, 'gc>) {
+    // Everything drawn into a mask only contributes its shape
+    let mut filters = if context.allow_mask {
+        this.render_filters()
+    } else {
+        vec![]
+    };
+    let was_offscreen = context.is_offscreen;
+    if !filters.is_empty() && !context.is_offscreen {
+        // Filters can spread the object out past its own bounds,
+        // so cull based on the area that the filtered result covers instead.
+        let culled = context.is_culled(|| {
+            let mut bounds = this.world_bounds();
+            for filter in &filters {
+                bounds = filter.calculate_dest_rect(bounds);
+            }
+            bounds
+        });
+        if culled {
+            return;
+        }
+        // Children can't be culled by their own bounds, as their filtered pixels may still be visible.
+        context.is_offscreen = true;
+    }
+    context.transform_stack.push(&this.base().transform());
+    // The filters can be cached along with the contents, unless something is drawn between them
+    let cached_filters = if !filters.is_empty()
+        && this.scroll_rect().is_none()
+        && this.masker().is_none()
+        && cache_with_filters(this, context, &filters)
+    {
+        std::mem::take(&mut filters)
+    } else {
+        vec![]
+    };
+    for filter in &mut filters {
+        if let Filter::DisplacementMapFilter(filter) = filter {
+            // The map is positioned relative to the object, but the filter is applied to the whole frame
+            let bounds = this.bounds_with_transform(&context.transform_stack.transform().matrix);
+            filter.map_point.0 += bounds.x_min.to_pixels().round() as i32;
+            filter.map_point.1 += bounds.y_min.to_pixels().round() as i32;
+        }
+    }
+    let blend_mode = if context.allow_mask {
+        this.blend_mode()
+    } else {
+        BlendMode::Normal
+    };
+    let original_commands = if blend_mode != BlendMode::Normal || !filters.is_empty() {
         Some(std::mem::take(&mut context.commands))
     } else {
         None
     };
+    // A layer is composited as a single group, so its color transform (most notably its alpha)
+    // is applied to the finished group rather than to each child, which would otherwise
+    // show seams wherever the children overlap.
+    let blend_color_transform = if blend_mode == BlendMode::Layer {
+        let color_transform = context.transform_stack.transform().color_transform;
+        context.transform_stack.push_without_color_transform();
+        color_transform
+    } else {
+        ColorTransform::IDENTITY
+    };
 
-    let scroll_rect_matrix = if let Some(rect) = this.scroll_rect() {
+    // Masks can't be nested, so the crop of a scroll rect inside of a mask is ignored
+    let scroll_rect_matrix = if let Some(rect) = this.scroll_rect().filter(|_| context.allow_mask) {
         let cur_transform = context.transform_stack.transform();
         // The matrix we use for actually drawing a rectangle for cropping purposes
         // Note that we do *not* apply the translation yet
@@ -532,21 +728,32 @@ pub fn render_base<'gc>(this: DisplayObject<'gc>, context: &mut RenderContext<'_
         context.transform_stack.push(&Transform {
             matrix: Matrix::translate(-rect.x_min, -rect.y_min),
             color_transform: Default::default(),
+            matrix3d: None,
         });
     }
 
     let mask = this.masker();
+    // Flash only masks by alpha when both the mask and the maskee are cached as bitmaps,
+    // and otherwise masks by shape using the stencil buffer.
+    let alpha_mask =
+        mask.filter(|m| context.allow_mask && this.is_bitmap_cached() && m.is_bitmap_cached());
+    let maskee_outer_commands = alpha_mask.map(|_| std::mem::take(&mut context.commands));
     let mut mask_transform = ruffle_render::transform::Transform::default();
     if let Some(m) = mask {
+        // The mask is rendered from wherever it is on the display list (if anywhere),
+        // so undo our own transform and apply that of its parent instead.
         mask_transform.matrix = this.global_to_local_matrix();
-        mask_transform.matrix *= m.local_to_global_matrix();
-        context.commands.push_mask();
-        context.allow_mask = false;
-        context.transform_stack.push(&mask_transform);
-        m.render_self(context);
-        context.transform_stack.pop();
-        context.allow_mask = true;
-        context.commands.activate_mask();
+        if let Some(parent) = m.parent() {
+            mask_transform.matrix *= parent.local_to_global_matrix();
+        }
+        if alpha_mask.is_none() && context.allow_mask {
+            context.commands.push_mask();
+            render_mask(m, &mask_transform, context);
+            context.commands.activate_mask();
+        } else if !context.allow_mask {
+            // Like a clip layer inside of a mask, a nested mask becomes part of the outer mask
+            render_mask(m, &mask_transform, context);
+        }
     }
 
     // There are two parts to 'DisplayObject.scrollRect':
@@ -567,7 +774,7 @@ pub fn render_base<'gc>(this: DisplayObject<'gc>, context: &mut RenderContext<'_
         context.commands.activate_mask();
     }
 
-    this.render_self(context);
+    render_contents(this, context, &cached_filters);
 
     if let Some(rect_mat) = scroll_rect_matrix {
         // Draw the rectangle again after deactivating the mask,
@@ -577,19 +784,38 @@ pub fn render_base<'gc>(this: DisplayObject<'gc>, context: &mut RenderContext<'_
         context.commands.pop_mask();
     }
 
-    if let Some(m) = mask {
-        context.commands.deactivate_mask();
-        context.allow_mask = false;
+    if let (Some(m), Some(outer_commands)) = (alpha_mask, maskee_outer_commands) {
+        let maskee = std::mem::replace(&mut context.commands, CommandList::new());
+        // The maskee's color transform applies to the masked result, not to the mask
+        context.transform_stack.push_without_color_transform();
         context.transform_stack.push(&mask_transform);
-        m.render_self(context);
+        render_object(m, context);
+        context.transform_stack.pop();
         context.transform_stack.pop();
-        context.allow_mask = true;
+        let mask = std::mem::replace(&mut context.commands, outer_commands);
+        context.commands.alpha_mask(maskee, mask);
+    } else if let Some(m) = mask.filter(|_| context.allow_mask) {
+        context.commands.deactivate_mask();
+        render_mask(m, &mask_transform, context);
         context.commands.pop_mask();
     }
 
     if let Some(original_commands) = original_commands {
-        let sub_commands = std::mem::replace(&mut context.commands, original_commands);
-        context.commands.blend(sub_commands, blend_mode);
+        let mut sub_commands = std::mem::replace(&mut context.commands, original_commands);
+        // Each filter is applied to the output of the previous one,
+        // and the blend mode is applied to the final filtered result.
+        if !filters.is_empty() {
+            let mut filtered = CommandList::new();
+            filtered.filters(sub_commands, filters);
+            sub_commands = filtered;
+        }
+        if blend_mode == BlendMode::Normal {
+            context.commands.commands.extend(sub_commands.commands);
+        } else {
+            context
+                .commands
+                .blend(sub_commands, blend_mode, blend_color_transform);
+        }
     }
 
     if scroll_rect_matrix.is_some() {
@@ -597,7 +823,260 @@ pub fn render_base<'gc>(this: DisplayObject<'gc>, context: &mut RenderContext<'_
         context.transform_stack.pop();
     }
 
+    if blend_mode == BlendMode::Layer {
+        context.transform_stack.pop();
+    }
+
     context.transform_stack.pop();
+    context.is_offscreen = was_offscreen;
+}
+
+/// Draws a mask (and all of its children) into the stencil buffer.
+///
+/// The mask is drawn even if it's invisible, as invisible masks still clip in Flash.
+fn render_mask<'gc>(
+    mask: DisplayObject<'gc>,
+    transform: &Transform,
+    context: &mut RenderContext<'_, 'gc>,
+) {
+    let allow_mask = context.allow_mask;
+    context.allow_mask = false;
+    context.transform_stack.push(transform);
+    mask.render(context);
+    context.transform_stack.pop();
+    context.allow_mask = allow_mask;
+}
+
+/// Brings the cached bitmap rendering of a display object up to date, with the given filters
+/// applied to it. Returns `false` if it can't be cached, in which case the filters must be
+/// applied separately.
+fn cache_with_filters<'gc>(
+    this: DisplayObject<'gc>,
+    context: &mut RenderContext<'_, 'gc>,
+    filters: &[Filter],
+) -> bool {
+    let Some(mut bitmap_cache) = this.base_mut(context.gc_context).bitmap_cache.take() else {
+        return false;
+    };
+    let transform = context.transform_stack.transform();
+    let matrix = transform.matrix;
+    // A cached bitmap is positioned in 2D, so anything in 3D is always rendered normally
+    let valid = !bitmap_cache.is_unsupported()
+        && context.allow_mask
+        && transform.matrix3d.is_none()
+        && (bitmap_cache.is_valid(&matrix, true)
+            || update_bitmap_cache(this, context, &mut bitmap_cache, &matrix, filters));
+    this.base_mut(context.gc_context).bitmap_cache = Some(bitmap_cache);
+    valid
+}
+
+/// Renders the contents of a display object, drawing its cached bitmap rendering instead
+/// if it's cached as a bitmap, and rendering that again first if it's out of date.
+///
+/// `filters` are the filters that are cached along with the contents, if any.
+fn render_contents<'gc>(
+    this: DisplayObject<'gc>,
+    context: &mut RenderContext<'_, 'gc>,
+    filters: &[Filter],
+) {
+    let bitmap_cache = this.base_mut(context.gc_context).bitmap_cache.take();
+    // A mask is drawn into the stencil buffer, which a bitmap would fill completely,
+    // and a cached bitmap can't be drawn with a 3D transform
+    let is_3d = context.transform_stack.transform().matrix3d.is_some();
+    let mut bitmap_cache = match bitmap_cache {
+        Some(bitmap_cache) if !bitmap_cache.is_unsupported() && context.allow_mask && !is_3d => {
+            bitmap_cache
+        }
+        bitmap_cache => {
+            this.base_mut(context.gc_context).bitmap_cache = bitmap_cache;
+            this.render_self(context);
+            return;
+        }
+    };
+
+    let transform = context.transform_stack.transform();
+    if !bitmap_cache.is_valid(&transform.matrix, !filters.is_empty())
+        && !update_bitmap_cache(this, context, &mut bitmap_cache, &transform.matrix, filters)
+    {
+        this.base_mut(context.gc_context).bitmap_cache = Some(bitmap_cache);
+        this.render_self(context);
+        return;
+    }
+
+    if let Some(handle) = bitmap_cache.handle() {
+        // Cached bitmaps are always drawn at whole pixels, like in Flash
+        let (offset_x, offset_y) = bitmap_cache.offset();
+        let x = Twips::from_pixels(transform.matrix.tx.to_pixels().round()) + offset_x;
+        let y = Twips::from_pixels(transform.matrix.ty.to_pixels().round()) + offset_y;
+        context.commands.render_bitmap(
+            handle,
+            Transform {
+                matrix: Matrix::translate(x, y),
+                color_transform: transform.color_transform,
+                matrix3d: None,
+            },
+            false,
+        );
+    }
+    if let Some(cache_dependencies) = &mut context.cache_dependencies {
+        cache_dependencies.extend(bitmap_cache.dependencies());
+    }
+    this.base_mut(context.gc_context).bitmap_cache = Some(bitmap_cache);
+}
+
+/// Renders the contents of a display object into its cached bitmap, with the given transform
+/// and then the given filters applied.
+/// Returns `false` if it can't be cached right now, in which case it should be rendered normally.
+fn update_bitmap_cache<'gc>(
+    this: DisplayObject<'gc>,
+    context: &mut RenderContext<'_, 'gc>,
+    bitmap_cache: &mut BitmapCache<'gc>,
+    matrix: &Matrix,
+    filters: &[Filter],
+) -> bool {
+    // Moving the object doesn't change the rendering, so it's made at the origin
+    let content_matrix = Matrix {
+        tx: Twips::ZERO,
+        ty: Twips::ZERO,
+        ..*matrix
+    };
+    let mut bounds = content_bounds(this, &content_matrix);
+    for filter in filters {
+        bounds = filter.calculate_dest_rect(bounds);
+    }
+    if !bounds.valid {
+        return false;
+    }
+    let x_min = bounds.x_min.to_pixels().floor();
+    let y_min = bounds.y_min.to_pixels().floor();
+    let width = (bounds.x_max.to_pixels().ceil() - x_min) as u32;
+    let height = (bounds.y_max.to_pixels().ceil() - y_min) as u32;
+    if width == 0
+        || height == 0
+        || width > MAX_CACHE_SIZE
+        || height > MAX_CACHE_SIZE
+        || width * height > MAX_CACHE_PIXELS
+    {
+        // Flash doesn't cache anything this big either
+        bitmap_cache.clear();
+        return false;
+    }
+
+    let pixels = vec![0; width as usize * height as usize * 4];
+    let handle = match bitmap_cache.take_bitmap(width, height) {
+        Some(handle) => {
+            if let Err(e) = context
+                .renderer
+                .update_texture(&handle, width, height, pixels)
+            {
+                tracing::warn!("Failed to clear cached bitmap: {:?}", e);
+                return false;
+            }
+            handle
+        }
+        None => {
+            let bitmap = ruffle_render::bitmap::Bitmap::new(
+                width,
+                height,
+                ruffle_render::bitmap::BitmapFormat::Rgba,
+                pixels,
+            );
+            match context.renderer.register_bitmap(bitmap) {
+                Ok(handle) => handle,
+                Err(e) => {
+                    tracing::warn!("Failed to register cached bitmap: {:?}", e);
+                    return false;
+                }
+            }
+        }
+    };
+
+    let offset = (Twips::from_pixels(x_min), Twips::from_pixels(y_min));
+    let mut transform_stack = ruffle_render::transform::TransformStack::new();
+    transform_stack.push(&Transform {
+        matrix: Matrix::translate(-offset.0, -offset.1) * content_matrix,
+        color_transform: Default::default(),
+        matrix3d: None,
+    });
+    let mut offscreen_context = RenderContext {
+        renderer: context.renderer,
+        commands: CommandList::new(),
+        gc_context: context.gc_context,
+        ui: context.ui,
+        library: context.library,
+        transform_stack: &mut transform_stack,
+        is_offscreen: true,
+        stage: context.stage,
+        clip_depth_stack: vec![],
+        allow_mask: true,
+        cache_dependencies: Some(Default::default()),
+        culling_stats: Default::default(),
+    };
+    this.render_self(&mut offscreen_context);
+    let mut commands = offscreen_context.commands;
+    if !filters.is_empty() {
+        let mut filters = filters.to_vec();
+        let object_matrix = offscreen_context.transform_stack.transform().matrix;
+        let object_bounds = this.bounds_with_transform(&object_matrix);
+        for filter in &mut filters {
+            if let Filter::DisplacementMapFilter(filter) = filter {
+                // The map is positioned relative to the object, not the bitmap
+                filter.map_point.0 += object_bounds.x_min.to_pixels().round() as i32;
+                filter.map_point.1 += object_bounds.y_min.to_pixels().round() as i32;
+            }
+        }
+        let mut filtered = CommandList::new();
+        filtered.filters(commands, filters);
+        commands = filtered;
+    }
+    let dependencies = offscreen_context.cache_dependencies.unwrap_or_default();
+
+    let quality = context.stage.quality();
+    if context
+        .renderer
+        .render_offscreen(handle.clone(), width, height, commands, quality)
+        .is_none()
+    {
+        // This renderer doesn't support it, so don't try again
+        bitmap_cache.set_unsupported();
+        return false;
+    }
+    bitmap_cache.update(
+        matrix,
+        !filters.is_empty(),
+        offset,
+        (handle, width, height),
+        dependencies,
+    );
+    true
+}
+
+/// The area covered by the contents of a display object when drawn with the given transform,
+/// including anything that the filters of its children spread out into.
+/// Unlike `bounds_with_transform`, this ignores the object's own scroll rect.
+fn content_bounds<'gc>(this: DisplayObject<'gc>, matrix: &Matrix) -> BoundingBox {
+    let Some(container) = this.as_container() else {
+        return if this.scroll_rect().is_some() {
+            this.self_bounds().transform(matrix)
+        } else {
+            this.bounds_with_transform(matrix)
+        };
+    };
+
+    let mut bounds = this.self_bounds().transform(matrix);
+    for child in container.iter_render_list() {
+        let matrix = *matrix * *child.base().matrix();
+        let mut child_bounds = if child.scroll_rect().is_some() {
+            child.bounds_with_transform(&matrix)
+        } else {
+            content_bounds(child, &matrix)
+        };
+        for filter in child.render_filters() {
+            child_bounds = filter.calculate_dest_rect(child_bounds);
+        }
+        bounds.union(&child_bounds);
+    }
+    bounds
 }
 
 #[enum_trait_object(
@@ -703,6 +1182,7 @@ pub trait TDisplayObject<'gc>:
 
     fn set_matrix(&self, gc_context: MutationContext<'gc, '_>, matrix: Matrix) {
         self.base_mut(gc_context).set_matrix(matrix);
+        self.invalidate_parent_cached_bitmap(gc_context);
     }
 
     fn set_color_transform(
@@ -711,7 +1191,8 @@ pub trait TDisplayObject<'gc>:
         color_transform: ColorTransform,
     ) {
         self.base_mut(gc_context)
-            .set_color_transform(color_transform)
+            .set_color_transform(color_transform);
+        self.invalidate_parent_cached_bitmap(gc_context);
     }
 
     /// Should only be used to implement 'Transform.concatenatedMatrix'
@@ -773,6 +1254,7 @@ pub trait TDisplayObject<'gc>:
     /// Set by the `_x`/`x` ActionScript properties.
     fn set_x(&self, gc_context: MutationContext<'gc, '_>, value: f64) {
         self.base_mut(gc_context).set_x(value);
+        self.invalidate_parent_cached_bitmap(gc_context);
     }
 
     /// The `y` position in pixels of this display object in local space.
@@ -785,6 +1267,7 @@ pub trait TDisplayObject<'gc>:
     /// Set by the `_y`/`y` ActionScript properties.
     fn set_y(&self, gc_context: MutationContext<'gc, '_>, value: f64) {
         self.base_mut(gc_context).set_y(value);
+        self.invalidate_parent_cached_bitmap(gc_context);
     }
 
     /// The rotation in degrees this display object in local space.
@@ -799,9 +1282,36 @@ pub trait TDisplayObject<'gc>:
     /// Set by the `_rotation`/`rotation` ActionScript properties.
     fn set_rotation(&self, gc_context: MutationContext<'gc, '_>, radians: Degrees) {
         self.base_mut(gc_context).set_rotation(radians);
+        self.invalidate_parent_cached_bitmap(gc_context);
         self.set_scale_rotation_cached(gc_context);
     }
 
+    /// The 3D properties of this display object, which are all neutral unless any were set.
+    /// Returned by the AVM2 `z`, `rotationX`, `rotationY` and `scaleZ` properties.
+    fn transform_3d(&self) -> Transform3D {
+        self.base().transform_3d()
+    }
+
+    /// Sets the 3D properties of this display object, which makes it render in 3D.
+    /// Set by the AVM2 `z`, `rotationX`, `rotationY` and `scaleZ` properties.
+    fn set_transform_3d(&self, gc_context: MutationContext<'gc, '_>, transform_3d: Transform3D) {
+        self.base_mut(gc_context).set_transform_3d(transform_3d);
+        self.invalidate_parent_cached_bitmap(gc_context);
+    }
+
+    /// The 3D transform of this display object relative to its parent, if it's in 3D.
+    /// Returned by the AVM2 `transform.matrix3D` property.
+    fn matrix3d(&self) -> Option<Matrix3D> {
+        self.base().matrix3d()
+    }
+
+    /// Sets the 3D transform of this display object, or makes it 2D again if `None`.
+    /// Set by the AVM2 `transform.matrix3D` property.
+    fn set_matrix3d(&self, gc_context: MutationContext<'gc, '_>, matrix: Option<Matrix3D>) {
+        self.base_mut(gc_context).set_matrix3d(matrix);
+        self.invalidate_parent_cached_bitmap(gc_context);
+    }
+
     /// The X axis scale for this display object in local space.
     /// Returned by the `_xscale`/`scaleX` ActionScript properties.
     fn scale_x(&self, gc_context: MutationContext<'gc, '_>) -> Percent {
@@ -814,6 +1324,7 @@ pub trait TDisplayObject<'gc>:
     /// Set by the `_xscale`/`scaleX` ActionScript properties.
     fn set_scale_x(&self, gc_context: MutationContext<'gc, '_>, value: Percent) {
         self.base_mut(gc_context).set_scale_x(value);
+        self.invalidate_parent_cached_bitmap(gc_context);
         self.set_scale_rotation_cached(gc_context);
     }
 
@@ -829,6 +1340,7 @@ pub trait TDisplayObject<'gc>:
     /// Returned by the `_yscale`/`scaleY` ActionScript properties.
     fn set_scale_y(&self, gc_context: MutationContext<'gc, '_>, value: Percent) {
         self.base_mut(gc_context).set_scale_y(value);
+        self.invalidate_parent_cached_bitmap(gc_context);
         self.set_scale_rotation_cached(gc_context);
     }
 
@@ -938,7 +1450,8 @@ pub trait TDisplayObject<'gc>:
     /// 1 is fully opaque.
     /// Set by the `_alpha`/`alpha` ActionScript properties.
     fn set_alpha(&self, gc_context: MutationContext<'gc, '_>, value: f64) {
-        self.base_mut(gc_context).set_alpha(value)
+        self.base_mut(gc_context).set_alpha(value);
+        self.invalidate_parent_cached_bitmap(gc_context);
     }
 
     fn name(&self) -> AvmString<'gc> {
@@ -956,6 +1469,17 @@ pub trait TDisplayObject<'gc>:
         self.base_mut(gc_context).set_filters(filters)
     }
 
+    /// The filters used when rendering this display object.
+    fn render_filters(&self) -> Vec<Filter> {
+        self.base().render_filters().to_vec()
+    }
+
+    fn set_render_filters(&self, gc_context: MutationContext<'gc, '_>, filters: Vec<Filter>) {
+        self.base_mut(gc_context).set_render_filters(filters);
+        // The filters are usually part of the object's own cached bitmap
+        self.invalidate_cached_bitmap(gc_context);
+    }
+
     /// Returns the dot-syntax path to this display object, e.g. `_level0.foo.clip`
     fn path(&self) -> WString {
         if let Some(parent) = self.avm1_parent() {
@@ -1002,6 +1526,7 @@ pub trait TDisplayObject<'gc>:
     }
     fn set_clip_depth(&self, gc_context: MutationContext<'gc, '_>, depth: Depth) {
         self.base_mut(gc_context).set_clip_depth(depth);
+        self.invalidate_parent_cached_bitmap(gc_context);
     }
 
     /// Retrieve the parent of this display object.
@@ -1059,6 +1584,7 @@ pub trait TDisplayObject<'gc>:
             }
         }
         self.base_mut(gc_context).set_masker(node);
+        self.invalidate_parent_cached_bitmap(gc_context);
     }
     fn maskee(&self) -> Option<DisplayObject<'gc>> {
         self.base().maskee()
@@ -1075,6 +1601,7 @@ pub trait TDisplayObject<'gc>:
             }
         }
         self.base_mut(gc_context).set_maskee(node);
+        self.invalidate_parent_cached_bitmap(gc_context);
     }
 
     fn scroll_rect(&self) -> Option<Rectangle<Twips>> {
@@ -1091,6 +1618,7 @@ pub trait TDisplayObject<'gc>:
         rectangle: Rectangle<Twips>,
     ) {
         self.base_mut(gc_context).next_scroll_rect = rectangle;
+        self.invalidate_parent_cached_bitmap(gc_context);
     }
 
     fn removed(&self) -> bool {
@@ -1118,6 +1646,7 @@ pub trait TDisplayObject<'gc>:
     /// Returned by the `_visible`/`visible` ActionScript properties.
     fn set_visible(&self, gc_context: MutationContext<'gc, '_>, value: bool) {
         self.base_mut(gc_context).set_visible(value);
+        self.invalidate_parent_cached_bitmap(gc_context);
     }
 
     /// The blend mode used when rendering this display object.
@@ -1130,6 +1659,7 @@ pub trait TDisplayObject<'gc>:
     /// Values other than the defualt `BlendMode::Normal` implicitly cause cache-as-bitmap behavior.
     fn set_blend_mode(&self, gc_context: MutationContext<'gc, '_>, value: BlendMode) {
         self.base_mut(gc_context).set_blend_mode(value);
+        self.invalidate_parent_cached_bitmap(gc_context);
     }
 
     /// The opaque background color of this display object.
@@ -1143,6 +1673,7 @@ pub trait TDisplayObject<'gc>:
     /// is ignored.
     fn set_opaque_background(&self, gc_context: MutationContext<'gc, '_>, value: Option<Color>) {
         self.base_mut(gc_context).set_opaque_background(value);
+        self.invalidate_parent_cached_bitmap(gc_context);
     }
 
     /// Whether this display object represents the root of loaded content.
@@ -1200,7 +1731,8 @@ pub trait TDisplayObject<'gc>:
     /// Explicilty sets this display object to be cached into a bitmap rendering.
     /// Note that the object will still be bitmap cached if a filter or blend mode is active.
     fn set_is_bitmap_cached(&self, gc_context: MutationContext<'gc, '_>, value: bool) {
-        self.base_mut(gc_context).set_is_bitmap_cached(value)
+        self.base_mut(gc_context).set_is_bitmap_cached(value);
+        self.invalidate_parent_cached_bitmap(gc_context);
     }
 
     /// Whether this display object has a scroll rectangle applied.
@@ -1210,7 +1742,30 @@ pub trait TDisplayObject<'gc>:
 
     /// Sets whether this display object has a scroll rectangle applied.
     fn set_has_scroll_rect(&self, gc_context: MutationContext<'gc, '_>, value: bool) {
-        self.base_mut(gc_context).set_has_scroll_rect(value)
+        self.base_mut(gc_context).set_has_scroll_rect(value);
+        self.invalidate_parent_cached_bitmap(gc_context);
+    }
+
+    /// Marks the cached bitmap renderings of this display object and everything containing it as
+    /// out of date, because the contents of this display object have changed.
+    fn invalidate_cached_bitmap(&self, gc_context: MutationContext<'gc, '_>) {
+        if let Some(bitmap_cache) = &mut self.base_mut(gc_context).bitmap_cache {
+            bitmap_cache.make_dirty();
+        }
+        self.invalidate_parent_cached_bitmap(gc_context);
+    }
+
+    /// Marks the cached bitmap renderings of everything containing this display object as
+    /// out of date, because the way this display object is drawn into them has changed.
+    /// Its own cached bitmap is still valid, as that doesn't include its transform.
+    fn invalidate_parent_cached_bitmap(&self, gc_context: MutationContext<'gc, '_>) {
+        if let Some(parent) = self.parent() {
+            parent.invalidate_cached_bitmap(gc_context);
+        }
+        // A mask is drawn as part of the object it masks
+        if let Some(maskee) = self.maskee() {
+            maskee.invalidate_parent_cached_bitmap(gc_context);
+        }
     }
 
     /// Called whenever the focus tracker has deemed this display object worthy, or no longer worthy,
@@ -1538,6 +2093,12 @@ pub trait TDisplayObject<'gc>:
             if let Some(blend_mode) = place_object.blend_mode {
                 self.set_blend_mode(context.gc_context, blend_mode);
             }
+            if let Some(filters) = &place_object.filters {
+                self.set_render_filters(
+                    context.gc_context,
+                    filters.iter().filter_map(Filter::from_swf).collect(),
+                );
+            }
             if self.swf_version() >= 11 {
                 if let Some(visible) = place_object.is_visible {
                     self.set_visible(context.gc_context, visible);
@@ -1557,7 +2118,6 @@ pub trait TDisplayObject<'gc>:
             // name, clip_depth, clip_actions
             // These properties are only set on initial placement in `MovieClip::instantiate_child`
             // and can not be modified by subsequent PlaceObject tags.
-            // TODO: Filters need to be applied here.
         }
     }
 
@@ -1581,6 +2141,18 @@ pub trait TDisplayObject<'gc>:
         self.world_bounds().contains(pos)
     }
 
+    /// Tests if a given stage position point is inside the scroll rect of this object,
+    /// if it has one. Anything outside of the scroll rect is clipped away, so it can't be clicked.
+    fn hit_test_scroll_rect(&self, pos: (Twips, Twips)) -> bool {
+        if let Some(rect) = self.scroll_rect() {
+            // This is relative to the scrolled contents, which the rect is also relative to
+            let (x, y) = self.global_to_local(pos);
+            x >= rect.x_min && x < rect.x_max && y >= rect.y_min && y < rect.y_max
+        } else {
+            true
+        }
+    }
+
     /// Tests if a given object's world bounds intersects with the world bounds
     /// of this object.
     fn hit_test_object(&self, other: DisplayObject<'gc>) -> bool {
@@ -535,7 +535,7 @@ impl<'gc> TInteractiveObject<'gc> for Avm1Button<'gc> {
         require_button_mode: bool,
     ) -> Option<InteractiveObject<'gc>> {
         // The button is hovered if the mouse is over any child nodes.
-        if self.visible() && self.mouse_enabled() {
+        if self.visible() && self.mouse_enabled() && self.hit_test_scroll_rect(point) {
             for child in self.iter_render_list().rev() {
                 let result = child
                     .as_interactive()
@@ -285,6 +285,8 @@ impl<'gc> Avm2Button<'gc> {
         if let Some(state) = self.get_state_child(state.into()) {
             state.set_parent(context.gc_context, Some(self.into()));
         }
+        drop(button);
+        self.invalidate_cached_bitmap(context.gc_context);
     }
 
     /// Get the display object that represents a particular button state.
@@ -327,6 +329,10 @@ impl<'gc> Avm2Button<'gc> {
             }
         }
 
+        if is_cur_state {
+            self.invalidate_cached_bitmap(context.gc_context);
+        }
+
         if let Some(old_state_child) = old_state_child {
             old_state_child.unload(context);
             old_state_child.set_parent(context.gc_context, None);
@@ -831,7 +837,7 @@ impl<'gc> TInteractiveObject<'gc> for Avm2Button<'gc> {
         require_button_mode: bool,
     ) -> Avm2MousePick<'gc> {
         // The button is hovered if the mouse is over any child nodes.
-        if self.visible() && self.mouse_enabled() {
+        if self.visible() && self.mouse_enabled() && self.hit_test_scroll_rect(point) {
             let state = self.0.read().state;
             let state_child = self.get_state_child(state.into());
 
@@ -14,6 +14,7 @@ use crate::vminterface::Instantiator;
 use core::fmt;
 use gc_arena::{Collect, GcCell, MutationContext};
 use ruffle_render::bitmap::BitmapFormat;
+use ruffle_render::quality::StageQuality;
 use std::cell::{Ref, RefMut};
 use std::sync::Arc;
 
@@ -122,6 +123,13 @@ impl<'gc> Bitmap<'gc> {
         id: CharacterId,
         bitmap: ruffle_render::bitmap::Bitmap,
     ) -> Result<Self, ruffle_render::error::Error> {
+        // Bitmap data is always premultiplied, with 8 bits per channel
+        let bitmap = match bitmap.format() {
+            BitmapFormat::StraightRgba | BitmapFormat::Rgba16 | BitmapFormat::Rgba16Float => {
+                bitmap.to_rgba()
+            }
+            _ => bitmap,
+        };
         let width = bitmap.width();
         let height = bitmap.height();
         let pixels: Vec<_> = bitmap
@@ -133,7 +141,10 @@ impl<'gc> Bitmap<'gc> {
             width,
             height,
             match bitmap.format() {
-                BitmapFormat::Rgba => true,
+                BitmapFormat::Rgba
+                | BitmapFormat::StraightRgba
+                | BitmapFormat::Rgba16
+                | BitmapFormat::Rgba16Float => true,
                 BitmapFormat::Rgb => false,
             },
             pixels,
@@ -180,6 +191,7 @@ impl<'gc> Bitmap<'gc> {
         bitmap_data: GcCell<'gc, crate::bitmap::bitmap_data::BitmapData<'gc>>,
     ) {
         self.0.write(context.gc_context).bitmap_data = BitmapDataWrapper::new(bitmap_data);
+        self.invalidate_cached_bitmap(context.gc_context);
     }
 
     pub fn avm2_bitmapdata_class(self) -> Option<Avm2ClassObject<'gc>> {
@@ -218,6 +230,7 @@ impl<'gc> Bitmap<'gc> {
 
     pub fn set_smoothing(self, mc: MutationContext<'gc, '_>, smoothing: bool) {
         self.0.write(mc).smoothing = smoothing;
+        self.invalidate_cached_bitmap(mc);
     }
 }
 
@@ -288,15 +301,15 @@ impl<'gc> TDisplayObject<'gc> for Bitmap<'gc> {
     }
 
     fn render_self(&self, context: &mut RenderContext<'_, 'gc>) {
-        if !context.is_offscreen && !self.world_bounds().intersects(&context.stage.view_bounds()) {
+        if context.is_culled(|| self.world_bounds()) {
             // Off-screen; culled
             return;
         }
 
         let bitmap_data = self.0.read();
-        bitmap_data
-            .bitmap_data
-            .render(bitmap_data.smoothing, context);
+        // Low quality never smooths bitmaps, regardless of the bitmap's own setting.
+        let smoothing = bitmap_data.smoothing && context.stage.quality() != StageQuality::Low;
+        bitmap_data.bitmap_data.render(smoothing, context);
     }
 
     fn object2(&self) -> Avm2Value<'gc> {
@@ -0,0 +1,180 @@
+//! Caching of display objects into bitmaps, for `cacheAsBitmap`
+
+use crate::bitmap::bitmap_data::BitmapData;
+use gc_arena::{Collect, GcCell};
+use ruffle_render::bitmap::BitmapHandle;
+use ruffle_render::matrix::Matrix;
+use swf::Twips;
+
+/// The largest width or height that Flash will cache a display object at.
+/// Anything larger than this is rendered normally instead.
+pub const MAX_CACHE_SIZE: u32 = 8191;
+
+/// The largest number of pixels that Flash will cache a display object at.
+pub const MAX_CACHE_PIXELS: u32 = 16_777_215;
+
+/// Everything that the contents of a cached bitmap rendering depend on,
+/// besides the display objects themselves (which invalidate the cache when they change).
+#[derive(Clone, Collect, Default)]
+#[collect(no_drop)]
+pub struct CacheDependencies<'gc> {
+    /// The bitmaps that were drawn, along with the generation of each that was drawn.
+    bitmaps: Vec<(GcCell<'gc, BitmapData<'gc>>, u32)>,
+
+    /// Whether anything was drawn that can change without invalidating the cache,
+    /// such as a video or a text field. Such a rendering is only ever used once.
+    volatile: bool,
+}
+
+impl<'gc> CacheDependencies<'gc> {
+    pub fn add_bitmap(&mut self, bitmap_data: GcCell<'gc, BitmapData<'gc>>, generation: u32) {
+        self.bitmaps.push((bitmap_data, generation));
+    }
+
+    pub fn set_volatile(&mut self) {
+        self.volatile = true;
+    }
+
+    /// Adds all of the dependencies of a nested rendering to this one.
+    pub fn extend(&mut self, other: &Self) {
+        self.bitmaps.extend_from_slice(&other.bitmaps);
+        self.volatile |= other.volatile;
+    }
+
+    /// Whether anything that was drawn has changed since.
+    pub fn has_changed(&self) -> bool {
+        self.volatile
+            || self.bitmaps.iter().any(|(bitmap_data, generation)| {
+                // If the bitmap is being written to, it's about to change anyway
+                bitmap_data
+                    .try_read()
+                    .map(|bitmap_data| bitmap_data.generation() != *generation)
+                    .unwrap_or(true)
+            })
+    }
+}
+
+/// A rendering of the contents of a display object, which can be drawn again on later frames
+/// until the object or any of its children are changed.
+#[derive(Collect)]
+#[collect(no_drop)]
+pub struct BitmapCache<'gc> {
+    /// Whether the object has changed since it was rendered.
+    dirty: bool,
+
+    /// Whether the render backend can't render into a cached bitmap,
+    /// in which case the object is always rendered normally instead.
+    unsupported: bool,
+
+    /// The scale, rotation and skew that the contents were rendered with.
+    /// Moving the object doesn't require them to be rendered again, but anything else does.
+    #[collect(require_static)]
+    matrix: Option<(f32, f32, f32, f32)>,
+
+    /// Whether the object's filters were applied to the rendering.
+    /// They're left out when something has to be drawn between the contents and the filters,
+    /// such as a mask or a scroll rect.
+    filtered: bool,
+
+    /// The position of the top left corner of the bitmap, relative to the object's origin.
+    #[collect(require_static)]
+    offset: (Twips, Twips),
+
+    /// The rendered contents, along with their width and height in pixels.
+    #[collect(require_static)]
+    bitmap: Option<(BitmapHandle, u32, u32)>,
+
+    dependencies: CacheDependencies<'gc>,
+}
+
+impl<'gc> Default for BitmapCache<'gc> {
+    fn default() -> Self {
+        Self {
+            dirty: true,
+            unsupported: false,
+            matrix: None,
+            filtered: false,
+            offset: (Twips::ZERO, Twips::ZERO),
+            bitmap: None,
+            dependencies: Default::default(),
+        }
+    }
+}
+
+impl<'gc> Clone for BitmapCache<'gc> {
+    fn clone(&self) -> Self {
+        // A copy of the object must render into its own bitmap
+        Self {
+            unsupported: self.unsupported,
+            ..Default::default()
+        }
+    }
+}
+
+impl<'gc> BitmapCache<'gc> {
+    pub fn make_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    pub fn is_unsupported(&self) -> bool {
+        self.unsupported
+    }
+
+    pub fn set_unsupported(&mut self) {
+        self.unsupported = true;
+        self.bitmap = None;
+    }
+
+    /// Whether the cached rendering can be drawn again with the given transform,
+    /// either with or without the object's filters applied.
+    pub fn is_valid(&self, matrix: &Matrix, filtered: bool) -> bool {
+        !self.dirty
+            && self.matrix == Some((matrix.a, matrix.b, matrix.c, matrix.d))
+            && self.filtered == filtered
+            && !self.dependencies.has_changed()
+    }
+
+    /// Forgets the previous rendering, such as when the object is now too large to be cached.
+    pub fn clear(&mut self) {
+        self.bitmap = None;
+        self.dependencies = Default::default();
+    }
+
+    /// Returns a bitmap of the given size to render into, reusing the previous one if possible.
+    /// The caller is responsible for clearing a reused bitmap.
+    pub fn take_bitmap(&mut self, width: u32, height: u32) -> Option<BitmapHandle> {
+        match self.bitmap.take() {
+            Some((handle, w, h)) if w == width && h == height => Some(handle),
+            _ => None,
+        }
+    }
+
+    /// Records a new rendering of the contents.
+    pub fn update(
+        &mut self,
+        matrix: &Matrix,
+        filtered: bool,
+        offset: (Twips, Twips),
+        bitmap: (BitmapHandle, u32, u32),
+        dependencies: CacheDependencies<'gc>,
+    ) {
+        self.dirty = false;
+        self.matrix = Some((matrix.a, matrix.b, matrix.c, matrix.d));
+        self.filtered = filtered;
+        self.offset = offset;
+        self.bitmap = Some(bitmap);
+        self.dependencies = dependencies;
+    }
+
+    pub fn offset(&self) -> (Twips, Twips) {
+        self.offset
+    }
+
+    pub fn handle(&self) -> Option<BitmapHandle> {
+        self.bitmap.as_ref().map(|(handle, _, _)| handle.clone())
+    }
+
+    pub fn dependencies(&self) -> &CacheDependencies<'gc> {
+        &self.dependencies
+    }
+}
@@ -210,6 +210,9 @@ pub trait TDisplayObjectContainer<'gc>:
         child.set_place_frame(context.gc_context, 0);
         child.set_depth(context.gc_context, depth);
 
+        let this: DisplayObject<'gc> = self.into();
+        this.invalidate_cached_bitmap(context.gc_context);
+
         if let Some(removed_child) = removed_child {
             removed_child.unload(context);
             removed_child.set_parent(context.gc_context, None);
@@ -243,6 +246,7 @@ pub trait TDisplayObjectContainer<'gc>:
             child,
             depth,
         );
+        this.invalidate_cached_bitmap(context.gc_context);
     }
 
     /// Insert a child display object into the container at a specific position
@@ -280,6 +284,7 @@ pub trait TDisplayObjectContainer<'gc>:
 
         self.raw_container_mut(context.gc_context)
             .insert_at_id(child, index);
+        this.invalidate_cached_bitmap(context.gc_context);
 
         if parent_changed {
             dispatch_added_event(this, child, child_was_on_stage, context);
@@ -297,6 +302,8 @@ pub trait TDisplayObjectContainer<'gc>:
     ) {
         self.raw_container_mut(context.gc_context)
             .swap_at_id(index1, index2);
+        let this: DisplayObject<'gc> = (*self).into();
+        this.invalidate_cached_bitmap(context.gc_context);
     }
 
     /// Remove (and unloads) a child display object from this container's render and depth lists.
@@ -334,6 +341,10 @@ pub trait TDisplayObjectContainer<'gc>:
 
                 // Re-Insert the child at the new depth
                 raw_container.insert_child_into_depth_list(child.depth(), child);
+                drop(raw_container);
+
+                let this: DisplayObject<'gc> = (*self).into();
+                this.invalidate_cached_bitmap(context.gc_context);
 
                 return;
             }
@@ -355,6 +366,9 @@ pub trait TDisplayObjectContainer<'gc>:
         let removed_from_render_list = write.remove_child_from_render_list(child);
         drop(write);
 
+        let this: DisplayObject<'gc> = (*self).into();
+        this.invalidate_cached_bitmap(context.gc_context);
+
         if removed_from_render_list {
             child.unload(context);
 
@@ -380,6 +394,8 @@ pub trait TDisplayObjectContainer<'gc>:
 
         self.raw_container_mut(context.gc_context)
             .remove_child_from_depth_list(child);
+        let this: DisplayObject<'gc> = (*self).into();
+        this.invalidate_cached_bitmap(context.gc_context);
     }
 
     /// Remove a set of children identified by their render list indicies from
@@ -416,6 +432,10 @@ pub trait TDisplayObjectContainer<'gc>:
 
             write = self.raw_container_mut(context.gc_context);
         }
+        drop(write);
+
+        let this: DisplayObject<'gc> = (*self).into();
+        this.invalidate_cached_bitmap(context.gc_context);
     }
 
     /// Determine if the container is empty.
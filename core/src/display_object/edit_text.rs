@@ -1,5 +1,6 @@
 //! `EditText` display object and support code.
 
+use crate::avm1::object::NativeObject;
 use crate::avm1::Avm1;
 use crate::avm1::ExecutionReason;
 use crate::avm1::{Activation as Avm1Activation, ActivationIdentifier};
@@ -10,16 +11,22 @@ use crate::avm1::{
 use crate::avm2::{
     Activation as Avm2Activation, Object as Avm2Object, StageObject as Avm2StageObject,
 };
+use crate::backend::navigator::Request;
 use crate::backend::ui::MouseCursor;
 use crate::context::{RenderContext, UpdateContext};
 use crate::display_object::interactive::{
     InteractiveObject, InteractiveObjectBase, TInteractiveObject,
 };
-use crate::display_object::{DisplayObjectBase, DisplayObjectPtr, TDisplayObject};
+use crate::display_object::{
+    render_base, DisplayObjectBase, DisplayObjectPtr, MovieClip, TDisplayObject,
+};
 use crate::drawing::Drawing;
 use crate::events::{ButtonKeyCode, ClipEvent, ClipEventResult, KeyCode};
 use crate::font::{round_down_to_pixel, Glyph, TextRenderSettings};
-use crate::html::{BoxBounds, FormatSpans, LayoutBox, LayoutContent, LayoutMetrics, TextFormat};
+use crate::html::{
+    BoxBounds, FormatSpans, LayoutBox, LayoutContent, LayoutMetrics, Position, Size, TextFormat,
+    TextImage,
+};
 use crate::prelude::*;
 use crate::string::{utils as string_utils, AvmString, WStr, WString};
 use crate::tag_utils::SwfMovie;
@@ -123,6 +130,10 @@ pub struct EditTextData<'gc> {
     /// The selected portion of the text, or None if the text is not selected.
     selection: Option<TextSelection>,
 
+    /// The portion of the text that an input method is composing, which is shown
+    /// underlined until the input method commits it.
+    ime_composition: Option<TextSelection>,
+
     /// Which rendering engine this text field will use.
     render_settings: TextRenderSettings,
 
@@ -139,19 +150,48 @@ pub struct EditTextData<'gc> {
     /// Doesn't affect script-triggered modifications.
     max_chars: i32,
 
+    /// The AVM1 `TextField.StyleSheet` that the HTML text is styled with.
+    ///
+    /// Text fields can't be edited by the user while they have one.
+    style_sheet: Option<Avm1Object<'gc>>,
+
+    /// The HTML that the text was last set to, which is styled again whenever the
+    /// style sheet changes, and is what `htmlText` gives back while there's a style sheet.
+    html_source: Option<WString>,
+
+    /// The clips showing the text's `<img>` images, in the order they appear in the text.
+    images: Vec<HtmlImage<'gc>>,
+
     /// Flags indicating the text field's settings.
     flags: EditTextFlag,
 }
 
+/// An image from an `<img>` tag, and the clip that shows it.
+#[derive(Clone, Collect)]
+#[collect(no_drop)]
+struct HtmlImage<'gc> {
+    #[collect(require_static)]
+    image: TextImage,
+
+    /// The library symbol, or the clip that the image is loaded into.
+    clip: DisplayObject<'gc>,
+
+    /// The bounds of the clip when the text was last laid out, which the text is laid
+    /// out again for when they change, such as when the image finishes loading.
+    #[collect(require_static)]
+    laid_out_bounds: BoundingBox,
+}
+
 // TODO: would be nicer to compute (and return) this during layout, instead of afterwards
 /// Compute line (index, offset, extent) from the layout data.
 fn get_line_data(layout: &[LayoutBox]) -> Vec<LineData> {
+    // Images are beside lines rather than part of them
+    let mut layout = layout.iter().filter(|layout_box| !layout_box.is_image());
+
     // if there are no boxes, there are no lines
-    if layout.is_empty() {
+    let Some(first_box) = layout.next() else {
         return Vec::new();
-    }
-
-    let first_box = &layout[0];
+    };
 
     let mut index = 1;
     let mut offset = first_box.bounds().offset_y();
@@ -159,7 +199,7 @@ fn get_line_data(layout: &[LayoutBox]) -> Vec<LineData> {
 
     let mut line_data = Vec::new();
 
-    for layout_box in layout.get(1..).unwrap() {
+    for layout_box in layout {
         let bounds = layout_box.bounds();
 
         // if the top of the new box is lower than the bottom of the old box, it's a new line
@@ -204,10 +244,11 @@ impl<'gc> EditText<'gc> {
 
         let text = WString::from_utf8(&text.to_str_lossy(encoding));
         let mut text_spans = if swf_tag.is_html() {
-            FormatSpans::from_html(&text, default_format, swf_tag.is_multiline())
+            FormatSpans::from_html(&text, default_format, None, swf_tag.is_multiline())
         } else {
-            FormatSpans::from_text(text, default_format)
+            FormatSpans::from_text(text.clone(), default_format)
         };
+        let html_source = swf_tag.is_html().then_some(text);
 
         if swf_tag.is_password() {
             text_spans.hide_text();
@@ -228,8 +269,12 @@ impl<'gc> EditText<'gc> {
             bounds.width() - Twips::from_pixels(Self::INTERNAL_PADDING * 2.0),
             swf_tag.is_word_wrap(),
             !swf_tag.use_outlines(),
+            &[],
         );
         let line_data = get_line_data(&layout);
+        let has_images = text_spans.iter_spans().any(|(_start, _end, text, span)| {
+            span.image.is_some() && text.get(0) == Some(TextImage::CHARACTER)
+        });
 
         let mut base = InteractiveObjectBase::default();
 
@@ -280,15 +325,21 @@ impl<'gc> EditText<'gc> {
                 variable: variable.map(|s| s.to_string_lossy(encoding)),
                 bound_stage_object: None,
                 selection: None,
+                ime_composition: None,
                 render_settings: Default::default(),
                 hscroll: 0.0,
                 line_data,
                 scroll: 1,
                 max_chars: 0,
+                style_sheet: None,
+                html_source,
+                images: Vec::new(),
             },
         ));
 
-        if swf_tag.is_auto_size() {
+        // Images are only created, and laid out at their own size, once there's a text field
+        // for them to belong to.
+        if swf_tag.is_auto_size() || has_images {
             et.relayout(context);
         } else {
             et.redraw_border(context.gc_context);
@@ -339,6 +390,8 @@ impl<'gc> EditText<'gc> {
         let mut edit_text = self.0.write(context.gc_context);
         let default_format = edit_text.text_spans.default_format().clone();
         edit_text.text_spans = FormatSpans::from_text(text.into(), default_format);
+        edit_text.html_source = None;
+        edit_text.ime_composition = None;
         drop(edit_text);
 
         self.relayout(context);
@@ -346,7 +399,12 @@ impl<'gc> EditText<'gc> {
 
     pub fn html_text(self) -> WString {
         if self.is_html() {
-            self.0.read().text_spans.to_html()
+            let read = self.0.read();
+            match (&read.style_sheet, &read.html_source) {
+                // Styled text can't be turned back into HTML, so it's given back as it was set
+                (Some(_), Some(html_source)) => html_source.clone(),
+                _ => read.text_spans.to_html(),
+            }
         } else {
             // Non-HTML text fields always return plain text.
             self.text()
@@ -357,11 +415,21 @@ impl<'gc> EditText<'gc> {
         if self.is_html() {
             let mut write = self.0.write(context.gc_context);
             let default_format = write.text_spans.default_format().clone();
+            let style_sheet = write.style_sheet.and_then(|object| match object.native() {
+                NativeObject::StyleSheet(style_sheet) => Some(style_sheet),
+                _ => None,
+            });
             write.text_spans = FormatSpans::from_html(
                 text,
                 default_format,
+                style_sheet
+                    .as_ref()
+                    .map(|style_sheet| style_sheet.read())
+                    .as_deref(),
                 write.flags.contains(EditTextFlag::MULTILINE),
             );
+            write.html_source = Some(text.into());
+            write.ime_composition = None;
             drop(write);
 
             self.relayout(context);
@@ -370,6 +438,26 @@ impl<'gc> EditText<'gc> {
         }
     }
 
+    pub fn style_sheet(self) -> Option<Avm1Object<'gc>> {
+        self.0.read().style_sheet
+    }
+
+    /// Sets the style sheet, and styles the HTML that the text was last set to with it.
+    pub fn set_style_sheet(
+        self,
+        style_sheet: Option<Avm1Object<'gc>>,
+        context: &mut UpdateContext<'_, 'gc>,
+    ) {
+        let mut write = self.0.write(context.gc_context);
+        write.style_sheet = style_sheet;
+        let html_source = write.html_source.take();
+        drop(write);
+
+        if let Some(html_source) = html_source {
+            self.set_html_text(&html_source, context);
+        }
+    }
+
     pub fn text_length(self) -> usize {
         self.0.read().text_spans.text().len()
     }
@@ -548,10 +636,10 @@ impl<'gc> EditText<'gc> {
         text: &WStr,
         context: &mut UpdateContext<'_, 'gc>,
     ) {
-        self.0
-            .write(context.gc_context)
-            .text_spans
-            .replace_text(from, to, text, None);
+        let mut write = self.0.write(context.gc_context);
+        write.text_spans.replace_text(from, to, text, None);
+        write.html_source = None;
+        drop(write);
         self.relayout(context);
     }
 
@@ -693,6 +781,8 @@ impl<'gc> EditText<'gc> {
     /// have already been calculated and applied to HTML trees lowered into the
     /// text-span representation.
     fn relayout(self, context: &mut UpdateContext<'_, 'gc>) {
+        self.sync_images(context);
+
         let mut edit_text = self.0.write(context.gc_context);
         let autosize = edit_text.autosize;
         let is_word_wrap = edit_text.flags.contains(EditTextFlag::WORD_WRAP);
@@ -707,6 +797,15 @@ impl<'gc> EditText<'gc> {
             edit_text.text_spans.clear_displayed_text();
         }
 
+        let mut image_sizes = Vec::with_capacity(edit_text.images.len());
+        for image in &mut edit_text.images {
+            image.laid_out_bounds = image.clip.bounds();
+            image_sizes.push((
+                image.laid_out_bounds.width(),
+                image.laid_out_bounds.height(),
+            ));
+        }
+
         let (new_layout, intrinsic_bounds) = LayoutBox::lower_from_text_spans(
             &edit_text.text_spans,
             context,
@@ -714,8 +813,33 @@ impl<'gc> EditText<'gc> {
             edit_text.bounds.width() - padding,
             is_word_wrap,
             !edit_text.flags.contains(EditTextFlag::USE_OUTLINES),
+            &image_sizes,
         );
 
+        // Fit each image's clip to the box that it was laid out in.
+        for layout_box in &new_layout {
+            if let LayoutContent::Image { index, .. } = layout_box.content() {
+                if let Some(image) = edit_text.images.get(*index) {
+                    let natural = &image.laid_out_bounds;
+                    let bounds = layout_box.bounds();
+                    let scale = |size: Twips, natural_size: Twips| {
+                        if natural_size > Twips::ZERO {
+                            (size.get() as f64 / natural_size.get() as f64) as f32
+                        } else {
+                            1.0
+                        }
+                    };
+                    let matrix = Matrix::translate(bounds.offset_x(), bounds.offset_y())
+                        * Matrix::scale(
+                            scale(bounds.width(), natural.width()),
+                            scale(bounds.height(), natural.height()),
+                        )
+                        * Matrix::translate(-natural.x_min, -natural.y_min);
+                    image.clip.set_matrix(context.gc_context, matrix);
+                }
+            }
+        }
+
         edit_text.line_data = get_line_data(&new_layout);
         edit_text.layout = new_layout;
         edit_text.intrinsic_bounds = intrinsic_bounds;
@@ -748,6 +872,160 @@ impl<'gc> EditText<'gc> {
         }
     }
 
+    /// Creates the clips for the text's images, keeping the clips of the images that it
+    /// already had and unloading the rest.
+    fn sync_images(self, context: &mut UpdateContext<'_, 'gc>) {
+        let text_images: Vec<TextImage> = self
+            .0
+            .read()
+            .text_spans
+            .iter_spans()
+            .filter(|(_start, _end, text, _span)| text.get(0) == Some(TextImage::CHARACTER))
+            .filter_map(|(_start, _end, _text, span)| span.image.clone())
+            .collect();
+        let old_images = std::mem::take(&mut self.0.write(context.gc_context).images);
+        if old_images.is_empty() && text_images.is_empty() {
+            return;
+        }
+
+        let mut images = Vec::with_capacity(text_images.len());
+        let mut unused_images = Vec::new();
+        let mut old_images = old_images.into_iter();
+        for text_image in text_images {
+            match old_images.next() {
+                Some(old_image) if old_image.image == text_image => images.push(old_image),
+                old_image => {
+                    unused_images.extend(old_image);
+                    let clip = self.create_image_clip(context, &text_image);
+                    images.push(HtmlImage {
+                        image: text_image,
+                        clip,
+                        laid_out_bounds: Default::default(),
+                    });
+                }
+            }
+        }
+        unused_images.extend(old_images);
+
+        self.0.write(context.gc_context).images = images;
+        for unused_image in unused_images {
+            unused_image.clip.unload(context);
+        }
+    }
+
+    /// Creates the clip for an image, which is an instance of the library symbol that its
+    /// `src` is the linkage ID of, or otherwise a clip that the URL is loaded into.
+    fn create_image_clip(
+        self,
+        context: &mut UpdateContext<'_, 'gc>,
+        image: &TextImage,
+    ) -> DisplayObject<'gc> {
+        let movie = self.movie();
+        let export_name = AvmString::new(context.gc_context, image.src.clone());
+        let library = context.library.library_for_movie_mut(movie.clone());
+        let symbol = library
+            .character_by_export_name(export_name)
+            .is_some()
+            .then(|| library.instantiate_by_export_name(export_name, context.gc_context))
+            .and_then(Result::ok);
+
+        let clip = symbol.unwrap_or_else(|| {
+            let clip: DisplayObject<'gc> = MovieClip::new(movie, context.gc_context).into();
+            let future = context.load_manager.load_movie_into_clip(
+                context.player.clone(),
+                clip,
+                Request::get(image.src.to_utf8_lossy().into_owned()),
+                None,
+                None,
+                None,
+            );
+            context.navigator.spawn_future(future);
+            clip
+        });
+
+        clip.set_parent(context.gc_context, Some(self.into()));
+        if let Some(id) = &image.id {
+            clip.set_name(
+                context.gc_context,
+                AvmString::new(context.gc_context, id.clone()),
+            );
+        }
+        clip.post_instantiation(context, None, Instantiator::Movie, false);
+        clip
+    }
+
+    /// Lays the text out again if any of its images have changed size since it was last
+    /// laid out.
+    fn reflow_images(self, context: &mut UpdateContext<'_, 'gc>) {
+        let needs_reflow = self
+            .0
+            .read()
+            .images
+            .iter()
+            .any(|image| image.clip.bounds() != image.laid_out_bounds);
+        if needs_reflow {
+            self.relayout(context);
+        }
+    }
+
+    /// The clips showing the text's images.
+    fn image_clips(self) -> Vec<DisplayObject<'gc>> {
+        self.0
+            .read()
+            .images
+            .iter()
+            .map(|image| image.clip)
+            .collect()
+    }
+
+    /// Finds the clip of the image with the given `id`.
+    pub fn image_by_id(self, id: &WStr, case_sensitive: bool) -> Option<DisplayObject<'gc>> {
+        self.0.read().images.iter().find_map(|image| {
+            let image_id = image.image.id.as_deref()?;
+            let matches = if case_sensitive {
+                image_id == id
+            } else {
+                image_id.eq_ignore_case(id)
+            };
+            matches.then_some(image.clip)
+        })
+    }
+
+    /// The bounds of a character, relative to the text field, or `None` if it isn't
+    /// laid out.
+    ///
+    /// An image is a single character, whose bounds are those of the image.
+    pub fn char_boundaries(self, index: usize) -> Option<BoxBounds<Twips>> {
+        let read = self.0.read();
+        let padding = Twips::from_pixels(Self::INTERNAL_PADDING);
+        let padding = Position::from((padding, padding));
+
+        for layout_box in &read.layout {
+            match layout_box.content() {
+                LayoutContent::Image { position, .. } if *position == index => {
+                    return Some(layout_box.bounds() + padding);
+                }
+                LayoutContent::Text { start, end, .. } if (*start..*end).contains(&index) => {
+                    let (text, _tf, font, params, _color) =
+                        layout_box.as_renderable_text(read.text_spans.displayed_text())?;
+                    let char_start = index - start;
+                    let char_end = string_utils::next_char_boundary(text, char_start);
+                    let x = font.measure(&text[..char_start], params, false).0;
+                    let width = font.measure(&text[..char_end], params, false).0 - x;
+
+                    let bounds = layout_box.bounds();
+                    return Some(BoxBounds::from_position_and_size(
+                        bounds.origin() + Position::from((x, Twips::ZERO)) + padding,
+                        Size::from((width, bounds.height())),
+                    ));
+                }
+                _ => {}
+            }
+        }
+
+        None
+    }
+
     /// Measure the width and height of the `EditText`'s current text load.
     ///
     /// The returned tuple should be interpreted as width, then height.
@@ -897,6 +1175,7 @@ impl<'gc> EditText<'gc> {
                             context.transform_stack.push(&Transform {
                                 matrix: transform.matrix,
                                 color_transform: ColorTransform::default(),
+                                matrix3d: None,
                             });
                         }
                         _ => {
@@ -942,6 +1221,22 @@ impl<'gc> EditText<'gc> {
             drawing.render(context);
         }
 
+        // The image itself is drawn along with the rest of the images, over the selection.
+        if let LayoutContent::Image { position, .. } = lbox.content() {
+            if selection.map_or(false, |selection| selection.contains(*position)) {
+                let bounds = lbox.bounds();
+                let selection_box = context.transform_stack.transform().matrix
+                    * Matrix::create_box(
+                        bounds.width().to_pixels() as f32,
+                        bounds.height().to_pixels() as f32,
+                        0.0,
+                        Twips::ZERO,
+                        Twips::ZERO,
+                    );
+                context.commands.draw_rect(Color::BLACK, selection_box);
+            }
+        }
+
         context.transform_stack.pop();
     }
 
@@ -1151,6 +1446,27 @@ impl<'gc> EditText<'gc> {
             matrix.invert();
             let local_position = matrix * position;
 
+            // An image is a single character, which is after the caret in its left half
+            if let LayoutContent::Image {
+                position: image_position,
+                ..
+            } = layout_box.content()
+            {
+                let width = layout_box.bounds().width();
+                if local_position.0 >= Twips::ZERO
+                    && local_position.0 <= width
+                    && local_position.1 >= Twips::ZERO
+                    && local_position.1 <= layout_box.bounds().height()
+                {
+                    if local_position.0 >= width / 2 {
+                        return Some(image_position + 1);
+                    } else {
+                        return Some(*image_position);
+                    }
+                }
+                continue;
+            }
+
             if let Some((text, _tf, font, params, color)) =
                 layout_box.as_renderable_text(text.text_spans.text())
             {
@@ -1185,9 +1501,11 @@ impl<'gc> EditText<'gc> {
     }
 
     pub fn text_input(self, character: char, context: &mut UpdateContext<'_, 'gc>) {
-        if self.0.read().flags.contains(EditTextFlag::READ_ONLY) {
+        let read = self.0.read();
+        if read.flags.contains(EditTextFlag::READ_ONLY) || read.style_sheet.is_some() {
             return;
         }
+        drop(read);
 
         if let Some(selection) = self.selection() {
             let mut changed = false;
@@ -1267,6 +1585,162 @@ impl<'gc> EditText<'gc> {
         }
     }
 
+    /// Shows the text that an input method is composing in place of the selection,
+    /// underlined, until it's committed. Empty text drops what was being composed.
+    ///
+    /// Scripts aren't told that the text changed until the composition is committed.
+    pub fn set_ime_composition(self, text: &WStr, context: &mut UpdateContext<'_, 'gc>) {
+        let read = self.0.read();
+        if read.flags.contains(EditTextFlag::READ_ONLY) || read.style_sheet.is_some() {
+            return;
+        }
+        let Some(selection) = read.selection else {
+            return;
+        };
+        if read.ime_composition.is_none() && text.is_empty() {
+            return;
+        }
+        drop(read);
+
+        let mut write = self.0.write(context.gc_context);
+        let mut range = write.ime_composition.unwrap_or(selection);
+        range.clamp(write.text_spans.text().len());
+        let (start, end) = (range.start(), range.end());
+        write.text_spans.replace_text(start, end, text, None);
+        if !text.is_empty() {
+            let underline = TextFormat {
+                underline: Some(true),
+                ..Default::default()
+            };
+            write
+                .text_spans
+                .set_text_format(start, start + text.len(), &underline);
+        }
+        write.ime_composition =
+            (!text.is_empty()).then(|| TextSelection::for_range(start, start + text.len()));
+        write.selection = Some(TextSelection::for_position(start + text.len()));
+        write.html_source = None;
+        drop(write);
+        self.relayout(context);
+    }
+
+    /// Replaces the text that an input method was composing, or the selection if it
+    /// wasn't composing any, with the text that it committed, as a single change.
+    pub fn commit_ime_composition(self, text: &WStr, context: &mut UpdateContext<'_, 'gc>) {
+        let read = self.0.read();
+        if read.flags.contains(EditTextFlag::READ_ONLY) || read.style_sheet.is_some() {
+            return;
+        }
+        let Some(selection) = read.selection else {
+            return;
+        };
+        let mut range = read.ime_composition.unwrap_or(selection);
+        range.clamp(read.text_spans.text().len());
+        let (start, end) = (range.start(), range.end());
+
+        // Only as much of the text is kept as fits within the character limit.
+        let length = if read.max_chars > 0 {
+            let remaining = read.text_spans.text().len() - (end - start);
+            text.len()
+                .min((read.max_chars as usize).saturating_sub(remaining))
+        } else {
+            text.len()
+        };
+        let text = text.slice(..length).unwrap_or_else(WStr::empty);
+        drop(read);
+
+        self.0.write(context.gc_context).ime_composition = None;
+        if text.is_empty() && start == end {
+            return;
+        }
+        self.replace_text(start, end, text, context);
+        self.set_selection(
+            Some(TextSelection::for_position(start + text.len())),
+            context.gc_context,
+        );
+
+        let mut activation = Avm1Activation::from_nothing(
+            context.reborrow(),
+            ActivationIdentifier::root("[Propagate Text Binding]"),
+            self.into(),
+        );
+        self.propagate_text_binding(&mut activation);
+        self.on_changed(&mut activation);
+    }
+
+    /// The bounds of the caret on the stage, which input methods show what they're
+    /// composing next to, or `None` if this text field isn't being typed into.
+    pub fn caret_bounds(self) -> Option<BoundingBox> {
+        let edit_text = self.0.read();
+        if !edit_text.flags.contains(EditTextFlag::HAS_FOCUS)
+            || edit_text.flags.contains(EditTextFlag::READ_ONLY)
+        {
+            return None;
+        }
+        let position = edit_text.selection?.to();
+
+        // An empty text field has its caret at the start, as tall as the default font size.
+        let default_size = edit_text.text_spans.default_format().size;
+        let mut caret = (
+            Twips::ZERO,
+            Twips::ZERO,
+            Twips::from_pixels(default_size.unwrap_or_default()),
+        );
+        for layout_box in &edit_text.layout {
+            let LayoutContent::Text { start, end, .. } = layout_box.content() else {
+                continue;
+            };
+            if position < *start || position > *end {
+                continue;
+            }
+            if let Some((text, _tf, font, params, color)) =
+                layout_box.as_renderable_text(edit_text.text_spans.displayed_text())
+            {
+                // The caret comes after every glyph before its position.
+                let mut caret_x = Twips::ZERO;
+                let baseline_adjustment =
+                    font.get_baseline_for_height(params.height()) - params.height();
+                font.evaluate(
+                    text,
+                    self.text_transform(color, baseline_adjustment),
+                    params,
+                    |pos, _transform, _glyph: &Glyph, advance, x| {
+                        if start + pos < position {
+                            caret_x = x + advance;
+                        }
+                    },
+                );
+                let origin = layout_box.bounds().origin();
+                caret = (origin.x() + caret_x, origin.y(), params.height());
+                break;
+            }
+        }
+
+        let scroll_offset = if edit_text.scroll > 1 {
+            edit_text
+                .line_data
+                .get(edit_text.scroll - 1)
+                .map_or(Twips::ZERO, |line_data| line_data.offset)
+        } else {
+            Twips::ZERO
+        };
+        let (x, y, height) = caret;
+        let x = edit_text.bounds.x_min + Twips::from_pixels(Self::INTERNAL_PADDING)
+            - Twips::from_pixels(edit_text.hscroll)
+            + x;
+        let y =
+            edit_text.bounds.y_min + Twips::from_pixels(Self::INTERNAL_PADDING) - scroll_offset + y;
+        let bounds = BoundingBox {
+            x_min: x,
+            y_min: y,
+            x_max: x + Twips::from_pixels(1.0),
+            y_max: y + height,
+            valid: true,
+        };
+        drop(edit_text);
+        Some(bounds.transform(&self.local_to_global_matrix()))
+    }
+
     /// Listens for keyboard text control commands.
     ///
     /// TODO: Add explicit text control events (#4452).
@@ -1428,6 +1902,11 @@ impl<'gc> EditText<'gc> {
         let read = self.0.read();
 
         for layout_box in read.layout.iter() {
+            // Images aren't part of the lines beside them
+            if layout_box.is_image() {
+                continue;
+            }
+
             if let Some(line) = line {
                 if layout_box.bounds().offset_y() < line.offset
                     || layout_box.bounds().extent_y() > line.extent
@@ -1460,7 +1939,7 @@ impl<'gc> EditText<'gc> {
                         font = Some(box_font);
                         text_format = Some(box_text_format);
                     }
-                    LayoutContent::Drawing { .. } => {}
+                    LayoutContent::Drawing { .. } | LayoutContent::Image { .. } => {}
                 }
             }
         }
@@ -1508,16 +1987,33 @@ impl<'gc> TDisplayObject<'gc> for EditText<'gc> {
         self.0.read().static_data.swf.clone()
     }
 
+    fn enter_frame(&self, context: &mut UpdateContext<'_, 'gc>) {
+        // Images aren't on the display list, so they're only reached through their text field
+        if context.is_action_script_3() {
+            for clip in self.image_clips() {
+                clip.enter_frame(context);
+            }
+        }
+    }
+
     /// Construct objects placed on this frame.
     fn construct_frame(&self, context: &mut UpdateContext<'_, 'gc>) {
         if context.is_action_script_3() && matches!(self.object2(), Avm2Value::Null) {
             self.construct_as_avm2_object(context, (*self).into());
             self.on_construction_complete(context);
         }
+
+        if context.is_action_script_3() {
+            for clip in self.image_clips() {
+                clip.construct_frame(context);
+            }
+            self.reflow_images(context);
+        }
     }
 
-    fn run_frame(&self, _context: &mut UpdateContext) {
-        // Noop
+    fn run_frame(&self, context: &mut UpdateContext<'_, 'gc>) {
+        // AVM1 image clips run their own frames, and only need the text to follow their size.
+        self.reflow_images(context);
     }
 
     fn as_edit_text(&self) -> Option<EditText<'gc>> {
@@ -1649,11 +2145,16 @@ impl<'gc> TDisplayObject<'gc> for EditText<'gc> {
     }
 
     fn render_self(&self, context: &mut RenderContext<'_, 'gc>) {
-        if !context.is_offscreen && !self.world_bounds().intersects(&context.stage.view_bounds()) {
+        if context.is_culled(|| self.world_bounds()) {
             // Off-screen; culled
             return;
         }
 
+        if let Some(cache_dependencies) = &mut context.cache_dependencies {
+            // Edits, scrolling and the blinking caret don't invalidate anything
+            cache_dependencies.set_volatile();
+        }
+
         let edit_text = self.0.read();
         context.transform_stack.push(&Transform {
             matrix: Matrix::translate(edit_text.bounds.x_min, edit_text.bounds.y_min),
@@ -1662,7 +2163,6 @@ impl<'gc> TDisplayObject<'gc> for EditText<'gc> {
 
         edit_text.drawing.render(context);
 
-        context.commands.push_mask();
         let mask = Matrix::create_box(
             edit_text.bounds.width().to_pixels() as f32,
             edit_text.bounds.height().to_pixels() as f32,
@@ -1670,11 +2170,16 @@ impl<'gc> TDisplayObject<'gc> for EditText<'gc> {
             Twips::ZERO,
             Twips::ZERO,
         );
-        context.commands.draw_rect(
-            Color::WHITE,
-            context.transform_stack.transform().matrix * mask,
-        );
-        context.commands.activate_mask();
+        // Masks can't be nested, so text is left uncropped when drawn as part of a mask
+        let crop = context.allow_mask;
+        if crop {
+            context.commands.push_mask();
+            context.commands.draw_rect(
+                Color::WHITE,
+                context.transform_stack.transform().matrix * mask,
+            );
+            context.commands.activate_mask();
+        }
 
         let scroll_offset = if edit_text.scroll > 1 {
             let line_data = &edit_text.line_data;
@@ -1723,16 +2228,21 @@ impl<'gc> TDisplayObject<'gc> for EditText<'gc> {
             for layout_box in edit_text.layout.iter() {
                 self.render_layout_box(context, layout_box);
             }
+            for image in &edit_text.images {
+                render_base(image.clip, context);
+            }
         }
 
         context.transform_stack.pop();
 
-        context.commands.deactivate_mask();
-        context.commands.draw_rect(
-            Color::WHITE,
-            context.transform_stack.transform().matrix * mask,
-        );
-        context.commands.pop_mask();
+        if crop {
+            context.commands.deactivate_mask();
+            context.commands.draw_rect(
+                Color::WHITE,
+                context.transform_stack.transform().matrix * mask,
+            );
+            context.commands.pop_mask();
+        }
 
         context.transform_stack.pop();
     }
@@ -1771,6 +2281,10 @@ impl<'gc> TDisplayObject<'gc> for EditText<'gc> {
                 .retain(|&text_field| !DisplayObject::ptr_eq(text_field.into(), (*self).into()));
         }
 
+        for clip in self.image_clips() {
+            clip.unload(context);
+        }
+
         self.set_removed(context.gc_context, true);
     }
 
@@ -1842,6 +2356,7 @@ impl<'gc> TInteractiveObject<'gc> for EditText<'gc> {
         if self.visible()
             && self.mouse_enabled()
             && self.is_selectable()
+            && self.hit_test_scroll_rect(point)
             && self.hit_test_shape(context, point, HitTestOptions::MOUSE_PICK)
         {
             Some((*self).into())
@@ -1860,6 +2375,7 @@ impl<'gc> TInteractiveObject<'gc> for EditText<'gc> {
         if self.visible()
             && self.mouse_enabled()
             && self.is_selectable()
+            && self.hit_test_scroll_rect(point)
             && self.hit_test_shape(context, point, HitTestOptions::MOUSE_PICK)
         {
             Avm2MousePick::Hit((*self).into())
@@ -13,7 +13,8 @@ use core::fmt;
 use gc_arena::{Collect, GcCell, MutationContext};
 use ruffle_render::backend::ShapeHandle;
 use ruffle_render::commands::CommandHandler;
-use std::cell::{Ref, RefMut};
+use ruffle_render::shape_utils::{DistilledShape, ShapeHitTester, StrokeScaling};
+use std::cell::{Ref, RefCell, RefMut};
 use std::sync::Arc;
 
 #[derive(Clone, Collect, Copy)]
@@ -35,6 +36,8 @@ pub struct GraphicData<'gc> {
     static_data: gc_arena::Gc<'gc, GraphicStatic>,
     avm2_object: Option<Avm2Object<'gc>>,
     drawing: Option<Drawing>,
+    #[collect(require_static)]
+    scaled_shape: ScaledShape,
 }
 
 impl<'gc> Graphic<'gc> {
@@ -45,17 +48,22 @@ impl<'gc> Graphic<'gc> {
         movie: Arc<SwfMovie>,
     ) -> Self {
         let library = context.library.library_for_movie(movie.clone()).unwrap();
+        let shape: DistilledShape = (&swf_shape).into();
+        let stroke_scaling = StrokeScaling::from_paths(&shape.paths);
+        let render_handle = context.renderer.register_shape(
+            shape,
+            &MovieLibrarySource {
+                library,
+                gc_context: context.gc_context,
+            },
+        );
         let static_data = GraphicStatic {
             id: swf_shape.id,
             bounds: (&swf_shape.shape_bounds).into(),
-            render_handle: Some(context.renderer.register_shape(
-                (&swf_shape).into(),
-                &MovieLibrarySource {
-                    library,
-                    gc_context: context.gc_context,
-                },
-            )),
+            render_handle: Some(render_handle),
+            stroke_scaling,
             shape: swf_shape,
+            hit_tester: RefCell::new(None),
             movie,
         };
 
@@ -66,6 +74,7 @@ impl<'gc> Graphic<'gc> {
                 static_data: gc_arena::Gc::allocate(context.gc_context, static_data),
                 avm2_object: None,
                 drawing: None,
+                scaled_shape: Default::default(),
             },
         ))
     }
@@ -79,6 +88,7 @@ impl<'gc> Graphic<'gc> {
             id: 0,
             bounds: Default::default(),
             render_handle: None,
+            stroke_scaling: Default::default(),
             shape: swf::Shape {
                 version: 32,
                 id: 0,
@@ -91,6 +101,7 @@ impl<'gc> Graphic<'gc> {
                 },
                 shape: Vec::new(),
             },
+            hit_tester: RefCell::new(None),
             movie: context.swf.clone(),
         };
         let drawing = Drawing::new();
@@ -102,11 +113,47 @@ impl<'gc> Graphic<'gc> {
                 static_data: gc_arena::Gc::allocate(context.gc_context, static_data),
                 avm2_object: Some(avm2_object),
                 drawing: Some(drawing),
+                scaled_shape: Default::default(),
             },
         ))
     }
 
+    /// Returns the shape to draw with the current transform,
+    /// tessellating its strokes for the transform if they depend on it.
+    fn render_handle(&self, context: &mut RenderContext<'_, 'gc>) -> Option<ShapeHandle> {
+        let static_data = self.0.read().static_data;
+        let matrix = context.transform_stack.transform().matrix;
+        let Some(stroke_matrix) = static_data.stroke_scaling.stroke_matrix(&matrix) else {
+            return static_data.render_handle.clone();
+        };
+        let library = context
+            .library
+            .library_for_movie(static_data.movie.clone())?;
+
+        let mut write = self.0.write(context.gc_context);
+        let scaled_shape = &mut write.scaled_shape;
+        if scaled_shape.handle.is_none() || scaled_shape.matrix != Some(stroke_matrix) {
+            let mut shape: DistilledShape = (&static_data.shape).into();
+            shape.stroke_matrix = Some(stroke_matrix);
+            let bitmap_source = MovieLibrarySource {
+                library,
+                gc_context: context.gc_context,
+            };
+            if let Some(handle) = scaled_shape.handle.clone() {
+                context
+                    .renderer
+                    .replace_shape(shape, &bitmap_source, handle);
+            } else {
+                scaled_shape.handle = Some(context.renderer.register_shape(shape, &bitmap_source));
+            }
+            scaled_shape.matrix = Some(stroke_matrix);
+        }
+        scaled_shape.handle.clone()
+    }
+
     pub fn drawing(&self, gc_context: MutationContext<'gc, '_>) -> RefMut<'_, Drawing> {
+        // We're about to draw something new
+        self.invalidate_cached_bitmap(gc_context);
         RefMut::map(self.0.write(gc_context), |w| {
             w.drawing.get_or_insert_with(Drawing::new)
         })
@@ -172,7 +219,12 @@ impl<'gc> TDisplayObject<'gc> for Graphic<'gc> {
             .library_for_movie_mut(self.movie())
             .get_graphic(id)
         {
-            self.0.write(context.gc_context).static_data = new_graphic.0.read().static_data;
+            let mut write = self.0.write(context.gc_context);
+            write.static_data = new_graphic.0.read().static_data;
+            // The new shape has to be tessellated again, but its handle can be reused
+            write.scaled_shape.matrix = None;
+            drop(write);
+            self.invalidate_cached_bitmap(context.gc_context);
         } else {
             tracing::warn!("PlaceObject: expected Graphic at character ID {}", id);
         }
@@ -182,15 +234,17 @@ impl<'gc> TDisplayObject<'gc> for Graphic<'gc> {
         // Noop
     }
 
-    fn render_self(&self, context: &mut RenderContext) {
-        if !context.is_offscreen && !self.world_bounds().intersects(&context.stage.view_bounds()) {
+    fn render_self(&self, context: &mut RenderContext<'_, 'gc>) {
+        if context.is_culled(|| self.world_bounds()) {
             // Off-screen; culled
             return;
         }
 
         if let Some(drawing) = &self.0.read().drawing {
             drawing.render(context);
-        } else if let Some(render_handle) = self.0.read().static_data.render_handle {
+            return;
+        }
+        if let Some(render_handle) = self.render_handle(context) {
             context
                 .commands
                 .render_shape(render_handle, context.transform_stack.transform())
@@ -212,8 +266,11 @@ impl<'gc> TDisplayObject<'gc> for Graphic<'gc> {
                     return true;
                 }
             } else {
-                let shape = &self.0.read().static_data.shape;
-                return ruffle_render::shape_utils::shape_hit_test(shape, point, &local_matrix);
+                let static_data = self.0.read().static_data;
+                let mut hit_tester = static_data.hit_tester.borrow_mut();
+                return hit_tester
+                    .get_or_insert_with(|| ShapeHitTester::new(&static_data.shape))
+                    .hit_test(point, &local_matrix);
             }
         }
 
@@ -268,7 +325,27 @@ impl<'gc> TDisplayObject<'gc> for Graphic<'gc> {
 struct GraphicStatic {
     id: CharacterId,
     shape: swf::Shape,
+    /// Built the first time that the shape is hit tested.
+    hit_tester: RefCell<Option<ShapeHitTester>>,
     render_handle: Option<ShapeHandle>,
+    stroke_scaling: StrokeScaling,
     bounds: BoundingBox,
     movie: Arc<SwfMovie>,
 }
+
+/// The shape tessellated for the transform that an instance was last drawn with,
+/// for shapes whose strokes don't simply scale along with them.
+#[derive(Default)]
+struct ScaledShape {
+    handle: Option<ShapeHandle>,
+
+    /// The transform that the strokes were tessellated for.
+    matrix: Option<Matrix>,
+}
+
+impl Clone for ScaledShape {
+    fn clone(&self) -> Self {
+        // A copy of the object must tessellate into its own shape
+        Self::default()
+    }
+}
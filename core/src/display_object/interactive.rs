@@ -74,6 +74,10 @@ bitflags! {
 
         /// Whether this `InteractiveObject` accepts double-clicks.
         const DOUBLE_CLICK_ENABLED = 1 << 1;
+
+        /// Whether an on-screen keyboard should be shown when this `InteractiveObject`
+        /// has focus.
+        const NEEDS_SOFT_KEYBOARD = 1 << 2;
     }
 }
 
@@ -155,6 +159,20 @@ pub trait TInteractiveObject<'gc>:
             .set(InteractiveObjectFlags::DOUBLE_CLICK_ENABLED, value)
     }
 
+    /// Check if the interactive object should get an on-screen keyboard while focused.
+    fn needs_soft_keyboard(self) -> bool {
+        self.raw_interactive()
+            .flags
+            .contains(InteractiveObjectFlags::NEEDS_SOFT_KEYBOARD)
+    }
+
+    /// Set if the interactive object should get an on-screen keyboard while focused.
+    fn set_needs_soft_keyboard(self, mc: MutationContext<'gc, '_>, value: bool) {
+        self.raw_interactive_mut(mc)
+            .flags
+            .set(InteractiveObjectFlags::NEEDS_SOFT_KEYBOARD, value)
+    }
+
     fn context_menu(self) -> Avm2Value<'gc> {
         self.raw_interactive().context_menu
     }
@@ -147,6 +147,10 @@ impl<'gc> TInteractiveObject<'gc> for LoaderDisplay<'gc> {
         pos: (Twips, Twips),
         require_button_mode: bool,
     ) -> Option<InteractiveObject<'gc>> {
+        if !self.hit_test_scroll_rect(pos) {
+            return None;
+        }
+
         for child in self.iter_render_list().rev() {
             if let Some(int) = child.as_interactive() {
                 if let Some(result) = int.mouse_pick_avm1(context, pos, require_button_mode) {
@@ -164,6 +168,10 @@ impl<'gc> TInteractiveObject<'gc> for LoaderDisplay<'gc> {
         pos: (Twips, Twips),
         require_button_mode: bool,
     ) -> Avm2MousePick<'gc> {
+        if !self.hit_test_scroll_rect(pos) {
+            return Avm2MousePick::Miss;
+        }
+
         // We have at most one child
         if let Some(child) = self.iter_render_list().next() {
             if let Some(int) = child.as_interactive() {
@@ -7,6 +7,7 @@ use core::fmt;
 use gc_arena::{Collect, Gc, GcCell, MutationContext};
 use ruffle_render::backend::ShapeHandle;
 use ruffle_render::commands::CommandHandler;
+use ruffle_render::shape_utils::ShapeHitTester;
 use std::cell::{Ref, RefCell, RefMut};
 use std::sync::Arc;
 use swf::{Fixed16, Fixed8, Twips};
@@ -54,6 +55,7 @@ impl<'gc> MorphShape<'gc> {
 
     pub fn set_ratio(&mut self, gc_context: MutationContext<'gc, '_>, ratio: u16) {
         self.0.write(gc_context).ratio = ratio;
+        self.invalidate_cached_bitmap(gc_context);
     }
 }
 
@@ -89,6 +91,7 @@ impl<'gc> TDisplayObject<'gc> for MorphShape<'gc> {
             .get_morph_shape(id)
         {
             self.0.write(context.gc_context).static_data = new_morph_shape.0.read().static_data;
+            self.invalidate_cached_bitmap(context.gc_context);
         } else {
             tracing::warn!("PlaceObject: expected morph shape at character ID {}", id);
         }
@@ -99,13 +102,26 @@ impl<'gc> TDisplayObject<'gc> for MorphShape<'gc> {
     }
 
     fn render_self(&self, context: &mut RenderContext) {
+        if context.is_culled(|| self.world_bounds()) {
+            // Off-screen; culled
+            return;
+        }
+
         let this = self.0.read();
         let ratio = this.ratio;
         let static_data = this.static_data;
-        let shape_handle = static_data.get_shape(context, context.library, ratio);
-        context
-            .commands
-            .render_shape(shape_handle, context.transform_stack.transform());
+        if let Some(morph_handle) = static_data.get_morph_shape(context) {
+            context.commands.render_morph_shape(
+                morph_handle,
+                context.transform_stack.transform(),
+                ratio,
+            );
+        } else {
+            let shape_handle = static_data.get_shape(context, context.library, ratio);
+            context
+                .commands
+                .render_shape(shape_handle, context.transform_stack.transform());
+        }
     }
 
     fn self_bounds(&self) -> BoundingBox {
@@ -123,14 +139,17 @@ impl<'gc> TDisplayObject<'gc> for MorphShape<'gc> {
         _options: HitTestOptions,
     ) -> bool {
         if self.world_bounds().contains(point) {
-            if let Some(frame) = self.0.read().static_data.frames.borrow().get(&self.ratio()) {
+            let static_data = self.0.read().static_data;
+            let mut frames = static_data.frames.borrow_mut();
+            if let Some(frame) = frames.get_mut(&self.ratio()) {
                 let local_matrix = self.global_to_local_matrix();
                 let point = local_matrix * point;
-                return ruffle_render::shape_utils::shape_hit_test(
-                    &frame.shape,
-                    point,
-                    &local_matrix,
-                );
+                let Frame {
+                    shape, hit_tester, ..
+                } = frame;
+                return hit_tester
+                    .get_or_insert_with(|| ShapeHitTester::new(shape))
+                    .hit_test(point, &local_matrix);
             } else {
                 tracing::warn!("Missing ratio for morph shape");
             }
@@ -148,6 +167,8 @@ impl<'gc> TDisplayObject<'gc> for MorphShape<'gc> {
 struct Frame {
     shape_handle: Option<ShapeHandle>,
     shape: swf::Shape,
+    /// Built the first time that the frame is hit tested.
+    hit_tester: Option<ShapeHitTester>,
     bounds: BoundingBox,
 }
 
@@ -160,6 +181,9 @@ pub struct MorphShapeStatic {
     start: swf::MorphShape,
     end: swf::MorphShape,
     frames: RefCell<fnv::FnvHashMap<u16, Frame>>,
+    /// The shape that the renderer interpolates to any ratio itself, once it's been registered.
+    /// This is `Some(None)` if the renderer can't do so, and each ratio is tessellated instead.
+    morph_handle: RefCell<Option<Option<ShapeHandle>>>,
     movie: Arc<SwfMovie>,
 }
 
@@ -170,6 +194,7 @@ impl MorphShapeStatic {
             start: swf_tag.start.clone(),
             end: swf_tag.end.clone(),
             frames: RefCell::new(fnv::FnvHashMap::default()),
+            morph_handle: RefCell::new(None),
             movie,
         }
     }
@@ -194,8 +219,8 @@ impl MorphShapeStatic {
         ratio: u16,
     ) -> ShapeHandle {
         let mut frame = self.get_frame(ratio);
-        if let Some(handle) = frame.shape_handle {
-            handle
+        if let Some(handle) = &frame.shape_handle {
+            handle.clone()
         } else {
             let library = library.library_for_movie(self.movie.clone()).unwrap();
             let handle = context.renderer.register_shape(
@@ -205,11 +230,26 @@ impl MorphShapeStatic {
                     gc_context: context.gc_context,
                 },
             );
-            frame.shape_handle = Some(handle);
+            frame.shape_handle = Some(handle.clone());
             handle
         }
     }
 
+    /// Retrieves the `ShapeHandle` that can be drawn at any ratio, if the renderer supports it.
+    /// Lazily registers the start and end shapes with the renderer on first use.
+    fn get_morph_shape(&self, context: &mut RenderContext<'_, '_>) -> Option<ShapeHandle> {
+        if let Some(handle) = &*self.morph_handle.borrow() {
+            return handle.clone();
+        }
+        let start = self.build_morph_frame(0);
+        let end = self.build_morph_frame(u16::MAX);
+        let handle = context
+            .renderer
+            .register_morph_shape((&start.shape).into(), (&end.shape).into());
+        *self.morph_handle.borrow_mut() = Some(handle.clone());
+        handle
+    }
+
     fn build_morph_frame(&self, ratio: u16) -> Frame {
         // Interpolate MorphShapes into a Shape.
         use swf::{FillStyle, LineStyle, ShapeRecord, ShapeStyles};
@@ -331,6 +371,7 @@ impl MorphShapeStatic {
         Frame {
             shape_handle: None,
             shape,
+            hit_tester: None,
             bounds: bounds.into(),
         }
     }
@@ -1533,7 +1533,7 @@ impl<'gc> MovieClip<'gc> {
                                 .collect(),
                         );
                     }
-                    // TODO: Missing PlaceObject properties: amf_data, filters
+                    // TODO: Missing PlaceObject property: amf_data
 
                     // Run first frame.
                     child.post_instantiation(context, None, Instantiator::Movie, false);
@@ -2186,6 +2186,8 @@ impl<'gc> MovieClip<'gc> {
     }
 
     pub fn drawing(&self, gc_context: MutationContext<'gc, '_>) -> RefMut<'_, Drawing> {
+        // We're about to draw something new
+        self.invalidate_cached_bitmap(gc_context);
         RefMut::map(self.0.write(gc_context), |s| &mut s.drawing)
     }
 
@@ -2453,7 +2455,13 @@ impl<'gc> TDisplayObject<'gc> for MovieClip<'gc> {
     }
 
     fn render_self(&self, context: &mut RenderContext<'_, 'gc>) {
-        self.0.read().drawing.render(context);
+        // Only the clip's own drawing can be culled here, its children cull themselves
+        let drawing_bounds = self.self_bounds();
+        if drawing_bounds.valid
+            && !context.is_culled(|| drawing_bounds.transform(&self.local_to_global_matrix()))
+        {
+            self.0.read().drawing.render(context);
+        }
         self.render_children(context);
     }
 
@@ -2478,7 +2486,7 @@ impl<'gc> TDisplayObject<'gc> for MovieClip<'gc> {
             return false;
         }
 
-        if self.world_bounds().contains(point) {
+        if self.world_bounds().contains(point) && self.hit_test_scroll_rect(point) {
             if let Some(masker) = self.masker() {
                 if !masker.hit_test_shape(context, point, HitTestOptions::SKIP_INVISIBLE) {
                     return false;
@@ -2756,6 +2764,10 @@ impl<'gc> TInteractiveObject<'gc> for MovieClip<'gc> {
         if self.visible() {
             let this: InteractiveObject<'gc> = (*self).into();
 
+            if !self.hit_test_scroll_rect(point) {
+                return None;
+            }
+
             if let Some(masker) = self.masker() {
                 if !masker.hit_test_shape(context, point, HitTestOptions::SKIP_INVISIBLE) {
                     return None;
@@ -2841,6 +2853,10 @@ impl<'gc> TInteractiveObject<'gc> for MovieClip<'gc> {
         if self.visible() {
             let this: InteractiveObject<'gc> = (*self).into();
 
+            if !self.hit_test_scroll_rect(point) {
+                return Avm2MousePick::Miss;
+            }
+
             if let Some(masker) = self.masker() {
                 if !masker.hit_test_shape(context, point, HitTestOptions::SKIP_INVISIBLE) {
                     return Avm2MousePick::Miss;
@@ -4168,13 +4184,14 @@ impl<'a> GotoPlaceObject<'a> {
                 if place_object.background_color.is_none() {
                     place_object.background_color = Some(Color::from_rgba(0));
                 }
+                if place_object.filters.is_none() {
+                    place_object.filters = Some(Default::default());
+                }
                 // Purposely omitted properties:
                 // name, clip_depth, clip_actions, amf_data
                 // These properties are only set on initial placement in `MovieClip::instantiate_child`
                 // and can not be modified by subsequent PlaceObject tags.
                 // Also, is_visible flag persists during rewind unlike all other properties.
-                // TODO: Filters need to be applied here. Rewinding will erase filters if initial
-                // PlaceObject tag has none.
             }
         }
 
@@ -4226,11 +4243,14 @@ impl<'a> GotoPlaceObject<'a> {
         if next_place.background_color.is_some() {
             cur_place.background_color = next_place.background_color.take();
         }
+        // New filters overwrite old filters.
+        if next_place.filters.is_some() {
+            cur_place.filters = next_place.filters.take();
+        }
         // Purposely omitted properties:
         // name, clip_depth, clip_actions, amf_data
         // These properties are only set on initial placement in `MovieClip::instantiate_child`
         // and can not be modified by subsequent PlaceObject tags.
-        // TODO: Filters need to be applied here. New filters will overwrite old filters.
     }
 }
 
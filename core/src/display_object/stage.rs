@@ -16,7 +16,8 @@ use crate::display_object::interactive::{
     InteractiveObject, InteractiveObjectBase, TInteractiveObject,
 };
 use crate::display_object::{
-    render_base, DisplayObject, DisplayObjectBase, DisplayObjectPtr, TDisplayObject,
+    render_base, CacheDependencies, DisplayObject, DisplayObjectBase, DisplayObjectPtr,
+    TDisplayObject,
 };
 use crate::events::{ClipEvent, ClipEventResult};
 use crate::prelude::*;
@@ -27,12 +28,16 @@ use bitflags::bitflags;
 use gc_arena::{Collect, GcCell, MutationContext};
 use ruffle_render::backend::ViewportDimensions;
 use ruffle_render::commands::CommandHandler;
+use ruffle_render::matrix3d::Matrix3D;
 use ruffle_render::quality::StageQuality;
 use std::cell::{Ref, RefMut};
 use std::fmt::{self, Display, Formatter};
 use std::str::FromStr;
 use std::sync::Arc;
 
+/// The field of view of the default perspective projection, in degrees.
+const FIELD_OF_VIEW: f64 = 55.0;
+
 /// The Stage is the root of the display object hierarchy. It contains all AVM1
 /// levels as well as AVM2 movies.
 #[derive(Clone, Collect, Copy)]
@@ -97,6 +102,19 @@ pub struct StageData<'gc> {
     /// Whether or not a RENDER event should be dispatched on the next render
     invalidated: bool,
 
+    /// Whether anything on the stage has changed since the last frame was rendered.
+    needs_redraw: bool,
+
+    /// Whether the stage is rendered again on every frame, even if nothing has changed.
+    /// This is the case once the movie has drawn the stage into a bitmap, as that relies on
+    /// every frame having been rendered. It's never cleared, not even once the movie stops
+    /// drawing the stage, as there's no telling whether it will draw it again.
+    always_redraw: bool,
+
+    /// Everything that the last rendered frame depended on, besides the display objects
+    /// themselves (which set `needs_redraw` when they change).
+    render_dependencies: CacheDependencies<'gc>,
+
     /// Whether to use high quality downsampling for bitmaps.
     ///
     /// This is usally implied by `quality` being `Best` or higher, but the AVM1
@@ -158,6 +176,9 @@ impl<'gc> Stage<'gc> {
                     StageDisplayState::Normal
                 },
                 invalidated: false,
+                needs_redraw: true,
+                always_redraw: false,
+                render_dependencies: Default::default(),
                 align: Default::default(),
                 use_bitmap_downsampling: false,
                 view_bounds: Default::default(),
@@ -179,7 +200,14 @@ impl<'gc> Stage<'gc> {
     }
 
     pub fn set_background_color(self, gc_context: MutationContext<'gc, '_>, color: Option<Color>) {
-        self.0.write(gc_context).background_color = color;
+        let mut stage = self.0.write(gc_context);
+        stage.background_color = color;
+        stage.needs_redraw = true;
+    }
+
+    /// The matrix from the stage to the viewport that it's shown in.
+    pub fn view_matrix(self) -> Matrix {
+        *self.base().matrix()
     }
 
     pub fn inverse_view_matrix(self) -> Matrix {
@@ -194,7 +222,9 @@ impl<'gc> Stage<'gc> {
     }
 
     pub fn set_letterbox(self, gc_context: MutationContext<'gc, '_>, letterbox: Letterbox) {
-        self.0.write(gc_context).letterbox = letterbox
+        let mut stage = self.0.write(gc_context);
+        stage.letterbox = letterbox;
+        stage.needs_redraw = true;
     }
 
     /// Get the size of the SWF file.
@@ -229,6 +259,29 @@ impl<'gc> Stage<'gc> {
         self.0.write(gc_context).invalidated = value;
     }
 
+    /// Whether the stage has to be rendered again, because something has changed since the last
+    /// frame was rendered. Otherwise, the last frame can be shown again as it is.
+    pub fn needs_redraw(self) -> bool {
+        let stage = self.0.read();
+        stage.needs_redraw || stage.always_redraw || stage.render_dependencies.has_changed()
+    }
+
+    /// Stops skipping the rendering of frames where nothing has changed.
+    pub fn set_always_redraw(self, gc_context: MutationContext<'gc, '_>) {
+        self.0.write(gc_context).always_redraw = true;
+    }
+
+    /// Records that a frame has been rendered, along with what it depended on.
+    pub fn set_rendered(
+        self,
+        gc_context: MutationContext<'gc, '_>,
+        dependencies: CacheDependencies<'gc>,
+    ) {
+        let mut stage = self.0.write(gc_context);
+        stage.needs_redraw = false;
+        stage.render_dependencies = dependencies;
+    }
+
     /// Returns the quality setting of the stage.
     ///
     /// In the Flash Player, the quality setting affects anti-aliasing and smoothing of bitmaps.
@@ -246,6 +299,7 @@ impl<'gc> Stage<'gc> {
     pub fn set_quality(self, context: &mut UpdateContext<'_, 'gc>, quality: StageQuality) {
         let mut this = self.0.write(context.gc_context);
         this.quality = quality;
+        this.needs_redraw = true;
         this.use_bitmap_downsampling = matches!(
             quality,
             StageQuality::Best
@@ -286,6 +340,21 @@ impl<'gc> Stage<'gc> {
         self.0.read().stage_size
     }
 
+    /// The projection that everything in 3D is drawn with, relative to the viewport.
+    ///
+    /// This is Flash's default `PerspectiveProjection`: a 55 degree field of view, looking at
+    /// the center of the stage.
+    pub fn perspective_projection(self) -> Matrix3D {
+        let (width, height) = self.stage_size();
+        let (width, height) = (f64::from(width), f64::from(height));
+        let focal_length = (width / 2.0) / (FIELD_OF_VIEW / 2.0).to_radians().tan();
+        // The stage sits between the viewport and everything on it,
+        // so the projection is moved into its coordinates and then back out
+        Matrix3D::from(*self.base().matrix())
+            * Matrix3D::perspective(focal_length, (width / 2.0, height / 2.0))
+            * Matrix3D::from(self.inverse_view_matrix())
+    }
+
     /// Get the stage mode.
     /// This controls how the content scales to fill the viewport.
     pub fn scale_mode(self) -> StageScaleMode {
@@ -383,7 +452,9 @@ impl<'gc> Stage<'gc> {
     /// Sets whether bitmaps will use high quality downsampling when scaled down.
     /// This setting is currently ignored in Ruffle.
     pub fn set_use_bitmap_downsampling(self, gc_context: MutationContext<'gc, '_>, value: bool) {
-        self.0.write(gc_context).use_bitmap_downsampling = value;
+        let mut stage = self.0.write(gc_context);
+        stage.use_bitmap_downsampling = value;
+        stage.needs_redraw = true;
     }
 
     /// Get the stage mode.
@@ -395,7 +466,9 @@ impl<'gc> Stage<'gc> {
 
     /// Sets the window mode.
     pub fn set_window_mode(self, context: &mut UpdateContext<'_, 'gc>, window_mode: WindowMode) {
-        self.0.write(context.gc_context).window_mode = window_mode;
+        let mut stage = self.0.write(context.gc_context);
+        stage.window_mode = window_mode;
+        stage.needs_redraw = true;
     }
 
     pub fn view_bounds(self) -> BoundingBox {
@@ -427,6 +500,7 @@ impl<'gc> Stage<'gc> {
     /// Update the stage's transform matrix in response to a root movie change.
     pub fn build_matrices(self, context: &mut UpdateContext<'_, 'gc>) {
         let mut stage = self.0.write(context.gc_context);
+        stage.needs_redraw = true;
         let scale_mode = stage.scale_mode;
         let align = stage.align;
         let prev_stage_size = stage.stage_size;
@@ -783,16 +857,28 @@ impl<'gc> TDisplayObject<'gc> for Stage<'gc> {
         self.render_children(context);
     }
 
+    fn invalidate_cached_bitmap(&self, gc_context: MutationContext<'gc, '_>) {
+        // Everything on the stage invalidates it, so this is how the player knows to render again
+        self.0.write(gc_context).needs_redraw = true;
+    }
+
     fn render(&self, context: &mut RenderContext<'_, 'gc>) {
         // All of our Stage3D instances get rendered *underneath* the main stage.
         // Note that the stage background color is actually the lowest possible layer,
         // and get applied when we start the frame (before `render` is called).
         for stage3d in self.stage3ds().iter() {
             if let Some(context3d) = stage3d.as_stage_3d().unwrap().context3d() {
+                if let Some(cache_dependencies) = &mut context.cache_dependencies {
+                    // Presenting a new Stage3D frame doesn't invalidate anything
+                    cache_dependencies.set_volatile();
+                }
                 context3d.as_context_3d().unwrap().render(context);
             }
         }
 
+        context
+            .transform_stack
+            .set_perspective(Some(self.perspective_projection()));
         render_base((*self).into(), context);
 
         if self.should_letterbox() {
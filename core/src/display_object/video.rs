@@ -439,11 +439,16 @@ impl<'gc> TDisplayObject<'gc> for Video<'gc> {
     }
 
     fn render(&self, context: &mut RenderContext) {
-        if !context.is_offscreen && !self.world_bounds().intersects(&context.stage.view_bounds()) {
+        if context.is_culled(|| self.world_bounds()) {
             // Off-screen; culled
             return;
         }
 
+        if let Some(cache_dependencies) = &mut context.cache_dependencies {
+            // The next frame of the video doesn't invalidate anything
+            cache_dependencies.set_volatile();
+        }
+
         context.transform_stack.push(self.base().transform());
 
         let read = self.0.read();
@@ -4,17 +4,23 @@ use ruffle_render::backend::{RenderBackend, ShapeHandle};
 use ruffle_render::bitmap::{BitmapHandle, BitmapInfo, BitmapSize, BitmapSource};
 use ruffle_render::bounding_box::BoundingBox;
 use ruffle_render::commands::CommandHandler;
-use ruffle_render::shape_utils::{DistilledShape, DrawCommand, DrawPath};
-use std::cell::Cell;
+use ruffle_render::matrix::Matrix;
+use ruffle_render::shape_utils::{
+    DistilledShape, DrawCommand, DrawPath, FillRule, StrokeScaling, TriangleList,
+};
+use std::cell::{Cell, RefCell};
 use swf::{FillStyle, LineStyle, Twips};
 
 #[derive(Clone, Debug, Collect)]
 #[collect(require_static)]
 pub struct Drawing {
-    render_handle: Cell<Option<ShapeHandle>>,
+    render_handle: RefCell<Option<ShapeHandle>>,
     shape_bounds: BoundingBox,
     edge_bounds: BoundingBox,
     dirty: Cell<bool>,
+    stroke_scaling: Cell<StrokeScaling>,
+    /// The transform that the strokes were tessellated for, if they depend on it.
+    stroke_matrix: Cell<Option<Matrix>>,
     paths: Vec<DrawingPath>,
     bitmaps: Vec<BitmapInfo>,
     current_fill: Option<DrawingFill>,
@@ -33,10 +39,12 @@ impl Default for Drawing {
 impl Drawing {
     pub fn new() -> Self {
         Self {
-            render_handle: Cell::new(None),
+            render_handle: RefCell::new(None),
             shape_bounds: BoundingBox::default(),
             edge_bounds: BoundingBox::default(),
             dirty: Cell::new(false),
+            stroke_scaling: Cell::new(StrokeScaling::default()),
+            stroke_matrix: Cell::new(None),
             paths: Vec::new(),
             bitmaps: Vec::new(),
             current_fill: None,
@@ -49,10 +57,12 @@ impl Drawing {
 
     pub fn from_swf_shape(shape: &swf::Shape) -> Self {
         let mut this = Self {
-            render_handle: Cell::new(None),
+            render_handle: RefCell::new(None),
             shape_bounds: (&shape.shape_bounds).into(),
             edge_bounds: (&shape.edge_bounds).into(),
             dirty: Cell::new(true),
+            stroke_scaling: Cell::new(StrokeScaling::default()),
+            stroke_matrix: Cell::new(None),
             paths: Vec::new(),
             bitmaps: Vec::new(),
             current_fill: None,
@@ -78,7 +88,9 @@ impl Drawing {
 
                     this.set_line_style(None);
                 }
-                DrawPath::Fill { style, commands } => {
+                DrawPath::Fill {
+                    style, commands, ..
+                } => {
                     this.set_fill_style(Some(style.clone()));
 
                     for command in commands {
@@ -87,6 +99,8 @@ impl Drawing {
 
                     this.set_fill_style(None);
                 }
+                // SWF shapes are never made of triangles
+                DrawPath::Triangles { .. } => {}
             }
         }
 
@@ -120,12 +134,89 @@ impl Drawing {
                     x: self.cursor.0,
                     y: self.cursor.1,
                 }],
+                winding_rule: FillRule::EvenOdd,
             });
         }
         self.fill_start = self.cursor;
         self.dirty.set(true);
     }
 
+    /// Changes how the current fill is filled where it overlaps itself.
+    /// Anything that was already drawn with the fill keeps its previous rule.
+    pub fn set_winding_rule(&mut self, winding_rule: FillRule) {
+        let Some(fill) = &self.current_fill else {
+            return;
+        };
+        if fill.winding_rule == winding_rule {
+            return;
+        }
+        if fill.commands.len() > 1 {
+            let style = fill.style.clone();
+            self.set_fill_style(Some(style));
+        }
+        if let Some(fill) = &mut self.current_fill {
+            fill.winding_rule = winding_rule;
+        }
+    }
+
+    /// Fills a list of triangles with the current fill, and outlines each of them with the current
+    /// line. Triangles that face the culled direction, or that have corners which don't exist,
+    /// are left out.
+    pub fn draw_triangles(&mut self, mut triangles: TriangleList, culling: TriangleCulling) {
+        let vertex_count = triangles
+            .vertices
+            .len()
+            .min(triangles.uvs.as_ref().map_or(usize::MAX, |uvs| uvs.len()));
+        let vertices = &triangles.vertices;
+        triangles.indices = triangles
+            .indices
+            .chunks_exact(3)
+            .filter(|indices| {
+                indices.iter().all(|&index| (index as usize) < vertex_count)
+                    && culling.is_drawn(indices.iter().map(|&index| vertices[index as usize]))
+            })
+            .flatten()
+            .copied()
+            .collect();
+        if triangles.indices.is_empty() {
+            return;
+        }
+
+        let outlines: Vec<_> = if self.current_line.is_some() {
+            triangles.triangles().map(|(corners, _)| corners).collect()
+        } else {
+            Vec::new()
+        };
+
+        if let Some(fill) = &self.current_fill {
+            // The triangles are drawn over anything that was drawn with the fill before them
+            let style = fill.style.clone();
+            self.set_fill_style(Some(style.clone()));
+            for &index in &triangles.indices {
+                let (x, y) = triangles.vertices[index as usize];
+                self.shape_bounds.encompass(x, y);
+                self.edge_bounds.encompass(x, y);
+            }
+            self.paths.push(DrawingPath::Triangles(DrawingTriangles {
+                style,
+                triangles,
+            }));
+        }
+
+        if !outlines.is_empty() {
+            // Only the line follows the outlines, as the triangles are already filled
+            let fill = self.current_fill.take();
+            for [(x0, y0), (x1, y1), (x2, y2)] in outlines {
+                self.draw_command(DrawCommand::MoveTo { x: x0, y: y0 });
+                self.draw_command(DrawCommand::LineTo { x: x1, y: y1 });
+                self.draw_command(DrawCommand::LineTo { x: x2, y: y2 });
+                self.draw_command(DrawCommand::LineTo { x: x0, y: y0 });
+            }
+            self.set_fill_style(fill.map(|fill| fill.style));
+        }
+        self.dirty.set(true);
+    }
+
     pub fn clear(&mut self) {
         self.current_fill = None;
         self.current_line = None;
@@ -203,6 +294,68 @@ impl Drawing {
         self.dirty.set(true);
     }
 
+    /// Draws a cubic Bezier curve from the current position, approximated by quadratic curves.
+    pub fn draw_cubic_curve(
+        &mut self,
+        control_a: (Twips, Twips),
+        control_b: (Twips, Twips),
+        anchor: (Twips, Twips),
+    ) {
+        const SEGMENTS: usize = 4;
+
+        let point = |(x, y): (Twips, Twips)| (x.get() as f64, y.get() as f64);
+        let p0 = point(self.cursor);
+        let p1 = point(control_a);
+        let p2 = point(control_b);
+        let p3 = point(anchor);
+        let position = |t: f64| {
+            let u = 1.0 - t;
+            let (a, b, c, d) = (u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t);
+            (
+                a * p0.0 + b * p1.0 + c * p2.0 + d * p3.0,
+                a * p0.1 + b * p1.1 + c * p2.1 + d * p3.1,
+            )
+        };
+        let tangent = |t: f64| {
+            let u = 1.0 - t;
+            let (a, b, c) = (3.0 * u * u, 6.0 * u * t, 3.0 * t * t);
+            (
+                a * (p1.0 - p0.0) + b * (p2.0 - p1.0) + c * (p3.0 - p2.0),
+                a * (p1.1 - p0.1) + b * (p2.1 - p1.1) + c * (p3.1 - p2.1),
+            )
+        };
+
+        for segment in 0..SEGMENTS {
+            let t0 = segment as f64 / SEGMENTS as f64;
+            let t1 = (segment + 1) as f64 / SEGMENTS as f64;
+            let step = (t1 - t0) / 3.0;
+            // The control points of this part of the cubic, which the quadratic's single control
+            // point is then fitted between.
+            let (start, start_tangent) = (position(t0), tangent(t0));
+            let (end, end_tangent) = (position(t1), tangent(t1));
+            let control_0 = (
+                start.0 + start_tangent.0 * step,
+                start.1 + start_tangent.1 * step,
+            );
+            let control_1 = (end.0 - end_tangent.0 * step, end.1 - end_tangent.1 * step);
+            let control = (
+                (3.0 * (control_0.0 + control_1.0) - start.0 - end.0) / 4.0,
+                (3.0 * (control_0.1 + control_1.1) - start.1 - end.1) / 4.0,
+            );
+            let (x2, y2) = if segment + 1 == SEGMENTS {
+                anchor
+            } else {
+                (Twips::new(end.0 as i32), Twips::new(end.1 as i32))
+            };
+            self.draw_command(DrawCommand::CurveTo {
+                x1: Twips::new(control.0 as i32),
+                y1: Twips::new(control.1 as i32),
+                x2,
+                y2,
+            });
+        }
+    }
+
     pub fn add_bitmap(&mut self, bitmap: BitmapInfo) -> u16 {
         let id = self.bitmaps.len() as u16;
         self.bitmaps.push(bitmap);
@@ -210,7 +363,9 @@ impl Drawing {
     }
 
     pub fn render(&self, context: &mut RenderContext) {
-        if self.dirty.get() {
+        let matrix = context.transform_stack.transform().matrix;
+        let rescaled = self.stroke_scaling.get().stroke_matrix(&matrix) != self.stroke_matrix.get();
+        if self.dirty.get() || rescaled {
             self.dirty.set(false);
             let mut paths = Vec::with_capacity(self.paths.len());
 
@@ -220,6 +375,7 @@ impl Drawing {
                         paths.push(DrawPath::Fill {
                             style: &fill.style,
                             commands: fill.commands.to_owned(),
+                            winding_rule: fill.winding_rule,
                         });
                     }
                     DrawingPath::Line(line) => {
@@ -229,6 +385,12 @@ impl Drawing {
                             is_closed: line.is_closed,
                         });
                     }
+                    DrawingPath::Triangles(triangles) => {
+                        paths.push(DrawPath::Triangles {
+                            style: &triangles.style,
+                            triangles: &triangles.triangles,
+                        });
+                    }
                 }
             }
 
@@ -236,6 +398,7 @@ impl Drawing {
                 paths.push(DrawPath::Fill {
                     style: &fill.style,
                     commands: fill.commands.to_owned(),
+                    winding_rule: fill.winding_rule,
                 })
             }
 
@@ -275,21 +438,27 @@ impl Drawing {
                 })
             }
 
+            let stroke_scaling = StrokeScaling::from_paths(&paths);
+            self.stroke_scaling.set(stroke_scaling);
+            let stroke_matrix = stroke_scaling.stroke_matrix(&matrix);
+            self.stroke_matrix.set(stroke_matrix);
             let shape = DistilledShape {
                 paths,
                 shape_bounds: self.shape_bounds.clone(),
                 edge_bounds: self.edge_bounds.clone(),
                 id: 0,
+                stroke_matrix,
             };
-            if let Some(handle) = self.render_handle.get() {
+            let handle = self.render_handle.borrow().clone();
+            if let Some(handle) = handle {
                 context.renderer.replace_shape(shape, self, handle);
             } else {
-                self.render_handle
-                    .set(Some(context.renderer.register_shape(shape, self)));
+                *self.render_handle.borrow_mut() =
+                    Some(context.renderer.register_shape(shape, self));
             }
         }
 
-        if let Some(handle) = self.render_handle.get() {
+        if let Some(handle) = self.render_handle.borrow().clone() {
             context
                 .commands
                 .render_shape(handle, context.transform_stack.transform());
@@ -300,16 +469,21 @@ impl Drawing {
         self.shape_bounds.clone()
     }
 
-    pub fn hit_test(
-        &self,
-        point: (Twips, Twips),
-        local_matrix: &ruffle_render::matrix::Matrix,
-    ) -> bool {
+    pub fn hit_test(&self, point: (Twips, Twips), local_matrix: &Matrix) -> bool {
         use ruffle_render::shape_utils;
         for path in &self.paths {
             match path {
                 DrawingPath::Fill(fill) => {
-                    if shape_utils::draw_command_fill_hit_test(&fill.commands, point) {
+                    if shape_utils::draw_command_fill_hit_test(
+                        &fill.commands,
+                        fill.winding_rule,
+                        point,
+                    ) {
+                        return true;
+                    }
+                }
+                DrawingPath::Triangles(triangles) => {
+                    if shape_utils::triangle_list_hit_test(&triangles.triangles, point) {
                         return true;
                     }
                 }
@@ -328,7 +502,7 @@ impl Drawing {
 
         // The pending fill will auto-close.
         if let Some(fill) = &self.current_fill {
-            if shape_utils::draw_command_fill_hit_test(&fill.commands, point) {
+            if shape_utils::draw_command_fill_hit_test(&fill.commands, fill.winding_rule, point) {
                 return true;
             }
         }
@@ -417,6 +591,7 @@ impl BitmapSource for Drawing {
 struct DrawingFill {
     style: FillStyle,
     commands: Vec<DrawCommand>,
+    winding_rule: FillRule,
 }
 
 #[derive(Debug, Clone)]
@@ -426,10 +601,51 @@ struct DrawingLine {
     is_closed: bool,
 }
 
+#[derive(Debug, Clone)]
+struct DrawingTriangles {
+    style: FillStyle,
+    triangles: TriangleList,
+}
+
 #[derive(Debug, Clone)]
 enum DrawingPath {
     Fill(DrawingFill),
     Line(DrawingLine),
+    Triangles(DrawingTriangles),
+}
+
+/// Which of the triangles given to [`Drawing::draw_triangles`] are left out,
+/// by the direction that their corners go around them in.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum TriangleCulling {
+    /// Every triangle is drawn.
+    #[default]
+    None,
+
+    /// Triangles with a positive normal are left out, which are those that go clockwise.
+    Positive,
+
+    /// Triangles with a negative normal are left out, which are those that go counterclockwise.
+    Negative,
+}
+
+impl TriangleCulling {
+    fn is_drawn(self, mut corners: impl Iterator<Item = (Twips, Twips)>) -> bool {
+        if self == Self::None {
+            return true;
+        }
+        let mut corner = || {
+            let (x, y) = corners.next().unwrap_or_default();
+            (i64::from(x.get()), i64::from(y.get()))
+        };
+        let ((x0, y0), (x1, y1), (x2, y2)) = (corner(), corner(), corner());
+        let normal = (x1 - x0) * (y2 - y0) - (y1 - y0) * (x2 - x0);
+        match self {
+            Self::None => true,
+            Self::Positive => normal <= 0,
+            Self::Negative => normal >= 0,
+        }
+    }
 }
 
 fn stretch_bounding_box(
@@ -32,6 +32,15 @@ pub enum PlayerEvent {
     TextInput {
         codepoint: char,
     },
+    /// The text that an input method is composing, which is shown in the focused text field
+    /// until it's committed. Empty text means that the composition was cancelled.
+    ImeComposition {
+        text: String,
+    },
+    /// The text that an input method committed, which replaces what it was composing.
+    ImeCommit {
+        text: String,
+    },
 }
 
 /// The distance scrolled by the mouse wheel.
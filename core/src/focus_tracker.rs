@@ -1,7 +1,9 @@
 use crate::avm1::Avm1;
 use crate::avm1::Value;
 use crate::context::UpdateContext;
+use crate::display_object::TInteractiveObject;
 pub use crate::display_object::{DisplayObject, TDisplayObject, TDisplayObjectContainer};
+use crate::string::WStr;
 use gc_arena::{Collect, GcCell, MutationContext};
 
 #[derive(Clone, Copy, Collect)]
@@ -36,10 +38,20 @@ impl<'gc> FocusTracker<'gc> {
         }
 
         if let Some(old) = old {
+            // Whatever an input method was composing in a text field is dropped with its focus.
+            if let Some(text) = old.as_edit_text() {
+                text.set_ime_composition(WStr::empty(), context);
+            }
             old.on_focus_changed(context.gc_context, false);
         }
         if let Some(new) = focused_element {
             new.on_focus_changed(context.gc_context, true);
+            if new
+                .as_interactive()
+                .map_or(false, |new| new.needs_soft_keyboard())
+            {
+                context.ui.request_soft_keyboard();
+            }
         }
 
         tracing::info!("Focus is now on {:?}", focused_element);
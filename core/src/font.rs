@@ -1,11 +1,15 @@
+use crate::backend::ui::FontDefinition;
 use crate::html::TextSpan;
 use crate::prelude::*;
 use crate::string::WStr;
 use gc_arena::{Collect, Gc, MutationContext};
+use once_cell::unsync::OnceCell;
+use owned_ttf_parser::{AsFaceRef, FaceParsingError, GlyphId, OutlineBuilder, OwnedFace};
 use ruffle_render::backend::{RenderBackend, ShapeHandle};
 use ruffle_render::transform::Transform;
-use std::cell::{Cell, Ref, RefCell};
+use std::cell::{Ref, RefCell};
 use std::cmp::max;
+use std::rc::Rc;
 
 pub use swf::TextGridFit;
 
@@ -62,27 +66,25 @@ impl EvalParameters {
 
 #[derive(Debug, Clone, Collect, Copy)]
 #[collect(no_drop)]
-pub struct Font<'gc>(Gc<'gc, FontData>);
+pub struct Font<'gc>(Gc<'gc, FontData<'gc>>);
 
-#[derive(Debug, Clone, Collect)]
-#[collect(require_static)]
-struct FontData {
-    /// The list of glyphs defined in the font.
-    /// Used directly by `DefineText` tags.
-    glyphs: Vec<Glyph>,
+#[derive(Debug, Collect)]
+#[collect(no_drop)]
+struct FontData<'gc> {
+    /// Where the glyphs of the font come from.
+    #[collect(require_static)]
+    glyphs: Rc<GlyphSource>,
 
-    /// A map from a Unicode code point to glyph in the `glyphs` array.
-    /// Used by `DefineEditText` tags.
-    code_point_to_glyph: fnv::FnvHashMap<u16, usize>,
+    /// The fonts that characters this font has no glyphs for are drawn with, in order.
+    ///
+    /// Only device fonts have these. They all have the same scale, so that their glyphs
+    /// can be laid out together.
+    fallbacks: Vec<Font<'gc>>,
 
     /// The scaling applied to the font height to render at the proper size.
     /// This depends on the DefineFont tag version.
     scale: f32,
 
-    /// Kerning infomration.
-    /// Maps from a pair of unicode code points to horizontal offset value.
-    kerning_pairs: fnv::FnvHashMap<(u16, u16), Twips>,
-
     /// The distance from the top of each glyph to the baseline of the font, in
     /// EM-square coordinates.
     ascent: u16,
@@ -99,6 +101,222 @@ struct FontData {
     descriptor: FontDescriptor,
 }
 
+/// The glyphs of a font.
+#[derive(Debug)]
+enum GlyphSource {
+    /// Glyphs from a font tag, which are all read along with it.
+    Memory {
+        /// The list of glyphs defined in the font.
+        /// Used directly by `DefineText` tags.
+        glyphs: Vec<Glyph>,
+
+        /// A map from a Unicode code point to glyph in the `glyphs` array.
+        /// Used by `DefineEditText` tags.
+        code_point_to_glyph: fnv::FnvHashMap<u16, usize>,
+
+        /// Kerning infomration.
+        /// Maps from a pair of unicode code points to horizontal offset value.
+        kerning_pairs: fnv::FnvHashMap<(u16, u16), Twips>,
+    },
+
+    /// Glyphs from a font file, which are converted as they're first used.
+    FontFace(FontFace),
+}
+
+/// The EM-square size that glyphs from font files are converted to, which is that of
+/// DefineFont3 tags. The embedded device font is one, so it can be a fallback of theirs.
+const DEVICE_FONT_SCALE: f32 = 20480.0;
+
+/// A TrueType or OpenType font that device font glyphs are read from.
+struct FontFace {
+    face: OwnedFace,
+
+    /// The glyphs that have been converted so far, by their glyph IDs.
+    glyphs: Vec<OnceCell<Glyph>>,
+
+    /// Converts from the font's units to the `DEVICE_FONT_SCALE` EM-square.
+    units_scale: f32,
+}
+
+impl FontFace {
+    fn new(data: Vec<u8>, index: u32) -> Result<Self, FaceParsingError> {
+        let face = OwnedFace::from_vec(data, index)?;
+        let face_ref = face.as_face_ref();
+        let glyphs = (0..face_ref.number_of_glyphs())
+            .map(|_| OnceCell::new())
+            .collect();
+        let units_scale = DEVICE_FONT_SCALE / f32::from(face_ref.units_per_em());
+        Ok(Self {
+            face,
+            glyphs,
+            units_scale,
+        })
+    }
+
+    fn get_glyph_for_char(&self, c: char) -> Option<&Glyph> {
+        let glyph_id = self.face.as_face_ref().glyph_index(c)?;
+        let glyph = self.glyphs.get(usize::from(glyph_id.0))?;
+        Some(glyph.get_or_init(|| self.convert_glyph(c, glyph_id)))
+    }
+
+    /// Converts the outline of a glyph into the shape records of a font tag's glyph.
+    fn convert_glyph(&self, c: char, glyph_id: GlyphId) -> Glyph {
+        let face = self.face.as_face_ref();
+        let mut builder = GlyphBuilder::new(self.units_scale);
+        // Glyphs without outlines, like spaces, only have an advance
+        face.outline_glyph(glyph_id, &mut builder);
+        let advance = face.glyph_hor_advance(glyph_id).unwrap_or_default();
+
+        Glyph {
+            shape_handle: RefCell::new(None),
+            advance: (f32::from(advance) * self.units_scale) as i16,
+            shape: RefCell::new(None),
+            swf_glyph: swf::Glyph {
+                shape_records: builder.records,
+                code: c as u16,
+                advance: (f32::from(advance) * self.units_scale) as i16,
+                bounds: None,
+            },
+        }
+    }
+
+    fn get_kerning_offset(&self, left: char, right: char) -> Option<Twips> {
+        let face = self.face.as_face_ref();
+        let left = face.glyph_index(left)?;
+        let right = face.glyph_index(right)?;
+        let adjustment = face
+            .tables()
+            .kern?
+            .subtables
+            .into_iter()
+            .filter(|subtable| subtable.horizontal && !subtable.variable)
+            .find_map(|subtable| subtable.glyphs_kerning(left, right))?;
+        Some(Twips::new(
+            (f32::from(adjustment) * self.units_scale) as i32,
+        ))
+    }
+
+    fn has_kerning_info(&self) -> bool {
+        self.face.as_face_ref().tables().kern.is_some()
+    }
+}
+
+impl std::fmt::Debug for FontFace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FontFace")
+            .field("units_scale", &self.units_scale)
+            .finish()
+    }
+}
+
+/// Builds the shape records of a glyph from the outline of a font file's glyph, which is
+/// flipped from pointing up to pointing down the way that shapes do.
+struct GlyphBuilder {
+    records: Vec<swf::ShapeRecord>,
+    units_scale: f32,
+    /// The position that the last edge ended at, which the next one is relative to.
+    position: (Twips, Twips),
+    /// The position that the current contour started at, which it's closed back to.
+    start: (Twips, Twips),
+}
+
+impl GlyphBuilder {
+    fn new(units_scale: f32) -> Self {
+        Self {
+            records: Vec::new(),
+            units_scale,
+            position: Default::default(),
+            start: Default::default(),
+        }
+    }
+
+    fn point(&self, x: f32, y: f32) -> (Twips, Twips) {
+        (
+            Twips::new((x * self.units_scale).round() as i32),
+            Twips::new((-y * self.units_scale).round() as i32),
+        )
+    }
+
+    fn line(&mut self, to: (Twips, Twips)) {
+        if to != self.position {
+            self.records.push(swf::ShapeRecord::StraightEdge {
+                delta_x: to.0 - self.position.0,
+                delta_y: to.1 - self.position.1,
+            });
+            self.position = to;
+        }
+    }
+
+    fn quad(&mut self, control: (Twips, Twips), to: (Twips, Twips)) {
+        self.records.push(swf::ShapeRecord::CurvedEdge {
+            control_delta_x: control.0 - self.position.0,
+            control_delta_y: control.1 - self.position.1,
+            anchor_delta_x: to.0 - control.0,
+            anchor_delta_y: to.1 - control.1,
+        });
+        self.position = to;
+    }
+}
+
+impl OutlineBuilder for GlyphBuilder {
+    fn move_to(&mut self, x: f32, y: f32) {
+        let to = self.point(x, y);
+        // The first move also sets the fill that glyph shapes are drawn with
+        let fill_style_1 = self.records.is_empty().then_some(1);
+        self.records.push(swf::ShapeRecord::StyleChange(Box::new(
+            swf::StyleChangeData {
+                move_to: Some(to),
+                fill_style_0: None,
+                fill_style_1,
+                line_style: None,
+                new_styles: None,
+            },
+        )));
+        self.position = to;
+        self.start = to;
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        let to = self.point(x, y);
+        self.line(to);
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        let control = self.point(x1, y1);
+        let to = self.point(x, y);
+        self.quad(control, to);
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        // Shapes only have quadratic curves, so a cubic curve is split in half with each
+        // half approximated by a quadratic curve.
+        let (x0, y0) = (
+            self.position.0.get() as f32 / self.units_scale,
+            -self.position.1.get() as f32 / self.units_scale,
+        );
+        let mid = |a: f32, b: f32| (a + b) / 2.0;
+        let (ax, ay) = (mid(x0, x1), mid(y0, y1));
+        let (bx, by) = (mid(x1, x2), mid(y1, y2));
+        let (cx, cy) = (mid(x2, x), mid(y2, y));
+        let (abx, aby) = (mid(ax, bx), mid(ay, by));
+        let (bcx, bcy) = (mid(bx, cx), mid(by, cy));
+        let (split_x, split_y) = (mid(abx, bcx), mid(aby, bcy));
+        let control = |p0: f32, c1: f32, c2: f32, p3: f32| (3.0 * (c1 + c2) - p0 - p3) / 4.0;
+
+        let first_control =
+            self.point(control(x0, ax, abx, split_x), control(y0, ay, aby, split_y));
+        let split = self.point(split_x, split_y);
+        self.quad(first_control, split);
+        let second_control = self.point(control(split_x, bcx, cx, x), control(split_y, bcy, cy, y));
+        let to = self.point(x, y);
+        self.quad(second_control, to);
+    }
+
+    fn close(&mut self) {
+        self.line(self.start);
+    }
+}
+
 impl<'gc> Font<'gc> {
     pub fn from_swf_tag(
         gc_context: MutationContext<'gc, '_>,
@@ -126,7 +344,7 @@ impl<'gc> Font<'gc> {
             };
             let glyph_code = swf_glyph.code;
             let glyph = Glyph {
-                shape_handle: Cell::new(handle),
+                shape_handle: RefCell::new(handle),
                 advance: swf_glyph.advance,
                 shape: RefCell::new(None),
                 swf_glyph,
@@ -148,13 +366,16 @@ impl<'gc> Font<'gc> {
         Font(Gc::allocate(
             gc_context,
             FontData {
-                glyphs,
-                code_point_to_glyph,
+                glyphs: Rc::new(GlyphSource::Memory {
+                    glyphs,
+                    code_point_to_glyph,
+                    kerning_pairs,
+                }),
+                fallbacks: Vec::new(),
 
                 /// DefineFont3 stores coordinates at 20x the scale of DefineFont1/2.
                 /// (SWF19 p.164)
                 scale: if tag_version >= 3 { 20480.0 } else { 1024.0 },
-                kerning_pairs,
                 ascent,
                 descent,
                 leading,
@@ -163,28 +384,102 @@ impl<'gc> Font<'gc> {
         ))
     }
 
+    /// Reads a font from a font file, to draw device fonts with.
+    pub fn from_font_file(
+        gc_context: MutationContext<'gc, '_>,
+        definition: FontDefinition,
+    ) -> Result<Font<'gc>, FaceParsingError> {
+        let FontDefinition::FontFile {
+            name,
+            is_bold,
+            is_italic,
+            data,
+            index,
+        } = definition;
+        let face = FontFace::new(data, index)?;
+        let face_ref = face.face.as_face_ref();
+        let ascent = (f32::from(face_ref.ascender()) * face.units_scale) as u16;
+        let descent = (-f32::from(face_ref.descender()) * face.units_scale) as u16;
+        let leading = (f32::from(face_ref.line_gap()) * face.units_scale) as i16;
+
+        Ok(Font(Gc::allocate(
+            gc_context,
+            FontData {
+                glyphs: Rc::new(GlyphSource::FontFace(face)),
+                fallbacks: Vec::new(),
+                scale: DEVICE_FONT_SCALE,
+                ascent,
+                descent,
+                leading,
+                descriptor: FontDescriptor::from_parts(&name, is_bold, is_italic),
+            },
+        )))
+    }
+
+    /// Returns a copy of this font that draws the characters that it has no glyphs for
+    /// with the first of `fallbacks` that does.
+    ///
+    /// The fallbacks must have the same scale as this font.
+    pub fn with_fallbacks(
+        self,
+        gc_context: MutationContext<'gc, '_>,
+        fallbacks: Vec<Self>,
+    ) -> Self {
+        Font(Gc::allocate(
+            gc_context,
+            FontData {
+                glyphs: self.0.glyphs.clone(),
+                fallbacks,
+                scale: self.0.scale,
+                ascent: self.0.ascent,
+                descent: self.0.descent,
+                leading: self.0.leading,
+                descriptor: self.0.descriptor.clone(),
+            },
+        ))
+    }
+
     /// Returns whether this font contains glyph shapes.
     /// If not, this font should be rendered as a device font.
     pub fn has_glyphs(&self) -> bool {
-        !self.0.glyphs.is_empty()
+        match &*self.0.glyphs {
+            GlyphSource::Memory { glyphs, .. } => !glyphs.is_empty(),
+            GlyphSource::FontFace(_) => true,
+        }
     }
 
     /// Returns a glyph entry by index.
     /// Used by `Text` display objects.
     pub fn get_glyph(&self, i: usize) -> Option<&Glyph> {
-        self.0.glyphs.get(i)
+        match &*self.0.glyphs {
+            GlyphSource::Memory { glyphs, .. } => glyphs.get(i),
+            GlyphSource::FontFace(_) => None,
+        }
     }
 
-    /// Returns a glyph entry by character.
+    /// Returns a glyph entry by character, from this font or else one of its fallbacks.
     /// Used by `EditText` display objects.
     pub fn get_glyph_for_char(&self, c: char) -> Option<&Glyph> {
-        // TODO: Properly handle UTF-16/out-of-bounds code points.
-        let code_point = c as u16;
-        if let Some(index) = self.0.code_point_to_glyph.get(&code_point) {
-            self.get_glyph(*index)
-        } else {
-            None
-        }
+        let glyph = match &*self.0.glyphs {
+            GlyphSource::Memory {
+                glyphs,
+                code_point_to_glyph,
+                ..
+            } => {
+                // TODO: Properly handle UTF-16/out-of-bounds code points.
+                let code_point = c as u16;
+                code_point_to_glyph
+                    .get(&code_point)
+                    .and_then(|index| glyphs.get(*index))
+            }
+            GlyphSource::FontFace(face) => face.get_glyph_for_char(c),
+        };
+        glyph.or_else(|| {
+            self.0
+                .fallbacks
+                .iter()
+                .find_map(|fallback| fallback.get_glyph_for_char(c))
+        })
     }
 
     /// Determine if this font contains all the glyphs within a given string.
@@ -203,14 +498,18 @@ impl<'gc> Font<'gc> {
     /// to the advance value between these two characters.
     /// Returns 0 twips if no kerning offset exists between these two characters.
     pub fn get_kerning_offset(&self, left: char, right: char) -> Twips {
-        // TODO: Properly handle UTF-16/out-of-bounds code points.
-        let left_code_point = left as u16;
-        let right_code_point = right as u16;
-        self.0
-            .kerning_pairs
-            .get(&(left_code_point, right_code_point))
-            .cloned()
-            .unwrap_or_default()
+        match &*self.0.glyphs {
+            GlyphSource::Memory { kerning_pairs, .. } => {
+                // TODO: Properly handle UTF-16/out-of-bounds code points.
+                let left_code_point = left as u16;
+                let right_code_point = right as u16;
+                kerning_pairs
+                    .get(&(left_code_point, right_code_point))
+                    .cloned()
+                    .unwrap_or_default()
+            }
+            GlyphSource::FontFace(face) => face.get_kerning_offset(left, right).unwrap_or_default(),
+        }
     }
 
     /// Return the leading for this font at a given height.
@@ -236,7 +535,10 @@ impl<'gc> Font<'gc> {
 
     /// Returns whether this font contains kerning information.
     pub fn has_kerning_info(&self) -> bool {
-        !self.0.kerning_pairs.is_empty()
+        match &*self.0.glyphs {
+            GlyphSource::Memory { kerning_pairs, .. } => !kerning_pairs.is_empty(),
+            GlyphSource::FontFace(face) => face.has_kerning_info(),
+        }
     }
 
     pub fn scale(&self) -> f32 {
@@ -409,6 +711,11 @@ impl<'gc> Font<'gc> {
         None
     }
 
+    /// Returns whether two fonts are the same font, rather than copies of one.
+    pub fn ptr_eq(a: Font<'gc>, b: Font<'gc>) -> bool {
+        Gc::ptr_eq(a.0, b.0)
+    }
+
     pub fn descriptor(&self) -> &FontDescriptor {
         &self.0.descriptor
     }
@@ -419,7 +726,7 @@ pub struct Glyph {
     pub advance: i16,
     // Handle to registered shape.
     // If None, it'll be loaded lazily on first render of this glyph.
-    shape_handle: Cell<Option<ShapeHandle>>,
+    shape_handle: RefCell<Option<ShapeHandle>>,
     // Same shape as one in swf_glyph, but wrapped in an swf::Shape;
     // For use in hit tests. Created lazily on first use.
     // (todo: refactor hit tests to not require this?
@@ -431,11 +738,10 @@ pub struct Glyph {
 
 impl Glyph {
     pub fn shape_handle(&self, renderer: &mut dyn RenderBackend) -> ShapeHandle {
-        if self.shape_handle.get().is_none() {
-            self.shape_handle
-                .set(Some(renderer.register_glyph_shape(&self.swf_glyph)))
-        }
-        self.shape_handle.get().unwrap()
+        self.shape_handle
+            .borrow_mut()
+            .get_or_insert_with(|| renderer.register_glyph_shape(&self.swf_glyph))
+            .clone()
     }
 
     pub fn as_shape(&self) -> Ref<'_, swf::Shape> {
@@ -866,4 +1172,20 @@ mod tests {
             assert_eq!(None, breakpoint5);
         });
     }
+
+    #[test]
+    fn measure_letter_spacing() {
+        with_device_font(|_mc, df| {
+            let string = WStr::from_units(b"abcd");
+            let params =
+                EvalParameters::from_parts(Twips::from_pixels(12.0), Twips::from_pixels(0.0), true);
+            let spaced_params =
+                EvalParameters::from_parts(Twips::from_pixels(12.0), Twips::from_pixels(2.0), true);
+            let (width, _) = df.measure(string, params, false);
+            let (spaced_width, _) = df.measure(string, spaced_params, false);
+
+            // The spacing follows every glyph's advance, including the last one's
+            assert_eq!(width + Twips::from_pixels(8.0), spaced_width);
+        });
+    }
 }
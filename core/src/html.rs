@@ -1,14 +1,18 @@
 //! HTML related utilities
 
 mod dimensions;
+mod image;
 mod iterators;
 mod layout;
+mod style_sheet;
 mod text_format;
 
 pub use dimensions::BoxBounds;
 pub use dimensions::Position;
 pub use dimensions::Size;
+pub use image::{ImageAlign, TextImage};
 pub use layout::{LayoutBox, LayoutContent, LayoutMetrics};
+pub use style_sheet::{Display, Style, StyleSheet};
 pub use text_format::{FormatSpans, TextFormat, TextSpan};
 
 #[cfg(test)]
@@ -0,0 +1,61 @@
+//! Images embedded into HTML text with `<img>`
+
+use crate::string::{WStr, WString};
+
+/// Which side of the text field an image is placed on, with the text wrapping around it.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ImageAlign {
+    Left,
+    Right,
+}
+
+/// An image embedded into text by an `<img>` tag.
+///
+/// It takes the place of a single character of the text, which is laid out as the image
+/// instead of as a glyph.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextImage {
+    /// The linkage ID of a library symbol, or the URL of an image or movie to load.
+    pub src: WString,
+
+    /// The instance name of the clip that shows the image, which scripts can get it by.
+    pub id: Option<WString>,
+
+    /// The width to scale the image to, in pixels, or its own width if not given.
+    pub width: Option<f64>,
+
+    /// The height to scale the image to, in pixels, or its own height if not given.
+    pub height: Option<f64>,
+
+    /// The space to either side of the image that text is kept out of, in pixels.
+    pub hspace: f64,
+
+    /// The space above and below the image that text is kept out of, in pixels.
+    pub vspace: f64,
+
+    pub align: ImageAlign,
+}
+
+impl TextImage {
+    /// The character that stands in for an image in the text.
+    pub const CHARACTER: u16 = 0xFFFC;
+
+    /// Reads an image from the attributes of an `<img>` tag, if it has a `src`.
+    pub fn from_attributes(attribute: impl Fn(&'static [u8]) -> Option<WString>) -> Option<Self> {
+        let src = attribute(b"src")?;
+        let pixels = |name| attribute(name).and_then(|value| value.trim().parse().ok());
+        let align = match attribute(b"align") {
+            Some(align) if align.eq_ignore_case(WStr::from_units(b"right")) => ImageAlign::Right,
+            _ => ImageAlign::Left,
+        };
+        Some(Self {
+            src,
+            id: attribute(b"id"),
+            width: pixels(b"width"),
+            height: pixels(b"height"),
+            hspace: pixels(b"hspace").unwrap_or(8.0),
+            vspace: pixels(b"vspace").unwrap_or(8.0),
+            align,
+        })
+    }
+}
@@ -4,6 +4,7 @@ use crate::context::UpdateContext;
 use crate::drawing::Drawing;
 use crate::font::{EvalParameters, Font};
 use crate::html::dimensions::{BoxBounds, Position, Size};
+use crate::html::image::{ImageAlign, TextImage};
 use crate::html::text_format::{FormatSpans, TextFormat, TextSpan};
 use crate::string::{utils as string_utils, WStr};
 use crate::tag_utils::SwfMovie;
@@ -33,6 +34,18 @@ fn draw_underline(drawing: &mut Drawing, starting_pos: Position<Twips>, width: T
     });
 }
 
+/// An image that text wraps around for as long as it's beside it.
+struct Float {
+    align: ImageAlign,
+
+    /// The vertical extent of the image, including the space around it.
+    top: Twips,
+    bottom: Twips,
+
+    /// The width of the image, including the space to either side of it.
+    width: Twips,
+}
+
 /// Contains information relating to the current layout operation.
 pub struct LayoutContext<'a, 'gc> {
     /// The movie this layout context is pulling fonts from.
@@ -87,6 +100,9 @@ pub struct LayoutContext<'a, 'gc> {
 
     /// The total width of the text field being laid out.
     max_bounds: Twips,
+
+    /// The images laid out so far, which keep text out of the lines beside them.
+    floats: Vec<Float>,
 }
 
 impl<'a, 'gc> LayoutContext<'a, 'gc> {
@@ -104,6 +120,7 @@ impl<'a, 'gc> LayoutContext<'a, 'gc> {
             current_line: 0,
             current_line_span: Default::default(),
             max_bounds,
+            floats: Vec::new(),
         }
     }
 
@@ -257,9 +274,11 @@ impl<'a, 'gc> LayoutContext<'a, 'gc> {
 
         let mut line_bounds = line_bounds.unwrap_or_default();
 
+        let (left_floats, right_floats) = self.float_widths();
         let left_adjustment =
-            Self::left_alignment_offset(&self.current_line_span, self.is_first_line);
-        let right_adjustment = Twips::from_pixels(self.current_line_span.right_margin);
+            Self::left_alignment_offset(&self.current_line_span, self.is_first_line) + left_floats;
+        let right_adjustment =
+            Twips::from_pixels(self.current_line_span.right_margin) + right_floats;
 
         let misalignment =
             self.max_bounds - left_adjustment - right_adjustment - line_bounds.width();
@@ -428,14 +447,23 @@ impl<'a, 'gc> LayoutContext<'a, 'gc> {
         is_device_font: bool,
     ) -> Option<Font<'gc>> {
         let library = context.library.library_for_movie_mut(self.movie.clone());
+        let font_name = span.font.to_utf8_lossy();
 
-        // If this text field is set to use device fonts, fallback to using our embedded Noto Sans.
+        // If this text field is set to use device fonts, fallback to looking the font up on the system,
+        // and then to our embedded Noto Sans.
         // Note that the SWF can still contain a DefineFont tag with no glyphs/layout info in this case (see #451).
-        // In an ideal world, device fonts would search for a matching font on the system and render it in some way.
         if let Some(font) = library
-            .get_font_by_name(&span.font.to_utf8_lossy(), span.bold, span.italic)
+            .get_font_by_name(&font_name, span.bold, span.italic)
             .filter(|f| !is_device_font && f.has_glyphs())
-            .or_else(|| context.library.device_font())
+            .or_else(|| {
+                context.library.get_or_load_device_font(
+                    context.gc_context,
+                    &*context.ui,
+                    &font_name,
+                    span.bold,
+                    span.italic,
+                )
+            })
         {
             self.font = Some(font);
             return self.font;
@@ -515,6 +543,93 @@ impl<'a, 'gc> LayoutContext<'a, 'gc> {
         }
     }
 
+    /// Append an image, which goes to the side of the text field that it's aligned to and
+    /// has the lines of text beside it wrap around it.
+    ///
+    /// Images start on a line of their own, so any text before one on its line is
+    /// finished first. `natural_size` is the size of the image's content, which is used
+    /// for the dimensions that the image doesn't set.
+    fn append_image(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc>,
+        position: usize,
+        index: usize,
+        span: &TextSpan,
+        image: &TextImage,
+        natural_size: (Twips, Twips),
+    ) {
+        if !self.is_start_of_line() {
+            self.newline(context, self.text, position, span);
+        }
+
+        let width = image.width.map_or(natural_size.0, Twips::from_pixels);
+        let height = image.height.map_or(natural_size.1, Twips::from_pixels);
+        let hspace = Twips::from_pixels(image.hspace);
+        let vspace = Twips::from_pixels(image.vspace);
+
+        let (left_floats, right_floats) = self.float_widths();
+        let x = match image.align {
+            ImageAlign::Left => {
+                Self::left_alignment_offset_without_bullet(span, self.is_first_line)
+                    + left_floats
+                    + hspace
+            }
+            ImageAlign::Right => {
+                self.max_bounds
+                    - Twips::from_pixels(span.right_margin)
+                    - right_floats
+                    - hspace
+                    - width
+            }
+        };
+        let top = self.cursor.y();
+        let bounds = BoxBounds::from_position_and_size(
+            Position::from((x, top + vspace)),
+            Size::from((width, height)),
+        );
+        self.append_box(LayoutBox {
+            bounds,
+            content: LayoutContent::Image { position, index },
+        });
+
+        // The image isn't part of any line, so it's left alone by line fixup.
+        self.current_line = self.boxes.len();
+
+        let bottom = top + height + vspace + vspace;
+        self.floats.push(Float {
+            align: image.align,
+            top,
+            bottom,
+            width: width + hspace + hspace,
+        });
+
+        let float_bounds = BoxBounds::from_position_and_size(
+            Position::from((x - hspace, top)),
+            Size::from((width + hspace + hspace, bottom - top)),
+        );
+        if let Some(eb) = &mut self.exterior_bounds {
+            *eb += float_bounds;
+        } else {
+            self.exterior_bounds = Some(float_bounds);
+        }
+    }
+
+    /// The widths taken up by images to the left and right of the current line.
+    fn float_widths(&self) -> (Twips, Twips) {
+        let y = self.cursor.y();
+        let mut left = Twips::ZERO;
+        let mut right = Twips::ZERO;
+        for float in &self.floats {
+            if float.top <= y && y < float.bottom {
+                match float.align {
+                    ImageAlign::Left => left += float.width,
+                    ImageAlign::Right => right += float.width,
+                }
+            }
+        }
+        (left, right)
+    }
+
     /// Add a box to the current line of text.
     ///
     /// The box should have been positioned according to the current cursor
@@ -559,8 +674,11 @@ impl<'a, 'gc> LayoutContext<'a, 'gc> {
     ///
     /// Offsets returned by this function should not be considered final;
     fn wrap_dimensions(&self, current_span: &TextSpan) -> (Twips, Twips) {
-        let width = self.max_bounds - Twips::from_pixels(self.current_line_span.right_margin);
-        let offset = Self::left_alignment_offset(current_span, self.is_first_line);
+        let (left_floats, right_floats) = self.float_widths();
+        let width = self.max_bounds
+            - Twips::from_pixels(self.current_line_span.right_margin)
+            - right_floats;
+        let offset = Self::left_alignment_offset(current_span, self.is_first_line) + left_floats;
 
         (width, offset + self.cursor.x())
     }
@@ -603,7 +721,7 @@ pub struct LayoutBox<'gc> {
 
 /// Represents different content modes of a given `LayoutBox`.
 ///
-/// Currently, a `LayoutBox` can contain `Text`, `Bullet`s, or a `Drawing`.
+/// Currently, a `LayoutBox` can contain `Text`, `Bullet`s, a `Drawing`, or an `Image`.
 #[derive(Clone, Debug, Collect)]
 #[collect(no_drop)]
 pub enum LayoutContent<'gc> {
@@ -659,6 +777,18 @@ pub enum LayoutContent<'gc> {
     /// layout box's bounds. The size of those bounds do not affect the
     /// rendering of the drawing.
     Drawing(Drawing),
+
+    /// A layout box containing an image from an `<img>` tag.
+    ///
+    /// The image itself is a display object that belongs to the text field, which
+    /// is scaled to the bounds of the box.
+    Image {
+        /// The position of the character that the image stands in for.
+        position: usize,
+
+        /// Which of the text's images this is, counting from the start of the text.
+        index: usize,
+    },
 }
 
 impl<'gc> LayoutBox<'gc> {
@@ -706,6 +836,9 @@ impl<'gc> LayoutBox<'gc> {
     ///
     /// The returned bounds will include both the text bounds itself, as well
     /// as left and right margins on any of the lines.
+    ///
+    /// `image_sizes` holds the natural size of each of the text's images, in the order
+    /// they appear in the text.
     pub fn lower_from_text_spans(
         fs: &FormatSpans,
         context: &mut UpdateContext<'_, 'gc>,
@@ -713,13 +846,44 @@ impl<'gc> LayoutBox<'gc> {
         bounds: Twips,
         is_word_wrap: bool,
         is_device_font: bool,
+        image_sizes: &[(Twips, Twips)],
     ) -> (Vec<LayoutBox<'gc>>, BoxBounds<Twips>) {
         let mut layout_context = LayoutContext::new(movie, bounds, fs.displayed_text());
+        let mut image_index = 0;
 
         for (span_start, _end, span_text, span) in fs.iter_spans() {
+            // Only the first character of an image's span is the image, as text that's
+            // added right after it goes into the same span.
+            let image = span
+                .image
+                .as_ref()
+                .filter(|_| span_text.get(0) == Some(TextImage::CHARACTER));
+            if image.is_some() {
+                image_index += 1;
+            }
+
             if let Some(font) = layout_context.resolve_font(context, span, is_device_font) {
                 layout_context.newspan(span);
 
+                let mut span_start = span_start;
+                let mut span_text = span_text;
+                if let Some(image) = image {
+                    let index = image_index - 1;
+                    layout_context.append_image(
+                        context,
+                        span_start,
+                        index,
+                        span,
+                        image,
+                        image_sizes.get(index).copied().unwrap_or_default(),
+                    );
+                    span_start += 1;
+                    span_text = &span_text[1..];
+                    if span_text.is_empty() {
+                        continue;
+                    }
+                }
+
                 let params = EvalParameters::from_span(span);
 
                 for text in span_text.split(&[b'\n', b'\r', b'\t'][..]) {
@@ -861,7 +1025,7 @@ impl<'gc> LayoutBox<'gc> {
                 *params,
                 swf::Color::from_rgb(color.to_rgb(), 0xFF),
             )),
-            LayoutContent::Drawing(..) => None,
+            LayoutContent::Drawing(..) | LayoutContent::Image { .. } => None,
         }
     }
 
@@ -871,6 +1035,7 @@ impl<'gc> LayoutBox<'gc> {
             LayoutContent::Text { .. } => None,
             LayoutContent::Bullet { .. } => None,
             LayoutContent::Drawing(drawing) => Some(drawing),
+            LayoutContent::Image { .. } => None,
         }
     }
 
@@ -881,6 +1046,10 @@ impl<'gc> LayoutBox<'gc> {
     pub fn is_bullet(&self) -> bool {
         matches!(&self.content, LayoutContent::Bullet { .. })
     }
+
+    pub fn is_image(&self) -> bool {
+        matches!(&self.content, LayoutContent::Image { .. })
+    }
 }
 
 pub struct LayoutMetrics {
@@ -0,0 +1,304 @@
+//! CSS style sheets that are applied to HTML text
+
+use crate::html::TextFormat;
+use crate::string::{WStr, WString};
+use gc_arena::Collect;
+
+/// How the elements that a style applies to are laid out.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Display {
+    /// The element starts a new line after it ends, like `<p>` does.
+    Block,
+
+    /// The element is part of the line it's on.
+    Inline,
+
+    /// The element and everything within it are hidden.
+    None,
+}
+
+/// A CSS style, made of the properties that were set on it.
+///
+/// Property names are in the camel case that ActionScript uses, such as `fontFamily`.
+#[derive(Clone, Debug, Default)]
+pub struct Style {
+    properties: Vec<(WString, WString)>,
+    format: TextFormat,
+    display: Option<Display>,
+}
+
+impl Style {
+    /// Creates a style from its properties, ignoring the ones that Flash doesn't support.
+    pub fn from_properties(properties: Vec<(WString, WString)>) -> Self {
+        let mut format = TextFormat::default();
+        let mut display = None;
+        for (name, value) in &properties {
+            let value = value.to_utf8_lossy();
+            let value = value.trim();
+            match &*name.to_utf8_lossy() {
+                "color" => format.color = parse_color(value),
+                "display" => {
+                    display = match value {
+                        "block" => Some(Display::Block),
+                        "inline" => Some(Display::Inline),
+                        "none" => Some(Display::None),
+                        _ => display,
+                    }
+                }
+                "fontFamily" => format.font = parse_font_family(value),
+                "fontSize" => format.size = parse_length(value),
+                "fontStyle" => format.italic = parse_keyword(value, "italic", "normal"),
+                "fontWeight" => format.bold = parse_keyword(value, "bold", "normal"),
+                "kerning" => format.kerning = parse_keyword(value, "true", "false"),
+                "leading" => format.leading = parse_length(value),
+                "letterSpacing" => format.letter_spacing = parse_length(value),
+                "marginLeft" => format.left_margin = parse_length(value),
+                "marginRight" => format.right_margin = parse_length(value),
+                "textAlign" => {
+                    format.align = match value {
+                        "left" => Some(swf::TextAlign::Left),
+                        "center" => Some(swf::TextAlign::Center),
+                        "right" => Some(swf::TextAlign::Right),
+                        "justify" => Some(swf::TextAlign::Justify),
+                        _ => None,
+                    }
+                }
+                "textDecoration" => {
+                    format.underline = parse_keyword(value, "underline", "none");
+                }
+                "textIndent" => format.indent = parse_length(value),
+                _ => {}
+            }
+        }
+
+        Self {
+            properties,
+            format,
+            display,
+        }
+    }
+
+    pub fn properties(&self) -> &[(WString, WString)] {
+        &self.properties
+    }
+
+    /// The text format that this style applies, where properties it doesn't set are `None`.
+    pub fn text_format(&self) -> &TextFormat {
+        &self.format
+    }
+
+    pub fn display(&self) -> Option<Display> {
+        self.display
+    }
+}
+
+/// A set of CSS styles, by the selector that they apply to.
+///
+/// Flash only supports simple selectors: tag names like `p`, class names like `.title`,
+/// and the `a:link`, `a:hover` and `a:active` states of links. They're case-insensitive,
+/// and kept in lowercase.
+#[derive(Clone, Debug, Default, Collect)]
+#[collect(require_static)]
+pub struct StyleSheet {
+    styles: Vec<(WString, Style)>,
+}
+
+impl StyleSheet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses CSS text and adds its styles, replacing the ones with the same selectors.
+    ///
+    /// Returns `false` without adding anything if the CSS can't be parsed.
+    pub fn parse_css(&mut self, css: &WStr) -> bool {
+        let Some(rules) = parse_rules(&css.to_utf8_lossy()) else {
+            return false;
+        };
+        for (selectors, properties) in rules {
+            let properties: Vec<_> = properties
+                .into_iter()
+                .map(|(name, value)| (WString::from_utf8(&name), WString::from_utf8(&value)))
+                .collect();
+            for selector in selectors {
+                self.set_style(
+                    &WString::from_utf8(&selector),
+                    Some(Style::from_properties(properties.clone())),
+                );
+            }
+        }
+        true
+    }
+
+    pub fn style(&self, selector: &WStr) -> Option<&Style> {
+        let selector = selector.to_ascii_lowercase();
+        self.styles
+            .iter()
+            .find_map(|(name, style)| (*name == selector).then_some(style))
+    }
+
+    /// Sets the style of a selector, or removes it if `style` is `None`.
+    pub fn set_style(&mut self, selector: &WStr, style: Option<Style>) {
+        let selector = selector.to_ascii_lowercase();
+        let index = self.styles.iter().position(|(name, _)| *name == selector);
+        match (index, style) {
+            (Some(index), Some(style)) => self.styles[index].1 = style,
+            (None, Some(style)) => self.styles.push((selector, style)),
+            (Some(index), None) => {
+                self.styles.remove(index);
+            }
+            (None, None) => {}
+        }
+    }
+
+    /// The selectors that have styles, in the order they were first set.
+    pub fn selectors(&self) -> impl Iterator<Item = &WStr> {
+        self.styles.iter().map(|(name, _)| &**name)
+    }
+
+    pub fn clear(&mut self) {
+        self.styles.clear();
+    }
+
+    /// Applies the styles for an element to the format and display that it inherits.
+    ///
+    /// The style of its tag comes first, then that of its class, and then the `a:link`
+    /// style for links.
+    pub fn apply(
+        &self,
+        tag: &WStr,
+        class: Option<&WStr>,
+        format: &mut TextFormat,
+        display: &mut Display,
+    ) {
+        let class = class.map(|class| {
+            let mut selector = WString::from_utf8(".");
+            selector.push_str(class);
+            selector
+        });
+        let is_link = tag.eq_ignore_case(WStr::from_units(b"a"));
+        let selectors = [
+            Some(tag),
+            class.as_deref(),
+            is_link.then_some(WStr::from_units(b"a:link")),
+        ];
+        for style in selectors
+            .into_iter()
+            .flatten()
+            .filter_map(|s| self.style(s))
+        {
+            *format = style.text_format().clone().mix_with(format.clone());
+            if let Some(style_display) = style.display() {
+                *display = style_display;
+            }
+        }
+    }
+}
+
+/// Parses the rules of a style sheet into their selectors and properties,
+/// with CSS property names like `font-family` turned into `fontFamily`.
+fn parse_rules(css: &str) -> Option<Vec<(Vec<String>, Vec<(String, String)>)>> {
+    let mut rules = Vec::new();
+    let css = strip_comments(css);
+    let mut rest = css.trim_start();
+    while !rest.is_empty() {
+        let (selectors, after_selectors) = rest.split_once('{')?;
+        let (body, after_body) = after_selectors.split_once('}')?;
+
+        let selectors: Vec<_> = selectors
+            .split(',')
+            .map(|selector| selector.trim().to_string())
+            .collect();
+        if selectors.iter().any(|selector| selector.is_empty()) {
+            return None;
+        }
+
+        let mut properties = Vec::new();
+        for declaration in body.split(';') {
+            let declaration = declaration.trim();
+            if declaration.is_empty() {
+                continue;
+            }
+            let (name, value) = declaration.split_once(':')?;
+            let name = name.trim();
+            if name.is_empty() {
+                return None;
+            }
+            properties.push((camel_case(name), value.trim().to_string()));
+        }
+
+        rules.push((selectors, properties));
+        rest = after_body.trim_start();
+    }
+    Some(rules)
+}
+
+fn strip_comments(css: &str) -> String {
+    let mut result = String::with_capacity(css.len());
+    let mut rest = css;
+    while let Some(start) = rest.find("/*") {
+        result.push_str(&rest[..start]);
+        rest = rest[start + 2..]
+            .split_once("*/")
+            .map_or("", |(_, after)| after);
+    }
+    result.push_str(rest);
+    result
+}
+
+fn camel_case(name: &str) -> String {
+    let mut parts = name.split('-');
+    let mut result = parts.next().unwrap_or_default().to_string();
+    for part in parts {
+        let mut chars = part.chars();
+        if let Some(first) = chars.next() {
+            result.extend(first.to_uppercase());
+            result.push_str(chars.as_str());
+        }
+    }
+    result
+}
+
+/// Parses a `#RRGGBB` color.
+fn parse_color(value: &str) -> Option<swf::Color> {
+    let hex = value.strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
+    }
+    let rgb = u32::from_str_radix(hex, 16).ok()?;
+    Some(swf::Color::from_rgb(rgb, 0))
+}
+
+/// Takes the first font of a list, with CSS's generic font families turned into
+/// the device fonts that Flash has for them.
+fn parse_font_family(value: &str) -> Option<WString> {
+    let font = value.split(',').next()?.trim();
+    let font = font.trim_matches(|c| c == '"' || c == '\'');
+    let font = match font {
+        "" => return None,
+        "sans-serif" => "_sans",
+        "serif" => "_serif",
+        "mono" | "monospace" => "_typewriter",
+        font => font,
+    };
+    Some(WString::from_utf8(font))
+}
+
+/// Parses a number of pixels, which may be written with a `px` or `pt` unit.
+fn parse_length(value: &str) -> Option<f64> {
+    let number = value
+        .strip_suffix("px")
+        .or_else(|| value.strip_suffix("pt"))
+        .unwrap_or(value);
+    number.trim().parse().ok()
+}
+
+fn parse_keyword(value: &str, on: &str, off: &str) -> Option<bool> {
+    if value.eq_ignore_ascii_case(on) {
+        Some(true)
+    } else if value.eq_ignore_ascii_case(off) {
+        Some(false)
+    } else {
+        None
+    }
+}
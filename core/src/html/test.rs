@@ -1,6 +1,8 @@
 //! Tests for HTML module
 
 use crate::html::dimensions::{BoxBounds, Position, Size};
+use crate::html::image::{ImageAlign, TextImage};
+use crate::html::style_sheet::StyleSheet;
 use crate::html::text_format::{FormatSpans, TextFormat, TextSpan};
 use crate::string::{WStr, WString};
 use swf::{Rectangle, Twips};
@@ -864,3 +866,113 @@ fn formatspans_replace_text_degenerate() {
     assert_eq!((0, 1), fs.get_span_boundaries(0, 5));
     assert_eq!((1, 2), fs.get_span_boundaries(5, 9));
 }
+
+#[test]
+fn formatspans_from_html_style_sheet() {
+    let mut style_sheet = StyleSheet::new();
+    assert!(style_sheet.parse_css(WStr::from_units(
+        b"/* Paragraphs */ P { color: #FF0000; font-size: 20px; } .Big, h1 { font-weight: bold; }"
+    )));
+    assert_eq!(
+        style_sheet.selectors().collect::<Vec<_>>(),
+        vec![
+            WStr::from_units(b"p"),
+            WStr::from_units(b".big"),
+            WStr::from_units(b"h1")
+        ]
+    );
+
+    let spans = FormatSpans::from_html(
+        WStr::from_units(b"<p class=\"big\">Hi</p><p>there</p>"),
+        TextFormat::default(),
+        Some(&style_sheet),
+        true,
+    );
+
+    assert_eq!(spans.text(), WStr::from_units(b"Hi\nthere\n"));
+    let styled = spans.span(0).unwrap();
+    assert_eq!(styled.span_length, 3);
+    assert_eq!(styled.color, swf::Color::from_rgb(0xFF0000, 0));
+    assert_eq!(styled.size, 20.0);
+    assert!(styled.bold);
+    let unclassed = spans.span(1).unwrap();
+    assert_eq!(unclassed.size, 20.0);
+    assert!(!unclassed.bold);
+}
+
+#[test]
+fn formatspans_from_html_style_sheet_display() {
+    let mut style_sheet = StyleSheet::new();
+    assert!(style_sheet.parse_css(WStr::from_units(
+        b"heading { display: block; } .secret { display: none; } p { display: inline; }"
+    )));
+
+    let spans = FormatSpans::from_html(
+        WStr::from_units(b"<heading>A</heading><b class=\"secret\">B</b><p>C</p>D"),
+        TextFormat::default(),
+        Some(&style_sheet),
+        true,
+    );
+
+    assert_eq!(spans.text(), WStr::from_units(b"A\nCD"));
+}
+
+#[test]
+fn style_sheet_parse_css_invalid() {
+    let mut style_sheet = StyleSheet::new();
+    assert!(!style_sheet.parse_css(WStr::from_units(b"p { color: #FF0000; ")));
+    assert!(!style_sheet.parse_css(WStr::from_units(b", p { color: #FF0000; }")));
+    assert_eq!(style_sheet.selectors().count(), 0);
+}
+
+#[test]
+fn formatspans_from_html_img() {
+    let spans = FormatSpans::from_html(
+        WStr::from_units(
+            b"a<img src=\"smiley\" id=\"face\" width=\"20\" hspace=\"2\" align=\"right\">b<img>",
+        ),
+        TextFormat::default(),
+        None,
+        true,
+    );
+
+    // The image takes the place of one character, and an `<img>` without a `src` is dropped
+    assert_eq!(
+        spans.text(),
+        WStr::from_units(&[u16::from(b'a'), TextImage::CHARACTER, u16::from(b'b')])
+    );
+    assert_eq!(spans.span(0).unwrap().image, None);
+    let image_span = spans.span(1).unwrap();
+    assert_eq!(image_span.span_length, 1);
+    assert_eq!(
+        image_span.image,
+        Some(TextImage {
+            src: WString::from_utf8("smiley"),
+            id: Some(WString::from_utf8("face")),
+            width: Some(20.0),
+            height: None,
+            hspace: 2.0,
+            vspace: 8.0,
+            align: ImageAlign::Right,
+        })
+    );
+    assert_eq!(spans.span(2).unwrap().image, None);
+}
+
+#[test]
+fn formatspans_to_html_img() {
+    let spans = FormatSpans::from_html(
+        WStr::from_units(b"<img src=\"icon.png\" height=\"10\">"),
+        TextFormat::default(),
+        None,
+        true,
+    );
+
+    let html = spans.to_html().to_utf8_lossy().into_owned();
+    assert!(
+        html.contains(
+            "<IMG SRC=\"icon.png\" HEIGHT=\"10\" HSPACE=\"8\" VSPACE=\"8\" ALIGN=\"left\">"
+        ),
+        "{html}"
+    );
+}
@@ -1,7 +1,9 @@
 //! Classes that store formatting options
 
 use crate::context::UpdateContext;
+use crate::html::image::{ImageAlign, TextImage};
 use crate::html::iterators::TextSpanIter;
+use crate::html::style_sheet::{Display, StyleSheet};
 use crate::string::{Integer, Units, WStr, WString};
 use crate::tag_utils::SwfMovie;
 use gc_arena::Collect;
@@ -340,6 +342,12 @@ pub struct TextSpan {
     pub bullet: bool,
     pub url: WString,
     pub target: WString,
+
+    /// The image that this span's character is shown as, for spans made from `<img>` tags.
+    ///
+    /// This isn't part of any `TextFormat`, so text that replaces the character
+    /// doesn't carry it on.
+    pub image: Option<TextImage>,
 }
 
 impl Default for TextSpan {
@@ -369,6 +377,7 @@ impl Default for TextSpan {
             bullet: false,
             url: WString::new(),
             target: WString::new(),
+            image: None,
         }
     }
 }
@@ -409,6 +418,9 @@ impl TextSpan {
             && self.bullet == rhs.bullet
             && self.url == rhs.url
             && self.target == rhs.target
+            // Every image keeps a span of its own
+            && self.image.is_none()
+            && rhs.image.is_none()
     }
 
     /// Apply a text format to this text span.
@@ -564,12 +576,18 @@ impl FormatSpans {
 
     /// Lower an HTML tree into text-span representation.
     ///
-    /// This is the "legacy" implementation of this process: it only looks for
-    /// a handful of presentational attributes in the HTML tree to generate
-    /// styling. There's also a `lower_from_css` that respects both
-    /// presentational markup and CSS stylesheets.
-    pub fn from_html(html: &WStr, default_format: TextFormat, is_multiline: bool) -> Self {
+    /// The presentational markup of the HTML tree is styled first, and then any
+    /// styles from the style sheet that apply to each element are layered on top.
+    /// Any tag can be styled like that, not just the ones that Flash knows.
+    pub fn from_html(
+        html: &WStr,
+        default_format: TextFormat,
+        style_sheet: Option<&StyleSheet>,
+        is_multiline: bool,
+    ) -> Self {
         let mut format_stack = vec![default_format.clone()];
+        // How each open element is displayed, which only style sheets can change.
+        let mut display_stack = Vec::new();
         let mut text = WString::new();
         let mut spans: Vec<TextSpan> = Vec::new();
 
@@ -630,8 +648,14 @@ impl FormatSpans {
                                 .then(|| decode_to_wstr(&attribute.value))
                         })
                     };
+                    let class = style_sheet.and_then(|_| attribute(b"class"));
                     let mut format = format_stack.last().unwrap().clone();
-                    match &e.name().to_ascii_lowercase()[..] {
+                    let tag = e.name().to_ascii_lowercase();
+                    let mut display = match &tag[..] {
+                        b"p" | b"li" => Display::Block,
+                        _ => Display::Inline,
+                    };
+                    match &tag[..] {
                         b"br" => {
                             if is_multiline {
                                 text.push_byte(b'\n');
@@ -643,6 +667,18 @@ impl FormatSpans {
                             // Skip push to `format_stack`.
                             continue;
                         }
+                        b"img" => {
+                            if let Some(image) = TextImage::from_attributes(&attribute) {
+                                let format = format_stack.last().unwrap().clone();
+                                text.push(TextImage::CHARACTER);
+                                let mut span = TextSpan::with_length_and_format(1, format);
+                                span.image = Some(image);
+                                spans.push(span);
+                            }
+
+                            // Skip push to `format_stack`.
+                            continue;
+                        }
                         b"sbr" => {
                             // TODO: <sbr> tags do not add a newline, but rather only break
                             // the format span.
@@ -759,8 +795,18 @@ impl FormatSpans {
                         }
                         _ => {}
                     }
+                    if let Some(style_sheet) = style_sheet {
+                        style_sheet.apply(
+                            &decode_to_wstr(&tag),
+                            class.as_deref(),
+                            &mut format,
+                            &mut display,
+                        );
+                    }
                     format_stack.push(format);
+                    display_stack.push(display);
                 }
+                Ok(Event::Text(_)) if display_stack.contains(&Display::None) => {}
                 Ok(Event::Text(e)) if !e.is_empty() => {
                     let e = decode_to_wstr(e.escaped());
                     let e = process_html_entity(&e).unwrap_or(e);
@@ -782,18 +828,16 @@ impl FormatSpans {
                         None => continue,
                     }
 
-                    match &e.name().to_ascii_lowercase()[..] {
-                        b"br" | b"sbr" => {
-                            // Skip pop from `format_stack`.
-                            continue;
-                        }
-                        b"p" | b"li" if is_multiline => {
-                            text.push_byte(b'\n');
-                            if let Some(span) = spans.last_mut() {
-                                span.span_length += 1;
-                            }
+                    if matches!(&e.name().to_ascii_lowercase()[..], b"br" | b"sbr" | b"img") {
+                        // Skip pop from `format_stack`.
+                        continue;
+                    }
+                    let display = display_stack.pop();
+                    if is_multiline && display == Some(Display::Block) {
+                        text.push_byte(b'\n');
+                        if let Some(span) = spans.last_mut() {
+                            span.span_length += 1;
                         }
-                        _ => {}
                     }
                     format_stack.pop();
                 }
@@ -1165,7 +1209,7 @@ impl FormatSpans {
                 span,
                 is_open: false,
             };
-            state.push_text(text);
+            state.push_span_content(text);
             state
         } else {
             return WString::new();
@@ -1173,7 +1217,7 @@ impl FormatSpans {
 
         for (_start, _end, text, span) in spans {
             state.set_span(span);
-            state.push_text(text);
+            state.push_span_content(text);
         }
 
         state.close_tags();
@@ -1415,6 +1459,42 @@ impl<'a> FormatState<'a> {
         self.span = span;
     }
 
+    /// Writes out the text of the current span, starting with the `<IMG>` tag of its image
+    /// if it has one.
+    fn push_span_content(&mut self, text: &WStr) {
+        let Some(image) = &self.span.image else {
+            self.push_text(text);
+            return;
+        };
+        if text.get(0) != Some(TextImage::CHARACTER) {
+            self.push_text(text);
+            return;
+        }
+
+        self.open_tags();
+        let _ = write!(self.result, "<IMG SRC=\"{}\"", image.src);
+        if let Some(id) = &image.id {
+            let _ = write!(self.result, " ID=\"{id}\"");
+        }
+        if let Some(width) = image.width {
+            let _ = write!(self.result, " WIDTH=\"{width}\"");
+        }
+        if let Some(height) = image.height {
+            let _ = write!(self.result, " HEIGHT=\"{height}\"");
+        }
+        let _ = write!(
+            self.result,
+            " HSPACE=\"{}\" VSPACE=\"{}\" ALIGN=\"{}\">",
+            image.hspace,
+            image.vspace,
+            match image.align {
+                ImageAlign::Left => "left",
+                ImageAlign::Right => "right",
+            }
+        );
+        self.push_text(&text[1..]);
+    }
+
     fn push_text(&mut self, text: &WStr) {
         for (i, text) in text.split(&[b'\n', b'\r'][..]).enumerate() {
             self.open_tags();
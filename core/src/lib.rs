@@ -15,6 +15,7 @@ extern crate downcast_rs;
 #[macro_use]
 extern crate num_derive;
 
+mod amf;
 #[macro_use]
 mod avm1;
 mod avm2;
@@ -34,9 +35,11 @@ mod html;
 mod library;
 pub mod limits;
 pub mod loader;
+pub mod local_connection;
 mod locale;
 mod player;
 mod prelude;
+pub mod socket;
 pub mod string;
 pub mod tag_utils;
 pub mod timer;
@@ -1,6 +1,7 @@
 use crate::avm1::PropertyMap as Avm1PropertyMap;
 use crate::avm2::{ClassObject as Avm2ClassObject, Domain as Avm2Domain};
 use crate::backend::audio::SoundHandle;
+use crate::backend::ui::{FontDefinition, UiBackend};
 use crate::character::Character;
 
 use crate::display_object::{Bitmap, Graphic, MorphShape, TDisplayObject, Text};
@@ -357,6 +358,17 @@ pub struct Library<'gc> {
     /// The embedded device font.
     device_font: Option<Font<'gc>>,
 
+    /// Fonts from font files that were given to the player, which device fonts are drawn
+    /// with ahead of the system's fonts.
+    registered_fonts: Vec<Font<'gc>>,
+
+    /// The device fonts that have been looked up so far, by the font they were looked up by.
+    device_fonts: HashMap<FontDescriptor, Font<'gc>>,
+
+    /// The fonts that the UI backend has loaded so far, which are shared by the device
+    /// fonts that they're part of rather than read again for each.
+    system_fonts: HashMap<FontDescriptor, Font<'gc>>,
+
     /// A list of the symbols associated with specific AVM2 constructor
     /// prototypes.
     avm2_class_registry: Avm2ClassRegistry<'gc>,
@@ -369,6 +381,13 @@ unsafe impl<'gc> gc_arena::Collect for Library<'gc> {
             val.trace(cc);
         }
         self.device_font.trace(cc);
+        self.registered_fonts.trace(cc);
+        for font in self.device_fonts.values() {
+            font.trace(cc);
+        }
+        for font in self.system_fonts.values() {
+            font.trace(cc);
+        }
         self.avm2_class_registry.trace(cc);
     }
 }
@@ -378,6 +397,9 @@ impl<'gc> Library<'gc> {
         Self {
             movie_libraries: PtrWeakKeyHashMap::new(),
             device_font: None,
+            registered_fonts: Vec::new(),
+            device_fonts: HashMap::new(),
+            system_fonts: HashMap::new(),
             avm2_class_registry: Default::default(),
         }
     }
@@ -400,6 +422,107 @@ impl<'gc> Library<'gc> {
     /// Sets the device font.
     pub fn set_device_font(&mut self, font: Font<'gc>) {
         self.device_font = Some(font);
+        self.device_fonts.clear();
+    }
+
+    /// Registers a font file to draw device fonts with, ahead of the system's fonts.
+    pub fn register_device_font(
+        &mut self,
+        gc_context: MutationContext<'gc, '_>,
+        definition: FontDefinition,
+    ) {
+        match Font::from_font_file(gc_context, definition) {
+            Ok(font) => {
+                self.registered_fonts.push(font);
+                self.device_fonts.clear();
+            }
+            Err(e) => tracing::error!("Couldn't register device font: {}", e),
+        }
+    }
+
+    /// Returns the device font to draw text in the given font with, which is looked up
+    /// the first time it's used.
+    ///
+    /// The fonts that were registered with the player that match come first, and then the
+    /// system's fonts from the UI backend. The characters that the font has no glyphs for
+    /// are drawn with the other fonts, then the rest of the registered fonts, and then the
+    /// embedded device font. The `_sans`, `_serif` and `_typewriter` fonts match the first
+    /// registered font, if there are any.
+    pub fn get_or_load_device_font(
+        &mut self,
+        gc_context: MutationContext<'gc, '_>,
+        ui: &dyn UiBackend,
+        name: &str,
+        is_bold: bool,
+        is_italic: bool,
+    ) -> Option<Font<'gc>> {
+        let descriptor = FontDescriptor::from_parts(name, is_bold, is_italic);
+        if let Some(font) = self.device_fonts.get(&descriptor) {
+            return Some(*font);
+        }
+
+        let mut fonts = Vec::new();
+        fonts.extend(self.find_registered_font(name, is_bold, is_italic));
+        let system_fonts = &mut self.system_fonts;
+        ui.load_device_font(name, is_bold, is_italic, &mut |definition| {
+            let FontDefinition::FontFile {
+                name: family,
+                is_bold,
+                is_italic,
+                ..
+            } = &definition;
+            let descriptor = FontDescriptor::from_parts(family, *is_bold, *is_italic);
+            if let Some(font) = system_fonts.get(&descriptor) {
+                fonts.push(*font);
+                return;
+            }
+            match Font::from_font_file(gc_context, definition) {
+                Ok(font) => {
+                    system_fonts.insert(descriptor, font);
+                    fonts.push(font);
+                }
+                Err(e) => tracing::error!("Couldn't load device font {}: {}", name, e),
+            }
+        });
+        for font in self.registered_fonts.iter().chain(&self.device_font) {
+            if !fonts.iter().any(|other| Font::ptr_eq(*font, *other)) {
+                fonts.push(*font);
+            }
+        }
+
+        let font = match fonts.len() {
+            0 => return None,
+            1 => fonts[0],
+            _ => {
+                let primary = fonts.remove(0);
+                primary.with_fallbacks(gc_context, fonts)
+            }
+        };
+        self.device_fonts.insert(descriptor, font);
+        Some(font)
+    }
+
+    /// Finds the registered font with the given name, preferring the one with the same style.
+    fn find_registered_font(
+        &self,
+        name: &str,
+        is_bold: bool,
+        is_italic: bool,
+    ) -> Option<Font<'gc>> {
+        if matches!(name, "_sans" | "_serif" | "_typewriter") {
+            return self.registered_fonts.first().copied();
+        }
+        let same_family = || {
+            self.registered_fonts
+                .iter()
+                .filter(|font| font.descriptor().class().eq_ignore_ascii_case(name))
+        };
+        same_family()
+            .find(|font| {
+                font.descriptor().bold() == is_bold && font.descriptor().italic() == is_italic
+            })
+            .or_else(|| same_family().next())
+            .copied()
     }
 
     /// Get the AVM2 class registry.
@@ -151,6 +151,9 @@ pub enum Error {
     #[error("Could not fetch: {0}")]
     FetchError(String),
 
+    #[error("HTTP status is not ok, got {0}")]
+    HttpNotOk(u16),
+
     #[error("Invalid SWF: {0}")]
     InvalidSwf(#[from] crate::tag_utils::Error),
 
@@ -449,6 +452,25 @@ pub enum LoaderStatus {
     Failed,
 }
 
+/// Why a `Loader` failed to load a movie.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum MovieLoadError {
+    /// The file couldn't be fetched.
+    UrlNotFound,
+    /// The file was fetched, but couldn't be parsed or decoded.
+    LoadNeverCompleted,
+}
+
+impl MovieLoadError {
+    /// The error code that AVM1's `MovieClipLoader.onLoadError` receives.
+    fn avm1_error_code(self) -> &'static str {
+        match self {
+            MovieLoadError::UrlNotFound => "URLNotFound",
+            MovieLoadError::LoadNeverCompleted => "LoadNeverCompleted",
+        }
+    }
+}
+
 #[derive(Collect, Clone, Copy)]
 #[collect(no_drop)]
 pub enum MovieLoaderEventHandler<'gc> {
@@ -766,7 +788,7 @@ impl<'gc> Loader<'gc> {
                 Err(e) => {
                     tracing::error!("Error during movie loading: {:?}", e);
                     player.lock().unwrap().update(|uc| -> Result<(), Error> {
-                        Loader::movie_loader_error(handle, uc)
+                        Loader::movie_loader_error(handle, uc, MovieLoadError::UrlNotFound)
                     })?;
                 }
             }
@@ -928,7 +950,7 @@ impl<'gc> Loader<'gc> {
 
                         let _ = that.call_method(
                             "onHTTPStatus".into(),
-                            &[200.into()],
+                            &[response.status.into()],
                             &mut activation,
                             ExecutionReason::Special,
                         );
@@ -951,13 +973,18 @@ impl<'gc> Loader<'gc> {
                             ExecutionReason::Special,
                         );
                     }
-                    Err(_) => {
+                    Err(error) => {
                         // TODO: Log "Error opening URL" trace similar to the Flash Player?
-                        // Simulate 404 HTTP status. This should probably be fired elsewhere
-                        // because a failed local load doesn't fire a 404.
+                        // Without a response from a server, simulate a 404 HTTP status.
+                        // This should probably be fired elsewhere because a failed local
+                        // load doesn't fire a 404.
+                        let status = match error {
+                            Error::HttpNotOk(status) => status,
+                            _ => 404,
+                        };
                         let _ = that.call_method(
                             "onHTTPStatus".into(),
-                            &[404.into()],
+                            &[status.into()],
                             &mut activation,
                             ExecutionReason::Special,
                         );
@@ -1350,7 +1377,17 @@ impl<'gc> Loader<'gc> {
 
             match sniffed_type {
                 ContentType::Swf => {
-                    let movie = Arc::new(SwfMovie::from_data(data, url, loader_url)?);
+                    let movie = match SwfMovie::from_data(data, url, loader_url) {
+                        Ok(movie) => Arc::new(movie),
+                        Err(e) => {
+                            tracing::error!("Error while parsing loaded movie: {:?}", e);
+                            return Loader::movie_loader_error(
+                                handle,
+                                uc,
+                                MovieLoadError::LoadNeverCompleted,
+                            );
+                        }
+                    };
 
                     match uc.load_manager.get_loader_mut(handle) {
                         Some(Loader::Movie {
@@ -1434,7 +1471,17 @@ impl<'gc> Loader<'gc> {
                         Loader::movie_loader_progress(handle, uc, 0, length)?;
                     }
 
-                    let bitmap = ruffle_render::utils::decode_define_bits_jpeg(data, None)?;
+                    let bitmap = match ruffle_render::utils::decode_define_bits_jpeg(data, None) {
+                        Ok(bitmap) => bitmap,
+                        Err(e) => {
+                            tracing::error!("Error while decoding loaded image: {:?}", e);
+                            return Loader::movie_loader_error(
+                                handle,
+                                uc,
+                                MovieLoadError::LoadNeverCompleted,
+                            );
+                        }
+                    };
                     let bitmap_obj = Bitmap::new(uc, 0, bitmap)?;
 
                     if let Some(mc) = clip.as_movie_clip() {
@@ -1572,12 +1619,14 @@ impl<'gc> Loader<'gc> {
     ///
     /// This is an associated function because we cannot borrow both the update
     /// context and one of it's loaders.
-    fn movie_loader_error(handle: Index, uc: &mut UpdateContext<'_, 'gc>) -> Result<(), Error> {
+    fn movie_loader_error(
+        handle: Index,
+        uc: &mut UpdateContext<'_, 'gc>,
+        error: MovieLoadError,
+    ) -> Result<(), Error> {
         //TODO: Inspect the fetch error.
         //This requires cooperation from the backend to send abstract
         //error types we can actually inspect.
-        //This also can get errors from decoding an invalid SWF file,
-        //too. We should distinguish those to player code.
         let (clip, event_handler) = match uc.load_manager.get_loader_mut(handle) {
             Some(Loader::Movie {
                 target_clip,
@@ -1595,10 +1644,12 @@ impl<'gc> Loader<'gc> {
                     broadcaster,
                     uc,
                     "broadcastMessage".into(),
+                    // TODO: Pass an actual httpStatus argument instead of 0.
                     &[
                         "onLoadError".into(),
                         clip.object(),
-                        "LoadNeverCompleted".into(),
+                        error.avm1_error_code().into(),
+                        0.into(),
                     ],
                 );
             }
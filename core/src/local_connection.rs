@@ -0,0 +1,381 @@
+//! Messaging between movies with AVM1 and AVM2 `LocalConnection`.
+//!
+//! Connections are made between the movies in the same player, including movies
+//! loaded into it. Messages are delivered on the next update, like in Flash,
+//! and their arguments are copied through AMF, so either AVM can receive them.
+
+use crate::amf::AmfValue;
+use crate::avm1::{
+    Activation, ActivationIdentifier, ExecutionReason, Object as Avm1Object, ScriptObject,
+    TObject as _,
+};
+use crate::avm2::object::TObject as _;
+use crate::avm2::{Activation as Avm2Activation, Avm2, Multiname, Object as Avm2Object};
+use crate::context::UpdateContext;
+use crate::string::AvmString;
+use fnv::FnvHashMap;
+use gc_arena::Collect;
+use std::collections::HashMap;
+use url::Url;
+
+/// The object that a connection belongs to.
+#[derive(Clone, Copy, Collect)]
+#[collect(no_drop)]
+pub enum LocalConnectionObject<'gc> {
+    /// An AVM1 `LocalConnection`, which is called with the messages itself.
+    Avm1(Avm1Object<'gc>),
+
+    /// An AVM2 `LocalConnection`, whose `client` is called with the messages.
+    Avm2(Avm2Object<'gc>),
+}
+
+impl<'gc> LocalConnectionObject<'gc> {
+    fn ptr_eq(self, other: Self) -> bool {
+        match (self, other) {
+            (Self::Avm1(a), Self::Avm1(b)) => Avm1Object::ptr_eq(a, b),
+            (Self::Avm2(a), Self::Avm2(b)) => Avm2Object::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+/// A connection that is listening for messages.
+#[derive(Collect)]
+#[collect(no_drop)]
+struct Connection<'gc> {
+    object: LocalConnectionObject<'gc>,
+
+    /// The domain of the movie that's listening, which may always be sent messages by itself.
+    #[collect(require_static)]
+    domain: String,
+}
+
+/// A message sent with `LocalConnection.send`.
+#[derive(Collect)]
+#[collect(no_drop)]
+struct Message<'gc> {
+    /// The connection that sent the message, which is told whether it was delivered.
+    sender: LocalConnectionObject<'gc>,
+
+    #[collect(require_static)]
+    sender_domain: String,
+
+    /// The full name of the connection that the message is for.
+    #[collect(require_static)]
+    connection_name: String,
+
+    #[collect(require_static)]
+    method_name: String,
+
+    #[collect(require_static)]
+    arguments: Vec<AmfValue>,
+}
+
+/// Manages all of the local connections of the movies in the player.
+#[derive(Collect)]
+#[collect(no_drop)]
+pub struct LocalConnections<'gc> {
+    /// The connections that are listening, by their full name.
+    connections: FnvHashMap<String, Connection<'gc>>,
+
+    /// Messages that were sent since the last update.
+    messages: Vec<Message<'gc>>,
+}
+
+impl<'gc> LocalConnections<'gc> {
+    pub fn empty() -> Self {
+        Self {
+            connections: Default::default(),
+            messages: Vec::new(),
+        }
+    }
+
+    /// The domain that the connections of a movie loaded from this URL belong to.
+    pub fn domain(url: Option<&str>) -> String {
+        url.and_then(|url| Url::parse(url).ok())
+            .filter(|url| url.scheme() != "file")
+            .and_then(|url| url.domain().map(str::to_owned))
+            .unwrap_or_else(|| "localhost".to_string())
+    }
+
+    /// The name that a connection is known by, from a movie in the given domain.
+    ///
+    /// Names are case-insensitive. Names starting with an underscore are shared by all
+    /// domains, while the others are prefixed with the domain of the movie, unless they
+    /// already name one, like `send` may do with `domain:name`.
+    pub fn full_name(domain: &str, name: &str) -> String {
+        let name = name.to_lowercase();
+        if name.starts_with('_') || name.contains(':') {
+            name
+        } else {
+            format!("{domain}:{name}")
+        }
+    }
+
+    /// Starts listening for messages on a connection.
+    ///
+    /// Fails if another connection already has that name, or if the object is already listening.
+    pub fn connect(
+        &mut self,
+        object: LocalConnectionObject<'gc>,
+        full_name: String,
+        domain: String,
+    ) -> bool {
+        if self.connections.contains_key(&full_name) || self.is_connected(object) {
+            return false;
+        }
+        self.connections
+            .insert(full_name, Connection { object, domain });
+        true
+    }
+
+    /// Stops listening for messages, returning whether the object was listening.
+    pub fn close(&mut self, object: LocalConnectionObject<'gc>) -> bool {
+        let count = self.connections.len();
+        self.connections
+            .retain(|_, connection| !connection.object.ptr_eq(object));
+        self.connections.len() != count
+    }
+
+    pub fn is_connected(&self, object: LocalConnectionObject<'gc>) -> bool {
+        self.connections
+            .values()
+            .any(|connection| connection.object.ptr_eq(object))
+    }
+
+    /// Sends a message to be delivered on the next update.
+    pub fn send(
+        &mut self,
+        sender: LocalConnectionObject<'gc>,
+        sender_domain: String,
+        connection_name: String,
+        method_name: String,
+        arguments: Vec<AmfValue>,
+    ) {
+        self.messages.push(Message {
+            sender,
+            sender_domain,
+            connection_name,
+            method_name,
+            arguments,
+        });
+    }
+
+    /// Delivers the messages that were sent since the last update, and tells their
+    /// senders whether that worked.
+    pub fn update_local_connections(context: &mut UpdateContext<'_, 'gc>) {
+        let messages = std::mem::take(&mut context.local_connections.messages);
+        for message in messages {
+            let receiver = context
+                .local_connections
+                .connections
+                .get(&message.connection_name)
+                .map(|connection| {
+                    (
+                        connection.object,
+                        connection.domain == message.sender_domain,
+                    )
+                });
+            let delivered = match receiver {
+                Some((LocalConnectionObject::Avm1(receiver), same_domain)) => {
+                    Self::deliver_avm1(context, receiver, same_domain, &message)
+                }
+                Some((LocalConnectionObject::Avm2(receiver), same_domain)) => {
+                    Self::deliver_avm2(context, receiver, same_domain, &message)
+                }
+                None => false,
+            };
+
+            let level = if delivered { "status" } else { "error" };
+            match message.sender {
+                LocalConnectionObject::Avm1(sender) => Self::status_avm1(context, sender, level),
+                LocalConnectionObject::Avm2(sender) => Self::status_avm2(context, sender, level),
+            }
+        }
+    }
+
+    fn deliver_avm1(
+        context: &mut UpdateContext<'_, 'gc>,
+        receiver: Avm1Object<'gc>,
+        same_domain: bool,
+        message: &Message<'gc>,
+    ) -> bool {
+        let root_clip = context.stage.root_clip();
+        let mut activation = Activation::from_nothing(
+            context.reborrow(),
+            ActivationIdentifier::root("[LocalConnection]"),
+            root_clip,
+        );
+
+        // Other domains are only let in if the receiver's `allowDomain` says so.
+        if !same_domain {
+            let domain = AvmString::new_utf8(activation.context.gc_context, &message.sender_domain);
+            let allowed = receiver
+                .call_method(
+                    "allowDomain".into(),
+                    &[domain.into()],
+                    &mut activation,
+                    ExecutionReason::Special,
+                )
+                .map(|allowed| allowed.as_bool(activation.swf_version()))
+                .unwrap_or(false);
+            if !allowed {
+                return false;
+            }
+        }
+
+        // The arguments are written together, so they may refer to each other's objects
+        let mut objects = HashMap::new();
+        let arguments: Vec<_> = message
+            .arguments
+            .iter()
+            .map(|argument| {
+                crate::avm1::globals::shared_object::deserialize_value(
+                    &mut activation,
+                    argument,
+                    &mut objects,
+                )
+            })
+            .collect();
+        let method_name = AvmString::new_utf8(activation.context.gc_context, &message.method_name);
+        if let Err(e) = receiver.call_method(
+            method_name,
+            &arguments,
+            &mut activation,
+            ExecutionReason::Special,
+        ) {
+            tracing::error!(
+                "Unhandled AVM1 error in LocalConnection.{}: {}",
+                message.method_name,
+                e
+            );
+        }
+        true
+    }
+
+    fn deliver_avm2(
+        context: &mut UpdateContext<'_, 'gc>,
+        receiver: Avm2Object<'gc>,
+        same_domain: bool,
+        message: &Message<'gc>,
+    ) -> bool {
+        let mut activation = Avm2Activation::from_nothing(context.reborrow());
+
+        // Other domains are only let in if the receiver passed them to `allowDomain`.
+        if !same_domain {
+            let allowed_domains = receiver
+                .get_property(
+                    &Multiname::new(activation.avm2().flash_net_internal, "_allowedDomains"),
+                    &mut activation,
+                )
+                .ok()
+                .and_then(|domains| domains.as_object());
+            let allowed = allowed_domains
+                .and_then(|domains| {
+                    let domains = domains.as_array_storage()?;
+                    let allowed = domains.iter().flatten().any(|domain| match domain {
+                        crate::avm2::Value::String(domain) => {
+                            let domain = domain.to_utf8_lossy();
+                            domain == "*" || domain.eq_ignore_ascii_case(&message.sender_domain)
+                        }
+                        _ => false,
+                    });
+                    Some(allowed)
+                })
+                .unwrap_or(false);
+            if !allowed {
+                return false;
+            }
+        }
+
+        let client = receiver
+            .get_public_property("client", &mut activation)
+            .ok()
+            .and_then(|client| client.as_object());
+        let Some(client) = client else {
+            tracing::error!("AVM2 LocalConnection has no client to receive messages");
+            return true;
+        };
+        let mut arguments = Vec::with_capacity(message.arguments.len());
+        let mut objects = HashMap::new();
+        for argument in &message.arguments {
+            match crate::avm2::amf::deserialize_value(&mut activation, argument, &mut objects) {
+                Ok(argument) => arguments.push(argument),
+                Err(e) => {
+                    tracing::error!("Error deserializing AVM2 LocalConnection argument: {}", e);
+                    arguments.push(crate::avm2::Value::Undefined);
+                }
+            }
+        }
+        let method_name = AvmString::new_utf8(activation.context.gc_context, &message.method_name);
+        if let Err(e) = client.call_public_property(method_name, &arguments, &mut activation) {
+            tracing::error!(
+                "Unhandled AVM2 error in LocalConnection.{}: {}",
+                message.method_name,
+                e
+            );
+        }
+        true
+    }
+
+    fn status_avm1(
+        context: &mut UpdateContext<'_, 'gc>,
+        sender: Avm1Object<'gc>,
+        level: &'static str,
+    ) {
+        let root_clip = context.stage.root_clip();
+        let mut activation = Activation::from_nothing(
+            context.reborrow(),
+            ActivationIdentifier::root("[LocalConnection]"),
+            root_clip,
+        );
+
+        let info = ScriptObject::new(
+            activation.context.gc_context,
+            Some(activation.context.avm1.prototypes().object),
+        );
+        let result = info
+            .set("level", level.into(), &mut activation)
+            .and_then(|_| {
+                sender.call_method(
+                    "onStatus".into(),
+                    &[info.into()],
+                    &mut activation,
+                    ExecutionReason::Special,
+                )
+            });
+        if let Err(e) = result {
+            tracing::error!("Unhandled AVM1 error in LocalConnection.onStatus: {}", e);
+        }
+    }
+
+    fn status_avm2(
+        context: &mut UpdateContext<'_, 'gc>,
+        sender: Avm2Object<'gc>,
+        level: &'static str,
+    ) {
+        let mut activation = Avm2Activation::from_nothing(context.reborrow());
+        let class = activation.avm2().classes().statusevent;
+        let event = class.construct(
+            &mut activation,
+            &[
+                "status".into(),
+                false.into(),
+                false.into(),
+                crate::avm2::Value::Null,
+                level.into(),
+            ],
+        );
+        match event {
+            Ok(event) => {
+                if let Err(e) = Avm2::dispatch_event(&mut activation.context, event, sender) {
+                    tracing::error!(
+                        "Unhandled AVM2 error in LocalConnection status event: {}",
+                        e
+                    );
+                }
+            }
+            Err(e) => tracing::error!("Error constructing AVM2 LocalConnection event: {}", e),
+        }
+    }
+}
@@ -15,10 +15,10 @@ use crate::backend::{
     log::LogBackend,
     navigator::{NavigatorBackend, Request},
     storage::StorageBackend,
-    ui::{InputManager, MouseCursor, UiBackend},
+    ui::{FontDefinition, InputManager, MouseCursor, UiBackend},
 };
 use crate::config::Letterbox;
-use crate::context::{ActionQueue, ActionType, RenderContext, UpdateContext};
+use crate::context::{ActionQueue, ActionType, CullingStats, RenderContext, UpdateContext};
 use crate::context_menu::{
     BuiltInItemFlags, ContextMenuCallback, ContextMenuItem, ContextMenuState,
 };
@@ -36,9 +36,11 @@ use crate::frame_lifecycle::{run_all_phases_avm2, FramePhase};
 use crate::library::Library;
 use crate::limits::ExecutionLimit;
 use crate::loader::{LoadBehavior, LoadManager};
+use crate::local_connection::LocalConnections;
 use crate::locale::get_current_date_time;
 use crate::prelude::*;
-use crate::string::AvmString;
+use crate::socket::Sockets;
+use crate::string::{AvmString, WString};
 use crate::stub::StubCollection;
 use crate::tag_utils::SwfMovie;
 use crate::timer::Timers;
@@ -47,6 +49,7 @@ use gc_arena::{ArenaParameters, Collect, GcCell};
 use instant::Instant;
 use rand::{rngs::SmallRng, SeedableRng};
 use ruffle_render::backend::{null::NullRenderer, RenderBackend, ViewportDimensions};
+use ruffle_render::bitmap::Bitmap;
 use ruffle_render::commands::CommandList;
 use ruffle_render::quality::StageQuality;
 use ruffle_render::transform::TransformStack;
@@ -142,6 +145,12 @@ struct GcRootData<'gc> {
     /// Timed callbacks created with `setInterval`/`setTimeout`.
     timers: Timers<'gc>,
 
+    /// Sockets opened by `XMLSocket`.
+    sockets: Sockets<'gc>,
+
+    /// Connections made with `LocalConnection`.
+    local_connections: LocalConnections<'gc>,
+
     current_context_menu: Option<ContextMenuState<'gc>>,
 
     /// External interface for (for example) JavaScript <-> ActionScript interaction
@@ -172,6 +181,8 @@ impl<'gc> GcRootData<'gc> {
         &mut HashMap<String, Avm2Object<'gc>>,
         &mut Vec<EditText<'gc>>,
         &mut Timers<'gc>,
+        &mut Sockets<'gc>,
+        &mut LocalConnections<'gc>,
         &mut Option<ContextMenuState<'gc>>,
         &mut ExternalInterface<'gc>,
         &mut AudioManager<'gc>,
@@ -188,6 +199,8 @@ impl<'gc> GcRootData<'gc> {
             &mut self.avm2_shared_objects,
             &mut self.unbound_text_fields,
             &mut self.timers,
+            &mut self.sockets,
+            &mut self.local_connections,
             &mut self.current_context_menu,
             &mut self.external_interface,
             &mut self.audio_manager,
@@ -235,6 +248,9 @@ pub struct Player {
 
     transform_stack: TransformStack,
 
+    /// How many display objects were culled while rendering the last frame.
+    culling_stats: CullingStats,
+
     rng: SmallRng,
 
     gc_arena: Rc<RefCell<GcArena>>,
@@ -264,6 +280,9 @@ pub struct Player {
     mouse_cursor: MouseCursor,
     mouse_cursor_needs_check: bool,
 
+    /// Where the caret of the focused text field was last reported to the UI to be.
+    text_input_area: Option<BoundingBox>,
+
     system: SystemProperties,
 
     /// The current instance ID. Used to generate default `instanceN` names.
@@ -521,6 +540,8 @@ impl Player {
             });
 
             self.update_timers(dt);
+            self.update_sockets();
+            self.update_local_connections();
             self.audio.tick();
         }
     }
@@ -730,6 +751,11 @@ impl Player {
         self.needs_render
     }
 
+    /// How many display objects were drawn and culled in the last frame that was rendered.
+    pub fn culling_stats(&self) -> CullingStats {
+        self.culling_stats
+    }
+
     pub fn background_color(&mut self) -> Option<Color> {
         self.mutate_with_update_context(|context| context.stage.background_color())
     }
@@ -793,6 +819,15 @@ impl Player {
         })
     }
 
+    /// Registers a font file to draw device fonts with, ahead of the system's fonts.
+    pub fn register_device_font(&mut self, definition: FontDefinition) {
+        self.mutate_with_update_context(|context| {
+            context
+                .library
+                .register_device_font(context.gc_context, definition);
+        })
+    }
+
     pub fn set_window_mode(&mut self, window_mode: &str) {
         self.mutate_with_update_context(|context| {
             let stage = context.stage;
@@ -1023,6 +1058,19 @@ impl Player {
                 }
             }
 
+            // Input methods compose their text into the focused text field.
+            if let PlayerEvent::ImeComposition { text } | PlayerEvent::ImeCommit { text } = &event {
+                if let Some(edit_text) = context.focus_tracker.get().and_then(|o| o.as_edit_text())
+                {
+                    let text = WString::from_utf8(text);
+                    if matches!(event, PlayerEvent::ImeCommit { .. }) {
+                        edit_text.commit_ime_composition(&text, context);
+                    } else {
+                        edit_text.set_ime_composition(&text, context);
+                    }
+                }
+            }
+
             // Propagate clip events.
             let (clip_event, listener) = match event {
                 PlayerEvent::KeyDown { .. } => {
@@ -1125,6 +1173,8 @@ impl Player {
                 }
             });
         }
+
+        self.update_text_input_area();
     }
 
     /// Update dragged object, if any.
@@ -1371,6 +1421,19 @@ impl Player {
         needs_render
     }
 
+    /// Tells the UI where the caret of the focused text field is, if it has moved.
+    fn update_text_input_area(&mut self) {
+        let area = self.mutate_with_update_context(|context| {
+            let edit_text = context.focus_tracker.get()?.as_edit_text()?;
+            let caret = edit_text.caret_bounds()?;
+            Some(caret.transform(&context.stage.view_matrix()))
+        });
+        if area != self.text_input_area {
+            self.text_input_area = area.clone();
+            self.ui.set_text_input_area(area);
+        }
+    }
+
     /// Preload all pending movies in the player, including the root movie.
     ///
     /// This should be called periodically with a reasonable execution limit.
@@ -1462,7 +1525,13 @@ impl Player {
             context.update_sounds();
         });
 
-        self.needs_render = true;
+        // A frame where nothing on the stage has changed doesn't have to be rendered again, so it
+        // isn't asked for. Whoever calls `render` anyway still gets the frame rendered.
+        let needs_redraw = self
+            .gc_arena
+            .borrow()
+            .mutate(|_, gc_root| gc_root.data.read().stage.needs_redraw());
+        self.needs_render |= needs_redraw;
     }
 
     #[instrument(level = "debug", skip_all)]
@@ -1478,8 +1547,29 @@ impl Player {
             });
         }
 
-        let (renderer, ui, transform_stack) =
-            (&mut self.renderer, &mut self.ui, &mut self.transform_stack);
+        let (background_color, commands) = self.render_stage(false);
+        self.renderer.submit_frame(background_color, commands);
+
+        self.needs_render = false;
+    }
+
+    /// Renders the current frame again and returns its pixels, for taking a screenshot.
+    /// Returns `None` if the renderer can't read back what it rendered.
+    pub fn capture_frame(&mut self) -> Option<Bitmap> {
+        let (background_color, commands) = self.render_stage(true);
+        self.renderer.capture_frame(background_color, commands)
+    }
+
+    /// Builds the commands to render the stage with, along with the color to clear the frame to.
+    ///
+    /// A `capture` is never presented, so it doesn't count as having rendered the frame.
+    fn render_stage(&mut self, capture: bool) -> (Color, CommandList) {
+        let (renderer, ui, transform_stack, culling_stats) = (
+            &mut self.renderer,
+            &mut self.ui,
+            &mut self.transform_stack,
+            &mut self.culling_stats,
+        );
         let mut background_color = Color::WHITE;
 
         let commands = self.gc_arena.borrow().mutate(|gc_context, gc_root| {
@@ -1497,9 +1587,16 @@ impl Player {
                 stage,
                 clip_depth_stack: vec![],
                 allow_mask: true,
+                cache_dependencies: Some(Default::default()),
+                culling_stats: Default::default(),
             };
 
             stage.render(&mut render_context);
+            *culling_stats = render_context.culling_stats;
+            if !capture {
+                let dependencies = render_context.cache_dependencies.take().unwrap_or_default();
+                stage.set_rendered(gc_context, dependencies);
+            }
 
             background_color =
                 if stage.window_mode() != WindowMode::Transparent || stage.is_fullscreen() {
@@ -1511,9 +1608,7 @@ impl Player {
             render_context.commands
         });
 
-        renderer.submit_frame(background_color, commands);
-
-        self.needs_render = false;
+        (background_color, commands)
     }
 
     /// The current frame of the main timeline, if available.
@@ -1701,6 +1796,8 @@ impl Player {
                 avm2_shared_objects,
                 unbound_text_fields,
                 timers,
+                sockets,
+                local_connections,
                 current_context_menu,
                 external_interface,
                 audio_manager,
@@ -1734,6 +1831,8 @@ impl Player {
                 avm2_shared_objects,
                 unbound_text_fields,
                 timers,
+                sockets,
+                local_connections,
                 current_context_menu,
                 needs_render: &mut self.needs_render,
                 avm1,
@@ -1821,6 +1920,7 @@ impl Player {
             Self::update_drag(context);
         });
         self.update_mouse_state(false, false);
+        self.update_text_input_area();
 
         // GC
         self.gc_arena.borrow_mut().collect_debt();
@@ -1859,6 +1959,18 @@ impl Player {
             self.mutate_with_update_context(|context| Timers::update_timers(context, dt));
     }
 
+    /// Runs the callbacks for anything that happened to an open socket.
+    pub fn update_sockets(&mut self) {
+        self.mutate_with_update_context(|context| Sockets::update_sockets(context));
+    }
+
+    /// Delivers the messages sent between movies with `LocalConnection`.
+    pub fn update_local_connections(&mut self) {
+        self.mutate_with_update_context(|context| {
+            LocalConnections::update_local_connections(context)
+        });
+    }
+
     /// Returns whether this player consumes mouse wheel events.
     /// Used by web to prevent scrolling.
     pub fn should_prevent_scrolling(&mut self) -> bool {
@@ -1937,6 +2049,7 @@ pub struct PlayerBuilder {
     player_version: Option<u8>,
     quality: StageQuality,
     sandbox_type: SandboxType,
+    device_fonts: Vec<FontDefinition>,
 }
 
 impl PlayerBuilder {
@@ -1977,6 +2090,7 @@ impl PlayerBuilder {
             player_version: None,
             quality: StageQuality::High,
             sandbox_type: SandboxType::LocalTrusted,
+            device_fonts: Vec::new(),
         }
     }
 
@@ -2122,6 +2236,14 @@ impl PlayerBuilder {
         self
     }
 
+    /// Adds a font file to draw device fonts with, ahead of the system's fonts.
+    ///
+    /// Fonts that are given to every player make device text look the same everywhere.
+    pub fn with_device_font(mut self, definition: FontDefinition) -> Self {
+        self.device_fonts.push(definition);
+        self
+    }
+
     /// Builds the player, wiring up the backends and configuring the specified settings.
     pub fn build(self) -> Arc<Mutex<Player>> {
         use crate::backend::*;
@@ -2188,11 +2310,13 @@ impl PlayerBuilder {
                 mouse_pos: (Twips::ZERO, Twips::ZERO),
                 mouse_cursor: MouseCursor::Arrow,
                 mouse_cursor_needs_check: false,
+                text_input_area: None,
 
                 // Misc. state
                 rng: SmallRng::seed_from_u64(get_current_date_time().timestamp_millis() as u64),
                 system: SystemProperties::new(self.sandbox_type),
                 transform_stack: TransformStack::new(),
+                culling_stats: Default::default(),
                 instance_counter: 0,
                 player_version,
                 is_playing: self.autoplay,
@@ -2231,6 +2355,8 @@ impl PlayerBuilder {
                                     fake_movie.clone(),
                                 ),
                                 timers: Timers::new(),
+                                sockets: Sockets::empty(),
+                                local_connections: LocalConnections::empty(),
                                 unbound_text_fields: Vec::new(),
                             },
                         ),
@@ -2260,6 +2386,9 @@ impl PlayerBuilder {
         player_lock.audio.set_frame_rate(frame_rate);
         player_lock.set_letterbox(self.letterbox);
         player_lock.set_quality(self.quality);
+        for definition in self.device_fonts {
+            player_lock.register_device_font(definition);
+        }
         player_lock.set_viewport_dimensions(ViewportDimensions {
             width: self.viewport_width,
             height: self.viewport_height,
@@ -2320,3 +2449,34 @@ fn run_mouse_pick<'gc>(
         })
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks that a frame only asks to be rendered if something on the stage has changed.
+    #[test]
+    fn needs_render_after_changes() {
+        let player = PlayerBuilder::new().with_movie(SwfMovie::empty(10)).build();
+        let mut player = player.lock().unwrap();
+
+        player.run_frame();
+        assert!(player.needs_render(), "The first frame must be rendered");
+        player.render();
+
+        player.run_frame();
+        assert!(
+            !player.needs_render(),
+            "An idle frame doesn't need rendering"
+        );
+
+        player.mutate_with_update_context(|context| {
+            context.stage.root_clip().set_x(context.gc_context, 10.0);
+        });
+        player.run_frame();
+        assert!(
+            player.needs_render(),
+            "A frame where an object has moved needs rendering"
+        );
+    }
+}
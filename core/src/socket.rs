@@ -0,0 +1,396 @@
+//! Socket connections for AVM1 `XMLSocket` and AVM2 `Socket`.
+//!
+//! The navigator backend only moves raw bytes in and out of each socket, and
+//! reports what happened to it through a channel that is drained every frame.
+//! Splitting those bytes up into messages happens here instead.
+
+use crate::avm1::{
+    Activation, ActivationIdentifier, ExecutionReason, Object as Avm1Object, TObject as _,
+    Value as Avm1Value,
+};
+use crate::avm2::object::{EventObject as Avm2EventObject, TObject as _};
+use crate::avm2::{Activation as Avm2Activation, Avm2, Multiname, Object as Avm2Object};
+use crate::backend::navigator::{ConnectionState, NavigatorBackend, SocketAction, SocketHandle};
+use crate::context::UpdateContext;
+use crate::string::AvmString;
+use async_channel::{unbounded, Receiver, Sender};
+use gc_arena::{Collect, CollectionContext};
+use generational_arena::Arena;
+use std::time::Duration;
+use url::Url;
+
+/// The object that a socket belongs to.
+#[derive(Clone, Copy, Collect)]
+#[collect(no_drop)]
+enum SocketTarget<'gc> {
+    /// An AVM1 `XMLSocket`, which is sent null-terminated messages.
+    Avm1(Avm1Object<'gc>),
+
+    /// An AVM2 `Socket`, which is sent the raw bytes.
+    Avm2(Avm2Object<'gc>),
+}
+
+/// A socket that is open, or is still being opened.
+#[derive(Collect)]
+#[collect(no_drop)]
+struct Socket<'gc> {
+    /// The object to call back into as things happen to the socket.
+    target: SocketTarget<'gc>,
+
+    /// The host and port that the socket connects to, to be reported in errors.
+    #[collect(require_static)]
+    address: String,
+
+    /// Sink for bytes to be written to the socket.
+    ///
+    /// Dropping this tells the backend to close the socket.
+    #[collect(require_static)]
+    sender: Sender<Vec<u8>>,
+
+    /// Bytes received since the end of the last complete message.
+    buffer: Vec<u8>,
+}
+
+/// Manages all of the sockets opened by the movie.
+pub struct Sockets<'gc> {
+    sockets: Arena<Socket<'gc>>,
+
+    /// Sink handed to the backend for everything that happens to a socket.
+    sender: Sender<SocketAction>,
+
+    /// Everything that happened to a socket since the last update.
+    receiver: Receiver<SocketAction>,
+}
+
+unsafe impl<'gc> Collect for Sockets<'gc> {
+    fn trace(&self, cc: CollectionContext) {
+        for (_, socket) in self.sockets.iter() {
+            socket.trace(cc)
+        }
+    }
+}
+
+impl<'gc> Sockets<'gc> {
+    pub fn empty() -> Self {
+        let (sender, receiver) = unbounded();
+
+        Self {
+            sockets: Arena::new(),
+            sender,
+            receiver,
+        }
+    }
+
+    /// Starts connecting a socket for an AVM1 `XMLSocket`.
+    ///
+    /// `onConnect` is called on `target` once the connection attempt finishes.
+    pub fn connect_avm1(
+        &mut self,
+        navigator: &mut dyn NavigatorBackend,
+        target: Avm1Object<'gc>,
+        host: String,
+        port: u16,
+        timeout: Duration,
+    ) -> SocketHandle {
+        self.connect(navigator, SocketTarget::Avm1(target), host, port, timeout)
+    }
+
+    /// Starts connecting a socket for an AVM2 `Socket`.
+    ///
+    /// Either `connect` or an error event is dispatched to `target` once the
+    /// connection attempt finishes.
+    pub fn connect_avm2(
+        &mut self,
+        navigator: &mut dyn NavigatorBackend,
+        target: Avm2Object<'gc>,
+        host: String,
+        port: u16,
+        timeout: Duration,
+    ) -> SocketHandle {
+        self.connect(navigator, SocketTarget::Avm2(target), host, port, timeout)
+    }
+
+    fn connect(
+        &mut self,
+        navigator: &mut dyn NavigatorBackend,
+        target: SocketTarget<'gc>,
+        host: String,
+        port: u16,
+        timeout: Duration,
+    ) -> SocketHandle {
+        let (sender, receiver) = unbounded();
+        let handle = self.sockets.insert(Socket {
+            target,
+            address: format!("{host}:{port}"),
+            sender,
+            buffer: Vec::new(),
+        });
+        navigator.connect_socket(host, port, timeout, handle, receiver, self.sender.clone());
+        handle
+    }
+
+    /// The host to connect to when the movie doesn't give one, which is the host
+    /// that the movie was loaded from.
+    pub fn default_host(context: &UpdateContext<'_, 'gc>) -> String {
+        context
+            .swf
+            .url()
+            .and_then(|url| Url::parse(url).ok())
+            .and_then(|url| url.host_str().map(str::to_owned))
+            .unwrap_or_else(|| "localhost".to_owned())
+    }
+
+    /// Finds the socket that belongs to an AVM2 `Socket`, if it has one.
+    pub fn avm2_handle(&self, target: Avm2Object<'gc>) -> Option<SocketHandle> {
+        self.sockets
+            .iter()
+            .find_map(|(handle, socket)| match socket.target {
+                SocketTarget::Avm2(object) if Avm2Object::ptr_eq(object, target) => Some(handle),
+                _ => None,
+            })
+    }
+
+    /// Queues up bytes to be written to a socket.
+    ///
+    /// Returns `false` if the socket isn't open.
+    pub fn send(&mut self, handle: SocketHandle, data: Vec<u8>) -> bool {
+        match self.sockets.get(handle) {
+            Some(socket) => socket.sender.try_send(data).is_ok(),
+            None => false,
+        }
+    }
+
+    /// Closes a socket, without calling back into its target.
+    pub fn close(&mut self, handle: SocketHandle) {
+        // Dropping the socket drops its sender, which closes it in the backend.
+        self.sockets.remove(handle);
+    }
+
+    /// Handles everything that happened to a socket since the last update,
+    /// and runs the necessary callbacks.
+    pub fn update_sockets(context: &mut UpdateContext<'_, 'gc>) {
+        let actions: Vec<_> =
+            std::iter::from_fn(|| context.sockets.receiver.try_recv().ok()).collect();
+
+        for action in actions {
+            match action {
+                SocketAction::Connect(handle, state) => {
+                    let Some(socket) = context.sockets.sockets.get(handle) else {
+                        continue;
+                    };
+                    let target = socket.target;
+                    let address = socket.address.clone();
+                    if state != ConnectionState::Connected {
+                        context.sockets.sockets.remove(handle);
+                    }
+
+                    match target {
+                        SocketTarget::Avm1(target) => {
+                            let connected = state == ConnectionState::Connected;
+                            Self::call_avm1(context, target, "onConnect", &[connected.into()]);
+                        }
+                        SocketTarget::Avm2(target) => {
+                            Self::connect_avm2_event(context, target, state, &address)
+                        }
+                    }
+                }
+                SocketAction::Data(handle, data) => {
+                    let Some(socket) = context.sockets.sockets.get_mut(handle) else {
+                        continue;
+                    };
+
+                    let target = socket.target;
+                    match target {
+                        SocketTarget::Avm1(target) => {
+                            socket.buffer.extend(data);
+
+                            // Flash ends each message with a null byte.
+                            let mut messages = Vec::new();
+                            while let Some(end) = socket.buffer.iter().position(|&byte| byte == 0) {
+                                let mut message: Vec<u8> = socket.buffer.drain(..=end).collect();
+                                message.pop();
+                                messages.push(message);
+                            }
+
+                            for message in messages {
+                                let message =
+                                    AvmString::new_utf8_bytes(context.gc_context, &message);
+                                Self::call_avm1(context, target, "onData", &[message.into()]);
+                            }
+                        }
+                        SocketTarget::Avm2(target) => Self::data_avm2_event(context, target, &data),
+                    }
+                }
+                SocketAction::Close(handle) => {
+                    let Some(socket) = context.sockets.sockets.remove(handle) else {
+                        continue;
+                    };
+
+                    match socket.target {
+                        SocketTarget::Avm1(target) => {
+                            Self::call_avm1(context, target, "onClose", &[]);
+                        }
+                        SocketTarget::Avm2(target) => {
+                            let mut activation = Avm2Activation::from_nothing(context.reborrow());
+                            if let Err(e) = Self::set_avm2_connected(&mut activation, target, false)
+                            {
+                                tracing::error!("Error closing AVM2 Socket: {}", e);
+                            }
+                            let event = Avm2EventObject::bare_default_event(
+                                &mut activation.context,
+                                "close",
+                            );
+                            Self::dispatch_avm2(&mut activation.context, event, target);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn connect_avm2_event(
+        context: &mut UpdateContext<'_, 'gc>,
+        target: Avm2Object<'gc>,
+        state: ConnectionState,
+        address: &str,
+    ) {
+        let mut activation = Avm2Activation::from_nothing(context.reborrow());
+        let event = match state {
+            ConnectionState::Connected => {
+                if let Err(e) = Self::set_avm2_connected(&mut activation, target, true) {
+                    tracing::error!("Error connecting AVM2 Socket: {}", e);
+                }
+                Ok(Avm2EventObject::bare_default_event(
+                    &mut activation.context,
+                    "connect",
+                ))
+            }
+            ConnectionState::Failed => {
+                let class = activation.avm2().classes().ioerrorevent;
+                let text = AvmString::new_utf8(
+                    activation.context.gc_context,
+                    format!("Error #2031: Socket Error. URL: {address}"),
+                );
+                class.construct(
+                    &mut activation,
+                    &[
+                        "ioError".into(),
+                        false.into(),
+                        false.into(),
+                        text.into(),
+                        2031.into(),
+                    ],
+                )
+            }
+            // Flash treats a socket that never answers like one it isn't allowed to connect to.
+            ConnectionState::TimedOut => {
+                let class = activation.avm2().classes().securityerrorevent;
+                let text = AvmString::new_utf8(
+                    activation.context.gc_context,
+                    format!(
+                        "Error #2048: Security sandbox violation: cannot load data from {address}."
+                    ),
+                );
+                class.construct(
+                    &mut activation,
+                    &[
+                        "securityError".into(),
+                        false.into(),
+                        false.into(),
+                        text.into(),
+                        2048.into(),
+                    ],
+                )
+            }
+        };
+
+        match event {
+            Ok(event) => Self::dispatch_avm2(&mut activation.context, event, target),
+            Err(e) => tracing::error!("Error constructing AVM2 Socket event: {}", e),
+        }
+    }
+
+    fn data_avm2_event(context: &mut UpdateContext<'_, 'gc>, target: Avm2Object<'gc>, data: &[u8]) {
+        let mut activation = Avm2Activation::from_nothing(context.reborrow());
+        let input = target
+            .get_property(
+                &Multiname::new(activation.avm2().flash_net_internal, "_input"),
+                &mut activation,
+            )
+            .ok()
+            .and_then(|input| input.as_object());
+        let Some(input) = input else {
+            tracing::error!("AVM2 Socket is missing its input buffer");
+            return;
+        };
+        if let Some(mut bytearray) = input.as_bytearray_mut(activation.context.gc_context) {
+            // Only the bytes that haven't been read yet are kept around.
+            bytearray.discard_read();
+            let length = bytearray.len();
+            if let Err(e) = bytearray.write_at(data, length) {
+                tracing::error!("Error buffering AVM2 Socket data: {}", e);
+            }
+        }
+
+        let class = activation.avm2().classes().progressevent;
+        let event = class.construct(
+            &mut activation,
+            &[
+                "socketData".into(),
+                false.into(),
+                false.into(),
+                data.len().into(),
+                0.into(),
+            ],
+        );
+        match event {
+            Ok(event) => Self::dispatch_avm2(&mut activation.context, event, target),
+            Err(e) => tracing::error!("Error constructing AVM2 Socket event: {}", e),
+        }
+    }
+
+    fn set_avm2_connected(
+        activation: &mut Avm2Activation<'_, 'gc>,
+        mut target: Avm2Object<'gc>,
+        connected: bool,
+    ) -> Result<(), crate::avm2::Error<'gc>> {
+        target.set_property(
+            &Multiname::new(activation.avm2().flash_net_internal, "_connected"),
+            connected.into(),
+            activation,
+        )
+    }
+
+    fn dispatch_avm2(
+        context: &mut UpdateContext<'_, 'gc>,
+        event: Avm2Object<'gc>,
+        target: Avm2Object<'gc>,
+    ) {
+        if let Err(e) = Avm2::dispatch_event(context, event, target) {
+            tracing::error!("Unhandled AVM2 error in Socket event: {}", e);
+        }
+    }
+
+    fn call_avm1(
+        context: &mut UpdateContext<'_, 'gc>,
+        target: Avm1Object<'gc>,
+        method: &'static str,
+        args: &[Avm1Value<'gc>],
+    ) {
+        let root_clip = context.stage.root_clip();
+        let mut activation = Activation::from_nothing(
+            context.reborrow(),
+            ActivationIdentifier::root("[XMLSocket]"),
+            root_clip,
+        );
+
+        if let Err(e) = target.call_method(
+            method.into(),
+            args,
+            &mut activation,
+            ExecutionReason::Special,
+        ) {
+            tracing::error!("Unhandled AVM1 error in XMLSocket.{}: {}", method, e);
+        }
+    }
+}
@@ -378,8 +378,9 @@ impl<'gc> XmlNode<'gc> {
     ///
     /// If the `deep` flag is set true, then the entire node tree will be cloned.
     pub fn duplicate(self, gc_context: MutationContext<'gc, '_>, deep: bool) -> Self {
+        // The attributes are copied oldest first, so that they keep their order.
         let attributes = ScriptObject::new(gc_context, None);
-        for (key, value) in self.attributes().own_properties() {
+        for (key, value) in self.attributes().own_properties().into_iter().rev() {
             attributes.define_value(gc_context, key, value, Attribute::empty());
         }
 
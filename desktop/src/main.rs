@@ -11,6 +11,7 @@ mod custom_event;
 mod executor;
 mod navigator;
 mod storage;
+mod system_fonts;
 mod task;
 mod ui;
 
@@ -26,6 +27,7 @@ use ruffle_core::{
 };
 use ruffle_render::backend::RenderBackend;
 use ruffle_render::quality::StageQuality;
+use ruffle_render::utils::unmultiply_alpha_rgba;
 use ruffle_render_wgpu::backend::WgpuRenderBackend;
 use ruffle_render_wgpu::clap::{GraphicsBackend, PowerPreference};
 use std::cell::RefCell;
@@ -38,8 +40,8 @@ use std::time::Instant;
 use url::Url;
 use winit::dpi::{LogicalSize, PhysicalPosition, PhysicalSize, Size};
 use winit::event::{
-    ElementState, KeyboardInput, ModifiersState, MouseButton, MouseScrollDelta, VirtualKeyCode,
-    WindowEvent,
+    ElementState, Ime, KeyboardInput, ModifiersState, MouseButton, MouseScrollDelta,
+    VirtualKeyCode, WindowEvent,
 };
 use winit::event_loop::{ControlFlow, EventLoop, EventLoopBuilder};
 use winit::window::{Fullscreen, Icon, Window, WindowBuilder};
@@ -95,6 +97,11 @@ struct Opt {
     #[clap(long, short, default_value = "high")]
     quality: StageQuality,
 
+    /// Number of MSAA samples to render with, instead of the number implied by the quality.
+    /// Lower values trade edge quality for speed. Unsupported values are rounded down.
+    #[clap(long)]
+    sample_count: Option<u32>,
+
     /// The scale mode of the stage.
     #[clap(long, short, default_value = "show-all")]
     scale: StageScaleMode,
@@ -141,6 +148,33 @@ struct Opt {
     /// The version of the player to emulate
     #[clap(long)]
     player_version: Option<u8>,
+
+    /// The function key that saves a screenshot of the movie as a PNG next to it, such as F12.
+    #[clap(long, default_value = "F12", value_parser = parse_function_key)]
+    screenshot_key: VirtualKeyCode,
+}
+
+fn parse_function_key(value: &str) -> Result<VirtualKeyCode, String> {
+    const FUNCTION_KEYS: [VirtualKeyCode; 12] = [
+        VirtualKeyCode::F1,
+        VirtualKeyCode::F2,
+        VirtualKeyCode::F3,
+        VirtualKeyCode::F4,
+        VirtualKeyCode::F5,
+        VirtualKeyCode::F6,
+        VirtualKeyCode::F7,
+        VirtualKeyCode::F8,
+        VirtualKeyCode::F9,
+        VirtualKeyCode::F10,
+        VirtualKeyCode::F11,
+        VirtualKeyCode::F12,
+    ];
+    value
+        .strip_prefix(['F', 'f'])
+        .and_then(|number| number.parse::<usize>().ok())
+        .and_then(|number| FUNCTION_KEYS.get(number.checked_sub(1)?))
+        .copied()
+        .ok_or_else(|| format!("{value} is not a function key from F1 to F12"))
 }
 
 #[cfg(feature = "render_trace")]
@@ -182,6 +216,47 @@ fn parse_parameters(opt: &Opt) -> impl '_ + Iterator<Item = (String, String)> {
     })
 }
 
+/// Finds an unused file name for a screenshot, next to the movie if it's a local file and in the
+/// current directory otherwise.
+fn screenshot_path(movie_url: &Url) -> PathBuf {
+    let (directory, name) = match movie_url.to_file_path() {
+        Ok(path) => (
+            path.parent().map(Path::to_owned).unwrap_or_default(),
+            path.file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned()),
+        ),
+        Err(()) => (
+            PathBuf::new(),
+            movie_url
+                .path_segments()
+                .and_then(|segments| segments.last())
+                .and_then(|segment| segment.split('.').next())
+                .filter(|name| !name.is_empty())
+                .map(str::to_owned),
+        ),
+    };
+    let name = name.unwrap_or_else(|| "ruffle".to_string());
+    (1..)
+        .map(|number| directory.join(format!("{name}_{number}.png")))
+        .find(|path| !path.exists())
+        .expect("Some screenshot name must be unused")
+}
+
+fn save_screenshot(player: &mut Player, movie_url: &Url) -> Result<PathBuf, Error> {
+    let bitmap = player
+        .capture_frame()
+        .ok_or_else(|| anyhow!("The renderer couldn't capture the frame"))?;
+    let mut rgba = bitmap.data().to_vec();
+    unmultiply_alpha_rgba(&mut rgba);
+    let image = image::RgbaImage::from_raw(bitmap.width(), bitmap.height(), rgba)
+        .ok_or_else(|| anyhow!("The captured frame has the wrong size"))?;
+    let path = screenshot_path(movie_url);
+    image
+        .save(&path)
+        .with_context(|| format!("Couldn't save screenshot to {}", path.display()))?;
+    Ok(path)
+}
+
 fn pick_file() -> Option<PathBuf> {
     FileDialog::new()
         .add_filter("Flash Files", &["swf", "spl"])
@@ -230,6 +305,7 @@ struct App {
     event_loop: EventLoop<RuffleEvent>,
     executor: Arc<Mutex<GlutinAsyncExecutor>>,
     player: Arc<Mutex<Player>>,
+    movie_url: Url,
 }
 
 impl App {
@@ -284,7 +360,7 @@ impl App {
         );
 
         let viewport_size = window.inner_size();
-        let renderer = WgpuRenderBackend::for_window(
+        let mut renderer = WgpuRenderBackend::for_window(
             &window,
             (viewport_size.width, viewport_size.height),
             opt.graphics.into(),
@@ -293,6 +369,7 @@ impl App {
         )
         .map_err(|e| anyhow!(e.to_string()))
         .context("Couldn't create wgpu rendering backend")?;
+        renderer.set_sample_count(opt.sample_count);
         RENDER_INFO.with(|i| *i.borrow_mut() = Some(renderer.debug_info().to_string()));
 
         let window = Rc::new(window);
@@ -341,6 +418,7 @@ impl App {
             event_loop,
             executor,
             player,
+            movie_url,
         })
     }
 
@@ -392,6 +470,20 @@ impl App {
                         } => self.player.lock().expect("Cannot reenter").update(|uc| {
                             uc.stage.set_display_state(uc, StageDisplayState::Normal);
                         }),
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(key),
+                            ..
+                        } if *key == self.opt.screenshot_key => {
+                            let mut player_lock = self.player.lock().expect("Cannot reenter");
+                            match save_screenshot(&mut player_lock, &self.movie_url) {
+                                Ok(path) => {
+                                    tracing::info!("Saved screenshot to {}", path.display())
+                                }
+                                Err(e) => tracing::error!("Couldn't take screenshot: {:#}", e),
+                            }
+                            return;
+                        }
                         _ => (),
                     }
                 }
@@ -537,6 +629,24 @@ impl App {
                                 self.window.request_redraw();
                             }
                         }
+                        WindowEvent::Ime(ime) => {
+                            let event = match ime {
+                                Ime::Preedit(text, _) => Some(PlayerEvent::ImeComposition { text }),
+                                Ime::Commit(text) => Some(PlayerEvent::ImeCommit { text }),
+                                // Whatever was being composed is dropped along with the IME.
+                                Ime::Disabled => Some(PlayerEvent::ImeComposition {
+                                    text: String::new(),
+                                }),
+                                Ime::Enabled => None,
+                            };
+                            if let Some(event) = event {
+                                let mut player_lock = self.player.lock().expect("Cannot reenter");
+                                player_lock.handle_event(event);
+                                if player_lock.needs_render() {
+                                    self.window.request_redraw();
+                                }
+                            }
+                        }
                         _ => (),
                     },
                     winit::event::Event::UserEvent(RuffleEvent::TaskPoll) => self
@@ -1,16 +1,24 @@
 //! Navigator backend for web
 
 use crate::custom_event::RuffleEvent;
+use async_channel::{Receiver, Sender as AsyncSender};
+use async_io::{Async, Timer};
+use futures::future::{self, Either};
+use futures::{AsyncReadExt, AsyncWriteExt};
 use isahc::{
     config::RedirectPolicy, prelude::*, AsyncReadResponseExt, HttpClient, Request as IsahcRequest,
 };
 use ruffle_core::backend::navigator::{
-    NavigationMethod, NavigatorBackend, OwnedFuture, Request, Response,
+    ConnectionState, NavigationMethod, NavigatorBackend, OwnedFuture, Request, Response,
+    SocketAction, SocketHandle,
 };
 use ruffle_core::indexmap::IndexMap;
 use ruffle_core::loader::Error;
+use std::io::ErrorKind;
+use std::net::{Shutdown, TcpStream, ToSocketAddrs};
 use std::rc::Rc;
 use std::sync::mpsc::Sender;
+use std::time::Duration;
 use url::Url;
 use winit::event_loop::EventLoopProxy;
 
@@ -155,16 +163,23 @@ impl NavigatorBackend for ExternalNavigatorBackend {
                     Err(e)
                 }).map_err(|e| Error::FetchError(e.to_string()))?;
 
-                Ok(Response { url, body })
+                Ok(Response {
+                    url,
+                    body,
+                    status: 0,
+                })
             }),
             _ => Box::pin(async move {
                 let client =
                     client.ok_or_else(|| Error::FetchError("Network unavailable".to_string()))?;
 
-                let isahc_request = match request.method() {
+                let mut isahc_request = match request.method() {
                     NavigationMethod::Get => IsahcRequest::get(processed_url.to_string()),
                     NavigationMethod::Post => IsahcRequest::post(processed_url.to_string()),
                 };
+                for (name, value) in request.headers() {
+                    isahc_request = isahc_request.header(name, value);
+                }
 
                 let (body_data, _) = request.body().clone().unwrap_or_default();
                 let body = isahc_request
@@ -176,11 +191,9 @@ impl NavigatorBackend for ExternalNavigatorBackend {
                     .await
                     .map_err(|e| Error::FetchError(e.to_string()))?;
 
-                if !response.status().is_success() {
-                    return Err(Error::FetchError(format!(
-                        "HTTP status is not ok, got {}",
-                        response.status()
-                    )));
+                let status = response.status();
+                if !status.is_success() {
+                    return Err(Error::HttpNotOk(status.as_u16()));
                 }
 
                 let url = if let Some(uri) = response.effective_uri() {
@@ -195,7 +208,11 @@ impl NavigatorBackend for ExternalNavigatorBackend {
                     .await
                     .map_err(|e| Error::FetchError(e.to_string()))?;
 
-                Ok(Response { url, body })
+                Ok(Response {
+                    url,
+                    body,
+                    status: status.as_u16(),
+                })
             }),
         }
     }
@@ -216,4 +233,95 @@ impl NavigatorBackend for ExternalNavigatorBackend {
         }
         url
     }
+
+    fn connect_socket(
+        &mut self,
+        host: String,
+        port: u16,
+        timeout: Duration,
+        handle: SocketHandle,
+        receiver: Receiver<Vec<u8>>,
+        sender: AsyncSender<SocketAction>,
+    ) {
+        let future: OwnedFuture<(), Error> = Box::pin(async move {
+            let address = match (host.as_str(), port).to_socket_addrs() {
+                Ok(mut addresses) => addresses.next(),
+                Err(e) => {
+                    tracing::warn!("Could not resolve socket host {}: {}", host, e);
+                    None
+                }
+            };
+            let Some(address) = address else {
+                let _ = sender
+                    .send(SocketAction::Connect(handle, ConnectionState::Failed))
+                    .await;
+                return Ok(());
+            };
+
+            let connect = Box::pin(Async::<TcpStream>::connect(address));
+            let stream = match future::select(connect, Timer::after(timeout)).await {
+                Either::Left((Ok(stream), _)) => stream,
+                Either::Left((Err(e), _)) => {
+                    let state = if e.kind() == ErrorKind::TimedOut {
+                        ConnectionState::TimedOut
+                    } else {
+                        tracing::warn!("Could not connect to {}:{}: {}", host, port, e);
+                        ConnectionState::Failed
+                    };
+                    let _ = sender.send(SocketAction::Connect(handle, state)).await;
+                    return Ok(());
+                }
+                Either::Right(_) => {
+                    let _ = sender
+                        .send(SocketAction::Connect(handle, ConnectionState::TimedOut))
+                        .await;
+                    return Ok(());
+                }
+            };
+            if sender
+                .send(SocketAction::Connect(handle, ConnectionState::Connected))
+                .await
+                .is_err()
+            {
+                // The player has gone away in the meantime.
+                return Ok(());
+            }
+
+            let read = Box::pin(async {
+                let mut buffer = [0; 4096];
+                loop {
+                    match (&stream).read(&mut buffer).await {
+                        Ok(0) => break,
+                        Ok(read) => {
+                            let data = buffer[..read].to_vec();
+                            if sender.send(SocketAction::Data(handle, data)).await.is_err() {
+                                break;
+                            }
+                        }
+                        Err(e) => {
+                            tracing::warn!("Error reading from socket {}:{}: {}", host, port, e);
+                            break;
+                        }
+                    }
+                }
+            });
+            let write = Box::pin(async {
+                // The receiver is closed once the socket is closed on the player's side.
+                while let Ok(data) = receiver.recv().await {
+                    if let Err(e) = (&stream).write_all(&data).await {
+                        tracing::warn!("Error writing to socket {}:{}: {}", host, port, e);
+                        break;
+                    }
+                }
+            });
+
+            // Whichever side finishes first closes the whole socket.
+            future::select(read, write).await;
+            let _ = stream.get_ref().shutdown(Shutdown::Both);
+            let _ = sender.send(SocketAction::Close(handle)).await;
+            Ok(())
+        });
+
+        self.spawn_future(future);
+    }
 }
@@ -0,0 +1,207 @@
+//! Device fonts from the fonts that are installed on this system
+
+use ruffle_core::backend::ui::FontDefinition;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+use ttf_parser::{name_id, Face};
+
+/// The fonts that `_sans` is drawn with, in the order they're preferred.
+const SANS_FONTS: &[&str] = &[
+    "Arial",
+    "Helvetica",
+    "Liberation Sans",
+    "DejaVu Sans",
+    "Noto Sans",
+];
+
+/// The fonts that `_serif` is drawn with, in the order they're preferred.
+const SERIF_FONTS: &[&str] = &[
+    "Times New Roman",
+    "Times",
+    "Liberation Serif",
+    "DejaVu Serif",
+    "Noto Serif",
+];
+
+/// The fonts that `_typewriter` is drawn with, in the order they're preferred.
+const TYPEWRITER_FONTS: &[&str] = &[
+    "Courier New",
+    "Courier",
+    "Liberation Mono",
+    "DejaVu Sans Mono",
+    "Noto Sans Mono",
+];
+
+/// Fonts with the characters that the fonts above don't have, such as CJK ideographs
+/// and symbols. Every one of these that's installed is used, in this order.
+const FALLBACK_FONTS: &[&str] = &[
+    "Noto Sans CJK JP",
+    "Noto Sans JP",
+    "Yu Gothic",
+    "MS Gothic",
+    "Hiragino Sans",
+    "Microsoft YaHei",
+    "PingFang SC",
+    "Malgun Gothic",
+    "Arial Unicode MS",
+    "Segoe UI Symbol",
+];
+
+/// A font that's installed on this system.
+struct SystemFont {
+    path: PathBuf,
+    index: u32,
+    family: String,
+    is_bold: bool,
+    is_italic: bool,
+}
+
+/// The fonts in the font directories of this system.
+pub struct SystemFonts {
+    fonts: Vec<SystemFont>,
+}
+
+impl SystemFonts {
+    /// Finds the fonts in the font directories, and the family and style of each.
+    pub fn scan() -> Self {
+        let mut fonts = Vec::new();
+        for directory in font_directories() {
+            scan_directory(&directory, &mut fonts);
+        }
+        Self { fonts }
+    }
+
+    /// Registers the font that best matches a device font, followed by the fonts for the
+    /// characters that it doesn't have.
+    ///
+    /// A family that isn't installed is drawn with the same font as `_sans`.
+    pub fn load(
+        &self,
+        name: &str,
+        is_bold: bool,
+        is_italic: bool,
+        register: &mut dyn FnMut(FontDefinition),
+    ) {
+        let families = match name {
+            "_sans" => SANS_FONTS,
+            "_serif" => SERIF_FONTS,
+            "_typewriter" => TYPEWRITER_FONTS,
+            _ => std::slice::from_ref(&name),
+        };
+        let primary = families
+            .iter()
+            .chain(SANS_FONTS)
+            .find_map(|family| self.find(family, is_bold, is_italic));
+        let fallbacks = FALLBACK_FONTS
+            .iter()
+            .filter_map(|family| self.find(family, false, false))
+            .filter(|font| !primary.map_or(false, |primary| std::ptr::eq(*font, primary)));
+
+        for font in primary.into_iter().chain(fallbacks) {
+            match std::fs::read(&font.path) {
+                Ok(data) => register(FontDefinition::FontFile {
+                    name: font.family.clone(),
+                    is_bold: font.is_bold,
+                    is_italic: font.is_italic,
+                    data,
+                    index: font.index,
+                }),
+                Err(e) => warn!("Couldn't read font {:?}: {}", font.path, e),
+            }
+        }
+    }
+
+    /// Finds a font of a family, preferring the one that's closest to the given style.
+    fn find(&self, family: &str, is_bold: bool, is_italic: bool) -> Option<&SystemFont> {
+        self.fonts
+            .iter()
+            .filter(|font| font.family.eq_ignore_ascii_case(family))
+            .min_by_key(|font| {
+                u8::from(font.is_bold != is_bold) + u8::from(font.is_italic != is_italic)
+            })
+    }
+}
+
+fn font_directories() -> Vec<PathBuf> {
+    let mut directories = Vec::new();
+    if cfg!(target_os = "windows") {
+        if let Some(windows) = std::env::var_os("WINDIR") {
+            directories.push(Path::new(&windows).join("Fonts"));
+        }
+        if let Some(local) = dirs::data_local_dir() {
+            directories.push(local.join("Microsoft").join("Windows").join("Fonts"));
+        }
+    } else if cfg!(target_os = "macos") {
+        directories.push(PathBuf::from("/System/Library/Fonts"));
+        directories.push(PathBuf::from("/Library/Fonts"));
+    } else {
+        directories.push(PathBuf::from("/usr/share/fonts"));
+        directories.push(PathBuf::from("/usr/local/share/fonts"));
+        if let Some(home) = dirs::home_dir() {
+            directories.push(home.join(".fonts"));
+        }
+    }
+    // The fonts that are only installed for this user
+    directories.extend(dirs::font_dir());
+    directories
+}
+
+fn scan_directory(directory: &Path, fonts: &mut Vec<SystemFont>) {
+    let Ok(entries) = std::fs::read_dir(directory) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if entry
+            .file_type()
+            .map_or(false, |file_type| file_type.is_dir())
+        {
+            scan_directory(&path, fonts);
+            continue;
+        }
+
+        let is_font_file = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map_or(false, |extension| {
+                ["ttf", "otf", "ttc", "otc"]
+                    .iter()
+                    .any(|font| extension.eq_ignore_ascii_case(font))
+            });
+        if !is_font_file {
+            continue;
+        }
+        let Ok(data) = std::fs::read(&path) else {
+            continue;
+        };
+
+        let count = ttf_parser::fonts_in_collection(&data).unwrap_or(1);
+        for index in 0..count {
+            let Ok(face) = Face::parse(&data, index) else {
+                continue;
+            };
+            if let Some(family) = family_name(&face) {
+                fonts.push(SystemFont {
+                    path: path.clone(),
+                    index,
+                    family,
+                    is_bold: face.is_bold(),
+                    is_italic: face.is_italic(),
+                });
+            }
+        }
+    }
+}
+
+/// The family that a font belongs to, which doesn't include styles like "Bold"
+/// when the font names its typographic family.
+fn family_name(face: &Face) -> Option<String> {
+    [name_id::TYPOGRAPHIC_FAMILY, name_id::FAMILY]
+        .into_iter()
+        .find_map(|id| {
+            face.names()
+                .into_iter()
+                .filter(|name| name.name_id == id)
+                .find_map(|name| name.to_string())
+        })
+}
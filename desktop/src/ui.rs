@@ -1,15 +1,23 @@
+use crate::system_fonts::SystemFonts;
 use anyhow::{Context, Error};
 use arboard::Clipboard;
+use once_cell::unsync::OnceCell;
 use rfd::{MessageButtons, MessageDialog, MessageLevel};
-use ruffle_core::backend::ui::{FullscreenError, MouseCursor, UiBackend};
+use ruffle_core::backend::ui::{FontDefinition, FullscreenError, MouseCursor, UiBackend};
+use ruffle_render::bounding_box::BoundingBox;
 use std::rc::Rc;
 use tracing::error;
+use winit::dpi::PhysicalPosition;
 use winit::window::{Fullscreen, Window};
 
 pub struct DesktopUiBackend {
     window: Rc<Window>,
     cursor_visible: bool,
     clipboard: Clipboard,
+    /// The fonts installed on this system, which are only looked for once a movie needs them.
+    system_fonts: OnceCell<SystemFonts>,
+    /// Whether input methods are enabled, which they are while a text field is typed into.
+    ime_allowed: bool,
 }
 
 impl DesktopUiBackend {
@@ -18,6 +26,8 @@ impl DesktopUiBackend {
             window,
             cursor_visible: true,
             clipboard: Clipboard::new().context("Couldn't get platform clipboard")?,
+            system_fonts: OnceCell::new(),
+            ime_allowed: false,
         })
     }
 }
@@ -94,4 +104,34 @@ impl UiBackend for DesktopUiBackend {
             .set_buttons(MessageButtons::Ok);
         dialog.show();
     }
+
+    fn load_device_font(
+        &self,
+        name: &str,
+        is_bold: bool,
+        is_italic: bool,
+        register: &mut dyn FnMut(FontDefinition),
+    ) {
+        self.system_fonts
+            .get_or_init(SystemFonts::scan)
+            .load(name, is_bold, is_italic, register);
+    }
+
+    fn set_text_input_area(&mut self, area: Option<BoundingBox>) {
+        if area.is_some() != self.ime_allowed {
+            self.ime_allowed = area.is_some();
+            self.window.set_ime_allowed(self.ime_allowed);
+        }
+        // The candidate window of the input method is placed under the caret.
+        if let Some(area) = area {
+            self.window.set_ime_position(PhysicalPosition::new(
+                area.x_min.to_pixels(),
+                area.y_max.to_pixels(),
+            ));
+        }
+    }
+
+    fn request_soft_keyboard(&mut self) -> bool {
+        false
+    }
 }
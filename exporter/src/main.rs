@@ -3,6 +3,7 @@ use clap::Parser;
 use image::RgbaImage;
 use indicatif::{ProgressBar, ProgressStyle};
 use rayon::prelude::*;
+use ruffle_core::backend::ui::FontDefinition;
 use ruffle_core::limits::ExecutionLimit;
 use ruffle_core::tag_utils::SwfMovie;
 use ruffle_core::PlayerBuilder;
@@ -80,9 +81,38 @@ struct Opt {
     /// Skip unsupported movie types (currently AVM 2)
     #[clap(long, action)]
     skip_unsupported: bool,
+
+    /// A font file to draw device text with, instead of the fonts of this system.
+    /// Can be given more than once, with later fonts used for characters that the
+    /// earlier ones don't have.
+    #[clap(long = "font")]
+    fonts: Vec<PathBuf>,
+}
+
+/// Reads the font files to draw device text with.
+fn load_fonts(paths: &[PathBuf]) -> Result<Vec<FontDefinition>> {
+    paths
+        .iter()
+        .map(|path| {
+            let data = std::fs::read(path)
+                .map_err(|e| anyhow!("Unable to read font {:?}: {}", path, e))?;
+            Ok(FontDefinition::FontFile {
+                name: path
+                    .file_stem()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .into_owned(),
+                is_bold: false,
+                is_italic: false,
+                data,
+                index: 0,
+            })
+        })
+        .collect()
 }
 
 /// Captures a screenshot. The resulting image uses straight alpha
+#[allow(clippy::too_many_arguments)]
 fn take_screenshot(
     descriptors: Arc<Descriptors>,
     swf_path: &Path,
@@ -91,6 +121,7 @@ fn take_screenshot(
     progress: &Option<ProgressBar>,
     size: SizeOpt,
     skip_unsupported: bool,
+    fonts: &[FontDefinition],
 ) -> Result<Vec<RgbaImage>> {
     let movie = SwfMovie::from_path(swf_path, None).map_err(|e| anyhow!(e.to_string()))?;
 
@@ -112,13 +143,16 @@ fn take_screenshot(
 
     let target = TextureTarget::new(&descriptors.device, (width, height))
         .map_err(|e| anyhow!(e.to_string()))?;
-    let player = PlayerBuilder::new()
+    let mut builder = PlayerBuilder::new()
         .with_renderer(
             WgpuRenderBackend::new(descriptors, target).map_err(|e| anyhow!(e.to_string()))?,
         )
         .with_movie(movie)
-        .with_viewport_dimensions(width, height, size.scale)
-        .build();
+        .with_viewport_dimensions(width, height, size.scale);
+    for font in fonts {
+        builder = builder.with_device_font(font.clone());
+    }
+    let player = builder.build();
 
     let mut result = Vec::new();
     let totalframes = frames + skipframes;
@@ -197,6 +231,7 @@ fn find_files(root: &Path, with_progress: bool) -> Vec<DirEntry> {
 }
 
 fn capture_single_swf(descriptors: Arc<Descriptors>, opt: &Opt) -> Result<()> {
+    let fonts = load_fonts(&opt.fonts)?;
     let output = opt.output_path.clone().unwrap_or_else(|| {
         let mut result = PathBuf::new();
         result.set_file_name(opt.swf.file_stem().unwrap());
@@ -232,6 +267,7 @@ fn capture_single_swf(descriptors: Arc<Descriptors>, opt: &Opt) -> Result<()> {
         &progress,
         opt.size,
         opt.skip_unsupported,
+        &fonts,
     )?;
 
     if let Some(progress) = &progress {
@@ -274,6 +310,7 @@ fn capture_single_swf(descriptors: Arc<Descriptors>, opt: &Opt) -> Result<()> {
 
 #[allow(clippy::branches_sharing_code)]
 fn capture_multiple_swfs(descriptors: Arc<Descriptors>, opt: &Opt) -> Result<()> {
+    let fonts = load_fonts(&opt.fonts)?;
     let output = opt.output_path.clone().unwrap();
     let files = find_files(&opt.swf, !opt.silent);
 
@@ -309,6 +346,7 @@ fn capture_multiple_swfs(descriptors: Arc<Descriptors>, opt: &Opt) -> Result<()>
             &progress,
             opt.size,
             opt.skip_unsupported,
+            &fonts,
         ) {
             let mut relative_path = file
                 .path()
@@ -6,19 +6,22 @@ use ruffle_render::backend::{
     Context3D, Context3DCommand, RenderBackend, ShapeHandle, ViewportDimensions,
 };
 use ruffle_render::bitmap::{
-    Bitmap, BitmapFormat, BitmapHandle, BitmapHandleImpl, BitmapSource, SyncHandle,
+    Bitmap, BitmapFormat, BitmapHandle, BitmapHandleImpl, BitmapRegion, BitmapSource, SyncHandle,
 };
 use ruffle_render::color_transform::ColorTransform;
 use ruffle_render::commands::{CommandHandler, CommandList};
 use ruffle_render::error::Error;
+use ruffle_render::filters::Filter;
 use ruffle_render::matrix::Matrix;
 use ruffle_render::quality::StageQuality;
-use ruffle_render::shape_utils::{DistilledShape, DrawCommand, LineScaleMode, LineScales};
+use ruffle_render::shape_utils::{
+    triangle_bitmap_matrix, DistilledShape, DrawCommand, FillRule, LineScaleMode, LineScales,
+};
 use ruffle_render::transform::Transform;
 use ruffle_web_common::{JsError, JsResult};
 use std::borrow::Cow;
 use std::sync::Arc;
-use swf::{BlendMode, Color};
+use swf::{BlendMode, Color, Twips};
 use wasm_bindgen::{Clamped, JsCast, JsValue};
 use web_sys::{
     CanvasGradient, CanvasPattern, CanvasRenderingContext2d, CanvasWindingRule, DomMatrix, Element,
@@ -36,6 +39,8 @@ pub struct WebCanvasRenderBackend {
     viewport_height: u32,
     rect: Path2d,
     mask_state: MaskState,
+    /// Whether the mask that's being drawn shows its content outside of the mask, instead of inside.
+    invert_mask: bool,
     blend_modes: Vec<BlendMode>,
 
     // This is currnetly unused - we just store it to report
@@ -96,6 +101,7 @@ enum CanvasDrawCommand {
     Fill {
         path: Path2d,
         fill_style: CanvasFillStyle,
+        winding_rule: CanvasWindingRule,
     },
 }
 
@@ -296,6 +302,7 @@ impl WebCanvasRenderBackend {
             viewport_scale_factor: 1.0,
             rect,
             mask_state: MaskState::DrawContent,
+            invert_mask: false,
             blend_modes: vec![BlendMode::Normal],
         };
         Ok(renderer)
@@ -439,7 +446,7 @@ impl RenderBackend for WebCanvasRenderBackend {
         shape: DistilledShape,
         bitmap_source: &dyn BitmapSource,
     ) -> ShapeHandle {
-        let handle = ShapeHandle(self.shapes.len());
+        let handle = ShapeHandle::from_index(self.shapes.len());
         let data = swf_shape_to_canvas_commands(&shape, bitmap_source, self);
         self.shapes.push(data);
         handle
@@ -452,7 +459,7 @@ impl RenderBackend for WebCanvasRenderBackend {
         handle: ShapeHandle,
     ) {
         let data = swf_shape_to_canvas_commands(&shape, bitmap_source, self);
-        self.shapes[handle.0] = data;
+        self.shapes[handle.index()] = data;
     }
 
     fn register_glyph_shape(&mut self, glyph: &swf::Glyph) -> ShapeHandle {
@@ -476,6 +483,32 @@ impl RenderBackend for WebCanvasRenderBackend {
         commands.execute(self);
     }
 
+    fn capture_frame(&mut self, clear: Color, commands: CommandList) -> Option<Bitmap> {
+        self.submit_frame(clear, commands);
+
+        let (width, height) = (self.canvas.width(), self.canvas.height());
+        let image_data = match self
+            .context
+            .get_image_data(0.0, 0.0, width.into(), height.into())
+        {
+            Ok(image_data) => image_data,
+            Err(e) => {
+                log::warn!("Couldn't capture frame: {:?}", e);
+                return None;
+            }
+        };
+
+        // The canvas gives straight alpha, but bitmaps are premultiplied
+        let mut rgba = image_data.data().0;
+        for pixel in rgba.chunks_exact_mut(4) {
+            let alpha = u16::from(pixel[3]);
+            for channel in &mut pixel[..3] {
+                *channel = (u16::from(*channel) * alpha / 255) as u8;
+            }
+        }
+        Some(Bitmap::new(width, height, BitmapFormat::Rgba, rgba))
+    }
+
     fn register_bitmap(&mut self, bitmap: Bitmap) -> Result<BitmapHandle, Error> {
         let bitmap_data = BitmapData::new(bitmap).map_err(Error::JavascriptError)?;
         Ok(BitmapHandle(Arc::new(bitmap_data)))
@@ -530,16 +563,54 @@ impl CommandHandler for WebCanvasRenderBackend {
         self.clear_color_filter();
     }
 
+    fn render_bitmap_region(
+        &mut self,
+        bitmap: BitmapHandle,
+        region: BitmapRegion,
+        transform: Transform,
+        smoothing: bool,
+    ) {
+        if self.mask_state == MaskState::ClearMask {
+            return;
+        }
+
+        self.context.set_image_smoothing_enabled(smoothing);
+
+        self.set_transform(&transform.matrix);
+        self.set_color_filter(&transform);
+        let bitmap = as_bitmap_data(&bitmap);
+        let (x, y) = (f64::from(region.x), f64::from(region.y));
+        let (width, height) = (f64::from(region.width), f64::from(region.height));
+        let _ = self
+            .context
+            .draw_image_with_html_canvas_element_and_sw_and_sh_and_dx_and_dy_and_dw_and_dh(
+                &bitmap.canvas,
+                x,
+                y,
+                width,
+                height,
+                0.0,
+                0.0,
+                width,
+                height,
+            );
+        self.clear_color_filter();
+    }
+
     fn render_shape(&mut self, shape: ShapeHandle, transform: Transform) {
         match &self.mask_state {
             MaskState::DrawContent => {
                 let mut line_scale = LineScales::new(&transform.matrix);
                 let dom_matrix = transform.matrix.to_dom_matrix();
                 let mut transform_dirty = true;
-                if let Some(shape) = self.shapes.get(shape.0) {
+                if let Some(shape) = self.shapes.get(shape.index()) {
                     for command in shape.0.iter() {
                         match command {
-                            CanvasDrawCommand::Fill { path, fill_style } => {
+                            CanvasDrawCommand::Fill {
+                                path,
+                                fill_style,
+                                winding_rule,
+                            } => {
                                 if transform_dirty {
                                     let _ = self.context.set_transform(
                                         transform.matrix.a.into(),
@@ -556,10 +627,8 @@ impl CommandHandler for WebCanvasRenderBackend {
                                         let color =
                                             color.color_transform(&transform.color_transform);
                                         self.context.set_fill_style(&color.0.into());
-                                        self.context.fill_with_path_2d_and_winding(
-                                            path,
-                                            CanvasWindingRule::Evenodd,
-                                        );
+                                        self.context
+                                            .fill_with_path_2d_and_winding(path, *winding_rule);
                                     }
                                     CanvasFillStyle::Gradient(gradient) => {
                                         self.set_color_filter(&transform);
@@ -583,13 +652,11 @@ impl CommandHandler for WebCanvasRenderBackend {
                                             );
                                             self.context.fill_with_path_2d_and_winding(
                                                 &untransformed_path,
-                                                CanvasWindingRule::Evenodd,
+                                                *winding_rule,
                                             );
                                         } else {
-                                            self.context.fill_with_path_2d_and_winding(
-                                                path,
-                                                CanvasWindingRule::Evenodd,
-                                            );
+                                            self.context
+                                                .fill_with_path_2d_and_winding(path, *winding_rule);
                                         }
 
                                         self.clear_color_filter();
@@ -598,10 +665,8 @@ impl CommandHandler for WebCanvasRenderBackend {
                                         self.set_color_filter(&transform);
                                         self.context.set_image_smoothing_enabled(bitmap.smoothed);
                                         self.context.set_fill_style(&bitmap.pattern);
-                                        self.context.fill_with_path_2d_and_winding(
-                                            path,
-                                            CanvasWindingRule::Evenodd,
-                                        );
+                                        self.context
+                                            .fill_with_path_2d_and_winding(path, *winding_rule);
                                         self.clear_color_filter();
                                     }
                                 }
@@ -696,7 +761,7 @@ impl CommandHandler for WebCanvasRenderBackend {
             // Add the shape path to the mask path.
             // Strokes are ignored.
             MaskState::DrawMask(mask_path) => {
-                if let Some(shape) = self.shapes.get(shape.0) {
+                if let Some(shape) = self.shapes.get(shape.index()) {
                     for command in shape.0.iter() {
                         if let CanvasDrawCommand::Fill { path, .. } = command {
                             mask_path.add_path_with_transformation(
@@ -746,6 +811,23 @@ impl CommandHandler for WebCanvasRenderBackend {
             self.context.save();
             self.mask_state =
                 MaskState::DrawMask(Path2d::new().expect("Path2d constructor must succeed"));
+            self.invert_mask = false;
+        }
+    }
+
+    fn push_inverted_mask(&mut self) {
+        if self.mask_state == MaskState::DrawContent {
+            self.context.save();
+            // The mask's shapes are cut out of a rectangle over the whole canvas when it's activated.
+            let mask_path = Path2d::new().expect("Path2d constructor must succeed");
+            mask_path.rect(
+                0.0,
+                0.0,
+                self.viewport_width.into(),
+                self.viewport_height.into(),
+            );
+            self.mask_state = MaskState::DrawMask(mask_path);
+            self.invert_mask = true;
         }
     }
 
@@ -762,8 +844,15 @@ impl CommandHandler for WebCanvasRenderBackend {
             // Most likely it would happen with dynamiuc masks via drawing API or similar.
             // A possible improvement is to choose the winding rule based on whether the shape has
             // layers or not (via a flag in DistilledShape?)
+            // Inverted masks use even-odd instead, so that the mask is cut out of the rectangle
+            // around it. This shares the same problem with overlapping shapes.
+            let winding = if self.invert_mask {
+                CanvasWindingRule::Evenodd
+            } else {
+                CanvasWindingRule::Nonzero
+            };
             self.context
-                .clip_with_path_2d_and_winding(mask_path, CanvasWindingRule::Nonzero);
+                .clip_with_path_2d_and_winding(mask_path, winding);
             self.mask_state = MaskState::DrawContent;
         }
     }
@@ -782,11 +871,34 @@ impl CommandHandler for WebCanvasRenderBackend {
         }
     }
 
-    fn blend(&mut self, commands: CommandList, blend: BlendMode) {
+    fn blend(
+        &mut self,
+        mut commands: CommandList,
+        blend: BlendMode,
+        color_transform: ColorTransform,
+    ) {
+        // Groups aren't composited separately here, so apply the color transform to each child.
+        commands.apply_color_transform(color_transform);
         self.push_blend_mode(blend);
         commands.execute(self);
         self.pop_blend_mode();
     }
+
+    fn filters(&mut self, commands: CommandList, _filters: Vec<Filter>) {
+        // Filters aren't supported here, so just draw the unfiltered contents.
+        commands.execute(self);
+    }
+
+    fn alpha_mask(&mut self, maskee: CommandList, mask: CommandList) {
+        // Alpha masks aren't supported here, so fall back to masking by shape.
+        self.push_mask();
+        mask.clone().execute(self);
+        self.activate_mask();
+        maskee.execute(self);
+        self.deactivate_mask();
+        mask.execute(self);
+        self.pop_mask();
+    }
 }
 
 /// Convert a series of `DrawCommands` to a `Path2d` shape.
@@ -838,7 +950,9 @@ fn swf_shape_to_canvas_commands(
     for path in &shape.paths {
         match path {
             DrawPath::Fill {
-                commands, style, ..
+                commands,
+                style,
+                winding_rule,
             } => {
                 let canvas_path = Path2d::new().expect("Path2d constructor must succeed");
                 canvas_path.add_path_with_transformation(
@@ -846,53 +960,86 @@ fn swf_shape_to_canvas_commands(
                     bounds_viewbox_matrix.unchecked_ref(),
                 );
 
-                let fill_style = match style {
-                    FillStyle::Color(color) => CanvasFillStyle::Color(color.into()),
-                    FillStyle::LinearGradient(gradient) => CanvasFillStyle::Gradient(
-                        create_linear_gradient(&backend.context, gradient, true)
-                            .expect("Couldn't create linear gradient"),
-                    ),
-                    FillStyle::RadialGradient(gradient) => CanvasFillStyle::Gradient(
-                        create_radial_gradient(&backend.context, gradient, 0.0, true)
-                            .expect("Couldn't create radial gradient"),
-                    ),
-                    FillStyle::FocalGradient {
-                        gradient,
-                        focal_point,
-                    } => CanvasFillStyle::Gradient(
-                        create_radial_gradient(
-                            &backend.context,
-                            gradient,
-                            focal_point.to_f64(),
-                            true,
-                        )
-                        .expect("Couldn't create radial gradient"),
-                    ),
+                let Some(fill_style) = create_fill_style(style, bitmap_source, backend) else {
+                    continue;
+                };
+                canvas_data.0.push(CanvasDrawCommand::Fill {
+                    path: canvas_path,
+                    fill_style,
+                    winding_rule: match winding_rule {
+                        FillRule::EvenOdd => CanvasWindingRule::Evenodd,
+                        FillRule::NonZero => CanvasWindingRule::Nonzero,
+                    },
+                });
+            }
+            DrawPath::Triangles { style, triangles } => {
+                let triangle_path = |corners: [(Twips, Twips); 3]| {
+                    let path = Path2d::new().expect("Path2d constructor must succeed");
+                    path.move_to(corners[0].0.get().into(), corners[0].1.get().into());
+                    path.line_to(corners[1].0.get().into(), corners[1].1.get().into());
+                    path.line_to(corners[2].0.get().into(), corners[2].1.get().into());
+                    path.close_path();
+                    let canvas_path = Path2d::new().expect("Path2d constructor must succeed");
+                    canvas_path
+                        .add_path_with_transformation(&path, bounds_viewbox_matrix.unchecked_ref());
+                    canvas_path
+                };
+
+                if let (
                     FillStyle::Bitmap {
                         id,
-                        matrix,
                         is_smoothed,
                         is_repeating,
-                    } => {
-                        let bitmap = if let Some(bitmap) = create_bitmap_pattern(
+                        ..
+                    },
+                    Some(_),
+                ) = (style, &triangles.uvs)
+                {
+                    // Each triangle places the bitmap differently, so each needs its own pattern
+                    let Some(bitmap) = bitmap_source.bitmap_size(*id) else {
+                        continue;
+                    };
+                    for (corners, uvs) in triangles.triangles() {
+                        let Some(matrix) = uvs.and_then(|uvs| {
+                            triangle_bitmap_matrix(
+                                corners,
+                                uvs,
+                                bitmap.width.into(),
+                                bitmap.height.into(),
+                            )
+                        }) else {
+                            continue;
+                        };
+                        let Some(pattern) = create_bitmap_pattern(
                             *id,
-                            *matrix,
+                            matrix.into(),
                             *is_smoothed,
                             *is_repeating,
                             bitmap_source,
                             backend,
-                        ) {
-                            bitmap
-                        } else {
+                        ) else {
                             continue;
                         };
-                        CanvasFillStyle::Bitmap(bitmap)
+                        canvas_data.0.push(CanvasDrawCommand::Fill {
+                            path: triangle_path(corners),
+                            fill_style: CanvasFillStyle::Bitmap(pattern),
+                            winding_rule: CanvasWindingRule::Nonzero,
+                        });
                     }
-                };
+                    continue;
+                }
 
+                let Some(fill_style) = create_fill_style(style, bitmap_source, backend) else {
+                    continue;
+                };
+                let canvas_path = Path2d::new().expect("Path2d constructor must succeed");
+                for (corners, _) in triangles.triangles() {
+                    canvas_path.add_path(&triangle_path(corners));
+                }
                 canvas_data.0.push(CanvasDrawCommand::Fill {
                     path: canvas_path,
                     fill_style,
+                    winding_rule: CanvasWindingRule::Nonzero,
                 });
             }
             DrawPath::Stroke {
@@ -957,12 +1104,7 @@ fn swf_shape_to_canvas_commands(
                     line_cap: line_cap.to_string(),
                     line_join: line_join.to_string(),
                     miter_limit: miter_limit as f64 / 20.0,
-                    scale_mode: match (style.allow_scale_x(), style.allow_scale_y()) {
-                        (false, false) => LineScaleMode::None,
-                        (true, false) => LineScaleMode::Horizontal,
-                        (false, true) => LineScaleMode::Vertical,
-                        (true, true) => LineScaleMode::Both,
-                    },
+                    scale_mode: style.into(),
                 });
             }
         }
@@ -1076,18 +1218,35 @@ fn swf_to_canvas_gradient(
     // anything better using the current Canvas API. Maybe we could consider the size of the
     // shape here to make sure we fill the area.
     let canvas_gradient = create_gradient_fn(matrix, gradient_scale)?;
-    let color_stops: Vec<_> = swf_gradient
+    let mut color_stops: Vec<_> = swf_gradient
         .records
         .iter()
         .map(|record| {
             (
                 f32::from(record.ratio) / 255.0,
+                [
+                    f32::from(record.color.r) / 255.0,
+                    f32::from(record.color.g) / 255.0,
+                    f32::from(record.color.b) / 255.0,
+                    f32::from(record.color.a) / 255.0,
+                ],
+            )
+        })
+        .collect();
+    if swf_gradient.interpolation == swf::GradientInterpolation::LinearRgb {
+        color_stops = linear_rgb_color_stops(&color_stops);
+    }
+    let color_stops: Vec<_> = color_stops
+        .into_iter()
+        .map(|(ratio, [r, g, b, a])| {
+            (
+                ratio,
                 format!(
                     "rgba({},{},{},{})",
-                    record.color.r,
-                    record.color.g,
-                    record.color.b,
-                    f32::from(record.color.a) / 255.0
+                    (r * 255.0).round(),
+                    (g * 255.0).round(),
+                    (b * 255.0).round(),
+                    a
                 ),
             )
         })
@@ -1167,6 +1326,90 @@ fn swf_to_canvas_gradient(
 }
 
 /// Converts an SWF bitmap fill to a canvas pattern.
+/// Canvas gradients are always interpolated in sRGB. A linear RGB gradient is approximated by
+/// adding stops in between its own, with colors that were interpolated in linear space instead.
+fn linear_rgb_color_stops(color_stops: &[(f32, [f32; 4])]) -> Vec<(f32, [f32; 4])> {
+    const STEPS: usize = 8;
+
+    fn srgb_to_linear(n: f32) -> f32 {
+        if n <= 0.04045 {
+            n / 12.92
+        } else {
+            f32::powf((n + 0.055) / 1.055, 2.4)
+        }
+    }
+
+    fn linear_to_srgb(n: f32) -> f32 {
+        if n <= 0.0031308 {
+            n * 12.92
+        } else {
+            1.055 * f32::powf(n, 1.0 / 2.4) - 0.055
+        }
+    }
+
+    let mut stops = Vec::with_capacity(color_stops.len() * STEPS);
+    for pair in color_stops.windows(2) {
+        let (start_ratio, start) = pair[0];
+        let (end_ratio, end) = pair[1];
+        for step in 0..STEPS {
+            let t = step as f32 / STEPS as f32;
+            let mut color = [0.0; 4];
+            for ((color, start), end) in color.iter_mut().zip(start).zip(end).take(3) {
+                let start = srgb_to_linear(start);
+                let end = srgb_to_linear(end);
+                *color = linear_to_srgb(start + (end - start) * t);
+            }
+            // Alpha is interpolated the same way in both modes
+            color[3] = start[3] + (end[3] - start[3]) * t;
+            stops.push((start_ratio + (end_ratio - start_ratio) * t, color));
+        }
+    }
+    stops.extend(color_stops.last().copied());
+    stops
+}
+
+/// Converts the style of a fill, or returns `None` if its bitmap is missing.
+fn create_fill_style(
+    style: &swf::FillStyle,
+    bitmap_source: &dyn BitmapSource,
+    backend: &mut WebCanvasRenderBackend,
+) -> Option<CanvasFillStyle> {
+    use swf::FillStyle;
+
+    let fill_style = match style {
+        FillStyle::Color(color) => CanvasFillStyle::Color(color.into()),
+        FillStyle::LinearGradient(gradient) => CanvasFillStyle::Gradient(
+            create_linear_gradient(&backend.context, gradient, true)
+                .expect("Couldn't create linear gradient"),
+        ),
+        FillStyle::RadialGradient(gradient) => CanvasFillStyle::Gradient(
+            create_radial_gradient(&backend.context, gradient, 0.0, true)
+                .expect("Couldn't create radial gradient"),
+        ),
+        FillStyle::FocalGradient {
+            gradient,
+            focal_point,
+        } => CanvasFillStyle::Gradient(
+            create_radial_gradient(&backend.context, gradient, focal_point.to_f64(), true)
+                .expect("Couldn't create radial gradient"),
+        ),
+        FillStyle::Bitmap {
+            id,
+            matrix,
+            is_smoothed,
+            is_repeating,
+        } => CanvasFillStyle::Bitmap(create_bitmap_pattern(
+            *id,
+            *matrix,
+            *is_smoothed,
+            *is_repeating,
+            bitmap_source,
+            backend,
+        )?),
+    };
+    Some(fill_style)
+}
+
 fn create_bitmap_pattern(
     id: swf::CharacterId,
     matrix: swf::Matrix,
@@ -1,4 +1,5 @@
 pub mod null;
+pub mod switchable;
 
 use crate::bitmap::{Bitmap, BitmapHandle, BitmapSource, SyncHandle};
 use crate::commands::CommandList;
@@ -10,7 +11,9 @@ use downcast_rs::{impl_downcast, Downcast};
 use gc_arena::{Collect, GcCell, MutationContext};
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
+use std::fmt::Debug;
 use std::rc::Rc;
+use std::sync::Arc;
 use swf;
 
 pub trait RenderBackend: Downcast {
@@ -31,14 +34,25 @@ pub trait RenderBackend: Downcast {
     );
     fn register_glyph_shape(&mut self, shape: &swf::Glyph) -> ShapeHandle;
 
-    /// Creates a new `RenderBackend` which renders directly
-    /// to the texture specified by `BitmapHandle` with the given
-    /// `width` and `height`. This backend is passed to the callback
-    /// `f`, which performs the desired draw operations.
+    /// Registers a morph shape, which is drawn at any ratio between `start` and `end`
+    /// without being tessellated again. Both shapes must come from the same DefineMorphShape tag.
     ///
-    /// After the callback `f` exectures, the texture data is copied
-    /// from the GPU texture to an `RgbaImage`. There is no need to call
-    /// `update_texture` with the pixels from this image, as they
+    /// Returns `None` if the backend can't interpolate between them itself, such as when their
+    /// edges don't match up. The caller then registers a separate shape for each ratio instead.
+    fn register_morph_shape(
+        &mut self,
+        _start: DistilledShape,
+        _end: DistilledShape,
+    ) -> Option<ShapeHandle> {
+        None
+    }
+
+    /// Renders `commands` directly into the texture specified by `BitmapHandle`,
+    /// with the given `width` and `height`, on top of what it already contains.
+    /// Masks and blend modes inside of `commands` apply as they would on the stage.
+    ///
+    /// The returned handle copies the texture data from the GPU into a `Bitmap`.
+    /// There is no need to call `update_texture` with the pixels from it, as they
     /// reflect data that is already stored on the GPU texture.
     fn render_offscreen(
         &mut self,
@@ -67,8 +81,23 @@ pub trait RenderBackend: Downcast {
         None
     }
 
+    /// Whether `apply_filter` can apply the given filter.
+    ///
+    /// Filters that a backend can't apply have to be applied to the bitmap's pixels on the CPU
+    /// instead, if that's possible at all.
+    fn is_filter_supported(&self, _filter: &Filter) -> bool {
+        false
+    }
+
     fn submit_frame(&mut self, clear: swf::Color, commands: CommandList);
 
+    /// Renders a frame like `submit_frame`, and returns its pixels for taking a screenshot.
+    /// The frame may or may not be presented as well, so the caller should render again if that
+    /// matters.
+    ///
+    /// Returns `None` if the backend can't read back what it rendered.
+    fn capture_frame(&mut self, clear: swf::Color, commands: CommandList) -> Option<Bitmap>;
+
     fn register_bitmap(&mut self, bitmap: Bitmap) -> Result<BitmapHandle, Error>;
     fn update_texture(
         &mut self,
@@ -89,6 +118,14 @@ pub trait RenderBackend: Downcast {
     fn debug_info(&self) -> Cow<'static, str>;
 
     fn set_quality(&mut self, quality: StageQuality);
+
+    /// Whether the given blend mode is drawn with the GPU's own blending.
+    ///
+    /// Blend modes that aren't have to read back the destination in an extra pass,
+    /// which is considerably slower when used heavily.
+    fn is_blend_mode_accelerated(&self, _blend_mode: swf::BlendMode) -> bool {
+        true
+    }
 }
 impl_downcast!(RenderBackend);
 
@@ -226,8 +263,34 @@ pub enum Context3DCommand<'gc> {
     },
 }
 
-#[derive(Copy, Clone, Debug)]
-pub struct ShapeHandle(pub usize);
+/// A shape registered with a backend, which the backend may free once every clone of the handle
+/// has been dropped.
+#[derive(Clone, Debug)]
+pub struct ShapeHandle(pub Arc<dyn ShapeHandleImpl>);
+
+pub trait ShapeHandleImpl: Downcast + Debug {}
+impl_downcast!(ShapeHandleImpl);
+
+/// The handle of a backend that keeps its shapes in a list of its own.
+#[derive(Debug)]
+struct ShapeIndex(usize);
+
+impl ShapeHandleImpl for ShapeIndex {}
+
+impl ShapeHandle {
+    /// A handle for the shape at `index` in the list of shapes of a backend.
+    pub fn from_index(index: usize) -> Self {
+        Self(Arc::new(ShapeIndex(index)))
+    }
+
+    /// The index of a handle that was created with [`ShapeHandle::from_index`].
+    pub fn index(&self) -> usize {
+        self.0
+            .downcast_ref::<ShapeIndex>()
+            .expect("Shape handle must belong to this backend")
+            .0
+    }
+}
 
 #[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
@@ -48,7 +48,7 @@ impl RenderBackend for NullRenderer {
         _shape: DistilledShape,
         _bitmap_source: &dyn BitmapSource,
     ) -> ShapeHandle {
-        ShapeHandle(0)
+        ShapeHandle::from_index(0)
     }
     fn replace_shape(
         &mut self,
@@ -58,7 +58,7 @@ impl RenderBackend for NullRenderer {
     ) {
     }
     fn register_glyph_shape(&mut self, _shape: &swf::Glyph) -> ShapeHandle {
-        ShapeHandle(0)
+        ShapeHandle::from_index(0)
     }
 
     fn render_offscreen(
@@ -73,6 +73,9 @@ impl RenderBackend for NullRenderer {
     }
 
     fn submit_frame(&mut self, _clear: Color, _commands: CommandList) {}
+    fn capture_frame(&mut self, _clear: Color, _commands: CommandList) -> Option<Bitmap> {
+        None
+    }
     fn register_bitmap(&mut self, _bitmap: Bitmap) -> Result<BitmapHandle, Error> {
         Ok(BitmapHandle(Arc::new(NullBitmapHandle)))
     }
@@ -0,0 +1,912 @@
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::fmt;
+use std::sync::{Arc, Weak};
+
+use crate::backend::{
+    Context3D, Context3DCommand, RenderBackend, ShapeHandle, ShapeHandleImpl, ViewportDimensions,
+};
+use crate::bitmap::{
+    Bitmap, BitmapFormat, BitmapHandle, BitmapHandleImpl, BitmapSize, BitmapSource, SyncHandle,
+};
+use crate::bounding_box::BoundingBox;
+use crate::commands::{Command, CommandList};
+use crate::error::Error;
+use crate::filters::Filter;
+use crate::matrix::Matrix;
+use crate::quality::StageQuality;
+use crate::shape_utils::{DistilledShape, DrawCommand, DrawPath, FillRule, TriangleList};
+use gc_arena::MutationContext;
+use swf::{CharacterId, Color, FillStyle, LineStyle};
+
+/// A render backend that can be swapped out for another one while the player is running,
+/// such as when the GPU behind it goes away.
+///
+/// Everything registered through it is kept, so that it can be registered again with the new
+/// backend. The handles it gives out stay the same across a switch, and are translated into
+/// those of the current backend whenever they're drawn. This costs a copy of the pixels of
+/// every bitmap, and of the paths of every shape, for as long as their handles are alive.
+/// Only wrap a backend in this if there is something to switch to.
+///
+/// Pixels that the current backend draws into a bitmap are read back from it before a switch,
+/// unless they already have been. If it can't read them back anymore, such as once its context
+/// is lost, the bitmap goes back to the last pixels that were uploaded to it or read back.
+///
+/// Morph shapes aren't registered as a whole, as the next backend may not be able to draw them.
+/// A shape is registered for each of their ratios instead.
+pub struct SwitchableRenderBackend {
+    inner: Box<dyn RenderBackend>,
+    quality: Option<StageQuality>,
+    shapes: Vec<Weak<SwitchableShape>>,
+    bitmaps: Vec<Weak<SwitchableBitmap>>,
+}
+
+/// A bitmap along with a copy of its pixels, which are uploaded again after a switch.
+#[derive(Debug)]
+struct SwitchableBitmap {
+    inner: RefCell<BitmapHandle>,
+    pixels: RefCell<Bitmap>,
+    /// Retrieves what was last drawn into the bitmap, if it hasn't been retrieved yet.
+    pending: RefCell<Option<Box<dyn SyncHandle>>>,
+}
+
+impl BitmapHandleImpl for SwitchableBitmap {}
+
+fn as_switchable_bitmap(handle: &BitmapHandle) -> Option<&SwitchableBitmap> {
+    <dyn BitmapHandleImpl>::downcast_ref::<SwitchableBitmap>(&*handle.0)
+}
+
+/// Returns the handle of the current backend for a bitmap, or the handle itself if it wasn't
+/// registered through a `SwitchableRenderBackend`, such as the texture of a `Context3D`.
+fn inner_bitmap(handle: &BitmapHandle) -> BitmapHandle {
+    match as_switchable_bitmap(handle) {
+        Some(bitmap) => bitmap.inner.borrow().clone(),
+        None => handle.clone(),
+    }
+}
+
+fn inner_filter(mut filter: Filter) -> Filter {
+    if let Filter::DisplacementMapFilter(filter) = &mut filter {
+        filter.map = inner_bitmap(&filter.map);
+    }
+    filter
+}
+
+/// Retrieves the pixels that a `render_offscreen` or `apply_filter` call drew into a bitmap,
+/// which become the copy of the bitmap.
+///
+/// The handle of the current backend is kept by the bitmap itself, so that the pixels can be
+/// retrieved before a switch. If that already happened, the copy is up to date.
+#[derive(Debug)]
+struct SwitchableSyncHandle {
+    bitmap: BitmapHandle,
+}
+
+impl SyncHandle for SwitchableSyncHandle {
+    fn retrieve_offscreen_texture(self: Box<Self>) -> Result<Bitmap, Error> {
+        let bitmap = as_switchable_bitmap(&self.bitmap)
+            .ok_or_else(|| Error::UnknownHandle(self.bitmap.clone()))?;
+        let pending = bitmap.pending.borrow_mut().take();
+        if let Some(pending) = pending {
+            *bitmap.pixels.borrow_mut() = pending.retrieve_offscreen_texture()?;
+        }
+        Ok(bitmap.pixels.borrow().clone())
+    }
+}
+
+/// Keeps the handle that retrieves what was drawn into `bitmap`, and returns one that
+/// retrieves it through the bitmap.
+fn wrap_sync_handle(
+    handle: Option<Box<dyn SyncHandle>>,
+    bitmap: &BitmapHandle,
+) -> Option<Box<dyn SyncHandle>> {
+    let handle = handle?;
+    match as_switchable_bitmap(bitmap) {
+        Some(switchable) => {
+            *switchable.pending.borrow_mut() = Some(handle);
+            Some(Box::new(SwitchableSyncHandle {
+                bitmap: bitmap.clone(),
+            }))
+        }
+        None => Some(handle),
+    }
+}
+
+/// A shape along with what it was registered from, which is registered again after a switch.
+struct SwitchableShape {
+    inner: RefCell<ShapeHandle>,
+    source: RefCell<ShapeSource>,
+}
+
+impl fmt::Debug for SwitchableShape {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SwitchableShape")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ShapeHandleImpl for SwitchableShape {}
+
+fn as_switchable_shape(handle: &ShapeHandle) -> Option<&SwitchableShape> {
+    <dyn ShapeHandleImpl>::downcast_ref::<SwitchableShape>(&*handle.0)
+}
+
+/// Returns the handle of the current backend for a shape, or the handle itself if it wasn't
+/// registered through a `SwitchableRenderBackend`.
+fn inner_shape(handle: &ShapeHandle) -> ShapeHandle {
+    match as_switchable_shape(handle) {
+        Some(shape) => shape.inner.borrow().clone(),
+        None => handle.clone(),
+    }
+}
+
+enum ShapeSource {
+    Shape(RecordedShape),
+    Glyph(swf::Glyph),
+}
+
+impl ShapeSource {
+    fn register(&self, backend: &mut dyn RenderBackend) -> ShapeHandle {
+        match self {
+            ShapeSource::Shape(shape) => backend.register_shape(shape.distilled(), &shape.bitmaps),
+            ShapeSource::Glyph(glyph) => backend.register_glyph_shape(glyph),
+        }
+    }
+}
+
+/// An owned copy of a `DistilledShape`.
+struct RecordedShape {
+    paths: Vec<RecordedPath>,
+    shape_bounds: BoundingBox,
+    edge_bounds: BoundingBox,
+    id: CharacterId,
+    stroke_matrix: Option<Matrix>,
+    bitmaps: ResolvedBitmaps,
+}
+
+impl RecordedShape {
+    fn new(
+        shape: DistilledShape,
+        bitmap_source: &dyn BitmapSource,
+        renderer: &mut dyn RenderBackend,
+    ) -> Self {
+        let paths: Vec<_> = shape.paths.into_iter().map(RecordedPath::new).collect();
+        let bitmaps = ResolvedBitmaps::resolve(&paths, bitmap_source, renderer);
+        Self {
+            paths,
+            shape_bounds: shape.shape_bounds,
+            edge_bounds: shape.edge_bounds,
+            id: shape.id,
+            stroke_matrix: shape.stroke_matrix,
+            bitmaps,
+        }
+    }
+
+    fn distilled(&self) -> DistilledShape {
+        DistilledShape {
+            paths: self.paths.iter().map(RecordedPath::draw_path).collect(),
+            shape_bounds: self.shape_bounds.clone(),
+            edge_bounds: self.edge_bounds.clone(),
+            id: self.id,
+            stroke_matrix: self.stroke_matrix,
+        }
+    }
+}
+
+/// An owned copy of a `DrawPath`.
+enum RecordedPath {
+    Stroke {
+        style: LineStyle,
+        is_closed: bool,
+        commands: Vec<DrawCommand>,
+    },
+    Fill {
+        style: FillStyle,
+        commands: Vec<DrawCommand>,
+        winding_rule: FillRule,
+    },
+    Triangles {
+        style: FillStyle,
+        triangles: TriangleList,
+    },
+}
+
+impl RecordedPath {
+    fn new(path: DrawPath) -> Self {
+        match path {
+            DrawPath::Stroke {
+                style,
+                is_closed,
+                commands,
+            } => RecordedPath::Stroke {
+                style: style.clone(),
+                is_closed,
+                commands,
+            },
+            DrawPath::Fill {
+                style,
+                commands,
+                winding_rule,
+            } => RecordedPath::Fill {
+                style: style.clone(),
+                commands,
+                winding_rule,
+            },
+            DrawPath::Triangles { style, triangles } => RecordedPath::Triangles {
+                style: style.clone(),
+                triangles: triangles.clone(),
+            },
+        }
+    }
+
+    fn fill_style(&self) -> &FillStyle {
+        match self {
+            RecordedPath::Stroke { style, .. } => style.fill_style(),
+            RecordedPath::Fill { style, .. } | RecordedPath::Triangles { style, .. } => style,
+        }
+    }
+
+    fn draw_path(&self) -> DrawPath {
+        match self {
+            RecordedPath::Stroke {
+                style,
+                is_closed,
+                commands,
+            } => DrawPath::Stroke {
+                style,
+                is_closed: *is_closed,
+                commands: commands.clone(),
+            },
+            RecordedPath::Fill {
+                style,
+                commands,
+                winding_rule,
+            } => DrawPath::Fill {
+                style,
+                commands: commands.clone(),
+                winding_rule: *winding_rule,
+            },
+            RecordedPath::Triangles { style, triangles } => {
+                DrawPath::Triangles { style, triangles }
+            }
+        }
+    }
+}
+
+/// The bitmaps used by the fills of a shape, looked up once when it's registered.
+/// These hand out the handles of whichever backend is current.
+#[derive(Default)]
+struct ResolvedBitmaps(Vec<(u16, BitmapSize, BitmapHandle)>);
+
+impl ResolvedBitmaps {
+    fn resolve(
+        paths: &[RecordedPath],
+        bitmap_source: &dyn BitmapSource,
+        renderer: &mut dyn RenderBackend,
+    ) -> Self {
+        let mut bitmaps = Self::default();
+        for path in paths {
+            let FillStyle::Bitmap { id, .. } = path.fill_style() else {
+                continue;
+            };
+            if bitmaps.0.iter().any(|(other, _, _)| other == id) {
+                continue;
+            }
+            if let (Some(size), Some(handle)) = (
+                bitmap_source.bitmap_size(*id),
+                bitmap_source.bitmap_handle(*id, renderer),
+            ) {
+                bitmaps.0.push((*id, size, handle));
+            }
+        }
+        bitmaps
+    }
+
+    fn get(&self, id: u16) -> Option<&(u16, BitmapSize, BitmapHandle)> {
+        self.0.iter().find(|(other, _, _)| *other == id)
+    }
+}
+
+impl BitmapSource for ResolvedBitmaps {
+    fn bitmap_size(&self, id: u16) -> Option<BitmapSize> {
+        self.get(id).map(|(_, size, _)| *size)
+    }
+
+    fn bitmap_handle(&self, id: u16, _renderer: &mut dyn RenderBackend) -> Option<BitmapHandle> {
+        self.get(id).map(|(_, _, handle)| inner_bitmap(handle))
+    }
+}
+
+impl SwitchableRenderBackend {
+    pub fn new(inner: Box<dyn RenderBackend>) -> Self {
+        Self {
+            inner,
+            quality: None,
+            shapes: Vec::new(),
+            bitmaps: Vec::new(),
+        }
+    }
+
+    /// The backend that everything is currently drawn with.
+    pub fn inner(&self) -> &dyn RenderBackend {
+        self.inner.as_ref()
+    }
+
+    /// Replaces the current backend with `backend`, after registering every shape and bitmap
+    /// that's still in use with it.
+    ///
+    /// Pixels that were drawn into bitmaps and not retrieved yet are read back from the current
+    /// backend first. If any bitmap fails to register, the current backend is kept.
+    pub fn switch_to(&mut self, mut backend: Box<dyn RenderBackend>) -> Result<(), Error> {
+        backend.set_viewport_dimensions(self.inner.viewport_dimensions());
+        if let Some(quality) = self.quality {
+            backend.set_quality(quality);
+        }
+
+        self.bitmaps.retain(|bitmap| bitmap.strong_count() > 0);
+        let mut replaced = Vec::with_capacity(self.bitmaps.len());
+        for bitmap in self.bitmaps.iter().filter_map(Weak::upgrade) {
+            let pending = bitmap.pending.borrow_mut().take();
+            if let Some(pending) = pending {
+                match pending.retrieve_offscreen_texture() {
+                    Ok(pixels) => *bitmap.pixels.borrow_mut() = pixels,
+                    Err(e) => tracing::warn!("Couldn't read back the pixels of a bitmap: {e}"),
+                }
+            }
+            let pixels = bitmap.pixels.borrow().clone();
+            match backend.register_bitmap(pixels) {
+                Ok(handle) => {
+                    let old = bitmap.inner.replace(handle);
+                    replaced.push((bitmap, old));
+                }
+                Err(e) => {
+                    for (bitmap, old) in replaced {
+                        bitmap.inner.replace(old);
+                    }
+                    return Err(e);
+                }
+            }
+        }
+
+        self.shapes.retain(|shape| shape.strong_count() > 0);
+        for shape in self.shapes.iter().filter_map(Weak::upgrade) {
+            let handle = shape.source.borrow().register(backend.as_mut());
+            shape.inner.replace(handle);
+        }
+        self.inner = backend;
+        Ok(())
+    }
+
+    /// Wraps a shape that was registered with the current backend.
+    fn add_shape(&mut self, inner: ShapeHandle, source: ShapeSource) -> ShapeHandle {
+        let shape = Arc::new(SwitchableShape {
+            inner: RefCell::new(inner),
+            source: RefCell::new(source),
+        });
+        self.shapes.retain(|shape| shape.strong_count() > 0);
+        self.shapes.push(Arc::downgrade(&shape));
+        ShapeHandle(shape)
+    }
+
+    /// Translates the handles in `commands` into those of the current backend.
+    fn inner_commands(&self, commands: CommandList) -> CommandList {
+        let commands = commands
+            .commands
+            .into_iter()
+            .map(|command| match command {
+                Command::RenderBitmap {
+                    bitmap,
+                    transform,
+                    smoothing,
+                } => Command::RenderBitmap {
+                    bitmap: inner_bitmap(&bitmap),
+                    transform,
+                    smoothing,
+                },
+                Command::RenderBitmapRegion {
+                    bitmap,
+                    region,
+                    transform,
+                    smoothing,
+                } => Command::RenderBitmapRegion {
+                    bitmap: inner_bitmap(&bitmap),
+                    region,
+                    transform,
+                    smoothing,
+                },
+                Command::RenderShape { shape, transform } => Command::RenderShape {
+                    shape: inner_shape(&shape),
+                    transform,
+                },
+                Command::RenderMorphShape {
+                    shape,
+                    transform,
+                    ratio,
+                } => Command::RenderMorphShape {
+                    shape: inner_shape(&shape),
+                    transform,
+                    ratio,
+                },
+                Command::Blend(commands, blend_mode, color_transform) => {
+                    Command::Blend(self.inner_commands(commands), blend_mode, color_transform)
+                }
+                Command::Filters(commands, filters) => Command::Filters(
+                    self.inner_commands(commands),
+                    filters.into_iter().map(inner_filter).collect(),
+                ),
+                Command::AlphaMask(maskee, mask) => {
+                    Command::AlphaMask(self.inner_commands(maskee), self.inner_commands(mask))
+                }
+                command @ (Command::DrawRect { .. }
+                | Command::PushMask
+                | Command::PushInvertedMask
+                | Command::ActivateMask
+                | Command::DeactivateMask
+                | Command::PopMask) => command,
+            })
+            .collect();
+        CommandList { commands }
+    }
+}
+
+impl RenderBackend for SwitchableRenderBackend {
+    fn viewport_dimensions(&self) -> ViewportDimensions {
+        self.inner.viewport_dimensions()
+    }
+
+    fn set_viewport_dimensions(&mut self, dimensions: ViewportDimensions) {
+        self.inner.set_viewport_dimensions(dimensions);
+    }
+
+    fn register_shape(
+        &mut self,
+        shape: DistilledShape,
+        bitmap_source: &dyn BitmapSource,
+    ) -> ShapeHandle {
+        let source = ShapeSource::Shape(RecordedShape::new(shape, bitmap_source, self));
+        let inner = source.register(self.inner.as_mut());
+        self.add_shape(inner, source)
+    }
+
+    fn replace_shape(
+        &mut self,
+        shape: DistilledShape,
+        bitmap_source: &dyn BitmapSource,
+        handle: ShapeHandle,
+    ) {
+        let Some(switchable) = as_switchable_shape(&handle) else {
+            self.inner.replace_shape(shape, bitmap_source, handle);
+            return;
+        };
+        let shape = RecordedShape::new(shape, bitmap_source, self);
+        let inner = switchable.inner.borrow().clone();
+        self.inner
+            .replace_shape(shape.distilled(), &shape.bitmaps, inner);
+        *switchable.source.borrow_mut() = ShapeSource::Shape(shape);
+    }
+
+    fn register_glyph_shape(&mut self, shape: &swf::Glyph) -> ShapeHandle {
+        let source = ShapeSource::Glyph(shape.clone());
+        let inner = source.register(self.inner.as_mut());
+        self.add_shape(inner, source)
+    }
+
+    fn render_offscreen(
+        &mut self,
+        handle: BitmapHandle,
+        width: u32,
+        height: u32,
+        commands: CommandList,
+        quality: StageQuality,
+    ) -> Option<Box<dyn SyncHandle>> {
+        let commands = self.inner_commands(commands);
+        let sync =
+            self.inner
+                .render_offscreen(inner_bitmap(&handle), width, height, commands, quality);
+        wrap_sync_handle(sync, &handle)
+    }
+
+    fn apply_filter(
+        &mut self,
+        source: BitmapHandle,
+        source_point: (u32, u32),
+        source_size: (u32, u32),
+        destination: BitmapHandle,
+        dest_point: (u32, u32),
+        filter: Filter,
+    ) -> Option<Box<dyn SyncHandle>> {
+        let sync = self.inner.apply_filter(
+            inner_bitmap(&source),
+            source_point,
+            source_size,
+            inner_bitmap(&destination),
+            dest_point,
+            inner_filter(filter),
+        );
+        wrap_sync_handle(sync, &destination)
+    }
+
+    fn is_filter_supported(&self, filter: &Filter) -> bool {
+        self.inner.is_filter_supported(filter)
+    }
+
+    fn submit_frame(&mut self, clear: Color, commands: CommandList) {
+        let commands = self.inner_commands(commands);
+        self.inner.submit_frame(clear, commands);
+    }
+
+    fn capture_frame(&mut self, clear: Color, commands: CommandList) -> Option<Bitmap> {
+        let commands = self.inner_commands(commands);
+        self.inner.capture_frame(clear, commands)
+    }
+
+    fn register_bitmap(&mut self, bitmap: Bitmap) -> Result<BitmapHandle, Error> {
+        let handle = self.inner.register_bitmap(bitmap.clone())?;
+        let bitmap = Arc::new(SwitchableBitmap {
+            inner: RefCell::new(handle),
+            pixels: RefCell::new(bitmap),
+            pending: RefCell::new(None),
+        });
+        self.bitmaps.retain(|bitmap| bitmap.strong_count() > 0);
+        self.bitmaps.push(Arc::downgrade(&bitmap));
+        Ok(BitmapHandle(bitmap))
+    }
+
+    fn update_texture(
+        &mut self,
+        handle: &BitmapHandle,
+        width: u32,
+        height: u32,
+        rgba: Vec<u8>,
+    ) -> Result<(), Error> {
+        match as_switchable_bitmap(handle) {
+            Some(bitmap) => {
+                self.inner
+                    .update_texture(&bitmap.inner.borrow(), width, height, rgba.clone())?;
+                // Whatever was drawn into the bitmap before has been overwritten
+                bitmap.pending.take();
+                *bitmap.pixels.borrow_mut() = Bitmap::new(width, height, BitmapFormat::Rgba, rgba);
+                Ok(())
+            }
+            None => self.inner.update_texture(handle, width, height, rgba),
+        }
+    }
+
+    fn create_context3d(&mut self) -> Result<Box<dyn Context3D>, Error> {
+        self.inner.create_context3d()
+    }
+
+    fn context3d_present<'gc>(
+        &mut self,
+        context: &mut dyn Context3D,
+        commands: Vec<Context3DCommand<'gc>>,
+        mc: MutationContext<'gc, '_>,
+    ) -> Result<(), Error> {
+        self.inner.context3d_present(context, commands, mc)
+    }
+
+    fn debug_info(&self) -> Cow<'static, str> {
+        self.inner.debug_info()
+    }
+
+    fn set_quality(&mut self, quality: StageQuality) {
+        self.quality = Some(quality);
+        self.inner.set_quality(quality);
+    }
+
+    fn is_blend_mode_accelerated(&self, blend_mode: swf::BlendMode) -> bool {
+        self.inner.is_blend_mode_accelerated(blend_mode)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::CommandHandler;
+    use crate::transform::Transform;
+    use swf::Twips;
+
+    /// A bitmap of a `TestBackend`, numbered by the backend it belongs to and its index there.
+    #[derive(Debug)]
+    struct TestBitmap(u32, usize);
+    impl BitmapHandleImpl for TestBitmap {}
+
+    fn test_bitmap(handle: &BitmapHandle) -> (u32, usize) {
+        let bitmap = <dyn BitmapHandleImpl>::downcast_ref::<TestBitmap>(&*handle.0)
+            .expect("Handle should belong to a test backend");
+        (bitmap.0, bitmap.1)
+    }
+
+    /// What a `TestBackend` drew into a bitmap, which it only hands over when it's retrieved.
+    #[derive(Debug)]
+    struct TestSyncHandle(Bitmap);
+
+    impl SyncHandle for TestSyncHandle {
+        fn retrieve_offscreen_texture(self: Box<Self>) -> Result<Bitmap, Error> {
+            Ok(self.0)
+        }
+    }
+
+    const DRAWN: [u8; 4] = [0, 255, 0, 255];
+
+    /// Records what's registered with it, and the last frame submitted to it.
+    struct TestBackend {
+        id: u32,
+        bitmaps: Vec<Bitmap>,
+        /// The bitmap that each shape's fill was drawn with.
+        shapes: Vec<Option<(u32, usize)>>,
+        frame: CommandList,
+    }
+
+    impl TestBackend {
+        fn new(id: u32) -> Box<Self> {
+            Box::new(Self {
+                id,
+                bitmaps: Vec::new(),
+                shapes: Vec::new(),
+                frame: CommandList::new(),
+            })
+        }
+    }
+
+    impl RenderBackend for TestBackend {
+        fn viewport_dimensions(&self) -> ViewportDimensions {
+            ViewportDimensions {
+                width: 1,
+                height: 1,
+                scale_factor: 1.0,
+            }
+        }
+        fn set_viewport_dimensions(&mut self, _dimensions: ViewportDimensions) {}
+        fn register_shape(
+            &mut self,
+            shape: DistilledShape,
+            bitmap_source: &dyn BitmapSource,
+        ) -> ShapeHandle {
+            let bitmap = shape.paths.iter().find_map(|path| match path {
+                DrawPath::Fill {
+                    style: FillStyle::Bitmap { id, .. },
+                    ..
+                } => bitmap_source.bitmap_handle(*id, self),
+                _ => None,
+            });
+            self.shapes.push(bitmap.as_ref().map(test_bitmap));
+            ShapeHandle::from_index(self.shapes.len() - 1)
+        }
+        fn replace_shape(
+            &mut self,
+            _shape: DistilledShape,
+            _bitmap_source: &dyn BitmapSource,
+            _handle: ShapeHandle,
+        ) {
+        }
+        fn register_glyph_shape(&mut self, _shape: &swf::Glyph) -> ShapeHandle {
+            self.shapes.push(None);
+            ShapeHandle::from_index(self.shapes.len() - 1)
+        }
+        fn render_offscreen(
+            &mut self,
+            _handle: BitmapHandle,
+            width: u32,
+            height: u32,
+            _commands: CommandList,
+            _quality: StageQuality,
+        ) -> Option<Box<dyn SyncHandle>> {
+            let rgba = DRAWN.repeat((width * height) as usize);
+            Some(Box::new(TestSyncHandle(Bitmap::new(
+                width,
+                height,
+                BitmapFormat::Rgba,
+                rgba,
+            ))))
+        }
+        fn submit_frame(&mut self, _clear: Color, commands: CommandList) {
+            self.frame = commands;
+        }
+        fn capture_frame(&mut self, _clear: Color, _commands: CommandList) -> Option<Bitmap> {
+            None
+        }
+        fn register_bitmap(&mut self, bitmap: Bitmap) -> Result<BitmapHandle, Error> {
+            self.bitmaps.push(bitmap);
+            Ok(BitmapHandle(Arc::new(TestBitmap(
+                self.id,
+                self.bitmaps.len() - 1,
+            ))))
+        }
+        fn update_texture(
+            &mut self,
+            handle: &BitmapHandle,
+            width: u32,
+            height: u32,
+            rgba: Vec<u8>,
+        ) -> Result<(), Error> {
+            let (_, index) = test_bitmap(handle);
+            self.bitmaps[index] = Bitmap::new(width, height, BitmapFormat::Rgba, rgba);
+            Ok(())
+        }
+        fn create_context3d(&mut self) -> Result<Box<dyn Context3D>, Error> {
+            Err(Error::Unimplemented)
+        }
+        fn context3d_present<'gc>(
+            &mut self,
+            _context: &mut dyn Context3D,
+            _commands: Vec<Context3DCommand<'gc>>,
+            _mc: MutationContext<'gc, '_>,
+        ) -> Result<(), Error> {
+            Err(Error::Unimplemented)
+        }
+        fn debug_info(&self) -> Cow<'static, str> {
+            Cow::Borrowed("Renderer: Test")
+        }
+        fn set_quality(&mut self, _quality: StageQuality) {}
+    }
+
+    /// Registers bitmap 1 the first time it's asked for, like a movie library does.
+    #[derive(Default)]
+    struct TestLibrary(RefCell<Option<BitmapHandle>>);
+
+    impl BitmapSource for TestLibrary {
+        fn bitmap_size(&self, _id: u16) -> Option<BitmapSize> {
+            Some(BitmapSize {
+                width: 1,
+                height: 1,
+            })
+        }
+        fn bitmap_handle(&self, id: u16, renderer: &mut dyn RenderBackend) -> Option<BitmapHandle> {
+            assert_eq!(id, 1);
+            let mut handle = self.0.borrow_mut();
+            if handle.is_none() {
+                let bitmap = Bitmap::new(1, 1, BitmapFormat::Rgba, vec![255, 0, 0, 255]);
+                *handle = renderer.register_bitmap(bitmap).ok();
+            }
+            handle.clone()
+        }
+    }
+
+    fn bitmap_fill() -> FillStyle {
+        FillStyle::Bitmap {
+            id: 1,
+            matrix: swf::Matrix::IDENTITY,
+            is_smoothed: false,
+            is_repeating: false,
+        }
+    }
+
+    fn empty_glyph() -> swf::Glyph {
+        swf::Glyph {
+            shape_records: Vec::new(),
+            code: 0,
+            advance: 0,
+            bounds: None,
+        }
+    }
+
+    fn square(style: &FillStyle) -> DistilledShape {
+        DistilledShape {
+            paths: vec![DrawPath::Fill {
+                style,
+                commands: vec![
+                    DrawCommand::MoveTo {
+                        x: Twips::ZERO,
+                        y: Twips::ZERO,
+                    },
+                    DrawCommand::LineTo {
+                        x: Twips::ONE,
+                        y: Twips::ONE,
+                    },
+                ],
+                winding_rule: FillRule::EvenOdd,
+            }],
+            shape_bounds: Default::default(),
+            edge_bounds: Default::default(),
+            id: 0,
+            stroke_matrix: None,
+        }
+    }
+
+    /// The shape and bitmap drawn by the last frame of the current backend.
+    fn last_frame(backend: &SwitchableRenderBackend) -> (usize, (u32, usize)) {
+        let inner = backend
+            .inner()
+            .downcast_ref::<TestBackend>()
+            .expect("Inner backend should be a test backend");
+        match &inner.frame.commands[..] {
+            [Command::RenderShape { shape, .. }, Command::RenderBitmap { bitmap, .. }] => {
+                (shape.index(), test_bitmap(bitmap))
+            }
+            commands => panic!("Unexpected frame {commands:?}"),
+        }
+    }
+
+    #[test]
+    fn switch_registers_everything_again() {
+        let mut backend = SwitchableRenderBackend::new(TestBackend::new(0));
+        let library = TestLibrary::default();
+        // Something else is registered first, so that the handles of both backends differ
+        let _glyph = backend.register_glyph_shape(&empty_glyph());
+        let fill = bitmap_fill();
+        let shape = backend.register_shape(square(&fill), &library);
+        let bitmap = library
+            .bitmap_handle(1, &mut backend)
+            .expect("Bitmap should be registered");
+
+        let draw = |backend: &mut SwitchableRenderBackend| {
+            let mut commands = CommandList::new();
+            commands.render_shape(shape.clone(), Transform::default());
+            commands.render_bitmap(bitmap.clone(), Transform::default(), false);
+            backend.submit_frame(Color::WHITE, commands);
+        };
+        draw(&mut backend);
+        assert_eq!(last_frame(&backend), (1, (0, 0)));
+
+        backend
+            .update_texture(&bitmap, 1, 1, vec![0, 0, 255, 255])
+            .expect("Texture should update");
+        let mut next = TestBackend::new(1);
+        next.register_bitmap(Bitmap::new(1, 1, BitmapFormat::Rgba, vec![0; 4]))
+            .expect("Bitmap should register");
+        next.register_glyph_shape(&empty_glyph());
+        backend
+            .switch_to(next)
+            .expect("Switching backends should succeed");
+        draw(&mut backend);
+        assert_eq!(last_frame(&backend), (2, (1, 1)));
+
+        let inner = backend
+            .inner()
+            .downcast_ref::<TestBackend>()
+            .expect("Inner backend should be a test backend");
+        assert_eq!(inner.bitmaps[1].data(), [0, 0, 255, 255]);
+        assert_eq!(inner.shapes[2], Some((1, 1)));
+    }
+
+    #[test]
+    fn dropped_shapes_are_freed() {
+        let mut backend = SwitchableRenderBackend::new(TestBackend::new(0));
+        let library = TestLibrary::default();
+        let fill = bitmap_fill();
+        let kept = backend.register_shape(square(&fill), &library);
+        drop(backend.register_shape(square(&fill), &library));
+        drop(backend.register_glyph_shape(&empty_glyph()));
+        let _glyph = backend.register_glyph_shape(&empty_glyph());
+        assert_eq!(backend.shapes.len(), 2);
+
+        backend
+            .switch_to(TestBackend::new(1))
+            .expect("Switching backends should succeed");
+        let inner = backend
+            .inner()
+            .downcast_ref::<TestBackend>()
+            .expect("Inner backend should be a test backend");
+        assert_eq!(inner.shapes, [Some((1, 0)), None]);
+        assert_eq!(inner_shape(&kept).index(), 0);
+    }
+
+    #[test]
+    fn switch_keeps_drawn_pixels() {
+        let mut backend = SwitchableRenderBackend::new(TestBackend::new(0));
+        let bitmap = backend
+            .register_bitmap(Bitmap::new(1, 1, BitmapFormat::Rgba, vec![255, 0, 0, 255]))
+            .expect("Bitmap should register");
+        // Like `BitmapData.draw`, whose pixels are only retrieved once they're needed
+        let sync =
+            backend.render_offscreen(bitmap.clone(), 1, 1, CommandList::new(), StageQuality::High);
+        assert!(sync.is_some());
+
+        backend
+            .switch_to(TestBackend::new(1))
+            .expect("Switching backends should succeed");
+        let inner = backend
+            .inner()
+            .downcast_ref::<TestBackend>()
+            .expect("Inner backend should be a test backend");
+        assert_eq!(inner.bitmaps[0].data(), DRAWN);
+
+        let pixels = sync
+            .expect("Sync handle should be returned")
+            .retrieve_offscreen_texture()
+            .expect("Pixels should be retrieved");
+        assert_eq!(pixels.data(), DRAWN);
+    }
+}
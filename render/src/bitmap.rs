@@ -4,6 +4,7 @@ use std::sync::Arc;
 use downcast_rs::{impl_downcast, Downcast};
 
 use crate::backend::RenderBackend;
+use crate::utils::{f16_to_f32, premultiply_alpha_rgba};
 
 #[derive(Clone, Debug)]
 pub struct BitmapHandle(pub Arc<dyn BitmapHandleImpl>);
@@ -25,6 +26,32 @@ pub struct BitmapSize {
     pub height: u16,
 }
 
+/// A rectangle of pixels within a bitmap, such as one image of a sprite sheet.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct BitmapRegion {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl BitmapRegion {
+    /// The matrix that maps the unit square onto this region, in the texture coordinates
+    /// of a bitmap of the given size. It's in column-major order.
+    pub fn texture_matrix(&self, bitmap_width: u32, bitmap_height: u32) -> [[f32; 3]; 3] {
+        let (bitmap_width, bitmap_height) = (bitmap_width as f32, bitmap_height as f32);
+        [
+            [self.width as f32 / bitmap_width, 0.0, 0.0],
+            [0.0, self.height as f32 / bitmap_height, 0.0],
+            [
+                self.x as f32 / bitmap_width,
+                self.y as f32 / bitmap_height,
+                1.0,
+            ],
+        ]
+    }
+}
+
 /// An object that returns a bitmap given an ID.
 ///
 /// This is used by render backends to get the bitmap used in a bitmap fill.
@@ -78,15 +105,27 @@ impl Bitmap {
     }
 
     pub fn to_rgba(mut self) -> Self {
-        // Converts this bitmap to RGBA, if it is not already.
-        if self.format == BitmapFormat::Rgb {
-            self.data = self
-                .data
-                .chunks_exact(3)
-                .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], 255])
-                .collect();
-            self.format = BitmapFormat::Rgba;
+        // Converts this bitmap to premultiplied RGBA, if it is not already.
+        match self.format {
+            BitmapFormat::Rgb => {
+                self.data = self
+                    .data
+                    .chunks_exact(3)
+                    .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], 255])
+                    .collect();
+            }
+            BitmapFormat::StraightRgba => premultiply_alpha_rgba(&mut self.data),
+            BitmapFormat::Rgba => {}
+            BitmapFormat::Rgba16 | BitmapFormat::Rgba16Float => {
+                let format = self.format;
+                self.data = self
+                    .data
+                    .chunks_exact(format.bytes_per_pixel())
+                    .flat_map(|pixel| format.pixel_to_rgba8(pixel))
+                    .collect();
+            }
         }
+        self.format = BitmapFormat::Rgba;
         self
     }
 
@@ -116,17 +155,13 @@ impl Bitmap {
     }
 
     pub fn as_colors(&self) -> impl Iterator<Item = i32> + '_ {
-        let chunks = match self.format {
-            BitmapFormat::Rgb => self.data.chunks_exact(3),
-            BitmapFormat::Rgba => self.data.chunks_exact(4),
-        };
-        chunks.map(|chunk| {
-            let red = chunk[0];
-            let green = chunk[1];
-            let blue = chunk[2];
-            let alpha = chunk.get(3).copied().unwrap_or(0xFF);
-            i32::from_le_bytes([blue, green, red, alpha])
-        })
+        let format = self.format;
+        self.data
+            .chunks_exact(format.bytes_per_pixel())
+            .map(move |chunk| {
+                let [red, green, blue, alpha] = format.pixel_to_rgba8(chunk);
+                i32::from_le_bytes([blue, green, red, alpha])
+            })
     }
 }
 
@@ -138,6 +173,23 @@ pub enum BitmapFormat {
 
     /// 32-bit RGBA with premultiplied alpha.
     Rgba,
+
+    /// 32-bit RGBA with straight alpha, where the colors aren't multiplied by it.
+    ///
+    /// Backends that can't draw this premultiply it when it's registered.
+    StraightRgba,
+
+    /// 64-bit RGBA with premultiplied alpha, where each channel is a little-endian
+    /// 16-bit unsigned integer.
+    ///
+    /// Backends that can't draw this convert it to `Rgba` when it's registered.
+    Rgba16,
+
+    /// 64-bit RGBA with premultiplied alpha, where each channel is a little-endian
+    /// half precision float from 0 to 1.
+    ///
+    /// Backends that can't draw this convert it to `Rgba` when it's registered.
+    Rgba16Float,
 }
 
 impl BitmapFormat {
@@ -145,7 +197,25 @@ impl BitmapFormat {
     pub fn bytes_per_pixel(self) -> usize {
         match self {
             BitmapFormat::Rgb => 3,
-            BitmapFormat::Rgba => 4,
+            BitmapFormat::Rgba | BitmapFormat::StraightRgba => 4,
+            BitmapFormat::Rgba16 | BitmapFormat::Rgba16Float => 8,
+        }
+    }
+
+    /// Converts a pixel of this format to 8-bit RGBA, with the same kind of alpha.
+    fn pixel_to_rgba8(self, pixel: &[u8]) -> [u8; 4] {
+        let channel = |i: usize| u16::from_le_bytes([pixel[i * 2], pixel[i * 2 + 1]]);
+        match self {
+            BitmapFormat::Rgb => [pixel[0], pixel[1], pixel[2], 255],
+            BitmapFormat::Rgba | BitmapFormat::StraightRgba => {
+                [pixel[0], pixel[1], pixel[2], pixel[3]]
+            }
+            BitmapFormat::Rgba16 => {
+                std::array::from_fn(|i| ((u32::from(channel(i)) * 255 + 32767) / 65535) as u8)
+            }
+            BitmapFormat::Rgba16Float => std::array::from_fn(|i| {
+                (f16_to_f32(channel(i)).clamp(0.0, 1.0) * 255.0).round() as u8
+            }),
         }
     }
 }
@@ -11,6 +11,12 @@ pub struct ColorTransform {
     pub g_add: i16,
     pub b_add: i16,
     pub a_add: i16,
+
+    /// How far colors are mixed towards their luminance before they're multiplied,
+    /// from 0 for full color to 1 for grayscale.
+    ///
+    /// Flash content can't set this, and of the renderers only wgpu draws it.
+    pub luminance_mix: Fixed8,
 }
 
 impl From<swf::ColorTransform> for ColorTransform {
@@ -24,6 +30,7 @@ impl From<swf::ColorTransform> for ColorTransform {
             g_add: color_transform.g_add,
             b_add: color_transform.b_add,
             a_add: color_transform.a_add,
+            luminance_mix: Fixed8::ZERO,
         }
     }
 }
@@ -38,6 +45,7 @@ impl ColorTransform {
         b_add: 0,
         g_add: 0,
         a_add: 0,
+        luminance_mix: Fixed8::ZERO,
     };
 
     /// Returns the multiplicative component of this color transform in RGBA order
@@ -98,6 +106,13 @@ impl Mul for ColorTransform {
             a_add: self
                 .a_add
                 .wrapping_add(self.a_mult.wrapping_mul_int(rhs.a_add)),
+            // Mixing a color towards its luminance keeps that luminance, so mixing twice
+            // leaves `(1 - a) * (1 - b)` of the color. This is only exact when `rhs` tints
+            // the red, green and blue channels alike, like alpha-only transforms do.
+            luminance_mix: self
+                .luminance_mix
+                .wrapping_add(rhs.luminance_mix)
+                .wrapping_sub(self.luminance_mix.wrapping_mul(rhs.luminance_mix)),
         }
     }
 }
@@ -112,6 +127,16 @@ impl Mul<Color> for ColorTransform {
     type Output = Color;
 
     fn mul(self, mut color: Color) -> Color {
+        if !self.luminance_mix.is_zero() {
+            let luminance = luminance(color);
+            let mix = |channel: u8| {
+                let channel = i16::from(channel);
+                channel.wrapping_add(self.luminance_mix.wrapping_mul_int(luminance - channel)) as u8
+            };
+            color.r = mix(color.r);
+            color.g = mix(color.g);
+            color.b = mix(color.b);
+        }
         color.r = self
             .r_mult
             .wrapping_mul_int(i16::from(color.r))
@@ -131,3 +156,10 @@ impl Mul<Color> for ColorTransform {
         color
     }
 }
+
+/// The luminance of a color from 0 to 255, weighted like the wgpu shaders weigh it.
+fn luminance(color: Color) -> i16 {
+    let weighted =
+        299 * u32::from(color.r) + 587 * u32::from(color.g) + 114 * u32::from(color.b) + 500;
+    (weighted / 1000) as i16
+}
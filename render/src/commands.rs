@@ -1,19 +1,56 @@
 use crate::backend::ShapeHandle;
-use crate::bitmap::BitmapHandle;
+use crate::bitmap::{BitmapHandle, BitmapRegion};
+use crate::color_transform::ColorTransform;
+use crate::filters::Filter;
 use crate::matrix::Matrix;
 use crate::transform::Transform;
 use swf::{BlendMode, Color};
 
 pub trait CommandHandler {
     fn render_bitmap(&mut self, bitmap: BitmapHandle, transform: Transform, smoothing: bool);
+
+    /// Renders only the `region` of a bitmap, as though it were a bitmap of that size.
+    /// This lets the images of a sprite sheet be drawn from a single bitmap.
+    ///
+    /// When smoothed, the pixels just outside of the region are blended into its edges.
+    fn render_bitmap_region(
+        &mut self,
+        bitmap: BitmapHandle,
+        region: BitmapRegion,
+        transform: Transform,
+        smoothing: bool,
+    );
     fn render_shape(&mut self, shape: ShapeHandle, transform: Transform);
+
+    /// Renders a shape from `RenderBackend::register_morph_shape`, interpolated to `ratio`.
+    /// Only backends that register morph shapes ever receive these.
+    fn render_morph_shape(&mut self, shape: ShapeHandle, transform: Transform, _ratio: u16) {
+        self.render_shape(shape, transform);
+    }
     fn draw_rect(&mut self, color: Color, matrix: Matrix);
     fn push_mask(&mut self);
+
+    /// Like `push_mask`, but the masked content is drawn everywhere except where the mask is.
+    /// The mask is then activated, deactivated and popped in the same way as any other.
+    fn push_inverted_mask(&mut self);
     fn activate_mask(&mut self);
     fn deactivate_mask(&mut self);
     fn pop_mask(&mut self);
 
-    fn blend(&mut self, commands: CommandList, blend_mode: BlendMode);
+    fn blend(
+        &mut self,
+        commands: CommandList,
+        blend_mode: BlendMode,
+        color_transform: ColorTransform,
+    );
+
+    /// Renders `commands` offscreen and then runs the result through each of `filters` in turn,
+    /// so that every filter applies to the output of the one before it.
+    fn filters(&mut self, commands: CommandList, filters: Vec<Filter>);
+
+    /// Renders `maskee` and `mask` separately, and then composites `maskee`
+    /// with its alpha multiplied by that of `mask`.
+    fn alpha_mask(&mut self, maskee: CommandList, mask: CommandList);
 }
 
 #[derive(Debug, Default, Clone)]
@@ -34,13 +71,62 @@ impl CommandList {
                     transform,
                     smoothing,
                 } => handler.render_bitmap(bitmap, transform, smoothing),
+                Command::RenderBitmapRegion {
+                    bitmap,
+                    region,
+                    transform,
+                    smoothing,
+                } => handler.render_bitmap_region(bitmap, region, transform, smoothing),
                 Command::RenderShape { shape, transform } => handler.render_shape(shape, transform),
+                Command::RenderMorphShape {
+                    shape,
+                    transform,
+                    ratio,
+                } => handler.render_morph_shape(shape, transform, ratio),
                 Command::DrawRect { color, matrix } => handler.draw_rect(color, matrix),
                 Command::PushMask => handler.push_mask(),
+                Command::PushInvertedMask => handler.push_inverted_mask(),
                 Command::ActivateMask => handler.activate_mask(),
                 Command::DeactivateMask => handler.deactivate_mask(),
                 Command::PopMask => handler.pop_mask(),
-                Command::Blend(commands, blend_mode) => handler.blend(commands, blend_mode),
+                Command::Blend(commands, blend_mode, color_transform) => {
+                    handler.blend(commands, blend_mode, color_transform)
+                }
+                Command::Filters(commands, filters) => handler.filters(commands, filters),
+                Command::AlphaMask(maskee, mask) => handler.alpha_mask(maskee, mask),
+            }
+        }
+    }
+
+    /// Applies `color_transform` to every draw in this list, including nested blends.
+    ///
+    /// Backends that can't composite a blend group as a whole use this to fall back
+    /// to applying the group's color transform to each of its children individually.
+    pub fn apply_color_transform(&mut self, color_transform: ColorTransform) {
+        if color_transform == ColorTransform::IDENTITY {
+            return;
+        }
+        for command in &mut self.commands {
+            match command {
+                Command::RenderBitmap { transform, .. }
+                | Command::RenderBitmapRegion { transform, .. }
+                | Command::RenderShape { transform, .. }
+                | Command::RenderMorphShape { transform, .. } => {
+                    transform.color_transform = color_transform * transform.color_transform;
+                }
+                Command::Blend(_, _, group_color_transform) => {
+                    *group_color_transform = color_transform * *group_color_transform;
+                }
+                Command::Filters(commands, _) => commands.apply_color_transform(color_transform),
+                Command::AlphaMask(maskee, _) => maskee.apply_color_transform(color_transform),
+                Command::DrawRect { color, .. } => {
+                    *color = color_transform * color.clone();
+                }
+                Command::PushMask
+                | Command::PushInvertedMask
+                | Command::ActivateMask
+                | Command::DeactivateMask
+                | Command::PopMask => {}
             }
         }
     }
@@ -55,11 +141,34 @@ impl CommandHandler for CommandList {
         });
     }
 
+    fn render_bitmap_region(
+        &mut self,
+        bitmap: BitmapHandle,
+        region: BitmapRegion,
+        transform: Transform,
+        smoothing: bool,
+    ) {
+        self.commands.push(Command::RenderBitmapRegion {
+            bitmap,
+            region,
+            transform,
+            smoothing,
+        });
+    }
+
     fn render_shape(&mut self, shape: ShapeHandle, transform: Transform) {
         self.commands
             .push(Command::RenderShape { shape, transform });
     }
 
+    fn render_morph_shape(&mut self, shape: ShapeHandle, transform: Transform, ratio: u16) {
+        self.commands.push(Command::RenderMorphShape {
+            shape,
+            transform,
+            ratio,
+        });
+    }
+
     fn draw_rect(&mut self, color: Color, matrix: Matrix) {
         self.commands.push(Command::DrawRect { color, matrix });
     }
@@ -68,6 +177,10 @@ impl CommandHandler for CommandList {
         self.commands.push(Command::PushMask);
     }
 
+    fn push_inverted_mask(&mut self) {
+        self.commands.push(Command::PushInvertedMask);
+    }
+
     fn activate_mask(&mut self) {
         self.commands.push(Command::ActivateMask);
     }
@@ -80,8 +193,22 @@ impl CommandHandler for CommandList {
         self.commands.push(Command::PopMask);
     }
 
-    fn blend(&mut self, commands: CommandList, blend_mode: BlendMode) {
-        self.commands.push(Command::Blend(commands, blend_mode));
+    fn blend(
+        &mut self,
+        commands: CommandList,
+        blend_mode: BlendMode,
+        color_transform: ColorTransform,
+    ) {
+        self.commands
+            .push(Command::Blend(commands, blend_mode, color_transform));
+    }
+
+    fn filters(&mut self, commands: CommandList, filters: Vec<Filter>) {
+        self.commands.push(Command::Filters(commands, filters));
+    }
+
+    fn alpha_mask(&mut self, maskee: CommandList, mask: CommandList) {
+        self.commands.push(Command::AlphaMask(maskee, mask));
     }
 }
 
@@ -92,17 +219,63 @@ pub enum Command {
         transform: Transform,
         smoothing: bool,
     },
+    /// Renders a region of a bitmap, where the transform applies to the region's own pixels.
+    RenderBitmapRegion {
+        bitmap: BitmapHandle,
+        region: BitmapRegion,
+        transform: Transform,
+        smoothing: bool,
+    },
     RenderShape {
         shape: ShapeHandle,
         transform: Transform,
     },
+    /// Renders a morph shape, where a `ratio` of 0 is the start shape and 65535 is the end shape.
+    RenderMorphShape {
+        shape: ShapeHandle,
+        transform: Transform,
+        ratio: u16,
+    },
     DrawRect {
         color: Color,
         matrix: Matrix,
     },
     PushMask,
+    /// Starts a mask that shows the masked content only outside of the mask's shapes.
+    PushInvertedMask,
     ActivateMask,
     DeactivateMask,
     PopMask,
-    Blend(CommandList, BlendMode),
+    /// Renders the given commands as a single group, which is then composited
+    /// using the blend mode and color transform.
+    Blend(CommandList, BlendMode, ColorTransform),
+    /// Renders the given commands as a single group, which is then composited
+    /// after running it through each of the filters in order.
+    Filters(CommandList, Vec<Filter>),
+    /// Renders the first commands masked by the alpha of the second commands.
+    AlphaMask(CommandList, CommandList),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use swf::Fixed8;
+
+    #[test]
+    fn apply_color_transform_to_rect() {
+        let mut commands = CommandList::new();
+        commands.draw_rect(Color::from_rgb(0x804020, 255), Matrix::IDENTITY);
+
+        commands.apply_color_transform(ColorTransform {
+            r_mult: Fixed8::from_f32(0.5),
+            a_mult: Fixed8::from_f32(0.5),
+            b_add: 32,
+            ..ColorTransform::IDENTITY
+        });
+
+        let Command::DrawRect { color, .. } = &commands.commands[0] else {
+            panic!("Expected a rect, got {:?}", commands.commands[0]);
+        };
+        assert_eq!(*color, Color::from_rgb(0x404040, 127));
+    }
 }
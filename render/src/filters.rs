@@ -1,9 +1,57 @@
-use swf::Fixed16;
+use crate::bitmap::BitmapHandle;
+use crate::bounding_box::BoundingBox;
+use swf::{Color, Fixed16, Twips};
 
-#[derive(Debug)]
+pub mod cpu;
+
+#[derive(Debug, Clone)]
 pub enum Filter {
+    BevelFilter(BevelFilter),
     BlurFilter(BlurFilter),
     ColorMatrixFilter(ColorMatrixFilter),
+    ConvolutionFilter(ConvolutionFilter),
+    DisplacementMapFilter(DisplacementMapFilter),
+    DropShadowFilter(DropShadowFilter),
+    GlowFilter(GlowFilter),
+}
+
+impl Filter {
+    /// Converts a filter defined in a SWF tag, returning `None` if it isn't supported yet.
+    pub fn from_swf(filter: &swf::Filter) -> Option<Self> {
+        match filter {
+            swf::Filter::BevelFilter(filter) => {
+                Some(Filter::BevelFilter((**filter).clone().into()))
+            }
+            swf::Filter::BlurFilter(filter) => Some(Filter::BlurFilter((**filter).clone().into())),
+            swf::Filter::ColorMatrixFilter(filter) => {
+                Some(Filter::ColorMatrixFilter((**filter).clone().into()))
+            }
+            swf::Filter::ConvolutionFilter(filter) => {
+                Some(Filter::ConvolutionFilter((**filter).clone().into()))
+            }
+            swf::Filter::DropShadowFilter(filter) => {
+                Some(Filter::DropShadowFilter((**filter).clone().into()))
+            }
+            swf::Filter::GlowFilter(filter) => Some(Filter::GlowFilter((**filter).clone().into())),
+            _ => None,
+        }
+    }
+
+    /// Calculates the area that this filter may draw to, when applied to the given area.
+    ///
+    /// Filters such as blurs and shadows spread out beyond their source,
+    /// so anything that culls or clips the result must use this instead of the source area.
+    pub fn calculate_dest_rect(&self, source_rect: BoundingBox) -> BoundingBox {
+        match self {
+            Filter::BevelFilter(filter) => filter.calculate_dest_rect(source_rect),
+            Filter::BlurFilter(filter) => filter.calculate_dest_rect(source_rect),
+            Filter::DropShadowFilter(filter) => filter.calculate_dest_rect(source_rect),
+            Filter::GlowFilter(filter) => {
+                DropShadowFilter::from(filter.clone()).calculate_dest_rect(source_rect)
+            }
+            _ => source_rect,
+        }
+    }
 }
 
 impl Default for Filter {
@@ -14,7 +62,7 @@ impl Default for Filter {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct BlurFilter {
     pub blur_x: f32,
     pub blur_y: f32,
@@ -31,6 +79,32 @@ impl From<swf::BlurFilter> for BlurFilter {
     }
 }
 
+impl BlurFilter {
+    /// How many pixels this blur spreads out by on each side, horizontally and vertically.
+    pub fn margin(&self) -> (u32, u32) {
+        // Each pass is a box blur that spreads by half of its size, rounded up to a whole pixel
+        let margin = |blur: f32| ((blur - 1.0).max(0.0) / 2.0).ceil() as u32;
+        let passes = u32::from(self.quality.max(1));
+        (margin(self.blur_x) * passes, margin(self.blur_y) * passes)
+    }
+
+    pub fn calculate_dest_rect(&self, source_rect: BoundingBox) -> BoundingBox {
+        if !source_rect.valid {
+            return source_rect;
+        }
+        let (x, y) = self.margin();
+        let x = Twips::from_pixels_i32(x as i32);
+        let y = Twips::from_pixels_i32(y as i32);
+        BoundingBox {
+            x_min: source_rect.x_min - x,
+            y_min: source_rect.y_min - y,
+            x_max: source_rect.x_max + x,
+            y_max: source_rect.y_max + y,
+            valid: true,
+        }
+    }
+}
+
 impl Default for BlurFilter {
     fn default() -> Self {
         Self {
@@ -41,7 +115,7 @@ impl Default for BlurFilter {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ColorMatrixFilter {
     pub matrix: [f32; 20],
 }
@@ -66,3 +140,355 @@ impl Default for ColorMatrixFilter {
         }
     }
 }
+
+#[derive(Debug, Clone)]
+pub struct ConvolutionFilter {
+    /// The number of columns in the matrix.
+    pub matrix_x: u8,
+
+    /// The number of rows in the matrix.
+    pub matrix_y: u8,
+
+    /// The kernel, row by row. Missing values are treated as 0.
+    pub matrix: Vec<f32>,
+
+    /// The result of the kernel is divided by this.
+    pub divisor: f32,
+
+    /// Added to the result after dividing, in color values from 0 to 255.
+    pub bias: f32,
+
+    /// Whether the alpha of each pixel is kept as is, with only the colors being convolved.
+    pub preserve_alpha: bool,
+
+    /// Whether pixels outside of the source use the nearest edge pixel, rather than `color`.
+    pub clamp: bool,
+
+    /// The color used for pixels outside of the source when not clamping, with straight alpha.
+    pub color: Color,
+}
+
+impl From<swf::ConvolutionFilter> for ConvolutionFilter {
+    fn from(value: swf::ConvolutionFilter) -> Self {
+        Self {
+            matrix_x: value.num_matrix_cols,
+            matrix_y: value.num_matrix_rows,
+            matrix: value.matrix.iter().copied().map(Fixed16::to_f32).collect(),
+            divisor: value.divisor.to_f32(),
+            bias: value.bias.to_f32(),
+            preserve_alpha: value.is_preserve_alpha(),
+            clamp: value.is_clamped(),
+            color: value.default_color.clone(),
+        }
+    }
+}
+
+impl Default for ConvolutionFilter {
+    fn default() -> Self {
+        Self {
+            matrix_x: 0,
+            matrix_y: 0,
+            matrix: vec![],
+            divisor: 1.0,
+            bias: 0.0,
+            preserve_alpha: true,
+            clamp: true,
+            color: Color::from_rgb(0, 0),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DisplacementMapFilterMode {
+    /// Pixels displaced from outside of the source wrap around to the other side.
+    Wrap,
+
+    /// Pixels displaced from outside of the source use the nearest edge pixel.
+    Clamp,
+
+    /// Pixels displaced from outside of the source aren't displaced at all.
+    Ignore,
+
+    /// Pixels displaced from outside of the source are replaced with the filter color.
+    Color,
+}
+
+#[derive(Debug, Clone)]
+pub struct DisplacementMapFilter {
+    /// The image containing the displacement data.
+    pub map: BitmapHandle,
+
+    /// The offset of the filtered area from the top left corner of the map.
+    pub map_point: (i32, i32),
+
+    /// The channel of the map used for horizontal displacement, as a `BitmapDataChannel` flag.
+    pub component_x: u8,
+
+    /// The channel of the map used for vertical displacement, as a `BitmapDataChannel` flag.
+    pub component_y: u8,
+
+    pub scale_x: f32,
+    pub scale_y: f32,
+    pub mode: DisplacementMapFilterMode,
+
+    /// The color used by `DisplacementMapFilterMode::Color`, with straight alpha.
+    pub color: Color,
+}
+
+#[derive(Debug, Clone)]
+pub struct DropShadowFilter {
+    /// The color of the shadow, with straight alpha.
+    pub color: Color,
+
+    /// The direction that the shadow is cast in, in radians.
+    pub angle: f32,
+
+    /// How far the shadow is offset from the object, in pixels.
+    pub distance: f32,
+
+    pub blur_x: f32,
+    pub blur_y: f32,
+    pub strength: f32,
+    pub quality: u8,
+
+    /// Whether the shadow is cast inside of the object, rather than behind it.
+    pub inner: bool,
+
+    /// Whether the object is cut out of the shadow, with the object itself hidden.
+    pub knockout: bool,
+
+    /// Whether only the shadow is drawn, without the object on top.
+    pub hide_object: bool,
+}
+
+impl From<swf::DropShadowFilter> for DropShadowFilter {
+    fn from(value: swf::DropShadowFilter) -> Self {
+        Self {
+            color: value.color.clone(),
+            angle: value.angle.to_f32(),
+            distance: value.distance.to_f32(),
+            blur_x: value.blur_x.to_f32(),
+            blur_y: value.blur_y.to_f32(),
+            strength: value.strength.to_f32(),
+            quality: value.num_passes(),
+            inner: value.is_inner(),
+            knockout: value.is_knockout(),
+            hide_object: !value
+                .flags
+                .contains(swf::DropShadowFilterFlags::COMPOSITE_SOURCE),
+        }
+    }
+}
+
+impl DropShadowFilter {
+    pub fn calculate_dest_rect(&self, source_rect: BoundingBox) -> BoundingBox {
+        if !source_rect.valid || self.inner {
+            // An inner shadow never leaves the object
+            return source_rect;
+        }
+        let blurred = BlurFilter {
+            blur_x: self.blur_x,
+            blur_y: self.blur_y,
+            quality: self.quality,
+        }
+        .calculate_dest_rect(source_rect.clone());
+        let x = Twips::from_pixels(f64::from(self.angle.cos() * self.distance));
+        let y = Twips::from_pixels(f64::from(self.angle.sin() * self.distance));
+        let mut dest_rect = BoundingBox {
+            x_min: blurred.x_min + x,
+            y_min: blurred.y_min + y,
+            x_max: blurred.x_max + x,
+            y_max: blurred.y_max + y,
+            valid: true,
+        };
+        if !self.knockout && !self.hide_object {
+            // The object itself is drawn too
+            dest_rect.union(&source_rect);
+        }
+        dest_rect
+    }
+}
+
+impl Default for DropShadowFilter {
+    fn default() -> Self {
+        Self {
+            color: Color::BLACK,
+            angle: std::f32::consts::FRAC_PI_4,
+            distance: 4.0,
+            blur_x: 4.0,
+            blur_y: 4.0,
+            strength: 1.0,
+            quality: 1,
+            inner: false,
+            knockout: false,
+            hide_object: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GlowFilter {
+    /// The color of the glow, with straight alpha.
+    pub color: Color,
+
+    pub blur_x: f32,
+    pub blur_y: f32,
+    pub strength: f32,
+    pub quality: u8,
+
+    /// Whether the glow is inside of the object, rather than around it.
+    pub inner: bool,
+
+    /// Whether the object is cut out of the glow, with the object itself hidden.
+    pub knockout: bool,
+}
+
+impl From<swf::GlowFilter> for GlowFilter {
+    fn from(value: swf::GlowFilter) -> Self {
+        Self {
+            color: value.color.clone(),
+            blur_x: value.blur_x.to_f32(),
+            blur_y: value.blur_y.to_f32(),
+            strength: value.strength.to_f32(),
+            quality: value.num_passes(),
+            inner: value.is_inner(),
+            knockout: value.is_knockout(),
+        }
+    }
+}
+
+/// A glow is a drop shadow that isn't offset from the object.
+impl From<GlowFilter> for DropShadowFilter {
+    fn from(value: GlowFilter) -> Self {
+        Self {
+            color: value.color,
+            angle: 0.0,
+            distance: 0.0,
+            blur_x: value.blur_x,
+            blur_y: value.blur_y,
+            strength: value.strength,
+            quality: value.quality,
+            inner: value.inner,
+            knockout: value.knockout,
+            hide_object: false,
+        }
+    }
+}
+
+impl Default for GlowFilter {
+    fn default() -> Self {
+        Self {
+            color: Color::from_rgb(0xFF0000, 255),
+            blur_x: 6.0,
+            blur_y: 6.0,
+            strength: 2.0,
+            quality: 1,
+            inner: false,
+            knockout: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BevelFilterType {
+    /// The bevel is only drawn inside of the object.
+    Inner,
+
+    /// The bevel is only drawn around the outside of the object.
+    Outer,
+
+    /// The bevel is drawn both inside and outside of the object.
+    Full,
+}
+
+#[derive(Debug, Clone)]
+pub struct BevelFilter {
+    /// The color of the highlight, with straight alpha.
+    pub highlight_color: Color,
+
+    /// The color of the shadow, with straight alpha.
+    pub shadow_color: Color,
+
+    /// The direction that the light comes from, in radians.
+    /// The shadow is cast in this direction, and the highlight in the opposite one.
+    pub angle: f32,
+
+    /// How far the highlight and shadow are offset from the object, in pixels.
+    pub distance: f32,
+
+    pub blur_x: f32,
+    pub blur_y: f32,
+    pub strength: f32,
+    pub quality: u8,
+    pub bevel_type: BevelFilterType,
+
+    /// Whether the object is cut out of the bevel, with the object itself hidden.
+    pub knockout: bool,
+}
+
+impl BevelFilter {
+    pub fn calculate_dest_rect(&self, source_rect: BoundingBox) -> BoundingBox {
+        if !source_rect.valid || self.bevel_type == BevelFilterType::Inner {
+            // An inner bevel never leaves the object
+            return source_rect;
+        }
+        // The highlight and shadow are on opposite sides, so the bevel goes both ways
+        let shadow = DropShadowFilter {
+            angle: self.angle,
+            distance: self.distance,
+            blur_x: self.blur_x,
+            blur_y: self.blur_y,
+            quality: self.quality,
+            hide_object: true,
+            ..Default::default()
+        };
+        let highlight = DropShadowFilter {
+            angle: self.angle + std::f32::consts::PI,
+            ..shadow.clone()
+        };
+        let mut dest_rect = shadow.calculate_dest_rect(source_rect.clone());
+        dest_rect.union(&highlight.calculate_dest_rect(source_rect.clone()));
+        dest_rect.union(&source_rect);
+        dest_rect
+    }
+}
+
+impl From<swf::BevelFilter> for BevelFilter {
+    fn from(value: swf::BevelFilter) -> Self {
+        Self {
+            highlight_color: value.highlight_color.clone(),
+            shadow_color: value.shadow_color.clone(),
+            angle: value.angle.to_f32(),
+            distance: value.distance.to_f32(),
+            blur_x: value.blur_x.to_f32(),
+            blur_y: value.blur_y.to_f32(),
+            strength: value.strength.to_f32(),
+            quality: value.num_passes(),
+            bevel_type: if value.is_on_top() {
+                BevelFilterType::Full
+            } else if value.is_inner() {
+                BevelFilterType::Inner
+            } else {
+                BevelFilterType::Outer
+            },
+            knockout: value.is_knockout(),
+        }
+    }
+}
+
+impl Default for BevelFilter {
+    fn default() -> Self {
+        Self {
+            highlight_color: Color::WHITE,
+            shadow_color: Color::BLACK,
+            angle: std::f32::consts::FRAC_PI_4,
+            distance: 4.0,
+            blur_x: 4.0,
+            blur_y: 4.0,
+            strength: 1.0,
+            quality: 1,
+            bevel_type: BevelFilterType::Inner,
+            knockout: false,
+        }
+    }
+}
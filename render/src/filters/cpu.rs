@@ -0,0 +1,695 @@
+//! Software implementations of the filters, for backends that can't apply them on the GPU.
+//!
+//! These follow the same math as the wgpu filter shaders, step for step,
+//! which also makes them the reference that those shaders are tested against.
+
+use crate::filters::{BlurFilter, ColorMatrixFilter, ConvolutionFilter, DropShadowFilter, Filter};
+
+/// A premultiplied color, with each component from 0 to 1.
+pub type FilterColor = [f32; 4];
+
+const TRANSPARENT: FilterColor = [0.0, 0.0, 0.0, 0.0];
+
+/// An image that filters read from and write to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilterImage {
+    width: u32,
+    height: u32,
+    pixels: Vec<FilterColor>,
+}
+
+impl FilterImage {
+    /// Creates an image filled with transparent black.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![TRANSPARENT; width as usize * height as usize],
+        }
+    }
+
+    /// Creates an image from premultiplied RGBA bytes, row by row.
+    pub fn from_rgba(width: u32, height: u32, rgba: &[u8]) -> Self {
+        let pixels = rgba
+            .chunks_exact(4)
+            .map(|pixel| {
+                [pixel[0], pixel[1], pixel[2], pixel[3]]
+                    .map(|component| f32::from(component) / 255.0)
+            })
+            .collect::<Vec<_>>();
+        debug_assert_eq!(pixels.len(), width as usize * height as usize);
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// Converts this image to premultiplied RGBA bytes, row by row.
+    pub fn to_rgba(&self) -> Vec<u8> {
+        self.pixels
+            .iter()
+            .flat_map(|pixel| pixel.map(to_byte))
+            .collect()
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Reads a pixel, treating anything outside of the image as transparent.
+    pub fn get(&self, x: i32, y: i32) -> FilterColor {
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+            return TRANSPARENT;
+        }
+        self.pixels[y as usize * self.width as usize + x as usize]
+    }
+
+    pub fn set(&mut self, x: u32, y: u32, color: FilterColor) {
+        self.pixels[y as usize * self.width as usize + x as usize] = color;
+    }
+
+    /// Rounds every pixel to what an 8 bit texture would store,
+    /// as the GPU does between each pass of a filter.
+    fn quantize(&mut self) {
+        for pixel in &mut self.pixels {
+            *pixel = pixel.map(|component| f32::from(to_byte(component)) / 255.0);
+        }
+    }
+}
+
+fn to_byte(component: f32) -> u8 {
+    (component.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+/// Whether `apply_filter` can apply this filter.
+pub fn is_supported(filter: &Filter) -> bool {
+    matches!(
+        filter,
+        Filter::BlurFilter(_)
+            | Filter::ColorMatrixFilter(_)
+            | Filter::ConvolutionFilter(_)
+            | Filter::DropShadowFilter(_)
+            | Filter::GlowFilter(_)
+    )
+}
+
+/// Runs `filter` over the given area of `source`, returning a new image of `source_size`
+/// that contains the result.
+///
+/// Returns `None` if this filter can't be applied on the CPU yet.
+pub fn apply_filter(
+    filter: &Filter,
+    source: &FilterImage,
+    source_point: (u32, u32),
+    source_size: (u32, u32),
+) -> Option<FilterImage> {
+    match filter {
+        Filter::BlurFilter(filter) => Some(apply_blur(filter, source, source_point, source_size)),
+        Filter::ColorMatrixFilter(filter) => Some(apply_color_matrix(filter, source, source_size)),
+        Filter::ConvolutionFilter(filter) => {
+            Some(apply_convolution(filter, source, source_point, source_size))
+        }
+        Filter::DropShadowFilter(filter) => {
+            Some(apply_drop_shadow(filter, source, source_point, source_size))
+        }
+        Filter::GlowFilter(filter) => Some(apply_drop_shadow(
+            &filter.clone().into(),
+            source,
+            source_point,
+            source_size,
+        )),
+        Filter::BevelFilter(_) | Filter::DisplacementMapFilter(_) => None,
+    }
+}
+
+/// Straightens a premultiplied color, treating a fully transparent one as transparent black.
+fn unmultiply(color: FilterColor) -> FilterColor {
+    if color[3] > 0.0 {
+        [
+            color[0] / color[3],
+            color[1] / color[3],
+            color[2] / color[3],
+            color[3],
+        ]
+    } else {
+        color
+    }
+}
+
+fn premultiply(color: FilterColor) -> FilterColor {
+    [
+        color[0] * color[3],
+        color[1] * color[3],
+        color[2] * color[3],
+        color[3],
+    ]
+}
+
+/// Like Flash, this reads from the top left of `source` whatever the position of the source rect.
+fn apply_color_matrix(
+    filter: &ColorMatrixFilter,
+    source: &FilterImage,
+    source_size: (u32, u32),
+) -> FilterImage {
+    let m = &filter.matrix;
+    let mut target = FilterImage::new(source_size.0, source_size.1);
+    for y in 0..source_size.1 {
+        for x in 0..source_size.0 {
+            let [r, g, b, a] = unmultiply(source.get(x as i32, y as i32));
+            let row = |i: usize| {
+                (m[i] * r + m[i + 1] * g + m[i + 2] * b + m[i + 3] * a + m[i + 4] / 255.0)
+                    .clamp(0.0, 1.0)
+            };
+            target.set(x, y, premultiply([row(0), row(5), row(10), row(15)]));
+        }
+    }
+    target
+}
+
+/// Blurs a single line along one axis, with a box of `whole` pixels on either side of the center
+/// and a partial pixel of `fraction` beyond those.
+///
+/// `line` holds the pixels being blurred with `edge` extra pixels on either side of them,
+/// and `output` receives the blurred pixels between those.
+fn blur_line(line: &[FilterColor], output: &mut [FilterColor], whole: usize, fraction: f32) {
+    let edge = whole + 1;
+    // Running totals make the cost of each pixel the same, no matter how large the box is
+    let mut totals = Vec::with_capacity(line.len() + 1);
+    let mut total = [0.0f64; 4];
+    totals.push(total);
+    for pixel in line {
+        for (sum, component) in total.iter_mut().zip(pixel) {
+            *sum += f64::from(*component);
+        }
+        totals.push(total);
+    }
+    let weight = 1.0 + 2.0 * whole as f32 + 2.0 * fraction;
+    for (i, out) in output.iter_mut().enumerate() {
+        // The box is centered on `line[i + edge]`, with its partial pixels at `i` and `i + 2 * edge`
+        let (start, end) = (&totals[i + 1], &totals[i + 2 * edge]);
+        *out = [0, 1, 2, 3].map(|c| {
+            let inside = (end[c] - start[c]) as f32;
+            let outside = (line[i][c] + line[i + 2 * edge][c]) * fraction;
+            (inside + outside) / weight
+        });
+    }
+}
+
+/// Runs one horizontal or vertical box blur pass, reading pixels relative to the result.
+fn blur_pass(
+    read: impl Fn(i32, i32) -> FilterColor,
+    size: (u32, u32),
+    full_size: f32,
+    horizontal: bool,
+) -> FilterImage {
+    let radius = ((full_size - 1.0) / 2.0).max(0.0);
+    let whole = radius.floor();
+    let fraction = radius - whole;
+    let whole = whole as usize;
+    let edge = whole as i32 + 1;
+
+    let mut target = FilterImage::new(size.0, size.1);
+    let (length, lines) = if horizontal {
+        (size.0, size.1)
+    } else {
+        (size.1, size.0)
+    };
+    let mut line = Vec::with_capacity(length as usize + 2 * edge as usize);
+    let mut output = vec![TRANSPARENT; length as usize];
+    for j in 0..lines {
+        line.clear();
+        line.extend((-edge..length as i32 + edge).map(|i| {
+            if horizontal {
+                read(i, j as i32)
+            } else {
+                read(j as i32, i)
+            }
+        }));
+        blur_line(&line, &mut output, whole, fraction);
+        for (i, color) in output.iter().enumerate() {
+            if horizontal {
+                target.set(i as u32, j, *color);
+            } else {
+                target.set(j, i as u32, *color);
+            }
+        }
+    }
+    target.quantize();
+    target
+}
+
+fn apply_blur(
+    filter: &BlurFilter,
+    source: &FilterImage,
+    source_point: (u32, u32),
+    source_size: (u32, u32),
+) -> FilterImage {
+    // Each quality level is another horizontal and vertical box blur, which makes the
+    // result look more and more like a gaussian blur. An axis that isn't blurred is skipped.
+    let mut passes = Vec::new();
+    for _ in 0..filter.quality.max(1) {
+        if filter.blur_x > 1.0 {
+            passes.push((filter.blur_x, true));
+        }
+        if filter.blur_y > 1.0 {
+            passes.push((filter.blur_y, false));
+        }
+    }
+
+    // The first pass reads the source area directly out of the source,
+    // including anything around that area which falls inside of the box
+    let (x, y) = (source_point.0 as i32, source_point.1 as i32);
+    let read_source = |dx: i32, dy: i32| source.get(x + dx, y + dy);
+    let Some((&(full_size, horizontal), rest)) = passes.split_first() else {
+        // Nothing to blur, but the source still needs to be copied into the result
+        return blur_pass(read_source, source_size, 0.0, true);
+    };
+    let mut target = blur_pass(read_source, source_size, full_size, horizontal);
+    for &(full_size, horizontal) in rest {
+        let previous = target;
+        target = blur_pass(
+            |x, y| previous.get(x, y),
+            source_size,
+            full_size,
+            horizontal,
+        );
+    }
+    target
+}
+
+/// Samples the alpha of an image between pixels, like a smoothed sampler that clamps to the edge.
+fn sample_alpha(image: &FilterImage, x: f32, y: f32) -> f32 {
+    let (x, y) = (x - 0.5, y - 0.5);
+    let (x0, y0) = (x.floor(), y.floor());
+    let (tx, ty) = (x - x0, y - y0);
+    let alpha = |x: f32, y: f32| {
+        let x = (x as i32).clamp(0, image.width as i32 - 1);
+        let y = (y as i32).clamp(0, image.height as i32 - 1);
+        image.get(x, y)[3]
+    };
+    let top = alpha(x0, y0) * (1.0 - tx) + alpha(x0 + 1.0, y0) * tx;
+    let bottom = alpha(x0, y0 + 1.0) * (1.0 - tx) + alpha(x0 + 1.0, y0 + 1.0) * tx;
+    top * (1.0 - ty) + bottom * ty
+}
+
+fn apply_drop_shadow(
+    filter: &DropShadowFilter,
+    source: &FilterImage,
+    source_point: (u32, u32),
+    source_size: (u32, u32),
+) -> FilterImage {
+    // The shadow is a blurred copy of the source, which is then tinted and offset
+    let blurred = apply_blur(
+        &BlurFilter {
+            blur_x: filter.blur_x,
+            blur_y: filter.blur_y,
+            quality: filter.quality,
+        },
+        source,
+        source_point,
+        source_size,
+    );
+    let color = [
+        f32::from(filter.color.r) / 255.0,
+        f32::from(filter.color.g) / 255.0,
+        f32::from(filter.color.b) / 255.0,
+        f32::from(filter.color.a) / 255.0,
+    ];
+    let offset = (
+        filter.angle.cos() * filter.distance,
+        filter.angle.sin() * filter.distance,
+    );
+    let size = (source_size.0 as f32, source_size.1 as f32);
+
+    let mut target = FilterImage::new(source_size.0, source_size.1);
+    for y in 0..source_size.1 {
+        for x in 0..source_size.0 {
+            let src = source.get((source_point.0 + x) as i32, (source_point.1 + y) as i32);
+
+            // Anything that has been moved in from outside of the source is transparent
+            let shadow_x = x as f32 + 0.5 - offset.0;
+            let shadow_y = y as f32 + 0.5 - offset.1;
+            let shadow_alpha =
+                if shadow_x >= 0.0 && shadow_y >= 0.0 && shadow_x < size.0 && shadow_y < size.1 {
+                    sample_alpha(&blurred, shadow_x, shadow_y)
+                } else {
+                    0.0
+                };
+
+            let result = if filter.inner {
+                // The shadow is cast by the area *around* the object, and only visible inside of it
+                let alpha = ((1.0 - shadow_alpha) * filter.strength).clamp(0.0, 1.0) * color[3];
+                let shadow = [
+                    color[0] * alpha * src[3],
+                    color[1] * alpha * src[3],
+                    color[2] * alpha * src[3],
+                    alpha * src[3],
+                ];
+                if filter.knockout || filter.hide_object {
+                    shadow
+                } else {
+                    [
+                        src[0] * (1.0 - alpha) + shadow[0],
+                        src[1] * (1.0 - alpha) + shadow[1],
+                        src[2] * (1.0 - alpha) + shadow[2],
+                        src[3],
+                    ]
+                }
+            } else {
+                let alpha = (shadow_alpha * filter.strength).clamp(0.0, 1.0) * color[3];
+                let shadow = [color[0] * alpha, color[1] * alpha, color[2] * alpha, alpha];
+                if filter.knockout {
+                    // Cut the object out of its shadow
+                    shadow.map(|component| component * (1.0 - src[3]))
+                } else if filter.hide_object {
+                    shadow
+                } else {
+                    // The object is drawn on top of its shadow
+                    [0, 1, 2, 3].map(|c| src[c] + shadow[c] * (1.0 - src[3]))
+                }
+            };
+            target.set(x, y, result);
+        }
+    }
+    target
+}
+
+fn apply_convolution(
+    filter: &ConvolutionFilter,
+    source: &FilterImage,
+    source_point: (u32, u32),
+    source_size: (u32, u32),
+) -> FilterImage {
+    let default_color = [
+        f32::from(filter.color.r) / 255.0,
+        f32::from(filter.color.g) / 255.0,
+        f32::from(filter.color.b) / 255.0,
+        f32::from(filter.color.a) / 255.0,
+    ];
+    // Reads the straight color of a pixel, relative to the top left of the filtered area
+    let load = |x: i32, y: i32| {
+        let (mut x, mut y) = (x, y);
+        let inside = x >= 0 && y >= 0 && x < source_size.0 as i32 && y < source_size.1 as i32;
+        if !inside {
+            if !filter.clamp {
+                return default_color;
+            }
+            x = x.clamp(0, source_size.0 as i32 - 1);
+            y = y.clamp(0, source_size.1 as i32 - 1);
+        }
+        unmultiply(source.get(source_point.0 as i32 + x, source_point.1 as i32 + y))
+    };
+
+    let matrix_x = u32::from(filter.matrix_x);
+    let matrix_y = u32::from(filter.matrix_y);
+    // The kernel is centered on the pixel, rounding towards the top left for even sizes
+    let center = ((matrix_x / 2) as i32, (matrix_y / 2) as i32);
+    // A divisor of 0 is treated as 1
+    let divisor = if filter.divisor == 0.0 {
+        1.0
+    } else {
+        filter.divisor
+    };
+
+    let mut target = FilterImage::new(source_size.0, source_size.1);
+    for y in 0..source_size.1 as i32 {
+        for x in 0..source_size.0 as i32 {
+            let mut sum = TRANSPARENT;
+            for ky in 0..matrix_y {
+                for kx in 0..matrix_x {
+                    let index = (ky * matrix_x + kx) as usize;
+                    let weight = filter.matrix.get(index).copied().unwrap_or_default();
+                    let color = load(x + kx as i32 - center.0, y + ky as i32 - center.1);
+                    for (total, component) in sum.iter_mut().zip(color) {
+                        *total += component * weight;
+                    }
+                }
+            }
+            let mut color =
+                sum.map(|total| (total / divisor + filter.bias / 255.0).clamp(0.0, 1.0));
+            if filter.preserve_alpha {
+                color[3] = load(x, y)[3];
+            }
+            target.set(x as u32, y as u32, premultiply(color));
+        }
+    }
+    target
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filters::GlowFilter;
+    use swf::Color;
+
+    fn solid(width: u32, height: u32, color: FilterColor) -> FilterImage {
+        let mut image = FilterImage::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                image.set(x, y, color);
+            }
+        }
+        image
+    }
+
+    fn total_alpha(image: &FilterImage) -> f32 {
+        image.pixels.iter().map(|pixel| pixel[3]).sum()
+    }
+
+    #[test]
+    fn rgba_round_trip() {
+        let rgba = [0, 64, 128, 255, 10, 0, 5, 20];
+        assert_eq!(FilterImage::from_rgba(2, 1, &rgba).to_rgba(), rgba);
+    }
+
+    #[test]
+    fn blur_spreads_evenly() {
+        let mut source = FilterImage::new(9, 9);
+        source.set(4, 4, [1.0; 4]);
+        let filter = Filter::BlurFilter(BlurFilter {
+            blur_x: 3.0,
+            blur_y: 3.0,
+            quality: 1,
+        });
+        let blurred = apply_filter(&filter, &source, (0, 0), (9, 9)).unwrap();
+
+        // A box of 3 pixels averages each pixel with its neighbours
+        for y in 0..9 {
+            for x in 0..9 {
+                let expected = if (3..=5).contains(&x) && (3..=5).contains(&y) {
+                    to_byte(to_byte(1.0 / 3.0) as f32 / 255.0 / 3.0)
+                } else {
+                    0
+                };
+                assert_eq!(
+                    to_byte(blurred.get(x, y)[3]),
+                    expected,
+                    "Pixel at ({x}, {y})"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn blur_of_fractional_size() {
+        let mut source = FilterImage::new(5, 1);
+        source.set(2, 0, [1.0; 4]);
+        let filter = Filter::BlurFilter(BlurFilter {
+            blur_x: 2.0,
+            blur_y: 0.0,
+            quality: 1,
+        });
+        let blurred = apply_filter(&filter, &source, (0, 0), (5, 1)).unwrap();
+
+        // The neighbours on each side only count for half of a pixel
+        assert_eq!(to_byte(blurred.get(2, 0)[3]), to_byte(1.0 / 2.0));
+        assert_eq!(to_byte(blurred.get(1, 0)[3]), to_byte(0.25));
+        assert_eq!(to_byte(blurred.get(3, 0)[3]), to_byte(0.25));
+        assert_eq!(blurred.get(0, 0), TRANSPARENT);
+    }
+
+    #[test]
+    fn blur_reads_around_the_source_area() {
+        let source = solid(8, 8, [1.0; 4]);
+        let filter = Filter::BlurFilter(BlurFilter {
+            blur_x: 5.0,
+            blur_y: 0.0,
+            quality: 1,
+        });
+        // The area is surrounded by more of the same color, so blurring it changes nothing
+        let blurred = apply_filter(&filter, &source, (2, 2), (4, 4)).unwrap();
+        assert_eq!(blurred.get(0, 0), [1.0; 4]);
+        assert_eq!(blurred.get(3, 3), [1.0; 4]);
+    }
+
+    #[test]
+    fn blur_without_size_copies_area() {
+        let mut source = FilterImage::new(4, 4);
+        source.set(1, 2, [0.2, 0.4, 0.0, 0.4]);
+        let filter = Filter::BlurFilter(BlurFilter {
+            blur_x: 1.0,
+            blur_y: 0.0,
+            quality: 2,
+        });
+        let copy = apply_filter(&filter, &source, (1, 1), (2, 2)).unwrap();
+        assert_eq!(
+            copy.to_rgba(),
+            [0, 0, 0, 0, 0, 0, 0, 0, 51, 102, 0, 102, 0, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn higher_quality_spreads_further() {
+        let mut source = FilterImage::new(21, 21);
+        for y in 8..13 {
+            for x in 8..13 {
+                source.set(x, y, [1.0; 4]);
+            }
+        }
+        let blur = |quality| {
+            let filter = Filter::BlurFilter(BlurFilter {
+                blur_x: 5.0,
+                blur_y: 5.0,
+                quality,
+            });
+            apply_filter(&filter, &source, (0, 0), (21, 21)).unwrap()
+        };
+        // Each pass spreads the block out by another 2 pixels
+        assert_eq!(blur(1).get(10, 15), TRANSPARENT);
+        assert_ne!(blur(3).get(10, 15), TRANSPARENT);
+    }
+
+    #[test]
+    fn identity_color_matrix() {
+        let source = solid(2, 2, [0.1, 0.2, 0.3, 0.5]);
+        let filtered = apply_filter(&Filter::default(), &source, (0, 0), (2, 2)).unwrap();
+        assert_eq!(filtered.to_rgba(), source.to_rgba());
+    }
+
+    #[test]
+    fn color_matrix_uses_straight_colors() {
+        let source = solid(1, 1, [0.25, 0.0, 0.0, 0.5]);
+        let mut filter = ColorMatrixFilter::default();
+        // Red into green, with everything else removed
+        filter.matrix[0] = 0.0;
+        filter.matrix[5] = 1.0;
+        filter.matrix[6] = 0.0;
+        filter.matrix[12] = 0.0;
+        let filtered =
+            apply_filter(&Filter::ColorMatrixFilter(filter), &source, (0, 0), (1, 1)).unwrap();
+        assert_eq!(filtered.get(0, 0), [0.0, 0.25, 0.0, 0.5]);
+    }
+
+    #[test]
+    fn color_matrix_reads_from_top_left() {
+        let mut source = solid(2, 1, [0.0, 0.0, 0.0, 1.0]);
+        source.set(1, 0, [1.0; 4]);
+        let filtered = apply_filter(&Filter::default(), &source, (1, 0), (1, 1)).unwrap();
+        assert_eq!(filtered.get(0, 0), [0.0, 0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn convolution_identity_kernel() {
+        let mut source = FilterImage::new(3, 3);
+        source.set(1, 1, [0.0, 0.5, 0.0, 0.5]);
+        let filter = Filter::ConvolutionFilter(ConvolutionFilter {
+            matrix_x: 3,
+            matrix_y: 3,
+            matrix: vec![0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0],
+            preserve_alpha: false,
+            ..Default::default()
+        });
+        let filtered = apply_filter(&filter, &source, (0, 0), (3, 3)).unwrap();
+        assert_eq!(filtered.to_rgba(), source.to_rgba());
+    }
+
+    #[test]
+    fn convolution_edges() {
+        let source = solid(2, 1, [1.0; 4]);
+        let shift_left = |clamp| ConvolutionFilter {
+            matrix_x: 3,
+            matrix_y: 1,
+            matrix: vec![0.0, 0.0, 1.0],
+            preserve_alpha: false,
+            clamp,
+            color: Color::from_rgb(0x0000FF, 255),
+            ..Default::default()
+        };
+        let clamped = apply_filter(
+            &Filter::ConvolutionFilter(shift_left(true)),
+            &source,
+            (0, 0),
+            (2, 1),
+        )
+        .unwrap();
+        assert_eq!(clamped.get(1, 0), [1.0; 4]);
+        let colored = apply_filter(
+            &Filter::ConvolutionFilter(shift_left(false)),
+            &source,
+            (0, 0),
+            (2, 1),
+        )
+        .unwrap();
+        assert_eq!(colored.get(1, 0), [0.0, 0.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn drop_shadow_offsets_behind_object() {
+        let mut source = FilterImage::new(6, 6);
+        source.set(1, 1, [1.0; 4]);
+        let filter = Filter::DropShadowFilter(DropShadowFilter {
+            color: Color::from_rgb(0x00FF00, 255),
+            angle: 0.0,
+            distance: 2.0,
+            blur_x: 0.0,
+            blur_y: 0.0,
+            ..Default::default()
+        });
+        let filtered = apply_filter(&filter, &source, (0, 0), (6, 6)).unwrap();
+        assert_eq!(filtered.get(1, 1), [1.0; 4]);
+        assert_eq!(filtered.get(3, 1), [0.0, 1.0, 0.0, 1.0]);
+        assert_eq!(filtered.get(2, 1), TRANSPARENT);
+    }
+
+    #[test]
+    fn knockout_glow_removes_object() {
+        let source = solid(4, 4, [1.0; 4]);
+        let filter = Filter::GlowFilter(GlowFilter {
+            knockout: true,
+            ..Default::default()
+        });
+        let filtered = apply_filter(&filter, &source, (0, 0), (4, 4)).unwrap();
+        assert_eq!(total_alpha(&filtered), 0.0);
+    }
+
+    #[test]
+    fn inner_glow_stays_inside() {
+        let mut source = FilterImage::new(8, 8);
+        for y in 2..6 {
+            for x in 2..6 {
+                source.set(x, y, [1.0; 4]);
+            }
+        }
+        let filter = Filter::GlowFilter(GlowFilter {
+            inner: true,
+            knockout: true,
+            ..Default::default()
+        });
+        let filtered = apply_filter(&filter, &source, (0, 0), (8, 8)).unwrap();
+        assert_eq!(filtered.get(1, 1), TRANSPARENT);
+        assert!(filtered.get(2, 2)[3] > 0.0);
+    }
+
+    #[test]
+    fn unsupported_filters() {
+        let filter = Filter::BevelFilter(Default::default());
+        assert!(!is_supported(&filter));
+        assert!(apply_filter(&filter, &FilterImage::new(1, 1), (0, 0), (1, 1)).is_none());
+        assert!(is_supported(&Filter::default()));
+    }
+}
@@ -7,6 +7,7 @@ pub mod color_transform;
 pub mod error;
 pub mod filters;
 pub mod matrix;
+pub mod matrix3d;
 pub mod shape_utils;
 pub mod transform;
 pub mod utils;
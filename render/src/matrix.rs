@@ -131,6 +131,23 @@ impl Matrix {
             ty: Twips::new(out_ty),
         };
     }
+
+    /// How much more this matrix stretches things in one direction than in another,
+    /// as the ratio between the largest and smallest scale it applies in any direction.
+    ///
+    /// This is 1.0 for any combination of uniform scaling and rotation, and grows as the matrix
+    /// is skewed or scaled unevenly. It's infinite if the matrix flattens everything to a line.
+    pub fn anisotropy(&self) -> f32 {
+        // The singular values of the 2x2 part of the matrix
+        let q = f32::hypot((self.a + self.d) / 2.0, (self.b - self.c) / 2.0);
+        let r = f32::hypot((self.a - self.d) / 2.0, (self.b + self.c) / 2.0);
+        let (max_scale, min_scale) = (q + r, (q - r).abs());
+        if max_scale == 0.0 {
+            1.0
+        } else {
+            max_scale / min_scale
+        }
+    }
 }
 
 impl std::ops::Mul for Matrix {
@@ -189,7 +206,7 @@ impl std::ops::MulAssign for Matrix {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use approx::{assert_ulps_eq, AbsDiffEq, UlpsEq};
+    use approx::{assert_abs_diff_eq, assert_ulps_eq, AbsDiffEq, UlpsEq};
 
     macro_rules! test_invert {
         ( $test: ident, $($args: expr),* ) => {
@@ -749,6 +766,28 @@ mod tests {
             (Twips::new(141), Twips::ZERO)
         )
     );
+
+    #[test]
+    fn anisotropy() {
+        assert_abs_diff_eq!(Matrix::IDENTITY.anisotropy(), 1.0);
+        assert_abs_diff_eq!(Matrix::scale(3.0, 3.0).anisotropy(), 1.0);
+        assert_abs_diff_eq!(Matrix::scale(4.0, -2.0).anisotropy(), 2.0);
+        assert_abs_diff_eq!(Matrix::rotate(1.0).anisotropy(), 1.0, epsilon = 1e-6);
+        assert_abs_diff_eq!(
+            (Matrix::rotate(0.5) * Matrix::scale(1.0, 0.25)).anisotropy(),
+            4.0,
+            epsilon = 1e-5
+        );
+
+        // A shear by 1 stretches a square along its diagonal by the golden ratio, squared
+        let skew = Matrix {
+            c: 1.0,
+            ..Matrix::IDENTITY
+        };
+        assert_abs_diff_eq!(skew.anisotropy(), 2.618_034, epsilon = 1e-5);
+
+        assert_eq!(Matrix::scale(1.0, 0.0).anisotropy(), f32::INFINITY);
+    }
 }
 
 impl From<swf::Matrix> for Matrix {
@@ -0,0 +1,213 @@
+//! 3D transformation matrices, for display objects that have a `z` or a 3D rotation.
+
+use crate::matrix::Matrix;
+use swf::Twips;
+
+/// A 4x4 transformation matrix, laid out the same as the `rawData` of an AS3 `Matrix3D`.
+///
+/// The elements are stored column by column, so the translation (in pixels) is in elements
+/// 12 to 14, and points are transformed as column vectors.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Matrix3D {
+    pub raw_data: [f64; 16],
+}
+
+impl Matrix3D {
+    pub const IDENTITY: Self = Self {
+        raw_data: [
+            1.0, 0.0, 0.0, 0.0, //
+            0.0, 1.0, 0.0, 0.0, //
+            0.0, 0.0, 1.0, 0.0, //
+            0.0, 0.0, 0.0, 1.0, //
+        ],
+    };
+
+    pub fn translate(x: f64, y: f64, z: f64) -> Self {
+        let mut matrix = Self::IDENTITY;
+        matrix.raw_data[12] = x;
+        matrix.raw_data[13] = y;
+        matrix.raw_data[14] = z;
+        matrix
+    }
+
+    pub fn scale(x: f64, y: f64, z: f64) -> Self {
+        let mut matrix = Self::IDENTITY;
+        matrix.raw_data[0] = x;
+        matrix.raw_data[5] = y;
+        matrix.raw_data[10] = z;
+        matrix
+    }
+
+    /// A rotation around the X axis, which tilts the top of an object away from the viewer.
+    pub fn rotate_x(radians: f64) -> Self {
+        let (sin, cos) = radians.sin_cos();
+        let mut matrix = Self::IDENTITY;
+        matrix.raw_data[5] = cos;
+        matrix.raw_data[6] = sin;
+        matrix.raw_data[9] = -sin;
+        matrix.raw_data[10] = cos;
+        matrix
+    }
+
+    /// A rotation around the Y axis, which turns the right of an object towards the viewer.
+    pub fn rotate_y(radians: f64) -> Self {
+        let (sin, cos) = radians.sin_cos();
+        let mut matrix = Self::IDENTITY;
+        matrix.raw_data[0] = cos;
+        matrix.raw_data[2] = -sin;
+        matrix.raw_data[8] = sin;
+        matrix.raw_data[10] = cos;
+        matrix
+    }
+
+    /// A rotation around the Z axis, the same as a 2D rotation.
+    pub fn rotate_z(radians: f64) -> Self {
+        let (sin, cos) = radians.sin_cos();
+        let mut matrix = Self::IDENTITY;
+        matrix.raw_data[0] = cos;
+        matrix.raw_data[1] = sin;
+        matrix.raw_data[4] = -sin;
+        matrix.raw_data[5] = cos;
+        matrix
+    }
+
+    /// A perspective projection onto the plane at `z = 0`, as seen from `focal_length` pixels
+    /// in front of `center`.
+    ///
+    /// Anything further away is drawn smaller and closer to the center. The depth of every point
+    /// is flattened to 0 afterwards, as nothing past the projection uses it.
+    pub fn perspective(focal_length: f64, center: (f64, f64)) -> Self {
+        let mut projection = Self::IDENTITY;
+        projection.raw_data[10] = 0.0;
+        projection.raw_data[11] = 1.0 / focal_length;
+        Self::translate(center.0, center.1, 0.0)
+            * projection
+            * Self::translate(-center.0, -center.1, 0.0)
+    }
+
+    /// The element in the given column and row.
+    pub fn get(&self, column: usize, row: usize) -> f64 {
+        self.raw_data[column * 4 + row]
+    }
+
+    /// The 2D matrix that this is closest to, with any depth and perspective ignored.
+    pub fn to_affine(&self) -> Matrix {
+        Matrix {
+            a: self.raw_data[0] as f32,
+            b: self.raw_data[1] as f32,
+            c: self.raw_data[4] as f32,
+            d: self.raw_data[5] as f32,
+            tx: Twips::from_pixels(self.raw_data[12]),
+            ty: Twips::from_pixels(self.raw_data[13]),
+        }
+    }
+
+    /// Transforms a point, including the division by its `w` that a perspective calls for.
+    pub fn transform_point(&self, (x, y, z): (f64, f64, f64)) -> (f64, f64, f64) {
+        let row = |row| {
+            self.get(0, row) * x + self.get(1, row) * y + self.get(2, row) * z + self.get(3, row)
+        };
+        let w = row(3);
+        (row(0) / w, row(1) / w, row(2) / w)
+    }
+}
+
+impl Default for Matrix3D {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl From<Matrix> for Matrix3D {
+    fn from(matrix: Matrix) -> Self {
+        Self {
+            raw_data: [
+                matrix.a.into(),
+                matrix.b.into(),
+                0.0,
+                0.0,
+                matrix.c.into(),
+                matrix.d.into(),
+                0.0,
+                0.0,
+                0.0,
+                0.0,
+                1.0,
+                0.0,
+                matrix.tx.to_pixels(),
+                matrix.ty.to_pixels(),
+                0.0,
+                1.0,
+            ],
+        }
+    }
+}
+
+impl std::ops::Mul for Matrix3D {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        let mut raw_data = [0.0; 16];
+        for column in 0..4 {
+            for row in 0..4 {
+                raw_data[column * 4 + row] =
+                    (0..4).map(|i| self.get(i, row) * rhs.get(column, i)).sum();
+            }
+        }
+        Self { raw_data }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_abs_diff_eq;
+
+    #[test]
+    fn affine_round_trip() {
+        let matrix = Matrix {
+            a: 2.0,
+            b: 0.5,
+            c: -0.25,
+            d: 3.0,
+            tx: Twips::from_pixels(10.0),
+            ty: Twips::from_pixels(-20.0),
+        };
+        assert_eq!(Matrix3D::from(matrix).to_affine(), matrix);
+    }
+
+    #[test]
+    fn multiply_matches_affine() {
+        let a = Matrix::rotate(0.5) * Matrix::scale(2.0, 3.0);
+        let b = Matrix::translate(Twips::from_pixels(5.0), Twips::from_pixels(7.0));
+        let product = (Matrix3D::from(a) * Matrix3D::from(b)).to_affine();
+        let expected = a * b;
+        assert_abs_diff_eq!(product.a, expected.a, epsilon = 1e-6);
+        assert_abs_diff_eq!(product.b, expected.b, epsilon = 1e-6);
+        assert_abs_diff_eq!(product.c, expected.c, epsilon = 1e-6);
+        assert_abs_diff_eq!(product.d, expected.d, epsilon = 1e-6);
+        assert_eq!(product.tx, expected.tx);
+        assert_eq!(product.ty, expected.ty);
+    }
+
+    #[test]
+    fn perspective_shrinks_towards_center() {
+        let projection = Matrix3D::perspective(100.0, (50.0, 50.0));
+        // Nothing changes on the projection plane
+        let (x, y, _) = projection.transform_point((0.0, 100.0, 0.0));
+        assert_abs_diff_eq!(x, 0.0);
+        assert_abs_diff_eq!(y, 100.0);
+        // Twice as far away is half the distance from the center
+        let (x, y, z) = projection.transform_point((0.0, 100.0, 100.0));
+        assert_abs_diff_eq!(x, 25.0);
+        assert_abs_diff_eq!(y, 75.0);
+        assert_abs_diff_eq!(z, 0.0);
+    }
+
+    #[test]
+    fn rotate_y_turns_edge_away() {
+        let rotation = Matrix3D::rotate_y(std::f64::consts::FRAC_PI_2);
+        let (x, _, z) = rotation.transform_point((1.0, 0.0, 0.0));
+        assert_abs_diff_eq!(x, 0.0, epsilon = 1e-12);
+        assert_abs_diff_eq!(z, -1.0, epsilon = 1e-12);
+    }
+}
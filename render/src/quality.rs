@@ -69,7 +69,7 @@ impl StageQuality {
             StageQuality::Low => 1,
             StageQuality::Medium => 2,
             StageQuality::High => 4,
-            StageQuality::Best => 4,
+            StageQuality::Best => 8,
             StageQuality::High8x8 => 8,
             StageQuality::High8x8Linear => 8,
             StageQuality::High16x16 => 16,
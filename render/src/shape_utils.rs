@@ -59,10 +59,10 @@ pub fn calculate_shape_bounds(shape_records: &[swf::ShapeRecord]) -> swf::Rectan
     bounds
 }
 
-/// `DrawPath` represents a solid fill or a stroke.
+/// `DrawPath` represents a solid fill, a stroke or a list of filled triangles.
 /// Fills are always closed paths, while strokes may be open or closed.
 /// Closed paths will have the first point equal to the last point.
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum DrawPath<'a> {
     Stroke {
         style: &'a LineStyle,
@@ -72,17 +72,116 @@ pub enum DrawPath<'a> {
     Fill {
         style: &'a FillStyle,
         commands: Vec<DrawCommand>,
+        winding_rule: FillRule,
+    },
+    Triangles {
+        style: &'a FillStyle,
+        triangles: &'a TriangleList,
     },
 }
 
+/// How the insides of a fill are determined where its outline overlaps itself.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum FillRule {
+    /// Anything that is surrounded by an odd number of edges is filled.
+    /// This is what SWF shapes use, unless they're flagged as using the fill winding rule.
+    #[default]
+    EvenOdd,
+
+    /// Anything that the outline winds around at all is filled.
+    NonZero,
+}
+
+/// Triangles that are filled as they are, without being tessellated,
+/// such as those drawn by `Graphics.drawTriangles`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TriangleList {
+    /// The corners of the triangles.
+    pub vertices: Vec<(Twips, Twips)>,
+
+    /// Every three indices into `vertices` make up a triangle.
+    pub indices: Vec<u32>,
+
+    /// The position of each vertex on the bitmap of a bitmap fill, from 0 to 1 across it.
+    /// Without these, the bitmap is placed by the matrix of the fill as usual.
+    pub uvs: Option<Vec<(f32, f32)>>,
+}
+
+impl TriangleList {
+    /// The corners of each triangle, along with their texture coordinates if there are any.
+    pub fn triangles(
+        &self,
+    ) -> impl Iterator<Item = ([(Twips, Twips); 3], Option<[(f32, f32); 3]>)> + '_ {
+        self.indices.chunks_exact(3).map(|indices| {
+            let corner = |i: usize| self.vertices[indices[i] as usize];
+            let uv = |uvs: &Vec<(f32, f32)>, i: usize| uvs[indices[i] as usize];
+            (
+                [corner(0), corner(1), corner(2)],
+                self.uvs
+                    .as_ref()
+                    .map(|uvs| [uv(uvs, 0), uv(uvs, 1), uv(uvs, 2)]),
+            )
+        })
+    }
+}
+
+/// The bitmap fill matrix that maps a bitmap of the given size onto a triangle,
+/// so that each corner of the triangle shows the bitmap at its texture coordinates.
+///
+/// Returns `None` if the texture coordinates don't span an area of the bitmap.
+pub fn triangle_bitmap_matrix(
+    corners: [(Twips, Twips); 3],
+    uvs: [(f32, f32); 3],
+    bitmap_width: u32,
+    bitmap_height: u32,
+) -> Option<Matrix> {
+    // Bitmap fill matrices map from the bitmap in twips, the same as the shape
+    let texel = |(u, v): (f32, f32)| {
+        (
+            f64::from(u) * f64::from(bitmap_width) * f64::from(Twips::TWIPS_PER_PIXEL),
+            f64::from(v) * f64::from(bitmap_height) * f64::from(Twips::TWIPS_PER_PIXEL),
+        )
+    };
+    let corner = |(x, y): (Twips, Twips)| (f64::from(x.get()), f64::from(y.get()));
+    let [t0, t1, t2] = uvs.map(texel);
+    let [p0, p1, p2] = corners.map(corner);
+
+    // Solve for the matrix that maps the edges of the triangle on the bitmap to its edges
+    let (u1, v1) = (t1.0 - t0.0, t1.1 - t0.1);
+    let (u2, v2) = (t2.0 - t0.0, t2.1 - t0.1);
+    let det = u1 * v2 - u2 * v1;
+    if det.abs() < f64::EPSILON {
+        return None;
+    }
+    let (x1, y1) = (p1.0 - p0.0, p1.1 - p0.1);
+    let (x2, y2) = (p2.0 - p0.0, p2.1 - p0.1);
+    let a = (x1 * v2 - x2 * v1) / det;
+    let c = (x2 * u1 - x1 * u2) / det;
+    let b = (y1 * v2 - y2 * v1) / det;
+    let d = (y2 * u1 - y1 * u2) / det;
+    Some(Matrix {
+        a: a as f32,
+        b: b as f32,
+        c: c as f32,
+        d: d as f32,
+        tx: Twips::new((p0.0 - a * t0.0 - c * t0.1) as i32),
+        ty: Twips::new((p0.1 - b * t0.0 - d * t0.1) as i32),
+    })
+}
+
 /// `DistilledShape` represents a ready-to-be-consumed collection of paths (both fills and strokes)
 /// that has been converted down from another source (such as SWF's `swf::Shape` format).
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct DistilledShape<'a> {
     pub paths: Vec<DrawPath<'a>>,
     pub shape_bounds: BoundingBox,
     pub edge_bounds: BoundingBox,
     pub id: CharacterId,
+
+    /// The transform that the shape will be drawn with, if its strokes have to be tessellated
+    /// for it. Otherwise, the strokes are simply scaled along with the rest of the shape.
+    /// See [`StrokeScaling`].
+    pub stroke_matrix: Option<Matrix>,
 }
 
 impl<'a> From<&'a swf::Shape> for DistilledShape<'a> {
@@ -92,6 +191,7 @@ impl<'a> From<&'a swf::Shape> for DistilledShape<'a> {
             shape_bounds: (&shape.shape_bounds).into(),
             edge_bounds: (&shape.edge_bounds).into(),
             id: shape.id,
+            stroke_matrix: None,
         }
     }
 }
@@ -334,6 +434,9 @@ pub struct ShapeConverter<'a> {
     fill_style1: ActivePath,
     line_style: ActivePath,
 
+    // How the fills are filled where they overlap themselves.
+    winding_rule: FillRule,
+
     // Paths. These get flushed for each new layer.
     fills: Vec<PendingPath>,
     strokes: Vec<PendingPath>,
@@ -359,6 +462,12 @@ impl<'a> ShapeConverter<'a> {
             fill_style1: ActivePath::new(),
             line_style: ActivePath::new(),
 
+            winding_rule: if shape.flags.contains(swf::ShapeFlag::HAS_FILL_WINDING_RULE) {
+                FillRule::NonZero
+            } else {
+                FillRule::EvenOdd
+            },
+
             fills: vec![PendingPath::new(); shape.styles.fill_styles.len()],
             strokes: vec![PendingPath::new(); shape.styles.line_styles.len()],
 
@@ -513,6 +622,7 @@ impl<'a> ShapeConverter<'a> {
             self.commands.push(DrawPath::Fill {
                 style,
                 commands: path.to_draw_commands().collect(),
+                winding_rule: self.winding_rule,
             });
             path.segments.clear();
         }
@@ -621,6 +731,7 @@ mod tests {
                     y: Twips::from_pixels(100.0),
                 },
             ],
+            winding_rule: FillRule::EvenOdd,
         }];
         assert_eq!(commands, expected);
     }
@@ -685,9 +796,259 @@ mod tests {
                     y: Twips::from_pixels(100.0),
                 },
             ],
+            winding_rule: FillRule::EvenOdd,
         }];
         assert_eq!(commands, expected);
     }
+
+    /// Strokes that don't scale, or that are too thin to scale, are tessellated for the transform.
+    #[test]
+    fn stroke_scaling() {
+        let scaling = |style: LineStyle| {
+            StrokeScaling::from_paths(&[DrawPath::Stroke {
+                style: &style,
+                is_closed: false,
+                commands: vec![],
+            }])
+        };
+        let thick = scaling(LineStyle::new().with_width(Twips::from_pixels(2.0)));
+        assert!(!thick.is_needed(&Matrix::scale(4.0, 4.0)));
+        assert!(thick.is_needed(&Matrix::scale(0.25, 0.25)));
+
+        let hairline = scaling(LineStyle::new().with_width(Twips::ZERO));
+        assert!(hairline.is_needed(&Matrix::default()));
+
+        let non_scaling = scaling(
+            LineStyle::new()
+                .with_width(Twips::from_pixels(2.0))
+                .with_allow_scale_x(false)
+                .with_allow_scale_y(false),
+        );
+        assert!(non_scaling.is_needed(&Matrix::default()));
+        assert_eq!(StrokeScaling::from_paths(&[]), StrokeScaling::default());
+    }
+
+    /// Tessellating for the transform keeps strokes at their final width on screen.
+    #[test]
+    fn untransformed_width() {
+        let matrix = Matrix::scale(4.0, 4.0);
+        let mut line_scales = LineScales::new(&matrix);
+        assert_eq!(
+            line_scales.untransformed_width(2.0, LineScaleMode::Both),
+            2.0
+        );
+        assert_eq!(
+            line_scales.untransformed_width(2.0, LineScaleMode::None),
+            0.5
+        );
+        // A hairline is a single pixel wide at any scale
+        assert_eq!(
+            line_scales.untransformed_width(0.0, LineScaleMode::Both),
+            0.25
+        );
+    }
+
+    /// Overlapping outlines that wind the same way are only filled with the non-zero rule.
+    #[test]
+    fn fill_rules() {
+        let square = |size: f64| {
+            let size = Twips::from_pixels(size);
+            [
+                DrawCommand::MoveTo {
+                    x: Twips::ZERO,
+                    y: Twips::ZERO,
+                },
+                DrawCommand::LineTo {
+                    x: size,
+                    y: Twips::ZERO,
+                },
+                DrawCommand::LineTo { x: size, y: size },
+                DrawCommand::LineTo {
+                    x: Twips::ZERO,
+                    y: size,
+                },
+                DrawCommand::LineTo {
+                    x: Twips::ZERO,
+                    y: Twips::ZERO,
+                },
+            ]
+        };
+        let commands = [square(100.0), square(50.0)].concat();
+        let inner = (Twips::from_pixels(25.0), Twips::from_pixels(25.0));
+        let outer = (Twips::from_pixels(75.0), Twips::from_pixels(75.0));
+        assert!(!draw_command_fill_hit_test(
+            &commands,
+            FillRule::EvenOdd,
+            inner
+        ));
+        assert!(draw_command_fill_hit_test(
+            &commands,
+            FillRule::EvenOdd,
+            outer
+        ));
+        assert!(draw_command_fill_hit_test(
+            &commands,
+            FillRule::NonZero,
+            inner
+        ));
+        assert!(draw_command_fill_hit_test(
+            &commands,
+            FillRule::NonZero,
+            outer
+        ));
+    }
+
+    /// Makes a shape out of straight edges from `(0, 0)` through each of the points, in pixels.
+    fn polyline_shape(
+        points: &[(f64, f64)],
+        fill_style: Option<u32>,
+        line_styles: Vec<LineStyle>,
+        flags: swf::ShapeFlag,
+    ) -> swf::Shape {
+        let mut records = vec![ShapeRecord::StyleChange(Box::new(swf::StyleChangeData {
+            move_to: Some((Twips::ZERO, Twips::ZERO)),
+            fill_style_0: None,
+            fill_style_1: fill_style,
+            line_style: (!line_styles.is_empty()).then_some(1),
+            new_styles: None,
+        }))];
+        let mut last = (0.0, 0.0);
+        for &(x, y) in points {
+            records.push(ShapeRecord::StraightEdge {
+                delta_x: Twips::from_pixels(x - last.0),
+                delta_y: Twips::from_pixels(y - last.1),
+            });
+            last = (x, y);
+        }
+        swf::Shape {
+            flags,
+            styles: swf::ShapeStyles {
+                fill_styles: FILL_STYLES.to_vec(),
+                line_styles,
+            },
+            ..build_shape(records)
+        }
+    }
+
+    /// Strokes are hit where their caps and joins are drawn, depending on their style.
+    #[test]
+    fn stroke_caps_and_joins() {
+        let stroke = |cap, join| {
+            let style = LineStyle::new()
+                .with_width(Twips::from_pixels(20.0))
+                .with_start_cap(cap)
+                .with_end_cap(cap)
+                .with_join_style(join);
+            ShapeHitTester::new(&polyline_shape(
+                &[(100.0, 0.0), (100.0, 100.0)],
+                None,
+                vec![style],
+                swf::ShapeFlag::empty(),
+            ))
+        };
+        let hits = |tester: &ShapeHitTester, x, y| {
+            tester.hit_test(
+                (Twips::from_pixels(x), Twips::from_pixels(y)),
+                &Matrix::IDENTITY,
+            )
+        };
+
+        let round = stroke(swf::LineCapStyle::Round, swf::LineJoinStyle::Round);
+        let square = stroke(
+            swf::LineCapStyle::Square,
+            swf::LineJoinStyle::Miter(swf::Fixed8::from_f32(3.0)),
+        );
+        let none = stroke(swf::LineCapStyle::None, swf::LineJoinStyle::Bevel);
+        for tester in [&round, &square, &none] {
+            // Along the edges
+            assert!(hits(tester, 50.0, 9.0));
+            assert!(hits(tester, 91.0, 50.0));
+            assert!(!hits(tester, 50.0, 11.0));
+        }
+
+        // Just past the start, and in the corner of a square cap
+        assert!(hits(&round, -9.0, 0.0));
+        assert!(!hits(&round, -8.0, 8.0));
+        assert!(hits(&square, -8.0, 8.0));
+        assert!(!hits(&none, -1.0, 0.0));
+
+        // In the outside corner of the join
+        assert!(!hits(&round, 108.0, -8.0));
+        assert!(hits(&square, 108.0, -8.0));
+        assert!(!hits(&none, 108.0, -8.0));
+        assert!(hits(&none, 104.0, -4.0));
+    }
+
+    /// Overlapping outlines are only filled twice over with the fill winding rule.
+    #[test]
+    fn shape_fill_winding_rule() {
+        // Two squares drawn in the same direction, the second one inside of the first
+        let points = [
+            (100.0, 0.0),
+            (100.0, 100.0),
+            (0.0, 100.0),
+            (0.0, 0.0),
+            (50.0, 0.0),
+            (50.0, 50.0),
+            (0.0, 50.0),
+            (0.0, 0.0),
+        ];
+        let inner = (Twips::from_pixels(25.0), Twips::from_pixels(25.0));
+        let outer = (Twips::from_pixels(75.0), Twips::from_pixels(75.0));
+
+        let even_odd = polyline_shape(&points, Some(1), vec![], swf::ShapeFlag::empty());
+        assert!(!shape_hit_test(&even_odd, inner, &Matrix::IDENTITY));
+        assert!(shape_hit_test(&even_odd, outer, &Matrix::IDENTITY));
+
+        let non_zero = polyline_shape(
+            &points,
+            Some(1),
+            vec![],
+            swf::ShapeFlag::HAS_FILL_WINDING_RULE,
+        );
+        assert!(shape_hit_test(&non_zero, inner, &Matrix::IDENTITY));
+        assert!(shape_hit_test(&non_zero, outer, &Matrix::IDENTITY));
+    }
+
+    /// The corners of a triangle show the bitmap at their texture coordinates.
+    #[test]
+    fn triangle_bitmap_matrix() {
+        let corners = [
+            (Twips::ZERO, Twips::ZERO),
+            (Twips::from_pixels(100.0), Twips::ZERO),
+            (Twips::from_pixels(100.0), Twips::from_pixels(50.0)),
+        ];
+        let matrix =
+            super::triangle_bitmap_matrix(corners, [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0)], 10, 10);
+        assert_eq!(matrix, Some(Matrix::scale(10.0, 5.0)));
+
+        let flipped =
+            super::triangle_bitmap_matrix(corners, [(1.0, 0.0), (0.0, 0.0), (0.0, 1.0)], 10, 10);
+        assert_eq!(
+            flipped,
+            Some(Matrix {
+                a: -10.0,
+                d: 5.0,
+                tx: Twips::from_pixels(100.0),
+                ..Default::default()
+            })
+        );
+
+        // A bitmap that is squashed into a line can't be stretched back out
+        let degenerate =
+            super::triangle_bitmap_matrix(corners, [(0.0, 0.0), (1.0, 0.0), (0.5, 0.0)], 10, 10);
+        assert_eq!(degenerate, None);
+
+        let triangles = TriangleList {
+            vertices: corners.to_vec(),
+            indices: vec![0, 1, 2],
+            uvs: None,
+        };
+        let inside = (Twips::from_pixels(90.0), Twips::from_pixels(10.0));
+        let outside = (Twips::from_pixels(10.0), Twips::from_pixels(40.0));
+        assert!(triangle_list_hit_test(&triangles, inside));
+        assert!(!triangle_list_hit_test(&triangles, outside));
+    }
 }
 
 /* SHAPEFLAG HITTEST (point-in-contour)
@@ -710,126 +1071,503 @@ mod tests {
  *
  * For strokes, we calculate the distance to the line segment or curve and compare it to the stroke width.
  * Note that Flash renders with a minimum stroke width of 1px (20 twips) that we must account for.
- * TODO: We currently don't consider non-round endcaps or joins, or stroke scaling flags.
+ * Caps and joins are tested separately from the edges, so that their style is respected.
+ * TODO: We currently don't consider stroke scaling flags.
  */
 
 /// Test whether the given point in object space is contained within the contour of the given shape.
 /// local_matrix is used to calculate the proper stroke widths.
-pub fn shape_hit_test(
-    shape: &swf::Shape,
-    (point_x, point_y): (Twips, Twips),
-    local_matrix: &Matrix,
-) -> bool {
-    // Transform point to local space.
-    let mut x = Twips::ZERO;
-    let mut y = Twips::ZERO;
-    let mut winding = 0;
-
-    let mut has_fill_style0: bool = false;
-    let mut has_fill_style1: bool = false;
+///
+/// Anything that is hit tested more than once should use a [`ShapeHitTester`] instead.
+pub fn shape_hit_test(shape: &swf::Shape, point: (Twips, Twips), local_matrix: &Matrix) -> bool {
+    ShapeHitTester::new(shape).hit_test(point, local_matrix)
+}
 
-    let min_width = stroke_minimum_width(local_matrix);
-    let mut stroke_width = None;
-    let mut line_styles = &shape.styles.line_styles;
+/// The fills and strokes of a shape, sorted by the rows that they cover
+/// so that hit testing a point only has to look at the edges near it.
+#[derive(Debug)]
+pub struct ShapeHitTester {
+    fills: Vec<(FillRule, YBuckets<Edge>)>,
+    strokes: YBuckets<StrokePart>,
+    /// The widest of the strokes, and how far past half of their width any of them can reach
+    /// relative to it (such as with square caps or miter joins).
+    widest_stroke: f64,
+    stroke_reach: f64,
+}
 
-    for record in &shape.shape {
-        match record {
-            swf::ShapeRecord::StyleChange(style_change) => {
-                // New styles indicates a new layer;
-                // Check if the point is within the current layer, then reset winding.
-                if let Some(new_styles) = &style_change.new_styles {
-                    if winding & 0b1 != 0 {
-                        return true;
+impl ShapeHitTester {
+    pub fn new(shape: &swf::Shape) -> Self {
+        let mut fills = Vec::new();
+        let mut strokes = Vec::new();
+        let mut widest_stroke: f64 = 0.0;
+        let mut stroke_reach: f64 = 1.0;
+        for path in ShapeConverter::from_shape(shape).into_commands() {
+            match path {
+                DrawPath::Fill {
+                    commands,
+                    winding_rule,
+                    ..
+                } => {
+                    let edges = fill_edges(&commands)
+                        .into_iter()
+                        .map(|edge| (edge.y_range(), edge))
+                        .collect();
+                    fills.push((winding_rule, YBuckets::new(edges)));
+                }
+                DrawPath::Stroke {
+                    style,
+                    is_closed,
+                    commands,
+                } => {
+                    widest_stroke = widest_stroke.max(style.width().get().into());
+                    stroke_reach = stroke_reach.max(match style.join_style() {
+                        swf::LineJoinStyle::Miter(limit) => limit.to_f64(),
+                        _ => 1.0,
+                    });
+                    if style.start_cap() == swf::LineCapStyle::Square
+                        || style.end_cap() == swf::LineCapStyle::Square
+                    {
+                        stroke_reach = stroke_reach.max(std::f64::consts::SQRT_2);
                     }
-                    line_styles = &new_styles.line_styles;
-                    winding = 0;
+                    add_stroke_parts(&mut strokes, style, is_closed, &commands);
                 }
+                // Only drawings can have triangles.
+                DrawPath::Triangles { .. } => {}
+            }
+        }
+        let strokes = strokes
+            .into_iter()
+            .map(|part| (part.y_range(), part))
+            .collect();
+        Self {
+            fills,
+            strokes: YBuckets::new(strokes),
+            widest_stroke,
+            stroke_reach,
+        }
+    }
 
-                if let Some((move_x, move_y)) = style_change.move_to {
-                    x = move_x;
-                    y = move_y;
-                }
+    /// Test whether the given point in object space is contained within the contour of the shape.
+    /// local_matrix is used to calculate the proper stroke widths.
+    pub fn hit_test(&self, point: (Twips, Twips), local_matrix: &Matrix) -> bool {
+        let y = point.1.get() as f64;
+        for (winding_rule, edges) in &self.fills {
+            let winding: i32 = edges
+                .query(y, y)
+                .map(|edge| edge.winding_number(point))
+                .sum();
+            let inside = match winding_rule {
+                FillRule::EvenOdd => winding & 0b1 != 0,
+                FillRule::NonZero => winding != 0,
+            };
+            if inside {
+                return true;
+            }
+        }
 
-                if let Some(i) = style_change.fill_style_0 {
-                    has_fill_style0 = i > 0;
-                }
-                if let Some(i) = style_change.fill_style_1 {
-                    has_fill_style1 = i > 0;
-                }
-                if let Some(i) = style_change.line_style {
-                    stroke_width = if i > 0 {
-                        // Flash renders strokes with a 1px minimum width.
-                        if let Some(line_style) = line_styles.get(i as usize - 1) {
-                            let width = line_style.width().get() as f64;
-                            let scaled_width = 0.5 * width.max(min_width);
-                            Some((scaled_width, scaled_width * scaled_width))
-                        } else {
-                            None
-                        }
-                    } else {
-                        None
-                    };
-                }
+        // Flash renders strokes with a 1px minimum width.
+        let min_width = stroke_minimum_width(local_matrix);
+        let reach = 0.5 * self.widest_stroke.max(min_width) * self.stroke_reach;
+        self.strokes.query(y - reach, y + reach).any(|part| {
+            let half_width = 0.5 * part.width.max(min_width);
+            part.hit_test(point, half_width)
+        })
+    }
+}
+
+/// Items sorted into horizontal bands by the range of y coordinates that each covers,
+/// so that only the items near some y coordinate have to be looked at.
+#[derive(Debug)]
+struct YBuckets<T> {
+    items: Vec<T>,
+    min_y: f64,
+    bucket_height: f64,
+    /// The indices of the items that overlap each band.
+    buckets: Vec<Vec<u32>>,
+}
+
+impl<T> YBuckets<T> {
+    const MAX_BUCKETS: usize = 256;
+
+    fn new(items: Vec<((f64, f64), T)>) -> Self {
+        let min_y = items
+            .iter()
+            .map(|((min, _), _)| *min)
+            .fold(f64::INFINITY, f64::min);
+        let max_y = items
+            .iter()
+            .map(|((_, max), _)| *max)
+            .fold(f64::NEG_INFINITY, f64::max);
+        let (items, ranges): (Vec<_>, Vec<_>) =
+            items.into_iter().map(|(range, item)| (item, range)).unzip();
+        if items.is_empty() {
+            return Self {
+                items,
+                min_y: 0.0,
+                bucket_height: 1.0,
+                buckets: Vec::new(),
+            };
+        }
+
+        let num_buckets = ((items.len() as f64).sqrt().ceil() as usize).clamp(1, Self::MAX_BUCKETS);
+        let bucket_height = ((max_y - min_y) / num_buckets as f64).max(1.0);
+        let mut buckets = vec![Vec::new(); num_buckets];
+        for (index, (min, max)) in ranges.into_iter().enumerate() {
+            let first = ((min - min_y) / bucket_height) as usize;
+            let last = (((max - min_y) / bucket_height) as usize).min(num_buckets - 1);
+            for bucket in &mut buckets[first.min(num_buckets - 1)..=last] {
+                bucket.push(index as u32);
             }
-            swf::ShapeRecord::StraightEdge { delta_x, delta_y } => {
-                let x1 = x + *delta_x;
-                let y1 = y + *delta_y;
-                // If this edge has a fill style on only one-side, check for a crossing.
-                if has_fill_style1 {
-                    if !has_fill_style0 {
-                        winding += winding_number_line((point_x, point_y), (x, y), (x1, y1));
-                    }
-                } else if has_fill_style0 {
-                    winding += winding_number_line((point_x, point_y), (x1, y1), (x, y));
-                }
+        }
+        Self {
+            items,
+            min_y,
+            bucket_height,
+            buckets,
+        }
+    }
 
-                if let Some(width) = stroke_width {
-                    if hit_test_stroke((point_x, point_y), (x, y), (x1, y1), width) {
-                        return true;
-                    }
+    /// All items that may overlap the rows from `min` to `max`.
+    /// An item that spans several bands may be returned more than once.
+    fn query(&self, min: f64, max: f64) -> impl Iterator<Item = &T> {
+        let bucket = |y: f64| ((y - self.min_y) / self.bucket_height).floor();
+        let last = self.buckets.len() as f64 - 1.0;
+        let range = if self.buckets.is_empty() || bucket(max) < 0.0 || bucket(min) > last {
+            0..0
+        } else {
+            bucket(min).max(0.0) as usize..bucket(max).min(last) as usize + 1
+        };
+        self.buckets[range]
+            .iter()
+            .flatten()
+            .map(|&index| &self.items[index as usize])
+    }
+}
+
+/// A straight or curved edge of a fill or stroke.
+#[derive(Clone, Copy, Debug)]
+enum Edge {
+    Line((Twips, Twips), (Twips, Twips)),
+    Curve((Twips, Twips), (Twips, Twips), (Twips, Twips)),
+}
+
+impl Edge {
+    fn y_range(&self) -> (f64, f64) {
+        let (min, max) = match *self {
+            Edge::Line((_, y0), (_, y1)) => (y0.min(y1), y0.max(y1)),
+            Edge::Curve((_, y0), (_, y1), (_, y2)) => (y0.min(y1).min(y2), y0.max(y1).max(y2)),
+        };
+        (min.get().into(), max.get().into())
+    }
+
+    fn start(&self) -> (Twips, Twips) {
+        match *self {
+            Edge::Line(start, _) | Edge::Curve(start, _, _) => start,
+        }
+    }
+
+    fn end(&self) -> (Twips, Twips) {
+        match *self {
+            Edge::Line(_, end) | Edge::Curve(_, _, end) => end,
+        }
+    }
+
+    /// The direction that the edge leaves its start point in, unless it has no length.
+    fn start_direction(&self) -> Option<(f64, f64)> {
+        match *self {
+            Edge::Line(start, end) => direction(start, end),
+            Edge::Curve(start, control, end) => {
+                direction(start, control).or_else(|| direction(start, end))
+            }
+        }
+    }
+
+    /// The direction that the edge arrives at its end point in, unless it has no length.
+    fn end_direction(&self) -> Option<(f64, f64)> {
+        match *self {
+            Edge::Line(start, end) => direction(start, end),
+            Edge::Curve(start, control, end) => {
+                direction(control, end).or_else(|| direction(start, end))
+            }
+        }
+    }
+
+    fn winding_number(&self, point: (Twips, Twips)) -> i32 {
+        match *self {
+            Edge::Line(start, end) => winding_number_line(point, start, end),
+            Edge::Curve(start, control, end) => winding_number_curve(point, start, control, end),
+        }
+    }
+}
+
+/// The unit vector pointing from `from` to `to`, unless they're the same point.
+fn direction((x0, y0): (Twips, Twips), (x1, y1): (Twips, Twips)) -> Option<(f64, f64)> {
+    let dx = (x1 - x0).get() as f64;
+    let dy = (y1 - y0).get() as f64;
+    let length = (dx * dx + dy * dy).sqrt();
+    if length > 0.0 {
+        Some((dx / length, dy / length))
+    } else {
+        None
+    }
+}
+
+/// The edges of the paths specified by the draw commands, with each path closed.
+fn fill_edges(commands: &[DrawCommand]) -> Vec<Edge> {
+    let mut edges = Vec::with_capacity(commands.len());
+    let mut cursor = (Twips::ZERO, Twips::ZERO);
+    let mut fill_start = (Twips::ZERO, Twips::ZERO);
+    for command in commands {
+        match *command {
+            DrawCommand::MoveTo { x, y } => {
+                if cursor != fill_start {
+                    edges.push(Edge::Line(cursor, fill_start));
                 }
-                x = x1;
-                y = y1;
+                cursor = (x, y);
+                fill_start = (x, y);
             }
-            swf::ShapeRecord::CurvedEdge {
-                control_delta_x,
-                control_delta_y,
-                anchor_delta_x,
-                anchor_delta_y,
-            } => {
-                let x1 = x + *control_delta_x;
-                let y1 = y + *control_delta_y;
+            DrawCommand::LineTo { x, y } => {
+                edges.push(Edge::Line(cursor, (x, y)));
+                cursor = (x, y);
+            }
+            DrawCommand::CurveTo { x1, y1, x2, y2 } => {
+                edges.push(Edge::Curve(cursor, (x1, y1), (x2, y2)));
+                cursor = (x2, y2);
+            }
+        }
+    }
+    if cursor != fill_start {
+        edges.push(Edge::Line(cursor, fill_start));
+    }
+    edges
+}
 
-                let x2 = x1 + *anchor_delta_x;
-                let y2 = y1 + *anchor_delta_y;
+/// A piece of a stroke: either the area swept along one of its edges,
+/// or the cap or join at one of the ends of an edge.
+#[derive(Debug)]
+struct StrokePart {
+    /// The width of the stroke, before the minimum width is applied.
+    width: f64,
+    kind: StrokePartKind,
+}
 
-                // If this edge has a fill style on only one-side, check for a crossing.
-                if has_fill_style1 {
-                    if !has_fill_style0 {
-                        winding +=
-                            winding_number_curve((point_x, point_y), (x, y), (x1, y1), (x2, y2));
+#[derive(Debug)]
+enum StrokePartKind {
+    Edge(Edge),
+    /// The end of a stroke that isn't joined to anything, facing outwards along `direction`.
+    Cap {
+        point: (f64, f64),
+        direction: (f64, f64),
+        style: swf::LineCapStyle,
+    },
+    /// Where the edge arriving in the `incoming` direction meets the one leaving in `outgoing`.
+    Join {
+        point: (f64, f64),
+        incoming: (f64, f64),
+        outgoing: (f64, f64),
+        style: swf::LineJoinStyle,
+    },
+}
+
+impl StrokePart {
+    fn y_range(&self) -> (f64, f64) {
+        match self.kind {
+            StrokePartKind::Edge(edge) => edge.y_range(),
+            StrokePartKind::Cap { point, .. } | StrokePartKind::Join { point, .. } => {
+                (point.1, point.1)
+            }
+        }
+    }
+
+    fn hit_test(&self, point: (Twips, Twips), half_width: f64) -> bool {
+        let (px, py) = (point.0.get() as f64, point.1.get() as f64);
+        let widths = (half_width, half_width * half_width);
+        match self.kind {
+            StrokePartKind::Edge(Edge::Line(start, end)) => {
+                hit_test_stroke(point, start, end, widths, false)
+            }
+            StrokePartKind::Edge(Edge::Curve(start, control, end)) => {
+                hit_test_stroke_curve(point, start, control, end, widths, false)
+            }
+            StrokePartKind::Cap {
+                point: (x, y),
+                direction: (dx, dy),
+                style,
+            } => {
+                let (vx, vy) = (px - x, py - y);
+                match style {
+                    swf::LineCapStyle::Round => vx * vx + vy * vy <= half_width * half_width,
+                    swf::LineCapStyle::None => false,
+                    swf::LineCapStyle::Square => {
+                        let along = vx * dx + vy * dy;
+                        let across = vy * dx - vx * dy;
+                        (0.0..=half_width).contains(&along) && across.abs() <= half_width
                     }
-                } else if has_fill_style0 {
-                    winding += winding_number_curve((point_x, point_y), (x2, y2), (x1, y1), (x, y));
+                }
+            }
+            StrokePartKind::Join {
+                point: (x, y),
+                incoming,
+                outgoing,
+                style,
+            } => {
+                let (vx, vy) = (px - x, py - y);
+                if style == swf::LineJoinStyle::Round {
+                    return vx * vx + vy * vy <= half_width * half_width;
                 }
 
-                if let Some(width) = stroke_width {
-                    if hit_test_stroke_curve((point_x, point_y), (x, y), (x1, y1), (x2, y2), width)
-                    {
-                        return true;
-                    }
+                // The edges already cover the inside of the turn; the join fills the gap
+                // between them on the outside.
+                let cross = incoming.0 * outgoing.1 - incoming.1 * outgoing.0;
+                if cross.abs() < 1e-9 {
+                    return false;
+                }
+                let side = if cross > 0.0 { -half_width } else { half_width };
+                let a = (-incoming.1 * side, incoming.0 * side);
+                let b = (-outgoing.1 * side, outgoing.0 * side);
+                let bevel = [(0.0, 0.0), a, b];
+                let swf::LineJoinStyle::Miter(limit) = style else {
+                    return point_in_convex_polygon((vx, vy), &bevel);
+                };
+
+                // How far the corners reach along the middle of the turn.
+                // A miter that reaches further than the limit is clipped at it.
+                let cos = incoming.0 * outgoing.0 + incoming.1 * outgoing.1;
+                let miter_length = half_width * (2.0 / (1.0 + cos)).sqrt();
+                let bevel_length = half_width * ((1.0 + cos) / 2.0).sqrt();
+                let limit_length = half_width * limit.to_f64();
+                if limit_length <= bevel_length {
+                    return point_in_convex_polygon((vx, vy), &bevel);
+                }
+                let miter = ((a.0 + b.0) / (1.0 + cos), (a.1 + b.1) / (1.0 + cos));
+                if limit_length >= miter_length {
+                    return point_in_convex_polygon((vx, vy), &[(0.0, 0.0), a, miter, b]);
                 }
+                let t = (limit_length - bevel_length) / (miter_length - bevel_length);
+                let clip_a = (a.0 + (miter.0 - a.0) * t, a.1 + (miter.1 - a.1) * t);
+                let clip_b = (b.0 + (miter.0 - b.0) * t, b.1 + (miter.1 - b.1) * t);
+                point_in_convex_polygon((vx, vy), &[(0.0, 0.0), a, clip_a, clip_b, b])
+            }
+        }
+    }
+}
 
-                x = x2;
-                y = y2;
+/// Splits the stroke specified by the draw commands into the parts that hit test separately.
+fn add_stroke_parts(
+    parts: &mut Vec<StrokePart>,
+    style: &LineStyle,
+    is_closed: bool,
+    commands: &[DrawCommand],
+) {
+    let width: f64 = style.width().get().into();
+    let mut edges = Vec::with_capacity(commands.len());
+    let mut cursor = (Twips::ZERO, Twips::ZERO);
+    for command in commands {
+        match *command {
+            DrawCommand::MoveTo { x, y } => cursor = (x, y),
+            DrawCommand::LineTo { x, y } => {
+                edges.push(Edge::Line(cursor, (x, y)));
+                cursor = (x, y);
+            }
+            DrawCommand::CurveTo { x1, y1, x2, y2 } => {
+                edges.push(Edge::Curve(cursor, (x1, y1), (x2, y2)));
+                cursor = (x2, y2);
             }
         }
     }
-    winding & 0b1 != 0
+
+    let to_f64 = |(x, y): (Twips, Twips)| (x.get() as f64, y.get() as f64);
+    let edges: Vec<_> = edges
+        .into_iter()
+        .filter_map(|edge| Some((edge, edge.start_direction()?, edge.end_direction()?)))
+        .collect();
+    let (Some(first), Some(last)) = (edges.first(), edges.last()) else {
+        // A stroke without any length is still drawn as a dot, made of both of its caps
+        if !commands.is_empty() {
+            for (direction, style) in [
+                ((-1.0, 0.0), style.start_cap()),
+                ((1.0, 0.0), style.end_cap()),
+            ] {
+                parts.push(StrokePart {
+                    width,
+                    kind: StrokePartKind::Cap {
+                        point: to_f64(cursor),
+                        direction,
+                        style,
+                    },
+                });
+            }
+        }
+        return;
+    };
+
+    if is_closed {
+        parts.push(StrokePart {
+            width,
+            kind: StrokePartKind::Join {
+                point: to_f64(first.0.start()),
+                incoming: last.2,
+                outgoing: first.1,
+                style: style.join_style(),
+            },
+        });
+    } else {
+        parts.push(StrokePart {
+            width,
+            kind: StrokePartKind::Cap {
+                point: to_f64(first.0.start()),
+                direction: (-first.1 .0, -first.1 .1),
+                style: style.start_cap(),
+            },
+        });
+        parts.push(StrokePart {
+            width,
+            kind: StrokePartKind::Cap {
+                point: to_f64(last.0.end()),
+                direction: last.2,
+                style: style.end_cap(),
+            },
+        });
+    }
+    for pair in edges.windows(2) {
+        parts.push(StrokePart {
+            width,
+            kind: StrokePartKind::Join {
+                point: to_f64(pair[0].0.end()),
+                incoming: pair[0].2,
+                outgoing: pair[1].1,
+                style: style.join_style(),
+            },
+        });
+    }
+    parts.extend(edges.into_iter().map(|(edge, _, _)| StrokePart {
+        width,
+        kind: StrokePartKind::Edge(edge),
+    }));
+}
+
+/// Whether the point is inside of (or on the edge of) the convex polygon, in either winding order.
+fn point_in_convex_polygon((px, py): (f64, f64), polygon: &[(f64, f64)]) -> bool {
+    let mut sign = 0.0;
+    for (i, &(x0, y0)) in polygon.iter().enumerate() {
+        let (x1, y1) = polygon[(i + 1) % polygon.len()];
+        let cross = (x1 - x0) * (py - y0) - (y1 - y0) * (px - x0);
+        if cross != 0.0 {
+            if sign != 0.0 && cross.signum() != sign {
+                return false;
+            }
+            sign = cross.signum();
+        }
+    }
+    true
 }
 
 /// Test whether the given point is contained within the paths specified by the draw commands.
-pub fn draw_command_fill_hit_test(commands: &[DrawCommand], test_point: (Twips, Twips)) -> bool {
+pub fn draw_command_fill_hit_test(
+    commands: &[DrawCommand],
+    winding_rule: FillRule,
+    test_point: (Twips, Twips),
+) -> bool {
     let mut cursor = (Twips::ZERO, Twips::ZERO);
     let mut fill_start = (Twips::ZERO, Twips::ZERO);
     let mut winding = 0;
@@ -856,7 +1594,20 @@ pub fn draw_command_fill_hit_test(commands: &[DrawCommand], test_point: (Twips,
         winding += winding_number_line(test_point, cursor, fill_start);
     }
 
-    winding & 0b1 != 0
+    match winding_rule {
+        FillRule::EvenOdd => winding & 0b1 != 0,
+        FillRule::NonZero => winding != 0,
+    }
+}
+
+/// Test whether the given point is inside of any of the triangles.
+pub fn triangle_list_hit_test(triangles: &TriangleList, test_point: (Twips, Twips)) -> bool {
+    triangles.triangles().any(|([a, b, c], _)| {
+        winding_number_line(test_point, a, b)
+            + winding_number_line(test_point, b, c)
+            + winding_number_line(test_point, c, a)
+            != 0
+    })
 }
 
 /// Test whether the given point is contained within the strokes specified by the draw commands.
@@ -879,7 +1630,7 @@ pub fn draw_command_stroke_hit_test(
                 y = y1;
             }
             DrawCommand::LineTo { x: x1, y: y1 } => {
-                if hit_test_stroke((point_x, point_y), (x, y), (x1, y1), stroke_widths) {
+                if hit_test_stroke((point_x, point_y), (x, y), (x1, y1), stroke_widths, true) {
                     return true;
                 }
                 x = x1;
@@ -892,6 +1643,7 @@ pub fn draw_command_stroke_hit_test(
                     (x1, y1),
                     (x2, y2),
                     stroke_widths,
+                    true,
                 ) {
                     return true;
                 }
@@ -917,11 +1669,13 @@ fn stroke_minimum_width(matrix: &Matrix) -> f64 {
 
 /// Returns whether the given point is inside the stroked line segment.
 /// `width_sq` should be the squared width of the stroke.
+/// Without `round_ends`, only the area alongside the segment counts, and not its ends.
 fn hit_test_stroke(
     (point_x, point_y): (Twips, Twips),
     (x0, y0): (Twips, Twips),
     (x1, y1): (Twips, Twips),
     (stroke_width, stroke_width_sq): (f64, f64),
+    round_ends: bool,
 ) -> bool {
     let px = point_x.get() as f64;
     let py = point_y.get() as f64;
@@ -954,6 +1708,9 @@ fn hit_test_stroke(
     let apy = py - y0;
     let dot_a = abx * apx + aby * apy;
     let dist = if dot_a <= 0.0 {
+        if !round_ends {
+            return false;
+        }
         apx * apx + apy * apy
     } else {
         // If BP dot AB is >= 0.0, then BP is pointing away from BA, so B is the closest point.
@@ -961,6 +1718,9 @@ fn hit_test_stroke(
         let bpy = py - y1;
         let dot_b = abx * bpx + aby * bpy;
         if dot_b >= 0.0 {
+            if !round_ends {
+                return false;
+            }
             bpx * bpx + bpy * bpy
         } else {
             // Otherwise, the closest point will be within the interval of the segment.
@@ -977,12 +1737,14 @@ fn hit_test_stroke(
 
 /// Returns whether the given point is inside the stroked bezier curve.
 /// `width_sq` should be the squared width of the stroke.
+/// Without `round_ends`, only the area alongside the curve counts, and not its ends.
 fn hit_test_stroke_curve(
     (point_x, point_y): (Twips, Twips),
     (x0, y0): (Twips, Twips),
     (x1, y1): (Twips, Twips),
     (x2, y2): (Twips, Twips),
     (stroke_width, stroke_width_sq): (f64, f64),
+    round_ends: bool,
 ) -> bool {
     let px = point_x.get() as f64;
     let py = point_y.get() as f64;
@@ -1041,8 +1803,11 @@ fn hit_test_stroke_curve(
     };
 
     // Test end-caps
-    let mut dist = distance_to_curve(0.0);
-    dist = dist.min(distance_to_curve(1.0));
+    let mut dist = if round_ends {
+        distance_to_curve(0.0).min(distance_to_curve(1.0))
+    } else {
+        f64::INFINITY
+    };
 
     // Test roots.
     for t in solve_cubic(a, b, c, d) {
@@ -1326,6 +2091,17 @@ pub enum LineScaleMode {
     Both,
 }
 
+impl From<&LineStyle> for LineScaleMode {
+    fn from(style: &LineStyle) -> Self {
+        match (style.allow_scale_x(), style.allow_scale_y()) {
+            (false, false) => LineScaleMode::None,
+            (true, false) => LineScaleMode::Horizontal,
+            (false, true) => LineScaleMode::Vertical,
+            (true, true) => LineScaleMode::Both,
+        }
+    }
+}
+
 /// Helper type for calculating line widths for a transformed shape.
 pub struct LineScales<'a> {
     matrix: &'a Matrix,
@@ -1342,9 +2118,9 @@ impl<'a> LineScales<'a> {
         }
     }
 
-    /// Returns the final width of a line after transformation.
+    /// Returns how much a line with the given scale mode is scaled by the transformation.
     #[inline]
-    pub fn transform_width(&mut self, width: f32, scale_mode: LineScaleMode) -> f32 {
+    fn scale(&mut self, scale_mode: LineScaleMode) -> f32 {
         // Lazily calculate the scale to avoid doing so for shapes that have no strokes.
         let scales = self.scales.get_or_insert_with(|| {
             let line_scale_x = f32::abs(self.matrix.a + self.matrix.c);
@@ -1353,9 +2129,106 @@ impl<'a> LineScales<'a> {
                 ((line_scale_x * line_scale_x + line_scale_y * line_scale_y) / 2.0).sqrt();
             [1.0, line_scale_x, line_scale_y, line_scale]
         });
-        let scaled_width = width * scales[scale_mode as usize];
+        scales[scale_mode as usize]
+    }
+
+    /// Returns the final width of a line after transformation.
+    #[inline]
+    pub fn transform_width(&mut self, width: f32, scale_mode: LineScaleMode) -> f32 {
+        let scaled_width = width * self.scale(scale_mode);
         // Flash draws all strokes with a minimum width of 1 pixel.
         // This usually occurs in "hairline" strokes (exported with width of 1 twip).
         scaled_width.max(1.0)
     }
+
+    /// Returns the width that a line has to be tessellated with, so that it ends up
+    /// at its final width once the shape is transformed.
+    pub fn untransformed_width(&mut self, width: f32, scale_mode: LineScaleMode) -> f32 {
+        let scaled_width = self.transform_width(width, scale_mode);
+        self.shape_width(scaled_width)
+    }
+
+    /// Returns the width that a line has to be tessellated with, so that it ends up
+    /// `scaled_width` wide once the shape is transformed.
+    pub fn shape_width(&mut self, scaled_width: f32) -> f32 {
+        let shape_scale = self.scale(LineScaleMode::Both);
+        if shape_scale > 0.0 {
+            scaled_width / shape_scale
+        } else {
+            // Nothing of the shape is visible anyway
+            scaled_width
+        }
+    }
+}
+
+/// Whether the strokes of a shape end up at the right width when they're simply scaled along
+/// with the shape, or have to be tessellated for the transform that the shape is drawn with.
+///
+/// Strokes that don't scale in one or both directions always have to be, as do strokes that
+/// can be thinner than a pixel, which Flash draws 1 pixel wide regardless of scale.
+/// Pixel hinted strokes are snapped to the pixels that they're drawn on, so they also depend
+/// on where within a pixel the shape is moved to.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct StrokeScaling {
+    /// The width of the thinnest stroke that scales along with the shape, in pixels.
+    thinnest_width: Option<f32>,
+
+    /// Whether any stroke doesn't scale along with the shape in both directions.
+    has_non_scaling: bool,
+
+    /// Whether any stroke is pixel hinted.
+    has_pixel_hinted: bool,
+}
+
+impl StrokeScaling {
+    pub fn from_paths(paths: &[DrawPath]) -> Self {
+        let mut scaling = Self::default();
+        for path in paths {
+            if let DrawPath::Stroke { style, .. } = path {
+                scaling.add_style(style);
+            }
+        }
+        scaling
+    }
+
+    fn add_style(&mut self, style: &LineStyle) {
+        self.has_pixel_hinted |= style.is_pixel_hinted();
+        if LineScaleMode::from(style) != LineScaleMode::Both {
+            self.has_non_scaling = true;
+        } else {
+            let width = style.width().to_pixels() as f32;
+            self.thinnest_width = Some(self.thinnest_width.map_or(width, |w| w.min(width)));
+        }
+    }
+
+    /// Whether the strokes have to be tessellated for being drawn with the given transform.
+    pub fn is_needed(&self, matrix: &Matrix) -> bool {
+        if self.has_non_scaling || self.has_pixel_hinted {
+            return true;
+        }
+        // Strokes that are at least a pixel wide both before and after being transformed
+        // are the same either way.
+        self.thinnest_width.map_or(false, |width| {
+            width < 1.0 || width * LineScales::new(matrix).scale(LineScaleMode::Both) < 1.0
+        })
+    }
+
+    /// The transform that the strokes have to be tessellated for, if they depend on it.
+    ///
+    /// Only pixel hinted strokes depend on the translation, and only on the part of it
+    /// within a pixel, so moving the shape by whole pixels doesn't change them.
+    pub fn stroke_matrix(&self, matrix: &Matrix) -> Option<Matrix> {
+        if !self.is_needed(matrix) {
+            return None;
+        }
+        let (tx, ty) = if self.has_pixel_hinted {
+            (
+                Twips::new(matrix.tx.get().rem_euclid(Twips::TWIPS_PER_PIXEL)),
+                Twips::new(matrix.ty.get().rem_euclid(Twips::TWIPS_PER_PIXEL)),
+            )
+        } else {
+            (Twips::ZERO, Twips::ZERO)
+        };
+        Some(Matrix { tx, ty, ..*matrix })
+    }
 }
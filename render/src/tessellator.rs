@@ -1,11 +1,17 @@
 use crate::bitmap::BitmapSource;
-use crate::shape_utils::{DistilledShape, DrawCommand, DrawPath};
+use crate::matrix::Matrix;
+use crate::shape_utils::{
+    triangle_bitmap_matrix, DistilledShape, DrawCommand, DrawPath, FillRule, LineScales,
+    TriangleList,
+};
 use enum_map::Enum;
+use lyon::geom::QuadraticBezierSegment;
 use lyon::path::Path;
 use lyon::tessellation::{
     self,
     geometry_builder::{BuffersBuilder, FillVertexConstructor, VertexBuffers},
     FillTessellator, FillVertex, StrokeTessellator, StrokeVertex, StrokeVertexConstructor,
+    VertexSource,
 };
 use lyon::tessellation::{FillOptions, StrokeOptions};
 use tracing::instrument;
@@ -17,6 +23,7 @@ pub struct ShapeTessellator {
     lyon_mesh: VertexBuffers<Vertex, u32>,
     mask_index_count: Option<u32>,
     is_stroke: bool,
+    tolerance: f32,
 }
 
 impl ShapeTessellator {
@@ -28,9 +35,23 @@ impl ShapeTessellator {
             lyon_mesh: VertexBuffers::new(),
             mask_index_count: None,
             is_stroke: false,
+            tolerance: FillOptions::DEFAULT_TOLERANCE,
         }
     }
 
+    /// How far, in pixels before a shape is transformed, the lines that curves are flattened
+    /// into may stray from them. This defaults to lyon's default tolerance.
+    ///
+    /// Shapes that are scaled up need a lower tolerance to stay smooth, at the cost of
+    /// being tessellated into more triangles.
+    pub fn tolerance(&self) -> f32 {
+        self.tolerance
+    }
+
+    pub fn set_tolerance(&mut self, tolerance: f32) {
+        self.tolerance = tolerance;
+    }
+
     #[instrument(level = "debug", skip_all)]
     pub fn tessellate_shape(
         &mut self,
@@ -39,22 +60,58 @@ impl ShapeTessellator {
     ) -> Mesh {
         self.mesh = Vec::new();
         self.lyon_mesh = VertexBuffers::new();
+        let stroke_matrix = shape.stroke_matrix;
+        let mut line_scales = stroke_matrix.as_ref().map(LineScales::new);
         for path in shape.paths {
-            let (fill_style, lyon_path, next_is_stroke) = match &path {
-                DrawPath::Fill { style, commands } => {
-                    (*style, ruffle_path_to_lyon_path(commands, true), false)
-                }
-                DrawPath::Stroke {
-                    style,
-                    commands,
-                    is_closed,
-                } => (
-                    style.fill_style(),
-                    ruffle_path_to_lyon_path(commands, *is_closed),
-                    true,
-                ),
+            let (fill_style, next_is_stroke) = match &path {
+                DrawPath::Fill { style, .. } | DrawPath::Triangles { style, .. } => (*style, false),
+                DrawPath::Stroke { style, .. } => (style.fill_style(), true),
             };
 
+            if let DrawPath::Triangles {
+                style:
+                    swf::FillStyle::Bitmap {
+                        id,
+                        is_smoothed,
+                        is_repeating,
+                        ..
+                    },
+                triangles:
+                    TriangleList {
+                        vertices,
+                        indices,
+                        uvs: Some(uvs),
+                    },
+            } = &path
+            {
+                // Triangles with their own texture coordinates are a draw of their own,
+                // as the bitmap isn't placed by a matrix like all other fills are.
+                if bitmap_source.bitmap_size(*id).is_none() {
+                    continue;
+                }
+                self.flush_draw(DrawType::Color);
+                self.is_stroke = false;
+                self.mesh.push(Draw {
+                    draw_type: DrawType::TexturedTriangles(TexturedTriangles {
+                        bitmap_id: *id,
+                        uvs: uvs.iter().map(|&(u, v)| [u, v]).collect(),
+                        is_smoothed: *is_smoothed,
+                        is_repeating: *is_repeating,
+                    }),
+                    vertices: vertices
+                        .iter()
+                        .map(|&(x, y)| Vertex {
+                            x: x.to_pixels() as f32,
+                            y: y.to_pixels() as f32,
+                            color: swf::Color::WHITE,
+                        })
+                        .collect(),
+                    indices: indices.clone(),
+                    mask_index_count: indices.len() as u32,
+                });
+                continue;
+            }
+
             let (draw, color, needs_flush) = match fill_style {
                 swf::FillStyle::Color(color) => (DrawType::Color, color.clone(), false),
                 swf::FillStyle::LinearGradient(gradient) => (
@@ -129,49 +186,65 @@ impl ShapeTessellator {
             }
             self.is_stroke = next_is_stroke;
 
-            let mut buffers_builder =
-                BuffersBuilder::new(&mut self.lyon_mesh, RuffleVertexCtor { color });
             let result = match path {
-                DrawPath::Fill { .. } => self.fill_tess.tessellate_path(
-                    &lyon_path,
-                    &FillOptions::even_odd(),
-                    &mut buffers_builder,
-                ),
-                DrawPath::Stroke { style, .. } => {
-                    // TODO(Herschel): 0 width indicates "hairline".
-                    let width = (style.width().to_pixels() as f32).max(1.0);
-                    let mut stroke_options = StrokeOptions::default()
-                        .with_line_width(width)
-                        .with_start_cap(match style.start_cap() {
-                            swf::LineCapStyle::None => tessellation::LineCap::Butt,
-                            swf::LineCapStyle::Round => tessellation::LineCap::Round,
-                            swf::LineCapStyle::Square => tessellation::LineCap::Square,
-                        })
-                        .with_end_cap(match style.end_cap() {
-                            swf::LineCapStyle::None => tessellation::LineCap::Butt,
-                            swf::LineCapStyle::Round => tessellation::LineCap::Round,
-                            swf::LineCapStyle::Square => tessellation::LineCap::Square,
-                        });
-
-                    let line_join = match style.join_style() {
-                        swf::LineJoinStyle::Round => tessellation::LineJoin::Round,
-                        swf::LineJoinStyle::Bevel => tessellation::LineJoin::Bevel,
-                        swf::LineJoinStyle::Miter(limit) => {
-                            // Avoid lyon assert with small miter limits.
-                            let limit = limit.to_f32();
-                            if limit >= StrokeOptions::MINIMUM_MITER_LIMIT {
-                                stroke_options = stroke_options.with_miter_limit(limit);
-                                tessellation::LineJoin::MiterClip
-                            } else {
-                                tessellation::LineJoin::Bevel
-                            }
+                DrawPath::Fill {
+                    commands,
+                    winding_rule,
+                    ..
+                } => {
+                    let fill_options = match winding_rule {
+                        FillRule::EvenOdd => FillOptions::even_odd(),
+                        FillRule::NonZero => FillOptions::non_zero(),
+                    }
+                    .with_tolerance(self.tolerance);
+                    self.fill_tess.tessellate_path(
+                        &ruffle_path_to_lyon_path(&commands, true, None),
+                        &fill_options,
+                        &mut BuffersBuilder::new(&mut self.lyon_mesh, RuffleVertexCtor { color }),
+                    )
+                }
+                DrawPath::Triangles { triangles, .. } => {
+                    // These are already triangles, so they only have to be added to the mesh
+                    let first_index = self.lyon_mesh.vertices.len() as u32;
+                    self.lyon_mesh
+                        .vertices
+                        .extend(triangles.vertices.iter().map(|&(x, y)| Vertex {
+                            x: x.to_pixels() as f32,
+                            y: y.to_pixels() as f32,
+                            color: color.clone(),
+                        }));
+                    self.lyon_mesh
+                        .indices
+                        .extend(triangles.indices.iter().map(|index| index + first_index));
+                    Ok(())
+                }
+                DrawPath::Stroke {
+                    style,
+                    commands,
+                    is_closed,
+                } => {
+                    let width = style.width().to_pixels() as f32;
+                    let (width, pixel_snap) = match (&mut line_scales, &stroke_matrix) {
+                        (Some(line_scales), Some(matrix)) if style.is_pixel_hinted() => {
+                            // Hinted strokes are a whole number of pixels wide
+                            let scaled_width =
+                                line_scales.transform_width(width, style.into()).round();
+                            (
+                                line_scales.shape_width(scaled_width),
+                                PixelSnap::new(matrix, scaled_width),
+                            )
+                        }
+                        (Some(line_scales), _) => {
+                            (line_scales.untransformed_width(width, style.into()), None)
                         }
+                        // Without knowing the transform, just make sure that the stroke is visible
+                        (None, _) => (width.max(1.0), None),
                     };
-                    stroke_options = stroke_options.with_line_join(line_join);
+                    let stroke_options = stroke_options(style, width, self.tolerance);
                     self.stroke_tess.tessellate_path(
-                        &lyon_path,
+                        &ruffle_path_to_lyon_path(&commands, is_closed, pixel_snap.as_ref()),
                         &stroke_options,
-                        &mut buffers_builder,
+                        &mut BuffersBuilder::new(&mut self.lyon_mesh, RuffleVertexCtor { color }),
                     )
                 }
             };
@@ -196,6 +269,150 @@ impl ShapeTessellator {
         std::mem::take(&mut self.mesh)
     }
 
+    /// Tessellates the start and end shapes of a morph shape into a single mesh,
+    /// where every vertex has a position and color at both ends of the morph.
+    ///
+    /// Returns `None` if the two can't be drawn with the same triangles. That's the case for
+    /// anything but solid color fills and strokes, for edges that don't pair up, and for strokes
+    /// whose joins or caps come out differently at either end.
+    #[instrument(level = "debug", skip_all)]
+    pub fn tessellate_morph_shape(
+        &mut self,
+        start: DistilledShape,
+        end: DistilledShape,
+    ) -> Option<MorphMesh> {
+        if start.paths.len() != end.paths.len() {
+            return None;
+        }
+
+        let mut draws = Vec::new();
+        let mut mesh: VertexBuffers<MorphVertex, u32> = VertexBuffers::new();
+        let mut mask_index_count = None;
+        let mut is_stroke = false;
+        for (start_path, end_path) in start.paths.iter().zip(&end.paths) {
+            match (start_path, end_path) {
+                (
+                    DrawPath::Fill {
+                        style: swf::FillStyle::Color(start_color),
+                        commands: start_commands,
+                        ..
+                    },
+                    DrawPath::Fill {
+                        style: swf::FillStyle::Color(end_color),
+                        commands: end_commands,
+                        ..
+                    },
+                ) => {
+                    let (start_path, end_path) = morph_paths_to_lyon_paths(
+                        start_commands,
+                        end_commands,
+                        true,
+                        self.tolerance,
+                    )?;
+                    if is_stroke {
+                        // Strokes followed by fills are split up, as with regular shapes
+                        flush_morph_draw(&mut draws, &mut mesh, &mut mask_index_count);
+                        is_stroke = false;
+                    }
+                    let mut buffers_builder = BuffersBuilder::new(
+                        &mut mesh,
+                        MorphFillVertexCtor {
+                            end_path: &end_path,
+                            start_color,
+                            end_color,
+                        },
+                    );
+                    self.fill_tess
+                        .tessellate_path(
+                            &start_path,
+                            &FillOptions::even_odd(),
+                            &mut buffers_builder,
+                        )
+                        .ok()?;
+                }
+                (
+                    DrawPath::Stroke {
+                        style: start_style,
+                        is_closed,
+                        commands: start_commands,
+                    },
+                    DrawPath::Stroke {
+                        style: end_style,
+                        commands: end_commands,
+                        ..
+                    },
+                ) => {
+                    let (swf::FillStyle::Color(start_color), swf::FillStyle::Color(end_color)) =
+                        (start_style.fill_style(), end_style.fill_style())
+                    else {
+                        return None;
+                    };
+                    let (start_path, end_path) = morph_paths_to_lyon_paths(
+                        start_commands,
+                        end_commands,
+                        *is_closed,
+                        self.tolerance,
+                    )?;
+                    // Unlike fills, the outline of a stroke depends on its width and the angles
+                    // of its joins, so each end is tessellated and then checked to match up.
+                    let start_mesh =
+                        self.tessellate_morph_stroke(&start_path, start_style, start_color)?;
+                    let end_mesh = self.tessellate_morph_stroke(&end_path, end_style, end_color)?;
+                    if start_mesh.indices != end_mesh.indices
+                        || start_mesh.vertices.len() != end_mesh.vertices.len()
+                    {
+                        return None;
+                    }
+                    if !is_stroke {
+                        // Omit the strokes when using this shape as a mask.
+                        mask_index_count = Some(mesh.indices.len() as u32);
+                        is_stroke = true;
+                    }
+                    let first_index = mesh.vertices.len() as u32;
+                    mesh.vertices.extend(
+                        start_mesh
+                            .vertices
+                            .into_iter()
+                            .zip(end_mesh.vertices)
+                            .map(|(start, end)| MorphVertex { start, end }),
+                    );
+                    mesh.indices
+                        .extend(start_mesh.indices.iter().map(|index| index + first_index));
+                }
+                _ => return None,
+            }
+        }
+
+        flush_morph_draw(&mut draws, &mut mesh, &mut mask_index_count);
+        Some(draws)
+    }
+
+    fn tessellate_morph_stroke(
+        &mut self,
+        path: &Path,
+        style: &swf::LineStyle,
+        color: &swf::Color,
+    ) -> Option<VertexBuffers<Vertex, u32>> {
+        // Morph shapes are never tessellated for a particular transform,
+        // so just make sure that the stroke is visible
+        let width = (style.width().to_pixels() as f32).max(1.0);
+        let mut mesh = VertexBuffers::new();
+        let mut buffers_builder = BuffersBuilder::new(
+            &mut mesh,
+            RuffleVertexCtor {
+                color: color.clone(),
+            },
+        );
+        self.stroke_tess
+            .tessellate_path(
+                path,
+                &stroke_options(style, width, self.tolerance),
+                &mut buffers_builder,
+            )
+            .ok()?;
+        Some(mesh)
+    }
+
     fn flush_draw(&mut self, draw: DrawType) {
         if self.lyon_mesh.vertices.is_empty() || self.lyon_mesh.indices.len() < 3 {
             // Ignore degenerate fills
@@ -214,6 +431,56 @@ impl ShapeTessellator {
     }
 }
 
+/// Splits up textured triangles into a bitmap draw for each triangle, with the bitmap placed by
+/// a matrix as with any other bitmap fill. This is for backends that can't draw the texture
+/// coordinates of each vertex, at the cost of a draw call per triangle.
+pub fn split_textured_triangles(mesh: Mesh, bitmap_source: &dyn BitmapSource) -> Mesh {
+    let mut draws = Vec::with_capacity(mesh.len());
+    for draw in mesh {
+        let DrawType::TexturedTriangles(textured) = &draw.draw_type else {
+            draws.push(draw);
+            continue;
+        };
+        let Some(bitmap) = bitmap_source.bitmap_size(textured.bitmap_id) else {
+            continue;
+        };
+        for indices in draw.indices.chunks_exact(3) {
+            let vertices = [0, 1, 2].map(|i| draw.vertices[indices[i] as usize].clone());
+            let corners = vertices.clone().map(|vertex| {
+                (
+                    swf::Twips::from_pixels(vertex.x.into()),
+                    swf::Twips::from_pixels(vertex.y.into()),
+                )
+            });
+            let uvs = [0, 1, 2].map(|i| {
+                let [u, v] = textured.uvs[indices[i] as usize];
+                (u, v)
+            });
+            let Some(matrix) =
+                triangle_bitmap_matrix(corners, uvs, bitmap.width.into(), bitmap.height.into())
+            else {
+                continue;
+            };
+            draws.push(Draw {
+                draw_type: DrawType::Bitmap(Bitmap {
+                    matrix: swf_bitmap_to_gl_matrix(
+                        matrix,
+                        bitmap.width.into(),
+                        bitmap.height.into(),
+                    ),
+                    bitmap_id: textured.bitmap_id,
+                    is_smoothed: textured.is_smoothed,
+                    is_repeating: textured.is_repeating,
+                }),
+                vertices: vertices.to_vec(),
+                indices: vec![0, 1, 2],
+                mask_index_count: 3,
+            });
+        }
+    }
+    draws
+}
+
 impl Default for ShapeTessellator {
     fn default() -> Self {
         Self::new()
@@ -229,10 +496,27 @@ pub struct Draw {
     pub mask_index_count: u32,
 }
 
+type MorphMesh = Vec<MorphDraw>;
+
+/// A solid color draw of a morph shape, which is interpolated between its start and end vertices.
+pub struct MorphDraw {
+    pub vertices: Vec<MorphVertex>,
+    pub indices: Vec<u32>,
+    pub mask_index_count: u32,
+}
+
+/// The same vertex of a morph shape, at the start and at the end of the morph.
+#[derive(Clone, Debug)]
+pub struct MorphVertex {
+    pub start: Vertex,
+    pub end: Vertex,
+}
+
 pub enum DrawType {
     Color,
     Gradient(Gradient),
     Bitmap(Bitmap),
+    TexturedTriangles(TexturedTriangles),
 }
 
 impl DrawType {
@@ -241,6 +525,7 @@ impl DrawType {
             Self::Color => "Color",
             Self::Gradient { .. } => "Gradient",
             Self::Bitmap { .. } => "Bitmap",
+            Self::TexturedTriangles { .. } => "TexturedTriangles",
         }
     }
 }
@@ -272,6 +557,17 @@ pub struct Bitmap {
     pub is_repeating: bool,
 }
 
+/// Triangles with a bitmap mapped onto them by the texture coordinates of each vertex,
+/// rather than by a matrix.
+#[derive(Clone, Debug)]
+pub struct TexturedTriangles {
+    pub bitmap_id: u16,
+    /// The texture coordinates of each of the draw's vertices, from 0 to 1 across the bitmap.
+    pub uvs: Vec<[f32; 2]>,
+    pub is_smoothed: bool,
+    pub is_repeating: bool,
+}
+
 #[allow(clippy::many_single_char_names)]
 fn swf_to_gl_matrix(m: crate::matrix::Matrix) -> [[f32; 3]; 3] {
     let tx = m.tx.get() as f32;
@@ -326,10 +622,65 @@ fn swf_bitmap_to_gl_matrix(
     [[a, d, 0.0], [b, e, 0.0], [c, f, 1.0]]
 }
 
-fn ruffle_path_to_lyon_path(commands: &[DrawCommand], is_closed: bool) -> Path {
-    fn point(x: swf::Twips, y: swf::Twips) -> lyon::math::Point {
+/// Moves the points of a pixel hinted stroke so that, once the shape is transformed,
+/// the edges of the stroke land on pixel boundaries.
+struct PixelSnap {
+    matrix: Matrix,
+
+    /// The inverse of the scale, rotation and skew of the matrix.
+    inverse: [f32; 4],
+
+    /// Where within a pixel the middle of the stroke goes: its center for strokes that
+    /// are an odd number of pixels wide, or its corner for the others.
+    offset: f32,
+}
+
+impl PixelSnap {
+    /// Snaps a stroke that ends up `scaled_width` pixels wide with the given transform.
+    ///
+    /// Returns `None` if the transform flattens the shape, leaving nothing to snap.
+    fn new(matrix: &Matrix, scaled_width: f32) -> Option<Self> {
+        let det = matrix.a * matrix.d - matrix.b * matrix.c;
+        if det == 0.0 {
+            return None;
+        }
+        Some(Self {
+            matrix: *matrix,
+            inverse: [
+                matrix.d / det,
+                -matrix.b / det,
+                -matrix.c / det,
+                matrix.a / det,
+            ],
+            offset: if scaled_width % 2.0 == 1.0 { 0.5 } else { 0.0 },
+        })
+    }
+
+    fn snap(&self, point: lyon::math::Point) -> lyon::math::Point {
+        let Matrix { a, b, c, d, .. } = self.matrix;
+        let tx = self.matrix.tx.to_pixels() as f32;
+        let ty = self.matrix.ty.to_pixels() as f32;
+        let snap = |v: f32| (v - self.offset).round() + self.offset;
+        let x = snap(a * point.x + c * point.y + tx) - tx;
+        let y = snap(b * point.x + d * point.y + ty) - ty;
+        let [a, b, c, d] = self.inverse;
+        lyon::math::Point::new(a * x + c * y, b * x + d * y)
+    }
+}
+
+/// Converts a path to a lyon path, snapping its anchor points if it's a pixel hinted stroke.
+fn ruffle_path_to_lyon_path(
+    commands: &[DrawCommand],
+    is_closed: bool,
+    pixel_snap: Option<&PixelSnap>,
+) -> Path {
+    fn control_point(x: swf::Twips, y: swf::Twips) -> lyon::math::Point {
         lyon::math::Point::new(x.to_pixels() as f32, y.to_pixels() as f32)
     }
+    let point = |x, y| {
+        let point = control_point(x, y);
+        pixel_snap.map_or(point, |pixel_snap| pixel_snap.snap(point))
+    };
 
     let mut builder = Path::builder();
     let mut move_to = Some((swf::Twips::default(), swf::Twips::default()));
@@ -351,7 +702,7 @@ fn ruffle_path_to_lyon_path(commands: &[DrawCommand], is_closed: bool) -> Path {
                 if let Some((x, y)) = move_to.take() {
                     builder.begin(point(x, y));
                 }
-                builder.quadratic_bezier_to(point(x1, y1), point(x2, y2));
+                builder.quadratic_bezier_to(control_point(x1, y1), point(x2, y2));
             }
         }
     }
@@ -367,6 +718,164 @@ fn ruffle_path_to_lyon_path(commands: &[DrawCommand], is_closed: bool) -> Path {
     builder.build()
 }
 
+/// Converts the matching paths of a morph shape's start and end shapes to lyon paths,
+/// where every endpoint has the same ID as its counterpart in the other path.
+/// Curves are flattened into the same number of lines on both sides, so that they still pair up.
+///
+/// Returns `None` if the paths aren't made of the same edges.
+fn morph_paths_to_lyon_paths(
+    start: &[DrawCommand],
+    end: &[DrawCommand],
+    is_closed: bool,
+    tolerance: f32,
+) -> Option<(Path, Path)> {
+    fn point((x, y): (swf::Twips, swf::Twips)) -> lyon::math::Point {
+        lyon::math::Point::new(x.to_pixels() as f32, y.to_pixels() as f32)
+    }
+
+    if start.len() != end.len() {
+        return None;
+    }
+
+    let mut builders = [Path::builder(), Path::builder()];
+    let mut pens = [lyon::math::Point::zero(); 2];
+    let mut needs_begin = true;
+    for (start_cmd, end_cmd) in start.iter().zip(end) {
+        let end_points = [point(start_cmd.end_point()), point(end_cmd.end_point())];
+        match (start_cmd, end_cmd) {
+            (DrawCommand::MoveTo { .. }, DrawCommand::MoveTo { .. }) => {
+                if !needs_begin {
+                    builders.iter_mut().for_each(|builder| builder.end(false));
+                    needs_begin = true;
+                }
+                pens = end_points;
+                continue;
+            }
+            (DrawCommand::LineTo { .. }, DrawCommand::LineTo { .. })
+            | (DrawCommand::CurveTo { .. }, DrawCommand::CurveTo { .. }) => {}
+            _ => return None,
+        }
+
+        if needs_begin {
+            for (builder, pen) in builders.iter_mut().zip(pens) {
+                builder.begin(pen);
+            }
+            needs_begin = false;
+        }
+
+        if let (
+            &DrawCommand::CurveTo { x1, y1, .. },
+            &DrawCommand::CurveTo {
+                x1: end_x1,
+                y1: end_y1,
+                ..
+            },
+        ) = (start_cmd, end_cmd)
+        {
+            let curves = [
+                QuadraticBezierSegment {
+                    from: pens[0],
+                    ctrl: point((x1, y1)),
+                    to: end_points[0],
+                },
+                QuadraticBezierSegment {
+                    from: pens[1],
+                    ctrl: point((end_x1, end_y1)),
+                    to: end_points[1],
+                },
+            ];
+            // Use enough lines for whichever curve needs more of them
+            let segments = curves
+                .iter()
+                .map(|curve| curve_segments(curve, tolerance))
+                .max()
+                .unwrap_or(1);
+            for segment in 1..=segments {
+                let t = segment as f32 / segments as f32;
+                for (builder, curve) in builders.iter_mut().zip(&curves) {
+                    builder.line_to(curve.sample(t));
+                }
+            }
+        } else {
+            for (builder, end_point) in builders.iter_mut().zip(end_points) {
+                builder.line_to(end_point);
+            }
+        }
+        pens = end_points;
+    }
+
+    if !needs_begin {
+        for builder in &mut builders {
+            if is_closed {
+                builder.close();
+            } else {
+                builder.end(false);
+            }
+        }
+    }
+
+    let [start, end] = builders;
+    Some((start.build(), end.build()))
+}
+
+/// The number of lines needed to flatten a curve within `tolerance`.
+fn curve_segments(curve: &QuadraticBezierSegment<f32>, tolerance: f32) -> u32 {
+    let dx = curve.from.x - 2.0 * curve.ctrl.x + curve.to.x;
+    let dy = curve.from.y - 2.0 * curve.ctrl.y + curve.to.y;
+    let segments = (dx.hypot(dy) / (8.0 * tolerance)).sqrt();
+    (segments.ceil() as u32).clamp(1, 256)
+}
+
+fn flush_morph_draw(
+    draws: &mut MorphMesh,
+    mesh: &mut VertexBuffers<MorphVertex, u32>,
+    mask_index_count: &mut Option<u32>,
+) {
+    if mesh.vertices.is_empty() || mesh.indices.len() < 3 {
+        // Ignore degenerate fills
+        return;
+    }
+    let mesh = std::mem::replace(mesh, VertexBuffers::new());
+    draws.push(MorphDraw {
+        mask_index_count: mask_index_count.take().unwrap_or(mesh.indices.len() as u32),
+        vertices: mesh.vertices,
+        indices: mesh.indices,
+    });
+}
+
+fn stroke_options(style: &swf::LineStyle, width: f32, tolerance: f32) -> StrokeOptions {
+    let mut stroke_options = StrokeOptions::default()
+        .with_line_width(width)
+        .with_tolerance(tolerance)
+        .with_start_cap(match style.start_cap() {
+            swf::LineCapStyle::None => tessellation::LineCap::Butt,
+            swf::LineCapStyle::Round => tessellation::LineCap::Round,
+            swf::LineCapStyle::Square => tessellation::LineCap::Square,
+        })
+        .with_end_cap(match style.end_cap() {
+            swf::LineCapStyle::None => tessellation::LineCap::Butt,
+            swf::LineCapStyle::Round => tessellation::LineCap::Round,
+            swf::LineCapStyle::Square => tessellation::LineCap::Square,
+        });
+
+    let line_join = match style.join_style() {
+        swf::LineJoinStyle::Round => tessellation::LineJoin::Round,
+        swf::LineJoinStyle::Bevel => tessellation::LineJoin::Bevel,
+        swf::LineJoinStyle::Miter(limit) => {
+            // Flash clips miters `limit` half widths out from the corner, like `MiterClip`.
+            // Avoid lyon assert with small miter limits, which clip into a bevel anyway.
+            let limit = limit.to_f32();
+            if limit >= StrokeOptions::MINIMUM_MITER_LIMIT {
+                stroke_options = stroke_options.with_miter_limit(limit);
+                tessellation::LineJoin::MiterClip
+            } else {
+                tessellation::LineJoin::Bevel
+            }
+        }
+    };
+    stroke_options.with_line_join(line_join)
+}
+
 const MAX_GRADIENT_COLORS: usize = 15;
 
 /// Converts a gradient to the uniforms used by the shader.
@@ -403,7 +912,8 @@ fn swf_gradient_to_uniforms(
         colors,
         num_colors,
         repeat_mode: gradient.spread,
-        focal_point,
+        // Flash limits the focal point to the edge of the gradient's circle
+        focal_point: focal_point.clamp(-swf::Fixed8::ONE, swf::Fixed8::ONE),
         interpolation: gradient.interpolation,
     }
 }
@@ -443,6 +953,40 @@ impl StrokeVertexConstructor<Vertex> for RuffleVertexCtor {
     }
 }
 
+/// Builds the vertices of a morph shape's fill from its start shape,
+/// finding the matching position on the end shape from the edges that each vertex came from.
+struct MorphFillVertexCtor<'a> {
+    end_path: &'a Path,
+    start_color: &'a swf::Color,
+    end_color: &'a swf::Color,
+}
+
+impl FillVertexConstructor<MorphVertex> for MorphFillVertexCtor<'_> {
+    fn new_vertex(&mut self, vertex: FillVertex) -> MorphVertex {
+        let start = vertex.position();
+        let end = match vertex.sources().next() {
+            Some(VertexSource::Endpoint { id }) => self.end_path[id],
+            // Where edges intersect, the point is somewhere along both of them
+            Some(VertexSource::Edge { from, to, t }) => {
+                self.end_path[from].lerp(self.end_path[to], t)
+            }
+            None => start,
+        };
+        MorphVertex {
+            start: Vertex {
+                x: start.x,
+                y: start.y,
+                color: self.start_color.clone(),
+            },
+            end: Vertex {
+                x: end.x,
+                y: end.y,
+                color: self.end_color.clone(),
+            },
+        }
+    }
+}
+
 #[derive(Copy, Clone, PartialEq, Eq, Debug, Enum)]
 pub enum GradientType {
     Linear,
@@ -1,5 +1,6 @@
 use crate::color_transform::ColorTransform;
 use crate::matrix::Matrix;
+use crate::matrix3d::Matrix3D;
 
 /// Represents the transform for a DisplayObject.
 /// This includes both the transformation matrix and the color transform.
@@ -7,32 +8,89 @@ use crate::matrix::Matrix;
 pub struct Transform {
     pub matrix: Matrix,
     pub color_transform: ColorTransform,
+
+    /// The 3D transform of an object with a `z` or a 3D rotation, if it or any of its ancestors
+    /// have one. `matrix` is then only the closest 2D approximation, without any perspective,
+    /// for whatever can't draw in 3D.
+    pub matrix3d: Option<Matrix3D>,
 }
 
-pub struct TransformStack(Vec<Transform>);
+pub struct TransformStack {
+    stack: Vec<Transform>,
+    perspective: Option<Matrix3D>,
+}
 
 impl TransformStack {
     pub fn new() -> Self {
-        Self(vec![Transform::default()])
+        Self {
+            stack: vec![Transform::default()],
+            perspective: None,
+        }
+    }
+
+    /// Sets the projection that is applied to everything drawn with a 3D transform,
+    /// relative to the bottom of the stack. Without one, 3D content is drawn without perspective.
+    pub fn set_perspective(&mut self, perspective: Option<Matrix3D>) {
+        self.perspective = perspective;
     }
 
     pub fn push(&mut self, transform: &Transform) {
-        let cur_transform = self.transform();
-        let matrix = cur_transform.matrix * transform.matrix;
+        let cur_transform = self.top();
+        let matrix = cur_transform.matrix
+            * transform
+                .matrix3d
+                .as_ref()
+                .map_or(transform.matrix, Matrix3D::to_affine);
         let color_transform = cur_transform.color_transform * transform.color_transform;
-        self.0.push(Transform {
+        let matrix3d = if cur_transform.matrix3d.is_some() || transform.matrix3d.is_some() {
+            Some(
+                cur_transform
+                    .matrix3d
+                    .unwrap_or_else(|| cur_transform.matrix.into())
+                    * transform
+                        .matrix3d
+                        .unwrap_or_else(|| transform.matrix.into()),
+            )
+        } else {
+            None
+        };
+        self.stack.push(Transform {
             matrix,
             color_transform,
+            matrix3d,
         });
     }
 
+    /// Pushes a new entry that keeps the current matrix, but resets the color transform.
+    ///
+    /// This is used when content is rendered as a group and the color transform
+    /// is applied to the finished group instead.
+    pub fn push_without_color_transform(&mut self) {
+        let cur_transform = self.top();
+        let transform = Transform {
+            matrix: cur_transform.matrix,
+            color_transform: Default::default(),
+            matrix3d: cur_transform.matrix3d,
+        };
+        self.stack.push(transform);
+    }
+
     pub fn pop(&mut self) {
-        assert!(self.0.len() > 1, "Transform stack underflow");
-        self.0.pop();
+        assert!(self.stack.len() > 1, "Transform stack underflow");
+        self.stack.pop();
     }
 
+    /// The transform to draw with, including the perspective of any 3D transform.
     pub fn transform(&self) -> Transform {
-        self.0[self.0.len() - 1].clone()
+        let mut transform = self.top().clone();
+        if let (Some(matrix3d), Some(perspective)) = (&mut transform.matrix3d, self.perspective) {
+            *matrix3d = perspective * *matrix3d;
+        }
+        transform
+    }
+
+    fn top(&self) -> &Transform {
+        &self.stack[self.stack.len() - 1]
     }
 }
 
@@ -400,8 +400,22 @@ fn decode_gif(data: &[u8]) -> Result<Bitmap, Error> {
     ))
 }
 
+/// Converts the bits of a half precision float to a float.
+pub fn f16_to_f32(bits: u16) -> f32 {
+    let sign = if bits & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = i32::from((bits >> 10) & 0x1F);
+    let mantissa = f32::from(bits & 0x3FF);
+    let magnitude = match exponent {
+        0 => mantissa * 2f32.powi(-24),
+        0x1F if mantissa == 0.0 => f32::INFINITY,
+        0x1F => f32::NAN,
+        _ => (1.0 + mantissa / 1024.0) * 2f32.powi(exponent - 15),
+    };
+    sign * magnitude
+}
+
 /// Converts standard RBGA to premultiplied alpha.
-fn premultiply_alpha_rgba(rgba: &mut [u8]) {
+pub fn premultiply_alpha_rgba(rgba: &mut [u8]) {
     rgba.chunks_exact_mut(4).for_each(|rgba| {
         let a = f32::from(rgba[3]) / 255.0;
         rgba[0] = (f32::from(rgba[0]) * a) as u8;
@@ -423,6 +437,34 @@ pub fn unmultiply_alpha_rgba(rgba: &mut [u8]) {
     })
 }
 
+/// Halves the size of premultiplied RGBA data, averaging each 2x2 block of pixels into one.
+/// This produces the next level of a mipmap chain. A dimension of 1 stays 1, and the last
+/// row or column of an odd sized image is only blended into the pixels next to it.
+pub fn downsample_rgba(width: u32, height: u32, rgba: &[u8]) -> (u32, u32, Vec<u8>) {
+    let new_width = (width / 2).max(1);
+    let new_height = (height / 2).max(1);
+    let pixel = |x: u32, y: u32| {
+        let i = (y.min(height - 1) as usize * width as usize + x.min(width - 1) as usize) * 4;
+        &rgba[i..i + 4]
+    };
+    let mut out = Vec::with_capacity(new_width as usize * new_height as usize * 4);
+    for y in 0..new_height {
+        for x in 0..new_width {
+            let block = [
+                pixel(x * 2, y * 2),
+                pixel(x * 2 + 1, y * 2),
+                pixel(x * 2, y * 2 + 1),
+                pixel(x * 2 + 1, y * 2 + 1),
+            ];
+            for channel in 0..4 {
+                let sum: u32 = block.iter().map(|p| u32::from(p[channel])).sum();
+                out.push(((sum + 2) / 4) as u8);
+            }
+        }
+    }
+    (new_width, new_height, out)
+}
+
 /// Decodes zlib-compressed data.
 fn decompress_zlib(data: &[u8]) -> Result<Vec<u8>, Error> {
     let mut out_data = Vec::new();
@@ -433,3 +475,49 @@ fn decompress_zlib(data: &[u8]) -> Result<Vec<u8>, Error> {
     out_data.shrink_to_fit();
     Ok(out_data)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn downsample_checkerboard() {
+        // A checkerboard of single black and white pixels, which shimmers when point sampled
+        let size = 64;
+        let mut rgba = Vec::new();
+        for y in 0..size {
+            for x in 0..size {
+                let value = if (x + y) % 2 == 0 { 255 } else { 0 };
+                rgba.extend_from_slice(&[value, value, value, 255]);
+            }
+        }
+
+        // Scaled down by 8x, every pixel should be the same gray
+        let (mut width, mut height) = (size, size);
+        for _ in 0..3 {
+            (width, height, rgba) = downsample_rgba(width, height, &rgba);
+        }
+        assert_eq!((width, height), (8, 8));
+        assert!(rgba.chunks_exact(4).all(|p| p == [128, 128, 128, 255]));
+    }
+
+    #[test]
+    fn downsample_odd_sizes() {
+        let rgba = [255, 0, 0, 255, 0, 255, 0, 255, 0, 0, 255, 255];
+        assert_eq!(downsample_rgba(3, 1, &rgba), (1, 1, vec![128, 128, 0, 255]));
+        assert_eq!(downsample_rgba(1, 3, &rgba), (1, 1, vec![128, 128, 0, 255]));
+        assert_eq!(
+            downsample_rgba(1, 1, &rgba[..4]),
+            (1, 1, vec![255, 0, 0, 255])
+        );
+    }
+
+    #[test]
+    fn half_floats() {
+        assert_eq!(f16_to_f32(0x3C00), 1.0);
+        assert_eq!(f16_to_f32(0x3800), 0.5);
+        assert_eq!(f16_to_f32(0xC000), -2.0);
+        assert_eq!(f16_to_f32(0x0001), 2f32.powi(-24));
+        assert_eq!(f16_to_f32(0x7C00), f32::INFINITY);
+    }
+}
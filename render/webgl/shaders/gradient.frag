@@ -44,8 +44,18 @@ void main() {
         vec2 uv = frag_uv * 2.0 - 1.0;
         vec2 d = vec2(u_focal_point, 0.0) - uv;
         float l = length(d);
-        d /= l;
-        t = l / (sqrt(1.0 -  u_focal_point*u_focal_point*d.y*d.y) + u_focal_point*d.x);
+        if( l == 0.0 )
+        {
+            // The focal point itself has no direction, but always starts the gradient
+            t = 0.0;
+        }
+        else
+        {
+            d /= l;
+            // With the focal point on the edge of the circle, the denominator reaches 0 behind it.
+            // Keep t finite there, so that it's still handled by the spread mode below.
+            t = l / max(sqrt(1.0 - u_focal_point*u_focal_point*d.y*d.y) + u_focal_point*d.x, 0.0001);
+        }
     }
     if( u_repeat_mode == 0 )
     {
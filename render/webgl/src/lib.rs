@@ -12,14 +12,17 @@ use ruffle_render::backend::{
     Context3D, Context3DCommand, RenderBackend, ShapeHandle, ViewportDimensions,
 };
 use ruffle_render::bitmap::{
-    Bitmap, BitmapFormat, BitmapHandle, BitmapHandleImpl, BitmapSource, SyncHandle,
+    Bitmap, BitmapFormat, BitmapHandle, BitmapHandleImpl, BitmapRegion, BitmapSource, SyncHandle,
 };
+use ruffle_render::color_transform::ColorTransform;
 use ruffle_render::commands::{CommandHandler, CommandList};
 use ruffle_render::error::Error as BitmapError;
+use ruffle_render::filters::Filter;
 use ruffle_render::quality::StageQuality;
 use ruffle_render::shape_utils::DistilledShape;
 use ruffle_render::tessellator::{
-    Gradient as TessGradient, GradientType, ShapeTessellator, Vertex as TessVertex,
+    split_textured_triangles, Gradient as TessGradient, GradientType, ShapeTessellator,
+    Vertex as TessVertex,
 };
 use ruffle_render::transform::Transform;
 use ruffle_web_common::{JsError, JsResult};
@@ -139,6 +142,8 @@ pub struct WebGlRenderBackend {
     mask_state: MaskState,
     num_masks: u32,
     mask_state_dirty: bool,
+    /// Whether each of the masks that are currently pushed is inverted.
+    inverted_masks: Vec<bool>,
     is_transparent: bool,
 
     active_program: *const ShaderProgram,
@@ -311,8 +316,8 @@ impl WebGlRenderBackend {
             shape_tessellator: ShapeTessellator::new(),
 
             meshes: vec![],
-            color_quad_shape: ShapeHandle(0),
-            bitmap_quad_shape: ShapeHandle(1),
+            color_quad_shape: ShapeHandle::from_index(0),
+            bitmap_quad_shape: ShapeHandle::from_index(1),
             renderbuffer_width: 1,
             renderbuffer_height: 1,
             view_matrix: [[0.0; 4]; 4],
@@ -320,6 +325,7 @@ impl WebGlRenderBackend {
             mask_state: MaskState::NoMask,
             num_masks: 0,
             mask_state_dirty: true,
+            inverted_masks: vec![],
             is_transparent,
 
             active_program: std::ptr::null(),
@@ -581,9 +587,12 @@ impl WebGlRenderBackend {
     ) -> Result<Mesh, Error> {
         use ruffle_render::tessellator::DrawType as TessDrawType;
 
-        let lyon_mesh = self
-            .shape_tessellator
-            .tessellate_shape(shape, bitmap_source);
+        // Bitmaps can only be mapped onto shapes by a matrix here
+        let lyon_mesh = split_textured_triangles(
+            self.shape_tessellator
+                .tessellate_shape(shape, bitmap_source),
+            bitmap_source,
+        );
 
         let mut draws = Vec::with_capacity(lyon_mesh.len());
         for draw in lyon_mesh {
@@ -614,6 +623,9 @@ impl WebGlRenderBackend {
                 TessDrawType::Color => &self.color_program,
                 TessDrawType::Gradient(_) => &self.gradient_program,
                 TessDrawType::Bitmap(_) => &self.bitmap_program,
+                TessDrawType::TexturedTriangles(_) => {
+                    unreachable!("Textured triangles must be split into bitmap draws")
+                }
             };
 
             // Unfortunately it doesn't seem to be possible to ensure that vertex attributes will be in
@@ -696,6 +708,9 @@ impl WebGlRenderBackend {
                     num_indices,
                     num_mask_indices,
                 },
+                TessDrawType::TexturedTriangles(_) => {
+                    unreachable!("Textured triangles must be split into bitmap draws")
+                }
             });
 
             self.bind_vertex_array(None);
@@ -747,6 +762,14 @@ impl WebGlRenderBackend {
         }
     }
 
+    /// A rectangle over the whole render buffer, for drawing inverted masks.
+    fn cover_matrix(&self) -> ruffle_render::matrix::Matrix {
+        ruffle_render::matrix::Matrix::scale(
+            self.renderbuffer_width as f32,
+            self.renderbuffer_height as f32,
+        )
+    }
+
     fn set_stencil_state(&mut self) {
         // Set stencil state for masking, if necessary.
         if self.mask_state_dirty {
@@ -807,6 +830,7 @@ impl WebGlRenderBackend {
         self.mask_state = MaskState::NoMask;
         self.num_masks = 0;
         self.mask_state_dirty = true;
+        self.inverted_masks.clear();
 
         self.mult_color = None;
         self.add_color = None;
@@ -911,7 +935,7 @@ impl WebGlRenderBackend {
             program.uniform1i(&self.gl, ShaderUniform::BitmapTexture, 0);
 
             // Render the quad.
-            let quad = &self.meshes[self.bitmap_quad_shape.0];
+            let quad = &self.meshes[self.bitmap_quad_shape.index()];
             self.bind_vertex_array(Some(&quad.draws[0].vao));
             self.gl.draw_elements_with_i32(
                 Gl::TRIANGLES,
@@ -989,7 +1013,7 @@ impl RenderBackend for WebGlRenderBackend {
         shape: DistilledShape,
         bitmap_source: &dyn BitmapSource,
     ) -> ShapeHandle {
-        let handle = ShapeHandle(self.meshes.len());
+        let handle = ShapeHandle::from_index(self.meshes.len());
         match self.register_shape_internal(shape, bitmap_source) {
             Ok(mesh) => self.meshes.push(mesh),
             Err(e) => log::error!("Couldn't register shape: {:?}", e),
@@ -1003,16 +1027,16 @@ impl RenderBackend for WebGlRenderBackend {
         bitmap_source: &dyn BitmapSource,
         handle: ShapeHandle,
     ) {
-        self.delete_mesh(&self.meshes[handle.0]);
+        self.delete_mesh(&self.meshes[handle.index()]);
         match self.register_shape_internal(shape, bitmap_source) {
-            Ok(mesh) => self.meshes[handle.0] = mesh,
+            Ok(mesh) => self.meshes[handle.index()] = mesh,
             Err(e) => log::error!("Couldn't replace shape: {:?}", e),
         }
     }
 
     fn register_glyph_shape(&mut self, glyph: &swf::Glyph) -> ShapeHandle {
         let shape = ruffle_render::shape_utils::swf_glyph_to_shape(glyph);
-        let handle = ShapeHandle(self.meshes.len());
+        let handle = ShapeHandle::from_index(self.meshes.len());
         match self.register_shape_internal((&shape).into(), &NullBitmapSource) {
             Ok(mesh) => self.meshes.push(mesh),
             Err(e) => log::error!("Couldn't register glyph shape: {:?}", e),
@@ -1026,10 +1050,58 @@ impl RenderBackend for WebGlRenderBackend {
         self.end_frame();
     }
 
-    fn register_bitmap(&mut self, bitmap: Bitmap) -> Result<BitmapHandle, BitmapError> {
+    fn capture_frame(&mut self, clear: Color, commands: CommandList) -> Option<Bitmap> {
+        self.submit_frame(clear, commands);
+
+        // With MSAA, the resolved frame is still in its own framebuffer. Otherwise it's read
+        // straight from the canvas, which only works until the browser has presented it.
+        let framebuffer = self
+            .msaa_buffers
+            .as_ref()
+            .map(|msaa_buffers| &msaa_buffers.color_framebuffer);
+        self.gl.bind_framebuffer(Gl::FRAMEBUFFER, framebuffer);
+        let (width, height) = (self.renderbuffer_width, self.renderbuffer_height);
+        let mut pixels = vec![0; width as usize * height as usize * 4];
+        let result = self.gl.read_pixels_with_opt_u8_array(
+            0,
+            0,
+            width,
+            height,
+            Gl::RGBA,
+            Gl::UNSIGNED_BYTE,
+            Some(&mut pixels),
+        );
+        self.gl.bind_framebuffer(Gl::FRAMEBUFFER, None);
+        if let Err(e) = result {
+            log::warn!("Couldn't capture frame: {:?}", e);
+            return None;
+        }
+
+        // WebGL reads the rows from the bottom up
+        let row_size = width as usize * 4;
+        let rgba = pixels
+            .chunks_exact(row_size)
+            .rev()
+            .flatten()
+            .copied()
+            .collect();
+        Some(Bitmap::new(
+            width as u32,
+            height as u32,
+            BitmapFormat::Rgba,
+            rgba,
+        ))
+    }
+
+    fn register_bitmap(&mut self, mut bitmap: Bitmap) -> Result<BitmapHandle, BitmapError> {
         let format = match bitmap.format() {
             BitmapFormat::Rgb => Gl::RGB,
             BitmapFormat::Rgba => Gl::RGBA,
+            // Textures are drawn as premultiplied 8-bit RGBA, so other formats are converted up front
+            BitmapFormat::StraightRgba | BitmapFormat::Rgba16 | BitmapFormat::Rgba16Float => {
+                bitmap = bitmap.to_rgba();
+                Gl::RGBA
+            }
         };
 
         let texture = self
@@ -1146,23 +1218,33 @@ impl RenderBackend for WebGlRenderBackend {
 
 impl CommandHandler for WebGlRenderBackend {
     fn render_bitmap(&mut self, bitmap: BitmapHandle, transform: Transform, smoothing: bool) {
+        let entry = as_registry_data(&bitmap);
+        let region = BitmapRegion {
+            x: 0,
+            y: 0,
+            width: entry.bitmap.width(),
+            height: entry.bitmap.height(),
+        };
+        self.render_bitmap_region(bitmap, region, transform, smoothing);
+    }
+
+    fn render_bitmap_region(
+        &mut self,
+        bitmap: BitmapHandle,
+        region: BitmapRegion,
+        transform: Transform,
+        smoothing: bool,
+    ) {
         self.set_stencil_state();
         let entry = as_registry_data(&bitmap);
         // Adjust the quad draw to use the target bitmap.
-        let mesh = &self.meshes[self.bitmap_quad_shape.0];
+        let mesh = &self.meshes[self.bitmap_quad_shape.index()];
         let draw = &mesh.draws[0];
-        let bitmap_matrix = if let DrawType::Bitmap(BitmapDraw { matrix, .. }) = &draw.draw_type {
-            matrix
-        } else {
-            unreachable!()
-        };
+        let bitmap_matrix = region.texture_matrix(entry.bitmap.width(), entry.bitmap.height());
 
-        // Scale the quad to the bitmap's dimensions.
+        // Scale the quad to the region's dimensions.
         let matrix = transform.matrix
-            * ruffle_render::matrix::Matrix::scale(
-                entry.bitmap.width() as f32,
-                entry.bitmap.height() as f32,
-            );
+            * ruffle_render::matrix::Matrix::scale(region.width as f32, region.height as f32);
 
         let world_matrix = [
             [matrix.a, matrix.b, 0.0, 0.0],
@@ -1206,7 +1288,7 @@ impl CommandHandler for WebGlRenderBackend {
             self.add_color = Some(add_color);
         }
 
-        program.uniform_matrix3fv(&self.gl, ShaderUniform::TextureMatrix, bitmap_matrix);
+        program.uniform_matrix3fv(&self.gl, ShaderUniform::TextureMatrix, &bitmap_matrix);
 
         // Bind texture.
         self.gl.active_texture(Gl::TEXTURE0);
@@ -1253,7 +1335,7 @@ impl CommandHandler for WebGlRenderBackend {
 
         self.set_stencil_state();
 
-        let mesh = &self.meshes[shape.0];
+        let mesh = &self.meshes[shape.index()];
         for draw in &mesh.draws {
             // Ignore strokes when drawing a mask stencil.
             let num_indices = if self.mask_state != MaskState::DrawMaskStencil
@@ -1434,7 +1516,7 @@ impl CommandHandler for WebGlRenderBackend {
             self.add_color = Some(add_color);
         }
 
-        let quad = &self.meshes[self.color_quad_shape.0];
+        let quad = &self.meshes[self.color_quad_shape.index()];
         self.bind_vertex_array(Some(&quad.draws[0].vao));
 
         self.gl.draw_elements_with_i32(
@@ -1452,21 +1534,52 @@ impl CommandHandler for WebGlRenderBackend {
         self.num_masks += 1;
         self.mask_state = MaskState::DrawMaskStencil;
         self.mask_state_dirty = true;
+        self.inverted_masks.push(false);
+    }
+
+    fn push_inverted_mask(&mut self) {
+        // Raise the stencil of the whole parent mask area to the new level,
+        // so that the mask's shapes can be cut out of it by clearing them.
+        debug_assert!(
+            self.mask_state == MaskState::NoMask || self.mask_state == MaskState::DrawMaskedContent
+        );
+        self.num_masks += 1;
+        self.mask_state = MaskState::DrawMaskStencil;
+        self.mask_state_dirty = true;
+        self.inverted_masks.push(true);
+        self.draw_rect(Color::WHITE, self.cover_matrix());
+        self.mask_state = MaskState::ClearMaskStencil;
+        self.mask_state_dirty = true;
     }
 
     fn activate_mask(&mut self) {
-        debug_assert!(self.num_masks > 0 && self.mask_state == MaskState::DrawMaskStencil);
+        debug_assert!(
+            self.num_masks > 0
+                && (self.mask_state == MaskState::DrawMaskStencil
+                    || self.mask_state == MaskState::ClearMaskStencil)
+        );
         self.mask_state = MaskState::DrawMaskedContent;
         self.mask_state_dirty = true;
     }
 
     fn deactivate_mask(&mut self) {
         debug_assert!(self.num_masks > 0 && self.mask_state == MaskState::DrawMaskedContent);
-        self.mask_state = MaskState::ClearMaskStencil;
+        self.mask_state = if self.inverted_masks.last() == Some(&true) {
+            MaskState::DrawMaskStencil
+        } else {
+            MaskState::ClearMaskStencil
+        };
         self.mask_state_dirty = true;
     }
 
     fn pop_mask(&mut self) {
+        if self.inverted_masks.pop() == Some(true) {
+            debug_assert!(self.num_masks > 0 && self.mask_state == MaskState::DrawMaskStencil);
+            // Lower the stencil of the whole area back to the parent mask's level.
+            self.mask_state = MaskState::ClearMaskStencil;
+            self.mask_state_dirty = true;
+            self.draw_rect(Color::WHITE, self.cover_matrix());
+        }
         debug_assert!(self.num_masks > 0 && self.mask_state == MaskState::ClearMaskStencil);
         self.num_masks -= 1;
         self.mask_state = if self.num_masks == 0 {
@@ -1477,11 +1590,34 @@ impl CommandHandler for WebGlRenderBackend {
         self.mask_state_dirty = true;
     }
 
-    fn blend(&mut self, commands: CommandList, blend: BlendMode) {
+    fn blend(
+        &mut self,
+        mut commands: CommandList,
+        blend: BlendMode,
+        color_transform: ColorTransform,
+    ) {
+        // Groups aren't composited separately here, so apply the color transform to each child.
+        commands.apply_color_transform(color_transform);
         self.push_blend_mode(blend);
         commands.execute(self);
         self.pop_blend_mode();
     }
+
+    fn filters(&mut self, commands: CommandList, _filters: Vec<Filter>) {
+        // Filters aren't supported here, so just draw the unfiltered contents.
+        commands.execute(self);
+    }
+
+    fn alpha_mask(&mut self, maskee: CommandList, mask: CommandList) {
+        // Alpha masks aren't supported here, so fall back to masking by shape.
+        self.push_mask();
+        mask.clone().execute(self);
+        self.activate_mask();
+        maskee.execute(self);
+        self.deactivate_mask();
+        mask.execute(self);
+        self.pop_mask();
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -0,0 +1,230 @@
+//! Frame times on scenes that stress how draws are batched and how their uniforms are uploaded.
+//!
+//! Run with `cargo bench -p ruffle_render_wgpu`. Without a wgpu device, nothing is measured.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use ruffle_render::backend::null::NullBitmapSource;
+use ruffle_render::backend::{RenderBackend, ShapeHandle};
+use ruffle_render::bitmap::{Bitmap, BitmapFormat};
+use ruffle_render::color_transform::ColorTransform;
+use ruffle_render::commands::{CommandHandler, CommandList};
+use ruffle_render::matrix::Matrix;
+use ruffle_render::shape_utils::{DistilledShape, DrawCommand, DrawPath, FillRule};
+use ruffle_render::transform::Transform;
+use ruffle_render_wgpu::backend::WgpuRenderBackend;
+use ruffle_render_wgpu::target::TextureTarget;
+use ruffle_render_wgpu::wgpu;
+use swf::{Color, FillStyle, Fixed8, Twips};
+
+const SIZE: u32 = 512;
+
+/// How many shapes the scenes of shapes draw.
+const NUM_SHAPES: u32 = 5000;
+
+/// How many copies of a bitmap the scene of particles draws.
+const NUM_PARTICLES: u32 = 2000;
+
+/// A renderer drawing into a texture, or `None` if there's no device to measure.
+fn renderer() -> Option<WgpuRenderBackend<TextureTarget>> {
+    match WgpuRenderBackend::for_offscreen(
+        (SIZE, SIZE),
+        wgpu::Backends::all(),
+        wgpu::PowerPreference::HighPerformance,
+        None,
+    ) {
+        Ok(renderer) => Some(renderer),
+        Err(e) => {
+            eprintln!("Couldn't create a wgpu device to measure: {e}");
+            None
+        }
+    }
+}
+
+/// Measures how long it takes to submit the commands of `frame`, and for the device to
+/// finish drawing them.
+fn bench_frame(
+    c: &mut Criterion,
+    name: &str,
+    renderer: &mut WgpuRenderBackend<TextureTarget>,
+    frame: impl Fn() -> CommandList,
+) {
+    // The first frame also creates the pipelines and buffers that the rest reuse
+    renderer.submit_frame(Color::from_rgba(0), frame());
+    c.bench_function(name, |b| {
+        b.iter(|| {
+            renderer.submit_frame(Color::from_rgba(0), frame());
+            renderer.descriptors().device.poll(wgpu::Maintain::Wait);
+        })
+    });
+}
+
+/// A small triangle, filled with the given color.
+fn triangle(renderer: &mut WgpuRenderBackend<TextureTarget>, color: Color) -> ShapeHandle {
+    let style = FillStyle::Color(color);
+    let point = |x: f64, y: f64| (Twips::from_pixels(x), Twips::from_pixels(y));
+    let [(x0, y0), (x1, y1), (x2, y2)] = [point(0.0, 0.0), point(8.0, 0.0), point(0.0, 8.0)];
+    let shape = DistilledShape {
+        paths: vec![DrawPath::Fill {
+            style: &style,
+            commands: vec![
+                DrawCommand::MoveTo { x: x0, y: y0 },
+                DrawCommand::LineTo { x: x1, y: y1 },
+                DrawCommand::LineTo { x: x2, y: y2 },
+                DrawCommand::LineTo { x: x0, y: y0 },
+            ],
+            winding_rule: FillRule::EvenOdd,
+        }],
+        shape_bounds: Default::default(),
+        edge_bounds: Default::default(),
+        id: 0,
+        stroke_matrix: None,
+    };
+    renderer.register_shape(shape, &NullBitmapSource)
+}
+
+/// Two shapes, which are alternated between to keep them from being batched together.
+fn two_triangles(renderer: &mut WgpuRenderBackend<TextureTarget>) -> [ShapeHandle; 2] {
+    [
+        triangle(renderer, Color::from_rgb(0xFF0000, 255)),
+        triangle(renderer, Color::from_rgb(0x0000FF, 255)),
+    ]
+}
+
+/// Many shapes drawn one after another with the same transform, as particles that share
+/// a parent are. Each draw reuses the uniforms that the one before it uploaded.
+fn identical_transforms(c: &mut Criterion, renderer: &mut WgpuRenderBackend<TextureTarget>) {
+    let shapes = two_triangles(renderer);
+    let transform = Transform {
+        matrix: Matrix::translate(Twips::from_pixels(64.0), Twips::from_pixels(64.0)),
+        ..Default::default()
+    };
+    bench_frame(c, "identical_transforms", renderer, || {
+        let mut commands = CommandList::new();
+        for index in 0..NUM_SHAPES {
+            commands.render_shape(shapes[(index % 2) as usize].clone(), transform.clone());
+        }
+        commands
+    });
+}
+
+/// Many copies of one bitmap, each with its own position and alpha, as particle effects
+/// draw them. The copies are drawn one after another, so they're merged into one draw.
+fn bitmap_particles(c: &mut Criterion, renderer: &mut WgpuRenderBackend<TextureTarget>) {
+    let bitmap = renderer
+        .register_bitmap(Bitmap::new(8, 8, BitmapFormat::Rgba, vec![255; 8 * 8 * 4]))
+        .expect("Bitmap should be registered");
+    bench_frame(c, "bitmap_particles", renderer, || {
+        let mut commands = CommandList::new();
+        for index in 0..NUM_PARTICLES {
+            let transform = Transform {
+                matrix: Matrix::translate(
+                    Twips::from_pixels(f64::from(index % SIZE)),
+                    Twips::from_pixels(f64::from(index / SIZE * 8)),
+                ),
+                color_transform: ColorTransform {
+                    a_mult: Fixed8::from_f32((index % 256) as f32 / 255.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            commands.render_bitmap(bitmap.clone(), transform, true);
+        }
+        commands
+    });
+}
+
+/// Many small solid color rects, as a vector drawing of pixels is made of. The rects are
+/// drawn one after another, so they're merged into one draw.
+fn color_rects(c: &mut Criterion, renderer: &mut WgpuRenderBackend<TextureTarget>) {
+    bench_frame(c, "color_rects", renderer, || {
+        let mut commands = CommandList::new();
+        for index in 0..NUM_SHAPES {
+            let (x, y) = (index % (SIZE / 4) * 4, index / (SIZE / 4) * 4);
+            commands.draw_rect(
+                Color::from_rgb(index * 0x10101, 255),
+                Matrix::create_box(
+                    4.0,
+                    4.0,
+                    0.0,
+                    Twips::from_pixels(f64::from(x)),
+                    Twips::from_pixels(f64::from(y)),
+                ),
+            );
+        }
+        commands
+    });
+}
+
+/// Many shapes, each with its own transform. Only devices without push constants upload
+/// those transforms as uniforms.
+fn shapes(c: &mut Criterion, renderer: &mut WgpuRenderBackend<TextureTarget>) {
+    let shapes = two_triangles(renderer);
+    bench_frame(c, "shapes", renderer, || {
+        let mut commands = CommandList::new();
+        for index in 0..NUM_SHAPES {
+            let (x, y) = (index % 64, index / 64);
+            commands.render_shape(
+                shapes[(index % 2) as usize].clone(),
+                Transform {
+                    matrix: Matrix::translate(
+                        Twips::from_pixels(f64::from(x * 8)),
+                        Twips::from_pixels(f64::from(y * 8 % SIZE)),
+                    ),
+                    color_transform: ColorTransform {
+                        a_mult: Fixed8::from_f32(0.5),
+                        ..ColorTransform::IDENTITY
+                    },
+                    ..Default::default()
+                },
+            );
+        }
+        commands
+    });
+}
+
+/// One small bitmap tiled across the whole target 10,000 times, which is drawn as a single
+/// instanced draw whose instance buffer is reused from the frame before.
+fn bitmap_tiling(c: &mut Criterion, renderer: &mut WgpuRenderBackend<TextureTarget>) {
+    const TILES: u32 = 100;
+    let tile_size = SIZE / TILES;
+    let bitmap = renderer
+        .register_bitmap(Bitmap::new(
+            tile_size,
+            tile_size,
+            BitmapFormat::Rgba,
+            [0, 128, 255, 255].repeat((tile_size * tile_size) as usize),
+        ))
+        .expect("Bitmap should be registered");
+    bench_frame(c, "bitmap_tiling", renderer, || {
+        let mut commands = CommandList::new();
+        for index in 0..TILES * TILES {
+            let (x, y) = (index % TILES, index / TILES);
+            commands.render_bitmap(
+                bitmap.clone(),
+                Transform {
+                    matrix: Matrix::translate(
+                        Twips::from_pixels(f64::from(x * tile_size)),
+                        Twips::from_pixels(f64::from(y * tile_size)),
+                    ),
+                    ..Default::default()
+                },
+                false,
+            );
+        }
+        commands
+    });
+}
+
+fn frame(c: &mut Criterion) {
+    let Some(mut renderer) = renderer() else {
+        return;
+    };
+    identical_transforms(c, &mut renderer);
+    bitmap_particles(c, &mut renderer);
+    color_rects(c, &mut renderer);
+    shapes(c, &mut renderer);
+    bitmap_tiling(c, &mut renderer);
+}
+
+criterion_group!(benches, frame);
+criterion_main!(benches);
@@ -0,0 +1,66 @@
+//! Renders with a device that belongs to the application, into a texture of its own.
+
+use ruffle_render::backend::RenderBackend;
+use ruffle_render::commands::{CommandHandler, CommandList};
+use ruffle_render::matrix::Matrix;
+use ruffle_render_wgpu::backend::{request_device, WgpuRenderBackend};
+use ruffle_render_wgpu::descriptors::Descriptors;
+use ruffle_render_wgpu::target::ExternalTextureTarget;
+use ruffle_render_wgpu::wgpu;
+use std::sync::Arc;
+use swf::{Color, Twips};
+
+fn main() {
+    // The application creates its device however it likes, and keeps using it afterwards
+    let instance = wgpu::Instance::new(Default::default());
+    let adapter = futures::executor::block_on(instance.request_adapter(&Default::default()))
+        .expect("No graphics adapter found");
+    let (device, queue) = futures::executor::block_on(request_device(&adapter, None))
+        .expect("Couldn't create a device");
+    let device = Arc::new(device);
+
+    let (width, height) = (400, 300);
+    let format = wgpu::TextureFormat::Rgba8UnormSrgb;
+    let texture = Arc::new(device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Application texture"),
+        size: wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        view_formats: &[format],
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+    }));
+
+    // Ruffle creates everything it needs on the same device
+    let descriptors = Arc::new(Descriptors::with_shared_device(
+        adapter,
+        device.clone(),
+        Arc::new(queue),
+    ));
+    let target = ExternalTextureTarget::new(texture.clone(), format, (width, height));
+    let mut renderer =
+        WgpuRenderBackend::new(descriptors, target).expect("Couldn't create the renderer");
+
+    let mut commands = CommandList::new();
+    commands.draw_rect(
+        Color::from_rgb(0xFF8000, 255),
+        Matrix::create_box(
+            width as f32 / 2.0,
+            height as f32 / 2.0,
+            0.0,
+            Twips::from_pixels(width as f64 / 4.0),
+            Twips::from_pixels(height as f64 / 4.0),
+        ),
+    );
+    renderer.submit_frame(Color::from_rgb(0x000000, 255), commands);
+
+    // The texture now holds the frame, ready for the application to sample from
+    let _view = texture.create_view(&Default::default());
+    device.poll(wgpu::Maintain::Wait);
+    println!("Rendered a {width}x{height} frame into the application's texture");
+}
@@ -0,0 +1,27 @@
+#import filter
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+#if use_push_constants == true
+    @group(2) @binding(0) var mask_texture: texture_2d<f32>;
+#else
+    @group(4) @binding(0) var mask_texture: texture_2d<f32>;
+#endif
+
+@vertex
+fn main_vertex(in: filter::FilterVertexInput) -> filter::VertexOutput {
+    return filter::main_vertex(in);
+}
+
+@fragment
+fn main_fragment(in: filter::VertexOutput) -> @location(0) vec4<f32> {
+    // The maskee and the mask are both rendered over the whole frame, so they line up exactly
+    let position = vec2<i32>(floor(in.position.xy));
+    let maskee = textureLoad(filter::texture, position, 0);
+    let mask = textureLoad(mask_texture, position, 0);
+    // Both are premultiplied, so scaling every channel scales the alpha of the maskee
+    return maskee * mask.a;
+}
@@ -33,7 +33,12 @@ fn main_vertex(in: common::VertexInput) -> VertexOutput {
 
 @fragment
 fn main_fragment(in: VertexOutput) -> @location(0) vec4<f32> {
-    var color: vec4<f32> = textureSample(texture, texture_sampler, in.uv);
+    var color: vec4<f32>;
+    if( textureTransforms.premultiply != 0.0 ) {
+        color = common::sample_straight_alpha(texture, texture_sampler, in.uv, textureTransforms.premultiply);
+    } else {
+        color = textureSample(texture, texture_sampler, in.uv);
+    }
     #if use_push_constants == true
         var colorTransforms = pc.colorTransforms;
     #endif
@@ -41,7 +46,7 @@ fn main_fragment(in: VertexOutput) -> @location(0) vec4<f32> {
     // Unmultiply alpha, apply color transform, remultiply alpha.
     if( color.a > 0.0 ) {
         color = vec4<f32>(color.rgb / color.a, color.a);
-        color = color * colorTransforms.mult_color + colorTransforms.add_color;
+        color = common::apply_color_transforms(color, colorTransforms);
         let alpha = clamp(color.a, 0.0, 1.0);
         color = vec4<f32>(color.rgb * alpha, alpha);
     }
@@ -0,0 +1,55 @@
+/// Shader used for drawing many copies of the same bitmap, or of bitmaps in the atlas, in a single draw call.
+
+#import common
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) mult_color: vec4<f32>,
+    @location(2) add_color: vec4<f32>,
+};
+
+/// The per-instance transforms of each bitmap, and where it's drawn from in the texture.
+struct InstanceInput {
+    @location(1) world_matrix_0: vec4<f32>,
+    @location(2) world_matrix_1: vec4<f32>,
+    @location(3) world_matrix_2: vec4<f32>,
+    @location(4) world_matrix_3: vec4<f32>,
+    @location(5) mult_color: vec4<f32>,
+    @location(6) add_color: vec4<f32>,
+    /// The offset and size of the bitmap's texture coordinates, for bitmaps drawn out of the atlas.
+    @location(7) uv_rect: vec4<f32>,
+};
+
+@group(1) @binding(0) var<uniform> textureTransforms: common::TextureTransforms;
+@group(1) @binding(1) var texture: texture_2d<f32>;
+@group(1) @binding(2) var texture_sampler: sampler;
+
+@vertex
+fn main_vertex(in: common::VertexInput, instance: InstanceInput) -> VertexOutput {
+    let world_matrix = mat4x4<f32>(instance.world_matrix_0, instance.world_matrix_1, instance.world_matrix_2, instance.world_matrix_3);
+    let matrix_ = textureTransforms.texture_matrix;
+    let texture_uv = (mat3x3<f32>(matrix_[0].xyz, matrix_[1].xyz, matrix_[2].xyz) * vec3<f32>(in.position, 1.0)).xy;
+    let uv = instance.uv_rect.xy + texture_uv * instance.uv_rect.zw;
+    let pos = common::globals.view_matrix * world_matrix * vec4<f32>(in.position.x, in.position.y, 0.0, 1.0);
+    return VertexOutput(pos, uv, instance.mult_color, instance.add_color);
+}
+
+@fragment
+fn main_fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    var color: vec4<f32>;
+    if( textureTransforms.premultiply != 0.0 ) {
+        color = common::sample_straight_alpha(texture, texture_sampler, in.uv, textureTransforms.premultiply);
+    } else {
+        color = textureSample(texture, texture_sampler, in.uv);
+    }
+    // Texture is premultiplied by alpha.
+    // Unmultiply alpha, apply color transform, remultiply alpha.
+    if( color.a > 0.0 ) {
+        color = vec4<f32>(color.rgb / color.a, color.a);
+        color = common::apply_color_transform(color, in.mult_color, in.add_color);
+        let alpha = clamp(color.a, 0.0, 1.0);
+        color = vec4<f32>(color.rgb * alpha, alpha);
+    }
+    return color;
+}
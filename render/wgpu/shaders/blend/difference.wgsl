@@ -36,6 +36,10 @@ fn main_fragment(in: VertexOutput) -> @location(0) vec4<f32> {
     var src: vec4<f32> = textureSample(current_texture, texture_sampler, in.uv);
 
     if (src.a > 0.0) {
+        if (dst.a == 0.0) {
+            // There's nothing to blend with, and dividing by dst.a below would give NaN
+            return src;
+        }
         return vec4<f32>(src.rgb * (1.0 - dst.a) + dst.rgb * (1.0 - src.a) + src.a * dst.a * blend_func(src.rgb / src.a, dst.rgb / dst.a), src.a + dst.a * (1.0 - src.a));
     } else {
         if (true) {
@@ -24,10 +24,6 @@ fn main_vertex(in: common::VertexInput) -> VertexOutput {
     return VertexOutput(pos, uv);
 }
 
-fn blend_func(src: vec3<f32>, dst: vec3<f32>) -> vec3<f32> {
-    return 1.0 - dst;
-}
-
 @fragment
 fn main_fragment(in: VertexOutput) -> @location(0) vec4<f32> {
     // dst is the parent pixel we're blending onto
@@ -36,7 +32,9 @@ fn main_fragment(in: VertexOutput) -> @location(0) vec4<f32> {
     var src: vec4<f32> = textureSample(current_texture, texture_sampler, in.uv);
 
     if (src.a > 0.0) {
-        return vec4<f32>(src.rgb * (1.0 - dst.a) + dst.rgb * (1.0 - src.a) + src.a * dst.a * (1.0 - dst.rgb / dst.a), src.a + dst.a * (1.0 - src.a));
+        // The source color is ignored, only its alpha decides how much of the destination is inverted.
+        // With premultiplied alpha, the inverse of dst is `dst.a - dst.rgb`.
+        return vec4<f32>(dst.rgb * (1.0 - src.a) + src.a * (dst.a - dst.rgb), dst.a);
     } else {
         if (true) {
             // This needs to be in a branch because... reasons. Bug in naga.
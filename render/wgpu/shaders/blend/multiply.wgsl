@@ -24,14 +24,6 @@ fn main_vertex(in: common::VertexInput) -> VertexOutput {
     return VertexOutput(pos, uv);
 }
 
-fn blend_func(src: vec3<f32>, dst: vec3<f32>) -> vec3<f32> {
-    var out = src;
-    if (src.r <= 0.5) { out.r = (2.0 * src.r * dst.r); } else { out.r = (1.0 - 2.0 * (1.0 - dst.r) * (1.0 - src.r)); }
-    if (src.g <= 0.5) { out.g = (2.0 * src.g * dst.g); } else { out.g = (1.0 - 2.0 * (1.0 - dst.g) * (1.0 - src.g)); }
-    if (src.b <= 0.5) { out.b = (2.0 * src.b * dst.b); } else { out.b = (1.0 - 2.0 * (1.0 - dst.b) * (1.0 - src.b)); }
-    return out;
-}
-
 @fragment
 fn main_fragment(in: VertexOutput) -> @location(0) vec4<f32> {
     // dst is the parent pixel we're blending onto
@@ -40,7 +32,9 @@ fn main_fragment(in: VertexOutput) -> @location(0) vec4<f32> {
     var src: vec4<f32> = textureSample(current_texture, texture_sampler, in.uv);
 
     if (src.a > 0.0) {
-        return vec4<f32>(src.rgb * (1.0 - dst.a) + dst.rgb * (1.0 - src.a) + src.a * dst.a * blend_func(src.rgb / src.a, dst.rgb / dst.a), src.a + dst.a * (1.0 - src.a));
+        // Multiplying two premultiplied colors gives a premultiplied result,
+        // so there's no need to divide by alpha here.
+        return vec4<f32>(src.rgb * (1.0 - dst.a) + dst.rgb * (1.0 - src.a) + src.rgb * dst.rgb, src.a + dst.a * (1.0 - src.a));
     } else {
         if (true) {
             // This needs to be in a branch because... reasons. Bug in naga.
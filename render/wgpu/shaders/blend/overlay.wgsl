@@ -24,11 +24,20 @@ fn main_vertex(in: common::VertexInput) -> VertexOutput {
     return VertexOutput(pos, uv);
 }
 
+/// HardLight is Overlay with the source and destination swapped,
+/// so this shader is used for both.
 fn blend_func(src: vec3<f32>, dst: vec3<f32>) -> vec3<f32> {
-    var out = src;
-    if (dst.r <= 0.5) { out.r = (2.0 * src.r * dst.r); } else { out.r = (1.0 - 2.0 * (1.0 - dst.r) * (1.0 - src.r)); }
-    if (dst.g <= 0.5) { out.g = (2.0 * src.g * dst.g); } else { out.g = (1.0 - 2.0 * (1.0 - dst.g) * (1.0 - src.g)); }
-    if (dst.b <= 0.5) { out.b = (2.0 * src.b * dst.b); } else { out.b = (1.0 - 2.0 * (1.0 - dst.b) * (1.0 - src.b)); }
+    #if hard_light == true
+        let top = dst;
+        let bottom = src;
+    #else
+        let top = src;
+        let bottom = dst;
+    #endif
+    var out = top;
+    if (bottom.r <= 0.5) { out.r = (2.0 * top.r * bottom.r); } else { out.r = (1.0 - 2.0 * (1.0 - bottom.r) * (1.0 - top.r)); }
+    if (bottom.g <= 0.5) { out.g = (2.0 * top.g * bottom.g); } else { out.g = (1.0 - 2.0 * (1.0 - bottom.g) * (1.0 - top.g)); }
+    if (bottom.b <= 0.5) { out.b = (2.0 * top.b * bottom.b); } else { out.b = (1.0 - 2.0 * (1.0 - bottom.b) * (1.0 - top.b)); }
     return out;
 }
 
@@ -40,6 +49,10 @@ fn main_fragment(in: VertexOutput) -> @location(0) vec4<f32> {
     var src: vec4<f32> = textureSample(current_texture, texture_sampler, in.uv);
 
     if (src.a > 0.0) {
+        if (dst.a == 0.0) {
+            // There's nothing to blend with, and dividing by dst.a below would give NaN
+            return src;
+        }
         return vec4<f32>(src.rgb * (1.0 - dst.a) + dst.rgb * (1.0 - src.a) + src.a * dst.a * blend_func(src.rgb / src.a, dst.rgb / dst.a), src.a + dst.a * (1.0 - src.a));
     } else {
         if (true) {
@@ -0,0 +1,37 @@
+/// Shader used for drawing many copies of the same solid color fill in a single draw call.
+
+#import common
+
+struct VertexInput {
+    @location(0) position: vec2<f32>,
+    @location(1) color: vec4<f32>,
+};
+
+/// The per-instance transforms of each copy.
+struct InstanceInput {
+    @location(2) world_matrix_0: vec4<f32>,
+    @location(3) world_matrix_1: vec4<f32>,
+    @location(4) world_matrix_2: vec4<f32>,
+    @location(5) world_matrix_3: vec4<f32>,
+    @location(6) mult_color: vec4<f32>,
+    @location(7) add_color: vec4<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+};
+
+@vertex
+fn main_vertex(in: VertexInput, instance: InstanceInput) -> VertexOutput {
+    let world_matrix = mat4x4<f32>(instance.world_matrix_0, instance.world_matrix_1, instance.world_matrix_2, instance.world_matrix_3);
+    let pos = common::globals.view_matrix * world_matrix * vec4<f32>(in.position.x, in.position.y, 0.0, 1.0);
+    let color = common::apply_color_transform(in.color, instance.mult_color, instance.add_color);
+    return VertexOutput(pos, color);
+}
+
+@fragment
+fn main_fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    let alpha = clamp(in.color.a, 0.0, 1.0);
+    return vec4<f32>(in.color.rgb * alpha, alpha);
+}
@@ -22,6 +22,10 @@ struct ColorTransforms {
 
     /// The additive color transform of this object.
     add_color: vec4<f32>,
+
+    /// How far colors are mixed towards their luminance before they're multiplied,
+    /// from 0 for full color to 1 for grayscale.
+    luminance_mix: f32,
 };
 
 /// Uniforms used by texture draws (bitmaps and gradients).
@@ -29,6 +33,8 @@ struct TextureTransforms {
     /// The transform matrix of the gradient or texture.
     /// Transforms from object space to UV space.
     texture_matrix: mat4x4<f32>,
+    /// 1 if the texture has straight alpha, which is premultiplied as it's sampled.
+    premultiply: f32,
 };
 
 struct PushConstants {
@@ -45,6 +51,53 @@ struct VertexInput {
 /// Common uniform layout shared by all shaders.
 @group(0) @binding(0) var<uniform> globals: Globals;
 
+/// Applies a color transform the way Flash does.
+///
+/// Each channel is taken as a whole number from 0 to 255 and multiplied by its 8.8 fixed
+/// point multiplier, with the product truncated before the offset is added. Multiplying
+/// in floats instead rounds some results up, such as with several nested 50% alphas.
+fn apply_color_transform(color: vec4<f32>, mult_color: vec4<f32>, add_color: vec4<f32>) -> vec4<f32> {
+    let value = round(color * 255.0);
+    let mult = round(mult_color * 256.0);
+    let add = round(add_color * 255.0);
+    return (floor(value * mult / 256.0) + add) / 255.0;
+}
+
+/// Applies the color transform of an object, including its mix towards grayscale.
+fn apply_color_transforms(color: vec4<f32>, transforms: ColorTransforms) -> vec4<f32> {
+    var mixed = color;
+    if( transforms.luminance_mix != 0.0 ) {
+        let luminance = dot(color.rgb, vec3<f32>(0.299, 0.587, 0.114));
+        mixed = vec4<f32>(mix(color.rgb, vec3<f32>(luminance), transforms.luminance_mix), color.a);
+    }
+    return apply_color_transform(mixed, transforms.mult_color, transforms.add_color);
+}
+
+fn premultiply_texel(color: vec4<f32>) -> vec4<f32> {
+    return vec4<f32>(color.rgb * color.a, color.a);
+}
+
+/// Samples a texture with straight alpha, premultiplying it the way that `TextureTransforms.premultiply` says.
+/// Smoothed textures are filtered by hand, so that the texels are premultiplied before they're
+/// blended and the colors of transparent texels don't bleed into the edges around them.
+/// The sampler still wraps or clamps the texels, as they're each sampled at their centers.
+fn sample_straight_alpha(texture: texture_2d<f32>, texture_sampler: sampler, uv: vec2<f32>, premultiply: f32) -> vec4<f32> {
+    if( premultiply < 2.0 ) {
+        return premultiply_texel(textureSampleLevel(texture, texture_sampler, uv, 0.0));
+    }
+    let size = vec2<f32>(textureDimensions(texture));
+    let texel = uv * size - 0.5;
+    let base = floor(texel);
+    let weight = texel - base;
+    let uv00 = (base + 0.5) / size;
+    let step_ = 1.0 / size;
+    let c00 = premultiply_texel(textureSampleLevel(texture, texture_sampler, uv00, 0.0));
+    let c10 = premultiply_texel(textureSampleLevel(texture, texture_sampler, uv00 + vec2<f32>(step_.x, 0.0), 0.0));
+    let c01 = premultiply_texel(textureSampleLevel(texture, texture_sampler, uv00 + vec2<f32>(0.0, step_.y), 0.0));
+    let c11 = premultiply_texel(textureSampleLevel(texture, texture_sampler, uv00 + step_, 0.0));
+    return mix(mix(c00, c10, weight.x), mix(c01, c11, weight.x), weight.y);
+}
+
 /// Converts a color from linear to sRGB color space.
 fn linear_to_srgb(linear_: vec4<f32>) -> vec4<f32> {
     var rgb: vec3<f32> = linear_.rgb;
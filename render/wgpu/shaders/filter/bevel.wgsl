@@ -0,0 +1,78 @@
+#import filter
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+struct Filter {
+    /// The color of the highlight, with straight alpha.
+    highlight_color: vec4<f32>,
+    /// The color of the shadow, with straight alpha.
+    shadow_color: vec4<f32>,
+    /// The offset of the shadow, in pixels. The highlight is offset the opposite way.
+    offset: vec2<f32>,
+    /// The top left corner of the area being filtered, in source pixels.
+    source_point: vec2<f32>,
+    /// The size of the area being filtered, in source pixels.
+    source_size: vec2<f32>,
+    strength: f32,
+    /// 0 is inner, 1 is outer and 2 is full.
+    bevel_type: u32,
+    knockout: u32,
+}
+
+#if use_push_constants == true
+    @group(2) @binding(0) var<uniform> filter_args: Filter;
+    @group(2) @binding(1) var blurred: texture_2d<f32>;
+    @group(2) @binding(2) var blurred_sampler: sampler;
+#else
+    @group(4) @binding(0) var<uniform> filter_args: Filter;
+    @group(4) @binding(1) var blurred: texture_2d<f32>;
+    @group(4) @binding(2) var blurred_sampler: sampler;
+#endif
+
+@vertex
+fn main_vertex(in: filter::FilterVertexInput) -> filter::VertexOutput {
+    return filter::main_vertex(in);
+}
+
+/// Samples the alpha of the blurred source, treating anything outside of it as transparent.
+fn blurred_alpha(position: vec2<f32>, size: vec2<f32>) -> f32 {
+    if (any(position < vec2<f32>(0.0, 0.0)) || any(position >= size)) {
+        return 0.0;
+    }
+    return textureSampleLevel(blurred, blurred_sampler, position / size, 0.0).a;
+}
+
+@fragment
+fn main_fragment(in: filter::VertexOutput) -> @location(0) vec4<f32> {
+    let f = filter_args;
+    let dest = floor(in.position.xy);
+    let src = textureLoad(filter::texture, vec2<i32>(f.source_point + dest), 0);
+
+    // Edges facing along the offset are in shadow, and edges facing away from it are highlighted.
+    // Comparing the blur on either side of a pixel tells us which way it faces.
+    let difference = blurred_alpha(in.position.xy - f.offset, f.source_size) - blurred_alpha(in.position.xy + f.offset, f.source_size);
+    let shadow_alpha = clamp(difference * f.strength, 0.0, 1.0) * f.shadow_color.a;
+    let highlight_alpha = clamp(-difference * f.strength, 0.0, 1.0) * f.highlight_color.a;
+    let bevel = vec4<f32>(f.shadow_color.rgb * shadow_alpha, shadow_alpha) + vec4<f32>(f.highlight_color.rgb * highlight_alpha, highlight_alpha);
+
+    if (f.bevel_type == 0u) {
+        let inner = bevel * src.a;
+        if (f.knockout != 0u) {
+            return inner;
+        }
+        return vec4<f32>(inner.rgb + src.rgb * (1.0 - inner.a), src.a);
+    } else if (f.bevel_type == 1u) {
+        if (f.knockout != 0u) {
+            return bevel * (1.0 - src.a);
+        }
+        return src + bevel * (1.0 - src.a);
+    } else {
+        if (f.knockout != 0u) {
+            return bevel;
+        }
+        return bevel + src * (1.0 - bevel.a);
+    }
+}
@@ -6,6 +6,7 @@ struct VertexOutput {
 };
 
 struct Filter {
+    // Only one of these is ever non-zero, as each pass only blurs along one axis
     blur_x: f32,
     blur_y: f32,
     width: f32,
@@ -23,22 +24,37 @@ fn main_vertex(in: filter::FilterVertexInput) -> filter::VertexOutput {
     return filter::main_vertex(in);
 }
 
+/// Samples the texture, treating anything outside of it as transparent.
+fn sample(uv: vec2<f32>) -> vec4<f32> {
+    if (any(uv < vec2<f32>(0.0, 0.0)) || any(uv > vec2<f32>(1.0, 1.0))) {
+        return vec4<f32>(0.0, 0.0, 0.0, 0.0);
+    }
+    return textureSampleLevel(filter::texture, filter::texture_sampler, uv, 0.0);
+}
+
 @fragment
 fn main_fragment(in: filter::VertexOutput) -> @location(0) vec4<f32> {
-    var f = filter_args;
-    var color = vec4<f32>();
-    var sum = 0.0;
-
-    for (var x = -f.blur_x; x <= f.blur_x; x += 1.0) {
-        for (var y = -f.blur_y; y <= f.blur_y; y += 1.0) {
-            var offset = vec3<f32>(x / f.width, y / f.height, 0.0);
-            let sample = textureSample(filter::texture, filter::texture_sampler, in.uv + offset.xy);
-            let weight = 1.0;
-            color += vec4<f32>(sample.rgb / sample.a, sample.a) * weight;
-            sum += weight;
-        }
+    let f = filter_args;
+    var full_size = f.blur_y;
+    var direction = vec2<f32>(0.0, 1.0 / f.height);
+    if (f.blur_x > 0.0) {
+        full_size = f.blur_x;
+        direction = vec2<f32>(1.0 / f.width, 0.0);
+    }
+
+    // The box covers `full_size` pixels centered on this one. When that isn't a whole odd number,
+    // the outermost pixel on each side only partially falls inside of the box, and is weighted by how much does.
+    let radius = max((full_size - 1.0) / 2.0, 0.0);
+    let whole = floor(radius);
+    let fraction = radius - whole;
+
+    // Colors are premultiplied, so they can be averaged directly
+    var total = sample(in.uv);
+    for (var i = 1.0; i <= whole; i += 1.0) {
+        total += sample(in.uv + direction * i) + sample(in.uv - direction * i);
     }
-    color /= sum;
+    let edge = whole + 1.0;
+    total += (sample(in.uv + direction * edge) + sample(in.uv - direction * edge)) * fraction;
 
-    return vec4<f32>(color.rgb * color.a, color.a);
+    return total / (1.0 + 2.0 * whole + 2.0 * fraction);
 }
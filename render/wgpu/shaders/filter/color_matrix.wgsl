@@ -46,11 +46,16 @@ fn main_vertex(in: filter::FilterVertexInput) -> filter::VertexOutput {
 fn main_fragment(in: filter::VertexOutput) -> @location(0) vec4<f32> {
     var src = textureSample(filter::texture, filter::texture_sampler, in.uv);
     var f = filter_args;
+
+    // The matrix applies to straight colors, and a fully transparent pixel is treated as transparent black
+    if (src.a > 0.0) {
+        src = vec4<f32>(src.rgb / src.a, src.a);
+    }
     var color = vec4<f32>(
-        clamp((f.r_to_r * src.r / src.a) + (f.g_to_r * src.g / src.a) + (f.b_to_r * src.b / src.a) + (f.a_to_r * src.a) + (f.r_extra / 255.0), 0.0, 1.0),
-        clamp((f.r_to_g * src.r / src.a) + (f.g_to_g * src.g / src.a) + (f.b_to_g * src.b / src.a) + (f.a_to_g * src.a) + (f.g_extra / 255.0), 0.0, 1.0),
-        clamp((f.r_to_b * src.r / src.a) + (f.g_to_b * src.g / src.a) + (f.b_to_b * src.b / src.a) + (f.a_to_b * src.a) + (f.b_extra / 255.0), 0.0, 1.0),
-        clamp((f.r_to_a * src.r / src.a) + (f.g_to_a * src.g / src.a) + (f.b_to_a * src.b / src.a) + (f.a_to_a * src.a) + (f.a_extra / 255.0), 0.0, 1.0),
+        clamp((f.r_to_r * src.r) + (f.g_to_r * src.g) + (f.b_to_r * src.b) + (f.a_to_r * src.a) + (f.r_extra / 255.0), 0.0, 1.0),
+        clamp((f.r_to_g * src.r) + (f.g_to_g * src.g) + (f.b_to_g * src.b) + (f.a_to_g * src.a) + (f.g_extra / 255.0), 0.0, 1.0),
+        clamp((f.r_to_b * src.r) + (f.g_to_b * src.g) + (f.b_to_b * src.b) + (f.a_to_b * src.a) + (f.b_extra / 255.0), 0.0, 1.0),
+        clamp((f.r_to_a * src.r) + (f.g_to_a * src.g) + (f.b_to_a * src.b) + (f.a_to_a * src.a) + (f.a_extra / 255.0), 0.0, 1.0),
     );
     return vec4<f32>(color.rgb * color.a, color.a);
 }
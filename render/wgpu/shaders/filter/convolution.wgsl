@@ -0,0 +1,84 @@
+#import filter
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+struct Filter {
+    /// The kernel, row by row and packed 4 values to an element.
+    matrix: array<vec4<f32>, 64>,
+    /// The straight color used for pixels outside of the source when not clamping.
+    color: vec4<f32>,
+    /// The top left corner of the area being filtered, in source pixels.
+    source_point: vec2<f32>,
+    /// The size of the area being filtered, in source pixels.
+    source_size: vec2<f32>,
+    divisor: f32,
+    bias: f32,
+    matrix_x: u32,
+    matrix_y: u32,
+    preserve_alpha: u32,
+    clamp: u32,
+    _padding: vec2<u32>,
+}
+
+#if use_push_constants == true
+    @group(2) @binding(0) var<uniform> filter_args: Filter;
+#else
+    @group(4) @binding(0) var<uniform> filter_args: Filter;
+#endif
+
+@vertex
+fn main_vertex(in: filter::FilterVertexInput) -> filter::VertexOutput {
+    return filter::main_vertex(in);
+}
+
+/// Reads the straight color of a pixel, relative to the top left of the filtered area.
+fn load(position: vec2<f32>) -> vec4<f32> {
+    let size = filter_args.source_size;
+    var pos = position;
+    let inside = all(pos >= vec2<f32>(0.0, 0.0)) && all(pos < size);
+    if (!inside) {
+        if (filter_args.clamp == 0u) {
+            return filter_args.color;
+        }
+        pos = clamp(pos, vec2<f32>(0.0, 0.0), size - 1.0);
+    }
+    let color = textureLoad(filter::texture, vec2<i32>(filter_args.source_point + pos), 0);
+    if (color.a > 0.0) {
+        return vec4<f32>(color.rgb / color.a, color.a);
+    }
+    return color;
+}
+
+@fragment
+fn main_fragment(in: filter::VertexOutput) -> @location(0) vec4<f32> {
+    let matrix_x = filter_args.matrix_x;
+    let matrix_y = filter_args.matrix_y;
+    // The pixel being written, relative to the top left of the filtered area
+    let dest = floor(in.position.xy);
+    // The kernel is centered on the pixel, rounding towards the top left for even sizes
+    let center = vec2<f32>(f32(matrix_x / 2u), f32(matrix_y / 2u));
+
+    var sum = vec4<f32>(0.0, 0.0, 0.0, 0.0);
+    for (var y = 0u; y < matrix_y; y++) {
+        for (var x = 0u; x < matrix_x; x++) {
+            // The matrix is read from the uniform directly, as only that can be indexed dynamically
+            let index = y * matrix_x + x;
+            let weight = filter_args.matrix[index / 4u][index % 4u];
+            sum += load(dest + vec2<f32>(f32(x), f32(y)) - center) * weight;
+        }
+    }
+
+    // A divisor of 0 is treated as 1
+    var divisor = filter_args.divisor;
+    if (divisor == 0.0) {
+        divisor = 1.0;
+    }
+    var color = clamp(sum / divisor + filter_args.bias / 255.0, vec4<f32>(0.0), vec4<f32>(1.0));
+    if (filter_args.preserve_alpha != 0u) {
+        color.a = load(dest).a;
+    }
+    return vec4<f32>(color.rgb * color.a, color.a);
+}
@@ -0,0 +1,91 @@
+#import filter
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+struct Filter {
+    /// The premultiplied color used by the "color" mode.
+    color: vec4<f32>,
+    /// The top left corner of the area being filtered, in source pixels.
+    source_point: vec2<f32>,
+    /// The size of the area being filtered, in source pixels.
+    source_size: vec2<f32>,
+    /// The offset of the filtered area from the top left corner of the map.
+    map_point: vec2<f32>,
+    scale: vec2<f32>,
+    /// The `BitmapDataChannel` flags used for each axis.
+    component_x: u32,
+    component_y: u32,
+    /// 0 = wrap, 1 = clamp, 2 = ignore, 3 = color
+    mode: u32,
+    _padding: u32,
+}
+
+#if use_push_constants == true
+    @group(2) @binding(0) var<uniform> filter_args: Filter;
+    @group(2) @binding(1) var map_texture: texture_2d<f32>;
+#else
+    @group(4) @binding(0) var<uniform> filter_args: Filter;
+    @group(4) @binding(1) var map_texture: texture_2d<f32>;
+#endif
+
+@vertex
+fn main_vertex(in: filter::FilterVertexInput) -> filter::VertexOutput {
+    return filter::main_vertex(in);
+}
+
+fn get_component(color: vec4<f32>, component: u32) -> f32 {
+    // Flash reads the channels of the map without premultiplication
+    var straight = color;
+    if (color.a > 0.0) {
+        straight = vec4<f32>(color.rgb / color.a, color.a);
+    }
+    switch (component) {
+        case 1u: { return straight.r; }
+        case 2u: { return straight.g; }
+        case 4u: { return straight.b; }
+        case 8u: { return straight.a; }
+        // Unknown channels behave as the neutral value, displacing nothing
+        default: { return 128.0 / 255.0; }
+    }
+}
+
+@fragment
+fn main_fragment(in: filter::VertexOutput) -> @location(0) vec4<f32> {
+    let f = filter_args;
+    // The pixel being written, relative to the top left of the filtered area
+    let dest = floor(in.position.xy);
+
+    var offset = vec2<f32>(0.0, 0.0);
+    let map_position = dest - f.map_point;
+    let map_size = vec2<f32>(textureDimensions(map_texture));
+    if (all(map_position >= vec2<f32>(0.0, 0.0)) && all(map_position < map_size)) {
+        let map = textureLoad(map_texture, vec2<i32>(map_position), 0);
+        let value = vec2<f32>(get_component(map, f.component_x), get_component(map, f.component_y));
+        offset = (floor(value * 255.0 + 0.5) - 128.0) * f.scale / 256.0;
+    }
+
+    // Flash samples from the center of the displaced pixel, so round rather than truncate
+    var position = floor(dest + offset + 0.5);
+    let inside = all(position >= vec2<f32>(0.0, 0.0)) && all(position < f.source_size);
+    if (!inside) {
+        switch (f.mode) {
+            case 0u: {
+                position = position - f.source_size * floor(position / f.source_size);
+            }
+            case 1u: {
+                position = clamp(position, vec2<f32>(0.0, 0.0), f.source_size - 1.0);
+            }
+            case 2u: {
+                position = dest;
+            }
+            default: {
+                return f.color;
+            }
+        }
+    }
+
+    return textureLoad(filter::texture, vec2<i32>(f.source_point + position), 0);
+}
@@ -0,0 +1,73 @@
+#import filter
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+struct Filter {
+    /// The color of the shadow, with straight alpha.
+    color: vec4<f32>,
+    /// The offset of the shadow, in pixels.
+    offset: vec2<f32>,
+    /// The top left corner of the area being filtered, in source pixels.
+    source_point: vec2<f32>,
+    /// The size of the area being filtered, in source pixels.
+    source_size: vec2<f32>,
+    strength: f32,
+    inner: u32,
+    knockout: u32,
+    hide_object: u32,
+}
+
+#if use_push_constants == true
+    @group(2) @binding(0) var<uniform> filter_args: Filter;
+    @group(2) @binding(1) var blurred: texture_2d<f32>;
+    @group(2) @binding(2) var blurred_sampler: sampler;
+#else
+    @group(4) @binding(0) var<uniform> filter_args: Filter;
+    @group(4) @binding(1) var blurred: texture_2d<f32>;
+    @group(4) @binding(2) var blurred_sampler: sampler;
+#endif
+
+@vertex
+fn main_vertex(in: filter::FilterVertexInput) -> filter::VertexOutput {
+    return filter::main_vertex(in);
+}
+
+@fragment
+fn main_fragment(in: filter::VertexOutput) -> @location(0) vec4<f32> {
+    let f = filter_args;
+    let dest = floor(in.position.xy);
+    let src = textureLoad(filter::texture, vec2<i32>(f.source_point + dest), 0);
+
+    // The blurred copy of the source, moved by the shadow offset.
+    // Anything that has been moved in from outside of the source is transparent.
+    let shadow_position = in.position.xy - f.offset;
+    var shadow_alpha = 0.0;
+    if (all(shadow_position >= vec2<f32>(0.0, 0.0)) && all(shadow_position < f.source_size)) {
+        shadow_alpha = textureSampleLevel(blurred, blurred_sampler, shadow_position / f.source_size, 0.0).a;
+    }
+
+    if (f.inner != 0u) {
+        // The shadow is cast by the area *around* the object, and only visible inside of it
+        let alpha = clamp((1.0 - shadow_alpha) * f.strength, 0.0, 1.0) * f.color.a;
+        let shadow = vec4<f32>(f.color.rgb * alpha, alpha) * src.a;
+        if (f.knockout != 0u || f.hide_object != 0u) {
+            return shadow;
+        }
+        return vec4<f32>(src.rgb * (1.0 - alpha) + shadow.rgb, src.a);
+    } else {
+        let alpha = clamp(shadow_alpha * f.strength, 0.0, 1.0) * f.color.a;
+        let shadow = vec4<f32>(f.color.rgb * alpha, alpha);
+        if (f.knockout != 0u) {
+            // Cut the object out of its shadow
+            return shadow * (1.0 - src.a);
+        }
+        if (f.hide_object != 0u) {
+            return shadow;
+        }
+        // The object is drawn on top of its shadow
+        return src + shadow * (1.0 - src.a);
+    }
+}
@@ -16,24 +16,22 @@ struct VertexOutput {
 #endif
 
 struct Gradient {
-    colors: array<vec4<f32>, 16>,
-    ratios: array<vec4<f32>, 4u>, // secretly array<f32; 16> but this let's us squeeze it into alignment
-    gradient_type: i32,
-    num_colors: u32,
-    interpolation: i32,
     focal_point: f32,
+    _padding1: f32,
+    _padding2: f32,
+    _padding3: f32,
 };
 
 #if use_push_constants == true
     @group(1) @binding(1) var<uniform> gradient: Gradient;
+    @group(1) @binding(2) var gradient_texture: texture_2d<f32>;
+    @group(1) @binding(3) var gradient_sampler: sampler;
 #else
     @group(3) @binding(1) var<uniform> gradient: Gradient;
+    @group(3) @binding(2) var gradient_texture: texture_2d<f32>;
+    @group(3) @binding(3) var gradient_sampler: sampler;
 #endif
 
-fn ratio(i: u32) -> f32 {
-    return gradient.ratios[i / 4u][i % 4u];
-}
-
 fn find_t(focal_point: f32, uv: vec2<f32>) -> f32 {
     return 0.0;
 }
@@ -63,27 +61,13 @@ fn main_fragment(in: VertexOutput) -> @location(0) vec4<f32> {
     #if use_push_constants == true
         var colorTransforms = pc.colorTransforms;
     #endif
-    let last = gradient.num_colors - 1u;
-
-    // Calculate normalized `t` position in gradient, [0.0, 1.0] being the bounds of the ratios.
-    var t: f32 = find_t(gradient.focal_point, in.uv);
-
-    t = clamp(t, ratio(0u), ratio(last));
-
-    // Find the two gradient colors bordering our position.
-    var j: u32;
-    for( j = 1u; j < 16u && t > ratio(j); j = j + 1u) {
-        // Noop
-    }
-    let i = j - 1u;
+    // Calculate normalized `t` position in gradient, with the spread mode already applied.
+    let t = find_t(gradient.focal_point, in.uv);
 
-    // Lerp between the two colors.
-    let a = (t - ratio(i)) / (ratio(j) - ratio(i));
-    var color: vec4<f32> = mix(gradient.colors[i], gradient.colors[j], a);
-    if( gradient.interpolation != 0 ) {
-        color = common::linear_to_srgb(color);
-    }
-    let out = color * colorTransforms.mult_color + colorTransforms.add_color;
+    // The colors are baked into a ramp, with one texel for each of the 256 possible ratios.
+    // Sample at the texel centers, so that the ends of the ramp aren't blended with anything.
+    let color = textureSample(gradient_texture, gradient_sampler, vec2<f32>((t * 255.0 + 0.5) / 256.0, 0.5));
+    let out = common::apply_color_transforms(color, colorTransforms);
     let alpha = clamp(out.a, 0.0, 1.0);
     return vec4<f32>(out.rgb * alpha, alpha);
 }
\ No newline at end of file
@@ -4,8 +4,14 @@ override fn gradient::find_t(focal_point: f32, uv: vec2<f32>) -> f32 {
     let uv = uv * 2.0 - 1.0;
     var d: vec2<f32> = vec2<f32>(focal_point, 0.0) - uv;
     let l = length(d);
+    if (l == 0.0) {
+        // The focal point itself has no direction, but always starts the gradient
+        return 0.0;
+    }
     d = d / l;
-    var t = l / (sqrt(1.0 - focal_point * focal_point * d.y * d.y) + focal_point * d.x);
+    // With the focal point on the edge of the circle, the denominator reaches 0 behind it.
+    // Keep t finite there, so that it's still handled by the spread mode below.
+    var t = l / max(sqrt(1.0 - focal_point * focal_point * d.y * d.y) + focal_point * d.x, 0.0001);
     #if gradient_repeat_mode == 1
         // Mirror
         if( t < 0.0 ) {
@@ -0,0 +1,44 @@
+/// Shader used for drawing morph shapes, which are interpolated between their start and end shapes.
+
+#import common
+
+struct VertexInput {
+    @location(0) start_position: vec2<f32>,
+    @location(1) end_position: vec2<f32>,
+    @location(2) start_color: vec4<f32>,
+    @location(3) end_color: vec4<f32>,
+    /// How far along the morph is, from 0.0 at the start shape to 1.0 at the end shape.
+    @location(4) ratio: f32,
+};
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+};
+
+#if use_push_constants == true
+    var<push_constant> pc: common::PushConstants;
+#else
+    @group(1) @binding(0) var<uniform> transforms: common::Transforms;
+    @group(2) @binding(0) var<uniform> colorTransforms: common::ColorTransforms;
+#endif
+
+@vertex
+fn main_vertex(in: VertexInput) -> VertexOutput {
+    #if use_push_constants == true
+        var transforms = pc.transforms;
+    #endif
+    let position = mix(in.start_position, in.end_position, in.ratio);
+    let pos = common::globals.view_matrix * transforms.world_matrix * vec4<f32>(position.x, position.y, 0.0, 1.0);
+    return VertexOutput(pos, mix(in.start_color, in.end_color, in.ratio));
+}
+
+@fragment
+fn main_fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    #if use_push_constants == true
+        var colorTransforms = pc.colorTransforms;
+    #endif
+    let color = common::apply_color_transforms(in.color, colorTransforms);
+    let alpha = clamp(color.a, 0.0, 1.0);
+    return vec4<f32>(color.rgb * alpha, alpha);
+}
@@ -0,0 +1,57 @@
+/// Shader used for drawing triangles with a bitmap mapped onto them by their own texture coordinates.
+
+#import common
+
+struct VertexInput {
+    @location(0) position: vec2<f32>,
+    @location(1) uv: vec2<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+#if use_push_constants == true
+    var<push_constant> pc: common::PushConstants;
+    @group(1) @binding(0) var<uniform> textureTransforms: common::TextureTransforms;
+    @group(1) @binding(1) var texture: texture_2d<f32>;
+    @group(1) @binding(2) var texture_sampler: sampler;
+#else
+    @group(1) @binding(0) var<uniform> transforms: common::Transforms;
+    @group(2) @binding(0) var<uniform> colorTransforms: common::ColorTransforms;
+    @group(3) @binding(0) var<uniform> textureTransforms: common::TextureTransforms;
+    @group(3) @binding(1) var texture: texture_2d<f32>;
+    @group(3) @binding(2) var texture_sampler: sampler;
+#endif
+
+@vertex
+fn main_vertex(in: VertexInput) -> VertexOutput {
+    #if use_push_constants == true
+        var transforms = pc.transforms;
+    #endif
+    let pos = common::globals.view_matrix * transforms.world_matrix * vec4<f32>(in.position.x, in.position.y, 0.0, 1.0);
+    return VertexOutput(pos, in.uv);
+}
+
+@fragment
+fn main_fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    var color: vec4<f32>;
+    if( textureTransforms.premultiply != 0.0 ) {
+        color = common::sample_straight_alpha(texture, texture_sampler, in.uv, textureTransforms.premultiply);
+    } else {
+        color = textureSample(texture, texture_sampler, in.uv);
+    }
+    #if use_push_constants == true
+        var colorTransforms = pc.colorTransforms;
+    #endif
+    // Texture is premultiplied by alpha.
+    // Unmultiply alpha, apply color transform, remultiply alpha.
+    if( color.a > 0.0 ) {
+        color = vec4<f32>(color.rgb / color.a, color.a);
+        color = common::apply_color_transforms(color, colorTransforms);
+        let alpha = clamp(color.a, 0.0, 1.0);
+        color = vec4<f32>(color.rgb * alpha, alpha);
+    }
+    return color;
+}
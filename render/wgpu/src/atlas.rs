@@ -0,0 +1,436 @@
+use crate::bitmaps::BitmapSamplers;
+use crate::descriptors::{Descriptors, Quad};
+use crate::mesh::BitmapBinds;
+use once_cell::sync::OnceCell;
+use std::num::NonZeroU32;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+
+/// The width and height of the atlas texture.
+pub const ATLAS_SIZE: u32 = 2048;
+
+/// The largest bitmap, in either direction, that's packed into the atlas.
+pub const MAX_ATLAS_BITMAP_SIZE: u32 = 256;
+
+/// The smallest cell that the atlas is divided into.
+const MIN_CELL_SIZE: u32 = 8;
+
+/// How many texels of a bitmap's edges are repeated around it, so that smoothing
+/// doesn't blend the bitmaps next to it into its edges.
+const PADDING: u32 = 1;
+
+/// The texture coordinates of a bitmap that's drawn from a texture of its own.
+pub const FULL_UV_RECT: [f32; 4] = [0.0, 0.0, 1.0, 1.0];
+
+/// Whether a bitmap of this size is small enough to be packed into the atlas.
+pub fn fits_in_atlas(width: u32, height: u32) -> bool {
+    width > 0 && height > 0 && width <= MAX_ATLAS_BITMAP_SIZE && height <= MAX_ATLAS_BITMAP_SIZE
+}
+
+/// A texture that small bitmaps are packed into instead of textures of their own,
+/// so that runs of different small bitmaps can be drawn with a single bind group.
+///
+/// A bitmap only gets a texture of its own once it's evicted from the atlas, which happens
+/// as soon as something needs that texture, such as rendering to the bitmap or filling a shape
+/// with it. Its pixels are copied over, and it's drawn from its own texture from then on.
+/// When there's no room left for another bitmap, the bitmaps that were drawn out of the atlas
+/// least recently are evicted to make room.
+#[derive(Debug)]
+pub struct BitmapAtlas {
+    texture: wgpu::Texture,
+    binds_linear: BitmapBinds,
+    binds_nearest: BitmapBinds,
+    cells: Mutex<AtlasCells>,
+    /// Counts every draw out of the atlas, to tell which bitmaps were drawn least recently.
+    clock: AtomicU64,
+}
+
+/// Where a bitmap is in a [`BitmapAtlas`].
+#[derive(Debug)]
+pub struct AtlasEntry {
+    /// The cell that the bitmap was packed into, along with its padding.
+    level: usize,
+    cell: (u32, u32),
+    /// The texels of the bitmap itself.
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    /// The texture of its own that the bitmap was moved into when it was evicted.
+    evicted: OnceCell<Arc<wgpu::Texture>>,
+    /// The atlas's clock when the bitmap was last drawn out of it.
+    last_used: AtomicU64,
+}
+
+impl AtlasEntry {
+    /// The offset and size of the bitmap in the atlas, in texture coordinates.
+    pub fn uv_rect(&self) -> [f32; 4] {
+        let size = ATLAS_SIZE as f32;
+        [
+            self.x as f32 / size,
+            self.y as f32 / size,
+            self.width as f32 / size,
+            self.height as f32 / size,
+        ]
+    }
+
+    /// Whether the bitmap was taken out of the atlas, and has to be drawn from its own texture.
+    pub fn is_evicted(&self) -> bool {
+        self.evicted.get().is_some()
+    }
+}
+
+#[derive(Debug)]
+struct AtlasCells {
+    tree: CellTree,
+    /// Every bitmap that was packed into a cell. A bitmap that's been dropped keeps its cell
+    /// until the space is needed again.
+    slots: Vec<Slot>,
+}
+
+#[derive(Debug)]
+struct Slot {
+    level: usize,
+    cell: (u32, u32),
+    entry: Weak<AtlasEntry>,
+}
+
+impl AtlasCells {
+    fn allocate(&mut self, level: usize) -> Option<(u32, u32)> {
+        if let Some(cell) = self.tree.allocate(level) {
+            return Some(cell);
+        }
+
+        // Take back the cells of bitmaps that don't exist anymore
+        let tree = &mut self.tree;
+        self.slots.retain(|slot| {
+            let dropped = slot.entry.strong_count() == 0;
+            if dropped {
+                tree.free(slot.level, slot.cell);
+            }
+            !dropped
+        });
+        self.tree.allocate(level)
+    }
+
+    /// The bitmap that was drawn out of the atlas longest ago, which is evicted first.
+    fn least_recently_used(&self) -> Option<Arc<AtlasEntry>> {
+        self.slots
+            .iter()
+            .filter_map(|slot| slot.entry.upgrade())
+            .min_by_key(|entry| entry.last_used.load(Ordering::Relaxed))
+    }
+
+    fn free(&mut self, entry: &AtlasEntry) {
+        self.tree.free(entry.level, entry.cell);
+        self.slots
+            .retain(|slot| (slot.level, slot.cell) != (entry.level, entry.cell));
+    }
+}
+
+impl BitmapAtlas {
+    pub fn new(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        quad: &Quad,
+        samplers: &BitmapSamplers,
+    ) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: create_debug_label!("Bitmap atlas").as_deref(),
+            size: wgpu::Extent3d {
+                width: ATLAS_SIZE,
+                height: ATLAS_SIZE,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            view_formats: &[wgpu::TextureFormat::Rgba8Unorm],
+            usage: wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_DST
+                | wgpu::TextureUsages::COPY_SRC,
+        });
+        let binds = |smoothed: bool| {
+            BitmapBinds::new(
+                device,
+                layout,
+                samplers.get_sampler(false, smoothed),
+                &quad.texture_transforms,
+                0,
+                texture.create_view(&Default::default()),
+                create_debug_label!("Bitmap atlas bind group (smoothed: {})", smoothed),
+            )
+        };
+        let binds_linear = binds(true);
+        let binds_nearest = binds(false);
+        let num_levels = (ATLAS_SIZE / MIN_CELL_SIZE).trailing_zeros() as usize + 1;
+
+        Self {
+            texture,
+            binds_linear,
+            binds_nearest,
+            cells: Mutex::new(AtlasCells {
+                tree: CellTree::new(num_levels),
+                slots: Vec::new(),
+            }),
+            clock: AtomicU64::new(0),
+        }
+    }
+
+    /// Packs premultiplied RGBA pixels into the atlas, evicting other bitmaps if there's
+    /// no room for them. Returns `None` if the bitmap is too big to be packed at all.
+    pub fn insert(
+        &self,
+        descriptors: &Descriptors,
+        width: u32,
+        height: u32,
+        rgba: &[u8],
+    ) -> Option<Arc<AtlasEntry>> {
+        if !fits_in_atlas(width, height) {
+            return None;
+        }
+        let cell_size = (width.max(height) + 2 * PADDING)
+            .max(MIN_CELL_SIZE)
+            .next_power_of_two();
+        let level = (ATLAS_SIZE / cell_size).trailing_zeros() as usize;
+
+        let (mut cells, cell) = loop {
+            let mut cells = self.lock_cells();
+            if let Some(cell) = cells.allocate(level) {
+                break (cells, cell);
+            }
+            // Evicting takes the lock again, to free the cell
+            let oldest = cells.least_recently_used()?;
+            drop(cells);
+            self.evict(descriptors, &oldest);
+        };
+        let entry = Arc::new(AtlasEntry {
+            level,
+            cell,
+            x: cell.0 * cell_size + PADDING,
+            y: cell.1 * cell_size + PADDING,
+            width,
+            height,
+            evicted: OnceCell::new(),
+            last_used: AtomicU64::new(self.clock.load(Ordering::Relaxed)),
+        });
+        cells.slots.push(Slot {
+            level,
+            cell,
+            entry: Arc::downgrade(&entry),
+        });
+        drop(cells);
+
+        self.write(&descriptors.queue, &entry, rgba);
+        Some(entry)
+    }
+
+    /// Replaces the pixels of a bitmap in the atlas, which must be the same size as before.
+    pub fn write(&self, queue: &wgpu::Queue, entry: &AtlasEntry, rgba: &[u8]) {
+        let width = entry.width + 2 * PADDING;
+        let height = entry.height + 2 * PADDING;
+        let mut padded = Vec::with_capacity((width * height * 4) as usize);
+        for y in 0..height {
+            let source_y = y.saturating_sub(PADDING).min(entry.height - 1);
+            for x in 0..width {
+                let source_x = x.saturating_sub(PADDING).min(entry.width - 1);
+                let index = ((source_y * entry.width + source_x) * 4) as usize;
+                padded.extend_from_slice(&rgba[index..index + 4]);
+            }
+        }
+
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &self.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d {
+                    x: entry.x - PADDING,
+                    y: entry.y - PADDING,
+                    z: 0,
+                },
+                aspect: wgpu::TextureAspect::All,
+            },
+            &padded,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: NonZeroU32::new(4 * width),
+                rows_per_image: None,
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+
+    /// Takes a bitmap out of the atlas, copying its pixels into a texture of its own,
+    /// and returns that texture. Evicting a bitmap again just returns the same texture.
+    pub fn evict<'a>(
+        &self,
+        descriptors: &Descriptors,
+        entry: &'a AtlasEntry,
+    ) -> &'a Arc<wgpu::Texture> {
+        entry.evicted.get_or_init(|| {
+            let size = wgpu::Extent3d {
+                width: entry.width,
+                height: entry.height,
+                depth_or_array_layers: 1,
+            };
+            let texture = descriptors.device.create_texture(&wgpu::TextureDescriptor {
+                label: create_debug_label!("Bitmap").as_deref(),
+                size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8Unorm,
+                view_formats: &[wgpu::TextureFormat::Rgba8Unorm],
+                usage: wgpu::TextureUsages::TEXTURE_BINDING
+                    | wgpu::TextureUsages::COPY_DST
+                    | wgpu::TextureUsages::RENDER_ATTACHMENT
+                    | wgpu::TextureUsages::COPY_SRC,
+            });
+
+            let label = create_debug_label!("Bitmap atlas eviction encoder");
+            let mut encoder =
+                descriptors
+                    .device
+                    .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                        label: label.as_deref(),
+                    });
+            encoder.copy_texture_to_texture(
+                wgpu::ImageCopyTexture {
+                    texture: &self.texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d {
+                        x: entry.x,
+                        y: entry.y,
+                        z: 0,
+                    },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                wgpu::ImageCopyTexture {
+                    texture: &texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d::ZERO,
+                    aspect: wgpu::TextureAspect::All,
+                },
+                size,
+            );
+            // The copy is submitted before the cell can be handed out and written to again
+            descriptors.queue.submit(Some(encoder.finish()));
+            self.lock_cells().free(entry);
+
+            Arc::new(texture)
+        })
+    }
+
+    /// Notes that a bitmap is being drawn out of the atlas, so that it's evicted last.
+    pub fn mark_used(&self, entry: &AtlasEntry) {
+        let now = self.clock.fetch_add(1, Ordering::Relaxed) + 1;
+        entry.last_used.store(now, Ordering::Relaxed);
+    }
+
+    pub fn binds(&self, smoothed: bool) -> &BitmapBinds {
+        if smoothed {
+            &self.binds_linear
+        } else {
+            &self.binds_nearest
+        }
+    }
+
+    /// The number of bitmaps that are currently packed into the atlas.
+    pub fn bitmap_count(&self) -> usize {
+        self.lock_cells()
+            .slots
+            .iter()
+            .filter(|slot| slot.entry.strong_count() > 0)
+            .count()
+    }
+
+    fn lock_cells(&self) -> MutexGuard<AtlasCells> {
+        self.cells
+            .lock()
+            .expect("Atlas cells should not be already locked")
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Cell {
+    Free,
+    /// Divided into quarters on the next level, some of which are used.
+    Split,
+    Used,
+}
+
+/// Divides the atlas into quarters, which are divided into quarters again and so on,
+/// down to the smallest cells. Every bitmap gets the smallest cell that it fits in,
+/// and quarters are merged back together once they're all free again.
+#[derive(Debug)]
+struct CellTree {
+    /// The cells of every level, row by row. The first level is the whole atlas.
+    levels: Vec<Vec<Cell>>,
+}
+
+impl CellTree {
+    fn new(num_levels: usize) -> Self {
+        Self {
+            levels: (0..num_levels)
+                .map(|level| vec![Cell::Free; 1 << (2 * level)])
+                .collect(),
+        }
+    }
+
+    fn allocate(&mut self, level: usize) -> Option<(u32, u32)> {
+        self.allocate_within(level, 0, (0, 0))
+    }
+
+    fn allocate_within(
+        &mut self,
+        level: usize,
+        current: usize,
+        (x, y): (u32, u32),
+    ) -> Option<(u32, u32)> {
+        let index = cell_index(current, x, y);
+        match self.levels[current][index] {
+            Cell::Used => None,
+            Cell::Free if current == level => {
+                self.levels[current][index] = Cell::Used;
+                Some((x, y))
+            }
+            Cell::Split if current == level => None,
+            Cell::Free | Cell::Split => {
+                self.levels[current][index] = Cell::Split;
+                [(0, 0), (1, 0), (0, 1), (1, 1)]
+                    .into_iter()
+                    .find_map(|(dx, dy)| {
+                        self.allocate_within(level, current + 1, (x * 2 + dx, y * 2 + dy))
+                    })
+            }
+        }
+    }
+
+    fn free(&mut self, mut level: usize, (mut x, mut y): (u32, u32)) {
+        self.levels[level][cell_index(level, x, y)] = Cell::Free;
+        while level > 0 {
+            let (parent_x, parent_y) = (x / 2, y / 2);
+            let quarters_free = [(0, 0), (1, 0), (0, 1), (1, 1)]
+                .into_iter()
+                .all(|(dx, dy)| {
+                    self.levels[level][cell_index(level, parent_x * 2 + dx, parent_y * 2 + dy)]
+                        == Cell::Free
+                });
+            if !quarters_free {
+                break;
+            }
+            level -= 1;
+            (x, y) = (parent_x, parent_y);
+            self.levels[level][cell_index(level, x, y)] = Cell::Free;
+        }
+    }
+}
+
+fn cell_index(level: usize, x: u32, y: u32) -> usize {
+    ((y as usize) << level) + x as usize
+}
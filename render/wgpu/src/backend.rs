@@ -1,32 +1,39 @@
+use crate::blend::BlendType;
+use crate::buffer_arena::BufferArena;
 use crate::buffer_builder::BufferBuilder;
-use crate::buffer_pool::TexturePool;
 use crate::context3d::WgpuContext3D;
-use crate::mesh::{Mesh, PendingDraw};
-use crate::surface::Surface;
+use crate::mesh::{Draw, DrawType, Mesh, PendingDraw};
+use crate::surface::{RenderStatistics, Surface};
 use crate::target::RenderTargetFrame;
 use crate::target::TextureTarget;
 use crate::uniform_buffer::BufferStorage;
+use crate::utils::{
+    buffer_to_image, mip_level_count, rgba16_to_rgba16_float, write_texture_with_mipmaps,
+    BufferReadback,
+};
 use crate::{
-    as_texture, format_list, get_backend_names, ColorAdjustments, Descriptors, Error,
-    QueueSyncHandle, RenderTarget, SwapChainTarget, Texture, Transforms,
+    as_texture, format_list, get_backend_names, ColorAdjustments, Descriptors, Error, MorphVertex,
+    QueueSyncHandle, RenderTarget, SwapChainTarget, Texture, TextureStorage, Transforms,
 };
 use gc_arena::MutationContext;
 use ruffle_render::backend::{Context3D, Context3DCommand};
 use ruffle_render::backend::{RenderBackend, ShapeHandle, ViewportDimensions};
-use ruffle_render::bitmap::{Bitmap, BitmapHandle, BitmapSource, SyncHandle};
+use ruffle_render::bitmap::{Bitmap, BitmapFormat, BitmapHandle, BitmapSource, SyncHandle};
+use ruffle_render::bounding_box::BoundingBox;
 use ruffle_render::commands::CommandList;
 use ruffle_render::error::Error as BitmapError;
 use ruffle_render::filters::Filter;
 use ruffle_render::quality::StageQuality;
 use ruffle_render::shape_utils::DistilledShape;
 use ruffle_render::tessellator::ShapeTessellator;
+use ruffle_render::utils::unmultiply_alpha_rgba;
 use std::borrow::Cow;
 use std::cell::Cell;
 use std::mem;
 use std::num::NonZeroU32;
 use std::path::Path;
 use std::sync::Arc;
-use swf::Color;
+use swf::{BlendMode, Color};
 use tracing::instrument;
 use wgpu::Extent3d;
 
@@ -37,12 +44,17 @@ pub struct WgpuRenderBackend<T: RenderTarget> {
     target: T,
     surface: Surface,
     meshes: Vec<Mesh>,
+    /// The shared buffers that the vertices and indices of every mesh are allocated in.
+    vertex_arena: BufferArena,
+    index_arena: BufferArena,
     shape_tessellator: ShapeTessellator,
     // This is currently unused - we just store it to report in
     // `get_viewport_dimensions`
     viewport_scale_factor: f64,
-    texture_pool: TexturePool,
-    offscreen_texture_pool: TexturePool,
+    /// Overrides the MSAA sample count implied by the stage quality, if set.
+    sample_count: Option<u32>,
+    /// An error to fail the next frame with, instead of asking the target for its texture.
+    simulated_surface_error: Option<wgpu::SurfaceError>,
 }
 
 impl WgpuRenderBackend<SwapChainTarget> {
@@ -132,7 +144,6 @@ impl WgpuRenderBackend<crate::target::TextureTarget> {
     }
 
     pub fn capture_frame(&self, premultiplied_alpha: bool) -> Option<image::RgbaImage> {
-        use crate::utils::buffer_to_image;
         if let Some((buffer, dimensions)) = &self.target.buffer {
             Some(buffer_to_image(
                 &self.descriptors.device,
@@ -149,6 +160,10 @@ impl WgpuRenderBackend<crate::target::TextureTarget> {
 }
 
 impl<T: RenderTarget> WgpuRenderBackend<T> {
+    /// Creates a backend that renders to `target`.
+    ///
+    /// The same descriptors can be shared by any number of backends on the same device,
+    /// such as one for each movie, and their shaders and pipelines are then only created once.
     pub fn new(descriptors: Arc<Descriptors>, target: T) -> Result<Self, Error> {
         if target.width() > descriptors.limits.max_texture_dimension_2d
             || target.height() > descriptors.limits.max_texture_dimension_2d
@@ -183,13 +198,54 @@ impl<T: RenderTarget> WgpuRenderBackend<T> {
             target,
             surface,
             meshes: Vec::new(),
+            vertex_arena: BufferArena::new("Shape vertices", wgpu::BufferUsages::VERTEX),
+            index_arena: BufferArena::new("Shape indices", wgpu::BufferUsages::INDEX),
             shape_tessellator: ShapeTessellator::new(),
             viewport_scale_factor: 1.0,
-            texture_pool: TexturePool::new(),
-            offscreen_texture_pool: TexturePool::new(),
+            sample_count: None,
+            simulated_surface_error: None,
         })
     }
 
+    /// Sets the MSAA sample count used to render the stage, instead of deriving it from the
+    /// stage quality. Passing `None` goes back to following the quality.
+    ///
+    /// Counts that the adapter doesn't support for the target format are rounded down.
+    pub fn set_sample_count(&mut self, sample_count: Option<u32>) {
+        self.sample_count = sample_count;
+        self.surface = self.create_surface(
+            self.surface.quality(),
+            self.surface.size().width,
+            self.surface.size().height,
+        );
+    }
+
+    /// The MSAA sample count that the stage is currently rendered with.
+    pub fn sample_count(&self) -> u32 {
+        self.surface.sample_count()
+    }
+
+    /// Sets how far, in pixels before a shape is transformed, its curves may stray from
+    /// the triangles that they're tessellated into. Only shapes that are registered after this
+    /// are affected.
+    ///
+    /// Lowering it keeps shapes that are scaled far up from looking faceted, at the cost of
+    /// more triangles for every shape.
+    pub fn set_shape_tolerance(&mut self, tolerance: f32) {
+        self.shape_tessellator.set_tolerance(tolerance);
+    }
+
+    fn create_surface(&self, quality: StageQuality, width: u32, height: u32) -> Surface {
+        Surface::with_sample_count(
+            &self.descriptors,
+            quality,
+            self.sample_count.unwrap_or_else(|| quality.sample_count()),
+            width,
+            height,
+            self.target.format(),
+        )
+    }
+
     pub async fn request_device(
         backend: wgpu::Backends,
         instance: wgpu::Instance,
@@ -197,11 +253,25 @@ impl<T: RenderTarget> WgpuRenderBackend<T> {
         power_preference: wgpu::PowerPreference,
         trace_path: Option<&Path>,
     ) -> Result<(wgpu::Adapter, wgpu::Device, wgpu::Queue), Error> {
-        let adapter = instance.request_adapter(&wgpu::RequestAdapterOptions {
+        let adapter_options = |force_fallback_adapter| wgpu::RequestAdapterOptions {
             power_preference,
             compatible_surface: surface,
-            force_fallback_adapter: false,
-        }).await
+            force_fallback_adapter,
+        };
+        let adapter = match instance.request_adapter(&adapter_options(false)).await {
+            Some(adapter) => Some(adapter),
+            None => {
+                // Without a GPU, such as on a headless server, a software adapter can still render
+                let adapter = instance.request_adapter(&adapter_options(true)).await;
+                if let Some(adapter) = &adapter {
+                    tracing::warn!(
+                        "No graphics device was found, falling back to rendering with {}",
+                        adapter.get_info().name
+                    );
+                }
+                adapter
+            }
+        }
             .ok_or_else(|| {
                 let names = get_backend_names(backend);
                 if names.is_empty() {
@@ -223,6 +293,12 @@ impl<T: RenderTarget> WgpuRenderBackend<T> {
         bitmap_source: &dyn BitmapSource,
     ) -> Mesh {
         let shape_id = shape.id;
+        // Strokes that don't scale along with the shape can reach outside of its bounds
+        let bounds = if shape.stroke_matrix.is_some() {
+            BoundingBox::default()
+        } else {
+            shape.shape_bounds.clone()
+        };
         let lyon_mesh = self
             .shape_tessellator
             .tessellate_shape(shape, bitmap_source);
@@ -254,15 +330,15 @@ impl<T: RenderTarget> WgpuRenderBackend<T> {
             create_debug_label!("Shape {} uniforms", shape_id),
             wgpu::BufferUsages::UNIFORM,
         );
-        let vertex_buffer = vertex_buffer.finish(
+        let vertex_buffer = vertex_buffer.finish_in(
             &self.descriptors.device,
-            create_debug_label!("Shape {} vertices", shape_id),
-            wgpu::BufferUsages::VERTEX,
+            &self.descriptors.queue,
+            &mut self.vertex_arena,
         );
-        let index_buffer = index_buffer.finish(
+        let index_buffer = index_buffer.finish_in(
             &self.descriptors.device,
-            create_debug_label!("Shape {} indices", shape_id),
-            wgpu::BufferUsages::INDEX,
+            &self.descriptors.queue,
+            &mut self.index_arena,
         );
 
         let draws = draws
@@ -274,6 +350,7 @@ impl<T: RenderTarget> WgpuRenderBackend<T> {
             draws,
             vertex_buffer,
             index_buffer,
+            bounds,
         }
     }
 
@@ -288,6 +365,75 @@ impl<T: RenderTarget> WgpuRenderBackend<T> {
     pub fn device(&self) -> &wgpu::Device {
         &self.descriptors.device
     }
+
+    /// The number of draw calls that the last submitted frame was drawn with,
+    /// not counting those that applied blends and filters.
+    pub fn last_frame_draw_calls(&self) -> u32 {
+        self.surface.stats().draw_calls
+    }
+
+    /// The number of times that a pipeline, bitmap or gradient had to be changed between the
+    /// draw calls of the last submitted frame.
+    pub fn last_frame_state_changes(&self) -> u32 {
+        self.surface.stats().state_changes()
+    }
+
+    /// Everything that was counted while drawing the last submitted frame,
+    /// not counting the draws that applied blends and filters.
+    /// This is reset at the start of every frame.
+    pub fn last_frame_statistics(&self) -> RenderStatistics {
+        self.surface.stats()
+    }
+
+    /// Makes the next frame fail to begin with `error`, as if the target had reported it.
+    /// This is only meant for testing how the backend recovers from it.
+    pub fn simulate_surface_error(&mut self, error: wgpu::SurfaceError) {
+        self.simulated_surface_error = Some(error);
+    }
+
+    /// Gets the texture to render the next frame to.
+    ///
+    /// A surface that was lost or outdated, such as when the window was moved to another
+    /// display or the GPU was switched, is reconfigured straight away so that the frame can
+    /// still be rendered.
+    fn next_frame(&mut self) -> Option<T::Frame> {
+        let error = match self.simulated_surface_error.take() {
+            Some(error) => error,
+            None => match self.target.get_next_texture() {
+                Ok(frame) => return Some(frame),
+                Err(error) => error,
+            },
+        };
+        match error {
+            wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated => {
+                tracing::debug!("Reconfiguring render target: {}", error);
+                self.target.resize(
+                    &self.descriptors.device,
+                    self.target.width(),
+                    self.target.height(),
+                );
+                match self.target.get_next_texture() {
+                    Ok(frame) => Some(frame),
+                    Err(e) => {
+                        tracing::warn!(
+                            "Couldn't begin new render frame after reconfiguring: {}",
+                            e
+                        );
+                        None
+                    }
+                }
+            }
+            // The frame is skipped, and the next one will likely make it in time
+            wgpu::SurfaceError::Timeout => {
+                tracing::debug!("Couldn't begin new render frame: {}", error);
+                None
+            }
+            wgpu::SurfaceError::OutOfMemory => {
+                tracing::error!("Couldn't begin new render frame: {}", error);
+                None
+            }
+        }
+    }
 }
 
 impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
@@ -310,16 +456,15 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
         );
         self.target.resize(&self.descriptors.device, width, height);
 
-        self.surface = Surface::new(
-            &self.descriptors,
+        // Not every target can be resized, so the surface follows whatever size it ended up as
+        self.surface = self.create_surface(
             self.surface.quality(),
-            width,
-            height,
-            self.target.format(),
+            self.target.width(),
+            self.target.height(),
         );
 
         self.viewport_scale_factor = dimensions.scale_factor;
-        self.texture_pool = TexturePool::new();
+        self.descriptors.texture_pool().clear();
     }
 
     fn create_context3d(
@@ -346,13 +491,19 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
             });
 
         let handle = BitmapHandle(Arc::new(Texture {
+            mip_level_count: 1,
+            mipmaps_valid: Cell::new(false),
             bind_linear: Default::default(),
+            bind_mipmapped: Default::default(),
+            bind_anisotropic: Default::default(),
             bind_nearest: Default::default(),
-            texture: Arc::new(dummy_texture),
+            texture: TextureStorage::Own(Arc::new(dummy_texture)),
             texture_offscreen: Default::default(),
             width: 0,
             height: 0,
             copy_count: Cell::new(0),
+            premultiplied: true,
+            format,
         }));
         Ok(Box::new(WgpuContext3D::new(
             self.descriptors.clone(),
@@ -396,20 +547,54 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
         result.push(format!("Surface quality: {}", self.surface.quality()));
         result.push(format!("Surface samples: {}", self.surface.sample_count()));
         result.push(format!("Surface size: {:?}", self.surface.size()));
+        let stats = self.surface.stats();
+        result.push(format!("Draw calls last frame: {}", stats.draw_calls));
+        result.push(format!("Triangles last frame: {}", stats.triangles));
+        result.push(format!(
+            "Pipeline switches last frame: {}",
+            stats.pipeline_switches
+        ));
+        result.push(format!(
+            "Bind group switches last frame: {}",
+            stats.bind_group_switches
+        ));
+        result.push(format!("Stencil draws last frame: {}", stats.stencil_draws));
+        result.push(format!(
+            "Pipeline sets: {}",
+            self.descriptors.pipeline_set_count()
+        ));
+        result.push(format!(
+            "Pooled textures created: {}",
+            self.descriptors.pooled_textures_created()
+        ));
+
+        let (vertex_buffers, vertex_bytes) = self.vertex_arena.buffer_usage();
+        let (index_buffers, index_bytes) = self.index_arena.buffer_usage();
+        result.push(format!(
+            "Shape buffers: {} ({} bytes)",
+            vertex_buffers + index_buffers,
+            vertex_bytes + index_bytes
+        ));
 
         Cow::Owned(result.join("\n"))
     }
 
     fn set_quality(&mut self, quality: StageQuality) {
-        self.surface = Surface::new(
-            &self.descriptors,
+        self.surface = self.create_surface(
             quality,
             self.surface.size().width,
             self.surface.size().height,
-            self.target.format(),
         );
     }
 
+    fn is_blend_mode_accelerated(&self, blend_mode: BlendMode) -> bool {
+        matches!(BlendType::from(blend_mode), BlendType::Trivial(_))
+    }
+
+    fn is_filter_supported(&self, _filter: &Filter) -> bool {
+        true
+    }
+
     fn viewport_dimensions(&self) -> ViewportDimensions {
         ViewportDimensions {
             width: self.target.width(),
@@ -424,7 +609,7 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
         shape: DistilledShape,
         bitmap_source: &dyn BitmapSource,
     ) -> ShapeHandle {
-        let handle = ShapeHandle(self.meshes.len());
+        let handle = ShapeHandle::from_index(self.meshes.len());
         let mesh = self.register_shape_internal(shape, bitmap_source);
         self.meshes.push(mesh);
         handle
@@ -438,13 +623,15 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
         handle: ShapeHandle,
     ) {
         let mesh = self.register_shape_internal(shape, bitmap_source);
-        self.meshes[handle.0] = mesh;
+        let old_mesh = mem::replace(&mut self.meshes[handle.index()], mesh);
+        self.vertex_arena.free(old_mesh.vertex_buffer);
+        self.index_arena.free(old_mesh.index_buffer);
     }
 
     #[instrument(level = "debug", skip_all)]
     fn register_glyph_shape(&mut self, glyph: &swf::Glyph) -> ShapeHandle {
         let shape = ruffle_render::shape_utils::swf_glyph_to_shape(glyph);
-        let handle = ShapeHandle(self.meshes.len());
+        let handle = ShapeHandle::from_index(self.meshes.len());
         let mesh = self.register_shape_internal(
             (&shape).into(),
             &ruffle_render::backend::null::NullBitmapSource,
@@ -453,20 +640,56 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
         handle
     }
 
+    #[instrument(level = "debug", skip_all)]
+    fn register_morph_shape(
+        &mut self,
+        start: DistilledShape,
+        end: DistilledShape,
+    ) -> Option<ShapeHandle> {
+        // Every ratio of the morph lies somewhere between its start and its end
+        let mut bounds = start.shape_bounds.clone();
+        bounds.union(&end.shape_bounds);
+        let lyon_mesh = self.shape_tessellator.tessellate_morph_shape(start, end)?;
+
+        let mut vertex_buffer = BufferBuilder::new(0);
+        let mut index_buffer = BufferBuilder::new(0);
+        let mut draws = Vec::with_capacity(lyon_mesh.len());
+        for draw in lyon_mesh {
+            let vertices: Vec<_> = draw.vertices.into_iter().map(MorphVertex::from).collect();
+            draws.push(Draw {
+                draw_type: DrawType::Morph,
+                vertices: vertex_buffer.add(&vertices),
+                indices: index_buffer.add(&draw.indices),
+                num_indices: draw.indices.len() as u32,
+                num_mask_indices: draw.mask_index_count,
+            });
+        }
+
+        let vertex_buffer = vertex_buffer.finish_in(
+            &self.descriptors.device,
+            &self.descriptors.queue,
+            &mut self.vertex_arena,
+        );
+        let index_buffer = index_buffer.finish_in(
+            &self.descriptors.device,
+            &self.descriptors.queue,
+            &mut self.index_arena,
+        );
+
+        let handle = ShapeHandle::from_index(self.meshes.len());
+        self.meshes.push(Mesh {
+            draws,
+            vertex_buffer,
+            index_buffer,
+            bounds,
+        });
+        Some(handle)
+    }
+
     #[instrument(level = "debug", skip_all)]
     fn submit_frame(&mut self, clear: Color, commands: CommandList) {
-        let frame_output = match self.target.get_next_texture() {
-            Ok(frame) => frame,
-            Err(e) => {
-                tracing::warn!("Couldn't begin new render frame: {}", e);
-                // Attempt to recreate the swap chain in this case.
-                self.target.resize(
-                    &self.descriptors.device,
-                    self.target.width(),
-                    self.target.height(),
-                );
-                return;
-            }
+        let Some(frame_output) = self.next_frame() else {
+            return;
         };
 
         let command_buffers = self.surface.draw_commands_to(
@@ -482,7 +705,7 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
             &mut self.color_buffers_storage,
             &self.meshes,
             commands,
-            &mut self.texture_pool,
+            &mut self.descriptors.texture_pool(),
         );
 
         self.target.submit(
@@ -493,7 +716,75 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
         );
         self.uniform_buffers_storage.recall();
         self.color_buffers_storage.recall();
-        self.offscreen_texture_pool = TexturePool::new();
+        self.descriptors.texture_pool().end_frame();
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    fn capture_frame(&mut self, clear: Color, commands: CommandList) -> Option<Bitmap> {
+        // The frame is rendered again into a texture that can be read back, as a swap chain
+        // can't be. This uses the same format as `render_offscreen`, so the pixels match what
+        // `BitmapData.draw` would give regardless of the color space of the swap chain.
+        let target = match TextureTarget::new(
+            &self.descriptors.device,
+            (self.target.width(), self.target.height()),
+        ) {
+            Ok(target) => target,
+            Err(e) => {
+                tracing::warn!("Couldn't capture frame: {}", e);
+                return None;
+            }
+        };
+        let frame_output = target
+            .get_next_texture()
+            .expect("TextureTargetFrame.get_next_texture is infallible");
+
+        let mut surface = Surface::with_sample_count(
+            &self.descriptors,
+            self.surface.quality(),
+            self.surface.sample_count(),
+            target.size.width,
+            target.size.height,
+            target.format,
+        );
+        let command_buffers = surface.draw_commands_to(
+            frame_output.view(),
+            RenderTargetMode::FreshBuffer(wgpu::Color {
+                r: f64::from(clear.r) / 255.0,
+                g: f64::from(clear.g) / 255.0,
+                b: f64::from(clear.b) / 255.0,
+                a: f64::from(clear.a) / 255.0,
+            }),
+            &self.descriptors,
+            &mut self.uniform_buffers_storage,
+            &mut self.color_buffers_storage,
+            &self.meshes,
+            commands,
+            &mut self.descriptors.texture_pool(),
+        );
+        let index = target.submit(
+            &self.descriptors.device,
+            &self.descriptors.queue,
+            command_buffers,
+            frame_output,
+        );
+        self.uniform_buffers_storage.recall();
+        self.color_buffers_storage.recall();
+
+        let (buffer, dimensions) = target.buffer.as_ref()?;
+        let image = buffer_to_image(
+            &self.descriptors.device,
+            buffer,
+            dimensions,
+            Some(index),
+            target.size,
+            true,
+        );
+        Some(Bitmap::new(
+            image.width(),
+            image.height(),
+            BitmapFormat::Rgba,
+            image.into_raw(),
+        ))
     }
 
     #[instrument(level = "debug", skip_all)]
@@ -504,55 +795,137 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
             return Err(BitmapError::TooLarge);
         }
 
-        let bitmap = bitmap.to_rgba();
+        // Straight alpha is uploaded as it is, and premultiplied as it's drawn
+        let premultiplied = bitmap.format() != BitmapFormat::StraightRgba;
+        let (format, bitmap) = match bitmap.format() {
+            BitmapFormat::StraightRgba => (wgpu::TextureFormat::Rgba8Unorm, bitmap),
+            BitmapFormat::Rgba16
+                if self
+                    .descriptors
+                    .device
+                    .features()
+                    .contains(wgpu::Features::TEXTURE_FORMAT_16BIT_NORM) =>
+            {
+                (wgpu::TextureFormat::Rgba16Unorm, bitmap)
+            }
+            BitmapFormat::Rgba16 => {
+                let data = rgba16_to_rgba16_float(bitmap.data());
+                let bitmap = Bitmap::new(
+                    bitmap.width(),
+                    bitmap.height(),
+                    BitmapFormat::Rgba16Float,
+                    data,
+                );
+                (wgpu::TextureFormat::Rgba16Float, bitmap)
+            }
+            BitmapFormat::Rgba16Float => (wgpu::TextureFormat::Rgba16Float, bitmap),
+            BitmapFormat::Rgb | BitmapFormat::Rgba => {
+                (wgpu::TextureFormat::Rgba8Unorm, bitmap.to_rgba())
+            }
+        };
+        let is_8_bit = format == wgpu::TextureFormat::Rgba8Unorm;
         let extent = wgpu::Extent3d {
             width: bitmap.width(),
             height: bitmap.height(),
             depth_or_array_layers: 1,
         };
 
-        let texture_label = create_debug_label!("Bitmap");
-        let texture = self
-            .descriptors
-            .device
-            .create_texture(&wgpu::TextureDescriptor {
-                label: texture_label.as_deref(),
-                size: extent,
-                mip_level_count: 1,
-                sample_count: 1,
-                dimension: wgpu::TextureDimension::D2,
-                format: wgpu::TextureFormat::Rgba8Unorm,
-                view_formats: &[wgpu::TextureFormat::Rgba8Unorm],
-                usage: wgpu::TextureUsages::TEXTURE_BINDING
-                    | wgpu::TextureUsages::COPY_DST
-                    | wgpu::TextureUsages::RENDER_ATTACHMENT
-                    | wgpu::TextureUsages::COPY_SRC,
-            });
-
-        self.descriptors.queue.write_texture(
-            wgpu::ImageCopyTexture {
-                texture: &texture,
-                mip_level: 0,
-                origin: Default::default(),
-                aspect: wgpu::TextureAspect::All,
-            },
-            bitmap.data(),
-            wgpu::ImageDataLayout {
-                offset: 0,
-                bytes_per_row: NonZeroU32::new(4 * extent.width),
-                rows_per_image: None,
-            },
-            extent,
-        );
+        // Small bitmaps are packed into the atlas, to be drawn together out of it.
+        // The atlas holds premultiplied 8-bit pixels, so anything else gets a texture of its own.
+        let atlas_entry = if premultiplied && is_8_bit {
+            self.descriptors.bitmap_atlas().insert(
+                &self.descriptors,
+                extent.width,
+                extent.height,
+                bitmap.data(),
+            )
+        } else {
+            None
+        };
+        let (texture, mip_level_count) = match atlas_entry {
+            // Atlased bitmaps have no mipmaps, and neither does their own texture once evicted
+            Some(entry) => (TextureStorage::Atlas(entry), 1),
+            None => {
+                // Smooth bitmaps are sampled from a mipmap chain when they're scaled down,
+                // which is generated along with the texture so that they don't shimmer.
+                // Averaging straight alpha would blend in the colors of transparent pixels,
+                // so those are always sampled at full size, as are the wider formats that
+                // mipmaps aren't generated for.
+                let mip_level_count = if premultiplied && is_8_bit {
+                    mip_level_count(extent.width, extent.height)
+                } else {
+                    1
+                };
+                // Only 8-bit textures can be drawn to, as that's the format that's rendered in
+                let usage = if is_8_bit {
+                    wgpu::TextureUsages::TEXTURE_BINDING
+                        | wgpu::TextureUsages::COPY_DST
+                        | wgpu::TextureUsages::RENDER_ATTACHMENT
+                        | wgpu::TextureUsages::COPY_SRC
+                } else {
+                    wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST
+                };
+                let texture_label = create_debug_label!("Bitmap");
+                let texture = self
+                    .descriptors
+                    .device
+                    .create_texture(&wgpu::TextureDescriptor {
+                        label: texture_label.as_deref(),
+                        size: extent,
+                        mip_level_count,
+                        sample_count: 1,
+                        dimension: wgpu::TextureDimension::D2,
+                        format,
+                        view_formats: &[format],
+                        usage,
+                    });
+
+                if is_8_bit {
+                    write_texture_with_mipmaps(
+                        &self.descriptors.queue,
+                        &texture,
+                        mip_level_count,
+                        extent.width,
+                        extent.height,
+                        bitmap.data(),
+                    );
+                } else {
+                    self.descriptors.queue.write_texture(
+                        wgpu::ImageCopyTexture {
+                            texture: &texture,
+                            mip_level: 0,
+                            origin: Default::default(),
+                            aspect: wgpu::TextureAspect::All,
+                        },
+                        bitmap.data(),
+                        wgpu::ImageDataLayout {
+                            offset: 0,
+                            bytes_per_row: NonZeroU32::new(
+                                bitmap.format().bytes_per_pixel() as u32 * extent.width,
+                            ),
+                            rows_per_image: None,
+                        },
+                        extent,
+                    );
+                }
+                (TextureStorage::Own(Arc::new(texture)), mip_level_count)
+            }
+        };
 
         let handle = BitmapHandle(Arc::new(Texture {
-            texture: Arc::new(texture),
+            texture,
+            mip_level_count,
+            mipmaps_valid: Cell::new(true),
             bind_linear: Default::default(),
+            bind_mipmapped: Default::default(),
+            bind_anisotropic: Default::default(),
             bind_nearest: Default::default(),
             texture_offscreen: Default::default(),
             width: bitmap.width(),
             height: bitmap.height(),
             copy_count: Cell::new(0),
+            premultiplied,
+            format,
         }));
 
         Ok(handle)
@@ -564,9 +937,39 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
         handle: &BitmapHandle,
         width: u32,
         height: u32,
-        rgba: Vec<u8>,
+        mut rgba: Vec<u8>,
     ) -> Result<(), BitmapError> {
         let texture = as_texture(handle);
+        if texture.format != wgpu::TextureFormat::Rgba8Unorm {
+            return Err(BitmapError::Unimplemented);
+        }
+        // The pixels are premultiplied, so they're kept in the texture's own format
+        if !texture.premultiplied {
+            unmultiply_alpha_rgba(&mut rgba);
+        }
+
+        // A bitmap that's replaced as a whole stays in the atlas
+        if let Some(entry) = texture.atlas_entry() {
+            if width == texture.width && height == texture.height {
+                self.descriptors
+                    .bitmap_atlas()
+                    .write(&self.descriptors.queue, entry, &rgba);
+                return Ok(());
+            }
+        }
+
+        if width == texture.width && height == texture.height {
+            write_texture_with_mipmaps(
+                &self.descriptors.queue,
+                texture.texture(&self.descriptors),
+                texture.mip_level_count,
+                width,
+                height,
+                &rgba,
+            );
+            texture.mipmaps_valid.set(true);
+            return Ok(());
+        }
 
         let extent = wgpu::Extent3d {
             width,
@@ -576,7 +979,7 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
 
         self.descriptors.queue.write_texture(
             wgpu::ImageCopyTexture {
-                texture: &texture.texture,
+                texture: texture.texture(&self.descriptors),
                 mip_level: 0,
                 origin: Default::default(),
                 aspect: wgpu::TextureAspect::All,
@@ -589,6 +992,7 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
             },
             extent,
         );
+        texture.mipmaps_valid.set(false);
 
         Ok(())
     }
@@ -603,6 +1007,11 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
         quality: StageQuality,
     ) -> Option<Box<dyn SyncHandle>> {
         let texture = as_texture(&handle);
+        if texture.format != wgpu::TextureFormat::Rgba8Unorm {
+            tracing::warn!("Can't render to a {:?} texture", texture.format);
+            return None;
+        }
+        texture.mipmaps_valid.set(false);
 
         let extent = wgpu::Extent3d {
             width,
@@ -612,9 +1021,10 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
 
         let texture_offscreen = texture.texture_offscreen.get();
 
+        // Rendering to a bitmap in the atlas moves it into a texture of its own
         let mut target = TextureTarget {
             size: extent,
-            texture: texture.texture.clone(),
+            texture: texture.texture(&self.descriptors).clone(),
             format: wgpu::TextureFormat::Rgba8Unorm,
             buffer: texture_offscreen.map(|t| (t.buffer.clone(), t.buffer_dimensions.clone())),
         };
@@ -638,7 +1048,7 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
             &mut self.color_buffers_storage,
             &self.meshes,
             commands,
-            &mut self.offscreen_texture_pool,
+            &mut self.descriptors.texture_pool(),
         );
         let index = target.submit(
             &self.descriptors.device,
@@ -653,7 +1063,7 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
             Some(texture_offscreen) => Some(Box::new(QueueSyncHandle::AlreadyCopied {
                 index,
                 size: target.size,
-                buffer: texture_offscreen.buffer.clone(),
+                readback: BufferReadback::new(texture_offscreen.buffer.clone()),
                 buffer_dimensions: texture_offscreen.buffer_dimensions.clone(),
                 descriptors: self.descriptors.clone(),
             })),
@@ -676,6 +1086,14 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
     ) -> Option<Box<dyn SyncHandle>> {
         let source_texture = as_texture(&source);
         let dest_texture = as_texture(&destination);
+        if dest_texture.format != wgpu::TextureFormat::Rgba8Unorm {
+            tracing::warn!(
+                "Can't apply a filter to a {:?} texture",
+                dest_texture.format
+            );
+            return None;
+        }
+        dest_texture.mipmaps_valid.set(false);
 
         let mut target = TextureTarget {
             size: wgpu::Extent3d {
@@ -683,7 +1101,7 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
                 height: dest_texture.height,
                 depth_or_array_layers: 1,
             },
-            texture: dest_texture.texture.clone(),
+            texture: dest_texture.texture(&self.descriptors).clone(),
             format: wgpu::TextureFormat::Rgba8Unorm,
             buffer: dest_texture
                 .texture_offscreen
@@ -711,7 +1129,7 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
         surface.apply_filter(
             &self.descriptors,
             &mut draw_encoder,
-            &mut self.offscreen_texture_pool,
+            &mut self.descriptors.texture_pool(),
             source_texture,
             source_point,
             source_size,
@@ -729,7 +1147,7 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
             Some(texture_offscreen) => Some(Box::new(QueueSyncHandle::AlreadyCopied {
                 index,
                 size: target.size,
-                buffer: texture_offscreen.buffer.clone(),
+                readback: BufferReadback::new(texture_offscreen.buffer.clone()),
                 buffer_dimensions: texture_offscreen.buffer_dimensions.clone(),
                 descriptors: self.descriptors.clone(),
             })),
@@ -742,8 +1160,9 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
     }
 }
 
+/// Requests a device from `adapter` with every feature and limit that Ruffle can make use of.
 // We try to request the highest limits we can get away with
-async fn request_device(
+pub async fn request_device(
     adapter: &wgpu::Adapter,
     trace_path: Option<&Path>,
 ) -> Result<(wgpu::Device, wgpu::Queue), wgpu::RequestDeviceError> {
@@ -771,6 +1190,15 @@ async fn request_device(
         features |= wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES;
     }
 
+    // Bitmaps with 16 bits per channel keep all of them with this, rather than only
+    // the 11 bits of a half float
+    if adapter
+        .features()
+        .contains(wgpu::Features::TEXTURE_FORMAT_16BIT_NORM)
+    {
+        features |= wgpu::Features::TEXTURE_FORMAT_16BIT_NORM;
+    }
+
     adapter
         .request_device(
             &wgpu::DeviceDescriptor {
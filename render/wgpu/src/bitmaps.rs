@@ -1,15 +1,20 @@
+use std::num::NonZeroU8;
+
 #[derive(Debug)]
 pub struct BitmapSamplers {
     repeat_linear: wgpu::Sampler,
     repeat_nearest: wgpu::Sampler,
+    repeat_anisotropic: wgpu::Sampler,
     clamp_linear: wgpu::Sampler,
     clamp_nearest: wgpu::Sampler,
+    clamp_anisotropic: wgpu::Sampler,
 }
 
 fn create_sampler(
     device: &wgpu::Device,
     address_mode: wgpu::AddressMode,
     filter: wgpu::FilterMode,
+    anisotropy_clamp: Option<NonZeroU8>,
     sampler_label: Option<String>,
 ) -> wgpu::Sampler {
     let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
@@ -23,44 +28,71 @@ fn create_sampler(
         lod_min_clamp: 0.0,
         lod_max_clamp: 100.0,
         compare: None,
-        anisotropy_clamp: None,
+        anisotropy_clamp,
         border_color: None,
     });
     sampler
 }
 
 impl BitmapSamplers {
-    pub fn new(device: &wgpu::Device) -> Self {
+    /// Creates the samplers, where the anisotropic ones are plain linear samplers
+    /// if `anisotropic_filtering` isn't supported.
+    pub fn new(device: &wgpu::Device, anisotropic_filtering: bool) -> Self {
+        let anisotropy_clamp = if anisotropic_filtering {
+            NonZeroU8::new(16)
+        } else {
+            None
+        };
         let repeat_linear = create_sampler(
             device,
             wgpu::AddressMode::Repeat,
             wgpu::FilterMode::Linear,
+            None,
             create_debug_label!("Repeat & Linear sampler"),
         );
         let repeat_nearest = create_sampler(
             device,
             wgpu::AddressMode::Repeat,
             wgpu::FilterMode::Nearest,
+            None,
             create_debug_label!("Repeat & Nearest sampler"),
         );
+        let repeat_anisotropic = create_sampler(
+            device,
+            wgpu::AddressMode::Repeat,
+            wgpu::FilterMode::Linear,
+            anisotropy_clamp,
+            create_debug_label!("Repeat & Anisotropic sampler"),
+        );
         let clamp_linear = create_sampler(
             device,
             wgpu::AddressMode::ClampToEdge,
             wgpu::FilterMode::Linear,
+            None,
             create_debug_label!("Clamp & Linear sampler"),
         );
         let clamp_nearest = create_sampler(
             device,
             wgpu::AddressMode::ClampToEdge,
             wgpu::FilterMode::Nearest,
+            None,
             create_debug_label!("Clamp & Nearest sampler"),
         );
+        let clamp_anisotropic = create_sampler(
+            device,
+            wgpu::AddressMode::ClampToEdge,
+            wgpu::FilterMode::Linear,
+            anisotropy_clamp,
+            create_debug_label!("Clamp & Anisotropic sampler"),
+        );
 
         Self {
             repeat_linear,
             repeat_nearest,
+            repeat_anisotropic,
             clamp_linear,
             clamp_nearest,
+            clamp_anisotropic,
         }
     }
 
@@ -72,4 +104,13 @@ impl BitmapSamplers {
             (false, false) => &self.clamp_nearest,
         }
     }
+
+    /// A smoothed sampler that stays sharp when sampling mipmaps at an oblique angle.
+    pub fn get_anisotropic_sampler(&self, is_repeating: bool) -> &wgpu::Sampler {
+        if is_repeating {
+            &self.repeat_anisotropic
+        } else {
+            &self.clamp_anisotropic
+        }
+    }
 }
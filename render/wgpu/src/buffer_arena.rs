@@ -0,0 +1,180 @@
+use std::ops::Range;
+use std::sync::Arc;
+
+/// The size of the buffers that allocations are carved out of.
+/// Anything bigger than this gets a buffer of its own.
+const BLOCK_SIZE: wgpu::BufferAddress = 4 * 1024 * 1024;
+
+/// Sub-allocates the vertex or index data of many shapes out of a few large buffers,
+/// instead of every shape creating its own.
+///
+/// Space is handed back with [`BufferArena::free`] and reused by later allocations,
+/// and a buffer is released entirely once nothing is allocated in it anymore.
+#[derive(Debug)]
+pub struct BufferArena {
+    label: &'static str,
+    usage: wgpu::BufferUsages,
+    blocks: Vec<Option<Block>>,
+}
+
+#[derive(Debug)]
+struct Block {
+    buffer: Arc<wgpu::Buffer>,
+    size: wgpu::BufferAddress,
+    /// The unallocated parts of the buffer, sorted and never adjacent to each other.
+    free: Vec<Range<wgpu::BufferAddress>>,
+}
+
+impl Block {
+    fn allocate(&mut self, size: wgpu::BufferAddress) -> Option<Range<wgpu::BufferAddress>> {
+        let index = self
+            .free
+            .iter()
+            .position(|range| range.end - range.start >= size)?;
+        let range = &mut self.free[index];
+        let start = range.start;
+        range.start += size;
+        if range.start == range.end {
+            self.free.remove(index);
+        }
+        Some(start..start + size)
+    }
+
+    fn free(&mut self, range: Range<wgpu::BufferAddress>) {
+        let index = self.free.partition_point(|free| free.start < range.start);
+        let merges_previous = index > 0 && self.free[index - 1].end == range.start;
+        let merges_next = index < self.free.len() && self.free[index].start == range.end;
+        match (merges_previous, merges_next) {
+            (true, true) => {
+                self.free[index - 1].end = self.free[index].end;
+                self.free.remove(index);
+            }
+            (true, false) => self.free[index - 1].end = range.end,
+            (false, true) => self.free[index].start = range.start,
+            (false, false) => self.free.insert(index, range),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.free.len() == 1 && self.free[0] == (0..self.size)
+    }
+}
+
+/// Some space in one of the buffers of a [`BufferArena`].
+/// This must be given back to the arena that it came from when it's no longer needed.
+#[derive(Debug)]
+pub struct ArenaAllocation {
+    buffer: Arc<wgpu::Buffer>,
+    block: usize,
+    range: Range<wgpu::BufferAddress>,
+}
+
+impl ArenaAllocation {
+    /// A slice of the data that was allocated, with `range` relative to the start of it.
+    pub fn slice(&self, range: Range<wgpu::BufferAddress>) -> wgpu::BufferSlice {
+        self.buffer
+            .slice(self.range.start + range.start..self.range.start + range.end)
+    }
+}
+
+impl BufferArena {
+    pub fn new(label: &'static str, usage: wgpu::BufferUsages) -> Self {
+        Self {
+            label,
+            usage: usage | wgpu::BufferUsages::COPY_DST,
+            blocks: Vec::new(),
+        }
+    }
+
+    /// Copies `data` into the arena, making room for it if there isn't any.
+    pub fn allocate(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        data: &[u8],
+    ) -> ArenaAllocation {
+        // Copies have to be a multiple of 4 bytes, which also keeps every allocation aligned
+        // well enough to be bound as vertices or 32-bit indices.
+        let align_mask = wgpu::COPY_BUFFER_ALIGNMENT - 1;
+        let size = ((data.len() as wgpu::BufferAddress + align_mask) & !align_mask)
+            .max(wgpu::COPY_BUFFER_ALIGNMENT);
+
+        let found = self
+            .blocks
+            .iter_mut()
+            .enumerate()
+            .find_map(|(index, block)| Some((index, block.as_mut()?.allocate(size)?)));
+        let (block, range) = found.unwrap_or_else(|| self.add_block(device, size));
+        let buffer = self.blocks[block]
+            .as_ref()
+            .expect("Allocation must be in a block")
+            .buffer
+            .clone();
+
+        if data.len() as wgpu::BufferAddress == size {
+            queue.write_buffer(&buffer, range.start, data);
+        } else {
+            let mut padded = data.to_vec();
+            padded.resize(size as usize, 0);
+            queue.write_buffer(&buffer, range.start, &padded);
+        }
+
+        ArenaAllocation {
+            buffer,
+            block,
+            range,
+        }
+    }
+
+    /// Makes the space of an allocation available to later ones.
+    pub fn free(&mut self, allocation: ArenaAllocation) {
+        let slot = &mut self.blocks[allocation.block];
+        let block = slot.as_mut().expect("Allocation must be in a block");
+        block.free(allocation.range);
+        if block.is_empty() {
+            *slot = None;
+        }
+    }
+
+    /// The number of buffers that the arena currently uses, and their total size in bytes.
+    pub fn buffer_usage(&self) -> (usize, wgpu::BufferAddress) {
+        self.blocks
+            .iter()
+            .flatten()
+            .fold((0, 0), |(count, size), block| {
+                (count + 1, size + block.size)
+            })
+    }
+
+    fn add_block(
+        &mut self,
+        device: &wgpu::Device,
+        allocation_size: wgpu::BufferAddress,
+    ) -> (usize, Range<wgpu::BufferAddress>) {
+        let index = self
+            .blocks
+            .iter()
+            .position(Option::is_none)
+            .unwrap_or_else(|| {
+                self.blocks.push(None);
+                self.blocks.len() - 1
+            });
+        let size = allocation_size.max(BLOCK_SIZE);
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: create_debug_label!("{} {}", self.label, index).as_deref(),
+            size,
+            usage: self.usage,
+            mapped_at_creation: false,
+        });
+        let mut block = Block {
+            buffer: Arc::new(buffer),
+            size,
+            free: vec![0..size],
+        };
+        let range = block
+            .allocate(allocation_size)
+            .expect("New block must fit the allocation");
+        self.blocks[index] = Some(block);
+        (index, range)
+    }
+}
@@ -1,3 +1,4 @@
+use crate::buffer_arena::{ArenaAllocation, BufferArena};
 use bytemuck::{AnyBitPattern, NoUninit};
 use std::ops::Range;
 use wgpu::util::DeviceExt;
@@ -40,4 +41,14 @@ impl BufferBuilder {
             usage,
         })
     }
+
+    /// Copies the contents into a shared buffer instead of creating a new one.
+    pub fn finish_in(
+        self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        arena: &mut BufferArena,
+    ) -> ArenaAllocation {
+        arena.allocate(device, queue, &self.inner)
+    }
 }
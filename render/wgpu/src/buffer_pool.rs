@@ -6,12 +6,38 @@ use std::ops::Deref;
 use std::sync::{Arc, Mutex, Weak};
 
 type PoolInner<T> = Mutex<Vec<T>>;
-type Constructor<T> = Box<dyn Fn(&Descriptors) -> T>;
+type Constructor<T> = Box<dyn Fn(&Descriptors) -> T + Send + Sync>;
 
+/// How many frames something in a [`TexturePool`] can go unused for before it's freed.
+const MAX_UNUSED_FRAMES: u64 = 60;
+
+/// The smallest instance buffer that a [`TexturePool`] creates, in bytes.
+/// Every instance buffer is a power of two in size, so that a few sizes serve every batch.
+const MIN_INSTANCE_BUFFER_SIZE: usize = 4096;
+
+/// Textures that are only needed while rendering, such as the intermediate targets of filters
+/// and blends. Textures that are given back are handed out again, both later in the same frame
+/// and on later frames. Any size that isn't asked for in a while is freed.
+///
+/// The instance buffers of batched draws are kept here too.
 #[derive(Debug)]
 pub struct TexturePool {
-    pools: FnvHashMap<TextureKey, BufferPool<(wgpu::Texture, wgpu::TextureView)>>,
-    globals_cache: FnvHashMap<GlobalsKey, Arc<Globals>>,
+    pools: FnvHashMap<TextureKey, Pooled<BufferPool<(wgpu::Texture, wgpu::TextureView)>>>,
+    globals_cache: FnvHashMap<GlobalsKey, Pooled<Arc<Globals>>>,
+    instance_buffers: FnvHashMap<usize, Pooled<BufferPool<wgpu::Buffer>>>,
+    /// Every instance buffer that was handed out this frame.
+    /// Writes to a buffer only happen once the frame is submitted, so a buffer can't be
+    /// handed out again (and overwritten) until the frame that it was last written in ends.
+    frame_instance_buffers: Vec<Arc<PoolEntry<wgpu::Buffer>>>,
+    frame: u64,
+    textures_created: usize,
+}
+
+/// Something kept by a [`TexturePool`], along with the last frame that it was asked for on.
+#[derive(Debug)]
+struct Pooled<T> {
+    item: T,
+    last_used: u64,
 }
 
 impl TexturePool {
@@ -19,6 +45,10 @@ impl TexturePool {
         Self {
             pools: FnvHashMap::default(),
             globals_cache: FnvHashMap::default(),
+            instance_buffers: FnvHashMap::default(),
+            frame_instance_buffers: Vec::new(),
+            frame: 0,
+            textures_created: 0,
         }
     }
 
@@ -45,22 +75,29 @@ impl TexturePool {
             } else {
                 None
             };
-            BufferPool::new(Box::new(move |descriptors| {
-                let texture = descriptors.device.create_texture(&wgpu::TextureDescriptor {
-                    label: label.as_deref(),
-                    size,
-                    mip_level_count: 1,
-                    sample_count,
-                    dimension: wgpu::TextureDimension::D2,
-                    format,
-                    view_formats: &[format],
-                    usage,
-                });
-                let view = texture.create_view(&Default::default());
-                (texture, view)
-            }))
+            Pooled {
+                item: BufferPool::new(Box::new(move |descriptors| {
+                    let texture = descriptors.device.create_texture(&wgpu::TextureDescriptor {
+                        label: label.as_deref(),
+                        size,
+                        mip_level_count: 1,
+                        sample_count,
+                        dimension: wgpu::TextureDimension::D2,
+                        format,
+                        view_formats: &[format],
+                        usage,
+                    });
+                    let view = texture.create_view(&Default::default());
+                    (texture, view)
+                })),
+                last_used: self.frame,
+            }
         });
-        pool.take(descriptors)
+        pool.last_used = self.frame;
+        if pool.item.is_empty() {
+            self.textures_created += 1;
+        }
+        pool.item.take(descriptors)
     }
 
     pub fn get_globals(
@@ -69,20 +106,78 @@ impl TexturePool {
         viewport_width: u32,
         viewport_height: u32,
     ) -> Arc<Globals> {
-        self.globals_cache
+        let frame = self.frame;
+        let globals = self
+            .globals_cache
             .entry(GlobalsKey {
                 viewport_width,
                 viewport_height,
             })
-            .or_insert_with(|| {
-                Arc::new(Globals::new(
+            .or_insert_with(|| Pooled {
+                item: Arc::new(Globals::new(
                     &descriptors.device,
                     &descriptors.bind_layouts.globals,
                     viewport_width,
                     viewport_height,
-                ))
-            })
-            .clone()
+                )),
+                last_used: frame,
+            });
+        globals.last_used = frame;
+        globals.item.clone()
+    }
+
+    /// Returns a vertex buffer that holds `data`, for the instances of a batched draw.
+    /// The buffer may be larger than the data, so draws must only use as many instances as
+    /// were written.
+    pub fn get_instance_buffer(
+        &mut self,
+        descriptors: &Descriptors,
+        data: &[u8],
+    ) -> Arc<PoolEntry<wgpu::Buffer>> {
+        let size = data.len().next_power_of_two().max(MIN_INSTANCE_BUFFER_SIZE);
+        let pool = self.instance_buffers.entry(size).or_insert_with(|| Pooled {
+            item: BufferPool::new(Box::new(move |descriptors| {
+                descriptors.device.create_buffer(&wgpu::BufferDescriptor {
+                    label: create_debug_label!("Pooled instance buffer ({} bytes)", size)
+                        .as_deref(),
+                    size: size as wgpu::BufferAddress,
+                    usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+                    mapped_at_creation: false,
+                })
+            })),
+            last_used: self.frame,
+        });
+        pool.last_used = self.frame;
+        let buffer = Arc::new(pool.item.take(descriptors));
+        descriptors.queue.write_buffer(&buffer, 0, data);
+        self.frame_instance_buffers.push(buffer.clone());
+        buffer
+    }
+
+    /// Frees everything that hasn't been asked for in the last few frames.
+    /// Textures that are still in use at the time are freed once they're given back.
+    pub fn end_frame(&mut self) {
+        let frame = self.frame;
+        self.frame_instance_buffers.clear();
+        self.pools
+            .retain(|_, pool| frame - pool.last_used < MAX_UNUSED_FRAMES);
+        self.globals_cache
+            .retain(|_, globals| frame - globals.last_used < MAX_UNUSED_FRAMES);
+        self.instance_buffers
+            .retain(|_, pool| frame - pool.last_used < MAX_UNUSED_FRAMES);
+        self.frame += 1;
+    }
+
+    /// Frees every texture, such as when the sizes that are needed have all changed.
+    pub fn clear(&mut self) {
+        self.pools.clear();
+        self.globals_cache.clear();
+        self.instance_buffers.clear();
+    }
+
+    /// The number of textures that have been created since the pool was, as opposed to reused.
+    pub fn textures_created(&self) -> usize {
+        self.textures_created
     }
 }
 
@@ -119,6 +214,14 @@ impl<T> BufferPool<T> {
         }
     }
 
+    /// Whether there's nothing to hand out, so the next item taken has to be created.
+    pub fn is_empty(&self) -> bool {
+        self.available
+            .lock()
+            .expect("Should not be able to lock recursively")
+            .is_empty()
+    }
+
     pub fn take(&self, descriptors: &Descriptors) -> PoolEntry<T> {
         let item = self
             .available
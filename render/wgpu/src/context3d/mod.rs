@@ -12,7 +12,7 @@ use wgpu::{
 use wgpu::{CommandEncoder, Extent3d, RenderPass};
 
 use crate::descriptors::Descriptors;
-use crate::Texture;
+use crate::{Texture, TextureStorage};
 use gc_arena::{Collect, MutationContext};
 
 use std::num::NonZeroU64;
@@ -291,13 +291,19 @@ impl WgpuContext3D {
                     self.texture_view = Some(wgpu_texture.create_view(&Default::default()));
 
                     self.raw_texture_handle = BitmapHandle(Arc::new(Texture {
-                        texture: Arc::new(wgpu_texture),
+                        texture: TextureStorage::Own(Arc::new(wgpu_texture)),
+                        mip_level_count: 1,
+                        mipmaps_valid: Cell::new(false),
                         bind_linear: Default::default(),
+                        bind_mipmapped: Default::default(),
+                        bind_anisotropic: Default::default(),
                         bind_nearest: Default::default(),
                         texture_offscreen: Default::default(),
                         width: *width,
                         height: *height,
                         copy_count: Cell::new(0),
+                        premultiplied: true,
+                        format,
                     }));
                 }
                 Context3DCommand::UploadToIndexBuffer {
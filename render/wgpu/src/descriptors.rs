@@ -1,3 +1,5 @@
+use crate::atlas::BitmapAtlas;
+use crate::buffer_pool::TexturePool;
 use crate::layouts::BindLayouts;
 use crate::pipelines::VERTEX_BUFFERS_DESCRIPTION_POS;
 use crate::shaders::Shaders;
@@ -6,15 +8,22 @@ use crate::{
     TextureTransforms, Transforms, DEFAULT_COLOR_ADJUSTMENTS,
 };
 use fnv::FnvHashMap;
+use once_cell::sync::OnceCell;
+use ruffle_render::tessellator::Gradient;
 use std::fmt::Debug;
 use std::mem;
-use std::sync::{Arc, Mutex};
+use std::num::NonZeroU32;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+
+/// The number of texels that a gradient's colors are baked into, one for every possible ratio.
+pub const GRADIENT_SIZE: usize = 256;
 
 pub struct Descriptors {
     pub adapter: wgpu::Adapter,
-    pub device: wgpu::Device,
+    pub device: Arc<wgpu::Device>,
     pub limits: wgpu::Limits,
-    pub queue: wgpu::Queue,
+    pub queue: Arc<wgpu::Queue>,
     pub bitmap_samplers: BitmapSamplers,
     pub bind_layouts: BindLayouts,
     pub quad: Quad,
@@ -22,7 +31,19 @@ pub struct Descriptors {
     copy_srgb_pipeline: Mutex<FnvHashMap<(u32, wgpu::TextureFormat), Arc<wgpu::RenderPipeline>>>,
     shaders: Shaders,
     pipelines: Mutex<FnvHashMap<(u32, wgpu::TextureFormat), Arc<Pipelines>>>,
+    /// The color ramps of every gradient that's still in use, so that identical ones are shared.
+    gradient_ramps: Mutex<FnvHashMap<Vec<u8>, Weak<wgpu::TextureView>>>,
     pub default_color_bind_group: wgpu::BindGroup,
+    /// The texture that small bitmaps are packed into, created once the first one is registered.
+    bitmap_atlas: OnceCell<BitmapAtlas>,
+    /// Every possible morph ratio as a vertex attribute, so that a morph shape can be drawn at
+    /// any ratio by binding just the one it needs as its instance buffer.
+    pub morph_ratios: wgpu::Buffer,
+    /// The textures that rendering needs along the way, shared by everything that renders with
+    /// this device so that they can be reused from one frame or render to the next.
+    texture_pool: Mutex<TexturePool>,
+    /// The number of passes that blurs have been drawn with, including those of other filters.
+    blur_passes: AtomicUsize,
 }
 
 impl Debug for Descriptors {
@@ -33,9 +54,29 @@ impl Debug for Descriptors {
 
 impl Descriptors {
     pub fn new(adapter: wgpu::Adapter, device: wgpu::Device, queue: wgpu::Queue) -> Self {
+        Self::with_shared_device(adapter, Arc::new(device), Arc::new(queue))
+    }
+
+    /// Creates descriptors for a device that is also used by someone else, such as an
+    /// application that Ruffle is embedded in. Everything Ruffle creates is created on it.
+    ///
+    /// The device doesn't need any particular features or limits, as Ruffle only uses what it
+    /// was created with. [`crate::backend::request_device`] requests everything that Ruffle
+    /// can make use of, if the application would rather leave that choice to Ruffle.
+    pub fn with_shared_device(
+        adapter: wgpu::Adapter,
+        device: Arc<wgpu::Device>,
+        queue: Arc<wgpu::Queue>,
+    ) -> Self {
         let limits = device.limits();
         let bind_layouts = BindLayouts::new(&device);
-        let bitmap_samplers = BitmapSamplers::new(&device);
+        let bitmap_samplers = BitmapSamplers::new(
+            &device,
+            adapter
+                .get_downlevel_capabilities()
+                .flags
+                .contains(wgpu::DownlevelFlags::ANISOTROPIC_FILTERING),
+        );
         let shaders = Shaders::new(&device);
         let quad = Quad::new(&device);
         let default_color_transform = create_buffer_with_data(
@@ -53,6 +94,16 @@ impl Descriptors {
             }],
         });
 
+        let morph_ratios: Vec<f32> = (0..=u16::MAX)
+            .map(|ratio| ratio as f32 / u16::MAX as f32)
+            .collect();
+        let morph_ratios = create_buffer_with_data(
+            &device,
+            bytemuck::cast_slice(&morph_ratios),
+            wgpu::BufferUsages::VERTEX,
+            create_debug_label!("Morph ratios"),
+        );
+
         Self {
             adapter,
             device,
@@ -65,7 +116,12 @@ impl Descriptors {
             copy_srgb_pipeline: Default::default(),
             shaders,
             pipelines: Default::default(),
+            gradient_ramps: Default::default(),
             default_color_bind_group,
+            bitmap_atlas: OnceCell::new(),
+            morph_ratios,
+            texture_pool: Mutex::new(TexturePool::new()),
+            blur_passes: AtomicUsize::new(0),
         }
     }
 
@@ -245,6 +301,150 @@ impl Descriptors {
             })
             .clone()
     }
+
+    pub(crate) fn bitmap_atlas(&self) -> &BitmapAtlas {
+        self.bitmap_atlas.get_or_init(|| {
+            BitmapAtlas::new(
+                &self.device,
+                &self.bind_layouts.bitmap,
+                &self.quad,
+                &self.bitmap_samplers,
+            )
+        })
+    }
+
+    /// The number of bitmaps that are packed into the atlas, to be drawn together out of it.
+    pub fn atlas_bitmap_count(&self) -> usize {
+        self.bitmap_atlas.get().map_or(0, BitmapAtlas::bitmap_count)
+    }
+
+    /// The number of different sets of pipelines that have been created, one for every
+    /// combination of sample count and texture format that anything has rendered with.
+    pub fn pipeline_set_count(&self) -> usize {
+        self.pipelines
+            .lock()
+            .expect("Pipelines should not be already locked")
+            .len()
+    }
+
+    pub(crate) fn texture_pool(&self) -> MutexGuard<TexturePool> {
+        self.texture_pool
+            .lock()
+            .expect("Texture pool should not be already locked")
+    }
+
+    /// The number of textures that have been created for rendering, instead of reusing ones
+    /// that earlier rendering was done with.
+    pub fn pooled_textures_created(&self) -> usize {
+        self.texture_pool().textures_created()
+    }
+
+    /// The number of passes that blurs have been drawn with so far, including the blurs of glows,
+    /// drop shadows and bevels.
+    pub fn blur_passes(&self) -> usize {
+        self.blur_passes.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn count_blur_passes(&self, passes: usize) {
+        self.blur_passes.fetch_add(passes, Ordering::Relaxed);
+    }
+
+    /// Returns a texture with the colors of a gradient baked into it, from ratio 0 to 255.
+    /// Gradients with the same colors share the same texture.
+    pub fn gradient_ramp(&self, gradient: &Gradient) -> Arc<wgpu::TextureView> {
+        let texels = bake_gradient_ramp(gradient);
+        let mut ramps = self
+            .gradient_ramps
+            .lock()
+            .expect("Gradient ramps should not be already locked");
+        if let Some(ramp) = ramps.get(&texels).and_then(Weak::upgrade) {
+            return ramp;
+        }
+
+        let extent = wgpu::Extent3d {
+            width: GRADIENT_SIZE as u32,
+            height: 1,
+            depth_or_array_layers: 1,
+        };
+        let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: create_debug_label!("Gradient ramp").as_deref(),
+            size: extent,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            view_formats: &[wgpu::TextureFormat::Rgba8Unorm],
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        });
+        self.queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &texture,
+                mip_level: 0,
+                origin: Default::default(),
+                aspect: wgpu::TextureAspect::All,
+            },
+            &texels,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: NonZeroU32::new(4 * extent.width),
+                rows_per_image: None,
+            },
+            extent,
+        );
+        let ramp = Arc::new(texture.create_view(&Default::default()));
+
+        // Forget about the ramps that aren't used by any shape anymore
+        ramps.retain(|_, ramp| ramp.strong_count() > 0);
+        ramps.insert(texels, Arc::downgrade(&ramp));
+        ramp
+    }
+}
+
+/// Calculates the straight sRGB color of a gradient at every ratio.
+fn bake_gradient_ramp(gradient: &Gradient) -> Vec<u8> {
+    let ratios = &gradient.ratios[..gradient.num_colors];
+    let colors = &gradient.colors[..gradient.num_colors];
+    let mut texels = Vec::with_capacity(GRADIENT_SIZE * 4);
+    for i in 0..GRADIENT_SIZE {
+        let t = i as f32 / (GRADIENT_SIZE - 1) as f32;
+        // Find the two colors bordering this position, or the nearest one past either end
+        let color = match ratios.iter().position(|ratio| t <= *ratio) {
+            None => colors.last().copied().unwrap_or_default(),
+            Some(0) => colors[0],
+            Some(j) => {
+                let (start, end) = (ratios[j - 1], ratios[j]);
+                let a = if end > start {
+                    (t - start) / (end - start)
+                } else {
+                    1.0
+                };
+                let mut color = [0.0; 4];
+                for ((color, start), end) in color.iter_mut().zip(colors[j - 1]).zip(colors[j]) {
+                    *color = start + (end - start) * a;
+                }
+                color
+            }
+        };
+        let [r, g, b, a] = if gradient.interpolation == swf::GradientInterpolation::LinearRgb {
+            // The colors were converted to linear RGB to be interpolated in
+            let [r, g, b, a] = color;
+            [linear_to_srgb(r), linear_to_srgb(g), linear_to_srgb(b), a]
+        } else {
+            color
+        };
+        for component in [r, g, b, a] {
+            texels.push((component.clamp(0.0, 1.0) * 255.0).round() as u8);
+        }
+    }
+    texels
+}
+
+fn linear_to_srgb(n: f32) -> f32 {
+    if n <= 0.0031308 {
+        n * 12.92
+    } else {
+        1.055 * f32::powf(n, 1.0 / 2.4) - 0.055
+    }
 }
 
 pub struct Quad {
@@ -252,6 +452,10 @@ pub struct Quad {
     pub vertices_pos_color: wgpu::Buffer,
     pub indices: wgpu::Buffer,
     pub texture_transforms: wgpu::Buffer,
+    /// The same texture transforms, for textures with straight alpha that are sampled
+    /// without and with smoothing.
+    pub straight_texture_transforms: wgpu::Buffer,
+    pub smooth_straight_texture_transforms: wgpu::Buffer,
 }
 
 impl Quad {
@@ -311,18 +515,39 @@ impl Quad {
             create_debug_label!("Quad ibo"),
         );
 
+        let identity_transforms = TextureTransforms {
+            u_matrix: [
+                [1.0, 0.0, 0.0, 0.0],
+                [0.0, 1.0, 0.0, 0.0],
+                [0.0, 0.0, 1.0, 0.0],
+                [0.0, 0.0, 0.0, 1.0],
+            ],
+            premultiply: 0.0,
+            _padding: [0.0; 3],
+        };
         let tex_transforms = create_buffer_with_data(
+            device,
+            bytemuck::cast_slice(&[identity_transforms]),
+            wgpu::BufferUsages::UNIFORM,
+            create_debug_label!("Quad tex transforms"),
+        );
+        let straight_tex_transforms = create_buffer_with_data(
             device,
             bytemuck::cast_slice(&[TextureTransforms {
-                u_matrix: [
-                    [1.0, 0.0, 0.0, 0.0],
-                    [0.0, 1.0, 0.0, 0.0],
-                    [0.0, 0.0, 1.0, 0.0],
-                    [0.0, 0.0, 0.0, 1.0],
-                ],
+                premultiply: TextureTransforms::premultiply_mode(false, false),
+                ..identity_transforms
             }]),
             wgpu::BufferUsages::UNIFORM,
-            create_debug_label!("Quad tex transforms"),
+            create_debug_label!("Quad straight alpha tex transforms"),
+        );
+        let smooth_straight_tex_transforms = create_buffer_with_data(
+            device,
+            bytemuck::cast_slice(&[TextureTransforms {
+                premultiply: TextureTransforms::premultiply_mode(false, true),
+                ..identity_transforms
+            }]),
+            wgpu::BufferUsages::UNIFORM,
+            create_debug_label!("Quad smooth straight alpha tex transforms"),
         );
 
         Self {
@@ -330,6 +555,8 @@ impl Quad {
             vertices_pos_color: vbo_pos_color,
             indices: ibo,
             texture_transforms: tex_transforms,
+            straight_texture_transforms: straight_tex_transforms,
+            smooth_straight_texture_transforms: smooth_straight_tex_transforms,
         }
     }
 }
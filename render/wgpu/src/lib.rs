@@ -1,14 +1,16 @@
 // This is a new lint with false positives, see https://github.com/rust-lang/rust-clippy/issues/10318
 #![allow(clippy::extra_unused_type_parameters)]
 
+use crate::atlas::AtlasEntry;
 use crate::bitmaps::BitmapSamplers;
-use crate::descriptors::Quad;
 use crate::mesh::BitmapBinds;
 use crate::pipelines::Pipelines;
+pub use crate::surface::RenderStatistics;
 use crate::target::{RenderTarget, SwapChainTarget};
 use crate::uniform_buffer::UniformBuffer;
 use crate::utils::{
-    buffer_to_image, create_buffer_with_data, format_list, get_backend_names, BufferDimensions,
+    base_level_view, buffer_to_image, create_buffer_with_data, format_list, get_backend_names,
+    BufferDimensions, BufferReadback,
 };
 use bytemuck::{Pod, Zeroable};
 use descriptors::Descriptors;
@@ -16,7 +18,9 @@ use enum_map::Enum;
 use once_cell::sync::OnceCell;
 use ruffle_render::bitmap::{Bitmap, BitmapHandle, BitmapHandleImpl, SyncHandle};
 use ruffle_render::color_transform::ColorTransform;
-use ruffle_render::tessellator::{Gradient as TessGradient, GradientType, Vertex as TessVertex};
+use ruffle_render::tessellator::{
+    Gradient as TessGradient, MorphVertex as TessMorphVertex, Vertex as TessVertex,
+};
 use std::cell::Cell;
 use std::sync::Arc;
 pub use wgpu;
@@ -26,6 +30,7 @@ type Error = Box<dyn std::error::Error>;
 #[macro_use]
 mod utils;
 
+mod atlas;
 mod bitmaps;
 mod context3d;
 mod globals;
@@ -35,6 +40,7 @@ mod uniform_buffer;
 
 pub mod backend;
 mod blend;
+mod buffer_arena;
 mod buffer_builder;
 mod buffer_pool;
 #[cfg(feature = "clap")]
@@ -51,6 +57,12 @@ pub fn as_texture(handle: &BitmapHandle) -> &Texture {
     <dyn BitmapHandleImpl>::downcast_ref(&*handle.0).unwrap()
 }
 
+/// How deeply masks can be nested within the 8-bit stencil buffer.
+///
+/// Masks nested any deeper than this are ignored, so that their contents are only masked
+/// by the masks around them, instead of the stencil reference wrapping around.
+pub const MAX_MASK_DEPTH: u32 = u8::MAX as u32;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
 pub enum MaskState {
     NoMask,
@@ -59,6 +71,19 @@ pub enum MaskState {
     ClearMaskStencil,
 }
 
+impl MaskState {
+    /// The stencil reference to draw with in this state, with `num_masks` masks pushed.
+    pub fn stencil_reference(self, num_masks: u32) -> u32 {
+        match self {
+            MaskState::NoMask => 0,
+            MaskState::DrawMaskStencil => num_masks.saturating_sub(1).min(MAX_MASK_DEPTH),
+            MaskState::DrawMaskedContent | MaskState::ClearMaskStencil => {
+                num_masks.min(MAX_MASK_DEPTH)
+            }
+        }
+    }
+}
+
 #[repr(C)]
 #[derive(Copy, Clone, Debug, Pod, Zeroable)]
 pub struct PushConstants {
@@ -76,6 +101,33 @@ pub struct Transforms {
 #[derive(Copy, Clone, Debug, Pod, Zeroable)]
 struct TextureTransforms {
     u_matrix: [[f32; 4]; 4],
+    /// How a texture with straight alpha is premultiplied as it's sampled: 0 if it's already
+    /// premultiplied, 1 to premultiply the nearest texel, or 2 to premultiply the texels that
+    /// are filtered together before they're blended.
+    premultiply: f32,
+    _padding: [f32; 3],
+}
+
+impl TextureTransforms {
+    fn premultiply_mode(premultiplied: bool, smoothed: bool) -> f32 {
+        match (premultiplied, smoothed) {
+            (true, _) => 0.0,
+            (false, false) => 1.0,
+            (false, true) => 2.0,
+        }
+    }
+}
+
+/// Per-instance data used when drawing many copies of the same bitmap or solid color shape,
+/// or many bitmaps in the atlas, at once.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+struct DrawInstance {
+    world_matrix: [[f32; 4]; 4],
+    colors: ColorAdjustments,
+    /// The offset and size of the part of the texture that a bitmap is drawn from.
+    /// Only bitmaps that are drawn out of the atlas use anything but all of it.
+    uv_rect: [f32; 4],
 }
 
 #[repr(C)]
@@ -83,11 +135,16 @@ struct TextureTransforms {
 pub struct ColorAdjustments {
     mult_color: [f32; 4],
     add_color: [f32; 4],
+    /// How far colors are mixed towards their luminance, before they're multiplied.
+    luminance_mix: f32,
+    _padding: [f32; 3],
 }
 
 pub const DEFAULT_COLOR_ADJUSTMENTS: ColorAdjustments = ColorAdjustments {
     mult_color: [1.0, 1.0, 1.0, 1.0],
     add_color: [0.0, 0.0, 0.0, 0.0],
+    luminance_mix: 0.0,
+    _padding: [0.0; 3],
 };
 
 impl From<ColorTransform> for ColorAdjustments {
@@ -95,9 +152,13 @@ impl From<ColorTransform> for ColorAdjustments {
         if transform == ColorTransform::IDENTITY {
             DEFAULT_COLOR_ADJUSTMENTS
         } else {
+            // The 8.8 fixed point multipliers are exact as floats, which the shaders
+            // rely on to apply them with the same rounding as Flash.
             Self {
                 mult_color: transform.mult_rgba_normalized(),
                 add_color: transform.add_rgba_normalized(),
+                luminance_mix: transform.luminance_mix.to_f32(),
+                _padding: [0.0; 3],
             }
         }
     }
@@ -117,6 +178,14 @@ impl From<TessVertex> for PosVertex {
     }
 }
 
+/// A vertex with its own position on the bitmap that it's filled with.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+struct PosUvVertex {
+    position: [f32; 2],
+    uv: [f32; 2],
+}
+
 #[repr(C)]
 #[derive(Copy, Clone, Debug, Pod, Zeroable)]
 struct PosColorVertex {
@@ -138,38 +207,42 @@ impl From<TessVertex> for PosColorVertex {
     }
 }
 
+/// A vertex of a morph shape, which the vertex shader interpolates between its start and end.
 #[repr(C)]
 #[derive(Copy, Clone, Debug, Pod, Zeroable)]
-struct GradientUniforms {
-    colors: [[f32; 4]; 16],
-    ratios: [f32; 16],
-    gradient_type: i32,
-    num_colors: u32,
-    interpolation: i32,
-    focal_point: f32,
+struct MorphVertex {
+    start_position: [f32; 2],
+    end_position: [f32; 2],
+    start_color: [f32; 4],
+    end_color: [f32; 4],
 }
 
-impl From<TessGradient> for GradientUniforms {
-    fn from(gradient: TessGradient) -> Self {
-        let mut ratios = [0.0; 16];
-        let mut colors = [[0.0; 4]; 16];
-
-        for i in 0..gradient.num_colors {
-            ratios[i] = gradient.ratios[i];
-            colors[i].copy_from_slice(&gradient.colors[i]);
+impl From<TessMorphVertex> for MorphVertex {
+    fn from(vertex: TessMorphVertex) -> Self {
+        let start = PosColorVertex::from(vertex.start);
+        let end = PosColorVertex::from(vertex.end);
+        Self {
+            start_position: start.position,
+            end_position: end.position,
+            start_color: start.color,
+            end_color: end.color,
         }
+    }
+}
+
+/// The parts of a gradient that aren't baked into its color ramp.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+struct GradientUniforms {
+    focal_point: f32,
+    _padding: [f32; 3],
+}
 
+impl From<&TessGradient> for GradientUniforms {
+    fn from(gradient: &TessGradient) -> Self {
         Self {
-            colors,
-            ratios,
-            gradient_type: match gradient.gradient_type {
-                GradientType::Linear => 0,
-                GradientType::Radial => 1,
-                GradientType::Focal => 2,
-            },
-            num_colors: gradient.num_colors as u32,
-            interpolation: (gradient.interpolation == swf::GradientInterpolation::LinearRgb) as i32,
             focal_point: gradient.focal_point.to_f32(),
+            _padding: Default::default(),
         }
     }
 }
@@ -178,7 +251,7 @@ impl From<TessGradient> for GradientUniforms {
 pub enum QueueSyncHandle {
     AlreadyCopied {
         index: wgpu::SubmissionIndex,
-        buffer: Arc<wgpu::Buffer>,
+        readback: BufferReadback,
         buffer_dimensions: BufferDimensions,
         size: wgpu::Extent3d,
         descriptors: Arc<Descriptors>,
@@ -209,13 +282,12 @@ impl QueueSyncHandle {
         match self {
             QueueSyncHandle::AlreadyCopied {
                 index,
-                buffer,
+                readback,
                 buffer_dimensions,
                 size,
                 descriptors,
-            } => buffer_to_image(
+            } => readback.into_image(
                 &descriptors.device,
-                &buffer,
                 &buffer_dimensions,
                 Some(index),
                 size,
@@ -247,7 +319,7 @@ impl QueueSyncHandle {
                         });
                 encoder.copy_texture_to_buffer(
                     wgpu::ImageCopyTexture {
-                        texture: &texture.texture,
+                        texture: texture.texture(&descriptors),
                         mip_level: 0,
                         origin: wgpu::Origin3d::ZERO,
                         aspect: wgpu::TextureAspect::All,
@@ -291,38 +363,103 @@ impl QueueSyncHandle {
 
 #[derive(Debug)]
 pub struct Texture {
-    texture: Arc<wgpu::Texture>,
+    texture: TextureStorage,
+    /// The number of mipmap levels of the texture, including the full size one.
+    mip_level_count: u32,
+    /// Whether the smaller mipmap levels still match the full size one.
+    /// They're only generated when pixels are uploaded, so rendering to the texture outdates them.
+    mipmaps_valid: Cell<bool>,
     bind_linear: OnceCell<BitmapBinds>,
+    bind_mipmapped: OnceCell<BitmapBinds>,
+    bind_anisotropic: OnceCell<BitmapBinds>,
     bind_nearest: OnceCell<BitmapBinds>,
     texture_offscreen: OnceCell<TextureOffscreen>,
     copy_count: Cell<u8>,
     width: u32,
     height: u32,
+    /// Whether the colors of the texture are multiplied by its alpha.
+    ///
+    /// Only bitmaps that are registered with straight alpha aren't, and they're premultiplied
+    /// as they're drawn instead.
+    premultiplied: bool,
+    /// The format that the texture's pixels are kept in. Only `Rgba8Unorm` textures can be
+    /// rendered to, while bitmaps with 16 bits per channel are only ever sampled.
+    format: wgpu::TextureFormat,
+}
+
+/// Where the pixels of a bitmap are kept.
+#[derive(Debug)]
+enum TextureStorage {
+    /// A texture of the bitmap's own.
+    Own(Arc<wgpu::Texture>),
+    /// A place in the atlas, so that runs of different small bitmaps can be drawn together.
+    /// The bitmap only gets a texture of its own once it's evicted.
+    Atlas(Arc<AtlasEntry>),
 }
 
 impl Texture {
+    /// Where the bitmap is in the atlas, unless it was never packed into it or was evicted.
+    pub fn atlas_entry(&self) -> Option<&AtlasEntry> {
+        match &self.texture {
+            TextureStorage::Atlas(entry) if !entry.is_evicted() => Some(entry),
+            _ => None,
+        }
+    }
+
+    /// The bitmap's own texture, for anything but drawing it out of the atlas.
+    /// A bitmap that's still in the atlas is evicted from it first.
+    pub fn texture(&self, descriptors: &Descriptors) -> &Arc<wgpu::Texture> {
+        match &self.texture {
+            TextureStorage::Own(texture) => texture,
+            TextureStorage::Atlas(entry) => descriptors.bitmap_atlas().evict(descriptors, entry),
+        }
+    }
+
+    /// Gets the bind group to draw this texture with. Anisotropic filtering only applies to
+    /// smoothed bitmaps, and only makes a difference when they're sampled from their mipmaps.
     pub fn bind_group(
         &self,
         smoothed: bool,
-        device: &wgpu::Device,
-        layout: &wgpu::BindGroupLayout,
-        quad: &Quad,
+        anisotropic: bool,
+        descriptors: &Descriptors,
         handle: BitmapHandle,
-        samplers: &BitmapSamplers,
     ) -> &BitmapBinds {
-        let bind = match smoothed {
-            true => &self.bind_linear,
-            false => &self.bind_nearest,
+        // Downscaled smooth bitmaps are sampled from the mipmaps, as long as they're up to date
+        let mipmapped = smoothed && self.mip_level_count > 1 && self.mipmaps_valid.get();
+        let anisotropic = anisotropic && mipmapped;
+        let bind = match (smoothed, mipmapped, anisotropic) {
+            (true, true, true) => &self.bind_anisotropic,
+            (true, true, false) => &self.bind_mipmapped,
+            (true, false, _) => &self.bind_linear,
+            (false, _, _) => &self.bind_nearest,
         };
         bind.get_or_init(|| {
             BitmapBinds::new(
-                device,
-                layout,
-                samplers.get_sampler(false, smoothed),
-                &quad.texture_transforms,
+                &descriptors.device,
+                &descriptors.bind_layouts.bitmap,
+                if anisotropic {
+                    descriptors.bitmap_samplers.get_anisotropic_sampler(false)
+                } else {
+                    descriptors.bitmap_samplers.get_sampler(false, smoothed)
+                },
+                match (self.premultiplied, smoothed) {
+                    (true, _) => &descriptors.quad.texture_transforms,
+                    (false, false) => &descriptors.quad.straight_texture_transforms,
+                    (false, true) => &descriptors.quad.smooth_straight_texture_transforms,
+                },
                 0 as wgpu::BufferAddress,
-                self.texture.create_view(&Default::default()),
-                create_debug_label!("Bitmap {:?} bind group (smoothed: {})", handle.0, smoothed),
+                if mipmapped {
+                    self.texture(descriptors).create_view(&Default::default())
+                } else {
+                    base_level_view(self.texture(descriptors))
+                },
+                create_debug_label!(
+                    "Bitmap {:?} bind group (smoothed: {}, mipmapped: {}, anisotropic: {})",
+                    handle.0,
+                    smoothed,
+                    mipmapped,
+                    anisotropic
+                ),
             )
         })
     }
@@ -1,23 +1,31 @@
 use crate::backend::WgpuRenderBackend;
+use crate::buffer_arena::ArenaAllocation;
 use crate::target::RenderTarget;
+use crate::utils::base_level_view;
 use crate::{
-    as_texture, Descriptors, GradientUniforms, PosColorVertex, PosVertex, TextureTransforms,
+    as_texture, Descriptors, GradientUniforms, PosColorVertex, PosUvVertex, PosVertex, Texture,
+    TextureTransforms,
 };
 use std::ops::Range;
+use std::sync::Arc;
 
 use crate::buffer_builder::BufferBuilder;
 use ruffle_render::backend::RenderBackend;
 use ruffle_render::bitmap::BitmapSource;
+use ruffle_render::bounding_box::BoundingBox;
 use ruffle_render::tessellator::{
-    Bitmap, Draw as LyonDraw, DrawType as TessDrawType, Gradient, GradientType,
+    Bitmap, Draw as LyonDraw, DrawType as TessDrawType, Gradient, GradientType, TexturedTriangles,
 };
 use swf::{CharacterId, GradientSpread};
 
 #[derive(Debug)]
 pub struct Mesh {
     pub draws: Vec<Draw>,
-    pub vertex_buffer: wgpu::Buffer,
-    pub index_buffer: wgpu::Buffer,
+    pub vertex_buffer: ArenaAllocation,
+    pub index_buffer: ArenaAllocation,
+    /// The area that the mesh draws within before it's transformed, which is invalid if
+    /// that isn't known.
+    pub bounds: BoundingBox,
 }
 
 #[derive(Debug)]
@@ -69,6 +77,17 @@ impl PendingDraw {
                 .map(PosColorVertex::from)
                 .collect();
             vertex_buffer.add(&vertices)
+        } else if let TessDrawType::TexturedTriangles(textured) = &draw.draw_type {
+            let vertices: Vec<_> = draw
+                .vertices
+                .into_iter()
+                .zip(&textured.uvs)
+                .map(|(vertex, uv)| PosUvVertex {
+                    position: [vertex.x, vertex.y],
+                    uv: *uv,
+                })
+                .collect();
+            vertex_buffer.add(&vertices)
         } else {
             let vertices: Vec<_> = draw.vertices.into_iter().map(PosVertex::from).collect();
             vertex_buffer.add(&vertices)
@@ -79,11 +98,36 @@ impl PendingDraw {
         let index_count = draw.indices.len() as u32;
         let draw_type = match draw.draw_type {
             TessDrawType::Color => PendingDrawType::color(),
-            TessDrawType::Gradient(gradient) => {
-                PendingDrawType::gradient(gradient, shape_id, draw_id, uniform_buffer)
-            }
+            TessDrawType::Gradient(gradient) => PendingDrawType::gradient(
+                gradient,
+                shape_id,
+                draw_id,
+                backend.descriptors(),
+                uniform_buffer,
+            ),
             TessDrawType::Bitmap(bitmap) => {
-                PendingDrawType::bitmap(bitmap, shape_id, draw_id, source, backend, uniform_buffer)?
+                let descriptors = backend.descriptors().clone();
+                PendingDrawType::bitmap(
+                    bitmap,
+                    shape_id,
+                    draw_id,
+                    source,
+                    backend,
+                    &descriptors,
+                    uniform_buffer,
+                )?
+            }
+            TessDrawType::TexturedTriangles(textured) => {
+                let descriptors = backend.descriptors().clone();
+                PendingDrawType::textured_triangles(
+                    textured,
+                    shape_id,
+                    draw_id,
+                    source,
+                    backend,
+                    &descriptors,
+                    uniform_buffer,
+                )?
             }
         };
         Some(PendingDraw {
@@ -103,16 +147,16 @@ pub enum PendingDrawType {
     Gradient {
         texture_transforms_index: wgpu::BufferAddress,
         gradient: wgpu::BufferAddress,
+        ramp: Arc<wgpu::TextureView>,
         spread: GradientSpread,
         mode: GradientType,
         bind_group_label: Option<String>,
     },
     Bitmap {
-        texture_transforms_index: wgpu::BufferAddress,
-        texture_view: wgpu::TextureView,
-        is_repeating: bool,
-        is_smoothed: bool,
-        bind_group_label: Option<String>,
+        binds: PendingBitmapBinds,
+    },
+    TexturedTriangles {
+        binds: PendingBitmapBinds,
     },
 }
 
@@ -125,15 +169,18 @@ impl PendingDrawType {
         gradient: Gradient,
         shape_id: CharacterId,
         draw_id: usize,
+        descriptors: &Descriptors,
         uniform_buffers: &mut BufferBuilder,
     ) -> Self {
-        let tex_transforms_index = create_texture_transforms(&gradient.matrix, uniform_buffers);
+        let tex_transforms_index =
+            create_texture_transforms(&gradient.matrix, 0.0, uniform_buffers);
 
         let spread = gradient.repeat_mode;
         let mode = gradient.gradient_type;
+        let ramp = descriptors.gradient_ramp(&gradient);
 
         let gradient = uniform_buffers
-            .add(&[GradientUniforms::from(gradient)])
+            .add(&[GradientUniforms::from(&gradient)])
             .start;
 
         let bind_group_label =
@@ -141,6 +188,7 @@ impl PendingDrawType {
         PendingDrawType::Gradient {
             texture_transforms_index: tex_transforms_index,
             gradient,
+            ramp,
             spread,
             mode,
             bind_group_label,
@@ -153,21 +201,53 @@ impl PendingDrawType {
         draw_id: usize,
         source: &dyn BitmapSource,
         backend: &mut dyn RenderBackend,
+        descriptors: &Descriptors,
         uniform_buffers: &mut BufferBuilder,
     ) -> Option<Self> {
         let handle = source.bitmap_handle(bitmap.bitmap_id, backend)?;
-        let texture = as_texture(&handle);
-        let texture_view = texture.texture.create_view(&Default::default());
-        let texture_transforms_index = create_texture_transforms(&bitmap.matrix, uniform_buffers);
         let bind_group_label =
             create_debug_label!("Shape {} (bitmap) draw {} bindgroup", shape_id, draw_id);
 
         Some(PendingDrawType::Bitmap {
-            texture_transforms_index,
-            texture_view,
-            is_repeating: bitmap.is_repeating,
-            is_smoothed: bitmap.is_smoothed,
-            bind_group_label,
+            binds: PendingBitmapBinds::new(
+                as_texture(&handle),
+                &bitmap.matrix,
+                bitmap.is_repeating,
+                bitmap.is_smoothed,
+                descriptors,
+                uniform_buffers,
+                bind_group_label,
+            ),
+        })
+    }
+
+    pub fn textured_triangles(
+        textured: TexturedTriangles,
+        shape_id: CharacterId,
+        draw_id: usize,
+        source: &dyn BitmapSource,
+        backend: &mut dyn RenderBackend,
+        descriptors: &Descriptors,
+        uniform_buffers: &mut BufferBuilder,
+    ) -> Option<Self> {
+        let handle = source.bitmap_handle(textured.bitmap_id, backend)?;
+        let bind_group_label = create_debug_label!(
+            "Shape {} (textured triangles) draw {} bindgroup",
+            shape_id,
+            draw_id
+        );
+
+        // The bitmap bind group always has texture transforms, even though these don't use any
+        Some(PendingDrawType::TexturedTriangles {
+            binds: PendingBitmapBinds::new(
+                as_texture(&handle),
+                &[[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+                textured.is_repeating,
+                textured.is_smoothed,
+                descriptors,
+                uniform_buffers,
+                bind_group_label,
+            ),
         })
     }
 
@@ -177,6 +257,7 @@ impl PendingDrawType {
             PendingDrawType::Gradient {
                 texture_transforms_index,
                 gradient,
+                ramp,
                 spread,
                 mode,
                 bind_group_label,
@@ -206,36 +287,32 @@ impl PendingDrawType {
                                     ),
                                 }),
                             },
+                            wgpu::BindGroupEntry {
+                                binding: 2,
+                                resource: wgpu::BindingResource::TextureView(&ramp),
+                            },
+                            wgpu::BindGroupEntry {
+                                binding: 3,
+                                resource: wgpu::BindingResource::Sampler(
+                                    descriptors.bitmap_samplers.get_sampler(false, true),
+                                ),
+                            },
                         ],
                         label: bind_group_label.as_deref(),
                     });
                 DrawType::Gradient {
                     bind_group,
+                    _ramp: ramp,
                     spread,
                     mode,
                 }
             }
-            PendingDrawType::Bitmap {
-                texture_transforms_index,
-                texture_view,
-                is_repeating,
-                is_smoothed,
-                bind_group_label,
-            } => {
-                let binds = BitmapBinds::new(
-                    &descriptors.device,
-                    &descriptors.bind_layouts.bitmap,
-                    descriptors
-                        .bitmap_samplers
-                        .get_sampler(is_repeating, is_smoothed),
-                    uniform_buffer,
-                    texture_transforms_index,
-                    texture_view,
-                    bind_group_label,
-                );
-
-                DrawType::Bitmap { binds }
-            }
+            PendingDrawType::Bitmap { binds } => DrawType::Bitmap {
+                binds: binds.finish(descriptors, uniform_buffer),
+            },
+            PendingDrawType::TexturedTriangles { binds } => DrawType::TexturedTriangles {
+                binds: binds.finish(descriptors, uniform_buffer),
+            },
         }
     }
 }
@@ -244,16 +321,116 @@ impl PendingDrawType {
 #[derive(Debug)]
 pub enum DrawType {
     Color,
+    /// A solid color morph shape, whose vertices hold both its start and end shape.
+    Morph,
     Gradient {
         bind_group: wgpu::BindGroup,
+        /// Kept alive so that other gradients with the same colors can share it.
+        _ramp: Arc<wgpu::TextureView>,
         spread: GradientSpread,
         mode: GradientType,
     },
     Bitmap {
-        binds: BitmapBinds,
+        binds: BitmapFillBinds,
+    },
+    /// Triangles with their own texture coordinates, see `PosUvVertex`.
+    TexturedTriangles {
+        binds: BitmapFillBinds,
     },
 }
 
+/// What the bind groups of a bitmap fill are created from, once the uniform buffer exists.
+#[derive(Debug)]
+pub struct PendingBitmapBinds {
+    texture_transforms_index: wgpu::BufferAddress,
+    texture_view: wgpu::TextureView,
+    is_repeating: bool,
+    is_smoothed: bool,
+    /// The texture transforms and view to draw a smoothed fill with when it can't be smoothed.
+    nearest: Option<(wgpu::BufferAddress, wgpu::TextureView)>,
+    bind_group_label: Option<String>,
+}
+
+impl PendingBitmapBinds {
+    fn new(
+        texture: &Texture,
+        matrix: &[[f32; 3]; 3],
+        is_repeating: bool,
+        is_smoothed: bool,
+        descriptors: &Descriptors,
+        uniform_buffers: &mut BufferBuilder,
+        bind_group_label: Option<String>,
+    ) -> Self {
+        // Bitmap fills can repeat, so they're drawn from the bitmap's own texture
+        let mut binds = |is_smoothed| {
+            let texture_transforms_index = create_texture_transforms(
+                matrix,
+                TextureTransforms::premultiply_mode(texture.premultiplied, is_smoothed),
+                uniform_buffers,
+            );
+            (
+                texture_transforms_index,
+                base_level_view(texture.texture(descriptors)),
+            )
+        };
+        let (texture_transforms_index, texture_view) = binds(is_smoothed);
+        let nearest = is_smoothed.then(|| binds(false));
+        Self {
+            texture_transforms_index,
+            texture_view,
+            is_repeating,
+            is_smoothed,
+            nearest,
+            bind_group_label,
+        }
+    }
+
+    fn finish(self, descriptors: &Descriptors, uniform_buffer: &wgpu::Buffer) -> BitmapFillBinds {
+        let samplers = &descriptors.bitmap_samplers;
+        let nearest = self
+            .nearest
+            .map(|(texture_transforms_index, texture_view)| {
+                BitmapBinds::new(
+                    &descriptors.device,
+                    &descriptors.bind_layouts.bitmap,
+                    samplers.get_sampler(self.is_repeating, false),
+                    uniform_buffer,
+                    texture_transforms_index,
+                    texture_view,
+                    self.bind_group_label.clone(),
+                )
+            });
+        let binds = BitmapBinds::new(
+            &descriptors.device,
+            &descriptors.bind_layouts.bitmap,
+            samplers.get_sampler(self.is_repeating, self.is_smoothed),
+            uniform_buffer,
+            self.texture_transforms_index,
+            self.texture_view,
+            self.bind_group_label,
+        );
+        BitmapFillBinds { binds, nearest }
+    }
+}
+
+/// The bind groups of a bitmap fill of a shape.
+#[derive(Debug)]
+pub struct BitmapFillBinds {
+    binds: BitmapBinds,
+    /// The same fill without smoothing, if `binds` smooths it.
+    nearest: Option<BitmapBinds>,
+}
+
+impl BitmapFillBinds {
+    /// The bind group to draw the fill with, which only smooths it if `allow_smoothing` is set.
+    pub fn bind_group(&self, allow_smoothing: bool) -> &wgpu::BindGroup {
+        match &self.nearest {
+            Some(nearest) if !allow_smoothing => &nearest.bind_group,
+            _ => &self.binds.bind_group,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct BitmapBinds {
     pub bind_group: wgpu::BindGroup,
@@ -300,11 +477,18 @@ impl BitmapBinds {
 
 fn create_texture_transforms(
     matrix: &[[f32; 3]; 3],
+    premultiply: f32,
     buffer: &mut BufferBuilder,
 ) -> wgpu::BufferAddress {
     let mut texture_transform = [[0.0; 4]; 4];
     texture_transform[0][..3].copy_from_slice(&matrix[0]);
     texture_transform[1][..3].copy_from_slice(&matrix[1]);
     texture_transform[2][..3].copy_from_slice(&matrix[2]);
-    buffer.add(&[texture_transform]).start
+    buffer
+        .add(&[TextureTransforms {
+            u_matrix: texture_transform,
+            premultiply,
+            _padding: [0.0; 3],
+        }])
+        .start
 }
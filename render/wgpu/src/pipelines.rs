@@ -1,7 +1,10 @@
 use crate::blend::{ComplexBlend, TrivialBlend};
 use crate::layouts::BindLayouts;
 use crate::shaders::Shaders;
-use crate::{MaskState, PosColorVertex, PosVertex, PushConstants, Transforms};
+use crate::{
+    ColorAdjustments, DrawInstance, MaskState, MorphVertex, PosColorVertex, PosUvVertex, PosVertex,
+    PushConstants, Transforms,
+};
 use enum_map::{enum_map, Enum, EnumMap};
 use ruffle_render::tessellator::GradientType;
 use std::mem;
@@ -27,6 +30,98 @@ pub const VERTEX_BUFFERS_DESCRIPTION_COLOR: [wgpu::VertexBufferLayout; 1] =
         ],
     }];
 
+pub const VERTEX_BUFFERS_DESCRIPTION_POS_UV: [wgpu::VertexBufferLayout; 1] =
+    [wgpu::VertexBufferLayout {
+        array_stride: std::mem::size_of::<PosUvVertex>() as u64,
+        step_mode: wgpu::VertexStepMode::Vertex,
+        attributes: &vertex_attr_array![
+            0 => Float32x2,
+            1 => Float32x2,
+        ],
+    }];
+
+/// Morph shape vertices, followed by the ratio of the morph.
+/// The ratio is bound as a single instance out of `Descriptors::morph_ratios`.
+pub const VERTEX_BUFFERS_DESCRIPTION_MORPH: [wgpu::VertexBufferLayout; 2] = [
+    wgpu::VertexBufferLayout {
+        array_stride: std::mem::size_of::<MorphVertex>() as u64,
+        step_mode: wgpu::VertexStepMode::Vertex,
+        attributes: &vertex_attr_array![
+            0 => Float32x2,
+            1 => Float32x2,
+            2 => Float32x4,
+            3 => Float32x4,
+        ],
+    },
+    wgpu::VertexBufferLayout {
+        array_stride: std::mem::size_of::<f32>() as u64,
+        step_mode: wgpu::VertexStepMode::Instance,
+        attributes: &vertex_attr_array![
+            4 => Float32,
+        ],
+    },
+];
+
+pub const VERTEX_BUFFERS_DESCRIPTION_BITMAP_INSTANCED: [wgpu::VertexBufferLayout; 2] = [
+    wgpu::VertexBufferLayout {
+        array_stride: std::mem::size_of::<PosVertex>() as u64,
+        step_mode: wgpu::VertexStepMode::Vertex,
+        attributes: &vertex_attr_array![
+            0 => Float32x2,
+        ],
+    },
+    wgpu::VertexBufferLayout {
+        array_stride: std::mem::size_of::<DrawInstance>() as u64,
+        step_mode: wgpu::VertexStepMode::Instance,
+        attributes: &[
+            instance_attribute(0, 1),
+            instance_attribute(16, 2),
+            instance_attribute(32, 3),
+            instance_attribute(48, 4),
+            instance_attribute(64, 5),
+            instance_attribute(80, 6),
+            // The instanced pipelines don't mix colors towards their luminance,
+            // so that part of the colors is skipped
+            instance_attribute(
+                mem::size_of::<[[f32; 4]; 4]>() + mem::size_of::<ColorAdjustments>(),
+                7,
+            ),
+        ],
+    },
+];
+
+const fn instance_attribute(offset: usize, shader_location: u32) -> wgpu::VertexAttribute {
+    wgpu::VertexAttribute {
+        format: wgpu::VertexFormat::Float32x4,
+        offset: offset as wgpu::BufferAddress,
+        shader_location,
+    }
+}
+
+/// Solid color vertices, followed by the transform and color of each instance.
+pub const VERTEX_BUFFERS_DESCRIPTION_COLOR_INSTANCED: [wgpu::VertexBufferLayout; 2] = [
+    wgpu::VertexBufferLayout {
+        array_stride: std::mem::size_of::<PosColorVertex>() as u64,
+        step_mode: wgpu::VertexStepMode::Vertex,
+        attributes: &vertex_attr_array![
+            0 => Float32x2,
+            1 => Float32x4,
+        ],
+    },
+    wgpu::VertexBufferLayout {
+        array_stride: std::mem::size_of::<DrawInstance>() as u64,
+        step_mode: wgpu::VertexStepMode::Instance,
+        attributes: &vertex_attr_array![
+            2 => Float32x4,
+            3 => Float32x4,
+            4 => Float32x4,
+            5 => Float32x4,
+            6 => Float32x4,
+            7 => Float32x4,
+        ],
+    },
+];
+
 #[derive(Debug)]
 pub struct ShapePipeline {
     pub pipelines: EnumMap<MaskState, wgpu::RenderPipeline>,
@@ -36,11 +131,20 @@ pub struct ShapePipeline {
 #[derive(Debug)]
 pub struct Pipelines {
     pub color: ShapePipeline,
+    pub color_instanced: ShapePipeline,
+    pub morph: ShapePipeline,
     pub bitmap: EnumMap<TrivialBlend, ShapePipeline>,
+    pub bitmap_instanced: EnumMap<TrivialBlend, ShapePipeline>,
+    pub textured_triangles: ShapePipeline,
     pub gradients: EnumMap<GradientType, EnumMap<GradientSpread, ShapePipeline>>,
     pub complex_blends: EnumMap<ComplexBlend, ShapePipeline>,
     pub color_matrix_filter: wgpu::RenderPipeline,
+    pub convolution_filter: wgpu::RenderPipeline,
     pub blur_filter: wgpu::RenderPipeline,
+    pub displacement_map_filter: wgpu::RenderPipeline,
+    pub drop_shadow_filter: wgpu::RenderPipeline,
+    pub bevel_filter: wgpu::RenderPipeline,
+    pub alpha_mask: wgpu::RenderPipeline,
 }
 
 impl ShapePipeline {
@@ -122,6 +226,30 @@ impl Pipelines {
             full_push_constants,
         );
 
+        let color_instanced_pipelines = create_shape_pipeline(
+            "Instanced Color",
+            device,
+            format,
+            &shaders.color_instanced_shader,
+            msaa_sample_count,
+            &VERTEX_BUFFERS_DESCRIPTION_COLOR_INSTANCED,
+            &[&bind_layouts.globals],
+            wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING,
+            &[],
+        );
+
+        let morph_pipelines = create_shape_pipeline(
+            "Morph",
+            device,
+            format,
+            &shaders.morph_shader,
+            msaa_sample_count,
+            &VERTEX_BUFFERS_DESCRIPTION_MORPH,
+            &colort_bindings,
+            wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING,
+            full_push_constants,
+        );
+
         let gradient_bindings = if device.limits().max_push_constant_size > 0 {
             vec![&bind_layouts.globals, &bind_layouts.gradient]
         } else {
@@ -204,6 +332,34 @@ impl Pipelines {
             .try_into()
             .unwrap();
 
+        let textured_triangles_pipeline = create_shape_pipeline(
+            "Textured Triangles",
+            device,
+            format,
+            &shaders.textured_triangles_shader,
+            msaa_sample_count,
+            &VERTEX_BUFFERS_DESCRIPTION_POS_UV,
+            &bitmap_blend_bindings,
+            TrivialBlend::Normal.blend_state(),
+            full_push_constants,
+        );
+
+        // Instanced bitmaps carry their transforms in a vertex buffer,
+        // so they don't need push constants or per-draw uniforms.
+        let bitmap_instanced_pipelines = enum_map! {
+            blend => create_shape_pipeline(
+                &format!("Instanced Bitmap ({blend:?})"),
+                device,
+                format,
+                &shaders.bitmap_instanced_shader,
+                msaa_sample_count,
+                &VERTEX_BUFFERS_DESCRIPTION_BITMAP_INSTANCED,
+                &[&bind_layouts.globals, &bind_layouts.bitmap],
+                blend.blend_state(),
+                &[],
+            )
+        };
+
         let color_matrix_filter_bindings = if device.limits().max_push_constant_size > 0 {
             vec![
                 &bind_layouts.globals,
@@ -311,13 +467,291 @@ impl Pipelines {
             multiview: None,
         });
 
+        let convolution_filter_bindings = if device.limits().max_push_constant_size > 0 {
+            vec![
+                &bind_layouts.globals,
+                &bind_layouts.bitmap,
+                &bind_layouts.convolution_filter,
+            ]
+        } else {
+            vec![
+                &bind_layouts.globals,
+                &bind_layouts.transforms,
+                &bind_layouts.color_transforms,
+                &bind_layouts.bitmap,
+                &bind_layouts.convolution_filter,
+            ]
+        };
+
+        let convolution_filter_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: None,
+                bind_group_layouts: &convolution_filter_bindings,
+                push_constant_ranges: full_push_constants,
+            });
+
+        let convolution_filter = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: create_debug_label!("Convolution Filter").as_deref(),
+            layout: Some(&convolution_filter_layout),
+            vertex: wgpu::VertexState {
+                module: &shaders.convolution_filter,
+                entry_point: "main_vertex",
+                buffers: &VERTEX_BUFFERS_DESCRIPTION_POS,
+            },
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::default(),
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: msaa_sample_count,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shaders.convolution_filter,
+                entry_point: "main_fragment",
+                targets: &[Some(format.into())],
+            }),
+            multiview: None,
+        });
+
+        let displacement_map_filter_bindings = if device.limits().max_push_constant_size > 0 {
+            vec![
+                &bind_layouts.globals,
+                &bind_layouts.bitmap,
+                &bind_layouts.displacement_map_filter,
+            ]
+        } else {
+            vec![
+                &bind_layouts.globals,
+                &bind_layouts.transforms,
+                &bind_layouts.color_transforms,
+                &bind_layouts.bitmap,
+                &bind_layouts.displacement_map_filter,
+            ]
+        };
+
+        let displacement_map_filter_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: None,
+                bind_group_layouts: &displacement_map_filter_bindings,
+                push_constant_ranges: full_push_constants,
+            });
+
+        let displacement_map_filter =
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: create_debug_label!("Displacement Map Filter").as_deref(),
+                layout: Some(&displacement_map_filter_layout),
+                vertex: wgpu::VertexState {
+                    module: &shaders.displacement_map_filter,
+                    entry_point: "main_vertex",
+                    buffers: &VERTEX_BUFFERS_DESCRIPTION_POS,
+                },
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    strip_index_format: None,
+                    front_face: wgpu::FrontFace::Ccw,
+                    cull_mode: None,
+                    polygon_mode: wgpu::PolygonMode::default(),
+                    unclipped_depth: false,
+                    conservative: false,
+                },
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState {
+                    count: msaa_sample_count,
+                    mask: !0,
+                    alpha_to_coverage_enabled: false,
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shaders.displacement_map_filter,
+                    entry_point: "main_fragment",
+                    targets: &[Some(format.into())],
+                }),
+                multiview: None,
+            });
+
+        let drop_shadow_filter_bindings = if device.limits().max_push_constant_size > 0 {
+            vec![
+                &bind_layouts.globals,
+                &bind_layouts.bitmap,
+                &bind_layouts.drop_shadow_filter,
+            ]
+        } else {
+            vec![
+                &bind_layouts.globals,
+                &bind_layouts.transforms,
+                &bind_layouts.color_transforms,
+                &bind_layouts.bitmap,
+                &bind_layouts.drop_shadow_filter,
+            ]
+        };
+
+        let drop_shadow_filter_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: None,
+                bind_group_layouts: &drop_shadow_filter_bindings,
+                push_constant_ranges: full_push_constants,
+            });
+
+        let drop_shadow_filter = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: create_debug_label!("Drop Shadow Filter").as_deref(),
+            layout: Some(&drop_shadow_filter_layout),
+            vertex: wgpu::VertexState {
+                module: &shaders.drop_shadow_filter,
+                entry_point: "main_vertex",
+                buffers: &VERTEX_BUFFERS_DESCRIPTION_POS,
+            },
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::default(),
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: msaa_sample_count,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shaders.drop_shadow_filter,
+                entry_point: "main_fragment",
+                targets: &[Some(format.into())],
+            }),
+            multiview: None,
+        });
+
+        let bevel_filter_bindings = if device.limits().max_push_constant_size > 0 {
+            vec![
+                &bind_layouts.globals,
+                &bind_layouts.bitmap,
+                &bind_layouts.bevel_filter,
+            ]
+        } else {
+            vec![
+                &bind_layouts.globals,
+                &bind_layouts.transforms,
+                &bind_layouts.color_transforms,
+                &bind_layouts.bitmap,
+                &bind_layouts.bevel_filter,
+            ]
+        };
+
+        let bevel_filter_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: None,
+            bind_group_layouts: &bevel_filter_bindings,
+            push_constant_ranges: full_push_constants,
+        });
+
+        let bevel_filter = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: create_debug_label!("Bevel Filter").as_deref(),
+            layout: Some(&bevel_filter_layout),
+            vertex: wgpu::VertexState {
+                module: &shaders.bevel_filter,
+                entry_point: "main_vertex",
+                buffers: &VERTEX_BUFFERS_DESCRIPTION_POS,
+            },
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::default(),
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: msaa_sample_count,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shaders.bevel_filter,
+                entry_point: "main_fragment",
+                targets: &[Some(format.into())],
+            }),
+            multiview: None,
+        });
+
+        let alpha_mask_bindings = if device.limits().max_push_constant_size > 0 {
+            vec![
+                &bind_layouts.globals,
+                &bind_layouts.bitmap,
+                &bind_layouts.alpha_mask,
+            ]
+        } else {
+            vec![
+                &bind_layouts.globals,
+                &bind_layouts.transforms,
+                &bind_layouts.color_transforms,
+                &bind_layouts.bitmap,
+                &bind_layouts.alpha_mask,
+            ]
+        };
+
+        let alpha_mask_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: None,
+            bind_group_layouts: &alpha_mask_bindings,
+            push_constant_ranges: full_push_constants,
+        });
+
+        let alpha_mask = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: create_debug_label!("Alpha Mask").as_deref(),
+            layout: Some(&alpha_mask_layout),
+            vertex: wgpu::VertexState {
+                module: &shaders.alpha_mask_shader,
+                entry_point: "main_vertex",
+                buffers: &VERTEX_BUFFERS_DESCRIPTION_POS,
+            },
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::default(),
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: msaa_sample_count,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shaders.alpha_mask_shader,
+                entry_point: "main_fragment",
+                targets: &[Some(format.into())],
+            }),
+            multiview: None,
+        });
+
         Self {
             color: color_pipelines,
+            color_instanced: color_instanced_pipelines,
+            morph: morph_pipelines,
             bitmap: EnumMap::from_array(bitmap_pipelines),
+            bitmap_instanced: bitmap_instanced_pipelines,
+            textured_triangles: textured_triangles_pipeline,
             gradients: gradient_pipelines,
             complex_blends: complex_blend_pipelines,
             color_matrix_filter,
+            convolution_filter,
             blur_filter,
+            displacement_map_filter,
+            drop_shadow_filter,
+            bevel_filter,
+            alpha_mask,
         }
     }
 }
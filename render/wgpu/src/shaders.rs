@@ -11,13 +11,22 @@ use swf::GradientSpread;
 #[derive(Debug)]
 pub struct Shaders {
     pub color_shader: wgpu::ShaderModule,
+    pub color_instanced_shader: wgpu::ShaderModule,
+    pub morph_shader: wgpu::ShaderModule,
     pub bitmap_shader: wgpu::ShaderModule,
+    pub bitmap_instanced_shader: wgpu::ShaderModule,
+    pub textured_triangles_shader: wgpu::ShaderModule,
     pub gradient_shaders: EnumMap<GradientType, EnumMap<GradientSpread, wgpu::ShaderModule>>,
     pub copy_srgb_shader: wgpu::ShaderModule,
     pub copy_shader: wgpu::ShaderModule,
     pub blend_shaders: EnumMap<ComplexBlend, wgpu::ShaderModule>,
     pub color_matrix_filter: wgpu::ShaderModule,
+    pub convolution_filter: wgpu::ShaderModule,
     pub blur_filter: wgpu::ShaderModule,
+    pub displacement_map_filter: wgpu::ShaderModule,
+    pub drop_shadow_filter: wgpu::ShaderModule,
+    pub bevel_filter: wgpu::ShaderModule,
+    pub alpha_mask_shader: wgpu::ShaderModule,
 }
 
 impl Shaders {
@@ -35,6 +44,20 @@ impl Shaders {
             "color.wgsl",
             include_str!("../shaders/color.wgsl"),
         );
+        let color_instanced_shader = make_shader(
+            device,
+            &mut composer,
+            &shader_defs,
+            "color_instanced.wgsl",
+            include_str!("../shaders/color_instanced.wgsl"),
+        );
+        let morph_shader = make_shader(
+            device,
+            &mut composer,
+            &shader_defs,
+            "morph.wgsl",
+            include_str!("../shaders/morph.wgsl"),
+        );
         let bitmap_shader = make_shader(
             device,
             &mut composer,
@@ -42,6 +65,20 @@ impl Shaders {
             "bitmap.wgsl",
             include_str!("../shaders/bitmap.wgsl"),
         );
+        let bitmap_instanced_shader = make_shader(
+            device,
+            &mut composer,
+            &shader_defs,
+            "bitmap_instanced.wgsl",
+            include_str!("../shaders/bitmap_instanced.wgsl"),
+        );
+        let textured_triangles_shader = make_shader(
+            device,
+            &mut composer,
+            &shader_defs,
+            "textured_triangles.wgsl",
+            include_str!("../shaders/textured_triangles.wgsl"),
+        );
         let copy_srgb_shader = make_shader(
             device,
             &mut composer,
@@ -63,6 +100,13 @@ impl Shaders {
             "filter/color_matrix.wgsl",
             include_str!("../shaders/filter/color_matrix.wgsl"),
         );
+        let convolution_filter = make_shader(
+            device,
+            &mut composer,
+            &shader_defs,
+            "filter/convolution.wgsl",
+            include_str!("../shaders/filter/convolution.wgsl"),
+        );
         let blur_filter = make_shader(
             device,
             &mut composer,
@@ -70,16 +114,45 @@ impl Shaders {
             "filter/blur.wgsl",
             include_str!("../shaders/filter/blur.wgsl"),
         );
+        let displacement_map_filter = make_shader(
+            device,
+            &mut composer,
+            &shader_defs,
+            "filter/displacement_map.wgsl",
+            include_str!("../shaders/filter/displacement_map.wgsl"),
+        );
+        let drop_shadow_filter = make_shader(
+            device,
+            &mut composer,
+            &shader_defs,
+            "filter/drop_shadow.wgsl",
+            include_str!("../shaders/filter/drop_shadow.wgsl"),
+        );
+        let bevel_filter = make_shader(
+            device,
+            &mut composer,
+            &shader_defs,
+            "filter/bevel.wgsl",
+            include_str!("../shaders/filter/bevel.wgsl"),
+        );
+        let alpha_mask_shader = make_shader(
+            device,
+            &mut composer,
+            &shader_defs,
+            "alpha_mask.wgsl",
+            include_str!("../shaders/alpha_mask.wgsl"),
+        );
 
         let blend_shaders = enum_map! {
+            ComplexBlend::Multiply => make_shader(device, &mut composer, &shader_defs, "blend/multiply.wgsl", include_str!("../shaders/blend/multiply.wgsl")),
             ComplexBlend::Lighten => make_shader(device, &mut composer, &shader_defs, "blend/lighten.wgsl", include_str!("../shaders/blend/lighten.wgsl")),
             ComplexBlend::Darken => make_shader(device, &mut composer, &shader_defs, "blend/darken.wgsl", include_str!("../shaders/blend/darken.wgsl")),
             ComplexBlend::Difference => make_shader(device, &mut composer, &shader_defs, "blend/difference.wgsl", include_str!("../shaders/blend/difference.wgsl")),
             ComplexBlend::Invert => make_shader(device, &mut composer, &shader_defs, "blend/invert.wgsl", include_str!("../shaders/blend/invert.wgsl")),
             ComplexBlend::Alpha => make_shader(device, &mut composer, &shader_defs, "blend/alpha.wgsl", include_str!("../shaders/blend/alpha.wgsl")),
             ComplexBlend::Erase => make_shader(device, &mut composer, &shader_defs, "blend/erase.wgsl", include_str!("../shaders/blend/erase.wgsl")),
-            ComplexBlend::Overlay => make_shader(device, &mut composer, &shader_defs, "blend/overlay.wgsl", include_str!("../shaders/blend/overlay.wgsl")),
-            ComplexBlend::HardLight => make_shader(device, &mut composer, &shader_defs, "blend/hardlight.wgsl", include_str!("../shaders/blend/hardlight.wgsl")),
+            ComplexBlend::Overlay => create_overlay_shader(device, &mut composer, &shader_defs, false),
+            ComplexBlend::HardLight => create_overlay_shader(device, &mut composer, &shader_defs, true),
         };
 
         let gradient_shaders = enum_map! {
@@ -90,13 +163,22 @@ impl Shaders {
 
         Self {
             color_shader,
+            color_instanced_shader,
+            morph_shader,
             bitmap_shader,
+            bitmap_instanced_shader,
+            textured_triangles_shader,
             gradient_shaders,
             copy_srgb_shader,
             copy_shader,
             blend_shaders,
             color_matrix_filter,
+            convolution_filter,
             blur_filter,
+            displacement_map_filter,
+            drop_shadow_filter,
+            bevel_filter,
+            alpha_mask_shader,
         }
     }
 }
@@ -152,6 +234,27 @@ fn make_shader(
     })
 }
 
+fn create_overlay_shader(
+    device: &wgpu::Device,
+    composer: &mut Composer,
+    shader_defs: &HashMap<String, ShaderDefValue>,
+    hard_light: bool,
+) -> wgpu::ShaderModule {
+    let mut temporary_defs = shader_defs.clone();
+    temporary_defs.insert("hard_light".to_owned(), ShaderDefValue::Bool(hard_light));
+    make_shader(
+        device,
+        composer,
+        &temporary_defs,
+        if hard_light {
+            "blend/overlay.wgsl as hardlight"
+        } else {
+            "blend/overlay.wgsl"
+        },
+        include_str!("../shaders/blend/overlay.wgsl"),
+    )
+}
+
 fn create_gradient_shaders(
     device: &wgpu::Device,
     composer: &mut Composer,
@@ -5,15 +5,20 @@ use crate::backend::RenderTargetMode;
 use crate::blend::ComplexBlend;
 use crate::buffer_pool::TexturePool;
 use crate::mesh::Mesh;
-use crate::surface::commands::{chunk_blends, Chunk, CommandRenderer};
+pub use crate::surface::commands::RenderStatistics;
+use crate::surface::commands::{chunk_blends, Chunk, CommandRenderer, ScissorRect};
 use crate::uniform_buffer::BufferStorage;
-use crate::utils::{remove_srgb, supported_sample_count};
+use crate::utils::{base_level_view, remove_srgb, supported_sample_count};
 use crate::{
-    ColorAdjustments, Descriptors, MaskState, Pipelines, PushConstants, Texture, TextureTransforms,
-    Transforms, UniformBuffer, DEFAULT_COLOR_ADJUSTMENTS,
+    as_texture, ColorAdjustments, Descriptors, MaskState, Pipelines, PushConstants, Texture,
+    TextureTransforms, Transforms, UniformBuffer, DEFAULT_COLOR_ADJUSTMENTS,
 };
+use bytemuck::{Pod, Zeroable};
 use ruffle_render::commands::CommandList;
-use ruffle_render::filters::{BlurFilter, ColorMatrixFilter, Filter};
+use ruffle_render::filters::{
+    BevelFilter, BevelFilterType, BlurFilter, ColorMatrixFilter, ConvolutionFilter,
+    DisplacementMapFilter, DisplacementMapFilterMode, DropShadowFilter, Filter,
+};
 use ruffle_render::quality::StageQuality;
 use std::sync::Arc;
 use target::CommandTarget;
@@ -22,6 +27,68 @@ use wgpu::util::DeviceExt;
 
 use self::commands::run_copy_pipeline;
 
+/// The arguments of `filter/convolution.wgsl`.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+struct ConvolutionUniform {
+    matrix: [[f32; 4]; 64],
+    color: [f32; 4],
+    source_point: [f32; 2],
+    source_size: [f32; 2],
+    divisor: f32,
+    bias: f32,
+    matrix_x: u32,
+    matrix_y: u32,
+    preserve_alpha: u32,
+    clamp: u32,
+    _padding: [u32; 2],
+}
+
+/// The arguments of `filter/displacement_map.wgsl`.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+struct DisplacementMapUniform {
+    color: [f32; 4],
+    source_point: [f32; 2],
+    source_size: [f32; 2],
+    map_point: [f32; 2],
+    scale: [f32; 2],
+    component_x: u32,
+    component_y: u32,
+    mode: u32,
+    _padding: u32,
+}
+
+/// The arguments of `filter/drop_shadow.wgsl`.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+struct DropShadowUniform {
+    color: [f32; 4],
+    offset: [f32; 2],
+    source_point: [f32; 2],
+    source_size: [f32; 2],
+    strength: f32,
+    inner: u32,
+    knockout: u32,
+    hide_object: u32,
+    _padding: [u32; 2],
+}
+
+/// The arguments of `filter/bevel.wgsl`.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+struct BevelUniform {
+    highlight_color: [f32; 4],
+    shadow_color: [f32; 4],
+    offset: [f32; 2],
+    source_point: [f32; 2],
+    source_size: [f32; 2],
+    strength: f32,
+    bevel_type: u32,
+    knockout: u32,
+    _padding: [u32; 3],
+}
+
 #[derive(Debug)]
 pub struct Surface {
     size: wgpu::Extent3d,
@@ -30,6 +97,7 @@ pub struct Surface {
     pipelines: Arc<Pipelines>,
     format: wgpu::TextureFormat,
     actual_surface_format: wgpu::TextureFormat,
+    stats: RenderStatistics,
 }
 
 impl Surface {
@@ -39,6 +107,26 @@ impl Surface {
         width: u32,
         height: u32,
         surface_format: wgpu::TextureFormat,
+    ) -> Self {
+        Self::with_sample_count(
+            descriptors,
+            quality,
+            quality.sample_count(),
+            width,
+            height,
+            surface_format,
+        )
+    }
+
+    /// Creates a surface that uses the given MSAA sample count, rather than the one implied by
+    /// `quality`. The count is rounded down to one that the adapter supports, if needed.
+    pub fn with_sample_count(
+        descriptors: &Descriptors,
+        quality: StageQuality,
+        sample_count: u32,
+        width: u32,
+        height: u32,
+        surface_format: wgpu::TextureFormat,
     ) -> Self {
         let size = wgpu::Extent3d {
             width,
@@ -47,8 +135,12 @@ impl Surface {
         };
         let frame_buffer_format = remove_srgb(surface_format);
 
-        let sample_count =
-            supported_sample_count(&descriptors.adapter, quality, frame_buffer_format);
+        let sample_count = supported_sample_count(
+            &descriptors.adapter,
+            &descriptors.device,
+            sample_count,
+            frame_buffer_format,
+        );
         let pipelines = descriptors.pipelines(sample_count, frame_buffer_format);
         Self {
             size,
@@ -57,6 +149,7 @@ impl Surface {
             pipelines,
             format: frame_buffer_format,
             actual_surface_format: surface_format,
+            stats: RenderStatistics::default(),
         }
     }
 
@@ -73,15 +166,9 @@ impl Surface {
         commands: CommandList,
         texture_pool: &mut TexturePool,
     ) -> Vec<wgpu::CommandBuffer> {
-        let uniform_encoder_label = create_debug_label!("Uniform upload command encoder");
+        self.stats = RenderStatistics::default();
         let mut uniform_buffer = UniformBuffer::new(uniform_buffers_storage);
         let mut color_buffer = UniformBuffer::new(color_buffers_storage);
-        let mut uniform_encoder =
-            descriptors
-                .device
-                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                    label: uniform_encoder_label.as_deref(),
-                });
         let label = create_debug_label!("Draw encoder");
         let mut draw_encoder =
             descriptors
@@ -97,7 +184,6 @@ impl Surface {
             commands,
             &mut uniform_buffer,
             &mut color_buffer,
-            &mut uniform_encoder,
             &mut draw_encoder,
             None,
             texture_pool,
@@ -132,13 +218,26 @@ impl Surface {
             buffers.push(copy_encoder.finish());
         }
 
+        // The uniforms of every draw are uploaded at once, ahead of the draws that use them
+        let uniform_encoder_label = create_debug_label!("Uniform upload command encoder");
+        let mut uniform_encoder =
+            descriptors
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: uniform_encoder_label.as_deref(),
+                });
+        uniform_buffer.finish(&descriptors.device, &mut uniform_encoder);
+        color_buffer.finish(&descriptors.device, &mut uniform_encoder);
         buffers.insert(0, uniform_encoder.finish());
-        uniform_buffer.finish();
-        color_buffer.finish();
 
         buffers
     }
 
+    /// Draws the given commands into a new target.
+    ///
+    /// `nearest_layer` is the target of the closest ancestor with a `Layer` blend mode.
+    /// `Alpha` and `Erase` blends modify that layer rather than their direct parent.
+    /// When it's `None`, the new target itself acts as the layer.
     #[allow(clippy::too_many_arguments)]
     #[instrument(level = "debug", skip_all)]
     pub fn draw_commands<'frame, 'global: 'frame>(
@@ -149,7 +248,6 @@ impl Surface {
         commands: CommandList,
         uniform_buffers: &'frame mut UniformBuffer<'global, Transforms>,
         color_buffers: &'frame mut UniformBuffer<'global, ColorAdjustments>,
-        uniform_encoder: &'frame mut wgpu::CommandEncoder,
         draw_encoder: &'frame mut wgpu::CommandEncoder,
         nearest_layer: Option<&'frame CommandTarget>,
         texture_pool: &mut TexturePool,
@@ -166,12 +264,12 @@ impl Surface {
 
         let mut num_masks = 0;
         let mut mask_state = MaskState::NoMask;
+        let mut scissor = ScissorRect::full(target.width(), target.height());
         let chunks = chunk_blends(
             commands.commands,
             descriptors,
             uniform_buffers,
             color_buffers,
-            uniform_encoder,
             draw_encoder,
             meshes,
             self.quality,
@@ -179,6 +277,7 @@ impl Surface {
             target.height(),
             nearest_layer.unwrap_or(&target),
             texture_pool,
+            &mut self.stats,
         );
 
         for chunk in chunks {
@@ -209,11 +308,13 @@ impl Surface {
                         descriptors,
                         uniform_buffers,
                         color_buffers,
-                        uniform_encoder,
                         render_pass,
                         num_masks,
                         mask_state,
+                        scissor,
                         needs_depth,
+                        self.anisotropic_filtering(),
+                        self.quality != StageQuality::Low,
                     );
 
                     for command in &chunk {
@@ -222,6 +323,8 @@ impl Surface {
 
                     num_masks = renderer.num_masks();
                     mask_state = renderer.mask_state();
+                    scissor = renderer.scissor();
+                    self.stats += renderer.stats();
                 }
                 Chunk::Blend(texture, blend_mode, needs_depth) => {
                     let parent = match blend_mode {
@@ -291,19 +394,12 @@ impl Surface {
                             },
                         });
                     render_pass.set_bind_group(0, target.globals().bind_group(), &[]);
+                    scissor.apply(&mut render_pass);
 
                     if needs_depth {
-                        match mask_state {
-                            MaskState::NoMask => {}
-                            MaskState::DrawMaskStencil => {
-                                render_pass.set_stencil_reference(num_masks - 1);
-                            }
-                            MaskState::DrawMaskedContent => {
-                                render_pass.set_stencil_reference(num_masks);
-                            }
-                            MaskState::ClearMaskStencil => {
-                                render_pass.set_stencil_reference(num_masks);
-                            }
+                        if mask_state != MaskState::NoMask {
+                            render_pass
+                                .set_stencil_reference(mask_state.stencil_reference(num_masks));
                         }
                         render_pass.set_pipeline(
                             self.pipelines.complex_blends[blend_mode].pipeline_for(mask_state),
@@ -356,10 +452,26 @@ impl Surface {
         self.quality
     }
 
+    /// The draw calls, triangles and state changes that the shapes, bitmaps and rects of the last
+    /// `draw_commands_to` were drawn with, including those inside of blends and filters.
+    /// The draws that apply the blends and filters themselves aren't counted.
+    pub fn stats(&self) -> RenderStatistics {
+        self.stats
+    }
+
     pub fn sample_count(&self) -> u32 {
         self.sample_count
     }
 
+    /// Whether obliquely transformed bitmaps are drawn with anisotropic filtering.
+    fn anisotropic_filtering(&self) -> bool {
+        // Anything above the default high quality is worth the cost of sharper oblique bitmaps
+        !matches!(
+            self.quality,
+            StageQuality::Low | StageQuality::Medium | StageQuality::High
+        )
+    }
+
     pub fn size(&self) -> wgpu::Extent3d {
         self.size
     }
@@ -377,26 +489,17 @@ impl Surface {
         dest_point: (u32, u32),
         filter: Filter,
     ) {
-        let target = match filter {
-            Filter::ColorMatrixFilter(filter) => self.apply_color_matrix(
-                descriptors,
-                texture_pool,
-                draw_encoder,
-                source_texture,
-                source_point,
-                source_size,
-                &filter,
-            ),
-            Filter::BlurFilter(filter) => self.apply_blur(
-                descriptors,
-                texture_pool,
-                draw_encoder,
-                source_texture,
-                source_point,
-                source_size,
-                &filter,
-            ),
-        };
+        let source_view = base_level_view(source_texture.texture(descriptors));
+        let target = self.run_filter(
+            descriptors,
+            draw_encoder,
+            texture_pool,
+            &source_view,
+            (source_texture.width, source_texture.height),
+            source_point,
+            source_size,
+            filter,
+        );
 
         // We're about to perform a copy, so make sure that we've applied
         // a clear (in case no other draw commands were issued, we still need
@@ -411,7 +514,7 @@ impl Surface {
                 aspect: Default::default(),
             },
             wgpu::ImageCopyTexture {
-                texture: &dest_texture.texture,
+                texture: dest_texture.texture(descriptors),
                 mip_level: 0,
                 origin: wgpu::Origin3d {
                     x: dest_point.0,
@@ -428,14 +531,99 @@ impl Surface {
         )
     }
 
+    /// Runs `filter` over the given area of the source texture,
+    /// returning a new target of `source_size` that contains the result.
+    #[allow(clippy::too_many_arguments)]
+    pub fn run_filter(
+        &self,
+        descriptors: &Descriptors,
+        draw_encoder: &mut wgpu::CommandEncoder,
+        texture_pool: &mut TexturePool,
+        source_view: &wgpu::TextureView,
+        source_texture_size: (u32, u32),
+        source_point: (u32, u32),
+        source_size: (u32, u32),
+        filter: Filter,
+    ) -> CommandTarget {
+        match filter {
+            Filter::BevelFilter(filter) => self.apply_bevel(
+                descriptors,
+                texture_pool,
+                draw_encoder,
+                source_view,
+                source_texture_size,
+                source_point,
+                source_size,
+                &filter,
+            ),
+            Filter::ColorMatrixFilter(filter) => self.apply_color_matrix(
+                descriptors,
+                texture_pool,
+                draw_encoder,
+                source_view,
+                source_texture_size,
+                source_size,
+                &filter,
+            ),
+            Filter::BlurFilter(filter) => self.apply_blur(
+                descriptors,
+                texture_pool,
+                draw_encoder,
+                source_view,
+                source_texture_size,
+                source_point,
+                source_size,
+                &filter,
+            ),
+            Filter::ConvolutionFilter(filter) => self.apply_convolution(
+                descriptors,
+                texture_pool,
+                draw_encoder,
+                source_view,
+                source_point,
+                source_size,
+                &filter,
+            ),
+            Filter::DisplacementMapFilter(filter) => self.apply_displacement_map(
+                descriptors,
+                texture_pool,
+                draw_encoder,
+                source_view,
+                source_point,
+                source_size,
+                &filter,
+            ),
+            Filter::DropShadowFilter(filter) => self.apply_drop_shadow(
+                descriptors,
+                texture_pool,
+                draw_encoder,
+                source_view,
+                source_texture_size,
+                source_point,
+                source_size,
+                &filter,
+            ),
+            Filter::GlowFilter(filter) => self.apply_drop_shadow(
+                descriptors,
+                texture_pool,
+                draw_encoder,
+                source_view,
+                source_texture_size,
+                source_point,
+                source_size,
+                &filter.into(),
+            ),
+        }
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn apply_color_matrix(
         &self,
         descriptors: &Descriptors,
         texture_pool: &mut TexturePool,
         draw_encoder: &mut wgpu::CommandEncoder,
-        source_texture: &Texture,
-        source_point: (u32, u32),
+        source_view: &wgpu::TextureView,
+        source_texture_size: (u32, u32),
         source_size: (u32, u32),
         filter: &ColorMatrixFilter,
     ) -> CommandTarget {
@@ -452,6 +640,8 @@ impl Surface {
             RenderTargetMode::FreshBuffer(wgpu::Color::TRANSPARENT),
             draw_encoder,
         );
+        // Flash reads the pixels to transform from the top left of the source, whatever the
+        // position of the source rect, so only the size of the area is taken into account
         let texture_transform =
             descriptors
                 .device
@@ -460,24 +650,25 @@ impl Surface {
                     contents: bytemuck::cast_slice(&[TextureTransforms {
                         u_matrix: [
                             [
-                                1.0,
+                                source_size.0 as f32 / source_texture_size.0 as f32,
+                                0.0,
                                 0.0,
                                 0.0,
-                                (source_point.0 as f32 / source_size.0 as f32),
                             ],
                             [
                                 0.0,
-                                1.0,
+                                source_size.1 as f32 / source_texture_size.1 as f32,
+                                0.0,
                                 0.0,
-                                (source_point.1 as f32 / source_size.1 as f32),
                             ],
                             [0.0, 0.0, 1.0, 0.0],
                             [0.0, 0.0, 0.0, 1.0],
                         ],
+                        premultiply: 0.0,
+                        _padding: [0.0; 3],
                     }]),
                     usage: wgpu::BufferUsages::UNIFORM,
                 });
-        let source_view = source_texture.texture.create_view(&Default::default());
         let bitmap_group = descriptors
             .device
             .create_bind_group(&wgpu::BindGroupDescriptor {
@@ -490,7 +681,7 @@ impl Surface {
                     },
                     wgpu::BindGroupEntry {
                         binding: 1,
-                        resource: wgpu::BindingResource::TextureView(&source_view),
+                        resource: wgpu::BindingResource::TextureView(source_view),
                     },
                     wgpu::BindGroupEntry {
                         binding: 2,
@@ -532,8 +723,8 @@ impl Surface {
                 bytemuck::cast_slice(&[PushConstants {
                     transforms: Transforms {
                         world_matrix: [
-                            [self.size.width as f32, 0.0, 0.0, 0.0],
-                            [0.0, self.size.height as f32, 0.0, 0.0],
+                            [target.width() as f32, 0.0, 0.0, 0.0],
+                            [0.0, target.height() as f32, 0.0, 0.0],
                             [0.0, 0.0, 1.0, 0.0],
                             [0.0, 0.0, 0.0, 1.0],
                         ],
@@ -566,7 +757,8 @@ impl Surface {
         descriptors: &Descriptors,
         texture_pool: &mut TexturePool,
         draw_encoder: &mut wgpu::CommandEncoder,
-        source_texture: &Texture,
+        source_view: &wgpu::TextureView,
+        source_texture_size: (u32, u32),
         source_point: (u32, u32),
         source_size: (u32, u32),
         filter: &BlurFilter,
@@ -599,6 +791,7 @@ impl Surface {
                 draw_encoder,
             ),
         ];
+        // The first pass reads the source area directly out of the source texture
         let texture_transform =
             descriptors
                 .device
@@ -607,34 +800,54 @@ impl Surface {
                     contents: bytemuck::cast_slice(&[TextureTransforms {
                         u_matrix: [
                             [
-                                1.0,
+                                source_size.0 as f32 / source_texture_size.0 as f32,
+                                0.0,
                                 0.0,
                                 0.0,
-                                (source_point.0 as f32 / source_size.0 as f32),
                             ],
                             [
                                 0.0,
+                                source_size.1 as f32 / source_texture_size.1 as f32,
+                                0.0,
+                                0.0,
+                            ],
+                            [
+                                source_point.0 as f32 / source_texture_size.0 as f32,
+                                source_point.1 as f32 / source_texture_size.1 as f32,
                                 1.0,
                                 0.0,
-                                (source_point.1 as f32 / source_size.1 as f32),
                             ],
-                            [0.0, 0.0, 1.0, 0.0],
                             [0.0, 0.0, 0.0, 1.0],
                         ],
+                        premultiply: 0.0,
+                        _padding: [0.0; 3],
                     }]),
                     usage: wgpu::BufferUsages::UNIFORM,
                 });
-        let source_view = source_texture.texture.create_view(&Default::default());
-        for i in 0..2 {
-            let blur_x = (filter.blur_x - 1.0).max(0.0);
-            let blur_y = (filter.blur_y - 1.0).max(0.0);
+        // Each quality level is another horizontal and vertical box blur, which makes the
+        // result look more and more like a gaussian blur. An axis that isn't blurred is skipped.
+        let mut passes = Vec::new();
+        for _ in 0..filter.quality.max(1) {
+            if filter.blur_x > 1.0 {
+                passes.push((filter.blur_x, 0.0));
+            }
+            if filter.blur_y > 1.0 {
+                passes.push((0.0, filter.blur_y));
+            }
+        }
+        if passes.is_empty() {
+            // Nothing to blur, but the source still needs to be copied into the result
+            passes.push((0.0, 0.0));
+        }
+        descriptors.count_blur_passes(passes.len());
+        for (i, &(blur_x, blur_y)) in passes.iter().enumerate() {
             let current = &targets[i % 2];
             let (previous_view, previous_transform, previous_width, previous_height) = if i == 0 {
                 (
-                    &source_view,
+                    source_view,
                     texture_transform.as_entire_binding(),
-                    source_texture.width as f32,
-                    source_texture.height as f32,
+                    source_texture_size.0 as f32,
+                    source_texture_size.1 as f32,
                 )
             } else {
                 let previous = &targets[(i - 1) % 2];
@@ -672,8 +885,8 @@ impl Surface {
                 .create_buffer_init(&wgpu::util::BufferInitDescriptor {
                     label: create_debug_label!("Filter arguments").as_deref(),
                     contents: bytemuck::cast_slice(&[
-                        blur_x * ((i as u32) % 2) as f32,
-                        blur_y * (((i as u32) % 2) + 1) as f32,
+                        blur_x,
+                        blur_y,
                         previous_width,
                         previous_height,
                     ]),
@@ -690,7 +903,7 @@ impl Surface {
                     }],
                 });
             let mut render_pass = draw_encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: create_debug_label!("Color matrix filter").as_deref(),
+                label: create_debug_label!("Blur filter").as_deref(),
                 color_attachments: &[current.color_attachments()],
                 depth_stencil_attachment: None,
             });
@@ -731,7 +944,684 @@ impl Surface {
         }
         targets
             .into_iter()
-            .last()
-            .expect("Targets should not be empty")
+            .nth((passes.len() - 1) % 2)
+            .expect("There are always two targets")
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn apply_convolution(
+        &self,
+        descriptors: &Descriptors,
+        texture_pool: &mut TexturePool,
+        draw_encoder: &mut wgpu::CommandEncoder,
+        source_view: &wgpu::TextureView,
+        source_point: (u32, u32),
+        source_size: (u32, u32),
+        filter: &ConvolutionFilter,
+    ) -> CommandTarget {
+        let target = CommandTarget::new(
+            descriptors,
+            texture_pool,
+            wgpu::Extent3d {
+                width: source_size.0,
+                height: source_size.1,
+                depth_or_array_layers: 1,
+            },
+            self.format,
+            self.sample_count,
+            RenderTargetMode::FreshBuffer(wgpu::Color::TRANSPARENT),
+            draw_encoder,
+        );
+        let bitmap_group = descriptors
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: create_debug_label!("Bitmap copy group").as_deref(),
+                layout: &descriptors.bind_layouts.bitmap,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: descriptors.quad.texture_transforms.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::TextureView(source_view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: wgpu::BindingResource::Sampler(
+                            descriptors.bitmap_samplers.get_sampler(false, false),
+                        ),
+                    },
+                ],
+            });
+        let mut matrix = [[0.0; 4]; 64];
+        let size = usize::from(filter.matrix_x) * usize::from(filter.matrix_y);
+        for (i, value) in filter.matrix.iter().take(size).enumerate() {
+            matrix[i / 4][i % 4] = *value;
+        }
+        let buffer = descriptors
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: create_debug_label!("Filter arguments").as_deref(),
+                contents: bytemuck::cast_slice(&[ConvolutionUniform {
+                    matrix,
+                    color: [
+                        f32::from(filter.color.r) / 255.0,
+                        f32::from(filter.color.g) / 255.0,
+                        f32::from(filter.color.b) / 255.0,
+                        f32::from(filter.color.a) / 255.0,
+                    ],
+                    source_point: [source_point.0 as f32, source_point.1 as f32],
+                    source_size: [source_size.0 as f32, source_size.1 as f32],
+                    divisor: filter.divisor,
+                    bias: filter.bias,
+                    matrix_x: filter.matrix_x.into(),
+                    matrix_y: filter.matrix_y.into(),
+                    preserve_alpha: filter.preserve_alpha as u32,
+                    clamp: filter.clamp as u32,
+                    _padding: [0; 2],
+                }]),
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+        let filter_group = descriptors
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: create_debug_label!("Filter group").as_deref(),
+                layout: &descriptors.bind_layouts.convolution_filter,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: buffer.as_entire_binding(),
+                }],
+            });
+        let mut render_pass = draw_encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: create_debug_label!("Convolution filter").as_deref(),
+            color_attachments: &[target.color_attachments()],
+            depth_stencil_attachment: None,
+        });
+        render_pass.set_pipeline(&self.pipelines.convolution_filter);
+
+        render_pass.set_bind_group(0, target.globals().bind_group(), &[]);
+        if descriptors.limits.max_push_constant_size > 0 {
+            render_pass.set_push_constants(
+                wgpu::ShaderStages::VERTEX_FRAGMENT,
+                0,
+                bytemuck::cast_slice(&[PushConstants {
+                    transforms: Transforms {
+                        world_matrix: [
+                            [target.width() as f32, 0.0, 0.0, 0.0],
+                            [0.0, target.height() as f32, 0.0, 0.0],
+                            [0.0, 0.0, 1.0, 0.0],
+                            [0.0, 0.0, 0.0, 1.0],
+                        ],
+                    },
+                    colors: DEFAULT_COLOR_ADJUSTMENTS,
+                }]),
+            );
+            render_pass.set_bind_group(1, &bitmap_group, &[]);
+            render_pass.set_bind_group(2, &filter_group, &[]);
+        } else {
+            render_pass.set_bind_group(1, target.whole_frame_bind_group(descriptors), &[0]);
+            render_pass.set_bind_group(2, &descriptors.default_color_bind_group, &[0]);
+            render_pass.set_bind_group(3, &bitmap_group, &[]);
+            render_pass.set_bind_group(4, &filter_group, &[]);
+        }
+
+        render_pass.set_vertex_buffer(0, descriptors.quad.vertices_pos.slice(..));
+        render_pass.set_index_buffer(
+            descriptors.quad.indices.slice(..),
+            wgpu::IndexFormat::Uint32,
+        );
+        render_pass.draw_indexed(0..6, 0, 0..1);
+        drop(render_pass);
+        target
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn apply_displacement_map(
+        &self,
+        descriptors: &Descriptors,
+        texture_pool: &mut TexturePool,
+        draw_encoder: &mut wgpu::CommandEncoder,
+        source_view: &wgpu::TextureView,
+        source_point: (u32, u32),
+        source_size: (u32, u32),
+        filter: &DisplacementMapFilter,
+    ) -> CommandTarget {
+        let target = CommandTarget::new(
+            descriptors,
+            texture_pool,
+            wgpu::Extent3d {
+                width: source_size.0,
+                height: source_size.1,
+                depth_or_array_layers: 1,
+            },
+            self.format,
+            self.sample_count,
+            RenderTargetMode::FreshBuffer(wgpu::Color::TRANSPARENT),
+            draw_encoder,
+        );
+        let bitmap_group = descriptors
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: create_debug_label!("Bitmap copy group").as_deref(),
+                layout: &descriptors.bind_layouts.bitmap,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: descriptors.quad.texture_transforms.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::TextureView(source_view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: wgpu::BindingResource::Sampler(
+                            descriptors.bitmap_samplers.get_sampler(false, false),
+                        ),
+                    },
+                ],
+            });
+        let alpha = f32::from(filter.color.a) / 255.0;
+        let buffer = descriptors
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: create_debug_label!("Filter arguments").as_deref(),
+                contents: bytemuck::cast_slice(&[DisplacementMapUniform {
+                    color: [
+                        f32::from(filter.color.r) / 255.0 * alpha,
+                        f32::from(filter.color.g) / 255.0 * alpha,
+                        f32::from(filter.color.b) / 255.0 * alpha,
+                        alpha,
+                    ],
+                    source_point: [source_point.0 as f32, source_point.1 as f32],
+                    source_size: [source_size.0 as f32, source_size.1 as f32],
+                    map_point: [filter.map_point.0 as f32, filter.map_point.1 as f32],
+                    scale: [filter.scale_x, filter.scale_y],
+                    component_x: filter.component_x.into(),
+                    component_y: filter.component_y.into(),
+                    mode: match filter.mode {
+                        DisplacementMapFilterMode::Wrap => 0,
+                        DisplacementMapFilterMode::Clamp => 1,
+                        DisplacementMapFilterMode::Ignore => 2,
+                        DisplacementMapFilterMode::Color => 3,
+                    },
+                    _padding: 0,
+                }]),
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+        let map_view = base_level_view(as_texture(&filter.map).texture(descriptors));
+        let filter_group = descriptors
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: create_debug_label!("Filter group").as_deref(),
+                layout: &descriptors.bind_layouts.displacement_map_filter,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: buffer.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::TextureView(&map_view),
+                    },
+                ],
+            });
+        let mut render_pass = draw_encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: create_debug_label!("Displacement map filter").as_deref(),
+            color_attachments: &[target.color_attachments()],
+            depth_stencil_attachment: None,
+        });
+        render_pass.set_pipeline(&self.pipelines.displacement_map_filter);
+
+        render_pass.set_bind_group(0, target.globals().bind_group(), &[]);
+        if descriptors.limits.max_push_constant_size > 0 {
+            render_pass.set_push_constants(
+                wgpu::ShaderStages::VERTEX_FRAGMENT,
+                0,
+                bytemuck::cast_slice(&[PushConstants {
+                    transforms: Transforms {
+                        world_matrix: [
+                            [target.width() as f32, 0.0, 0.0, 0.0],
+                            [0.0, target.height() as f32, 0.0, 0.0],
+                            [0.0, 0.0, 1.0, 0.0],
+                            [0.0, 0.0, 0.0, 1.0],
+                        ],
+                    },
+                    colors: DEFAULT_COLOR_ADJUSTMENTS,
+                }]),
+            );
+            render_pass.set_bind_group(1, &bitmap_group, &[]);
+            render_pass.set_bind_group(2, &filter_group, &[]);
+        } else {
+            render_pass.set_bind_group(1, target.whole_frame_bind_group(descriptors), &[0]);
+            render_pass.set_bind_group(2, &descriptors.default_color_bind_group, &[0]);
+            render_pass.set_bind_group(3, &bitmap_group, &[]);
+            render_pass.set_bind_group(4, &filter_group, &[]);
+        }
+
+        render_pass.set_vertex_buffer(0, descriptors.quad.vertices_pos.slice(..));
+        render_pass.set_index_buffer(
+            descriptors.quad.indices.slice(..),
+            wgpu::IndexFormat::Uint32,
+        );
+        render_pass.draw_indexed(0..6, 0, 0..1);
+        drop(render_pass);
+        target
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn apply_drop_shadow(
+        &self,
+        descriptors: &Descriptors,
+        texture_pool: &mut TexturePool,
+        draw_encoder: &mut wgpu::CommandEncoder,
+        source_view: &wgpu::TextureView,
+        source_texture_size: (u32, u32),
+        source_point: (u32, u32),
+        source_size: (u32, u32),
+        filter: &DropShadowFilter,
+    ) -> CommandTarget {
+        // The shadow is a blurred copy of the source, which is then tinted and offset
+        let blurred = self.apply_blur(
+            descriptors,
+            texture_pool,
+            draw_encoder,
+            source_view,
+            source_texture_size,
+            source_point,
+            source_size,
+            &BlurFilter {
+                blur_x: filter.blur_x,
+                blur_y: filter.blur_y,
+                quality: filter.quality,
+            },
+        );
+        blurred.ensure_cleared(draw_encoder);
+
+        let target = CommandTarget::new(
+            descriptors,
+            texture_pool,
+            wgpu::Extent3d {
+                width: source_size.0,
+                height: source_size.1,
+                depth_or_array_layers: 1,
+            },
+            self.format,
+            self.sample_count,
+            RenderTargetMode::FreshBuffer(wgpu::Color::TRANSPARENT),
+            draw_encoder,
+        );
+        let bitmap_group = descriptors
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: create_debug_label!("Bitmap copy group").as_deref(),
+                layout: &descriptors.bind_layouts.bitmap,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: descriptors.quad.texture_transforms.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::TextureView(source_view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: wgpu::BindingResource::Sampler(
+                            descriptors.bitmap_samplers.get_sampler(false, false),
+                        ),
+                    },
+                ],
+            });
+        let buffer = descriptors
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: create_debug_label!("Filter arguments").as_deref(),
+                contents: bytemuck::cast_slice(&[DropShadowUniform {
+                    color: [
+                        f32::from(filter.color.r) / 255.0,
+                        f32::from(filter.color.g) / 255.0,
+                        f32::from(filter.color.b) / 255.0,
+                        f32::from(filter.color.a) / 255.0,
+                    ],
+                    offset: [
+                        filter.angle.cos() * filter.distance,
+                        filter.angle.sin() * filter.distance,
+                    ],
+                    source_point: [source_point.0 as f32, source_point.1 as f32],
+                    source_size: [source_size.0 as f32, source_size.1 as f32],
+                    strength: filter.strength,
+                    inner: filter.inner.into(),
+                    knockout: filter.knockout.into(),
+                    hide_object: filter.hide_object.into(),
+                    _padding: [0; 2],
+                }]),
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+        let filter_group = descriptors
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: create_debug_label!("Filter group").as_deref(),
+                layout: &descriptors.bind_layouts.drop_shadow_filter,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: buffer.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::TextureView(blurred.color_view()),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: wgpu::BindingResource::Sampler(
+                            descriptors.bitmap_samplers.get_sampler(false, true),
+                        ),
+                    },
+                ],
+            });
+        let mut render_pass = draw_encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: create_debug_label!("Drop shadow filter").as_deref(),
+            color_attachments: &[target.color_attachments()],
+            depth_stencil_attachment: None,
+        });
+        render_pass.set_pipeline(&self.pipelines.drop_shadow_filter);
+
+        render_pass.set_bind_group(0, target.globals().bind_group(), &[]);
+        if descriptors.limits.max_push_constant_size > 0 {
+            render_pass.set_push_constants(
+                wgpu::ShaderStages::VERTEX_FRAGMENT,
+                0,
+                bytemuck::cast_slice(&[PushConstants {
+                    transforms: Transforms {
+                        world_matrix: [
+                            [target.width() as f32, 0.0, 0.0, 0.0],
+                            [0.0, target.height() as f32, 0.0, 0.0],
+                            [0.0, 0.0, 1.0, 0.0],
+                            [0.0, 0.0, 0.0, 1.0],
+                        ],
+                    },
+                    colors: DEFAULT_COLOR_ADJUSTMENTS,
+                }]),
+            );
+            render_pass.set_bind_group(1, &bitmap_group, &[]);
+            render_pass.set_bind_group(2, &filter_group, &[]);
+        } else {
+            render_pass.set_bind_group(1, target.whole_frame_bind_group(descriptors), &[0]);
+            render_pass.set_bind_group(2, &descriptors.default_color_bind_group, &[0]);
+            render_pass.set_bind_group(3, &bitmap_group, &[]);
+            render_pass.set_bind_group(4, &filter_group, &[]);
+        }
+
+        render_pass.set_vertex_buffer(0, descriptors.quad.vertices_pos.slice(..));
+        render_pass.set_index_buffer(
+            descriptors.quad.indices.slice(..),
+            wgpu::IndexFormat::Uint32,
+        );
+        render_pass.draw_indexed(0..6, 0, 0..1);
+        drop(render_pass);
+        target
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn apply_bevel(
+        &self,
+        descriptors: &Descriptors,
+        texture_pool: &mut TexturePool,
+        draw_encoder: &mut wgpu::CommandEncoder,
+        source_view: &wgpu::TextureView,
+        source_texture_size: (u32, u32),
+        source_point: (u32, u32),
+        source_size: (u32, u32),
+        filter: &BevelFilter,
+    ) -> CommandTarget {
+        // The highlight and shadow both come from the same blurred copy of the source
+        let blurred = self.apply_blur(
+            descriptors,
+            texture_pool,
+            draw_encoder,
+            source_view,
+            source_texture_size,
+            source_point,
+            source_size,
+            &BlurFilter {
+                blur_x: filter.blur_x,
+                blur_y: filter.blur_y,
+                quality: filter.quality,
+            },
+        );
+        blurred.ensure_cleared(draw_encoder);
+
+        let target = CommandTarget::new(
+            descriptors,
+            texture_pool,
+            wgpu::Extent3d {
+                width: source_size.0,
+                height: source_size.1,
+                depth_or_array_layers: 1,
+            },
+            self.format,
+            self.sample_count,
+            RenderTargetMode::FreshBuffer(wgpu::Color::TRANSPARENT),
+            draw_encoder,
+        );
+        let bitmap_group = descriptors
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: create_debug_label!("Bitmap copy group").as_deref(),
+                layout: &descriptors.bind_layouts.bitmap,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: descriptors.quad.texture_transforms.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::TextureView(source_view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: wgpu::BindingResource::Sampler(
+                            descriptors.bitmap_samplers.get_sampler(false, false),
+                        ),
+                    },
+                ],
+            });
+        let buffer = descriptors
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: create_debug_label!("Filter arguments").as_deref(),
+                contents: bytemuck::cast_slice(&[BevelUniform {
+                    highlight_color: [
+                        f32::from(filter.highlight_color.r) / 255.0,
+                        f32::from(filter.highlight_color.g) / 255.0,
+                        f32::from(filter.highlight_color.b) / 255.0,
+                        f32::from(filter.highlight_color.a) / 255.0,
+                    ],
+                    shadow_color: [
+                        f32::from(filter.shadow_color.r) / 255.0,
+                        f32::from(filter.shadow_color.g) / 255.0,
+                        f32::from(filter.shadow_color.b) / 255.0,
+                        f32::from(filter.shadow_color.a) / 255.0,
+                    ],
+                    offset: [
+                        filter.angle.cos() * filter.distance,
+                        filter.angle.sin() * filter.distance,
+                    ],
+                    source_point: [source_point.0 as f32, source_point.1 as f32],
+                    source_size: [source_size.0 as f32, source_size.1 as f32],
+                    strength: filter.strength,
+                    bevel_type: match filter.bevel_type {
+                        BevelFilterType::Inner => 0,
+                        BevelFilterType::Outer => 1,
+                        BevelFilterType::Full => 2,
+                    },
+                    knockout: filter.knockout.into(),
+                    _padding: [0; 3],
+                }]),
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+        let filter_group = descriptors
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: create_debug_label!("Filter group").as_deref(),
+                layout: &descriptors.bind_layouts.bevel_filter,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: buffer.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::TextureView(blurred.color_view()),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: wgpu::BindingResource::Sampler(
+                            descriptors.bitmap_samplers.get_sampler(false, true),
+                        ),
+                    },
+                ],
+            });
+        let mut render_pass = draw_encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: create_debug_label!("Bevel filter").as_deref(),
+            color_attachments: &[target.color_attachments()],
+            depth_stencil_attachment: None,
+        });
+        render_pass.set_pipeline(&self.pipelines.bevel_filter);
+
+        render_pass.set_bind_group(0, target.globals().bind_group(), &[]);
+        if descriptors.limits.max_push_constant_size > 0 {
+            render_pass.set_push_constants(
+                wgpu::ShaderStages::VERTEX_FRAGMENT,
+                0,
+                bytemuck::cast_slice(&[PushConstants {
+                    transforms: Transforms {
+                        world_matrix: [
+                            [target.width() as f32, 0.0, 0.0, 0.0],
+                            [0.0, target.height() as f32, 0.0, 0.0],
+                            [0.0, 0.0, 1.0, 0.0],
+                            [0.0, 0.0, 0.0, 1.0],
+                        ],
+                    },
+                    colors: DEFAULT_COLOR_ADJUSTMENTS,
+                }]),
+            );
+            render_pass.set_bind_group(1, &bitmap_group, &[]);
+            render_pass.set_bind_group(2, &filter_group, &[]);
+        } else {
+            render_pass.set_bind_group(1, target.whole_frame_bind_group(descriptors), &[0]);
+            render_pass.set_bind_group(2, &descriptors.default_color_bind_group, &[0]);
+            render_pass.set_bind_group(3, &bitmap_group, &[]);
+            render_pass.set_bind_group(4, &filter_group, &[]);
+        }
+
+        render_pass.set_vertex_buffer(0, descriptors.quad.vertices_pos.slice(..));
+        render_pass.set_index_buffer(
+            descriptors.quad.indices.slice(..),
+            wgpu::IndexFormat::Uint32,
+        );
+        render_pass.draw_indexed(0..6, 0, 0..1);
+        drop(render_pass);
+        target
+    }
+
+    /// Draws `maskee` with its alpha multiplied by that of `mask`, into a new target.
+    /// Both must be the same size.
+    pub fn apply_alpha_mask(
+        &self,
+        descriptors: &Descriptors,
+        texture_pool: &mut TexturePool,
+        draw_encoder: &mut wgpu::CommandEncoder,
+        maskee: &CommandTarget,
+        mask: &CommandTarget,
+    ) -> CommandTarget {
+        let target = CommandTarget::new(
+            descriptors,
+            texture_pool,
+            wgpu::Extent3d {
+                width: maskee.width(),
+                height: maskee.height(),
+                depth_or_array_layers: 1,
+            },
+            self.format,
+            self.sample_count,
+            RenderTargetMode::FreshBuffer(wgpu::Color::TRANSPARENT),
+            draw_encoder,
+        );
+        let bitmap_group = descriptors
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: create_debug_label!("Alpha mask maskee group").as_deref(),
+                layout: &descriptors.bind_layouts.bitmap,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: descriptors.quad.texture_transforms.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::TextureView(maskee.color_view()),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: wgpu::BindingResource::Sampler(
+                            descriptors.bitmap_samplers.get_sampler(false, false),
+                        ),
+                    },
+                ],
+            });
+        let mask_group = descriptors
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: create_debug_label!("Alpha mask group").as_deref(),
+                layout: &descriptors.bind_layouts.alpha_mask,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(mask.color_view()),
+                }],
+            });
+        let mut render_pass = draw_encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: create_debug_label!("Alpha mask").as_deref(),
+            color_attachments: &[target.color_attachments()],
+            depth_stencil_attachment: None,
+        });
+        render_pass.set_pipeline(&self.pipelines.alpha_mask);
+
+        render_pass.set_bind_group(0, target.globals().bind_group(), &[]);
+        if descriptors.limits.max_push_constant_size > 0 {
+            render_pass.set_push_constants(
+                wgpu::ShaderStages::VERTEX_FRAGMENT,
+                0,
+                bytemuck::cast_slice(&[PushConstants {
+                    transforms: Transforms {
+                        world_matrix: [
+                            [target.width() as f32, 0.0, 0.0, 0.0],
+                            [0.0, target.height() as f32, 0.0, 0.0],
+                            [0.0, 0.0, 1.0, 0.0],
+                            [0.0, 0.0, 0.0, 1.0],
+                        ],
+                    },
+                    colors: DEFAULT_COLOR_ADJUSTMENTS,
+                }]),
+            );
+            render_pass.set_bind_group(1, &bitmap_group, &[]);
+            render_pass.set_bind_group(2, &mask_group, &[]);
+        } else {
+            render_pass.set_bind_group(1, target.whole_frame_bind_group(descriptors), &[0]);
+            render_pass.set_bind_group(2, &descriptors.default_color_bind_group, &[0]);
+            render_pass.set_bind_group(3, &bitmap_group, &[]);
+            render_pass.set_bind_group(4, &mask_group, &[]);
+        }
+
+        render_pass.set_vertex_buffer(0, descriptors.quad.vertices_pos.slice(..));
+        render_pass.set_index_buffer(
+            descriptors.quad.indices.slice(..),
+            wgpu::IndexFormat::Uint32,
+        );
+        render_pass.draw_indexed(0..6, 0, 0..1);
+        drop(render_pass);
+        target
     }
 }
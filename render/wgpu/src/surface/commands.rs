@@ -1,28 +1,70 @@
+use crate::atlas::FULL_UV_RECT;
 use crate::backend::RenderTargetMode;
 use crate::blend::TrivialBlend;
 use crate::blend::{BlendType, ComplexBlend};
-use crate::buffer_pool::TexturePool;
+use crate::buffer_pool::{PoolEntry, TexturePool};
 use crate::globals::Globals;
-use crate::mesh::{DrawType, Mesh};
+use crate::mesh::{BitmapBinds, DrawType, Mesh};
 use crate::surface::target::CommandTarget;
 use crate::surface::Surface;
+use crate::utils::{base_level_view, create_buffer_with_data};
 use crate::{
-    as_texture, ColorAdjustments, Descriptors, MaskState, Pipelines, PushConstants, Transforms,
-    UniformBuffer,
+    as_texture, ColorAdjustments, Descriptors, DrawInstance, MaskState, Pipelines, PushConstants,
+    TextureTransforms, Transforms, UniformBuffer, DEFAULT_COLOR_ADJUSTMENTS, MAX_MASK_DEPTH,
 };
 use ruffle_render::backend::ShapeHandle;
-use ruffle_render::bitmap::BitmapHandle;
+use ruffle_render::bitmap::{BitmapHandle, BitmapRegion};
+use ruffle_render::bounding_box::BoundingBox;
 use ruffle_render::color_transform::ColorTransform;
-use ruffle_render::commands::Command;
+use ruffle_render::commands::{Command, CommandList};
 use ruffle_render::matrix::Matrix;
+use ruffle_render::matrix3d::Matrix3D;
 use ruffle_render::quality::StageQuality;
 use ruffle_render::tessellator::GradientType;
 use ruffle_render::transform::Transform;
-use swf::{BlendMode, Color, Fixed8, GradientSpread};
+use std::sync::Arc;
+use swf::{BlendMode, Color, Fixed8, GradientSpread, Twips};
 use wgpu::CommandEncoder;
 
 use super::target::PoolOrArcTexture;
 
+/// How much more a bitmap must be stretched in one direction than in the other for it to be
+/// drawn with anisotropic filtering, when that's enabled.
+const ANISOTROPIC_FILTERING_THRESHOLD: f32 = 2.0;
+
+/// Counts of the work that rendering some commands took.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RenderStatistics {
+    /// The number of draw calls.
+    pub draw_calls: u32,
+    /// The number of triangles that were drawn, counting every instance of an instanced draw.
+    pub triangles: u32,
+    /// The number of times that a different pipeline was set.
+    pub pipeline_switches: u32,
+    /// The number of times that the bind group of a bitmap or gradient was set.
+    /// The bind groups of the transforms on devices without push constants aren't counted.
+    pub bind_group_switches: u32,
+    /// The number of draw calls that drew a mask into the stencil buffer, or cleared it again.
+    pub stencil_draws: u32,
+}
+
+impl RenderStatistics {
+    /// The number of times that a pipeline, bitmap or gradient had to be changed between draws.
+    pub fn state_changes(&self) -> u32 {
+        self.pipeline_switches + self.bind_group_switches
+    }
+}
+
+impl std::ops::AddAssign for RenderStatistics {
+    fn add_assign(&mut self, other: Self) {
+        self.draw_calls += other.draw_calls;
+        self.triangles += other.triangles;
+        self.pipeline_switches += other.pipeline_switches;
+        self.bind_group_switches += other.bind_group_switches;
+        self.stencil_draws += other.stencil_draws;
+    }
+}
+
 pub struct CommandRenderer<'pass, 'frame: 'pass, 'global: 'frame> {
     pipelines: &'frame Pipelines,
     meshes: &'global Vec<Mesh>,
@@ -32,8 +74,17 @@ pub struct CommandRenderer<'pass, 'frame: 'pass, 'global: 'frame> {
     render_pass: wgpu::RenderPass<'pass>,
     uniform_buffers: &'frame mut UniformBuffer<'global, Transforms>,
     color_buffers: &'frame mut UniformBuffer<'global, ColorAdjustments>,
-    uniform_encoder: &'frame mut wgpu::CommandEncoder,
     needs_depth: bool,
+    anisotropic_filtering: bool,
+    /// Whether the bitmap fills of shapes are smoothed where they ask to be.
+    allow_smoothing: bool,
+    stats: RenderStatistics,
+    // What's currently set on the render pass, so that setting the same again can be skipped
+    bound_pipeline: Option<&'pass wgpu::RenderPipeline>,
+    bound_fill: Option<&'pass wgpu::BindGroup>,
+    bound_transform: Option<([[f32; 4]; 4], ColorTransform)>,
+    stencil_reference: Option<u32>,
+    scissor: ScissorRect,
 }
 
 impl<'pass, 'frame: 'pass, 'global: 'frame> CommandRenderer<'pass, 'frame, 'global> {
@@ -44,12 +95,15 @@ impl<'pass, 'frame: 'pass, 'global: 'frame> CommandRenderer<'pass, 'frame, 'glob
         descriptors: &'global Descriptors,
         uniform_buffers: &'frame mut UniformBuffer<'global, Transforms>,
         color_buffers: &'frame mut UniformBuffer<'global, ColorAdjustments>,
-        uniform_encoder: &'frame mut wgpu::CommandEncoder,
-        render_pass: wgpu::RenderPass<'pass>,
+        mut render_pass: wgpu::RenderPass<'pass>,
         num_masks: u32,
         mask_state: MaskState,
+        scissor: ScissorRect,
         needs_depth: bool,
+        anisotropic_filtering: bool,
+        allow_smoothing: bool,
     ) -> Self {
+        scissor.apply(&mut render_pass);
         Self {
             pipelines,
             meshes,
@@ -59,25 +113,21 @@ impl<'pass, 'frame: 'pass, 'global: 'frame> CommandRenderer<'pass, 'frame, 'glob
             descriptors,
             uniform_buffers,
             color_buffers,
-            uniform_encoder,
             needs_depth,
+            anisotropic_filtering,
+            allow_smoothing,
+            stats: RenderStatistics::default(),
+            bound_pipeline: None,
+            bound_fill: None,
+            bound_transform: None,
+            stencil_reference: None,
+            scissor,
         }
     }
 
     pub fn execute(&mut self, command: &'frame DrawCommand) {
-        if self.needs_depth {
-            match self.mask_state {
-                MaskState::NoMask => {}
-                MaskState::DrawMaskStencil => {
-                    self.render_pass.set_stencil_reference(self.num_masks - 1);
-                }
-                MaskState::DrawMaskedContent => {
-                    self.render_pass.set_stencil_reference(self.num_masks);
-                }
-                MaskState::ClearMaskStencil => {
-                    self.render_pass.set_stencil_reference(self.num_masks);
-                }
-            }
+        if self.needs_depth && self.mask_state != MaskState::NoMask {
+            self.set_stencil_reference(self.mask_state.stencil_reference(self.num_masks));
         }
 
         match command {
@@ -87,31 +137,125 @@ impl<'pass, 'frame: 'pass, 'global: 'frame> CommandRenderer<'pass, 'frame, 'glob
                 smoothing,
                 blend_mode,
             } => self.render_bitmap(bitmap, transform, *smoothing, *blend_mode),
+            DrawCommand::RenderBitmaps {
+                bitmap,
+                instances,
+                num_instances,
+                smoothing,
+                blend_mode,
+            } => self.render_bitmaps(bitmap, instances, *num_instances, *smoothing, *blend_mode),
+            DrawCommand::RenderAtlasBitmaps {
+                instances,
+                num_instances,
+                smoothing,
+                blend_mode,
+                ..
+            } => self.render_atlas_bitmaps(instances, *num_instances, *smoothing, *blend_mode),
+            DrawCommand::RenderBitmapRegion {
+                binds,
+                size,
+                transform,
+                blend_mode,
+                ..
+            } => self.render_bitmap_region(binds, *size, transform, *blend_mode),
+            DrawCommand::RenderShapes {
+                shape,
+                instances,
+                num_instances,
+            } => self.render_shapes(shape, instances, *num_instances),
+            DrawCommand::DrawRects {
+                instances,
+                num_instances,
+            } => self.draw_rects(instances, *num_instances),
             DrawCommand::RenderTexture {
                 _texture,
                 binds,
                 transform,
                 blend_mode,
             } => self.render_texture(transform, binds, *blend_mode),
-            DrawCommand::RenderShape { shape, transform } => self.render_shape(*shape, transform),
+            DrawCommand::RenderShape {
+                shape,
+                transform,
+                ratio,
+            } => self.render_shape(shape, transform, *ratio),
             DrawCommand::DrawRect { color, matrix } => self.draw_rect(color, matrix),
             DrawCommand::PushMask => self.push_mask(),
+            DrawCommand::PushInvertedMask { cover } => self.push_inverted_mask(cover),
             DrawCommand::ActivateMask => self.activate_mask(),
             DrawCommand::DeactivateMask => self.deactivate_mask(),
+            DrawCommand::DeactivateInvertedMask => self.deactivate_inverted_mask(),
             DrawCommand::PopMask => self.pop_mask(),
+            DrawCommand::PopInvertedMask { cover } => self.pop_inverted_mask(cover),
+            DrawCommand::SetScissor(scissor) => self.set_scissor(*scissor),
+        }
+    }
+
+    fn set_pipeline(&mut self, pipeline: &'pass wgpu::RenderPipeline) {
+        if self
+            .bound_pipeline
+            .map_or(false, |bound| std::ptr::eq(bound, pipeline))
+        {
+            return;
+        }
+        self.render_pass.set_pipeline(pipeline);
+        self.bound_pipeline = Some(pipeline);
+        // Push constants don't survive a change of pipeline layout
+        self.bound_transform = None;
+        self.stats.pipeline_switches += 1;
+    }
+
+    /// Binds the bitmap or gradient that the following draws are filled with.
+    fn set_fill(&mut self, bind_group: &'pass wgpu::BindGroup) {
+        if self
+            .bound_fill
+            .map_or(false, |bound| std::ptr::eq(bound, bind_group))
+        {
+            return;
+        }
+        self.render_pass.set_bind_group(
+            if self.descriptors.limits.max_push_constant_size > 0 {
+                1
+            } else {
+                3
+            },
+            bind_group,
+            &[],
+        );
+        self.bound_fill = Some(bind_group);
+        self.stats.bind_group_switches += 1;
+    }
+
+    fn set_stencil_reference(&mut self, reference: u32) {
+        if self.stencil_reference != Some(reference) {
+            self.render_pass.set_stencil_reference(reference);
+            self.stencil_reference = Some(reference);
         }
     }
 
     pub fn prep_color(&mut self) {
         if self.needs_depth {
-            self.render_pass
-                .set_pipeline(self.pipelines.color.pipeline_for(self.mask_state));
+            self.set_pipeline(self.pipelines.color.pipeline_for(self.mask_state));
         } else {
-            self.render_pass
-                .set_pipeline(self.pipelines.color.depthless_pipeline());
+            self.set_pipeline(self.pipelines.color.depthless_pipeline());
         }
     }
 
+    pub fn prep_morph(&mut self, ratio: u16) {
+        if self.needs_depth {
+            self.set_pipeline(self.pipelines.morph.pipeline_for(self.mask_state));
+        } else {
+            self.set_pipeline(self.pipelines.morph.depthless_pipeline());
+        }
+
+        let offset = ratio as wgpu::BufferAddress * std::mem::size_of::<f32>() as u64;
+        self.render_pass.set_vertex_buffer(
+            1,
+            self.descriptors
+                .morph_ratios
+                .slice(offset..offset + std::mem::size_of::<f32>() as u64),
+        );
+    }
+
     pub fn prep_gradient(
         &mut self,
         bind_group: &'pass wgpu::BindGroup,
@@ -119,42 +263,36 @@ impl<'pass, 'frame: 'pass, 'global: 'frame> CommandRenderer<'pass, 'frame, 'glob
         spread: GradientSpread,
     ) {
         if self.needs_depth {
-            self.render_pass
-                .set_pipeline(self.pipelines.gradients[mode][spread].pipeline_for(self.mask_state));
+            self.set_pipeline(self.pipelines.gradients[mode][spread].pipeline_for(self.mask_state));
         } else {
-            self.render_pass
-                .set_pipeline(self.pipelines.gradients[mode][spread].depthless_pipeline());
+            self.set_pipeline(self.pipelines.gradients[mode][spread].depthless_pipeline());
         }
 
-        self.render_pass.set_bind_group(
-            if self.descriptors.limits.max_push_constant_size > 0 {
-                1
-            } else {
-                3
-            },
-            bind_group,
-            &[],
-        );
+        self.set_fill(bind_group);
     }
 
     pub fn prep_bitmap(&mut self, bind_group: &'pass wgpu::BindGroup, blend_mode: TrivialBlend) {
         if self.needs_depth {
-            self.render_pass
-                .set_pipeline(self.pipelines.bitmap[blend_mode].pipeline_for(self.mask_state));
+            self.set_pipeline(self.pipelines.bitmap[blend_mode].pipeline_for(self.mask_state));
         } else {
-            self.render_pass
-                .set_pipeline(self.pipelines.bitmap[blend_mode].depthless_pipeline());
+            self.set_pipeline(self.pipelines.bitmap[blend_mode].depthless_pipeline());
         }
 
-        self.render_pass.set_bind_group(
-            if self.descriptors.limits.max_push_constant_size > 0 {
-                1
-            } else {
-                3
-            },
-            bind_group,
-            &[],
-        );
+        self.set_fill(bind_group);
+    }
+
+    pub fn prep_textured_triangles(&mut self, bind_group: &'pass wgpu::BindGroup) {
+        if self.needs_depth {
+            self.set_pipeline(
+                self.pipelines
+                    .textured_triangles
+                    .pipeline_for(self.mask_state),
+            );
+        } else {
+            self.set_pipeline(self.pipelines.textured_triangles.depthless_pipeline());
+        }
+
+        self.set_fill(bind_group);
     }
 
     pub fn draw(
@@ -168,20 +306,54 @@ impl<'pass, 'frame: 'pass, 'global: 'frame> CommandRenderer<'pass, 'frame, 'glob
             .set_index_buffer(indices, wgpu::IndexFormat::Uint32);
 
         self.render_pass.draw_indexed(0..num_indices, 0, 0..1);
+        self.count_draw(num_indices / 3);
     }
 
-    pub fn apply_transform(&mut self, matrix: &Matrix, color_adjustments: &ColorTransform) {
-        let world_matrix = [
-            [matrix.a, matrix.b, 0.0, 0.0],
-            [matrix.c, matrix.d, 0.0, 0.0],
-            [0.0, 0.0, 1.0, 0.0],
-            [
-                matrix.tx.to_pixels() as f32,
-                matrix.ty.to_pixels() as f32,
-                0.0,
-                1.0,
-            ],
-        ];
+    /// Counts a draw call that was just issued, of `triangles` triangles in total.
+    fn count_draw(&mut self, triangles: u32) {
+        self.stats.draw_calls += 1;
+        self.stats.triangles += triangles;
+        if matches!(
+            self.mask_state,
+            MaskState::DrawMaskStencil | MaskState::ClearMaskStencil
+        ) {
+            self.stats.stencil_draws += 1;
+        }
+    }
+
+    /// Draws every instance of a solid color fill with the instanced color pipeline.
+    fn draw_color_instances(
+        &mut self,
+        vertices: wgpu::BufferSlice<'pass>,
+        indices: wgpu::BufferSlice<'pass>,
+        num_indices: u32,
+        instances: &'frame wgpu::Buffer,
+        num_instances: u32,
+    ) {
+        if self.needs_depth {
+            self.set_pipeline(self.pipelines.color_instanced.pipeline_for(self.mask_state));
+        } else {
+            self.set_pipeline(self.pipelines.color_instanced.depthless_pipeline());
+        }
+
+        self.render_pass.set_vertex_buffer(0, vertices);
+        self.render_pass.set_vertex_buffer(1, instances.slice(..));
+        self.render_pass
+            .set_index_buffer(indices, wgpu::IndexFormat::Uint32);
+        self.render_pass
+            .draw_indexed(0..num_indices, 0, 0..num_instances);
+        self.count_draw(num_indices / 3 * num_instances);
+    }
+
+    pub fn apply_transform(
+        &mut self,
+        world_matrix: [[f32; 4]; 4],
+        color_adjustments: &ColorTransform,
+    ) {
+        if self.bound_transform == Some((world_matrix, *color_adjustments)) {
+            return;
+        }
+        self.bound_transform = Some((world_matrix, *color_adjustments));
 
         if self.descriptors.limits.max_push_constant_size > 0 {
             self.render_pass.set_push_constants(
@@ -196,7 +368,6 @@ impl<'pass, 'frame: 'pass, 'global: 'frame> CommandRenderer<'pass, 'frame, 'glob
             self.uniform_buffers.write_uniforms(
                 &self.descriptors.device,
                 &self.descriptors.bind_layouts.transforms,
-                self.uniform_encoder,
                 &mut self.render_pass,
                 1,
                 &Transforms { world_matrix },
@@ -212,7 +383,6 @@ impl<'pass, 'frame: 'pass, 'global: 'frame> CommandRenderer<'pass, 'frame, 'glob
                 self.color_buffers.write_uniforms(
                     &self.descriptors.device,
                     &self.descriptors.bind_layouts.color_transforms,
-                    self.uniform_encoder,
                     &mut self.render_pass,
                     2,
                     &ColorAdjustments::from(*color_adjustments),
@@ -235,22 +405,52 @@ impl<'pass, 'frame: 'pass, 'global: 'frame> CommandRenderer<'pass, 'frame, 'glob
         let texture = as_texture(bitmap);
 
         let descriptors = self.descriptors;
-        let bind = texture.bind_group(
-            smoothing,
-            &descriptors.device,
-            &descriptors.bind_layouts.bitmap,
-            &descriptors.quad,
-            bitmap.clone(),
-            &descriptors.bitmap_samplers,
-        );
+        let anisotropic = self.anisotropic_filtering
+            && transform.matrix.anisotropy() > ANISOTROPIC_FILTERING_THRESHOLD;
+        let bind = texture.bind_group(smoothing, anisotropic, descriptors, bitmap.clone());
         self.prep_bitmap(&bind.bind_group, blend_mode);
         self.apply_transform(
-            &(transform.matrix
-                * Matrix {
+            transform_world_matrix(
+                transform,
+                Matrix {
                     a: texture.width as f32,
                     d: texture.height as f32,
                     ..Default::default()
-                }),
+                },
+            ),
+            &transform.color_transform,
+        );
+
+        self.draw(
+            self.descriptors.quad.vertices_pos.slice(..),
+            self.descriptors.quad.indices.slice(..),
+            6,
+        );
+        if cfg!(feature = "render_debug_labels") {
+            self.render_pass.pop_debug_group();
+        }
+    }
+
+    pub fn render_bitmap_region(
+        &mut self,
+        binds: &'frame BitmapBinds,
+        size: (u32, u32),
+        transform: &Transform,
+        blend_mode: TrivialBlend,
+    ) {
+        if cfg!(feature = "render_debug_labels") {
+            self.render_pass.push_debug_group("render_bitmap_region");
+        }
+        self.prep_bitmap(&binds.bind_group, blend_mode);
+        self.apply_transform(
+            transform_world_matrix(
+                transform,
+                Matrix {
+                    a: size.0 as f32,
+                    d: size.1 as f32,
+                    ..Default::default()
+                },
+            ),
             &transform.color_transform,
         );
 
@@ -264,6 +464,85 @@ impl<'pass, 'frame: 'pass, 'global: 'frame> CommandRenderer<'pass, 'frame, 'glob
         }
     }
 
+    pub fn render_bitmaps(
+        &mut self,
+        bitmap: &'frame BitmapHandle,
+        instances: &'frame wgpu::Buffer,
+        num_instances: u32,
+        smoothing: bool,
+        blend_mode: TrivialBlend,
+    ) {
+        if cfg!(feature = "render_debug_labels") {
+            self.render_pass
+                .push_debug_group(&format!("render_bitmaps {:?} x{}", bitmap.0, num_instances));
+        }
+        let texture = as_texture(bitmap);
+
+        let descriptors = self.descriptors;
+        // Every instance has its own transform, so they're all drawn without anisotropic filtering
+        let bind = texture.bind_group(smoothing, false, descriptors, bitmap.clone());
+        self.draw_bitmap_instances(&bind.bind_group, instances, num_instances, blend_mode);
+        if cfg!(feature = "render_debug_labels") {
+            self.render_pass.pop_debug_group();
+        }
+    }
+
+    /// Draws copies of many different bitmaps at once, which are all packed into the atlas.
+    pub fn render_atlas_bitmaps(
+        &mut self,
+        instances: &'frame wgpu::Buffer,
+        num_instances: u32,
+        smoothing: bool,
+        blend_mode: TrivialBlend,
+    ) {
+        if cfg!(feature = "render_debug_labels") {
+            self.render_pass
+                .push_debug_group(&format!("render_atlas_bitmaps x{num_instances}"));
+        }
+        let descriptors = self.descriptors;
+        let binds = descriptors.bitmap_atlas().binds(smoothing);
+        self.draw_bitmap_instances(&binds.bind_group, instances, num_instances, blend_mode);
+        if cfg!(feature = "render_debug_labels") {
+            self.render_pass.pop_debug_group();
+        }
+    }
+
+    /// Draws every instance of a bitmap quad with the instanced bitmap pipeline.
+    fn draw_bitmap_instances(
+        &mut self,
+        bind_group: &'pass wgpu::BindGroup,
+        instances: &'frame wgpu::Buffer,
+        num_instances: u32,
+        blend_mode: TrivialBlend,
+    ) {
+        if self.needs_depth {
+            self.set_pipeline(
+                self.pipelines.bitmap_instanced[blend_mode].pipeline_for(self.mask_state),
+            );
+        } else {
+            self.set_pipeline(self.pipelines.bitmap_instanced[blend_mode].depthless_pipeline());
+        }
+        if self.descriptors.limits.max_push_constant_size > 0 {
+            self.set_fill(bind_group);
+        } else {
+            // Without push constants, this is where the transforms of other draws are bound
+            self.render_pass.set_bind_group(1, bind_group, &[]);
+            self.bound_transform = None;
+            self.stats.bind_group_switches += 1;
+        }
+
+        let descriptors = self.descriptors;
+        self.render_pass
+            .set_vertex_buffer(0, descriptors.quad.vertices_pos.slice(..));
+        self.render_pass.set_vertex_buffer(1, instances.slice(..));
+        self.render_pass.set_index_buffer(
+            descriptors.quad.indices.slice(..),
+            wgpu::IndexFormat::Uint32,
+        );
+        self.render_pass.draw_indexed(0..6, 0, 0..num_instances);
+        self.count_draw(2 * num_instances);
+    }
+
     pub fn render_texture(
         &mut self,
         transform: &Transform,
@@ -274,7 +553,7 @@ impl<'pass, 'frame: 'pass, 'global: 'frame> CommandRenderer<'pass, 'frame, 'glob
             self.render_pass.push_debug_group("render_texture");
         }
         self.prep_bitmap(bind_group, blend_mode);
-        self.apply_transform(&transform.matrix, &transform.color_transform);
+        self.apply_transform(world_matrix(&transform.matrix), &transform.color_transform);
 
         self.draw(
             self.descriptors.quad.vertices_pos.slice(..),
@@ -286,13 +565,13 @@ impl<'pass, 'frame: 'pass, 'global: 'frame> CommandRenderer<'pass, 'frame, 'glob
         }
     }
 
-    pub fn render_shape(&mut self, shape: ShapeHandle, transform: &Transform) {
+    pub fn render_shape(&mut self, shape: &ShapeHandle, transform: &Transform, ratio: u16) {
         if cfg!(feature = "render_debug_labels") {
             self.render_pass
-                .push_debug_group(&format!("render_shape {}", shape.0));
+                .push_debug_group(&format!("render_shape {}", shape.index()));
         }
 
-        let mesh = &self.meshes[shape.0];
+        let mesh = &self.meshes[shape.index()];
         for draw in &mesh.draws {
             let num_indices = if self.mask_state != MaskState::DrawMaskStencil
                 && self.mask_state != MaskState::ClearMaskStencil
@@ -310,6 +589,9 @@ impl<'pass, 'frame: 'pass, 'global: 'frame> CommandRenderer<'pass, 'frame, 'glob
                 DrawType::Color => {
                     self.prep_color();
                 }
+                DrawType::Morph => {
+                    self.prep_morph(ratio);
+                }
                 DrawType::Gradient {
                     bind_group,
                     spread,
@@ -318,11 +600,17 @@ impl<'pass, 'frame: 'pass, 'global: 'frame> CommandRenderer<'pass, 'frame, 'glob
                 } => {
                     self.prep_gradient(bind_group, *mode, *spread);
                 }
-                DrawType::Bitmap { binds, .. } => {
-                    self.prep_bitmap(&binds.bind_group, TrivialBlend::Normal);
+                DrawType::Bitmap { binds } => {
+                    self.prep_bitmap(binds.bind_group(self.allow_smoothing), TrivialBlend::Normal);
+                }
+                DrawType::TexturedTriangles { binds } => {
+                    self.prep_textured_triangles(binds.bind_group(self.allow_smoothing));
                 }
             }
-            self.apply_transform(&transform.matrix, &transform.color_transform);
+            self.apply_transform(
+                transform_world_matrix(transform, Matrix::IDENTITY),
+                &transform.color_transform,
+            );
 
             self.draw(
                 mesh.vertex_buffer.slice(draw.vertices.clone()),
@@ -335,6 +623,61 @@ impl<'pass, 'frame: 'pass, 'global: 'frame> CommandRenderer<'pass, 'frame, 'glob
         }
     }
 
+    pub fn render_shapes(
+        &mut self,
+        shape: &ShapeHandle,
+        instances: &'frame wgpu::Buffer,
+        num_instances: u32,
+    ) {
+        if cfg!(feature = "render_debug_labels") {
+            self.render_pass.push_debug_group(&format!(
+                "render_shapes {} x{}",
+                shape.index(),
+                num_instances
+            ));
+        }
+
+        // Only shapes made of a single solid color draw are batched
+        let mesh = &self.meshes[shape.index()];
+        let draw = &mesh.draws[0];
+        let num_indices = if self.mask_state != MaskState::DrawMaskStencil
+            && self.mask_state != MaskState::ClearMaskStencil
+        {
+            draw.num_indices
+        } else {
+            draw.num_mask_indices
+        };
+        if num_indices > 0 {
+            self.draw_color_instances(
+                mesh.vertex_buffer.slice(draw.vertices.clone()),
+                mesh.index_buffer.slice(draw.indices.clone()),
+                num_indices,
+                instances,
+                num_instances,
+            );
+        }
+        if cfg!(feature = "render_debug_labels") {
+            self.render_pass.pop_debug_group();
+        }
+    }
+
+    pub fn draw_rects(&mut self, instances: &'frame wgpu::Buffer, num_instances: u32) {
+        if cfg!(feature = "render_debug_labels") {
+            self.render_pass
+                .push_debug_group(&format!("draw_rects x{num_instances}"));
+        }
+        self.draw_color_instances(
+            self.descriptors.quad.vertices_pos_color.slice(..),
+            self.descriptors.quad.indices.slice(..),
+            6,
+            instances,
+            num_instances,
+        );
+        if cfg!(feature = "render_debug_labels") {
+            self.render_pass.pop_debug_group();
+        }
+    }
+
     pub fn draw_rect(&mut self, color: &Color, matrix: &Matrix) {
         if cfg!(feature = "render_debug_labels") {
             self.render_pass.push_debug_group("draw_rect");
@@ -342,10 +685,10 @@ impl<'pass, 'frame: 'pass, 'global: 'frame> CommandRenderer<'pass, 'frame, 'glob
         self.prep_color();
 
         if color == &Color::WHITE {
-            self.apply_transform(matrix, &ColorTransform::IDENTITY);
+            self.apply_transform(world_matrix(matrix), &ColorTransform::IDENTITY);
         } else {
             self.apply_transform(
-                matrix,
+                world_matrix(matrix),
                 &ColorTransform {
                     r_mult: Fixed8::from_f32(f32::from(color.r) / 255.0),
                     g_mult: Fixed8::from_f32(f32::from(color.g) / 255.0),
@@ -366,36 +709,112 @@ impl<'pass, 'frame: 'pass, 'global: 'frame> CommandRenderer<'pass, 'frame, 'glob
         }
     }
 
+    fn set_mask_state(&mut self, mask_state: MaskState) {
+        self.mask_state = mask_state;
+        self.set_stencil_reference(mask_state.stencil_reference(self.num_masks));
+    }
+
+    /// Whether the current mask is nested too deeply for the stencil buffer, and so is ignored.
+    ///
+    /// Such a mask is drawn at the deepest stencil level there is, where drawing the stencil
+    /// doesn't change it, so it only needs to avoid clearing that level when it's deactivated.
+    fn is_mask_ignored(&self) -> bool {
+        self.num_masks > MAX_MASK_DEPTH
+    }
+
     pub fn push_mask(&mut self) {
         debug_assert!(
             self.mask_state == MaskState::NoMask || self.mask_state == MaskState::DrawMaskedContent
         );
         self.num_masks += 1;
-        self.mask_state = MaskState::DrawMaskStencil;
-        self.render_pass.set_stencil_reference(self.num_masks - 1);
+        self.set_mask_state(MaskState::DrawMaskStencil);
+    }
+
+    /// Starts a mask whose content is drawn everywhere within `cover` except for the mask itself.
+    ///
+    /// This raises the stencil of the whole parent mask area to the new level first, so that
+    /// the mask's shapes are then drawn by lowering it again, with the same pipelines and
+    /// stencil references that an ordinary mask uses to clear itself.
+    pub fn push_inverted_mask(&mut self, cover: &Matrix) {
+        self.push_mask();
+        if self.is_mask_ignored() {
+            return;
+        }
+        self.draw_rect(&Color::WHITE, cover);
+        self.set_mask_state(MaskState::ClearMaskStencil);
     }
 
     pub fn activate_mask(&mut self) {
-        debug_assert!(self.num_masks > 0 && self.mask_state == MaskState::DrawMaskStencil);
-        self.mask_state = MaskState::DrawMaskedContent;
-        self.render_pass.set_stencil_reference(self.num_masks);
+        if self.num_masks == 0 {
+            tracing::warn!("Tried to activate a mask that wasn't pushed");
+            return;
+        }
+        // Inverted masks are drawn by clearing the stencil, see `push_inverted_mask`
+        debug_assert!(
+            self.mask_state == MaskState::DrawMaskStencil
+                || self.mask_state == MaskState::ClearMaskStencil
+        );
+        self.set_mask_state(MaskState::DrawMaskedContent);
     }
 
     pub fn deactivate_mask(&mut self) {
-        debug_assert!(self.num_masks > 0 && self.mask_state == MaskState::DrawMaskedContent);
-        self.mask_state = MaskState::ClearMaskStencil;
-        self.render_pass.set_stencil_reference(self.num_masks);
+        if self.num_masks == 0 {
+            tracing::warn!("Tried to deactivate a mask that wasn't pushed");
+            return;
+        }
+        debug_assert!(self.mask_state == MaskState::DrawMaskedContent);
+        if self.is_mask_ignored() {
+            self.set_mask_state(MaskState::DrawMaskStencil);
+        } else {
+            self.set_mask_state(MaskState::ClearMaskStencil);
+        }
+    }
+
+    /// Undoes the lowering of the stencil by the shapes of an inverted mask,
+    /// which are drawn again after this.
+    pub fn deactivate_inverted_mask(&mut self) {
+        if self.num_masks == 0 {
+            tracing::warn!("Tried to deactivate a mask that wasn't pushed");
+            return;
+        }
+        debug_assert!(self.mask_state == MaskState::DrawMaskedContent);
+        self.set_mask_state(MaskState::DrawMaskStencil);
+    }
+
+    /// Lowers the stencil of everything within `cover` back to the parent mask's level.
+    pub fn pop_inverted_mask(&mut self, cover: &Matrix) {
+        if self.num_masks > 0 && !self.is_mask_ignored() {
+            debug_assert!(self.mask_state == MaskState::DrawMaskStencil);
+            self.set_mask_state(MaskState::ClearMaskStencil);
+            self.draw_rect(&Color::WHITE, cover);
+        }
+        self.pop_mask();
     }
 
     pub fn pop_mask(&mut self) {
-        debug_assert!(self.num_masks > 0 && self.mask_state == MaskState::ClearMaskStencil);
+        if self.num_masks == 0 {
+            tracing::warn!("Tried to pop a mask that wasn't pushed");
+            return;
+        }
+        // Ignored masks never clear the stencil, see `is_mask_ignored`
+        debug_assert!(
+            self.mask_state == MaskState::ClearMaskStencil
+                || (self.is_mask_ignored() && self.mask_state == MaskState::DrawMaskStencil)
+        );
         self.num_masks -= 1;
-        self.render_pass.set_stencil_reference(self.num_masks);
         if self.num_masks == 0 {
-            self.mask_state = MaskState::NoMask;
+            self.set_mask_state(MaskState::NoMask);
         } else {
-            self.mask_state = MaskState::DrawMaskedContent;
-        };
+            self.set_mask_state(MaskState::DrawMaskedContent);
+        }
+    }
+
+    /// Clips everything that's drawn after this to `scissor`.
+    pub fn set_scissor(&mut self, scissor: ScissorRect) {
+        if self.scissor != scissor {
+            scissor.apply(&mut self.render_pass);
+            self.scissor = scissor;
+        }
     }
 
     pub fn num_masks(&self) -> u32 {
@@ -405,6 +824,105 @@ impl<'pass, 'frame: 'pass, 'global: 'frame> CommandRenderer<'pass, 'frame, 'glob
     pub fn mask_state(&self) -> MaskState {
         self.mask_state
     }
+
+    pub fn scissor(&self) -> ScissorRect {
+        self.scissor
+    }
+
+    /// The draw calls, triangles and state changes that have been issued so far.
+    pub fn stats(&self) -> RenderStatistics {
+        self.stats
+    }
+}
+
+/// The area of a target, in whole pixels, that draws are clipped to.
+///
+/// This stands in for the stencil when a mask is a rectangle that's aligned with the target,
+/// which saves drawing the mask into the stencil and clearing it again.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ScissorRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl ScissorRect {
+    /// The whole of a target of the given size.
+    pub fn full(width: u32, height: u32) -> Self {
+        Self {
+            x: 0,
+            y: 0,
+            width,
+            height,
+        }
+    }
+
+    /// The pixels within `self` that a rectangle drawn with `matrix` covers, or `None` if
+    /// the rectangle is rotated or skewed, or covers nothing.
+    ///
+    /// Without multisampling, a pixel is covered when its center is inside of the rectangle,
+    /// which is the same as rounding the edges of the rectangle to the nearest pixel.
+    fn clip_rect(self, matrix: &Matrix) -> Option<Self> {
+        if matrix.b != 0.0 || matrix.c != 0.0 || !matrix.a.is_finite() || !matrix.d.is_finite() {
+            return None;
+        }
+        let edges = |start: f64, size: f32, min: u32, max: u32| {
+            let end = start + f64::from(size);
+            let clamp = |edge: f64| edge.round().clamp(f64::from(min), f64::from(max)) as u32;
+            (clamp(start.min(end)), clamp(start.max(end)))
+        };
+        let (left, right) = edges(matrix.tx.to_pixels(), matrix.a, self.x, self.x + self.width);
+        let (top, bottom) = edges(
+            matrix.ty.to_pixels(),
+            matrix.d,
+            self.y,
+            self.y + self.height,
+        );
+        if left == right || top == bottom {
+            return None;
+        }
+        Some(Self {
+            x: left,
+            y: top,
+            width: right - left,
+            height: bottom - top,
+        })
+    }
+
+    /// Whether anything within `bounds` could be drawn inside of `self`, which is assumed when
+    /// the bounds are invalid.
+    ///
+    /// The bounds are allowed to be a pixel off, as antialiasing and hairline strokes can
+    /// reach slightly outside of them.
+    fn intersects(self, bounds: &BoundingBox) -> bool {
+        if !bounds.valid {
+            return true;
+        }
+        let margin = Twips::ONE;
+        let clip = BoundingBox {
+            x_min: Twips::from_pixels(self.x.into()) - margin,
+            y_min: Twips::from_pixels(self.y.into()) - margin,
+            x_max: Twips::from_pixels((self.x + self.width).into()) + margin,
+            y_max: Twips::from_pixels((self.y + self.height).into()) + margin,
+            valid: true,
+        };
+        clip.intersects(bounds)
+    }
+
+    pub fn apply(self, render_pass: &mut wgpu::RenderPass) {
+        render_pass.set_scissor_rect(self.x, self.y, self.width, self.height);
+    }
+}
+
+/// How a mask that's currently pushed in `chunk_blends` is drawn.
+enum PushedMask {
+    Stencil,
+    Inverted,
+    /// A rectangle that's drawn as a scissor rect, which restores `previous` once it's popped.
+    Scissor {
+        previous: ScissorRect,
+    },
 }
 
 pub enum Chunk {
@@ -420,6 +938,34 @@ pub enum DrawCommand {
         smoothing: bool,
         blend_mode: TrivialBlend,
     },
+    /// A region of a bitmap, drawn with texture transforms that map the quad onto it.
+    RenderBitmapRegion {
+        _bitmap: BitmapHandle,
+        _texture_transforms: wgpu::Buffer,
+        binds: BitmapBinds,
+        /// The size of the region, which the quad is scaled to.
+        size: (u32, u32),
+        transform: Transform,
+        blend_mode: TrivialBlend,
+    },
+    /// Many copies of the same bitmap, drawn with a single instanced draw call.
+    RenderBitmaps {
+        bitmap: BitmapHandle,
+        instances: Arc<PoolEntry<wgpu::Buffer>>,
+        num_instances: u32,
+        smoothing: bool,
+        blend_mode: TrivialBlend,
+    },
+    /// Copies of many different bitmaps that are all packed into the atlas,
+    /// drawn with a single instanced draw call.
+    RenderAtlasBitmaps {
+        /// Kept alive until they're drawn, so that their places in the atlas aren't reused.
+        _bitmaps: Vec<BitmapHandle>,
+        instances: Arc<PoolEntry<wgpu::Buffer>>,
+        num_instances: u32,
+        smoothing: bool,
+        blend_mode: TrivialBlend,
+    },
     RenderTexture {
         _texture: PoolOrArcTexture,
         binds: wgpu::BindGroup,
@@ -429,15 +975,38 @@ pub enum DrawCommand {
     RenderShape {
         shape: ShapeHandle,
         transform: Transform,
+        /// The morph ratio to draw a morph shape at, which other shapes ignore.
+        ratio: u16,
+    },
+    /// Many copies of the same solid color shape, drawn with a single instanced draw call.
+    RenderShapes {
+        shape: ShapeHandle,
+        instances: Arc<PoolEntry<wgpu::Buffer>>,
+        num_instances: u32,
     },
     DrawRect {
         color: Color,
         matrix: Matrix,
     },
+    /// Many solid color rectangles, drawn with a single instanced draw call.
+    DrawRects {
+        instances: Arc<PoolEntry<wgpu::Buffer>>,
+        num_instances: u32,
+    },
     PushMask,
+    /// An inverted mask, where `cover` is a rectangle over the whole target.
+    PushInvertedMask {
+        cover: Matrix,
+    },
     ActivateMask,
     DeactivateMask,
+    DeactivateInvertedMask,
     PopMask,
+    PopInvertedMask {
+        cover: Matrix,
+    },
+    /// Clips everything that's drawn after this, in place of a rectangular mask.
+    SetScissor(ScissorRect),
 }
 
 /// Replaces every blend with a RenderBitmap, with the subcommands rendered out to a temporary texture
@@ -448,7 +1017,6 @@ pub fn chunk_blends<'a>(
     descriptors: &'a Descriptors,
     uniform_buffers: &mut UniformBuffer<'a, Transforms>,
     color_buffers: &mut UniformBuffer<'a, ColorAdjustments>,
-    uniform_encoder: &mut wgpu::CommandEncoder,
     draw_encoder: &mut wgpu::CommandEncoder,
     meshes: &'a Vec<Mesh>,
     quality: StageQuality,
@@ -456,15 +1024,28 @@ pub fn chunk_blends<'a>(
     height: u32,
     nearest_layer: &CommandTarget,
     texture_pool: &mut TexturePool,
+    stats: &mut RenderStatistics,
 ) -> Vec<Chunk> {
     let mut result = vec![];
     let mut current = vec![];
     let mut needs_depth = false;
     let mut num_masks = 0;
+    let mut masks = vec![];
+    let mut scissor = ScissorRect::full(width, height);
+    let cover = Matrix::scale(width as f32, height as f32);
+    // Nothing is smoothed at low quality, whatever the bitmap asks for
+    let allow_smoothing = quality != StageQuality::Low;
 
-    for command in commands {
+    let mut commands = commands.into_iter();
+    while let Some(command) = commands.next() {
         match command {
-            Command::Blend(commands, blend_mode) => {
+            Command::Blend(mut commands, blend_mode, color_transform) => {
+                let blend_type = BlendType::from(blend_mode);
+                if let BlendType::Complex(_) = blend_type {
+                    // Complex blends need the unmodified group to blend with, so the
+                    // color transform is applied to its contents instead.
+                    commands.apply_color_transform(color_transform);
+                }
                 let mut surface = Surface::new(
                     descriptors,
                     quality,
@@ -472,7 +1053,7 @@ pub fn chunk_blends<'a>(
                     height,
                     wgpu::TextureFormat::Rgba8Unorm,
                 );
-                let clear_color = BlendType::from(blend_mode).default_color();
+                let clear_color = blend_type.default_color();
                 let target = surface.draw_commands(
                     RenderTargetMode::FreshBuffer(clear_color),
                     descriptors,
@@ -480,7 +1061,6 @@ pub fn chunk_blends<'a>(
                     commands,
                     uniform_buffers,
                     color_buffers,
-                    uniform_encoder,
                     draw_encoder,
                     if blend_mode == BlendMode::Layer {
                         None
@@ -490,54 +1070,26 @@ pub fn chunk_blends<'a>(
                     texture_pool,
                 );
                 target.ensure_cleared(draw_encoder);
+                *stats += surface.stats();
 
-                match BlendType::from(blend_mode) {
-                    BlendType::Trivial(blend_mode) => {
-                        let transform = Transform {
-                            matrix: Matrix::scale(target.width() as f32, target.height() as f32),
-                            color_transform: Default::default(),
-                        };
-                        let texture = target.take_color_texture();
-                        let bind_group =
-                            descriptors
-                                .device
-                                .create_bind_group(&wgpu::BindGroupDescriptor {
-                                    layout: &descriptors.bind_layouts.bitmap,
-                                    entries: &[
-                                        wgpu::BindGroupEntry {
-                                            binding: 0,
-                                            resource: descriptors
-                                                .quad
-                                                .texture_transforms
-                                                .as_entire_binding(),
-                                        },
-                                        wgpu::BindGroupEntry {
-                                            binding: 1,
-                                            resource: wgpu::BindingResource::TextureView(
-                                                texture.view(),
-                                            ),
-                                        },
-                                        wgpu::BindGroupEntry {
-                                            binding: 2,
-                                            resource: wgpu::BindingResource::Sampler(
-                                                descriptors
-                                                    .bitmap_samplers
-                                                    .get_sampler(false, false),
-                                            ),
-                                        },
-                                    ],
-                                    label: None,
-                                });
-                        current.push(DrawCommand::RenderTexture {
-                            _texture: texture,
-                            binds: bind_group,
-                            transform,
-                            blend_mode,
-                        })
-                    }
+                match blend_type {
+                    BlendType::Trivial(blend_mode) => current.push(composite_texture(
+                        descriptors,
+                        target,
+                        color_transform,
+                        blend_mode,
+                    )),
                     BlendType::Complex(blend_mode) => {
                         if !current.is_empty() {
-                            result.push(Chunk::Draw(std::mem::take(&mut current), needs_depth));
+                            result.push(Chunk::Draw(
+                                batch_draws(
+                                    std::mem::take(&mut current),
+                                    descriptors,
+                                    meshes,
+                                    texture_pool,
+                                ),
+                                needs_depth,
+                            ));
                         }
                         result.push(Chunk::Blend(
                             target.take_color_texture(),
@@ -548,6 +1100,92 @@ pub fn chunk_blends<'a>(
                     }
                 }
             }
+            Command::Filters(commands, filters) => {
+                let mut surface = Surface::new(
+                    descriptors,
+                    quality,
+                    width,
+                    height,
+                    wgpu::TextureFormat::Rgba8Unorm,
+                );
+                let target = surface.draw_commands(
+                    RenderTargetMode::FreshBuffer(wgpu::Color::TRANSPARENT),
+                    descriptors,
+                    meshes,
+                    commands,
+                    uniform_buffers,
+                    color_buffers,
+                    draw_encoder,
+                    Some(nearest_layer),
+                    texture_pool,
+                );
+                target.ensure_cleared(draw_encoder);
+                *stats += surface.stats();
+                // Each filter reads the output of the previous one, whose texture then goes
+                // back to the pool to be reused by the next filter along the chain
+                let mut filtered = target;
+                for filter in filters {
+                    let source = filtered;
+                    filtered = surface.run_filter(
+                        descriptors,
+                        draw_encoder,
+                        texture_pool,
+                        source.color_view(),
+                        (source.width(), source.height()),
+                        (0, 0),
+                        (source.width(), source.height()),
+                        filter,
+                    );
+                    filtered.ensure_cleared(draw_encoder);
+                }
+                current.push(composite_texture(
+                    descriptors,
+                    filtered,
+                    ColorTransform::IDENTITY,
+                    TrivialBlend::Normal,
+                ));
+            }
+            Command::AlphaMask(maskee, mask) => {
+                let mut surface = Surface::new(
+                    descriptors,
+                    quality,
+                    width,
+                    height,
+                    wgpu::TextureFormat::Rgba8Unorm,
+                );
+                let mut render_offscreen = |commands: CommandList| {
+                    let target = surface.draw_commands(
+                        RenderTargetMode::FreshBuffer(wgpu::Color::TRANSPARENT),
+                        descriptors,
+                        meshes,
+                        commands,
+                        uniform_buffers,
+                        color_buffers,
+                        draw_encoder,
+                        Some(nearest_layer),
+                        texture_pool,
+                    );
+                    target.ensure_cleared(draw_encoder);
+                    target
+                };
+                let maskee = render_offscreen(maskee);
+                let mask = render_offscreen(mask);
+                *stats += surface.stats();
+                let masked = surface.apply_alpha_mask(
+                    descriptors,
+                    texture_pool,
+                    draw_encoder,
+                    &maskee,
+                    &mask,
+                );
+                masked.ensure_cleared(draw_encoder);
+                current.push(composite_texture(
+                    descriptors,
+                    masked,
+                    ColorTransform::IDENTITY,
+                    TrivialBlend::Normal,
+                ));
+            }
             Command::RenderBitmap {
                 bitmap,
                 transform,
@@ -555,43 +1193,496 @@ pub fn chunk_blends<'a>(
             } => current.push(DrawCommand::RenderBitmap {
                 bitmap,
                 transform,
-                smoothing,
+                smoothing: smoothing && allow_smoothing,
                 blend_mode: TrivialBlend::Normal,
             }),
-            Command::RenderShape { shape, transform } => {
-                current.push(DrawCommand::RenderShape { shape, transform })
+            Command::RenderBitmapRegion {
+                bitmap,
+                region,
+                transform,
+                smoothing,
+            } => current.push(bitmap_region(
+                descriptors,
+                bitmap,
+                region,
+                transform,
+                smoothing && allow_smoothing,
+            )),
+            // Shapes and rectangles that are entirely outside of the scissor rect are skipped,
+            // which saves drawing the parts of large masks that are off of the target
+            Command::RenderShape { shape, transform }
+                if scissor.intersects(&shape_bounds(meshes, &shape, &transform)) =>
+            {
+                current.push(DrawCommand::RenderShape {
+                    shape,
+                    transform,
+                    ratio: 0,
+                })
+            }
+            Command::RenderMorphShape {
+                shape,
+                transform,
+                ratio,
+            } if scissor.intersects(&shape_bounds(meshes, &shape, &transform)) => {
+                current.push(DrawCommand::RenderShape {
+                    shape,
+                    transform,
+                    ratio,
+                })
             }
-            Command::DrawRect { color, matrix } => {
+            Command::DrawRect { color, matrix } if scissor.intersects(&rect_bounds(&matrix)) => {
                 current.push(DrawCommand::DrawRect { color, matrix })
             }
-            Command::PushMask => {
+            Command::RenderShape { .. }
+            | Command::RenderMorphShape { .. }
+            | Command::DrawRect { .. } => {}
+            Command::PushMask | Command::PushInvertedMask => {
+                // A mask that's only an axis-aligned rectangle can clip with a scissor rect instead
+                let rect = match (&command, commands.as_slice()) {
+                    (
+                        Command::PushMask,
+                        [Command::DrawRect { matrix, .. }, Command::ActivateMask, ..],
+                    ) => scissor.clip_rect(matrix),
+                    _ => None,
+                };
+                if let Some(rect) = rect {
+                    // The rectangle and the activation of the mask are replaced by the scissor
+                    commands.nth(1);
+                    masks.push(PushedMask::Scissor { previous: scissor });
+                    scissor = rect;
+                    current.push(DrawCommand::SetScissor(scissor));
+                    continue;
+                }
                 needs_depth = true;
                 num_masks += 1;
-                current.push(DrawCommand::PushMask);
+                if num_masks == MAX_MASK_DEPTH + 1 {
+                    tracing::warn!(
+                        "Masks are nested more than {MAX_MASK_DEPTH} deep, ignoring the deeper ones"
+                    );
+                }
+                if matches!(command, Command::PushInvertedMask) {
+                    masks.push(PushedMask::Inverted);
+                    current.push(DrawCommand::PushInvertedMask { cover });
+                } else {
+                    masks.push(PushedMask::Stencil);
+                    current.push(DrawCommand::PushMask);
+                }
             }
             Command::ActivateMask => {
                 needs_depth = true;
                 current.push(DrawCommand::ActivateMask);
             }
-            Command::DeactivateMask => {
-                needs_depth = true;
-                current.push(DrawCommand::DeactivateMask);
-            }
-            Command::PopMask => {
-                needs_depth = true;
-                num_masks -= 1;
-                current.push(DrawCommand::PopMask);
-            }
+            Command::DeactivateMask => match masks.last() {
+                Some(PushedMask::Scissor { previous }) => {
+                    // There's no stencil to clear, so the rectangle isn't drawn again either
+                    scissor = *previous;
+                    masks.pop();
+                    current.push(DrawCommand::SetScissor(scissor));
+                    skip_mask(&mut commands);
+                }
+                Some(PushedMask::Inverted) => {
+                    needs_depth = true;
+                    current.push(DrawCommand::DeactivateInvertedMask);
+                }
+                Some(PushedMask::Stencil) | None => {
+                    needs_depth = true;
+                    current.push(DrawCommand::DeactivateMask);
+                }
+            },
+            Command::PopMask => match masks.pop() {
+                Some(PushedMask::Scissor { previous }) => {
+                    scissor = previous;
+                    current.push(DrawCommand::SetScissor(scissor));
+                }
+                Some(PushedMask::Inverted) => {
+                    needs_depth = true;
+                    num_masks = num_masks.saturating_sub(1);
+                    current.push(DrawCommand::PopInvertedMask { cover });
+                }
+                Some(PushedMask::Stencil) | None => {
+                    needs_depth = true;
+                    num_masks = num_masks.saturating_sub(1);
+                    current.push(DrawCommand::PopMask);
+                }
+            },
         }
     }
 
     if !current.is_empty() {
-        result.push(Chunk::Draw(current, needs_depth));
+        result.push(Chunk::Draw(
+            batch_draws(current, descriptors, meshes, texture_pool),
+            needs_depth,
+        ));
     }
 
     result
 }
 
+/// The area covered by `shape` when it's drawn with `transform`, which is left invalid for
+/// 3D transforms.
+fn shape_bounds(meshes: &[Mesh], shape: &ShapeHandle, transform: &Transform) -> BoundingBox {
+    if transform.matrix3d.is_some() {
+        return BoundingBox::default();
+    }
+    meshes[shape.index()].bounds.transform(&transform.matrix)
+}
+
+/// The area covered by a rectangle drawn with `matrix`.
+fn rect_bounds(matrix: &Matrix) -> BoundingBox {
+    BoundingBox {
+        x_min: Twips::ZERO,
+        y_min: Twips::ZERO,
+        x_max: Twips::ONE,
+        y_max: Twips::ONE,
+        valid: true,
+    }
+    .transform(matrix)
+}
+
+/// Skips the commands that draw a mask again after it's deactivated, up to and including
+/// the `PopMask` that ends it.
+fn skip_mask(commands: &mut impl Iterator<Item = Command>) {
+    let mut depth = 0;
+    for command in commands {
+        match command {
+            Command::PushMask | Command::PushInvertedMask => depth += 1,
+            Command::PopMask if depth == 0 => return,
+            Command::PopMask => depth -= 1,
+            _ => {}
+        }
+    }
+}
+
+/// Draws the finished contents of `target` over the whole frame.
+fn composite_texture(
+    descriptors: &Descriptors,
+    target: CommandTarget,
+    color_transform: ColorTransform,
+    blend_mode: TrivialBlend,
+) -> DrawCommand {
+    let transform = Transform {
+        matrix: Matrix::scale(target.width() as f32, target.height() as f32),
+        color_transform,
+        matrix3d: None,
+    };
+    let texture = target.take_color_texture();
+    let bind_group = descriptors
+        .device
+        .create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &descriptors.bind_layouts.bitmap,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: descriptors.quad.texture_transforms.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(texture.view()),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(
+                        descriptors.bitmap_samplers.get_sampler(false, false),
+                    ),
+                },
+            ],
+            label: None,
+        });
+    DrawCommand::RenderTexture {
+        _texture: texture,
+        binds: bind_group,
+        transform,
+        blend_mode,
+    }
+}
+
+/// Draws a region of a bitmap with texture transforms of its own. It's sampled without
+/// mipmaps, which would blend the pixels around the region into it when it's scaled down,
+/// and from the bitmap's own texture rather than the atlas.
+fn bitmap_region(
+    descriptors: &Descriptors,
+    bitmap: BitmapHandle,
+    region: BitmapRegion,
+    transform: Transform,
+    smoothing: bool,
+) -> DrawCommand {
+    let texture = as_texture(&bitmap);
+    let mut u_matrix = [[0.0; 4]; 4];
+    for (column, values) in u_matrix
+        .iter_mut()
+        .zip(region.texture_matrix(texture.width, texture.height))
+    {
+        column[..3].copy_from_slice(&values);
+    }
+    u_matrix[3][3] = 1.0;
+    let texture_transforms = create_buffer_with_data(
+        &descriptors.device,
+        bytemuck::cast_slice(&[TextureTransforms {
+            u_matrix,
+            premultiply: TextureTransforms::premultiply_mode(texture.premultiplied, smoothing),
+            _padding: [0.0; 3],
+        }]),
+        wgpu::BufferUsages::UNIFORM,
+        create_debug_label!("Bitmap region tex transforms"),
+    );
+    let binds = BitmapBinds::new(
+        &descriptors.device,
+        &descriptors.bind_layouts.bitmap,
+        descriptors.bitmap_samplers.get_sampler(false, smoothing),
+        &texture_transforms,
+        0,
+        base_level_view(texture.texture(descriptors)),
+        create_debug_label!("Bitmap {:?} region {:?} bind group", bitmap.0, region),
+    );
+    DrawCommand::RenderBitmapRegion {
+        _bitmap: bitmap,
+        _texture_transforms: texture_transforms,
+        binds,
+        size: (region.width, region.height),
+        transform,
+        blend_mode: TrivialBlend::Normal,
+    }
+}
+
+/// Merges runs of consecutive draws into a single instanced draw, where every draw in the run
+/// is a copy of the same bitmap, a bitmap in the atlas, a copy of the same solid color shape,
+/// or a solid color rect. Anything else in between (including mask changes) ends the current run.
+fn batch_draws(
+    commands: Vec<DrawCommand>,
+    descriptors: &Descriptors,
+    meshes: &[Mesh],
+    texture_pool: &mut TexturePool,
+) -> Vec<DrawCommand> {
+    let mut result = Vec::with_capacity(commands.len());
+    let mut run: Vec<DrawCommand> = vec![];
+
+    for command in commands {
+        let continues_run = match (run.last(), &command) {
+            (
+                Some(DrawCommand::RenderBitmap {
+                    bitmap: last_bitmap,
+                    smoothing: last_smoothing,
+                    blend_mode: last_blend_mode,
+                    ..
+                }),
+                DrawCommand::RenderBitmap {
+                    bitmap,
+                    smoothing,
+                    blend_mode,
+                    ..
+                },
+            ) => {
+                (Arc::ptr_eq(&last_bitmap.0, &bitmap.0)
+                    || (as_texture(last_bitmap).atlas_entry().is_some()
+                        && as_texture(bitmap).atlas_entry().is_some()))
+                    && last_smoothing == smoothing
+                    && last_blend_mode == blend_mode
+            }
+            (
+                Some(DrawCommand::RenderShape {
+                    shape: last_shape, ..
+                }),
+                DrawCommand::RenderShape { shape, .. },
+            ) => last_shape.index() == shape.index(),
+            (Some(DrawCommand::DrawRect { .. }), DrawCommand::DrawRect { .. }) => true,
+            _ => false,
+        };
+        if !continues_run {
+            flush_run(&mut run, &mut result, descriptors, texture_pool);
+        }
+        if can_batch(&command, meshes) {
+            run.push(command);
+        } else {
+            result.push(command);
+        }
+    }
+    flush_run(&mut run, &mut result, descriptors, texture_pool);
+
+    result
+}
+
+fn can_batch(command: &DrawCommand, meshes: &[Mesh]) -> bool {
+    match command {
+        // The instanced pipelines don't mix colors towards their luminance
+        DrawCommand::RenderBitmap { transform, .. } => {
+            transform.color_transform.luminance_mix.is_zero()
+        }
+        DrawCommand::DrawRect { .. } => true,
+        // Instancing a shape with several draws would put the later draws of every copy
+        // above the earlier draws of all the others, so only single fills are batched
+        DrawCommand::RenderShape {
+            shape, transform, ..
+        } => {
+            transform.color_transform.luminance_mix.is_zero()
+                && matches!(
+                    meshes[shape.index()].draws.as_slice(),
+                    [draw] if matches!(draw.draw_type, DrawType::Color)
+                )
+        }
+        _ => false,
+    }
+}
+
+fn flush_run(
+    run: &mut Vec<DrawCommand>,
+    result: &mut Vec<DrawCommand>,
+    descriptors: &Descriptors,
+    texture_pool: &mut TexturePool,
+) {
+    // Bitmaps in the atlas don't have a texture of their own, so they're always drawn out of it.
+    // A run of them is made up of nothing else.
+    let from_atlas = match run.first() {
+        Some(DrawCommand::RenderBitmap { bitmap, .. }) => {
+            as_texture(bitmap).atlas_entry().is_some()
+        }
+        _ => false,
+    };
+    if run.len() < 2 && !from_atlas {
+        // Not worth the instance buffer, draw it as normal
+        result.append(run);
+        return;
+    }
+
+    let mut instances = Vec::with_capacity(run.len());
+    let mut atlas_bitmaps = vec![];
+    let mut batch = None;
+    for command in run.drain(..) {
+        match command {
+            DrawCommand::RenderBitmap {
+                bitmap,
+                transform,
+                smoothing,
+                blend_mode,
+            } => {
+                let texture = as_texture(&bitmap);
+                instances.push(DrawInstance {
+                    world_matrix: transform_world_matrix(
+                        &transform,
+                        Matrix {
+                            a: texture.width as f32,
+                            d: texture.height as f32,
+                            ..Default::default()
+                        },
+                    ),
+                    colors: ColorAdjustments::from(transform.color_transform),
+                    uv_rect: match texture.atlas_entry() {
+                        Some(entry) => {
+                            descriptors.bitmap_atlas().mark_used(entry);
+                            entry.uv_rect()
+                        }
+                        None => FULL_UV_RECT,
+                    },
+                });
+                batch = Some(if from_atlas {
+                    atlas_bitmaps.push(bitmap);
+                    Batch::AtlasBitmaps(smoothing, blend_mode)
+                } else {
+                    Batch::Bitmap(bitmap, smoothing, blend_mode)
+                });
+            }
+            DrawCommand::RenderShape {
+                shape, transform, ..
+            } => {
+                instances.push(DrawInstance {
+                    world_matrix: transform_world_matrix(&transform, Matrix::IDENTITY),
+                    colors: ColorAdjustments::from(transform.color_transform),
+                    uv_rect: FULL_UV_RECT,
+                });
+                batch = Some(Batch::Shape(shape));
+            }
+            DrawCommand::DrawRect { color, matrix } => {
+                instances.push(DrawInstance {
+                    world_matrix: world_matrix(&matrix),
+                    colors: ColorAdjustments {
+                        mult_color: [
+                            f32::from(color.r) / 255.0,
+                            f32::from(color.g) / 255.0,
+                            f32::from(color.b) / 255.0,
+                            f32::from(color.a) / 255.0,
+                        ],
+                        add_color: [0.0; 4],
+                        ..DEFAULT_COLOR_ADJUSTMENTS
+                    },
+                    uv_rect: FULL_UV_RECT,
+                });
+                batch = Some(Batch::Rect);
+            }
+            _ => {}
+        }
+    }
+
+    let Some(batch) = batch else {
+        return;
+    };
+    let instances_buffer =
+        texture_pool.get_instance_buffer(descriptors, bytemuck::cast_slice(&instances));
+    let num_instances = instances.len() as u32;
+    result.push(match batch {
+        Batch::Bitmap(bitmap, smoothing, blend_mode) => DrawCommand::RenderBitmaps {
+            bitmap,
+            instances: instances_buffer,
+            num_instances,
+            smoothing,
+            blend_mode,
+        },
+        Batch::AtlasBitmaps(smoothing, blend_mode) => DrawCommand::RenderAtlasBitmaps {
+            _bitmaps: atlas_bitmaps,
+            instances: instances_buffer,
+            num_instances,
+            smoothing,
+            blend_mode,
+        },
+        Batch::Shape(shape) => DrawCommand::RenderShapes {
+            shape,
+            instances: instances_buffer,
+            num_instances,
+        },
+        Batch::Rect => DrawCommand::DrawRects {
+            instances: instances_buffer,
+            num_instances,
+        },
+    });
+}
+
+/// What every draw in a run that's being batched has in common.
+enum Batch {
+    Bitmap(BitmapHandle, bool, TrivialBlend),
+    /// Different bitmaps that are all drawn out of the atlas.
+    AtlasBitmaps(bool, TrivialBlend),
+    Shape(ShapeHandle),
+    Rect,
+}
+
+/// The world matrix of something that is first transformed by `inner` and then by `transform`,
+/// in 3D when the transform is.
+fn transform_world_matrix(transform: &Transform, inner: Matrix) -> [[f32; 4]; 4] {
+    match transform.matrix3d {
+        Some(matrix3d) => {
+            let raw_data = (matrix3d * Matrix3D::from(inner)).raw_data;
+            let mut world_matrix = [[0.0; 4]; 4];
+            for (index, value) in raw_data.into_iter().enumerate() {
+                world_matrix[index / 4][index % 4] = value as f32;
+            }
+            world_matrix
+        }
+        None => world_matrix(&(transform.matrix * inner)),
+    }
+}
+
+fn world_matrix(matrix: &Matrix) -> [[f32; 4]; 4] {
+    [
+        [matrix.a, matrix.b, 0.0, 0.0],
+        [matrix.c, matrix.d, 0.0, 0.0],
+        [0.0, 0.0, 1.0, 0.0],
+        [
+            matrix.tx.to_pixels() as f32,
+            matrix.ty.to_pixels() as f32,
+            0.0,
+            1.0,
+        ],
+    ]
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn run_copy_pipeline(
     descriptors: &Descriptors,
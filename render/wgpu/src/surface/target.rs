@@ -3,7 +3,7 @@ use crate::buffer_pool::{PoolEntry, TexturePool};
 use crate::descriptors::Descriptors;
 use crate::globals::Globals;
 use crate::surface::commands::run_copy_pipeline;
-use crate::utils::create_buffer_with_data;
+use crate::utils::{base_level_view, create_buffer_with_data};
 use crate::Transforms;
 use once_cell::race::OnceBool;
 use once_cell::sync::OnceCell;
@@ -30,10 +30,7 @@ impl ResolveBuffer {
 
     pub fn new_manual(texture: Arc<wgpu::Texture>) -> Self {
         Self {
-            texture: PoolOrArcTexture::Manual((
-                texture.clone(),
-                texture.create_view(&Default::default()),
-            )),
+            texture: PoolOrArcTexture::Manual((texture.clone(), base_level_view(&texture))),
         }
     }
 
@@ -100,10 +97,7 @@ impl FrameBuffer {
 
     pub fn new_manual(texture: Arc<wgpu::Texture>, size: wgpu::Extent3d) -> Self {
         Self {
-            texture: PoolOrArcTexture::Manual((
-                texture.clone(),
-                texture.create_view(&Default::default()),
-            )),
+            texture: PoolOrArcTexture::Manual((texture.clone(), base_level_view(&texture))),
             size,
         }
     }
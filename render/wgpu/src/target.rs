@@ -1,4 +1,4 @@
-use crate::utils::BufferDimensions;
+use crate::utils::{base_level_view, BufferDimensions};
 use crate::Error;
 use std::fmt::Debug;
 use std::sync::Arc;
@@ -233,9 +233,7 @@ impl RenderTarget for TextureTarget {
     }
 
     fn get_next_texture(&mut self) -> Result<Self::Frame, wgpu::SurfaceError> {
-        Ok(TextureTargetFrame(
-            self.texture.create_view(&Default::default()),
-        ))
+        Ok(TextureTargetFrame(base_level_view(&self.texture)))
     }
 
     #[instrument(level = "debug", skip_all)]
@@ -274,3 +272,79 @@ impl RenderTarget for TextureTarget {
         }
     }
 }
+
+/// Renders into a texture that belongs to someone else, such as an application that Ruffle is
+/// embedded in.
+///
+/// The texture must have been created with `RENDER_ATTACHMENT` usage, and `format` must match
+/// it. sRGB formats are handled the same as for a swap chain.
+#[derive(Debug)]
+pub struct ExternalTextureTarget {
+    texture: Arc<wgpu::Texture>,
+    format: wgpu::TextureFormat,
+    width: u32,
+    height: u32,
+}
+
+impl ExternalTextureTarget {
+    pub fn new(
+        texture: Arc<wgpu::Texture>,
+        format: wgpu::TextureFormat,
+        (width, height): (u32, u32),
+    ) -> Self {
+        Self {
+            texture,
+            format,
+            width,
+            height,
+        }
+    }
+
+    /// Replaces the texture that's rendered into, such as after the application resized it.
+    /// The viewport dimensions of the backend must be set again afterwards, so that its
+    /// internal buffers are recreated to match.
+    pub fn set_texture(&mut self, texture: Arc<wgpu::Texture>, (width, height): (u32, u32)) {
+        self.texture = texture;
+        self.width = width;
+        self.height = height;
+    }
+
+    pub fn texture(&self) -> &Arc<wgpu::Texture> {
+        &self.texture
+    }
+}
+
+impl RenderTarget for ExternalTextureTarget {
+    type Frame = TextureTargetFrame;
+
+    fn resize(&mut self, _device: &wgpu::Device, _width: u32, _height: u32) {
+        // The texture isn't ours to resize, so the viewport always matches its size instead
+    }
+
+    fn format(&self) -> wgpu::TextureFormat {
+        self.format
+    }
+
+    fn width(&self) -> u32 {
+        self.width
+    }
+
+    fn height(&self) -> u32 {
+        self.height
+    }
+
+    fn get_next_texture(&mut self) -> Result<Self::Frame, wgpu::SurfaceError> {
+        Ok(TextureTargetFrame(base_level_view(&self.texture)))
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    fn submit<I: IntoIterator<Item = wgpu::CommandBuffer>>(
+        &self,
+        _device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        command_buffers: I,
+        _frame: Self::Frame,
+    ) -> wgpu::SubmissionIndex {
+        queue.submit(command_buffers)
+    }
+}
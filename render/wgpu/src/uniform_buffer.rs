@@ -8,10 +8,24 @@ use wgpu::util::StagingBelt;
 /// A simple chunked bump allacator for managing dynamic uniforms that change per-draw.
 /// Each draw call may use `UniformBuffer::write_uniforms` can be used to queue
 /// the upload of uniform data to the GPU.
+///
+/// The uniforms of every draw are collected on the CPU, and only uploaded once per block
+/// in `UniformBuffer::finish`, instead of touching the staging belt for each draw.
 pub struct UniformBuffer<'a, T: Pod> {
     buffers: &'a BufferStorage<T>,
     cur_block: usize,
     cur_offset: u32,
+    last_write: Option<LastWrite<T>>,
+    /// What's been written to each block so far, laid out as it will be in the buffer.
+    pending: Vec<Vec<u8>>,
+}
+
+/// The location of the most recently written uniform data, so that identical
+/// consecutive writes can reuse it.
+struct LastWrite<T: Pod> {
+    data: T,
+    block: usize,
+    offset: u32,
 }
 
 #[self_referencing]
@@ -102,22 +116,39 @@ impl<'a, T: Pod> UniformBuffer<'a, T> {
             buffers,
             cur_block: 0,
             cur_offset: 0,
+            last_write: None,
+            pending: Vec::new(),
         }
     }
 
-    /// Enqueue `data` for upload into the given command encoder, and set the bind group on `render_pass`
+    /// Enqueue `data` for upload at the end of the frame, and set the bind group on `render_pass`
     /// to use the uniform data.
     pub fn write_uniforms<'b>(
         &mut self,
         device: &wgpu::Device,
         layout: &wgpu::BindGroupLayout,
-        command_encoder: &mut wgpu::CommandEncoder,
         render_pass: &mut wgpu::RenderPass<'b>,
         bind_group_index: u32,
         data: &T,
     ) where
         'a: 'b,
     {
+        // Consecutive draws often share the same uniforms (such as many objects with the same
+        // color transform), in which case we can point at the previous upload instead.
+        if let Some(last_write) = &self.last_write {
+            if bytemuck::bytes_of(&last_write.data) == bytemuck::bytes_of(data) {
+                let block: &'a Block = self
+                    .buffers
+                    .with_allocator(|alloc| alloc.borrow().blocks[last_write.block]);
+                render_pass.set_bind_group(
+                    bind_group_index,
+                    &block.bind_group,
+                    &[last_write.offset],
+                );
+                return;
+            }
+        }
+
         // Allocate a new block if we've exceeded our capacity.
         if self.cur_block
             >= self
@@ -131,21 +162,21 @@ impl<'a, T: Pod> UniformBuffer<'a, T> {
             .buffers
             .with_allocator(|alloc| alloc.borrow().blocks[self.cur_block]);
 
-        // Copy the data into the buffer via the staging belt.
-        self.buffers.with_staging_belt(|belt| {
-            belt.borrow_mut()
-                .write_buffer(
-                    command_encoder,
-                    &block.buffer,
-                    self.cur_offset.into(),
-                    BufferStorage::<T>::UNIFORMS_SIZE.try_into().unwrap(),
-                    device,
-                )
-                .copy_from_slice(bytemuck::cast_slice(std::slice::from_ref(data)));
-        });
+        // Copy the data to where it will be in the buffer, padding it to the alignment.
+        if self.pending.len() <= self.cur_block {
+            self.pending.resize_with(self.cur_block + 1, Vec::new);
+        }
+        let pending = &mut self.pending[self.cur_block];
+        pending.resize(self.cur_offset as usize, 0);
+        pending.extend_from_slice(bytemuck::bytes_of(data));
 
         // Set the bind group to the final uniform location.
         render_pass.set_bind_group(bind_group_index, &block.bind_group, &[self.cur_offset]);
+        self.last_write = Some(LastWrite {
+            data: *data,
+            block: self.cur_block,
+            offset: self.cur_offset,
+        });
 
         // Advance offset.
         self.cur_offset += self.buffers.borrow_aligned_uniforms_size();
@@ -158,10 +189,22 @@ impl<'a, T: Pod> UniformBuffer<'a, T> {
         }
     }
 
-    /// Should be called at the end of a frame.
-    pub fn finish(self) {
-        self.buffers
-            .with_staging_belt(|belt| belt.borrow_mut().finish());
+    /// Should be called at the end of a frame, before `command_encoder` is finished.
+    /// Uploads all of the uniforms that were written with one copy per block.
+    pub fn finish(self, device: &wgpu::Device, command_encoder: &mut wgpu::CommandEncoder) {
+        let buffers = self.buffers;
+        buffers.with_staging_belt(|belt| {
+            let mut belt = belt.borrow_mut();
+            for (index, data) in self.pending.iter().enumerate() {
+                let Some(size) = wgpu::BufferSize::new(data.len() as u64) else {
+                    continue;
+                };
+                let block: &Block = buffers.with_allocator(|alloc| alloc.borrow().blocks[index]);
+                belt.write_buffer(command_encoder, &block.buffer, 0, size, device)
+                    .copy_from_slice(data);
+            }
+            belt.finish();
+        });
     }
 }
 
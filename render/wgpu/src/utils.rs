@@ -1,8 +1,9 @@
-use ruffle_render::quality::StageQuality;
-use ruffle_render::utils::unmultiply_alpha_rgba;
+use ruffle_render::utils::{downsample_rgba, unmultiply_alpha_rgba};
 use std::borrow::Cow;
 use std::mem::size_of;
 use std::num::NonZeroU32;
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::sync::Arc;
 use wgpu::util::DeviceExt;
 
 macro_rules! create_debug_label {
@@ -89,6 +90,90 @@ pub fn create_buffer_with_data(
     })
 }
 
+/// The number of mipmap levels it takes to scale a texture of this size down to 1x1.
+pub fn mip_level_count(width: u32, height: u32) -> u32 {
+    u32::BITS - width.max(height).max(1).leading_zeros()
+}
+
+/// Creates a view of only the full size level of a texture.
+/// Anything rendering to a texture needs this, as does anything sampling it without mipmaps.
+pub fn base_level_view(texture: &wgpu::Texture) -> wgpu::TextureView {
+    texture.create_view(&wgpu::TextureViewDescriptor {
+        mip_level_count: NonZeroU32::new(1),
+        ..Default::default()
+    })
+}
+
+/// Converts 16-bit RGBA to half float RGBA, for devices without 16-bit normalized textures.
+/// Half floats still keep 11 bits of precision for every channel.
+pub fn rgba16_to_rgba16_float(rgba16: &[u8]) -> Vec<u8> {
+    rgba16
+        .chunks_exact(2)
+        .flat_map(|channel| {
+            let value = u16::from_le_bytes([channel[0], channel[1]]);
+            unit_f32_to_f16(f32::from(value) / 65535.0).to_le_bytes()
+        })
+        .collect()
+}
+
+/// Converts a float from 0 to 1 to the bits of the nearest half precision float.
+fn unit_f32_to_f16(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let exponent = ((bits >> 23) & 0xFF) as i32 - 127 + 15;
+    let mantissa = bits & 0x7F_FFFF;
+    if exponent <= 0 {
+        // Too small for a normal half float, so it's kept without the implicit leading 1
+        if exponent < -10 {
+            return 0;
+        }
+        let shift = (14 - exponent) as u32;
+        let mantissa = mantissa | 0x80_0000;
+        return (((mantissa >> (shift - 1)) + 1) >> 1) as u16;
+    }
+    // Rounding the largest mantissa up carries into the exponent, as it should
+    let half = ((exponent as u32) << 10) | (mantissa >> 13);
+    (half + ((mantissa >> 12) & 1)) as u16
+}
+
+/// Uploads premultiplied RGBA pixels to a texture, along with a downscaled copy of them for
+/// each of its other mipmap levels.
+pub fn write_texture_with_mipmaps(
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+    mip_level_count: u32,
+    width: u32,
+    height: u32,
+    rgba: &[u8],
+) {
+    let mut level = (width, height, Cow::Borrowed(rgba));
+    for mip_level in 0..mip_level_count {
+        if mip_level > 0 {
+            let (width, height, rgba) = downsample_rgba(level.0, level.1, &level.2);
+            level = (width, height, Cow::Owned(rgba));
+        }
+        let (width, height, ref rgba) = level;
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture,
+                mip_level,
+                origin: Default::default(),
+                aspect: wgpu::TextureAspect::All,
+            },
+            rgba,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: NonZeroU32::new(4 * width),
+                rows_per_image: None,
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+}
+
 // Based off wgpu example 'capture'
 #[derive(Debug, Clone)]
 pub struct BufferDimensions {
@@ -137,7 +222,17 @@ pub fn buffer_to_image(
             .unwrap_or(wgpu::Maintain::Wait),
     );
     let _ = receiver.recv().expect("MPSC channel must not fail");
-    let map = buffer_slice.get_mapped_range();
+    mapped_buffer_to_image(buffer, dimensions, size, premultiplied_alpha)
+}
+
+/// Reads the pixels out of a buffer that has already been mapped, and then unmaps it.
+fn mapped_buffer_to_image(
+    buffer: &wgpu::Buffer,
+    dimensions: &BufferDimensions,
+    size: wgpu::Extent3d,
+    premultiplied_alpha: bool,
+) -> image::RgbaImage {
+    let map = buffer.slice(..).get_mapped_range();
     let mut bytes = Vec::with_capacity(dimensions.height * dimensions.unpadded_bytes_per_row);
 
     for chunk in map.chunks(dimensions.padded_bytes_per_row.get() as usize) {
@@ -157,13 +252,83 @@ pub fn buffer_to_image(
     image
 }
 
+/// A buffer that is being mapped for reading in the background.
+///
+/// The mapping is requested as soon as this is created, so that by the time the pixels are
+/// actually needed the copy has usually finished and reading them doesn't stall.
+/// Dropping this without reading it cancels the mapping, so the buffer can be written to again.
+#[derive(Debug)]
+pub struct BufferReadback {
+    buffer: Arc<wgpu::Buffer>,
+    receiver: Option<Receiver<Result<(), wgpu::BufferAsyncError>>>,
+}
+
+impl BufferReadback {
+    /// Starts mapping `buffer`. This must be called after submitting the commands that write to it.
+    pub fn new(buffer: Arc<wgpu::Buffer>) -> Self {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                // The receiver is gone if the readback was cancelled, which is fine
+                let _ = sender.send(result);
+            });
+        Self {
+            buffer,
+            receiver: Some(receiver),
+        }
+    }
+
+    /// Reads the buffer into an image, only blocking if the mapping hasn't completed yet.
+    pub fn into_image(
+        mut self,
+        device: &wgpu::Device,
+        dimensions: &BufferDimensions,
+        index: Option<wgpu::SubmissionIndex>,
+        size: wgpu::Extent3d,
+        premultiplied_alpha: bool,
+    ) -> image::RgbaImage {
+        let receiver = self
+            .receiver
+            .take()
+            .expect("BufferReadback can only be read once");
+        if let Err(TryRecvError::Empty) = receiver.try_recv() {
+            device.poll(
+                index
+                    .map(wgpu::Maintain::WaitForSubmissionIndex)
+                    .unwrap_or(wgpu::Maintain::Wait),
+            );
+            let _ = receiver.recv().expect("MPSC channel must not fail");
+        }
+        mapped_buffer_to_image(&self.buffer, dimensions, size, premultiplied_alpha)
+    }
+}
+
+impl Drop for BufferReadback {
+    fn drop(&mut self) {
+        if self.receiver.is_some() {
+            // Never read, so cancel (or undo) the mapping
+            self.buffer.unmap();
+        }
+    }
+}
+
 pub fn supported_sample_count(
     adapter: &wgpu::Adapter,
-    quality: StageQuality,
+    device: &wgpu::Device,
+    mut sample_count: u32,
     format: wgpu::TextureFormat,
 ) -> u32 {
-    let mut sample_count = quality.sample_count();
-    let features = adapter.get_texture_format_features(format).flags;
+    // Whatever else the adapter supports is only allowed if the device was created for it,
+    // which a device that was shared with us may not have been
+    let features = if device
+        .features()
+        .contains(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES)
+    {
+        adapter.get_texture_format_features(format).flags
+    } else {
+        format.describe().guaranteed_format_features.flags
+    };
 
     // Keep halving the sample count until we get one that's supported - or 1 (no multisampling)
     // It's not guaranteed that supporting 4x means supporting 2x, so there's no "max" option
@@ -0,0 +1,77 @@
+use crate::util::environment::wgpu_renderer;
+use crate::util::pixels::pixel;
+use ruffle_core::swf::Twips;
+use ruffle_core::Color;
+use ruffle_render::backend::RenderBackend;
+use ruffle_render::bitmap::{Bitmap, BitmapFormat};
+use ruffle_render::commands::{CommandHandler, CommandList};
+use ruffle_render::matrix::Matrix;
+use ruffle_render::quality::StageQuality;
+use ruffle_render::transform::Transform;
+
+const SIZE: u32 = 64;
+
+/// How far apart a component of the result may be from what's expected, out of 255.
+const TOLERANCE: u8 = 2;
+
+/// The alpha of the mask in each column, which fades in from nothing on the left.
+fn mask_alpha(x: u32) -> u8 {
+    (x * 4) as u8
+}
+
+/// Checks that masking by a gradient of alpha fades out the maskee to match, instead of
+/// cutting it off at a hard edge like a stencil mask would.
+pub fn alpha_mask_feathered() -> Result<(), libtest_mimic::Failed> {
+    let mut renderer = wgpu_renderer((SIZE, SIZE))?;
+
+    // A premultiplied white gradient, so every component is the alpha
+    let gradient = (0..SIZE * SIZE)
+        .flat_map(|index| [mask_alpha(index % SIZE); 4])
+        .collect();
+    let gradient =
+        renderer.register_bitmap(Bitmap::new(SIZE, SIZE, BitmapFormat::Rgba, gradient))?;
+    let mut mask = CommandList::new();
+    mask.render_bitmap(gradient, Transform::default(), false);
+
+    let red = Color::from_rgb(0xFF0000, 255);
+    let mut maskee = CommandList::new();
+    maskee.draw_rect(
+        red.clone(),
+        Matrix::create_box(SIZE as f32, SIZE as f32, 0.0, Twips::ZERO, Twips::ZERO),
+    );
+
+    let mut commands = CommandList::new();
+    commands.alpha_mask(maskee, mask);
+    let handle = renderer.register_bitmap(Bitmap::new(
+        SIZE,
+        SIZE,
+        BitmapFormat::Rgba,
+        vec![0; (SIZE * SIZE * 4) as usize],
+    ))?;
+    let bitmap = renderer
+        .render_offscreen(handle, SIZE, SIZE, commands, StageQuality::Low)
+        .ok_or("Offscreen rendering is not supported")?
+        .retrieve_offscreen_texture()?;
+
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let alpha = mask_alpha(x);
+            let expected = [
+                (u32::from(red.r) * u32::from(alpha) / 255) as u8,
+                0,
+                0,
+                alpha,
+            ];
+            let actual = pixel(&bitmap, x, y);
+            assert!(
+                actual
+                    .iter()
+                    .zip(expected)
+                    .all(|(actual, expected)| actual.abs_diff(expected) <= TOLERANCE),
+                "Pixel at ({x}, {y}) is {actual:?}, expected {expected:?}"
+            );
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,86 @@
+use crate::util::environment::isolated_wgpu_renderer;
+use ruffle_core::Color;
+use ruffle_render::backend::RenderBackend;
+use ruffle_render::bitmap::{Bitmap, BitmapFormat};
+use ruffle_render::commands::{CommandHandler, CommandList};
+use ruffle_render::matrix::Matrix;
+use ruffle_render::quality::StageQuality;
+use ruffle_render::transform::Transform;
+use ruffle_render_wgpu::wgpu;
+
+/// Too big for the atlas, whose bitmaps aren't given mipmaps.
+const SIZE: u32 = 512;
+
+/// How many times shorter the bitmap is drawn than it is wide.
+const SQUASH: u32 = 8;
+
+/// Black and white stripes, each 4 pixels wide.
+fn stripes() -> Bitmap {
+    let mut rgba = Vec::with_capacity((SIZE * SIZE * 4) as usize);
+    for _ in 0..SIZE {
+        for x in 0..SIZE {
+            let value = if (x / 4) % 2 == 0 { 0 } else { 255 };
+            rgba.extend_from_slice(&[value, value, value, 255]);
+        }
+    }
+    Bitmap::new(SIZE, SIZE, BitmapFormat::Rgba, rgba)
+}
+
+/// Checks that a smoothed bitmap squashed to an eighth of its height keeps its stripes sharp
+/// at the best quality, where the mipmap chosen for its height alone would blur them into gray,
+/// as long as the adapter can filter anisotropically.
+pub fn anisotropic_filtering() -> Result<(), libtest_mimic::Failed> {
+    // Changing the quality changes the sampling for everything on the same descriptors
+    let mut renderer = isolated_wgpu_renderer((SIZE, SIZE / SQUASH))?;
+    let supported = renderer
+        .descriptors()
+        .adapter
+        .get_downlevel_capabilities()
+        .flags
+        .contains(wgpu::DownlevelFlags::ANISOTROPIC_FILTERING);
+    let bitmap = renderer.register_bitmap(stripes())?;
+
+    // The difference between the darkest and lightest pixels across the middle row
+    let mut contrast = |quality| {
+        renderer.set_quality(quality);
+        let mut commands = CommandList::new();
+        commands.render_bitmap(
+            bitmap.clone(),
+            Transform {
+                matrix: Matrix::scale(1.0, 1.0 / SQUASH as f32),
+                ..Default::default()
+            },
+            true,
+        );
+        renderer.submit_frame(Color::BLACK, commands);
+        let image = renderer
+            .capture_frame(false)
+            .ok_or("Texture target must be readable")?;
+        let row = (0..SIZE).map(|x| image.get_pixel(x, SIZE / SQUASH / 2).0[0]);
+        let (min, max) = row.fold((u8::MAX, u8::MIN), |(min, max), value| {
+            (min.min(value), max.max(value))
+        });
+        Ok::<_, libtest_mimic::Failed>(max - min)
+    };
+
+    let high = contrast(StageQuality::High)?;
+    let best = contrast(StageQuality::Best)?;
+    assert!(
+        high < 64,
+        "Stripes should blur together at high quality, contrast was {high}"
+    );
+    if supported {
+        assert!(
+            best > 192,
+            "Stripes should stay sharp at best quality, contrast was {best}"
+        );
+    } else {
+        // Without anisotropic filtering, the best quality samples just the same as high
+        assert!(
+            best.abs_diff(high) <= 2,
+            "Contrast was {high} at high quality, but {best} at best"
+        );
+    }
+
+    Ok(())
+}
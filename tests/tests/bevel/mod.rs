@@ -0,0 +1,94 @@
+use crate::util::environment::wgpu_renderer;
+use crate::util::pixels::check_pixel;
+use ruffle_core::swf::Twips;
+use ruffle_core::Color;
+use ruffle_render::backend::RenderBackend;
+use ruffle_render::commands::{CommandHandler, CommandList};
+use ruffle_render::filters::{BevelFilter, BevelFilterType, Filter};
+use ruffle_render::matrix::Matrix;
+
+const SIZE: u32 = 64;
+
+const WHITE: [u8; 4] = [255, 255, 255, 255];
+const GRAY: [u8; 4] = [128, 128, 128, 255];
+const YELLOW: [u8; 4] = [255, 255, 0, 255];
+const BLUE: [u8; 4] = [0, 0, 255, 255];
+
+/// A gray square from 16 to 48 pixels on both axes, lit from the top left.
+fn beveled_square(bevel_type: BevelFilterType) -> CommandList {
+    let mut square = CommandList::new();
+    square.draw_rect(
+        Color::from_rgb(0x808080, 255),
+        Matrix::create_box(
+            32.0,
+            32.0,
+            0.0,
+            Twips::from_pixels(16.0),
+            Twips::from_pixels(16.0),
+        ),
+    );
+    let mut commands = CommandList::new();
+    commands.filters(
+        square,
+        vec![Filter::BevelFilter(BevelFilter {
+            highlight_color: Color::from_rgb(0xFFFF00, 255),
+            shadow_color: Color::from_rgb(0x0000FF, 255),
+            angle: std::f32::consts::FRAC_PI_4,
+            distance: 4.0,
+            blur_x: 0.0,
+            blur_y: 0.0,
+            strength: 1.0,
+            quality: 1,
+            bevel_type,
+            knockout: false,
+        })],
+    );
+    commands
+}
+
+/// Checks that a bevel lit from the top left highlights the top left edges of a square and
+/// shades the bottom right ones when it's inside of the square, and the other way around
+/// when it's outside.
+pub fn bevel() -> Result<(), libtest_mimic::Failed> {
+    let mut renderer = wgpu_renderer((SIZE, SIZE))?;
+
+    let cases = [
+        (
+            "inner bevel",
+            BevelFilterType::Inner,
+            [
+                ((17, 32), YELLOW),
+                ((32, 17), YELLOW),
+                ((46, 32), BLUE),
+                ((32, 46), BLUE),
+                ((32, 32), GRAY),
+                ((14, 32), WHITE),
+                ((49, 32), WHITE),
+            ],
+        ),
+        (
+            "outer bevel",
+            BevelFilterType::Outer,
+            [
+                ((17, 32), GRAY),
+                ((32, 17), GRAY),
+                ((46, 32), GRAY),
+                ((32, 46), GRAY),
+                ((32, 32), GRAY),
+                ((14, 32), YELLOW),
+                ((49, 32), BLUE),
+            ],
+        ),
+    ];
+    for (name, bevel_type, expected) in cases {
+        renderer.submit_frame(Color::WHITE, beveled_square(bevel_type));
+        let image = renderer
+            .capture_frame(false)
+            .ok_or("Texture target must be readable")?;
+        for (position, color) in expected {
+            check_pixel(&image, position, color, 2, name)?;
+        }
+    }
+
+    Ok(())
+}
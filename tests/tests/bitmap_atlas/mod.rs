@@ -0,0 +1,219 @@
+use crate::util::environment::{isolated_wgpu_renderer, wgpu_renderer};
+use ruffle_core::swf::Twips;
+use ruffle_core::Color;
+use ruffle_render::backend::RenderBackend;
+use ruffle_render::bitmap::{Bitmap, BitmapFormat, BitmapHandle};
+use ruffle_render::commands::{CommandHandler, CommandList};
+use ruffle_render::matrix::Matrix;
+use ruffle_render::quality::StageQuality;
+use ruffle_render::transform::Transform;
+use ruffle_render_wgpu::as_texture;
+use ruffle_render_wgpu::backend::WgpuRenderBackend;
+use ruffle_render_wgpu::target::TextureTarget;
+
+const SIZE: u32 = 16;
+const CELLS: u32 = 10;
+/// With their padding, bitmaps this big take up a 256x256 cell each, 64 of which fill the atlas.
+const LARGE_SIZE: u32 = 254;
+/// Too big for the bitmaps to be packed into the atlas.
+const STANDALONE_SIZE: u32 = 260;
+const GREEN: [u8; 4] = [0, 255, 0, 255];
+const BLUE: [u8; 4] = [0, 0, 255, 255];
+
+fn color(index: u32) -> [u8; 4] {
+    [(index * 2) as u8, 255 - (index * 2) as u8, 128, 255]
+}
+
+fn is_atlased(bitmap: &BitmapHandle) -> bool {
+    as_texture(bitmap).atlas_entry().is_some()
+}
+
+fn draw_to_stage(
+    renderer: &mut WgpuRenderBackend<TextureTarget>,
+    bitmap: &BitmapHandle,
+) -> Result<image::RgbaImage, libtest_mimic::Failed> {
+    let mut commands = CommandList::new();
+    commands.render_bitmap(bitmap.clone(), Transform::default(), false);
+    renderer.submit_frame(Color::from_rgba(0), commands);
+    Ok(renderer
+        .capture_frame(false)
+        .ok_or("Texture target must be readable")?)
+}
+
+/// Checks that a small bitmap lives in the atlas, stays there when it's replaced as a whole,
+/// and is moved out of it by `BitmapData.draw` without losing any pixels.
+pub fn bitmap_atlas_draw() -> Result<(), libtest_mimic::Failed> {
+    let mut renderer = wgpu_renderer((SIZE, SIZE))?;
+
+    let bitmap = renderer.register_bitmap(Bitmap::new(
+        SIZE,
+        SIZE,
+        BitmapFormat::Rgba,
+        [255, 0, 0, 255].repeat((SIZE * SIZE) as usize),
+    ))?;
+    assert!(
+        is_atlased(&bitmap),
+        "A 16x16 bitmap is packed into the atlas"
+    );
+
+    renderer.update_texture(&bitmap, SIZE, SIZE, GREEN.repeat((SIZE * SIZE) as usize))?;
+    assert!(
+        is_atlased(&bitmap),
+        "Replacing every pixel is written in place in the atlas"
+    );
+    let image = draw_to_stage(&mut renderer, &bitmap)?;
+    assert_eq!(image.get_pixel(4, 4).0, GREEN);
+    assert_eq!(image.get_pixel(12, 4).0, GREEN);
+
+    // Like `BitmapData.draw` of a blue rectangle over the left half
+    let mut commands = CommandList::new();
+    commands.draw_rect(
+        Color::from_rgba(0xFF0000FF),
+        Matrix::create_box(
+            (SIZE / 2) as f32,
+            SIZE as f32,
+            0.0,
+            Twips::default(),
+            Twips::default(),
+        ),
+    );
+    let sync = renderer
+        .render_offscreen(bitmap.clone(), SIZE, SIZE, commands, StageQuality::High)
+        .ok_or("Rendering to a bitmap must hand back its pixels")?;
+    assert!(
+        !is_atlased(&bitmap),
+        "Rendering to a bitmap moves it out of the atlas"
+    );
+    let pixels = sync.retrieve_offscreen_texture()?;
+    let pixel = |x: u32, y: u32| {
+        let index = ((y * SIZE + x) * 4) as usize;
+        <[u8; 4]>::try_from(&pixels.data()[index..index + 4]).unwrap()
+    };
+    assert_eq!(pixel(4, 4), BLUE, "The drawn rectangle is kept");
+    assert_eq!(pixel(12, 4), GREEN, "The pixels from the atlas are kept");
+
+    let image = draw_to_stage(&mut renderer, &bitmap)?;
+    assert_eq!(image.get_pixel(4, 4).0, BLUE);
+    assert_eq!(image.get_pixel(12, 4).0, GREEN);
+
+    Ok(())
+}
+
+/// Draws each of the bitmaps, `size` pixels square, into its own cell of a grid.
+fn grid(bitmaps: &[BitmapHandle], size: u32) -> CommandList {
+    let scale = SIZE as f32 / size as f32;
+    let mut commands = CommandList::new();
+    for (index, bitmap) in (0..).zip(bitmaps) {
+        let (x, y) = (index % CELLS, index / CELLS);
+        commands.render_bitmap(
+            bitmap.clone(),
+            Transform {
+                matrix: Matrix::translate(
+                    Twips::from_pixels((x * SIZE) as f64),
+                    Twips::from_pixels((y * SIZE) as f64),
+                ) * Matrix::scale(scale, scale),
+                ..Default::default()
+            },
+            false,
+        );
+    }
+    commands
+}
+
+/// Checks that 100 small bitmaps are all packed into the atlas, and are drawn out of it
+/// with a single draw call when they're drawn one after another, where the same number of
+/// bitmaps too big for the atlas need a bind group switch each.
+pub fn bitmap_atlas() -> Result<(), libtest_mimic::Failed> {
+    // Other tests register bitmaps at the same time, so count them on descriptors of our own
+    let mut renderer = isolated_wgpu_renderer((CELLS * SIZE, CELLS * SIZE))?;
+    let descriptors = renderer.descriptors().clone();
+
+    let mut register = |size: u32| {
+        (0..CELLS * CELLS)
+            .map(|index| {
+                renderer.register_bitmap(Bitmap::new(
+                    size,
+                    size,
+                    BitmapFormat::Rgba,
+                    color(index).repeat((size * size) as usize),
+                ))
+            })
+            .collect::<Result<Vec<_>, _>>()
+    };
+    let bitmaps = register(SIZE)?;
+    let standalone = register(STANDALONE_SIZE)?;
+    assert!(
+        bitmaps.iter().all(is_atlased),
+        "Every bitmap must be packed into the atlas"
+    );
+    assert_eq!(descriptors.atlas_bitmap_count(), bitmaps.len());
+
+    renderer.submit_frame(Color::from_rgba(0), grid(&bitmaps, SIZE));
+    let statistics = renderer.last_frame_statistics();
+    assert_eq!(
+        statistics.draw_calls, 1,
+        "All of the bitmaps are drawn at once"
+    );
+    assert_eq!(
+        statistics.bind_group_switches, 1,
+        "All of the bitmaps are drawn from the same texture"
+    );
+
+    let image = renderer
+        .capture_frame(false)
+        .ok_or("Texture target must be readable")?;
+    for (x, y, pixel) in image.enumerate_pixels() {
+        let expected = color((y / SIZE) * CELLS + x / SIZE);
+        assert_eq!(pixel.0, expected, "Pixel at ({x}, {y})");
+    }
+
+    renderer.submit_frame(Color::from_rgba(0), grid(&standalone, STANDALONE_SIZE));
+    assert_eq!(
+        renderer.last_frame_statistics().bind_group_switches,
+        CELLS * CELLS,
+        "Bitmaps outside of the atlas are each drawn from their own texture"
+    );
+
+    Ok(())
+}
+
+/// Checks that filling the atlas up evicts the bitmap that was drawn least recently,
+/// which keeps its pixels in a texture of its own.
+pub fn bitmap_atlas_eviction() -> Result<(), libtest_mimic::Failed> {
+    // Filling up the shared atlas would evict the bitmaps of other tests
+    let mut renderer = isolated_wgpu_renderer((SIZE, SIZE))?;
+    let descriptors = renderer.descriptors().clone();
+
+    let register = |renderer: &mut WgpuRenderBackend<TextureTarget>, index: u32| {
+        renderer.register_bitmap(Bitmap::new(
+            LARGE_SIZE,
+            LARGE_SIZE,
+            BitmapFormat::Rgba,
+            color(index).repeat((LARGE_SIZE * LARGE_SIZE) as usize),
+        ))
+    };
+    let bitmaps = (0..64)
+        .map(|index| register(&mut renderer, index))
+        .collect::<Result<Vec<_>, _>>()?;
+    assert!(bitmaps.iter().all(is_atlased), "The atlas isn't full yet");
+
+    // The first bitmap is drawn, which leaves the second as the least recently drawn
+    let image = draw_to_stage(&mut renderer, &bitmaps[0])?;
+    assert_eq!(image.get_pixel(4, 4).0, color(0));
+
+    let newest = register(&mut renderer, 64)?;
+    assert!(is_atlased(&newest), "Room is made for the new bitmap");
+    assert!(is_atlased(&bitmaps[0]), "A recently drawn bitmap is kept");
+    assert!(
+        !is_atlased(&bitmaps[1]),
+        "The least recently drawn bitmap is evicted"
+    );
+    assert_eq!(descriptors.atlas_bitmap_count(), 64);
+
+    let image = draw_to_stage(&mut renderer, &bitmaps[1])?;
+    assert_eq!(image.get_pixel(4, 4).0, color(1), "Evicted pixels are kept");
+    let image = draw_to_stage(&mut renderer, &newest)?;
+    assert_eq!(image.get_pixel(4, 4).0, color(64));
+
+    Ok(())
+}
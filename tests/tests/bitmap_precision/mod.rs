@@ -0,0 +1,88 @@
+use crate::util::environment::wgpu_renderer;
+use ruffle_core::Color;
+use ruffle_render::backend::RenderBackend;
+use ruffle_render::bitmap::{Bitmap, BitmapFormat};
+use ruffle_render::commands::{CommandHandler, CommandList};
+use ruffle_render::matrix::Matrix;
+use ruffle_render::transform::Transform;
+use ruffle_render_wgpu::backend::WgpuRenderBackend;
+use ruffle_render_wgpu::target::TextureTarget;
+
+const WIDTH: u32 = 64;
+const HEIGHT: u32 = 4;
+
+/// The two ends of the gradient, as 16-bit values that lie between two 8-bit ones.
+const LEFT: u16 = 103;
+const RIGHT: u16 = 2673;
+
+/// Draws a two texel gradient stretched across the target, and returns how far the gray of
+/// the pixels between the texel centers is from the exact gradient on average.
+fn gradient_error(
+    renderer: &mut WgpuRenderBackend<TextureTarget>,
+    bitmap: Bitmap,
+) -> Result<f32, libtest_mimic::Failed> {
+    let bitmap = renderer.register_bitmap(bitmap)?;
+    let mut commands = CommandList::new();
+    commands.render_bitmap(
+        bitmap,
+        Transform {
+            matrix: Matrix::scale((WIDTH / 2) as f32, HEIGHT as f32),
+            ..Default::default()
+        },
+        true,
+    );
+    renderer.submit_frame(Color::from_rgb(0, 255), commands);
+    let image = renderer
+        .capture_frame(false)
+        .ok_or("Texture target must be readable")?;
+
+    let (left, right) = (f32::from(LEFT) / 65535.0, f32::from(RIGHT) / 65535.0);
+    let texel_centers = WIDTH / 4..WIDTH * 3 / 4;
+    let mut total = 0.0;
+    for x in texel_centers.clone() {
+        let t = (x as f32 + 0.5) / (WIDTH / 2) as f32 - 0.5;
+        let expected = (left + (right - left) * t) * 255.0;
+        total += (f32::from(image.get_pixel(x, HEIGHT / 2).0[0]) - expected).abs();
+    }
+    Ok(total / texel_centers.len() as f32)
+}
+
+/// Checks that a smoothed gradient from a bitmap with 16 bits per channel comes out closer
+/// to the exact gradient than the same bitmap with 8 bits per channel, which has its ends
+/// rounded to the nearest 8-bit values and bands in the wrong places.
+pub fn bitmap_precision() -> Result<(), libtest_mimic::Failed> {
+    let mut renderer = wgpu_renderer((WIDTH, HEIGHT))?;
+
+    let mut rgba16 = Vec::new();
+    for value in [LEFT, RIGHT] {
+        for channel in [value, value, value, u16::MAX] {
+            rgba16.extend_from_slice(&channel.to_le_bytes());
+        }
+    }
+    let wide_error = gradient_error(
+        &mut renderer,
+        Bitmap::new(2, 1, BitmapFormat::Rgba16, rgba16),
+    )?;
+
+    let to_8_bit = |value: u16| ((u32::from(value) * 255 + 32767) / 65535) as u8;
+    let (left, right) = (to_8_bit(LEFT), to_8_bit(RIGHT));
+    let narrow_error = gradient_error(
+        &mut renderer,
+        Bitmap::new(
+            2,
+            1,
+            BitmapFormat::Rgba,
+            vec![left, left, left, 255, right, right, right, 255],
+        ),
+    )?;
+
+    // Rounding the exact gradient puts it a quarter of a step off on average, while
+    // the 8-bit ends put it closer to half a step off.
+    if wide_error > 0.32 || wide_error >= narrow_error {
+        return Err(format!(
+            "16-bit gradient should be closer than 8-bit, but was {wide_error} off against {narrow_error}"
+        )
+        .into());
+    }
+    Ok(())
+}
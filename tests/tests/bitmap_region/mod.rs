@@ -0,0 +1,53 @@
+use crate::util::environment::wgpu_renderer;
+use ruffle_core::Color;
+use ruffle_render::backend::RenderBackend;
+use ruffle_render::bitmap::{Bitmap, BitmapFormat, BitmapRegion};
+use ruffle_render::commands::{CommandHandler, CommandList};
+use ruffle_render::matrix::Matrix;
+use ruffle_render::transform::Transform;
+
+const SIZE: u32 = 16;
+
+/// Checks that drawing the bottom right quadrant of a 2x2 bitmap, with a different color
+/// in each quadrant, fills the target with only the color of that quadrant.
+pub fn bitmap_region() -> Result<(), libtest_mimic::Failed> {
+    let mut renderer = wgpu_renderer((SIZE, SIZE))?;
+    let bitmap = renderer.register_bitmap(Bitmap::new(
+        2,
+        2,
+        BitmapFormat::Rgba,
+        vec![
+            255, 0, 0, 255, // Top left is red
+            0, 255, 0, 255, // Top right is green
+            0, 0, 255, 255, // Bottom left is blue
+            255, 255, 0, 255, // Bottom right is yellow
+        ],
+    ))?;
+
+    let mut commands = CommandList::new();
+    commands.render_bitmap_region(
+        bitmap,
+        BitmapRegion {
+            x: 1,
+            y: 1,
+            width: 1,
+            height: 1,
+        },
+        Transform {
+            matrix: Matrix::scale(SIZE as f32, SIZE as f32),
+            ..Default::default()
+        },
+        false,
+    );
+    renderer.submit_frame(Color::from_rgba(0), commands);
+    let image = renderer
+        .capture_frame(false)
+        .ok_or("Texture target must be readable")?;
+
+    for (x, y, pixel) in image.enumerate_pixels() {
+        if pixel.0 != [255, 255, 0, 255] {
+            return Err(format!("Pixel {x}, {y} should be yellow, but was {:?}", pixel.0).into());
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use crate::util::environment::wgpu_renderer;
+use ruffle_core::swf::Twips;
+use ruffle_core::Color;
+use ruffle_render::backend::RenderBackend;
+use ruffle_render::bitmap::{Bitmap, BitmapFormat};
+use ruffle_render::commands::{CommandHandler, CommandList};
+use ruffle_render::matrix::Matrix;
+use ruffle_render::transform::Transform;
+
+const TILES: u32 = 100;
+const TILE_SIZE: u32 = 4;
+const SIZE: u32 = TILES * TILE_SIZE;
+
+/// Checks that tiling one bitmap 10,000 times is drawn as a single instanced draw that covers
+/// the whole target, both when its instance buffer is created and when it's reused from the
+/// frame before.
+pub fn bitmap_tiling() -> Result<(), libtest_mimic::Failed> {
+    let mut renderer = wgpu_renderer((SIZE, SIZE))?;
+    let bitmap = renderer.register_bitmap(Bitmap::new(
+        TILE_SIZE,
+        TILE_SIZE,
+        BitmapFormat::Rgba,
+        [0, 128, 255, 255].repeat((TILE_SIZE * TILE_SIZE) as usize),
+    ))?;
+
+    let frame = || {
+        let mut commands = CommandList::new();
+        for index in 0..TILES * TILES {
+            let (x, y) = (index % TILES, index / TILES);
+            commands.render_bitmap(
+                bitmap.clone(),
+                Transform {
+                    matrix: Matrix::translate(
+                        Twips::from_pixels((x * TILE_SIZE) as f64),
+                        Twips::from_pixels((y * TILE_SIZE) as f64),
+                    ),
+                    ..Default::default()
+                },
+                false,
+            );
+        }
+        commands
+    };
+
+    for frame_number in 0..2 {
+        renderer.submit_frame(Color::from_rgba(0), frame());
+        assert_eq!(
+            renderer.last_frame_draw_calls(),
+            1,
+            "Draw calls in frame {frame_number}"
+        );
+        let image = renderer
+            .capture_frame(false)
+            .ok_or("Texture target must be readable")?;
+        for (x, y, pixel) in image.enumerate_pixels() {
+            assert_eq!(
+                pixel.0,
+                [0, 128, 255, 255],
+                "Pixel at ({x}, {y}) in frame {frame_number}"
+            );
+        }
+    }
+
+    Ok(())
+}
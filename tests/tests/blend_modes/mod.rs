@@ -0,0 +1,91 @@
+use crate::util::environment::wgpu_renderer;
+use crate::util::pixels::check_pixel;
+use ruffle_core::swf::{BlendMode, Twips};
+use ruffle_core::Color;
+use ruffle_render::backend::RenderBackend;
+use ruffle_render::color_transform::ColorTransform;
+use ruffle_render::commands::{CommandHandler, CommandList};
+use ruffle_render::matrix::Matrix;
+use ruffle_render_wgpu::backend::WgpuRenderBackend;
+use ruffle_render_wgpu::target::TextureTarget;
+
+const SIZE: u32 = 16;
+
+/// How far a component may be from the one expected, out of 255.
+const TOLERANCE: u8 = 2;
+
+/// Fills the target with `below`, and then blends `above` over its left half with `mode`.
+fn blend(
+    renderer: &mut WgpuRenderBackend<TextureTarget>,
+    below: Color,
+    above: Color,
+    mode: BlendMode,
+) -> Result<image::RgbaImage, libtest_mimic::Failed> {
+    let half = (SIZE / 2) as f32;
+    let mut group = CommandList::new();
+    group.draw_rect(
+        above,
+        Matrix::create_box(half, SIZE as f32, 0.0, Twips::ZERO, Twips::ZERO),
+    );
+    let mut commands = CommandList::new();
+    commands.draw_rect(
+        below,
+        Matrix::create_box(SIZE as f32, SIZE as f32, 0.0, Twips::ZERO, Twips::ZERO),
+    );
+    commands.blend(group, mode, ColorTransform::default());
+    renderer.submit_frame(Color::from_rgba(0), commands);
+    Ok(renderer
+        .capture_frame(false)
+        .ok_or("Texture target must be readable")?)
+}
+
+/// Checks that Screen gives `below + above - below * above` for each component.
+pub fn blend_screen() -> Result<(), libtest_mimic::Failed> {
+    let mut renderer = wgpu_renderer((SIZE, SIZE))?;
+    let image = blend(
+        &mut renderer,
+        Color::from_rgb(0x804000, 255),
+        Color::from_rgb(0x8080FF, 255),
+        BlendMode::Screen,
+    )?;
+    check_pixel(
+        &image,
+        (4, 8),
+        [192, 160, 255, 255],
+        TOLERANCE,
+        "Screened pixel",
+    )?;
+    check_pixel(
+        &image,
+        (12, 8),
+        [128, 64, 0, 255],
+        TOLERANCE,
+        "Pixel outside of the blend",
+    )
+}
+
+/// Checks that Subtract gives `below - above` for each component, and clamps at zero where
+/// `above` is the brighter one.
+pub fn blend_subtract() -> Result<(), libtest_mimic::Failed> {
+    let mut renderer = wgpu_renderer((SIZE, SIZE))?;
+    let image = blend(
+        &mut renderer,
+        Color::from_rgb(0x8040FF, 255),
+        Color::from_rgb(0xC02040, 255),
+        BlendMode::Subtract,
+    )?;
+    check_pixel(
+        &image,
+        (4, 8),
+        [0, 32, 191, 255],
+        TOLERANCE,
+        "Subtracted pixel",
+    )?;
+    check_pixel(
+        &image,
+        (12, 8),
+        [128, 64, 255, 255],
+        TOLERANCE,
+        "Pixel outside of the blend",
+    )
+}
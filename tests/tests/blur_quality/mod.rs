@@ -0,0 +1,116 @@
+use crate::util::environment::wgpu_renderer;
+use crate::util::pixels::square_pixels;
+use ruffle_render::backend::RenderBackend;
+use ruffle_render::bitmap::{Bitmap, BitmapFormat, BitmapHandle};
+use ruffle_render::filters::cpu::{self, FilterImage};
+use ruffle_render::filters::{BlurFilter, Filter};
+use ruffle_render_wgpu::backend::WgpuRenderBackend;
+use ruffle_render_wgpu::target::TextureTarget;
+
+const SIZE: u32 = 48;
+
+/// How far apart a component of the GPU and CPU blurs may be, out of 255.
+const TOLERANCE: u8 = 2;
+
+/// An opaque square from 20 to 28 pixels on both axes, on a transparent background.
+fn source_pixel(x: u32, y: u32) -> [u8; 4] {
+    let inside = (20..28).contains(&x) && (20..28).contains(&y);
+    if inside {
+        [0, 128, 255, 255]
+    } else {
+        [0; 4]
+    }
+}
+
+fn blur(
+    renderer: &mut WgpuRenderBackend<TextureTarget>,
+    source: &BitmapHandle,
+    filter: BlurFilter,
+) -> Result<Vec<u8>, libtest_mimic::Failed> {
+    let dest = renderer.register_bitmap(Bitmap::new(
+        SIZE,
+        SIZE,
+        BitmapFormat::Rgba,
+        vec![0; (SIZE * SIZE * 4) as usize],
+    ))?;
+    let blurred = renderer
+        .apply_filter(
+            source.clone(),
+            (0, 0),
+            (SIZE, SIZE),
+            dest,
+            (0, 0),
+            Filter::BlurFilter(filter),
+        )
+        .ok_or("Blurs must be supported on the GPU")?
+        .retrieve_offscreen_texture()?;
+    Ok(blurred.data().to_vec())
+}
+
+fn alpha(pixels: &[u8], x: u32, y: u32) -> u8 {
+    pixels[((y * SIZE + x) * 4 + 3) as usize]
+}
+
+/// Checks that blurs of quality 1, 3 and 15, of fractional sizes and along only one axis,
+/// give the same box blurs on the GPU as the CPU blur that matches Flash.
+pub fn blur_quality() -> Result<(), libtest_mimic::Failed> {
+    let mut renderer = wgpu_renderer((SIZE, SIZE))?;
+    let pixels = square_pixels(SIZE, source_pixel);
+    let source =
+        renderer.register_bitmap(Bitmap::new(SIZE, SIZE, BitmapFormat::Rgba, pixels.clone()))?;
+    let image = FilterImage::from_rgba(SIZE, SIZE, &pixels);
+
+    for quality in [1, 3, 15] {
+        for (blur_x, blur_y) in [(4.0, 4.0), (2.5, 5.5), (6.0, 0.0)] {
+            let filter = BlurFilter {
+                blur_x,
+                blur_y,
+                quality,
+            };
+            let name = format!("{blur_x}x{blur_y} blur of quality {quality}");
+            let expected = cpu::apply_filter(
+                &Filter::BlurFilter(filter.clone()),
+                &image,
+                (0, 0),
+                (SIZE, SIZE),
+            )
+            .ok_or_else(|| format!("{name} must be supported on the CPU"))?
+            .to_rgba();
+            let actual = blur(&mut renderer, &source, filter)?;
+
+            for (index, (actual, expected)) in actual.iter().zip(&expected).enumerate() {
+                let pixel = index as u32 / 4;
+                assert!(
+                    actual.abs_diff(*expected) <= TOLERANCE,
+                    "{name}: component {} of pixel ({}, {}) is {actual}, expected {expected}",
+                    index % 4,
+                    pixel % SIZE,
+                    pixel / SIZE,
+                );
+            }
+            if blur_y == 0.0 {
+                // Nothing is blurred above or below the square
+                assert_eq!(alpha(&actual, 24, 19), 0, "{name} must only blur sideways");
+                assert_eq!(alpha(&actual, 24, 28), 0, "{name} must only blur sideways");
+            }
+        }
+    }
+
+    // Every pass spreads the blur further out from the square
+    let spread = |renderer: &mut WgpuRenderBackend<TextureTarget>, quality| {
+        let blurred = blur(
+            renderer,
+            &source,
+            BlurFilter {
+                blur_x: 4.0,
+                blur_y: 4.0,
+                quality,
+            },
+        )?;
+        Ok::<_, libtest_mimic::Failed>(alpha(&blurred, 24, 15))
+    };
+    assert_eq!(spread(&mut renderer, 1)?, 0);
+    assert!(spread(&mut renderer, 3)? > 0);
+
+    Ok(())
+}
@@ -0,0 +1,42 @@
+use crate::util::environment::wgpu_renderer;
+use ruffle_core::swf::Twips;
+use ruffle_core::Color;
+use ruffle_render::backend::RenderBackend;
+use ruffle_render::commands::{CommandHandler, CommandList};
+use ruffle_render::matrix::Matrix;
+
+// Not a multiple of 64 pixels, so that every row of the readback is padded
+const WIDTH: u32 = 50;
+const HEIGHT: u32 = 30;
+
+fn solid_red() -> CommandList {
+    let mut commands = CommandList::new();
+    commands.draw_rect(
+        Color::from_rgb(0xFF0000, 255),
+        Matrix::create_box(WIDTH as f32, HEIGHT as f32, 0.0, Twips::ZERO, Twips::ZERO),
+    );
+    commands
+}
+
+pub fn capture_frame_solid_color() -> Result<(), libtest_mimic::Failed> {
+    let mut renderer = wgpu_renderer((WIDTH, HEIGHT))?;
+
+    renderer.submit_frame(Color::from_rgba(0), solid_red());
+    let image = renderer
+        .capture_frame(false)
+        .ok_or("Texture target must be readable")?;
+    assert_eq!(image.dimensions(), (WIDTH, HEIGHT));
+    for (x, y, pixel) in image.enumerate_pixels() {
+        assert_eq!(pixel.0, [255, 0, 0, 255], "Pixel at ({x}, {y})");
+    }
+
+    // Capturing without presenting gives the same pixels
+    let bitmap = RenderBackend::capture_frame(&mut renderer, Color::from_rgba(0), solid_red())
+        .ok_or("Frame capture is not supported")?;
+    assert_eq!((bitmap.width(), bitmap.height()), (WIDTH, HEIGHT));
+    for (index, pixel) in bitmap.data().chunks_exact(4).enumerate() {
+        assert_eq!(pixel, [255, 0, 0, 255], "Pixel {index}");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,92 @@
+use crate::util::environment::wgpu_renderer;
+use ruffle_core::swf::Twips;
+use ruffle_core::Color;
+use ruffle_render::backend::RenderBackend;
+use ruffle_render::commands::{CommandHandler, CommandList};
+use ruffle_render::matrix::Matrix;
+
+const CELLS: u32 = 8;
+const CELL_SIZE: u32 = 8;
+const SIZE: u32 = CELLS * CELL_SIZE;
+
+fn cell_color(x: u32, y: u32) -> [u8; 4] {
+    [(x * 32) as u8, (y * 32) as u8, 128, 255]
+}
+
+fn draw_cells(commands: &mut CommandList, rows: std::ops::Range<u32>) {
+    for y in rows {
+        for x in 0..CELLS {
+            let [r, g, b, a] = cell_color(x, y);
+            commands.draw_rect(
+                Color { r, g, b, a },
+                Matrix::create_box(
+                    CELL_SIZE as f32,
+                    CELL_SIZE as f32,
+                    0.0,
+                    Twips::from_pixels((x * CELL_SIZE) as f64),
+                    Twips::from_pixels((y * CELL_SIZE) as f64),
+                ),
+            );
+        }
+    }
+}
+
+fn check_cells(image: &image::RgbaImage, rows: std::ops::Range<u32>) {
+    for (x, y, pixel) in image.enumerate_pixels() {
+        let (cell_x, cell_y) = (x / CELL_SIZE, y / CELL_SIZE);
+        let expected = if rows.contains(&cell_y) {
+            cell_color(cell_x, cell_y)
+        } else {
+            [0, 0, 0, 0]
+        };
+        for (actual, expected) in pixel.0.iter().zip(expected) {
+            assert!(
+                actual.abs_diff(expected) <= 1,
+                "Pixel at ({x}, {y}) was {:?}, expected {expected:?}",
+                pixel.0
+            );
+        }
+    }
+}
+
+pub fn color_batching() -> Result<(), libtest_mimic::Failed> {
+    let mut renderer = wgpu_renderer((SIZE, SIZE))?;
+
+    // Every rect of the grid is drawn at once
+    let mut commands = CommandList::new();
+    draw_cells(&mut commands, 0..CELLS);
+    renderer.submit_frame(Color::from_rgba(0), commands);
+    assert_eq!(renderer.last_frame_draw_calls(), 1);
+    let image = renderer
+        .capture_frame(false)
+        .ok_or("Texture target must be readable")?;
+    check_cells(&image, 0..CELLS);
+
+    // A mask ends the batch, and the masked rects are batched separately.
+    // The mask is a rectangle, so it's a scissor rect rather than draws into the stencil.
+    let mask = Matrix::create_box(
+        SIZE as f32,
+        (SIZE / 2) as f32,
+        0.0,
+        Twips::ZERO,
+        Twips::ZERO,
+    );
+    let mut commands = CommandList::new();
+    draw_cells(&mut commands, 0..CELLS / 2);
+    commands.push_mask();
+    commands.draw_rect(Color::WHITE, mask);
+    commands.activate_mask();
+    draw_cells(&mut commands, CELLS / 2..CELLS);
+    commands.deactivate_mask();
+    commands.draw_rect(Color::WHITE, mask);
+    commands.pop_mask();
+    renderer.submit_frame(Color::from_rgba(0), commands);
+    assert_eq!(renderer.last_frame_draw_calls(), 2);
+    let image = renderer
+        .capture_frame(false)
+        .ok_or("Texture target must be readable")?;
+    // The mask only covers the top half, so everything that it masked is hidden
+    check_cells(&image, 0..CELLS / 2);
+
+    Ok(())
+}
@@ -0,0 +1,48 @@
+use crate::util::environment::wgpu_renderer;
+use crate::util::shapes::square;
+use ruffle_core::swf::{FillStyle, Fixed8};
+use ruffle_core::Color;
+use ruffle_render::backend::null::NullBitmapSource;
+use ruffle_render::backend::RenderBackend;
+use ruffle_render::color_transform::ColorTransform;
+use ruffle_render::commands::{CommandHandler, CommandList};
+use ruffle_render::transform::{Transform, TransformStack};
+
+const SIZE: u32 = 16;
+
+/// Checks that an opaque shape within four nested clips at 50% alpha ends up with the
+/// alpha that Flash draws it with.
+pub fn color_transform_alpha() -> Result<(), libtest_mimic::Failed> {
+    let mut renderer = wgpu_renderer((SIZE, SIZE))?;
+
+    let style = FillStyle::Color(Color::from_rgb(0xFF0000, 255));
+    let shape = renderer.register_shape(square(&style, SIZE), &NullBitmapSource);
+
+    let half_alpha = Transform {
+        color_transform: ColorTransform {
+            a_mult: Fixed8::from_f32(0.5),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let mut transform_stack = TransformStack::new();
+    for _ in 0..4 {
+        transform_stack.push(&half_alpha);
+    }
+
+    let mut commands = CommandList::new();
+    commands.render_shape(shape, transform_stack.transform());
+    renderer.submit_frame(Color::from_rgba(0), commands);
+    let image = renderer
+        .capture_frame(false)
+        .ok_or("Texture target must be readable")?;
+
+    // The multipliers concatenate to 16/256, and Flash truncates 255 * 16 / 256 to 15,
+    // where rounding would give 16.
+    let expected = 15;
+    let alpha = image.get_pixel(SIZE / 2, SIZE / 2).0[3];
+    if alpha != expected {
+        return Err(format!("Alpha should be {expected}, but was {alpha}").into());
+    }
+    Ok(())
+}
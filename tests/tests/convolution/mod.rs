@@ -0,0 +1,87 @@
+use crate::util::environment::wgpu_renderer;
+use crate::util::pixels::check_pixel;
+use ruffle_core::swf::Twips;
+use ruffle_core::Color;
+use ruffle_render::backend::RenderBackend;
+use ruffle_render::commands::{CommandHandler, CommandList};
+use ruffle_render::filters::cpu::{self, FilterImage};
+use ruffle_render::filters::{ConvolutionFilter, Filter};
+use ruffle_render::matrix::Matrix;
+
+const SIZE: u32 = 32;
+
+/// How far apart a component of the GPU and CPU convolutions may be, out of 255.
+const TOLERANCE: u8 = 2;
+
+/// Dark gray on the left half of the frame, and light gray on the right half.
+fn two_grays() -> CommandList {
+    let mut commands = CommandList::new();
+    for (x, gray) in [(0.0, 0x404040), (16.0, 0xC0C0C0)] {
+        commands.draw_rect(
+            Color::from_rgb(gray, 255),
+            Matrix::create_box(16.0, 32.0, 0.0, Twips::from_pixels(x), Twips::ZERO),
+        );
+    }
+    commands
+}
+
+fn sharpen(clamp: bool) -> Filter {
+    Filter::ConvolutionFilter(ConvolutionFilter {
+        matrix_x: 3,
+        matrix_y: 3,
+        matrix: vec![0.0, -1.0, 0.0, -1.0, 5.0, -1.0, 0.0, -1.0, 0.0],
+        divisor: 1.0,
+        bias: 0.0,
+        preserve_alpha: false,
+        clamp,
+        color: Color::from_rgb(0, 0),
+    })
+}
+
+/// Checks that a sharpening kernel run over a drawn object darkens the dark side of an edge
+/// and lightens the light side, and that it matches the CPU convolution everywhere, edges
+/// of the frame included.
+pub fn convolution() -> Result<(), libtest_mimic::Failed> {
+    let mut renderer = wgpu_renderer((SIZE, SIZE))?;
+
+    renderer.submit_frame(Color::WHITE, two_grays());
+    let unfiltered = renderer
+        .capture_frame(false)
+        .ok_or("Texture target must be readable")?;
+    let unfiltered = FilterImage::from_rgba(SIZE, SIZE, unfiltered.as_raw());
+
+    for clamp in [true, false] {
+        let name = if clamp { "clamped" } else { "unclamped" };
+        let mut commands = CommandList::new();
+        commands.filters(two_grays(), vec![sharpen(clamp)]);
+        renderer.submit_frame(Color::WHITE, commands);
+        let image = renderer
+            .capture_frame(false)
+            .ok_or("Texture target must be readable")?;
+
+        let expected = cpu::apply_filter(&sharpen(clamp), &unfiltered, (0, 0), (SIZE, SIZE))
+            .ok_or("Convolutions must be supported on the CPU")?
+            .to_rgba();
+        for (index, expected) in expected.chunks_exact(4).enumerate() {
+            let position = (index as u32 % SIZE, index as u32 / SIZE);
+            // The CPU result isn't composited yet, so do that over the white background
+            let alpha = u32::from(expected[3]);
+            let over_white = |component: u8| (u32::from(component) + 255 - alpha) as u8;
+            let expected = [
+                over_white(expected[0]),
+                over_white(expected[1]),
+                over_white(expected[2]),
+                255,
+            ];
+            check_pixel(&image, position, expected, TOLERANCE, name)?;
+        }
+
+        // Either side of the edge is pushed all the way to black and white
+        check_pixel(&image, (14, 16), [64, 64, 64, 255], TOLERANCE, name)?;
+        check_pixel(&image, (15, 16), [0, 0, 0, 255], TOLERANCE, name)?;
+        check_pixel(&image, (16, 16), [255, 255, 255, 255], TOLERANCE, name)?;
+        check_pixel(&image, (17, 16), [192, 192, 192, 255], TOLERANCE, name)?;
+    }
+
+    Ok(())
+}
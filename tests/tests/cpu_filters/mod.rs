@@ -0,0 +1,149 @@
+use crate::set_logger;
+use crate::util::runner::TestLogBackend;
+use ruffle_core::limits::ExecutionLimit;
+use ruffle_core::swf::avm1::types::{Action, Push, Value};
+use ruffle_core::swf::{self, Color, Compression, Fixed8, Header, Rectangle, SwfStr, Tag, Twips};
+use ruffle_core::tag_utils::SwfMovie;
+use ruffle_core::PlayerBuilder;
+use ruffle_render::backend::null::NullRenderer;
+use ruffle_render::backend::ViewportDimensions;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+const SIZE: i32 = 4;
+
+fn push(values: &[Value<'static>]) -> Action<'static> {
+    Action::Push(Push {
+        values: values.to_vec(),
+    })
+}
+
+fn string(value: &'static str) -> Value<'static> {
+    Value::Str(SwfStr::from_utf8_str(value))
+}
+
+/// Pushes `flash.<package>.<class>` and the name of its constructor, ready for `NewMethod`.
+fn push_class(package: &'static str, class: &'static str) -> Vec<Action<'static>> {
+    vec![
+        push(&[string("flash")]),
+        Action::GetVariable,
+        push(&[string(package)]),
+        Action::GetMember,
+        push(&[string(class)]),
+    ]
+}
+
+/// A movie that applies a color matrix filter that moves red into green to a red bitmap,
+/// and traces what `applyFilter` returned and the color of its first pixel afterwards.
+fn color_matrix_movie() -> Result<SwfMovie, libtest_mimic::Failed> {
+    let mut actions = vec![];
+
+    // var b = new flash.display.BitmapData(SIZE, SIZE, false, 0xFF0000);
+    actions.push(push(&[
+        string("b"),
+        Value::Int(0xFF0000),
+        Value::Bool(false),
+        Value::Int(SIZE),
+        Value::Int(SIZE),
+        Value::Int(4),
+    ]));
+    actions.extend(push_class("display", "BitmapData"));
+    actions.extend([Action::NewMethod, Action::SetVariable]);
+
+    // var filter = new flash.filters.ColorMatrixFilter([...]);
+    #[rustfmt::skip]
+    let matrix = [
+        0, 0, 0, 0, 0,
+        1, 0, 0, 0, 0,
+        0, 0, 1, 0, 0,
+        0, 0, 0, 1, 0,
+    ];
+    let mut values = vec![string("filter")];
+    values.extend(matrix.iter().rev().map(|&value| Value::Int(value)));
+    values.push(Value::Int(matrix.len() as i32));
+    actions.extend([push(&values), Action::InitArray, push(&[Value::Int(1)])]);
+    actions.extend(push_class("filters", "ColorMatrixFilter"));
+    actions.extend([Action::NewMethod, Action::SetVariable]);
+
+    // trace(b.applyFilter(b, b.rectangle, new flash.geom.Point(0, 0), filter));
+    actions.extend([push(&[string("filter")]), Action::GetVariable]);
+    actions.push(push(&[Value::Int(0), Value::Int(0), Value::Int(2)]));
+    actions.extend(push_class("geom", "Point"));
+    actions.push(Action::NewMethod);
+    actions.extend([
+        push(&[string("b")]),
+        Action::GetVariable,
+        push(&[string("rectangle")]),
+        Action::GetMember,
+        push(&[string("b")]),
+        Action::GetVariable,
+        push(&[Value::Int(4), string("b")]),
+        Action::GetVariable,
+        push(&[string("applyFilter")]),
+        Action::CallMethod,
+        Action::Trace,
+    ]);
+
+    // trace(b.getPixel(0, 0));
+    actions.extend([
+        push(&[Value::Int(0), Value::Int(0), Value::Int(2), string("b")]),
+        Action::GetVariable,
+        push(&[string("getPixel")]),
+        Action::CallMethod,
+        Action::Trace,
+        Action::End,
+    ]);
+
+    let mut action_data = vec![];
+    let mut writer = swf::avm1::write::Writer::new(&mut action_data, 10);
+    for action in &actions {
+        writer.write_action(action)?;
+    }
+
+    let header = Header {
+        compression: Compression::None,
+        version: 10,
+        stage_size: Rectangle {
+            x_min: Twips::ZERO,
+            x_max: Twips::from_pixels(SIZE.into()),
+            y_min: Twips::ZERO,
+            y_max: Twips::from_pixels(SIZE.into()),
+        },
+        frame_rate: Fixed8::from_f32(30.0),
+        num_frames: 1,
+    };
+    let mut data = vec![];
+    swf::write_swf(
+        &header,
+        &[
+            Tag::SetBackgroundColor(Color::WHITE),
+            Tag::DoAction(&action_data),
+            Tag::ShowFrame,
+        ],
+        &mut data,
+    )?;
+    Ok(SwfMovie::from_data(&data, None, None)?)
+}
+
+/// Checks that `BitmapData.applyFilter` applies filters on the CPU when the renderer can't apply
+/// them. The null renderer leaves the bitmap untouched if it's asked to apply them itself.
+pub fn cpu_filter_fallback_avm1() -> Result<(), libtest_mimic::Failed> {
+    set_logger();
+    let trace_output = Rc::new(RefCell::new(String::new()));
+    let renderer = NullRenderer::new(ViewportDimensions {
+        width: SIZE as u32,
+        height: SIZE as u32,
+        scale_factor: 1.0,
+    });
+    let player = PlayerBuilder::new()
+        .with_renderer(renderer)
+        .with_log(TestLogBackend::new(trace_output.clone()))
+        .with_movie(color_matrix_movie()?)
+        .build();
+    let mut player = player.lock().unwrap();
+    while !player.preload(&mut ExecutionLimit::exhausted()) {}
+    player.run_frame();
+
+    std::assert_eq!(*trace_output.borrow(), "0\n65280\n");
+    Ok(())
+}
@@ -0,0 +1,169 @@
+use crate::util::environment::wgpu_renderer;
+use crate::util::pixels::{check_pixel, square_pixels};
+use ruffle_core::swf::Twips;
+use ruffle_core::Color;
+use ruffle_render::backend::RenderBackend;
+use ruffle_render::bitmap::{Bitmap, BitmapFormat};
+use ruffle_render::commands::{CommandHandler, CommandList};
+use ruffle_render::filters::{DisplacementMapFilter, DisplacementMapFilterMode, Filter};
+use ruffle_render::matrix::Matrix;
+use ruffle_render::transform::Transform;
+
+const SIZE: u32 = 32;
+const FRAME_SIZE: u32 = 64;
+
+/// Each pixel of the source stores its own position, 8 per step, in its red and green.
+fn source_pixel(x: u32, y: u32) -> [u8; 4] {
+    [(x * 8) as u8, (y * 8) as u8, 0, 255]
+}
+
+/// A map that leaves the left half alone, and moves the right half over by 8 pixels.
+fn map_pixel(x: u32, _y: u32) -> [u8; 4] {
+    let red = if x < SIZE / 2 { 128 } else { 255 };
+    [red, 128, 0, 255]
+}
+
+/// Checks that displaced pixels are read from where the map points them at, and that every
+/// mode treats the ones pointed outside of the source the way it should.
+pub fn displacement_map() -> Result<(), libtest_mimic::Failed> {
+    let mut renderer = wgpu_renderer((SIZE, SIZE))?;
+    let source = renderer.register_bitmap(Bitmap::new(
+        SIZE,
+        SIZE,
+        BitmapFormat::Rgba,
+        square_pixels(SIZE, source_pixel),
+    ))?;
+    let map = renderer.register_bitmap(Bitmap::new(
+        SIZE,
+        SIZE,
+        BitmapFormat::Rgba,
+        square_pixels(SIZE, map_pixel),
+    ))?;
+
+    // (mode, pixel for a destination that was pointed past the right edge of the source)
+    let cases: [(_, fn(u32, u32) -> [u8; 4]); 4] = [
+        (DisplacementMapFilterMode::Wrap, |x, y| {
+            source_pixel(x + 8 - SIZE, y)
+        }),
+        (DisplacementMapFilterMode::Clamp, |_, y| {
+            source_pixel(SIZE - 1, y)
+        }),
+        (DisplacementMapFilterMode::Ignore, source_pixel),
+        (DisplacementMapFilterMode::Color, |_, _| [0, 0, 255, 255]),
+    ];
+    for (mode, outside) in cases {
+        let dest = renderer.register_bitmap(Bitmap::new(
+            SIZE,
+            SIZE,
+            BitmapFormat::Rgba,
+            vec![0; (SIZE * SIZE * 4) as usize],
+        ))?;
+        let displaced = renderer
+            .apply_filter(
+                source.clone(),
+                (0, 0),
+                (SIZE, SIZE),
+                dest,
+                (0, 0),
+                Filter::DisplacementMapFilter(DisplacementMapFilter {
+                    map: map.clone(),
+                    map_point: (0, 0),
+                    component_x: 1,
+                    component_y: 2,
+                    // (255 - 128) * 16 / 256 rounds to 8 pixels
+                    scale_x: 16.0,
+                    scale_y: 16.0,
+                    mode,
+                    color: Color::from_rgb(0x0000FF, 255),
+                }),
+            )
+            .ok_or("Displacement maps must be supported on the GPU")?
+            .retrieve_offscreen_texture()?;
+
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                let expected = if x < SIZE / 2 {
+                    source_pixel(x, y)
+                } else if x + 8 < SIZE {
+                    source_pixel(x + 8, y)
+                } else {
+                    outside(x, y)
+                };
+                let offset = ((y * SIZE + x) * 4) as usize;
+                assert_eq!(
+                    displaced.data()[offset..offset + 4],
+                    expected,
+                    "{mode:?} mode at ({x}, {y})"
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that the map of a filter on a display object lines up with the object, rather than
+/// with the top left of the frame that the filter is applied to.
+pub fn displacement_map_position() -> Result<(), libtest_mimic::Failed> {
+    let mut renderer = wgpu_renderer((FRAME_SIZE, FRAME_SIZE))?;
+    let source = renderer.register_bitmap(Bitmap::new(
+        SIZE,
+        SIZE,
+        BitmapFormat::Rgba,
+        square_pixels(SIZE, source_pixel),
+    ))?;
+    let map = renderer.register_bitmap(Bitmap::new(
+        SIZE,
+        SIZE,
+        BitmapFormat::Rgba,
+        square_pixels(SIZE, map_pixel),
+    ))?;
+
+    // The object is drawn from 16 to 48 pixels on both axes, with its map at the same place
+    let mut object = CommandList::new();
+    object.render_bitmap(
+        source,
+        Transform {
+            matrix: Matrix::translate(Twips::from_pixels(16.0), Twips::from_pixels(16.0)),
+            ..Default::default()
+        },
+        false,
+    );
+    let mut commands = CommandList::new();
+    commands.filters(
+        object,
+        vec![Filter::DisplacementMapFilter(DisplacementMapFilter {
+            map,
+            map_point: (16, 16),
+            component_x: 1,
+            component_y: 2,
+            scale_x: 16.0,
+            scale_y: 16.0,
+            mode: DisplacementMapFilterMode::Clamp,
+            color: Color::from_rgb(0, 0),
+        })],
+    );
+    renderer.submit_frame(Color::WHITE, commands);
+    let image = renderer
+        .capture_frame(false)
+        .ok_or("Texture target must be readable")?;
+
+    let white = [255, 255, 255, 255];
+    for y in 0..FRAME_SIZE {
+        for x in 0..FRAME_SIZE {
+            let expected = if !(16..48).contains(&y) {
+                white
+            } else if (16..32).contains(&x) {
+                source_pixel(x - 16, y - 16)
+            } else if (32..40).contains(&x) {
+                source_pixel(x + 8 - 16, y - 16)
+            } else {
+                // Either outside of the object, or displaced from the empty space to its right
+                white
+            };
+            check_pixel(&image, (x, y), expected, 1, "Displaced object")?;
+        }
+    }
+
+    Ok(())
+}
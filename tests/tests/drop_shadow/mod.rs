@@ -0,0 +1,94 @@
+use crate::util::environment::wgpu_renderer;
+use crate::util::pixels::check_pixel;
+use ruffle_core::swf::Twips;
+use ruffle_core::Color;
+use ruffle_render::backend::RenderBackend;
+use ruffle_render::commands::{CommandHandler, CommandList};
+use ruffle_render::filters::{DropShadowFilter, Filter};
+use ruffle_render::matrix::Matrix;
+
+const SIZE: u32 = 64;
+
+const WHITE: [u8; 4] = [255, 255, 255, 255];
+const RED: [u8; 4] = [255, 0, 0, 255];
+const BLUE: [u8; 4] = [0, 0, 255, 255];
+
+/// A red square from 16 to 40 pixels on both axes, casting a sharp blue shadow 8 pixels
+/// to the right.
+fn shadowed_square(shadow: DropShadowFilter) -> CommandList {
+    let mut square = CommandList::new();
+    square.draw_rect(
+        Color::from_rgb(0xFF0000, 255),
+        Matrix::create_box(
+            24.0,
+            24.0,
+            0.0,
+            Twips::from_pixels(16.0),
+            Twips::from_pixels(16.0),
+        ),
+    );
+    let mut commands = CommandList::new();
+    commands.filters(
+        square,
+        vec![Filter::DropShadowFilter(DropShadowFilter {
+            color: Color::from_rgb(0x0000FF, 255),
+            angle: 0.0,
+            distance: 8.0,
+            blur_x: 0.0,
+            blur_y: 0.0,
+            ..shadow
+        })],
+    );
+    commands
+}
+
+/// Checks that a drop shadow is offset by its distance, and that hiding the object,
+/// knocking it out and casting the shadow inside of it all change which parts are drawn.
+pub fn drop_shadow() -> Result<(), libtest_mimic::Failed> {
+    let mut renderer = wgpu_renderer((SIZE, SIZE))?;
+
+    let cases = [
+        (
+            "shadow",
+            DropShadowFilter::default(),
+            [(12, WHITE), (20, RED), (36, RED), (44, BLUE)],
+        ),
+        (
+            "hidden object",
+            DropShadowFilter {
+                hide_object: true,
+                ..Default::default()
+            },
+            [(12, WHITE), (20, WHITE), (36, BLUE), (44, BLUE)],
+        ),
+        (
+            "knockout",
+            DropShadowFilter {
+                knockout: true,
+                ..Default::default()
+            },
+            [(12, WHITE), (20, WHITE), (36, WHITE), (44, BLUE)],
+        ),
+        (
+            "inner shadow",
+            DropShadowFilter {
+                inner: true,
+                ..Default::default()
+            },
+            [(12, WHITE), (20, BLUE), (36, RED), (44, WHITE)],
+        ),
+    ];
+    for (name, shadow, expected) in cases {
+        renderer.submit_frame(Color::WHITE, shadowed_square(shadow));
+        let image = renderer
+            .capture_frame(false)
+            .ok_or("Texture target must be readable")?;
+        for (x, color) in expected {
+            check_pixel(&image, (x, 28), color, 2, name)?;
+        }
+        // The shadow is only cast sideways
+        check_pixel(&image, (28, 44), WHITE, 2, name)?;
+    }
+
+    Ok(())
+}
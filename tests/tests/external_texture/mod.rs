@@ -0,0 +1,103 @@
+use crate::set_logger;
+use crate::util::environment::WGPU;
+use ruffle_core::swf::Twips;
+use ruffle_core::Color;
+use ruffle_render::backend::{RenderBackend, ViewportDimensions};
+use ruffle_render::commands::{CommandHandler, CommandList};
+use ruffle_render::matrix::Matrix;
+use ruffle_render_wgpu::backend::WgpuRenderBackend;
+use ruffle_render_wgpu::target::ExternalTextureTarget;
+use ruffle_render_wgpu::wgpu;
+use std::num::NonZeroU32;
+use std::sync::Arc;
+
+// 64 pixels make a row of exactly 256 bytes, so the texture can be copied without padding
+const WIDTH: u32 = 64;
+const HEIGHT: u32 = 16;
+
+fn read_texture(device: &wgpu::Device, queue: &wgpu::Queue, texture: &wgpu::Texture) -> Vec<u8> {
+    let size = wgpu::Extent3d {
+        width: WIDTH,
+        height: HEIGHT,
+        depth_or_array_layers: 1,
+    };
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: None,
+        size: (WIDTH * HEIGHT * 4) as u64,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+    let mut encoder = device.create_command_encoder(&Default::default());
+    encoder.copy_texture_to_buffer(
+        texture.as_image_copy(),
+        wgpu::ImageCopyBuffer {
+            buffer: &buffer,
+            layout: wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: NonZeroU32::new(WIDTH * 4),
+                rows_per_image: None,
+            },
+        },
+        size,
+    );
+    let index = queue.submit(Some(encoder.finish()));
+    buffer.slice(..).map_async(wgpu::MapMode::Read, |_| {});
+    device.poll(wgpu::Maintain::WaitForSubmissionIndex(index));
+    let data = buffer.slice(..).get_mapped_range().to_vec();
+    buffer.unmap();
+    data
+}
+
+pub fn external_texture() -> Result<(), libtest_mimic::Failed> {
+    set_logger();
+    let descriptors = WGPU.clone().ok_or("Couldn't create a wgpu device")?;
+
+    // An sRGB texture, as an application would most likely have
+    let format = wgpu::TextureFormat::Rgba8UnormSrgb;
+    let texture = Arc::new(descriptors.device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("External texture"),
+        size: wgpu::Extent3d {
+            width: WIDTH,
+            height: HEIGHT,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        view_formats: &[format],
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+    }));
+    let target = ExternalTextureTarget::new(texture.clone(), format, (WIDTH, HEIGHT));
+    let mut renderer = WgpuRenderBackend::new(descriptors.clone(), target)?;
+
+    // The viewport can't be resized away from the size of the texture
+    renderer.set_viewport_dimensions(ViewportDimensions {
+        width: WIDTH * 2,
+        height: HEIGHT * 2,
+        scale_factor: 1.0,
+    });
+    let viewport = renderer.viewport_dimensions();
+    assert_eq!((viewport.width, viewport.height), (WIDTH, HEIGHT));
+
+    let color = Color::from_rgb(0x336699, 255);
+    let mut commands = CommandList::new();
+    commands.draw_rect(
+        color.clone(),
+        Matrix::create_box(WIDTH as f32, HEIGHT as f32, 0.0, Twips::ZERO, Twips::ZERO),
+    );
+    renderer.submit_frame(Color::from_rgba(0), commands);
+
+    // Flash colors are already in sRGB, so they must end up in the texture unchanged
+    let pixels = read_texture(&descriptors.device, &descriptors.queue, &texture);
+    for (index, pixel) in pixels.chunks_exact(4).enumerate() {
+        for (actual, expected) in pixel.iter().zip([color.r, color.g, color.b, color.a]) {
+            assert!(
+                actual.abs_diff(expected) <= 1,
+                "Pixel {index} is {pixel:?}, expected {color:?}"
+            );
+        }
+    }
+
+    Ok(())
+}
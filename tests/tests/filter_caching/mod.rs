@@ -0,0 +1,139 @@
+use crate::util::environment::isolated_wgpu_renderer;
+use ruffle_core::limits::ExecutionLimit;
+use ruffle_core::swf::{
+    self, BlurFilter, BlurFilterFlags, Color, Compression, FillStyle, Filter, Fixed16, Fixed8,
+    Header, PlaceObject, PlaceObjectAction, Rectangle, Shape, ShapeFlag, ShapeRecord, ShapeStyles,
+    StyleChangeData, Tag, Twips,
+};
+use ruffle_core::tag_utils::SwfMovie;
+use ruffle_core::PlayerBuilder;
+
+const SIZE: u32 = 64;
+const SQUARE_SIZE: f64 = 32.0;
+
+/// A movie that places a blurred square on its only frame, and then never changes it.
+fn blurred_square_movie() -> Result<SwfMovie, libtest_mimic::Failed> {
+    let header = Header {
+        compression: Compression::None,
+        version: 10,
+        stage_size: Rectangle {
+            x_min: Twips::ZERO,
+            x_max: Twips::from_pixels(SIZE.into()),
+            y_min: Twips::ZERO,
+            y_max: Twips::from_pixels(SIZE.into()),
+        },
+        frame_rate: Fixed8::from_f32(30.0),
+        num_frames: 1,
+    };
+    let square_bounds = Rectangle {
+        x_min: Twips::ZERO,
+        x_max: Twips::from_pixels(SQUARE_SIZE),
+        y_min: Twips::ZERO,
+        y_max: Twips::from_pixels(SQUARE_SIZE),
+    };
+    let side = Twips::from_pixels(SQUARE_SIZE);
+    let square = Shape {
+        version: 1,
+        id: 1,
+        shape_bounds: square_bounds.clone(),
+        edge_bounds: square_bounds,
+        flags: ShapeFlag::empty(),
+        styles: ShapeStyles {
+            fill_styles: vec![FillStyle::Color(Color::from_rgb(0xFF0000, 255))],
+            line_styles: vec![],
+        },
+        shape: vec![
+            ShapeRecord::StyleChange(Box::new(StyleChangeData {
+                move_to: None,
+                fill_style_0: None,
+                fill_style_1: Some(1),
+                line_style: None,
+                new_styles: None,
+            })),
+            ShapeRecord::StraightEdge {
+                delta_x: side,
+                delta_y: Twips::ZERO,
+            },
+            ShapeRecord::StraightEdge {
+                delta_x: Twips::ZERO,
+                delta_y: side,
+            },
+            ShapeRecord::StraightEdge {
+                delta_x: -side,
+                delta_y: Twips::ZERO,
+            },
+            ShapeRecord::StraightEdge {
+                delta_x: Twips::ZERO,
+                delta_y: -side,
+            },
+        ],
+    };
+    let place = PlaceObject {
+        version: 3,
+        action: PlaceObjectAction::Place(1),
+        depth: 1,
+        matrix: Some(swf::Matrix::translate(
+            Twips::from_pixels(16.0),
+            Twips::from_pixels(16.0),
+        )),
+        color_transform: None,
+        ratio: None,
+        name: None,
+        clip_depth: None,
+        class_name: None,
+        filters: Some(vec![Filter::BlurFilter(Box::new(BlurFilter {
+            blur_x: Fixed16::from_f32(8.0),
+            blur_y: Fixed16::from_f32(8.0),
+            flags: BlurFilterFlags::from_passes(2),
+        }))]),
+        background_color: None,
+        blend_mode: None,
+        clip_actions: None,
+        has_image: false,
+        is_bitmap_cached: Some(true),
+        is_visible: None,
+        amf_data: None,
+    };
+    let mut data = vec![];
+    swf::write_swf(
+        &header,
+        &[
+            Tag::SetBackgroundColor(Color::WHITE),
+            Tag::DefineShape(square),
+            Tag::PlaceObject(Box::new(place)),
+            Tag::ShowFrame,
+        ],
+        &mut data,
+    )?;
+    Ok(SwfMovie::from_data(&data, None, None)?)
+}
+
+/// Checks that a filtered object that doesn't change is only blurred for the first frame,
+/// and drawn out of its cached bitmap after that.
+pub fn filter_caching() -> Result<(), libtest_mimic::Failed> {
+    // Other tests blur at the same time, so count the passes on descriptors of our own
+    let renderer = isolated_wgpu_renderer((SIZE, SIZE))?;
+    let descriptors = renderer.descriptors().clone();
+    let player = PlayerBuilder::new()
+        .with_renderer(renderer)
+        .with_viewport_dimensions(SIZE, SIZE, 1.0)
+        .with_movie(blurred_square_movie()?)
+        .build();
+    let mut player = player.lock().unwrap();
+    while !player.preload(&mut ExecutionLimit::exhausted()) {}
+
+    player.run_frame();
+    player.render();
+    let blur_passes = descriptors.blur_passes();
+    assert!(blur_passes > 0, "The first frame must be blurred");
+
+    player.run_frame();
+    player.render();
+    assert_eq!(
+        descriptors.blur_passes() - blur_passes,
+        0,
+        "An unchanged frame must reuse the blurred bitmap"
+    );
+
+    Ok(())
+}
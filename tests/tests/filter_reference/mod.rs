@@ -0,0 +1,120 @@
+use crate::util::environment::wgpu_renderer;
+use crate::util::pixels::square_pixels;
+use ruffle_core::Color;
+use ruffle_render::backend::RenderBackend;
+use ruffle_render::bitmap::{Bitmap, BitmapFormat};
+use ruffle_render::filters::cpu::{self, FilterImage};
+use ruffle_render::filters::{
+    BlurFilter, ColorMatrixFilter, ConvolutionFilter, DropShadowFilter, Filter, GlowFilter,
+};
+
+const SIZE: u32 = 32;
+
+/// How far apart a component of the two results may be, out of 255.
+const TOLERANCE: u8 = 2;
+
+/// A transparent square, with a gradient across it and a solid square in the middle.
+fn source_pixel(x: u32, y: u32) -> [u8; 4] {
+    let inside = (8..24).contains(&x) && (8..24).contains(&y);
+    let alpha = if inside { 255 } else { (x * 4) as u8 };
+    let mul = |c: u32| ((c * u32::from(alpha)) / 255) as u8;
+    [mul(x * 8), mul(y * 8), mul(128), alpha]
+}
+
+fn filters() -> Vec<(&'static str, Filter)> {
+    let mut color_matrix = ColorMatrixFilter::default();
+    color_matrix.matrix[1] = 0.5;
+    color_matrix.matrix[4] = 32.0;
+    color_matrix.matrix[18] = 0.75;
+    vec![
+        (
+            "blur",
+            Filter::BlurFilter(BlurFilter {
+                blur_x: 5.0,
+                blur_y: 2.5,
+                quality: 2,
+            }),
+        ),
+        ("color matrix", Filter::ColorMatrixFilter(color_matrix)),
+        (
+            "convolution",
+            Filter::ConvolutionFilter(ConvolutionFilter {
+                matrix_x: 3,
+                matrix_y: 3,
+                matrix: vec![0.0, -1.0, 0.0, -1.0, 5.0, -1.0, 0.0, -1.0, 0.0],
+                preserve_alpha: false,
+                clamp: false,
+                color: Color::from_rgb(0x00FF00, 128),
+                ..Default::default()
+            }),
+        ),
+        (
+            "drop shadow",
+            Filter::DropShadowFilter(DropShadowFilter {
+                distance: 3.0,
+                ..Default::default()
+            }),
+        ),
+        (
+            "inner glow",
+            Filter::GlowFilter(GlowFilter {
+                inner: true,
+                ..Default::default()
+            }),
+        ),
+        (
+            "knockout glow",
+            Filter::GlowFilter(GlowFilter {
+                knockout: true,
+                ..Default::default()
+            }),
+        ),
+    ]
+}
+
+/// Checks that the GPU filters give the same results as the CPU ones they're based on.
+pub fn filter_reference() -> Result<(), libtest_mimic::Failed> {
+    let mut renderer = wgpu_renderer((SIZE, SIZE))?;
+    let pixels = square_pixels(SIZE, source_pixel);
+    let source =
+        renderer.register_bitmap(Bitmap::new(SIZE, SIZE, BitmapFormat::Rgba, pixels.clone()))?;
+    let image = FilterImage::from_rgba(SIZE, SIZE, &pixels);
+
+    for (name, filter) in filters() {
+        assert!(renderer.is_filter_supported(&filter));
+        let expected = cpu::apply_filter(&filter, &image, (4, 4), (SIZE - 8, SIZE - 8))
+            .ok_or_else(|| format!("{name} must be supported on the CPU"))?
+            .to_rgba();
+
+        let dest = renderer.register_bitmap(Bitmap::new(
+            SIZE - 8,
+            SIZE - 8,
+            BitmapFormat::Rgba,
+            vec![0; ((SIZE - 8) * (SIZE - 8) * 4) as usize],
+        ))?;
+        let actual = renderer
+            .apply_filter(
+                source.clone(),
+                (4, 4),
+                (SIZE - 8, SIZE - 8),
+                dest,
+                (0, 0),
+                filter,
+            )
+            .ok_or("Filters must be supported on the GPU")?
+            .retrieve_offscreen_texture()?;
+
+        for (index, (actual, expected)) in actual.data().iter().zip(&expected).enumerate() {
+            let pixel = index / 4;
+            assert!(
+                actual.abs_diff(*expected) <= TOLERANCE,
+                "{name}: component {} of pixel ({}, {}) is {actual}, expected {expected}",
+                index % 4,
+                pixel as u32 % (SIZE - 8),
+                pixel as u32 / (SIZE - 8),
+            );
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use crate::util::environment::wgpu_renderer;
+use crate::util::pixels::check_pixel;
+use crate::util::shapes::square;
+use ruffle_core::swf::{
+    self, FillStyle, Fixed16, Fixed8, Gradient, GradientInterpolation, GradientRecord,
+    GradientSpread, Twips,
+};
+use ruffle_core::Color;
+use ruffle_render::backend::null::NullBitmapSource;
+use ruffle_render::backend::RenderBackend;
+use ruffle_render::commands::{CommandHandler, CommandList};
+use ruffle_render::transform::Transform;
+use ruffle_render_wgpu::backend::WgpuRenderBackend;
+use ruffle_render_wgpu::target::TextureTarget;
+
+const SIZE: u32 = 64;
+
+/// How far a gray may be from the one expected, out of 255.
+const TOLERANCE: u8 = 4;
+
+/// A black to white gradient in a circle of radius 16 pixels, centered on the middle of the
+/// pixel at (16, 32), so that a focal point on its right edge is on the middle of (32, 32).
+fn focal_gradient(focal_point: f32) -> FillStyle {
+    let radius = 16.0;
+    FillStyle::FocalGradient {
+        gradient: Gradient {
+            matrix: swf::Matrix::translate(Twips::from_pixels(16.5), Twips::from_pixels(32.5))
+                * swf::Matrix::scale(
+                    Fixed16::from_f32(radius * 20.0 / 16384.0),
+                    Fixed16::from_f32(radius * 20.0 / 16384.0),
+                ),
+            spread: GradientSpread::Pad,
+            interpolation: GradientInterpolation::Rgb,
+            records: vec![
+                GradientRecord {
+                    ratio: 0,
+                    color: Color::BLACK,
+                },
+                GradientRecord {
+                    ratio: 255,
+                    color: Color::WHITE,
+                },
+            ],
+        },
+        focal_point: Fixed8::from_f32(focal_point),
+    }
+}
+
+fn draw(
+    renderer: &mut WgpuRenderBackend<TextureTarget>,
+    focal_point: f32,
+) -> Result<image::RgbaImage, libtest_mimic::Failed> {
+    let shape = renderer.register_shape(
+        square(&focal_gradient(focal_point), SIZE),
+        &NullBitmapSource,
+    );
+    let mut commands = CommandList::new();
+    commands.render_shape(shape, Transform::default());
+    renderer.submit_frame(Color::from_rgba(0), commands);
+    Ok(renderer
+        .capture_frame(false)
+        .ok_or("Texture target must be readable")?)
+}
+
+/// Checks that a focal gradient with its focal point on the edge of its circle starts at the
+/// focal point and spreads out evenly from there, with the pixels behind it padded, and that
+/// focal points past the edge are treated as being on it.
+pub fn focal_gradient() -> Result<(), libtest_mimic::Failed> {
+    let mut renderer = wgpu_renderer((SIZE, SIZE))?;
+
+    let image = draw(&mut renderer, 1.0)?;
+    for x in 0..SIZE {
+        // Across the circle, t goes from 1 on its left edge down to 0 at the focal point
+        let gray = if x > 32 {
+            255
+        } else {
+            (f64::from(32 - x) / 32.0 * 255.0).round() as u8
+        };
+        check_pixel(
+            &image,
+            (x, 32),
+            [gray, gray, gray, 255],
+            TOLERANCE,
+            "Focal point on the edge",
+        )?;
+    }
+
+    let clamped = draw(&mut renderer, 2.0)?;
+    for (x, y, pixel) in clamped.enumerate_pixels() {
+        check_pixel(
+            &image,
+            (x, y),
+            pixel.0,
+            TOLERANCE,
+            "Focal point past the edge",
+        )?;
+    }
+
+    Ok(())
+}
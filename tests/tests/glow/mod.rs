@@ -0,0 +1,75 @@
+use crate::util::environment::wgpu_renderer;
+use ruffle_core::swf::Twips;
+use ruffle_core::Color;
+use ruffle_render::backend::RenderBackend;
+use ruffle_render::commands::{CommandHandler, CommandList};
+use ruffle_render::filters::{Filter, GlowFilter};
+use ruffle_render::matrix::Matrix;
+
+const SIZE: u32 = 64;
+
+/// A red square from 16 to 48 pixels on both axes, with a green glow.
+fn glowing_square(inner: bool) -> CommandList {
+    let mut square = CommandList::new();
+    square.draw_rect(
+        Color::from_rgb(0xFF0000, 255),
+        Matrix::create_box(
+            32.0,
+            32.0,
+            0.0,
+            Twips::from_pixels(16.0),
+            Twips::from_pixels(16.0),
+        ),
+    );
+    let mut commands = CommandList::new();
+    commands.filters(
+        square,
+        vec![Filter::GlowFilter(GlowFilter {
+            color: Color::from_rgb(0x00FF00, 255),
+            blur_x: 8.0,
+            blur_y: 8.0,
+            inner,
+            ..Default::default()
+        })],
+    );
+    commands
+}
+
+/// Checks that an outer glow is only drawn around a shape, and an inner glow only inside of
+/// its edges, with the middle of the shape left alone either way.
+pub fn glow() -> Result<(), libtest_mimic::Failed> {
+    let mut renderer = wgpu_renderer((SIZE, SIZE))?;
+
+    renderer.submit_frame(Color::WHITE, glowing_square(false));
+    let image = renderer
+        .capture_frame(false)
+        .ok_or("Texture target must be readable")?;
+    let [r, g, b, _] = image.get_pixel(14, 32).0;
+    if g < 200 || r > 200 || b > 200 {
+        return Err(format!("Outer glow should be green, but was {:?}", [r, g, b]).into());
+    }
+    assert_eq!(
+        image.get_pixel(4, 32).0,
+        [255, 255, 255, 255],
+        "Far outside"
+    );
+    assert_eq!(image.get_pixel(17, 32).0, [255, 0, 0, 255], "Edge");
+    assert_eq!(image.get_pixel(32, 32).0, [255, 0, 0, 255], "Middle");
+
+    renderer.submit_frame(Color::WHITE, glowing_square(true));
+    let image = renderer
+        .capture_frame(false)
+        .ok_or("Texture target must be readable")?;
+    let [r, g, b, _] = image.get_pixel(17, 32).0;
+    if g < 100 || b > 0 {
+        return Err(format!(
+            "Inner glow should tint the edge green, but was {:?}",
+            [r, g, b]
+        )
+        .into());
+    }
+    assert_eq!(image.get_pixel(14, 32).0, [255, 255, 255, 255], "Outside");
+    assert_eq!(image.get_pixel(32, 32).0, [255, 0, 0, 255], "Middle");
+
+    Ok(())
+}
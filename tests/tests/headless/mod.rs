@@ -0,0 +1,71 @@
+use crate::set_logger;
+use ruffle_core::limits::ExecutionLimit;
+use ruffle_core::swf::{self, Color, Compression, Fixed8, Header, Rectangle, Tag, Twips};
+use ruffle_core::tag_utils::SwfMovie;
+use ruffle_core::PlayerBuilder;
+use ruffle_render_wgpu::backend::WgpuRenderBackend;
+use ruffle_render_wgpu::target::TextureTarget;
+use ruffle_render_wgpu::wgpu;
+
+const WIDTH: u32 = 40;
+const HEIGHT: u32 = 30;
+
+/// A movie with a single, empty frame over a solid background.
+fn background_movie(background: Color) -> Result<SwfMovie, libtest_mimic::Failed> {
+    let header = Header {
+        compression: Compression::None,
+        version: 10,
+        stage_size: Rectangle {
+            x_min: Twips::ZERO,
+            x_max: Twips::from_pixels(WIDTH.into()),
+            y_min: Twips::ZERO,
+            y_max: Twips::from_pixels(HEIGHT.into()),
+        },
+        frame_rate: Fixed8::from_f32(30.0),
+        num_frames: 1,
+    };
+    let mut data = vec![];
+    swf::write_swf(
+        &header,
+        &[Tag::SetBackgroundColor(background), Tag::ShowFrame],
+        &mut data,
+    )?;
+    Ok(SwfMovie::from_data(&data, None, None)?)
+}
+
+/// Checks that a movie can be rendered and read back without any window, on whichever
+/// adapter is available, even if that's a software one.
+pub fn headless_render() -> Result<(), libtest_mimic::Failed> {
+    set_logger();
+    let renderer = WgpuRenderBackend::for_offscreen(
+        (WIDTH, HEIGHT),
+        wgpu::Backends::all(),
+        wgpu::PowerPreference::default(),
+        None,
+    )?;
+
+    let background = Color::from_rgb(0x336699, 255);
+    let player = PlayerBuilder::new()
+        .with_renderer(renderer)
+        .with_viewport_dimensions(WIDTH, HEIGHT, 1.0)
+        .with_movie(background_movie(background.clone())?)
+        .build();
+    let mut player = player.lock().unwrap();
+    while !player.preload(&mut ExecutionLimit::exhausted()) {}
+    player.run_frame();
+    player.render();
+
+    let image = player
+        .renderer_mut()
+        .downcast_mut::<WgpuRenderBackend<TextureTarget>>()
+        .ok_or("Renderer must be the offscreen one it was built with")?
+        .capture_frame(false)
+        .ok_or("Texture target must be readable")?;
+    assert_eq!(image.dimensions(), (WIDTH, HEIGHT));
+    assert_eq!(
+        image.get_pixel(WIDTH / 2, HEIGHT / 2).0,
+        [background.r, background.g, background.b, background.a]
+    );
+
+    Ok(())
+}
@@ -0,0 +1,133 @@
+use crate::util::environment::wgpu_renderer;
+use crate::util::pixels::pixel;
+use ruffle_core::swf::{FillStyle, Twips};
+use ruffle_core::Color;
+use ruffle_render::backend::null::NullBitmapSource;
+use ruffle_render::backend::{RenderBackend, ShapeHandle};
+use ruffle_render::bitmap::{Bitmap, BitmapFormat};
+use ruffle_render::commands::{CommandHandler, CommandList};
+use ruffle_render::matrix::Matrix;
+use ruffle_render::quality::StageQuality;
+use ruffle_render::shape_utils::{DistilledShape, DrawCommand, DrawPath, FillRule};
+use ruffle_render::transform::Transform;
+
+const SIZE: u32 = 64;
+const RADIUS: f64 = 16.0;
+const SEGMENTS: u32 = 64;
+
+/// A circle in the middle of the target, made of enough lines to be round to within a pixel.
+fn circle(style: &FillStyle) -> DistilledShape {
+    let center = f64::from(SIZE) / 2.0;
+    let point = |index: u32| {
+        let angle = f64::from(index) / f64::from(SEGMENTS) * std::f64::consts::TAU;
+        (
+            Twips::from_pixels(center + RADIUS * angle.cos()),
+            Twips::from_pixels(center + RADIUS * angle.sin()),
+        )
+    };
+    let (x, y) = point(0);
+    let mut commands = vec![DrawCommand::MoveTo { x, y }];
+    commands.extend((1..=SEGMENTS).map(|index| {
+        let (x, y) = point(index);
+        DrawCommand::LineTo { x, y }
+    }));
+    DistilledShape {
+        paths: vec![DrawPath::Fill {
+            style,
+            commands,
+            winding_rule: FillRule::EvenOdd,
+        }],
+        shape_bounds: Default::default(),
+        edge_bounds: Default::default(),
+        id: 0,
+        stroke_matrix: None,
+    }
+}
+
+/// Fills the whole target in `color`, masked to everywhere but the circle.
+fn draw_inverted(commands: &mut CommandList, circle: &ShapeHandle, color: Color) {
+    let fill = Matrix::create_box(SIZE as f32, SIZE as f32, 0.0, Twips::ZERO, Twips::ZERO);
+    commands.push_inverted_mask();
+    commands.render_shape(circle.clone(), Transform::default());
+    commands.activate_mask();
+    commands.draw_rect(color, fill);
+    commands.deactivate_mask();
+    commands.render_shape(circle.clone(), Transform::default());
+    commands.pop_mask();
+}
+
+/// Checks every pixel that isn't on the edge of the circle, or of the area to the left of `right`.
+fn check_pixels(bitmap: &Bitmap, color: &Color, right: u32) {
+    let center = f64::from(SIZE) / 2.0;
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            if right < SIZE && x.abs_diff(right) <= 1 {
+                continue;
+            }
+            let distance = (f64::from(x) + 0.5 - center).hypot(f64::from(y) + 0.5 - center);
+            // Leave out the pixels on the edge of the circle, which may be antialiased
+            if (distance - RADIUS).abs() < 1.5 {
+                continue;
+            }
+            let expected = if distance > RADIUS && x < right {
+                [color.r, color.g, color.b, color.a]
+            } else {
+                [0, 0, 0, 0]
+            };
+            assert_eq!(pixel(bitmap, x, y), expected, "Pixel at ({x}, {y})");
+        }
+    }
+}
+
+pub fn inverted_mask() -> Result<(), libtest_mimic::Failed> {
+    let mut renderer = wgpu_renderer((SIZE, SIZE))?;
+    let red = Color::from_rgb(0xFF0000, 255);
+    let mask = renderer.register_shape(circle(&FillStyle::Color(Color::WHITE)), &NullBitmapSource);
+    let blank = || {
+        Bitmap::new(
+            SIZE,
+            SIZE,
+            BitmapFormat::Rgba,
+            vec![0; (SIZE * SIZE * 4) as usize],
+        )
+    };
+
+    // Only what's outside of the circle is drawn
+    let mut commands = CommandList::new();
+    draw_inverted(&mut commands, &mask, red.clone());
+    let handle = renderer.register_bitmap(blank())?;
+    let bitmap = renderer
+        .render_offscreen(handle, SIZE, SIZE, commands, StageQuality::Low)
+        .ok_or("Offscreen rendering is not supported")?
+        .retrieve_offscreen_texture()?;
+    check_pixels(&bitmap, &red, SIZE);
+
+    // Within an ordinary mask over the left half, the inverted mask only reveals that half.
+    // That mask is drawn twice, so that it's drawn into the stencil rather than being clipped
+    // with a scissor rect like a lone rectangle is.
+    let half = Matrix::create_box(
+        SIZE as f32 / 2.0,
+        SIZE as f32,
+        0.0,
+        Twips::ZERO,
+        Twips::ZERO,
+    );
+    let mut commands = CommandList::new();
+    commands.push_mask();
+    commands.draw_rect(Color::WHITE, half);
+    commands.draw_rect(Color::WHITE, half);
+    commands.activate_mask();
+    draw_inverted(&mut commands, &mask, red.clone());
+    commands.deactivate_mask();
+    commands.draw_rect(Color::WHITE, half);
+    commands.draw_rect(Color::WHITE, half);
+    commands.pop_mask();
+    let handle = renderer.register_bitmap(blank())?;
+    let bitmap = renderer
+        .render_offscreen(handle, SIZE, SIZE, commands, StageQuality::Low)
+        .ok_or("Offscreen rendering is not supported")?
+        .retrieve_offscreen_texture()?;
+    check_pixels(&bitmap, &red, SIZE / 2);
+
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use crate::util::environment::wgpu_renderer;
+use ruffle_core::swf::Twips;
+use ruffle_core::Color;
+use ruffle_render::backend::RenderBackend;
+use ruffle_render::commands::{CommandHandler, CommandList};
+use ruffle_render::matrix::Matrix;
+use ruffle_render_wgpu::backend::WgpuRenderBackend;
+use ruffle_render_wgpu::target::TextureTarget;
+
+const SIZE: u32 = 64;
+
+/// Fills the whole target in red, within a mask made up of all of `mask`.
+fn masked_fill(mask: &[Matrix]) -> CommandList {
+    let mut commands = CommandList::new();
+    commands.push_mask();
+    for rect in mask {
+        commands.draw_rect(Color::WHITE, *rect);
+    }
+    commands.activate_mask();
+    commands.draw_rect(
+        Color::from_rgb(0xFF0000, 255),
+        Matrix::create_box(SIZE as f32, SIZE as f32, 0.0, Twips::ZERO, Twips::ZERO),
+    );
+    commands.deactivate_mask();
+    for rect in mask {
+        commands.draw_rect(Color::WHITE, *rect);
+    }
+    commands.pop_mask();
+    commands
+}
+
+/// Renders `commands`, and checks that every pixel is `expected`.
+fn check_frame(
+    renderer: &mut WgpuRenderBackend<TextureTarget>,
+    commands: CommandList,
+    expected: [u8; 4],
+) -> Result<(), libtest_mimic::Failed> {
+    renderer.submit_frame(Color::from_rgba(0), commands);
+    let image = renderer
+        .capture_frame(false)
+        .ok_or("Texture target must be readable")?;
+    for (x, y, pixel) in image.enumerate_pixels() {
+        assert_eq!(pixel.0, expected, "Pixel at ({x}, {y})");
+    }
+    Ok(())
+}
+
+/// Checks that the parts of a mask that are entirely off of the target aren't drawn into
+/// the stencil, and that masking still works the same without them.
+pub fn large_mask() -> Result<(), libtest_mimic::Failed> {
+    let mut renderer = wgpu_renderer((SIZE, SIZE))?;
+    let far = Twips::from_pixels(f64::from(SIZE * 50));
+    // Far larger than the target, and rotated so that it can't be a scissor rect
+    let huge = Matrix::create_box((SIZE * 100) as f32, (SIZE * 100) as f32, 0.01, -far, -far);
+    let size = SIZE as f32;
+    let off_target = [
+        Matrix::create_box(size, size, 0.0, -far, Twips::ZERO),
+        Matrix::create_box(size, size, 0.0, far, Twips::ZERO),
+        Matrix::create_box(size, size, 0.0, Twips::ZERO, -far),
+        Matrix::create_box(size, size, 0.0, Twips::ZERO, far),
+    ];
+
+    // Only the huge rectangle is drawn into the stencil and cleared again,
+    // which is 2 triangles each time, along with the 2 triangles of the fill
+    let mut mask = vec![huge];
+    mask.extend(off_target);
+    check_frame(&mut renderer, masked_fill(&mask), [255, 0, 0, 255])?;
+    let statistics = renderer.last_frame_statistics();
+    assert_eq!(statistics.stencil_draws, 2);
+    assert_eq!(statistics.triangles, 6);
+
+    // A mask that's entirely off of the target draws nothing into the stencil, and hides everything
+    check_frame(&mut renderer, masked_fill(&off_target), [0, 0, 0, 0])?;
+    let statistics = renderer.last_frame_statistics();
+    assert_eq!(statistics.stencil_draws, 0);
+    assert_eq!(statistics.triangles, 2);
+
+    Ok(())
+}
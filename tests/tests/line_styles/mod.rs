@@ -0,0 +1,117 @@
+use crate::util::environment::wgpu_renderer;
+use ruffle_core::swf::{Fixed8, LineCapStyle, LineJoinStyle, LineStyle, Twips};
+use ruffle_core::Color;
+use ruffle_render::backend::null::NullBitmapSource;
+use ruffle_render::backend::RenderBackend;
+use ruffle_render::commands::{CommandHandler, CommandList};
+use ruffle_render::shape_utils::{DistilledShape, DrawCommand, DrawPath};
+use ruffle_render::transform::Transform;
+
+const SIZE: u32 = 64;
+const WIDTH: f64 = 8.0;
+
+/// An L going up from (16, 48) to a right angle at (16, 16), then right to (48, 16).
+fn l_shape(style: &LineStyle) -> DistilledShape {
+    let point = |x: f64, y: f64| (Twips::from_pixels(x), Twips::from_pixels(y));
+    let (x, y) = point(16.0, 48.0);
+    let mut commands = vec![DrawCommand::MoveTo { x, y }];
+    for (x, y) in [point(16.0, 16.0), point(48.0, 16.0)] {
+        commands.push(DrawCommand::LineTo { x, y });
+    }
+    DistilledShape {
+        paths: vec![DrawPath::Stroke {
+            style,
+            is_closed: false,
+            commands,
+        }],
+        shape_bounds: Default::default(),
+        edge_bounds: Default::default(),
+        id: 0,
+        stroke_matrix: None,
+    }
+}
+
+/// Checks that strokes are drawn with the caps and joins of their line style,
+/// and that miters are clipped at their limit like in Flash.
+pub fn line_styles() -> Result<(), libtest_mimic::Failed> {
+    let mut renderer = wgpu_renderer((SIZE, SIZE))?;
+    // Without antialiasing, so that every pixel is either in the stroke or out of it
+    renderer.set_sample_count(Some(1));
+
+    let caps = [
+        ("none", LineCapStyle::None, false, false),
+        ("round", LineCapStyle::Round, true, false),
+        ("square", LineCapStyle::Square, true, true),
+    ];
+    let joins = [
+        ("round", LineJoinStyle::Round, true, false),
+        ("bevel", LineJoinStyle::Bevel, false, false),
+        // The corner of a right angle is √2 half widths out, which is within this limit
+        (
+            "miter 3",
+            LineJoinStyle::Miter(Fixed8::from_f32(3.0)),
+            true,
+            true,
+        ),
+        // Clipped a half width out from the middle of the corner
+        (
+            "miter 1",
+            LineJoinStyle::Miter(Fixed8::from_f32(1.0)),
+            true,
+            false,
+        ),
+        // Clipped closer than a bevel would reach, so it's a bevel
+        (
+            "miter 0.5",
+            LineJoinStyle::Miter(Fixed8::from_f32(0.5)),
+            false,
+            false,
+        ),
+    ];
+
+    let mut failures = Vec::new();
+    for (cap_name, cap, cap_filled, cap_corner_filled) in caps {
+        for (join_name, join, join_filled, join_corner_filled) in joins {
+            let style = LineStyle::new()
+                .with_width(Twips::from_pixels(WIDTH))
+                .with_color(Color::from_rgb(0xFF0000, 255))
+                .with_start_cap(cap)
+                .with_end_cap(cap)
+                .with_join_style(join);
+            let shape = renderer.register_shape(l_shape(&style), &NullBitmapSource);
+
+            let mut commands = CommandList::new();
+            commands.render_shape(shape, Transform::default());
+            renderer.submit_frame(Color::from_rgba(0), commands);
+            let image = renderer
+                .capture_frame(false)
+                .ok_or("Texture target must be readable")?;
+            let filled = |x: u32, y: u32| image.get_pixel(x, y).0[3] != 0;
+
+            let probes = [
+                ("line", (16, 32), true),
+                // Just past the ends, and off to the side where only square caps reach
+                ("start cap", (16, 50), cap_filled),
+                ("start cap corner", (19, 51), cap_corner_filled),
+                ("end cap", (50, 16), cap_filled),
+                ("end cap corner", (51, 19), cap_corner_filled),
+                // Inside of a round join but outside of a bevel, and at the tip of a miter
+                ("join", (13, 13), join_filled),
+                ("join corner", (12, 12), join_corner_filled),
+            ];
+            for (probe, (x, y), expected) in probes {
+                if filled(x, y) != expected {
+                    failures.push(format!(
+                        "{cap_name} caps, {join_name} join: {probe} at ({x}, {y}) should {}be drawn",
+                        if expected { "" } else { "not " }
+                    ));
+                }
+            }
+        }
+    }
+
+    if !failures.is_empty() {
+        return Err(failures.join("\n").into());
+    }
+    Ok(())
+}
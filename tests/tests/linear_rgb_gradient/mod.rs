@@ -0,0 +1,81 @@
+use crate::util::environment::wgpu_renderer;
+use crate::util::pixels::check_pixel;
+use crate::util::shapes::square;
+use ruffle_core::swf::{
+    self, FillStyle, Fixed16, Gradient, GradientInterpolation, GradientRecord, GradientSpread,
+    Twips,
+};
+use ruffle_core::Color;
+use ruffle_render::backend::null::NullBitmapSource;
+use ruffle_render::backend::RenderBackend;
+use ruffle_render::commands::{CommandHandler, CommandList};
+use ruffle_render::transform::Transform;
+
+const SIZE: u32 = 64;
+
+/// How far a gray may be from the one expected, out of 255.
+const TOLERANCE: u8 = 3;
+
+/// A black to white linear gradient from the left edge of the target to its right edge.
+fn black_to_white(interpolation: GradientInterpolation) -> FillStyle {
+    let scale = Fixed16::from_f32(SIZE as f32 * 20.0 / 32768.0);
+    let middle = Twips::from_pixels(f64::from(SIZE / 2));
+    FillStyle::LinearGradient(Gradient {
+        matrix: swf::Matrix::translate(middle, middle) * swf::Matrix::scale(scale, scale),
+        spread: GradientSpread::Pad,
+        interpolation,
+        records: vec![
+            GradientRecord {
+                ratio: 0,
+                color: Color::BLACK,
+            },
+            GradientRecord {
+                ratio: 255,
+                color: Color::WHITE,
+            },
+        ],
+    })
+}
+
+fn linear_to_srgb(n: f64) -> f64 {
+    if n <= 0.0031308 {
+        n * 12.92
+    } else {
+        1.055 * n.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Checks that a gradient interpolated in linear RGB is lighter across its middle than the
+/// same gradient interpolated in sRGB, by as much as the conversion between the two says.
+pub fn linear_rgb_gradient() -> Result<(), libtest_mimic::Failed> {
+    let mut renderer = wgpu_renderer((SIZE, SIZE))?;
+
+    let cases: [(_, fn(f64) -> f64); 2] = [
+        (GradientInterpolation::Rgb, |t| t),
+        (GradientInterpolation::LinearRgb, linear_to_srgb),
+    ];
+    for (interpolation, to_srgb) in cases {
+        let style = black_to_white(interpolation);
+        let shape = renderer.register_shape(square(&style, SIZE), &NullBitmapSource);
+        let mut commands = CommandList::new();
+        commands.render_shape(shape, Transform::default());
+        renderer.submit_frame(Color::from_rgba(0), commands);
+        let image = renderer
+            .capture_frame(false)
+            .ok_or("Texture target must be readable")?;
+
+        for x in 0..SIZE {
+            let t = (f64::from(x) + 0.5) / f64::from(SIZE);
+            let gray = (to_srgb(t) * 255.0).round() as u8;
+            check_pixel(
+                &image,
+                (x, SIZE / 2),
+                [gray, gray, gray, 255],
+                TOLERANCE,
+                &format!("{interpolation:?} gradient"),
+            )?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,45 @@
+use crate::util::environment::wgpu_renderer;
+use crate::util::shapes::square;
+use ruffle_core::swf::{FillStyle, Fixed8};
+use ruffle_core::Color;
+use ruffle_render::backend::null::NullBitmapSource;
+use ruffle_render::backend::RenderBackend;
+use ruffle_render::color_transform::ColorTransform;
+use ruffle_render::commands::{CommandHandler, CommandList};
+use ruffle_render::transform::Transform;
+
+const SIZE: u32 = 16;
+
+/// Checks that a color shape drawn fully mixed towards its luminance comes out gray.
+pub fn luminance_mix() -> Result<(), libtest_mimic::Failed> {
+    let mut renderer = wgpu_renderer((SIZE, SIZE))?;
+
+    let style = FillStyle::Color(Color::from_rgb(0xFF8000, 255));
+    let shape = renderer.register_shape(square(&style, SIZE), &NullBitmapSource);
+
+    let mut commands = CommandList::new();
+    commands.render_shape(
+        shape,
+        Transform {
+            color_transform: ColorTransform {
+                luminance_mix: Fixed8::ONE,
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+    renderer.submit_frame(Color::from_rgba(0), commands);
+    let image = renderer
+        .capture_frame(false)
+        .ok_or("Texture target must be readable")?;
+
+    let [r, g, b, a] = image.get_pixel(SIZE / 2, SIZE / 2).0;
+    if r != g || g != b {
+        return Err(format!("Pixel should be gray, but was {:?}", [r, g, b, a]).into());
+    }
+    // The luminance of the orange, rather than black or white
+    if r.abs_diff(151) > 1 || a != 255 {
+        return Err(format!("Pixel should be 151 gray, but was {:?}", [r, g, b, a]).into());
+    }
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use crate::util::environment::wgpu_renderer;
+use ruffle_core::swf::Twips;
+use ruffle_core::Color;
+use ruffle_render::backend::RenderBackend;
+use ruffle_render::commands::{CommandHandler, CommandList};
+use ruffle_render::matrix::Matrix;
+
+const SIZE: u32 = 64;
+
+/// The parts of a mask sprite moved 8 pixels right and down, in the order they're drawn:
+/// two children either side of it, and the mask of one of those children in the middle.
+const PARTS: [(u32, u32, u32, u32); 3] = [(8, 8, 16, 48), (40, 8, 16, 48), (24, 24, 16, 16)];
+
+/// Draws every part of the mask, each with the sprite's transform applied.
+fn draw_mask(commands: &mut CommandList) {
+    let sprite = Matrix::translate(Twips::from_pixels(8.0), Twips::from_pixels(8.0));
+    for (x, y, width, height) in PARTS {
+        let part = Matrix::create_box(
+            width as f32,
+            height as f32,
+            0.0,
+            Twips::from_pixels(f64::from(x - 8)),
+            Twips::from_pixels(f64::from(y - 8)),
+        );
+        commands.draw_rect(Color::WHITE, sprite * part);
+    }
+}
+
+/// Checks that every part of a mask sprite drawn into the stencil clips what it masks, the way
+/// that a mask with children is drawn by the display list, rather than only its first part.
+pub fn mask_children() -> Result<(), libtest_mimic::Failed> {
+    let mut renderer = wgpu_renderer((SIZE, SIZE))?;
+
+    let mut commands = CommandList::new();
+    commands.push_mask();
+    draw_mask(&mut commands);
+    commands.activate_mask();
+    commands.draw_rect(
+        Color::from_rgb(0xFF0000, 255),
+        Matrix::create_box(SIZE as f32, SIZE as f32, 0.0, Twips::ZERO, Twips::ZERO),
+    );
+    commands.deactivate_mask();
+    draw_mask(&mut commands);
+    commands.pop_mask();
+    // Once popped, the mask no longer clips anything
+    commands.draw_rect(
+        Color::from_rgb(0x0000FF, 255),
+        Matrix::create_box(SIZE as f32, 4.0, 0.0, Twips::ZERO, Twips::ZERO),
+    );
+
+    renderer.submit_frame(Color::WHITE, commands);
+    let image = renderer
+        .capture_frame(false)
+        .ok_or("Texture target must be readable")?;
+    for (x, y, pixel) in image.enumerate_pixels() {
+        let inside = PARTS.iter().any(|&(left, top, width, height)| {
+            (left..left + width).contains(&x) && (top..top + height).contains(&y)
+        });
+        let expected = if y < 4 {
+            [0, 0, 255, 255]
+        } else if inside {
+            [255, 0, 0, 255]
+        } else {
+            [255, 255, 255, 255]
+        };
+        assert_eq!(pixel.0, expected, "Pixel at ({x}, {y})");
+    }
+
+    Ok(())
+}
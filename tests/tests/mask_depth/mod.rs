@@ -0,0 +1,102 @@
+use crate::util::environment::wgpu_renderer;
+use crate::util::pixels::pixel;
+use ruffle_core::swf::Twips;
+use ruffle_core::Color;
+use ruffle_render::backend::RenderBackend;
+use ruffle_render::bitmap::{Bitmap, BitmapFormat};
+use ruffle_render::commands::{CommandHandler, CommandList};
+use ruffle_render::matrix::Matrix;
+use ruffle_render::quality::StageQuality;
+use ruffle_render_wgpu::MAX_MASK_DEPTH;
+
+const SIZE: u32 = 64;
+
+/// More masks than the 8-bit stencil buffer can nest.
+const NUM_MASKS: u32 = 300;
+
+/// Draws a mask into the stencil.
+///
+/// It's drawn twice, as a lone rectangle would be clipped with a scissor rect instead.
+/// The second draw doesn't change anything, as the stencil has already moved on from
+/// the level that it's tested against.
+fn draw_mask(commands: &mut CommandList, mask: Matrix) {
+    commands.draw_rect(Color::WHITE, mask);
+    commands.draw_rect(Color::WHITE, mask);
+}
+
+/// Nests masks within each other, with `content` drawn inside all of them.
+fn nest_masks(
+    commands: &mut CommandList,
+    masks: &[Matrix],
+    content: impl FnOnce(&mut CommandList),
+) {
+    let Some((mask, inner)) = masks.split_first() else {
+        content(commands);
+        return;
+    };
+    commands.push_mask();
+    draw_mask(commands, *mask);
+    commands.activate_mask();
+    nest_masks(commands, inner, content);
+    commands.deactivate_mask();
+    draw_mask(commands, *mask);
+    commands.pop_mask();
+}
+
+/// Checks that masks nested too deeply for the stencil are ignored, and that everything
+/// is masked correctly again once they've been popped.
+pub fn mask_depth() -> Result<(), libtest_mimic::Failed> {
+    let mut renderer = wgpu_renderer((SIZE, SIZE))?;
+    let half = SIZE as f32 / 2.0;
+    let left = Matrix::create_box(half, SIZE as f32, 0.0, Twips::ZERO, Twips::ZERO);
+    let right = Matrix::create_box(
+        half,
+        SIZE as f32,
+        0.0,
+        Twips::from_pixels(half.into()),
+        Twips::ZERO,
+    );
+    let top = Matrix::create_box(SIZE as f32, half, 0.0, Twips::ZERO, Twips::ZERO);
+    let fill = Matrix::create_box(SIZE as f32, SIZE as f32, 0.0, Twips::ZERO, Twips::ZERO);
+    let red = Color::from_rgb(0xFF0000, 255);
+    let green = Color::from_rgb(0x00FF00, 255);
+
+    // All of the masks that fit in the stencil show the left half,
+    // and the ones beyond those would only show the top half
+    let masks = (0..NUM_MASKS)
+        .map(|depth| if depth < MAX_MASK_DEPTH { left } else { top })
+        .collect::<Vec<_>>();
+    let mut commands = CommandList::new();
+    nest_masks(&mut commands, &masks, |commands| {
+        commands.draw_rect(red.clone(), fill)
+    });
+    // If the stencil was cleared properly, a new mask works just as it would on its own
+    nest_masks(&mut commands, &[right], |commands| {
+        commands.draw_rect(green.clone(), fill)
+    });
+
+    let handle = renderer.register_bitmap(Bitmap::new(
+        SIZE,
+        SIZE,
+        BitmapFormat::Rgba,
+        vec![0; (SIZE * SIZE * 4) as usize],
+    ))?;
+    let bitmap = renderer
+        .render_offscreen(handle, SIZE, SIZE, commands, StageQuality::Low)
+        .ok_or("Offscreen rendering is not supported")?
+        .retrieve_offscreen_texture()?;
+
+    for y in 0..SIZE {
+        // Leave out the pixels on the edge of the masks, which may be antialiased
+        for x in (0..SIZE / 2 - 1).chain(SIZE / 2 + 1..SIZE) {
+            let expected = if x < SIZE / 2 {
+                [red.r, red.g, red.b, red.a]
+            } else {
+                [green.r, green.g, green.b, green.a]
+            };
+            assert_eq!(pixel(&bitmap, x, y), expected, "Pixel at ({x}, {y})");
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,72 @@
+use crate::util::options::TestOptions;
+use crate::util::test::Test;
+use anyhow::anyhow;
+use ruffle_render_wgpu::backend::WgpuRenderBackend;
+use ruffle_render_wgpu::target::TextureTarget;
+use std::collections::HashSet;
+use std::path::Path;
+
+/// Overlapping shapes with curved edges, in three solid colors.
+const SWF_DIR: &str = "tests/swfs/visual/simple_shapes/overlaps";
+
+/// Runs the SWF with the given `sample_count` player option, and captures what it renders.
+fn render(sample_count: u32) -> Result<image::RgbaImage, libtest_mimic::Failed> {
+    let options: TestOptions = toml::from_str(&format!(
+        "[player_options]\nwith_renderer = {{ sample_count = {sample_count} }}"
+    ))?;
+    let name = format!("msaa_edges_{sample_count}");
+    let test = Test::from_options(options, Path::new(SWF_DIR), name)?;
+
+    let mut image = None;
+    test.run(
+        |_| Ok(()),
+        |player| {
+            let mut player = player.lock().unwrap();
+            player.render();
+            let renderer = player
+                .renderer_mut()
+                .downcast_mut::<WgpuRenderBackend<TextureTarget>>()
+                .ok_or_else(|| anyhow!("Player must render with wgpu"))?;
+            image = renderer.capture_frame(false);
+            Ok(())
+        },
+    )?;
+    Ok(image.ok_or("Texture target must be readable")?)
+}
+
+/// Checks that the edges of shapes are blended with the shapes behind them with 4x MSAA,
+/// where they're aliased without it, and that nothing away from the edges changes.
+pub fn msaa_edges() -> Result<(), libtest_mimic::Failed> {
+    let aliased = render(1)?;
+    let smoothed = render(4)?;
+
+    let colors: HashSet<_> = aliased.pixels().map(|pixel| pixel.0).collect();
+    if colors.len() != 3 {
+        return Err(format!(
+            "Without MSAA, only the 3 colors of the shapes are drawn, not {}",
+            colors.len()
+        )
+        .into());
+    }
+
+    let blended = smoothed
+        .pixels()
+        .filter(|pixel| !colors.contains(&pixel.0))
+        .count();
+    if blended == 0 {
+        return Err("With 4x MSAA, the edges must be blended".into());
+    }
+
+    let changed = aliased
+        .pixels()
+        .zip(smoothed.pixels())
+        .filter(|(aliased, smoothed)| aliased != smoothed)
+        .count();
+    let total = (aliased.width() * aliased.height()) as usize;
+    if changed * 20 > total {
+        return Err(
+            format!("{changed} of {total} pixels changed, which is more than the edges").into(),
+        );
+    }
+    Ok(())
+}
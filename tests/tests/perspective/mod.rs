@@ -0,0 +1,54 @@
+use crate::util::environment::wgpu_renderer;
+use ruffle_core::Color;
+use ruffle_render::backend::RenderBackend;
+use ruffle_render::bitmap::{Bitmap, BitmapFormat};
+use ruffle_render::commands::{CommandHandler, CommandList};
+use ruffle_render::matrix3d::Matrix3D;
+use ruffle_render::transform::Transform;
+
+const SIZE: u32 = 100;
+const CARD_SIZE: u32 = 40;
+
+pub fn perspective() -> Result<(), libtest_mimic::Failed> {
+    let mut renderer = wgpu_renderer((SIZE, SIZE))?;
+    let card = renderer.register_bitmap(Bitmap::new(
+        CARD_SIZE,
+        CARD_SIZE,
+        BitmapFormat::Rgba,
+        vec![255; (CARD_SIZE * CARD_SIZE * 4) as usize],
+    ))?;
+
+    // A card in the middle of the target, turned so that its right edge comes towards the viewer
+    let center = f64::from(SIZE) / 2.0;
+    let half_card = f64::from(CARD_SIZE) / 2.0;
+    let matrix3d = Matrix3D::perspective(100.0, (center, center))
+        * Matrix3D::translate(center, center, 0.0)
+        * Matrix3D::rotate_y(60f64.to_radians())
+        * Matrix3D::translate(-half_card, -half_card, 0.0);
+    let mut commands = CommandList::new();
+    commands.render_bitmap(
+        card,
+        Transform {
+            matrix: matrix3d.to_affine(),
+            matrix3d: Some(matrix3d),
+            ..Default::default()
+        },
+        false,
+    );
+    renderer.submit_frame(Color::from_rgba(0), commands);
+    let image = renderer
+        .capture_frame(false)
+        .ok_or("Texture target must be readable")?;
+
+    let is_covered = |x, y| image.get_pixel(x, y).0[3] > 128;
+    // The near edge is taller than the far edge, and taller than the card is without perspective
+    assert!(is_covered(60, 29), "Near edge must be foreshortened");
+    assert!(!is_covered(44, 29), "Far edge must be foreshortened");
+    // Both edges are pulled towards the center, the far one more so
+    assert!(!is_covered(40, 50), "Far edge must be narrowed");
+    assert!(is_covered(42, 50), "Far edge must be drawn");
+    assert!(is_covered(61, 50), "Near edge must be drawn");
+    assert!(!is_covered(64, 50), "Near edge must be narrowed");
+
+    Ok(())
+}
@@ -1,17 +1,108 @@
-//! Tests running SWFs in a headless Ruffle instance.
+//! Tests running SWFs in a headless Ruffle instance, along with tests of the player and the
+//! renderers that don't need a SWF.
 //!
-//! Trace output can be compared with correct output from the official Flash Player.
+//! Each directory under `tests/swfs` with a `test.toml` is a SWF test. Its trace output, and
+//! optionally the image it renders, can be compared with correct output from the official
+//! Flash Player.
+//!
+//! The other tests are listed by hand in `main`. Those that render with wgpu directly go in
+//! the `wgpu_tests` table, and are ignored when there's no wgpu device to run them on.
 
+use crate::alpha_mask::alpha_mask_feathered;
+use crate::anisotropic_filtering::anisotropic_filtering;
+use crate::bevel::bevel;
+use crate::bitmap_atlas::{bitmap_atlas, bitmap_atlas_draw, bitmap_atlas_eviction};
+use crate::bitmap_precision::bitmap_precision;
+use crate::bitmap_region::bitmap_region;
+use crate::bitmap_tiling::bitmap_tiling;
+use crate::blend_modes::{blend_screen, blend_subtract};
+use crate::blur_quality::blur_quality;
+use crate::capture_frame::capture_frame_solid_color;
+use crate::color_batching::color_batching;
+use crate::color_transform_alpha::color_transform_alpha;
+use crate::convolution::convolution;
+use crate::cpu_filters::cpu_filter_fallback_avm1;
+use crate::displacement_map::{displacement_map, displacement_map_position};
+use crate::drop_shadow::drop_shadow;
 use crate::external_interface::tests::{external_interface_avm1, external_interface_avm2};
+use crate::external_texture::external_texture;
+use crate::filter_caching::filter_caching;
+use crate::filter_reference::filter_reference;
+use crate::focal_gradient::focal_gradient;
+use crate::glow::glow;
+use crate::headless::headless_render;
+use crate::inverted_mask::inverted_mask;
+use crate::large_mask::large_mask;
+use crate::line_styles::line_styles;
+use crate::linear_rgb_gradient::linear_rgb_gradient;
+use crate::luminance_mix::luminance_mix;
+use crate::mask_children::mask_children;
+use crate::mask_depth::mask_depth;
+use crate::msaa_edges::msaa_edges;
+use crate::perspective::perspective;
+use crate::render_offscreen::render_offscreen_masked;
+use crate::render_statistics::render_statistics;
+use crate::scissor_mask::scissor_mask;
+use crate::shape_tolerance::shape_tolerance;
+use crate::shared_descriptors::shared_descriptors;
 use crate::shared_object::{shared_object_avm1, shared_object_avm2};
+use crate::stage_quality::{stage_quality_samples, stage_quality_smoothing};
+use crate::state_changes::state_changes;
+use crate::straight_alpha::straight_alpha;
+use crate::stroke_scaling::stroke_scaling;
+use crate::surface_lost::surface_lost;
+use crate::texture_pool::texture_pool_reuse;
 use anyhow::Context;
 use anyhow::Result;
-use libtest_mimic::{Arguments, Trial};
+use libtest_mimic::{Arguments, Failed, Trial};
 use std::path::Path;
+use util::environment::WGPU;
 use util::test::Test;
 
+mod alpha_mask;
+mod anisotropic_filtering;
+mod bevel;
+mod bitmap_atlas;
+mod bitmap_precision;
+mod bitmap_region;
+mod bitmap_tiling;
+mod blend_modes;
+mod blur_quality;
+mod capture_frame;
+mod color_batching;
+mod color_transform_alpha;
+mod convolution;
+mod cpu_filters;
+mod displacement_map;
+mod drop_shadow;
 mod external_interface;
+mod external_texture;
+mod filter_caching;
+mod filter_reference;
+mod focal_gradient;
+mod glow;
+mod headless;
+mod inverted_mask;
+mod large_mask;
+mod line_styles;
+mod linear_rgb_gradient;
+mod luminance_mix;
+mod mask_children;
+mod mask_depth;
+mod msaa_edges;
+mod perspective;
+mod render_offscreen;
+mod render_statistics;
+mod scissor_mask;
+mod shape_tolerance;
+mod shared_descriptors;
 mod shared_object;
+mod stage_quality;
+mod state_changes;
+mod straight_alpha;
+mod stroke_scaling;
+mod surface_lost;
+mod texture_pool;
 mod util;
 
 fn set_logger() {
@@ -78,8 +169,10 @@ fn main() {
         .collect();
 
     // Manual tests here, since #[test] doesn't work once we use our own test harness
-    tests.push(Trial::test("shared_object_avm1", shared_object_avm1));
-    tests.push(Trial::test("shared_object_avm2", shared_object_avm2));
+    tests.push(Trial::test(
+        "cpu_filter_fallback_avm1",
+        cpu_filter_fallback_avm1,
+    ));
     tests.push(Trial::test(
         "external_interface_avm1",
         external_interface_avm1,
@@ -88,6 +181,63 @@ fn main() {
         "external_interface_avm2",
         external_interface_avm2,
     ));
+    tests.push(Trial::test("shared_object_avm1", shared_object_avm1));
+    tests.push(Trial::test("shared_object_avm2", shared_object_avm2));
+
+    // These render with wgpu directly, so they can't run without a device to do so. Like the
+    // SWF tests, only look for one when they're actually going to be run.
+    let wgpu_tests: &[(&str, fn() -> Result<(), Failed>)] = &[
+        ("alpha_mask_feathered", alpha_mask_feathered),
+        ("anisotropic_filtering", anisotropic_filtering),
+        ("bevel", bevel),
+        ("bitmap_atlas", bitmap_atlas),
+        ("bitmap_atlas_draw", bitmap_atlas_draw),
+        ("bitmap_atlas_eviction", bitmap_atlas_eviction),
+        ("bitmap_precision", bitmap_precision),
+        ("bitmap_region", bitmap_region),
+        ("bitmap_tiling", bitmap_tiling),
+        ("blend_screen", blend_screen),
+        ("blend_subtract", blend_subtract),
+        ("blur_quality", blur_quality),
+        ("capture_frame_solid_color", capture_frame_solid_color),
+        ("color_batching", color_batching),
+        ("color_transform_alpha", color_transform_alpha),
+        ("convolution", convolution),
+        ("displacement_map", displacement_map),
+        ("displacement_map_position", displacement_map_position),
+        ("drop_shadow", drop_shadow),
+        ("external_texture", external_texture),
+        ("filter_caching", filter_caching),
+        ("filter_reference", filter_reference),
+        ("focal_gradient", focal_gradient),
+        ("glow", glow),
+        ("headless_render", headless_render),
+        ("inverted_mask", inverted_mask),
+        ("large_mask", large_mask),
+        ("line_styles", line_styles),
+        ("linear_rgb_gradient", linear_rgb_gradient),
+        ("luminance_mix", luminance_mix),
+        ("mask_children", mask_children),
+        ("mask_depth", mask_depth),
+        ("msaa_edges", msaa_edges),
+        ("perspective", perspective),
+        ("render_offscreen_masked", render_offscreen_masked),
+        ("render_statistics", render_statistics),
+        ("scissor_mask", scissor_mask),
+        ("shape_tolerance", shape_tolerance),
+        ("shared_descriptors", shared_descriptors),
+        ("stage_quality_samples", stage_quality_samples),
+        ("stage_quality_smoothing", stage_quality_smoothing),
+        ("state_changes", state_changes),
+        ("straight_alpha", straight_alpha),
+        ("stroke_scaling", stroke_scaling),
+        ("surface_lost", surface_lost),
+        ("texture_pool_reuse", texture_pool_reuse),
+    ];
+    for &(name, test) in wgpu_tests {
+        let ignore = !args.list && is_candidate(&args, name) && WGPU.is_none();
+        tests.push(Trial::test(name, test).with_ignored_flag(ignore));
+    }
 
     tests.sort_unstable_by(|a, b| a.name().cmp(b.name()));
 
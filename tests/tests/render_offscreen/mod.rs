@@ -0,0 +1,60 @@
+use crate::util::environment::wgpu_renderer;
+use crate::util::pixels::pixel;
+use ruffle_core::swf::Twips;
+use ruffle_core::Color;
+use ruffle_render::backend::RenderBackend;
+use ruffle_render::bitmap::{Bitmap, BitmapFormat};
+use ruffle_render::commands::{CommandHandler, CommandList};
+use ruffle_render::matrix::Matrix;
+use ruffle_render::quality::StageQuality;
+
+const SIZE: u32 = 64;
+
+pub fn render_offscreen_masked() -> Result<(), libtest_mimic::Failed> {
+    let mut renderer = wgpu_renderer((SIZE, SIZE))?;
+    let handle = renderer.register_bitmap(Bitmap::new(
+        SIZE,
+        SIZE,
+        BitmapFormat::Rgba,
+        vec![0; (SIZE * SIZE * 4) as usize],
+    ))?;
+
+    // Fill the whole bitmap in red, masked to only its left half
+    let mask = Matrix::create_box(
+        SIZE as f32 / 2.0,
+        SIZE as f32,
+        0.0,
+        Twips::ZERO,
+        Twips::ZERO,
+    );
+    let fill = Matrix::create_box(SIZE as f32, SIZE as f32, 0.0, Twips::ZERO, Twips::ZERO);
+    let red = Color::from_rgb(0xFF0000, 255);
+    let mut commands = CommandList::new();
+    commands.push_mask();
+    commands.draw_rect(Color::WHITE, mask);
+    commands.activate_mask();
+    commands.draw_rect(red.clone(), fill);
+    commands.deactivate_mask();
+    commands.draw_rect(Color::WHITE, mask);
+    commands.pop_mask();
+
+    let bitmap = renderer
+        .render_offscreen(handle, SIZE, SIZE, commands, StageQuality::Low)
+        .ok_or("Offscreen rendering is not supported")?
+        .retrieve_offscreen_texture()?;
+    assert_eq!((bitmap.width(), bitmap.height()), (SIZE, SIZE));
+
+    for y in 0..SIZE {
+        // Leave out the pixels on the edge of the mask, which may be antialiased
+        for x in (0..SIZE / 2 - 1).chain(SIZE / 2 + 1..SIZE) {
+            let expected = if x < SIZE / 2 {
+                [red.r, red.g, red.b, red.a]
+            } else {
+                [0, 0, 0, 0]
+            };
+            assert_eq!(pixel(&bitmap, x, y), expected, "Pixel at ({x}, {y})");
+        }
+    }
+
+    Ok(())
+}
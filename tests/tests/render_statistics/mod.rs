@@ -0,0 +1,44 @@
+use crate::util::environment::wgpu_renderer;
+use ruffle_core::swf::Twips;
+use ruffle_core::Color;
+use ruffle_render::backend::RenderBackend;
+use ruffle_render::commands::{CommandHandler, CommandList};
+use ruffle_render::matrix::Matrix;
+use ruffle_render_wgpu::RenderStatistics;
+
+const SIZE: u32 = 16;
+
+fn one_rect() -> CommandList {
+    let mut commands = CommandList::new();
+    commands.draw_rect(
+        Color::from_rgb(0xFF0000, 255),
+        Matrix::create_box(SIZE as f32, SIZE as f32, 0.0, Twips::ZERO, Twips::ZERO),
+    );
+    commands
+}
+
+pub fn render_statistics() -> Result<(), libtest_mimic::Failed> {
+    let mut renderer = wgpu_renderer((SIZE, SIZE))?;
+    let expected = RenderStatistics {
+        draw_calls: 1,
+        triangles: 2,
+        pipeline_switches: 1,
+        bind_group_switches: 0,
+        stencil_draws: 0,
+    };
+
+    renderer.submit_frame(Color::from_rgba(0), one_rect());
+    assert_eq!(renderer.last_frame_statistics(), expected);
+
+    // The counters start again from nothing every frame
+    renderer.submit_frame(Color::from_rgba(0), one_rect());
+    assert_eq!(renderer.last_frame_statistics(), expected);
+
+    renderer.submit_frame(Color::from_rgba(0), CommandList::new());
+    assert_eq!(
+        renderer.last_frame_statistics(),
+        RenderStatistics::default()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,94 @@
+use crate::util::environment::wgpu_renderer;
+use ruffle_core::swf::Twips;
+use ruffle_core::Color;
+use ruffle_render::backend::RenderBackend;
+use ruffle_render::commands::{CommandHandler, CommandList};
+use ruffle_render::matrix::Matrix;
+use ruffle_render_wgpu::backend::WgpuRenderBackend;
+use ruffle_render_wgpu::target::TextureTarget;
+
+const SIZE: u32 = 64;
+
+/// Fills the whole target in red, within each of `masks` nested in each other.
+fn masked_fill(masks: &[Matrix]) -> CommandList {
+    let mut commands = CommandList::new();
+    for mask in masks {
+        commands.push_mask();
+        commands.draw_rect(Color::WHITE, *mask);
+        commands.activate_mask();
+    }
+    commands.draw_rect(
+        Color::from_rgb(0xFF0000, 255),
+        Matrix::create_box(SIZE as f32, SIZE as f32, 0.0, Twips::ZERO, Twips::ZERO),
+    );
+    for mask in masks.iter().rev() {
+        commands.deactivate_mask();
+        commands.draw_rect(Color::WHITE, *mask);
+        commands.pop_mask();
+    }
+    commands
+}
+
+/// Renders `commands`, and checks that the pixels for which `inside` is true are red
+/// and the rest are empty, other than those for which `skip` is true.
+fn check_frame(
+    renderer: &mut WgpuRenderBackend<TextureTarget>,
+    commands: CommandList,
+    inside: impl Fn(u32, u32) -> bool,
+    skip: impl Fn(u32, u32) -> bool,
+) -> Result<(), libtest_mimic::Failed> {
+    renderer.submit_frame(Color::from_rgba(0), commands);
+    let image = renderer
+        .capture_frame(false)
+        .ok_or("Texture target must be readable")?;
+    for (x, y, pixel) in image.enumerate_pixels() {
+        if skip(x, y) {
+            continue;
+        }
+        let expected = if inside(x, y) {
+            [255, 0, 0, 255]
+        } else {
+            [0, 0, 0, 0]
+        };
+        assert_eq!(pixel.0, expected, "Pixel at ({x}, {y})");
+    }
+    Ok(())
+}
+
+/// Checks that rectangular masks clip with a scissor rect instead of drawing into the stencil,
+/// but rotated ones still use the stencil.
+pub fn scissor_mask() -> Result<(), libtest_mimic::Failed> {
+    let mut renderer = wgpu_renderer((SIZE, SIZE))?;
+    let half = SIZE / 2;
+    let left = Matrix::create_box(half as f32, SIZE as f32, 0.0, Twips::ZERO, Twips::ZERO);
+    let top = Matrix::create_box(SIZE as f32, half as f32, 0.0, Twips::ZERO, Twips::ZERO);
+    let tilted = Matrix::create_box(half as f32, SIZE as f32, 0.01, Twips::ZERO, Twips::ZERO);
+
+    check_frame(
+        &mut renderer,
+        masked_fill(&[left]),
+        |x, _| x < half,
+        |_, _| false,
+    )?;
+    assert_eq!(renderer.last_frame_statistics().stencil_draws, 0);
+
+    // Nested rectangles clip to where they overlap
+    check_frame(
+        &mut renderer,
+        masked_fill(&[left, top]),
+        |x, y| x < half && y < half,
+        |_, _| false,
+    )?;
+    assert_eq!(renderer.last_frame_statistics().stencil_draws, 0);
+
+    // Once it's rotated, even by a little, the rectangle is drawn into the stencil and cleared again
+    check_frame(
+        &mut renderer,
+        masked_fill(&[tilted]),
+        |x, _| x < half,
+        |x, y| x == 0 || y == 0 || x.abs_diff(half) <= 1,
+    )?;
+    assert_eq!(renderer.last_frame_statistics().stencil_draws, 2);
+
+    Ok(())
+}
@@ -0,0 +1,111 @@
+use crate::util::environment::wgpu_renderer;
+use ruffle_core::swf::{FillStyle, Twips};
+use ruffle_core::Color;
+use ruffle_render::backend::null::NullBitmapSource;
+use ruffle_render::backend::{RenderBackend, ShapeHandle};
+use ruffle_render::commands::{CommandHandler, CommandList};
+use ruffle_render::matrix::Matrix;
+use ruffle_render::shape_utils::{DistilledShape, DrawCommand, DrawPath, FillRule};
+use ruffle_render::transform::Transform;
+use ruffle_render_wgpu::backend::WgpuRenderBackend;
+use ruffle_render_wgpu::target::TextureTarget;
+
+const SIZE: u32 = 64;
+const RADIUS: f64 = 3.0;
+const SCALE: f32 = 10.0;
+
+/// A circle around the origin, made of 8 curves like the circles that Flash draws.
+fn circle(style: &FillStyle) -> DistilledShape {
+    let point = |angle: f64, radius: f64| {
+        (
+            Twips::from_pixels(radius * angle.cos()),
+            Twips::from_pixels(radius * angle.sin()),
+        )
+    };
+    let step = std::f64::consts::FRAC_PI_4;
+    let (x, y) = point(0.0, RADIUS);
+    let mut commands = vec![DrawCommand::MoveTo { x, y }];
+    commands.extend((0..8).map(|index| {
+        let angle = f64::from(index) * step;
+        let (x1, y1) = point(angle + step / 2.0, RADIUS / (step / 2.0).cos());
+        let (x2, y2) = point(angle + step, RADIUS);
+        DrawCommand::CurveTo { x1, y1, x2, y2 }
+    }));
+    DistilledShape {
+        paths: vec![DrawPath::Fill {
+            style,
+            commands,
+            winding_rule: FillRule::EvenOdd,
+        }],
+        shape_bounds: Default::default(),
+        edge_bounds: Default::default(),
+        id: 0,
+        stroke_matrix: None,
+    }
+}
+
+/// Draws `circle` scaled up in the middle of the target, and returns the number of triangles
+/// that took along with the number of pixels that differ from a perfect circle.
+fn draw_scaled(
+    renderer: &mut WgpuRenderBackend<TextureTarget>,
+    circle: ShapeHandle,
+) -> Result<(u32, usize), libtest_mimic::Failed> {
+    let center = f64::from(SIZE) / 2.0;
+    let mut commands = CommandList::new();
+    commands.render_shape(
+        circle,
+        Transform {
+            matrix: Matrix::translate(Twips::from_pixels(center), Twips::from_pixels(center))
+                * Matrix::scale(SCALE, SCALE),
+            ..Default::default()
+        },
+    );
+    renderer.submit_frame(Color::from_rgba(0), commands);
+    let triangles = renderer.last_frame_statistics().triangles;
+
+    let image = renderer
+        .capture_frame(false)
+        .ok_or("Texture target must be readable")?;
+    let radius = RADIUS * f64::from(SCALE);
+    let wrong_pixels = image
+        .enumerate_pixels()
+        .filter(|(x, y, pixel)| {
+            let distance = (f64::from(*x) + 0.5 - center).hypot(f64::from(*y) + 0.5 - center);
+            let expected = if distance < radius {
+                [255, 0, 0, 255]
+            } else {
+                [0, 0, 0, 0]
+            };
+            pixel.0 != expected
+        })
+        .count();
+    Ok((triangles, wrong_pixels))
+}
+
+/// Checks that a lower tolerance tessellates a scaled up circle into more triangles,
+/// which stray less from its edge.
+pub fn shape_tolerance() -> Result<(), libtest_mimic::Failed> {
+    let mut renderer = wgpu_renderer((SIZE, SIZE))?;
+    // Without antialiasing, so that every pixel is either in the circle or out of it
+    renderer.set_sample_count(Some(1));
+    let red = FillStyle::Color(Color::from_rgb(0xFF0000, 255));
+
+    // At the default tolerance, the lines could be a pixel away from the edge at this scale
+    let coarse = renderer.register_shape(circle(&red), &NullBitmapSource);
+    renderer.set_shape_tolerance(0.01);
+    let fine = renderer.register_shape(circle(&red), &NullBitmapSource);
+
+    let (coarse_triangles, coarse_wrong_pixels) = draw_scaled(&mut renderer, coarse)?;
+    let (fine_triangles, fine_wrong_pixels) = draw_scaled(&mut renderer, fine)?;
+    assert!(
+        fine_triangles > coarse_triangles,
+        "{fine_triangles} triangles at the lower tolerance, {coarse_triangles} at the default"
+    );
+    assert!(
+        fine_wrong_pixels < coarse_wrong_pixels,
+        "{fine_wrong_pixels} pixels off of the circle at the lower tolerance, \
+         {coarse_wrong_pixels} at the default"
+    );
+
+    Ok(())
+}
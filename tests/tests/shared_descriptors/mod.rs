@@ -0,0 +1,55 @@
+use crate::util::environment::{isolated_wgpu_renderer, wgpu_renderer_with};
+use ruffle_core::swf::Twips;
+use ruffle_core::Color;
+use ruffle_render::backend::RenderBackend;
+use ruffle_render::commands::{CommandHandler, CommandList};
+use ruffle_render::matrix::Matrix;
+use ruffle_render::quality::StageQuality;
+
+fn fill(color: Color, width: u32, height: u32) -> CommandList {
+    let mut commands = CommandList::new();
+    commands.draw_rect(
+        color,
+        Matrix::create_box(width as f32, height as f32, 0.0, Twips::ZERO, Twips::ZERO),
+    );
+    commands
+}
+
+pub fn shared_descriptors() -> Result<(), libtest_mimic::Failed> {
+    // Other tests render with the shared descriptors at the same time, so count pipelines
+    // on descriptors of our own
+    let mut first = isolated_wgpu_renderer((40, 30))?;
+    let descriptors = first.descriptors().clone();
+    let pipeline_sets = descriptors.pipeline_set_count();
+    let mut second = wgpu_renderer_with(descriptors.clone(), (20, 60))?;
+    assert_eq!(
+        descriptors.pipeline_set_count(),
+        pipeline_sets,
+        "Backends with the same quality and format must share their pipelines"
+    );
+
+    // Changing the quality of one movie mustn't change how the other one is drawn
+    first.set_quality(StageQuality::Best);
+    second.set_quality(StageQuality::Low);
+
+    let red = Color::from_rgb(0xFF0000, 255);
+    let blue = Color::from_rgb(0x0000FF, 255);
+    first.submit_frame(Color::from_rgba(0), fill(red.clone(), 40, 30));
+    second.submit_frame(Color::from_rgba(0), fill(blue.clone(), 20, 60));
+
+    for (backend, color, size) in [(&first, red, (40, 30)), (&second, blue, (20, 60))] {
+        let image = backend
+            .capture_frame(false)
+            .ok_or("Texture target must be readable")?;
+        assert_eq!(image.dimensions(), size);
+        for (x, y, pixel) in image.enumerate_pixels() {
+            assert_eq!(
+                pixel.0,
+                [color.r, color.g, color.b, color.a],
+                "Pixel at ({x}, {y})"
+            );
+        }
+    }
+
+    Ok(())
+}
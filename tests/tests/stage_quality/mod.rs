@@ -0,0 +1,169 @@
+use crate::util::environment::wgpu_renderer;
+use crate::util::pixels::check_pixel;
+use crate::util::shapes::square;
+use ruffle_core::swf::{self, FillStyle, Fixed16};
+use ruffle_core::Color;
+use ruffle_render::backend::RenderBackend;
+use ruffle_render::bitmap::{Bitmap, BitmapFormat, BitmapHandle, BitmapSize, BitmapSource};
+use ruffle_render::commands::{CommandHandler, CommandList};
+use ruffle_render::matrix::Matrix;
+use ruffle_render::quality::StageQuality;
+use ruffle_render::transform::Transform;
+use ruffle_render_wgpu::backend::WgpuRenderBackend;
+use ruffle_render_wgpu::target::TextureTarget;
+
+const WIDTH: u32 = 16;
+const HEIGHT: u32 = 8;
+
+/// How many pixels each texel of the bitmap is stretched over.
+const SCALE: f32 = 8.0;
+
+/// How far a component may be from the one expected, out of 255.
+const TOLERANCE: u8 = 4;
+
+const BLACK: [u8; 4] = [0, 0, 0, 255];
+const WHITE: [u8; 4] = [255, 255, 255, 255];
+
+/// Where the black texel and the white one are blended together when the bitmap is smoothed.
+const SMOOTHED: [[u8; 4]; 2] = [[112, 112, 112, 255], [143, 143, 143, 255]];
+
+/// Hands out the bitmap that the fill of the test shape is drawn with.
+struct FillBitmap(BitmapHandle);
+
+impl BitmapSource for FillBitmap {
+    fn bitmap_size(&self, _id: u16) -> Option<BitmapSize> {
+        Some(BitmapSize {
+            width: 2,
+            height: 1,
+        })
+    }
+
+    fn bitmap_handle(&self, _id: u16, _renderer: &mut dyn RenderBackend) -> Option<BitmapHandle> {
+        Some(self.0.clone())
+    }
+}
+
+/// A bitmap that's black on its left and white on its right.
+fn register_bitmap(
+    renderer: &mut WgpuRenderBackend<TextureTarget>,
+) -> Result<BitmapHandle, libtest_mimic::Failed> {
+    Ok(renderer.register_bitmap(Bitmap::new(
+        2,
+        1,
+        BitmapFormat::Rgba,
+        [BLACK, WHITE].concat(),
+    ))?)
+}
+
+/// Draws the bitmap stretched over the whole target, asking for it to be smoothed.
+fn stretched(bitmap: &BitmapHandle) -> CommandList {
+    let mut commands = CommandList::new();
+    commands.render_bitmap(
+        bitmap.clone(),
+        Transform {
+            matrix: Matrix::scale(SCALE, SCALE),
+            ..Default::default()
+        },
+        true,
+    );
+    commands
+}
+
+/// Checks the pixels on either side of the middle of the target, where the two texels of the
+/// bitmap meet.
+fn check_middle(
+    image: &image::RgbaImage,
+    expected: [[u8; 4]; 2],
+    what: &str,
+) -> Result<(), libtest_mimic::Failed> {
+    check_pixel(
+        image,
+        (WIDTH / 2 - 1, HEIGHT / 2),
+        expected[0],
+        TOLERANCE,
+        what,
+    )?;
+    check_pixel(image, (WIDTH / 2, HEIGHT / 2), expected[1], TOLERANCE, what)
+}
+
+/// Checks that nothing is smoothed at low quality, whether it's a bitmap, the bitmap fill of
+/// a shape or a bitmap drawn into another one, while it all is at high quality.
+pub fn stage_quality_smoothing() -> Result<(), libtest_mimic::Failed> {
+    let mut renderer = wgpu_renderer((WIDTH, HEIGHT))?;
+    let bitmap = register_bitmap(&mut renderer)?;
+    let fill = FillStyle::Bitmap {
+        id: 1,
+        matrix: swf::Matrix::scale(Fixed16::from_f32(SCALE), Fixed16::from_f32(SCALE)),
+        is_smoothed: true,
+        is_repeating: false,
+    };
+    let shape = renderer.register_shape(square(&fill, WIDTH), &FillBitmap(bitmap.clone()));
+
+    for (quality, expected) in [
+        (StageQuality::High, SMOOTHED),
+        (StageQuality::Low, [BLACK, WHITE]),
+        (StageQuality::High, SMOOTHED),
+    ] {
+        renderer.set_quality(quality);
+
+        renderer.submit_frame(Color::from_rgba(0), stretched(&bitmap));
+        let image = renderer
+            .capture_frame(false)
+            .ok_or("Texture target must be readable")?;
+        check_middle(&image, expected, &format!("Bitmap at {quality}"))?;
+
+        let mut commands = CommandList::new();
+        commands.render_shape(shape.clone(), Transform::default());
+        renderer.submit_frame(Color::from_rgba(0), commands);
+        let image = renderer
+            .capture_frame(false)
+            .ok_or("Texture target must be readable")?;
+        check_middle(&image, expected, &format!("Bitmap fill at {quality}"))?;
+
+        // Like `BitmapData.draw`, which is drawn at the stage quality
+        let target = renderer.register_bitmap(Bitmap::new(
+            WIDTH,
+            HEIGHT,
+            BitmapFormat::Rgba,
+            vec![0; (WIDTH * HEIGHT * 4) as usize],
+        ))?;
+        let drawn = renderer
+            .render_offscreen(target, WIDTH, HEIGHT, stretched(&bitmap), quality)
+            .ok_or("Rendering to a bitmap must hand back its pixels")?
+            .retrieve_offscreen_texture()?;
+        let image = image::RgbaImage::from_raw(WIDTH, HEIGHT, drawn.data().to_vec())
+            .ok_or("Drawn bitmap must be the size of the target")?;
+        check_middle(&image, expected, &format!("Drawn bitmap at {quality}"))?;
+    }
+
+    Ok(())
+}
+
+/// Checks that the stage is rendered with more samples the higher the quality is, and without
+/// multisampling at low quality, as far as the device supports them.
+pub fn stage_quality_samples() -> Result<(), libtest_mimic::Failed> {
+    let mut renderer = wgpu_renderer((WIDTH, HEIGHT))?;
+
+    let mut last = 1;
+    for quality in [
+        StageQuality::Low,
+        StageQuality::Medium,
+        StageQuality::High,
+        StageQuality::Best,
+    ] {
+        renderer.set_quality(quality);
+        let samples = renderer.sample_count();
+        if samples < last || samples > quality.sample_count() {
+            return Err(
+                format!("{quality} quality has {samples} samples, after {last} below it").into(),
+            );
+        }
+        last = samples;
+    }
+
+    renderer.set_quality(StageQuality::Low);
+    if renderer.sample_count() != 1 {
+        return Err("Going back to low quality must turn multisampling off".into());
+    }
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use crate::util::environment::wgpu_renderer;
+use ruffle_core::swf::Twips;
+use ruffle_core::Color;
+use ruffle_render::backend::RenderBackend;
+use ruffle_render::bitmap::{Bitmap, BitmapFormat};
+use ruffle_render::commands::{CommandHandler, CommandList};
+use ruffle_render::matrix::Matrix;
+use ruffle_render::transform::Transform;
+
+const CELLS: u32 = 4;
+/// Too big for the bitmaps to be packed into the atlas, where they'd be drawn together.
+const CELL_SIZE: u32 = 260;
+const SIZE: u32 = CELLS * CELL_SIZE;
+const COLORS: [[u8; 4]; 2] = [[255, 0, 0, 255], [0, 0, 255, 255]];
+
+pub fn state_changes() -> Result<(), libtest_mimic::Failed> {
+    let mut renderer = wgpu_renderer((SIZE, SIZE))?;
+    let bitmaps = COLORS
+        .iter()
+        .map(|color| {
+            renderer.register_bitmap(Bitmap::new(
+                CELL_SIZE,
+                CELL_SIZE,
+                BitmapFormat::Rgba,
+                color.repeat((CELL_SIZE * CELL_SIZE) as usize),
+            ))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Alternating between the two bitmaps means that none of the draws can be batched,
+    // but they all share a pipeline
+    let mut commands = CommandList::new();
+    for index in 0..CELLS * CELLS {
+        let (x, y) = (index % CELLS, index / CELLS);
+        commands.render_bitmap(
+            bitmaps[(index % 2) as usize].clone(),
+            Transform {
+                matrix: Matrix::translate(
+                    Twips::from_pixels((x * CELL_SIZE) as f64),
+                    Twips::from_pixels((y * CELL_SIZE) as f64),
+                ),
+                ..Default::default()
+            },
+            false,
+        );
+    }
+    renderer.submit_frame(Color::from_rgba(0), commands);
+    assert_eq!(renderer.last_frame_draw_calls(), CELLS * CELLS);
+    // The pipeline is only set once, and then only the bitmap changes between draws
+    assert_eq!(renderer.last_frame_state_changes(), CELLS * CELLS + 1);
+
+    let image = renderer
+        .capture_frame(false)
+        .ok_or("Texture target must be readable")?;
+    for (x, y, pixel) in image.enumerate_pixels() {
+        let expected = COLORS[((x / CELL_SIZE) % 2) as usize];
+        assert_eq!(pixel.0, expected, "Pixel at ({x}, {y})");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,54 @@
+use crate::util::environment::wgpu_renderer;
+use ruffle_core::Color;
+use ruffle_render::backend::RenderBackend;
+use ruffle_render::bitmap::{Bitmap, BitmapFormat};
+use ruffle_render::commands::{CommandHandler, CommandList};
+use ruffle_render::matrix::Matrix;
+use ruffle_render::transform::Transform;
+
+const SIZE: u32 = 16;
+
+/// Checks that a smoothed bitmap with straight alpha, with an opaque white texel next to
+/// a transparent black one, fades from white to the red behind it without a dark halo.
+pub fn straight_alpha() -> Result<(), libtest_mimic::Failed> {
+    let mut renderer = wgpu_renderer((SIZE, SIZE))?;
+    let bitmap = renderer.register_bitmap(Bitmap::new(
+        2,
+        1,
+        BitmapFormat::StraightRgba,
+        vec![255, 255, 255, 255, 0, 0, 0, 0],
+    ))?;
+
+    let mut commands = CommandList::new();
+    commands.render_bitmap(
+        bitmap,
+        Transform {
+            matrix: Matrix::scale((SIZE / 2) as f32, SIZE as f32),
+            ..Default::default()
+        },
+        true,
+    );
+    renderer.submit_frame(Color::from_rgb(0xFF0000, 255), commands);
+    let image = renderer
+        .capture_frame(false)
+        .ok_or("Texture target must be readable")?;
+
+    let y = SIZE / 2;
+    let [r, g, b, _] = image.get_pixel(0, y).0;
+    if r != 255 || g < 253 || b < 253 {
+        return Err(format!("Left edge should be white, but was {:?}", [r, g, b]).into());
+    }
+    let [r, g, b, _] = image.get_pixel(SIZE - 1, y).0;
+    if r != 255 || g > 2 || b > 2 {
+        return Err(format!("Right edge should be red, but was {:?}", [r, g, b]).into());
+    }
+    // Blending white over red never darkens the red, which filtering the black of the
+    // transparent texel into the white one would.
+    for x in 0..SIZE {
+        let [r, g, b, _] = image.get_pixel(x, y).0;
+        if r < 253 || g.abs_diff(b) > 1 {
+            return Err(format!("Pixel {x} has a dark halo: {:?}", [r, g, b]).into());
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,106 @@
+use crate::util::environment::wgpu_renderer;
+use ruffle_core::swf::{LineStyle, Twips};
+use ruffle_core::Color;
+use ruffle_render::backend::null::NullBitmapSource;
+use ruffle_render::backend::RenderBackend;
+use ruffle_render::commands::{CommandHandler, CommandList};
+use ruffle_render::matrix::Matrix;
+use ruffle_render::shape_utils::{DistilledShape, DrawCommand, DrawPath, StrokeScaling};
+use ruffle_render::transform::Transform;
+
+const SIZE: u32 = 64;
+
+/// A square from (2, 2) to (12, 12), tessellated for the matrix it's drawn with
+/// like the shapes of display objects are.
+fn square<'a>(style: &'a LineStyle, matrix: &Matrix) -> DistilledShape<'a> {
+    let point = |x: f64, y: f64| (Twips::from_pixels(x), Twips::from_pixels(y));
+    let (x, y) = point(2.0, 2.0);
+    let mut commands = vec![DrawCommand::MoveTo { x, y }];
+    for (x, y) in [
+        point(12.0, 2.0),
+        point(12.0, 12.0),
+        point(2.0, 12.0),
+        point(2.0, 2.0),
+    ] {
+        commands.push(DrawCommand::LineTo { x, y });
+    }
+    let paths = vec![DrawPath::Stroke {
+        style,
+        is_closed: true,
+        commands,
+    }];
+    let stroke_matrix = StrokeScaling::from_paths(&paths).stroke_matrix(matrix);
+    DistilledShape {
+        paths,
+        shape_bounds: Default::default(),
+        edge_bounds: Default::default(),
+        id: 0,
+        stroke_matrix,
+    }
+}
+
+/// Checks that pixel hinted 1 pixel strokes stay 1 pixel wide when zoomed in if they don't
+/// scale, and grow by whole pixels if they do, wherever within a pixel they're drawn.
+pub fn stroke_scaling() -> Result<(), libtest_mimic::Failed> {
+    let mut renderer = wgpu_renderer((SIZE, SIZE))?;
+    // Without antialiasing, so that every pixel is either in the stroke or out of it
+    renderer.set_sample_count(Some(1));
+
+    let style = LineStyle::new()
+        .with_width(Twips::ONE)
+        .with_color(Color::from_rgb(0xFF0000, 255))
+        .with_is_pixel_hinted(true);
+    let styles = [
+        ("scaling", style.clone(), true),
+        (
+            "non-scaling",
+            style.with_allow_scale_x(false).with_allow_scale_y(false),
+            false,
+        ),
+    ];
+
+    let mut failures = Vec::new();
+    for (name, style, scales) in &styles {
+        for scale in [1u32, 2, 4] {
+            for offset in [0.0, 0.3] {
+                let matrix =
+                    Matrix::translate(Twips::from_pixels(offset), Twips::from_pixels(offset))
+                        * Matrix::scale(scale as f32, scale as f32);
+                let shape = renderer.register_shape(square(style, &matrix), &NullBitmapSource);
+
+                let mut commands = CommandList::new();
+                commands.render_shape(
+                    shape,
+                    Transform {
+                        matrix,
+                        ..Default::default()
+                    },
+                );
+                renderer.submit_frame(Color::from_rgba(0), commands);
+                let image = renderer
+                    .capture_frame(false)
+                    .ok_or("Texture target must be readable")?;
+                let filled = |x: u32, y: u32| image.get_pixel(x, y).0[3] != 0;
+
+                // Across the left and top edges, halfway along them
+                let middle = 7 * scale;
+                let left = (0..middle).filter(|&x| filled(x, middle)).count() as u32;
+                let top = (0..middle).filter(|&y| filled(middle, y)).count() as u32;
+                let expected = if *scales { scale } else { 1 };
+                for (edge, width) in [("left", left), ("top", top)] {
+                    if width != expected {
+                        failures.push(format!(
+                            "{name} stroke at {scale}x, {offset} pixels in: \
+                             {edge} edge is {width} pixels wide instead of {expected}"
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    if !failures.is_empty() {
+        return Err(failures.join("\n").into());
+    }
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use crate::util::environment::wgpu_renderer;
+use ruffle_core::swf::Twips;
+use ruffle_core::Color;
+use ruffle_render::backend::RenderBackend;
+use ruffle_render::commands::{CommandHandler, CommandList};
+use ruffle_render::matrix::Matrix;
+use ruffle_render_wgpu::wgpu;
+
+const SIZE: u32 = 32;
+
+pub fn surface_lost() -> Result<(), libtest_mimic::Failed> {
+    let mut renderer = wgpu_renderer((SIZE, SIZE))?;
+
+    // Every frame must still be rendered, right after the target is reconfigured
+    for (error, rgb) in [
+        (wgpu::SurfaceError::Lost, 0xFF0000),
+        (wgpu::SurfaceError::Outdated, 0x00FF00),
+    ] {
+        renderer.simulate_surface_error(error);
+        let mut commands = CommandList::new();
+        commands.draw_rect(
+            Color::from_rgb(rgb, 255),
+            Matrix::create_box(SIZE as f32, SIZE as f32, 0.0, Twips::ZERO, Twips::ZERO),
+        );
+        renderer.submit_frame(Color::from_rgba(0), commands);
+
+        let image = renderer
+            .capture_frame(false)
+            .ok_or("Texture target must be readable")?;
+        let expected = Color::from_rgb(rgb, 255);
+        for (x, y, pixel) in image.enumerate_pixels() {
+            assert_eq!(
+                pixel.0,
+                [expected.r, expected.g, expected.b, expected.a],
+                "Pixel at ({x}, {y})"
+            );
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,53 @@
+package {
+	public class test {}
+}
+
+import flash.display.BitmapData;
+import flash.geom.Point;
+import flash.geom.Rectangle;
+
+function dumpPixels(bitmap: BitmapData): void {
+	var pixels = [];
+	for (var x = 0; x < bitmap.width; x++) {
+		pixels.push(bitmap.getPixel32(x, 0).toString(16));
+	}
+	trace(pixels);
+}
+
+// Pixels that fail the test are copied from under the source rect
+var source: BitmapData = new BitmapData(4, 1, true, 0);
+source.setPixel32(0, 0, 0xFF102030);
+source.setPixel32(1, 0, 0xFF405060);
+source.setPixel32(2, 0, 0xFF708090);
+source.setPixel32(3, 0, 0xFFA0B0C0);
+var dest: BitmapData = new BitmapData(4, 1, true, 0xFF000000);
+trace("// dest.threshold(source, new Rectangle(1, 0, 3, 1), new Point(0, 0), \">\", 0xFF405060, 0xFFFF0000, 0xFFFFFFFF, true)");
+trace(dest.threshold(source, new Rectangle(1, 0, 3, 1), new Point(0, 0), ">", 0xFF405060, 0xFFFF0000, 0xFFFFFFFF, true));
+trace("// dest");
+dumpPixels(dest);
+
+// Each pixel is tested against the bitmap as it was before the call, not the pixels it's just written
+var same: BitmapData = new BitmapData(4, 1, true, 0);
+same.setPixel32(0, 0, 0xFF000010);
+same.setPixel32(1, 0, 0xFF000020);
+same.setPixel32(2, 0, 0xFF000030);
+same.setPixel32(3, 0, 0xFF000040);
+trace("// same.threshold(same, new Rectangle(0, 0, 3, 1), new Point(1, 0), \"==\", 0xFF000010, 0xFF000020, 0xFFFFFFFF, true)");
+trace(same.threshold(same, new Rectangle(0, 0, 3, 1), new Point(1, 0), "==", 0xFF000010, 0xFF000020, 0xFFFFFFFF, true));
+trace("// same");
+dumpPixels(same);
+
+// Likewise, each pixel is mapped from the bitmap as it was before the call
+var palette: BitmapData = new BitmapData(4, 1, true, 0);
+palette.setPixel32(0, 0, 0xFF000001);
+palette.setPixel32(1, 0, 0xFF000002);
+palette.setPixel32(2, 0, 0xFF000003);
+palette.setPixel32(3, 0, 0xFF000004);
+var invert: Array = [];
+for (var i = 0; i < 256; i++) {
+	invert.push(255 - i);
+}
+trace("// palette.paletteMap(palette, new Rectangle(0, 0, 3, 1), new Point(1, 0), null, null, invert, null)");
+palette.paletteMap(palette, new Rectangle(0, 0, 3, 1), new Point(1, 0), null, null, invert, null);
+trace("// palette");
+dumpPixels(palette);
@@ -0,0 +1,41 @@
+use crate::util::environment::isolated_wgpu_renderer;
+use ruffle_core::swf::Twips;
+use ruffle_core::Color;
+use ruffle_render::backend::RenderBackend;
+use ruffle_render::commands::{CommandHandler, CommandList};
+use ruffle_render::filters::{BlurFilter, Filter};
+use ruffle_render::matrix::Matrix;
+
+fn blurred_rect() -> CommandList {
+    let mut rect = CommandList::new();
+    rect.draw_rect(
+        Color::from_rgb(0xFF0000, 255),
+        Matrix::create_box(20.0, 20.0, 0.0, Twips::from_pixels(10.0), Twips::ZERO),
+    );
+    let mut commands = CommandList::new();
+    commands.filters(rect, vec![Filter::BlurFilter(BlurFilter::default())]);
+    commands
+}
+
+pub fn texture_pool_reuse() -> Result<(), libtest_mimic::Failed> {
+    // Other tests render with the shared descriptors at the same time, so count textures
+    // on descriptors of our own
+    let mut renderer = isolated_wgpu_renderer((40, 30))?;
+    let descriptors = renderer.descriptors().clone();
+
+    renderer.submit_frame(Color::from_rgba(0), blurred_rect());
+    let textures_created = descriptors.pooled_textures_created();
+    assert!(
+        textures_created > 0,
+        "Filters must render into pooled textures"
+    );
+
+    renderer.submit_frame(Color::from_rgba(0), blurred_rect());
+    assert_eq!(
+        descriptors.pooled_textures_created(),
+        textures_created,
+        "The same frame must reuse the textures of the previous one"
+    );
+
+    Ok(())
+}
@@ -1,3 +1,4 @@
+use crate::set_logger;
 use once_cell::sync::Lazy;
 use ruffle_render_wgpu::backend::WgpuRenderBackend;
 use ruffle_render_wgpu::descriptors::Descriptors;
@@ -28,7 +29,7 @@ fn create_wgpu_device() -> Option<(wgpu::Adapter, wgpu::Device, wgpu::Queue)> {
     .ok()
 }
 
-fn build_wgpu_descriptors() -> Option<Arc<Descriptors>> {
+pub fn build_wgpu_descriptors() -> Option<Arc<Descriptors>> {
     if let Some((adapter, device, queue)) = create_wgpu_device() {
         Some(Arc::new(Descriptors::new(adapter, device, queue)))
     } else {
@@ -37,3 +38,32 @@ fn build_wgpu_descriptors() -> Option<Arc<Descriptors>> {
 }
 
 pub static WGPU: Lazy<Option<Arc<Descriptors>>> = Lazy::new(build_wgpu_descriptors);
+
+/// Creates a renderer drawing into a texture of the given size, on the shared descriptors.
+///
+/// This fails without a device, so tests using it are registered with
+/// `.with_ignored_flag(WGPU.is_none())`.
+pub fn wgpu_renderer(
+    size: (u32, u32),
+) -> Result<WgpuRenderBackend<TextureTarget>, libtest_mimic::Failed> {
+    let descriptors = WGPU.clone().ok_or("Couldn't create a wgpu device")?;
+    wgpu_renderer_with(descriptors, size)
+}
+
+/// Like [`wgpu_renderer`], but on descriptors of its own, for tests that count what's
+/// created on them while other tests render at the same time.
+pub fn isolated_wgpu_renderer(
+    size: (u32, u32),
+) -> Result<WgpuRenderBackend<TextureTarget>, libtest_mimic::Failed> {
+    let descriptors = build_wgpu_descriptors().ok_or("Couldn't create a wgpu device")?;
+    wgpu_renderer_with(descriptors, size)
+}
+
+pub fn wgpu_renderer_with(
+    descriptors: Arc<Descriptors>,
+    size: (u32, u32),
+) -> Result<WgpuRenderBackend<TextureTarget>, libtest_mimic::Failed> {
+    set_logger();
+    let target = TextureTarget::new(&descriptors.device, size)?;
+    Ok(WgpuRenderBackend::new(descriptors, target)?)
+}
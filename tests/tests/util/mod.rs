@@ -3,7 +3,9 @@
 
 pub mod environment;
 pub mod options;
+pub mod pixels;
 pub mod runner;
+pub mod shapes;
 pub mod test;
 
 /// Wrapper around string slice that makes debug output `{:?}` to print string same way as `{}`.
@@ -0,0 +1,42 @@
+use image::RgbaImage;
+use ruffle_render::bitmap::Bitmap;
+
+/// Fails unless every component of the pixel at `(x, y)` is within `tolerance` of `expected`,
+/// describing the pixel as `what`.
+pub fn check_pixel(
+    image: &RgbaImage,
+    (x, y): (u32, u32),
+    expected: [u8; 4],
+    tolerance: u8,
+    what: &str,
+) -> Result<(), libtest_mimic::Failed> {
+    let actual = image.get_pixel(x, y).0;
+    if actual
+        .iter()
+        .zip(expected)
+        .any(|(actual, expected)| actual.abs_diff(expected) > tolerance)
+    {
+        return Err(format!("{what} at ({x}, {y}) is {actual:?}, expected {expected:?}").into());
+    }
+    Ok(())
+}
+
+/// Reads the premultiplied color of the pixel at `(x, y)` of a bitmap.
+pub fn pixel(bitmap: &Bitmap, x: u32, y: u32) -> [u8; 4] {
+    let index = ((y * bitmap.width() + x) * 4) as usize;
+    bitmap.data()[index..index + 4]
+        .try_into()
+        .expect("Pixel must have 4 components")
+}
+
+/// The pixels of a square bitmap of `size` pixels, with the color of each pixel given by
+/// `color_at`.
+pub fn square_pixels(size: u32, color_at: impl Fn(u32, u32) -> [u8; 4]) -> Vec<u8> {
+    let mut rgba = Vec::with_capacity((size * size * 4) as usize);
+    for y in 0..size {
+        for x in 0..size {
+            rgba.extend_from_slice(&color_at(x, y));
+        }
+    }
+    rgba
+}
@@ -49,7 +49,7 @@ impl AudioBackend for TestAudioBackend {
     fn pause(&mut self) {}
 }
 
-struct TestLogBackend {
+pub struct TestLogBackend {
     trace_output: Rc<RefCell<String>>,
 }
 
@@ -0,0 +1,25 @@
+use ruffle_core::swf::{FillStyle, Twips};
+use ruffle_render::shape_utils::{DistilledShape, DrawCommand, DrawPath, FillRule};
+
+/// A square of `size` pixels at the origin, filled with `style`.
+pub fn square(style: &FillStyle, size: u32) -> DistilledShape {
+    let size = Twips::from_pixels(f64::from(size));
+    let mut commands = vec![DrawCommand::MoveTo {
+        x: Twips::ZERO,
+        y: Twips::ZERO,
+    }];
+    for (x, y) in [(size, Twips::ZERO), (size, size), (Twips::ZERO, size)] {
+        commands.push(DrawCommand::LineTo { x, y });
+    }
+    DistilledShape {
+        paths: vec![DrawPath::Fill {
+            style,
+            commands,
+            winding_rule: FillRule::EvenOdd,
+        }],
+        shape_bounds: Default::default(),
+        edge_bounds: Default::default(),
+        id: 0,
+        stroke_matrix: None,
+    }
+}
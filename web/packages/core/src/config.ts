@@ -49,10 +49,12 @@ export const DEFAULT_CONFIG: Required<Config> = {
     menu: true,
     salign: "",
     quality: "high",
+    sampleCount: null,
     scale: "showAll",
     forceScale: false,
     wmode: WindowMode.Opaque,
     publicPath: null,
     polyfills: true,
     playerVersion: null,
+    socketProxy: [],
 };
@@ -117,6 +117,30 @@ export const enum WindowMode {
     Gpu = "gpu",
 }
 
+/**
+ * A WebSocket proxy to use in place of a TCP socket.
+ *
+ * Browsers can't open raw TCP sockets, so connections made by a movie
+ * (for example with `XMLSocket`) are made to a WebSocket proxy instead,
+ * which should relay the bytes to and from the real host.
+ */
+export interface SocketProxy {
+    /**
+     * The host that the movie connects to.
+     */
+    host: string;
+
+    /**
+     * The port that the movie connects to.
+     */
+    port: number;
+
+    /**
+     * The URL of the WebSocket proxy to connect to instead.
+     */
+    proxyUrl: string;
+}
+
 /**
  * Any options used for loading a movie.
  */
@@ -268,6 +292,17 @@ export interface BaseLoadOptions {
      */
     quality?: string;
 
+    /**
+     * The number of MSAA samples to render with, instead of the number implied by the quality.
+     *
+     * Lower values trade edge quality for speed. Counts that the browser doesn't support are
+     * rounded down. This is only used by the wgpu renderers.
+     * null means that the quality decides.
+     *
+     * @default null
+     */
+    sampleCount?: number | null;
+
     /**
      * This is equivalent to Stage.scaleMode.
      *
@@ -300,6 +335,15 @@ export interface BaseLoadOptions {
      * @default null
      */
     playerVersion?: number | null;
+
+    /**
+     * The WebSocket proxies to use for the sockets a movie connects to.
+     *
+     * Connections to a host and port without a proxy will fail.
+     *
+     * @default []
+     */
+    socketProxy?: Array<SocketProxy>;
 }
 
 /**
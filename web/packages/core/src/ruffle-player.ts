@@ -685,6 +685,15 @@ export class RufflePlayer extends HTMLElement {
         return false;
     }
 
+    /**
+     * The render backend that this player draws with, so that pages can warn about slow ones.
+     *
+     * @returns One of `webgpu`, `wgpu-webgl`, `webgl` or `canvas`, or null if no movie has been loaded yet.
+     */
+    get renderer(): string | null {
+        return this.instance?.renderer_name() ?? null;
+    }
+
     /**
      * Returns the master volume of the player.
      *
@@ -821,6 +830,32 @@ export class RufflePlayer extends HTMLElement {
         }
     }
 
+    /**
+     * Takes a screenshot of the movie as it currently looks.
+     *
+     * @returns A promise that resolves to a PNG image of the current frame.
+     */
+    async screenshot(): Promise<Blob> {
+        const imageData: ImageData | null =
+            this.instance?.capture_frame() ?? null;
+        if (!imageData) {
+            throw new Error("The current frame could not be captured");
+        }
+        const canvas = document.createElement("canvas");
+        canvas.width = imageData.width;
+        canvas.height = imageData.height;
+        canvas.getContext("2d")!.putImageData(imageData, 0, 0);
+        return new Promise((resolve, reject) => {
+            canvas.toBlob((blob) => {
+                if (blob) {
+                    resolve(blob);
+                } else {
+                    reject(new Error("The screenshot could not be encoded"));
+                }
+            }, "image/png");
+        });
+    }
+
     private contextMenuItems(): Array<ContextMenuItem | null> {
         const CHECKMARK = String.fromCharCode(0x2713);
         const items = [];
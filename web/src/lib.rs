@@ -20,7 +20,10 @@ use ruffle_core::{
     Color, Player, PlayerBuilder, PlayerEvent, SandboxType, StageScaleMode, StaticCallstack,
     ViewportDimensions,
 };
+#[cfg(feature = "canvas")]
+use ruffle_render::backend::switchable::SwitchableRenderBackend;
 use ruffle_render::quality::StageQuality;
+use ruffle_render::utils::unmultiply_alpha_rgba;
 use ruffle_video_software::backend::SoftwareVideoBackend;
 use ruffle_web_common::JsResult;
 use serde::{Deserialize, Serialize};
@@ -34,10 +37,11 @@ use tracing_subscriber::layer::{Layered, SubscriberExt};
 use tracing_subscriber::registry::Registry;
 use tracing_wasm::{WASMLayer, WASMLayerConfigBuilder};
 use url::Url;
-use wasm_bindgen::{prelude::*, JsCast, JsValue};
+use wasm_bindgen::{prelude::*, Clamped, JsCast, JsValue};
 use web_sys::{
-    AddEventListenerOptions, Element, Event, EventTarget, HtmlCanvasElement, HtmlElement,
-    KeyboardEvent, PointerEvent, WheelEvent, Window,
+    AddEventListenerOptions, CompositionEvent, Element, Event, EventTarget, HtmlCanvasElement,
+    HtmlElement, HtmlInputElement, ImageData, InputEvent, KeyboardEvent, PointerEvent, WheelEvent,
+    Window,
 };
 
 static RUFFLE_GLOBAL_PANIC: Once = Once::new();
@@ -58,6 +62,11 @@ struct RuffleInstance {
     callstack: Option<StaticCallstack>,
     js_player: JavascriptPlayer,
     canvas: HtmlCanvasElement,
+    /// A hidden input that input methods and on-screen keyboards type into, which is kept
+    /// at the caret of the text field being typed into.
+    text_input: HtmlInputElement,
+    /// Which of the render backends was created, such as `webgl`.
+    renderer_name: &'static str,
     canvas_width: i32,
     canvas_height: i32,
     device_pixel_ratio: f64,
@@ -74,7 +83,10 @@ struct RuffleInstance {
     mouse_wheel_callback: Option<Closure<dyn FnMut(WheelEvent)>>,
     key_down_callback: Option<Closure<dyn FnMut(KeyboardEvent)>>,
     key_up_callback: Option<Closure<dyn FnMut(KeyboardEvent)>>,
+    composition_callback: Option<Closure<dyn FnMut(CompositionEvent)>>,
+    text_input_callback: Option<Closure<dyn FnMut(InputEvent)>>,
     unload_callback: Option<Closure<dyn FnMut(Event)>>,
+    context_lost_callback: Option<Closure<dyn FnMut(Event)>>,
     has_focus: bool,
     trace_observer: Arc<RefCell<JsValue>>,
     log_subscriber: Arc<Layered<WASMLayer, Registry>>,
@@ -159,6 +171,9 @@ struct Config {
 
     quality: Option<String>,
 
+    #[serde(rename = "sampleCount")]
+    sample_count: Option<u32>,
+
     scale: Option<String>,
 
     #[serde(rename = "forceScale")]
@@ -177,6 +192,9 @@ struct Config {
 
     #[serde(rename = "playerVersion")]
     player_version: Option<u8>,
+
+    #[serde(rename = "socketProxy")]
+    socket_proxy: Vec<navigator::SocketProxy>,
 }
 
 /// Metadata about the playing SWF file to be passed back to JavaScript.
@@ -301,11 +319,42 @@ impl Ruffle {
         let _ = self.with_core_mut(|core| core.set_volume(value));
     }
 
-    pub fn renderer_debug_info(&self) -> JsValue {
-        self.with_core(|core| JsValue::from_str(&core.renderer().debug_info()))
+    pub fn renderer_name(&self) -> JsValue {
+        self.with_instance(|instance| JsValue::from_str(instance.renderer_name))
             .unwrap_or(JsValue::NULL)
     }
 
+    pub fn renderer_debug_info(&self) -> JsValue {
+        self.with_core(|core| {
+            let culling_stats = core.culling_stats();
+            JsValue::from_str(&format!(
+                "{}\nObjects drawn last frame: {}\nObjects culled last frame: {}",
+                core.renderer().debug_info(),
+                culling_stats.drawn,
+                culling_stats.culled
+            ))
+        })
+        .unwrap_or(JsValue::NULL)
+    }
+
+    /// Renders the current frame again and returns its pixels, or `null` if that isn't possible.
+    pub fn capture_frame(&mut self) -> JsValue {
+        self.with_core_mut(|core| {
+            let mut bitmap = core.capture_frame()?;
+            unmultiply_alpha_rgba(bitmap.data_mut());
+            ImageData::new_with_u8_clamped_array_and_sh(
+                Clamped(bitmap.data()),
+                bitmap.width(),
+                bitmap.height(),
+            )
+            .ok()
+        })
+        .ok()
+        .flatten()
+        .map(JsValue::from)
+        .unwrap_or(JsValue::NULL)
+    }
+
     // after the context menu is closed, remember to call `clear_custom_menu_items`!
     pub fn prepare_context_menu(&mut self) -> JsValue {
         self.with_core_mut(|core| {
@@ -331,6 +380,7 @@ impl Ruffle {
         // Remove instance from the active list.
         if let Ok(mut instance) = self.remove_instance() {
             instance.canvas.remove();
+            instance.text_input.remove();
 
             // Stop all audio playing from the instance.
             let _ = instance.with_core_mut(|core| {
@@ -390,6 +440,16 @@ impl Ruffle {
                     .warn_on_error();
                 instance.mouse_up_callback = None;
             }
+            if let Some(context_lost_callback) = &instance.context_lost_callback {
+                let canvas_events: &EventTarget = instance.canvas.as_ref();
+                canvas_events
+                    .remove_event_listener_with_callback(
+                        "webglcontextlost",
+                        context_lost_callback.as_ref().unchecked_ref(),
+                    )
+                    .warn_on_error();
+                instance.context_lost_callback = None;
+            }
             if let Some(mouse_wheel_callback) = &instance.mouse_wheel_callback {
                 let canvas_events: &EventTarget = instance.canvas.as_ref();
                 canvas_events
@@ -420,6 +480,28 @@ impl Ruffle {
                     .warn_on_error();
                 instance.key_up_callback = None;
             }
+            if let Some(composition_callback) = &instance.composition_callback {
+                let text_input_events: &EventTarget = instance.text_input.as_ref();
+                for event in ["compositionupdate", "compositionend"] {
+                    text_input_events
+                        .remove_event_listener_with_callback(
+                            event,
+                            composition_callback.as_ref().unchecked_ref(),
+                        )
+                        .warn_on_error();
+                }
+                instance.composition_callback = None;
+            }
+            if let Some(text_input_callback) = &instance.text_input_callback {
+                let text_input_events: &EventTarget = instance.text_input.as_ref();
+                text_input_events
+                    .remove_event_listener_with_callback(
+                        "input",
+                        text_input_callback.as_ref().unchecked_ref(),
+                    )
+                    .warn_on_error();
+                instance.text_input_callback = None;
+            }
             if let Some(unload_callback) = &instance.unload_callback {
                 instance
                     .window
@@ -509,13 +591,37 @@ impl Ruffle {
         let window = web_sys::window().ok_or("Expected window")?;
         let document = window.document().ok_or("Expected document")?;
 
-        let (mut builder, canvas) =
+        let (mut builder, canvas, renderer_name) =
             create_renderer(PlayerBuilder::new(), &document, &config).await?;
+        #[cfg(feature = "canvas")]
+        let is_transparent = config.wmode.as_deref() == Some("transparent");
 
         parent
             .append_child(&canvas.clone().into())
             .into_js_result()?;
 
+        let text_input: HtmlInputElement = document
+            .create_element("input")
+            .into_js_result()?
+            .dyn_into()
+            .map_err(|_| "Expected HtmlInputElement")?;
+        text_input.set_autocomplete("off");
+        let text_input_style = text_input.style();
+        for (name, value) in [
+            ("display", "none"),
+            ("position", "absolute"),
+            ("width", "1px"),
+            ("padding", "0"),
+            ("border", "0"),
+            ("opacity", "0"),
+            ("pointer-events", "none"),
+        ] {
+            text_input_style.set_property(name, value).warn_on_error();
+        }
+        parent
+            .append_child(&text_input.clone().into())
+            .into_js_result()?;
+
         if let Ok(audio) = audio::WebAudioBackend::new() {
             builder = builder.with_audio(audio);
         } else {
@@ -525,6 +631,7 @@ impl Ruffle {
             allow_script_access,
             config.upgrade_to_https,
             config.base_url,
+            config.socket_proxy,
         ));
 
         match window.local_storage() {
@@ -546,7 +653,11 @@ impl Ruffle {
         let trace_observer = Arc::new(RefCell::new(JsValue::UNDEFINED));
         let core = builder
             .with_log(log_adapter::WebLogBackend::new(trace_observer.clone()))
-            .with_ui(ui::WebUiBackend::new(js_player.clone(), &canvas))
+            .with_ui(ui::WebUiBackend::new(
+                js_player.clone(),
+                &canvas,
+                &text_input,
+            ))
             .with_video(SoftwareVideoBackend::new())
             .with_letterbox(config.letterbox)
             .with_max_execution_duration(config.max_execution_duration)
@@ -592,6 +703,8 @@ impl Ruffle {
             callstack,
             js_player: js_player.clone(),
             canvas: canvas.clone(),
+            text_input: text_input.clone(),
+            renderer_name,
             canvas_width: 0, // Initialize canvas width and height to 0 to force an initial canvas resize.
             canvas_height: 0,
             device_pixel_ratio: window.device_pixel_ratio(),
@@ -606,7 +719,10 @@ impl Ruffle {
             mouse_wheel_callback: None,
             key_down_callback: None,
             key_up_callback: None,
+            composition_callback: None,
+            text_input_callback: None,
             unload_callback: None,
+            context_lost_callback: None,
             timestamp: None,
             has_focus: false,
             trace_observer,
@@ -798,7 +914,7 @@ impl Ruffle {
             // Create keydown event handler.
             let key_down_callback = Closure::wrap(Box::new(move |js_event: KeyboardEvent| {
                 let _ = ruffle.with_instance(|instance| {
-                    if instance.has_focus {
+                    if instance.has_focus && !is_composing(&js_event) {
                         let _ = instance.with_core_mut(|core| {
                             let key_code = web_to_ruffle_key_code(&js_event.code());
                             let key_char = web_key_to_codepoint(&js_event.key());
@@ -825,7 +941,7 @@ impl Ruffle {
             // Create keyup event handler.
             let key_up_callback = Closure::wrap(Box::new(move |js_event: KeyboardEvent| {
                 let _ = ruffle.with_instance(|instance| {
-                    if instance.has_focus {
+                    if instance.has_focus && !is_composing(&js_event) {
                         let _ = instance.with_core_mut(|core| {
                             let key_code = web_to_ruffle_key_code(&js_event.code());
                             let key_char = web_key_to_codepoint(&js_event.key());
@@ -841,6 +957,60 @@ impl Ruffle {
                 .warn_on_error();
             instance.key_up_callback = Some(key_up_callback);
 
+            // Create input method composition handler.
+            let composition_callback =
+                Closure::wrap(Box::new(move |js_event: CompositionEvent| {
+                    let _ = ruffle.with_instance(|instance| {
+                        let text = js_event.data().unwrap_or_default();
+                        let event = if js_event.type_() == "compositionend" {
+                            instance.text_input.set_value("");
+                            PlayerEvent::ImeCommit { text }
+                        } else {
+                            PlayerEvent::ImeComposition { text }
+                        };
+                        let _ = instance.with_core_mut(|core| {
+                            core.handle_event(event);
+                        });
+                    });
+                }) as Box<dyn FnMut(CompositionEvent)>);
+
+            let text_input_events: &EventTarget = instance.text_input.as_ref();
+            for event in ["compositionupdate", "compositionend"] {
+                text_input_events
+                    .add_event_listener_with_callback(
+                        event,
+                        composition_callback.as_ref().unchecked_ref(),
+                    )
+                    .warn_on_error();
+            }
+            instance.composition_callback = Some(composition_callback);
+
+            // Create text input handler, for the text that on-screen keyboards type without
+            // key events for it.
+            let text_input_callback = Closure::wrap(Box::new(move |js_event: InputEvent| {
+                if js_event.is_composing() {
+                    return;
+                }
+                let _ = ruffle.with_instance(|instance| {
+                    let text = instance.text_input.value();
+                    instance.text_input.set_value("");
+                    let _ = instance.with_core_mut(|core| {
+                        for codepoint in text.chars() {
+                            core.handle_event(PlayerEvent::TextInput { codepoint });
+                        }
+                    });
+                });
+            }) as Box<dyn FnMut(InputEvent)>);
+
+            let text_input_events: &EventTarget = instance.text_input.as_ref();
+            text_input_events
+                .add_event_listener_with_callback(
+                    "input",
+                    text_input_callback.as_ref().unchecked_ref(),
+                )
+                .warn_on_error();
+            instance.text_input_callback = Some(text_input_callback);
+
             let unload_callback = Closure::wrap(Box::new(move |_| {
                 let _ = ruffle.with_core_mut(|core| {
                     core.flush_shared_objects();
@@ -854,6 +1024,33 @@ impl Ruffle {
                 )
                 .warn_on_error();
             instance.unload_callback = Some(unload_callback);
+
+            // Nothing is drawn anymore once the browser takes the context away, such as when
+            // too many are in use, so carry on with the canvas renderer, which doesn't need one.
+            // If even that fails, at least tell the user why
+            let context_lost_callback = Closure::wrap(Box::new(move |_| {
+                let _ = ruffle.with_instance_mut(|instance| {
+                    tracing::error!("The {} context was lost", instance.renderer_name);
+                    #[cfg(feature = "canvas")]
+                    match instance.fall_back_to_canvas(is_transparent) {
+                        Ok(()) => return,
+                        Err(error) => {
+                            tracing::error!("Error falling back to canvas renderer: {}", error)
+                        }
+                    }
+                    instance.js_player.display_message(
+                        "The graphics context of this content was lost, so it can't be drawn anymore. Reloading the page may fix this.",
+                    );
+                });
+            }) as Box<dyn FnMut(Event)>);
+
+            canvas_events
+                .add_event_listener_with_callback(
+                    "webglcontextlost",
+                    context_lost_callback.as_ref().unchecked_ref(),
+                )
+                .warn_on_error();
+            instance.context_lost_callback = Some(context_lost_callback);
         })?;
 
         // Set initial timestamp and do initial tick to start animation loop.
@@ -1095,6 +1292,83 @@ impl Ruffle {
 }
 
 impl RuffleInstance {
+    /// Replaces a renderer that lost its context with the canvas renderer, drawing to a new
+    /// canvas that takes the place of the old one.
+    ///
+    /// Only renderers wrapped by `create_renderer` can be replaced.
+    #[cfg(feature = "canvas")]
+    fn fall_back_to_canvas(&mut self, is_transparent: bool) -> Result<(), Box<dyn Error>> {
+        tracing::info!("Falling back to Canvas renderer...");
+        let document = self.window.document().ok_or("Expected document")?;
+        let canvas: HtmlCanvasElement = document
+            .create_element("canvas")
+            .into_js_result()?
+            .dyn_into()
+            .map_err(|_| "Expected HtmlCanvasElement")?;
+        let renderer = ruffle_render_canvas::WebCanvasRenderBackend::new(&canvas, is_transparent)?;
+        self.with_core_mut(|core| -> Result<(), Box<dyn Error>> {
+            core.renderer_mut()
+                .downcast_mut::<SwitchableRenderBackend>()
+                .ok_or("Renderer can't be replaced")?
+                .switch_to(Box::new(renderer))?;
+            Ok(())
+        })??;
+
+        // Listeners belong to the old canvas, so they have to be moved over to the new one
+        let old_events: &EventTarget = self.canvas.as_ref();
+        let new_events: &EventTarget = canvas.as_ref();
+        for (name, callback) in [
+            ("pointermove", &self.mouse_move_callback),
+            ("pointerdown", &self.mouse_down_callback),
+            ("pointerup", &self.mouse_up_callback),
+        ] {
+            if let Some(callback) = callback {
+                old_events
+                    .remove_event_listener_with_callback(name, callback.as_ref().unchecked_ref())
+                    .warn_on_error();
+                new_events
+                    .add_event_listener_with_callback(name, callback.as_ref().unchecked_ref())
+                    .warn_on_error();
+            }
+        }
+        if let Some(callback) = &self.mouse_wheel_callback {
+            old_events
+                .remove_event_listener_with_callback("wheel", callback.as_ref().unchecked_ref())
+                .warn_on_error();
+            let mut options = AddEventListenerOptions::new();
+            options.passive(false);
+            new_events
+                .add_event_listener_with_callback_and_add_event_listener_options(
+                    "wheel",
+                    callback.as_ref().unchecked_ref(),
+                    &options,
+                )
+                .warn_on_error();
+        }
+        if let Some(callback) = &self.context_lost_callback {
+            old_events
+                .remove_event_listener_with_callback(
+                    "webglcontextlost",
+                    callback.as_ref().unchecked_ref(),
+                )
+                .warn_on_error();
+        }
+
+        canvas
+            .style()
+            .set_property("touch-action", "none")
+            .warn_on_error();
+        self.canvas
+            .replace_with_with_node_1(&canvas)
+            .into_js_result()?;
+        self.canvas = canvas;
+        self.renderer_name = "canvas";
+        // Size the new canvas on the next tick
+        self.canvas_width = 0;
+        self.canvas_height = 0;
+        Ok(())
+    }
+
     #[allow(dead_code)]
     fn with_core<F, O>(&self, f: F) -> Result<O, RuffleInstanceError>
     where
@@ -1284,7 +1558,7 @@ async fn create_renderer(
     builder: PlayerBuilder,
     document: &web_sys::Document,
     config: &Config,
-) -> Result<(PlayerBuilder, HtmlCanvasElement), Box<dyn Error>> {
+) -> Result<(PlayerBuilder, HtmlCanvasElement, &'static str), Box<dyn Error>> {
     #[cfg(not(any(feature = "canvas", feature = "webgpu", feature = "wgpu-webgl")))]
     std::compile_error!("You must enable one of the render backend features (e.g., webgl).");
 
@@ -1309,8 +1583,9 @@ async fn create_renderer(
                 .map_err(|_| "Expected HtmlCanvasElement")?;
 
             match ruffle_render_wgpu::backend::WgpuRenderBackend::for_canvas(&canvas).await {
-                Ok(renderer) => {
-                    return Ok((builder.with_renderer(renderer), canvas));
+                Ok(mut renderer) => {
+                    renderer.set_sample_count(config.sample_count);
+                    return Ok((builder.with_renderer(renderer), canvas, "webgpu"));
                 }
                 Err(error) => tracing::error!("Error creating wgpu webgpu renderer: {}", error),
             }
@@ -1326,8 +1601,13 @@ async fn create_renderer(
             .map_err(|_| "Expected HtmlCanvasElement")?;
 
         match ruffle_render_wgpu::backend::WgpuRenderBackend::for_canvas(&canvas).await {
-            Ok(renderer) => {
-                return Ok((builder.with_renderer(renderer), canvas));
+            Ok(mut renderer) => {
+                renderer.set_sample_count(config.sample_count);
+                return Ok((
+                    with_switchable_renderer(builder, document, renderer),
+                    canvas,
+                    "wgpu-webgl",
+                ));
             }
             Err(error) => tracing::error!("Error creating wgpu webgl renderer: {}", error),
         }
@@ -1346,7 +1626,11 @@ async fn create_renderer(
             .map_err(|_| "Expected HtmlCanvasElement")?;
         match ruffle_render_webgl::WebGlRenderBackend::new(&canvas, _is_transparent) {
             Ok(renderer) => {
-                return Ok((builder.with_renderer(renderer), canvas));
+                return Ok((
+                    with_switchable_renderer(builder, document, renderer),
+                    canvas,
+                    "webgl",
+                ));
             }
             Err(error) => tracing::error!("Error creating WebGL renderer: {}", error),
         }
@@ -1362,7 +1646,7 @@ async fn create_renderer(
             .map_err(|_| "Expected HtmlCanvasElement")?;
         match ruffle_render_canvas::WebCanvasRenderBackend::new(&canvas, _is_transparent) {
             Ok(renderer) => {
-                return Ok((builder.with_renderer(renderer), canvas));
+                return Ok((builder.with_renderer(renderer), canvas, "canvas"));
             }
             Err(error) => tracing::error!("Error creating canvas renderer: {}", error),
         }
@@ -1371,6 +1655,42 @@ async fn create_renderer(
     Err("Unable to create renderer".into())
 }
 
+/// Wraps a WebGL renderer so that it can be replaced with the canvas renderer if its context is
+/// lost. See `RuffleInstance::fall_back_to_canvas`.
+///
+/// This keeps a copy of every shape and bitmap, so it's only done if the browser can give us a
+/// 2D context to fall back to.
+#[cfg(all(feature = "canvas", any(feature = "wgpu-webgl", feature = "webgl")))]
+fn with_switchable_renderer(
+    builder: PlayerBuilder,
+    document: &web_sys::Document,
+    renderer: impl ruffle_render::backend::RenderBackend + 'static,
+) -> PlayerBuilder {
+    let can_fall_back = document
+        .create_element("canvas")
+        .ok()
+        .and_then(|canvas| canvas.dyn_into::<HtmlCanvasElement>().ok())
+        .and_then(|canvas| canvas.get_context("2d").ok().flatten())
+        .is_some();
+    if can_fall_back {
+        builder.with_renderer(SwitchableRenderBackend::new(Box::new(renderer)))
+    } else {
+        builder.with_renderer(renderer)
+    }
+}
+
+#[cfg(all(
+    not(feature = "canvas"),
+    any(feature = "wgpu-webgl", feature = "webgl")
+))]
+fn with_switchable_renderer(
+    builder: PlayerBuilder,
+    _document: &web_sys::Document,
+    renderer: impl ruffle_render::backend::RenderBackend + 'static,
+) -> PlayerBuilder {
+    builder.with_renderer(renderer)
+}
+
 pub fn set_panic_handler() {
     static HOOK_HAS_BEEN_SET: Once = Once::new();
     HOOK_HAS_BEEN_SET.call_once(|| {
@@ -1559,6 +1879,12 @@ fn web_to_ruffle_key_code(key_code: &str) -> KeyCode {
 
 /// Convert a web `KeyboardEvent.key` value into a character codepoint.
 /// Return `None` if they input was not a printable character.
+/// Whether a key event is part of what an input method is composing, which is left for it
+/// to handle.
+fn is_composing(event: &KeyboardEvent) -> bool {
+    event.is_composing() || event.key() == "Process"
+}
+
 fn web_key_to_codepoint(key: &str) -> Option<char> {
     // TODO: This is a very cheesy way to tell if a `KeyboardEvent.key` is a printable character.
     // Single character strings will be an actual printable char that we can use as text input.
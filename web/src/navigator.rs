@@ -1,22 +1,42 @@
 //! Navigator backend for web
+use async_channel::{Receiver, Sender};
 use js_sys::{Array, ArrayBuffer, Uint8Array};
 use ruffle_core::backend::navigator::{
-    NavigationMethod, NavigatorBackend, OwnedFuture, Request, Response,
+    ConnectionState, NavigationMethod, NavigatorBackend, OwnedFuture, Request, Response,
+    SocketAction, SocketHandle,
 };
 use ruffle_core::indexmap::IndexMap;
 use ruffle_core::loader::Error;
+use serde::Deserialize;
 use std::borrow::Cow;
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::Duration;
 use url::Url;
+use wasm_bindgen::closure::Closure;
 use wasm_bindgen::JsCast;
 use wasm_bindgen_futures::{spawn_local, JsFuture};
 use web_sys::{
-    window, Blob, BlobPropertyBag, Request as WebRequest, RequestInit, Response as WebResponse,
+    window, BinaryType, Blob, BlobPropertyBag, Headers, MessageEvent, Request as WebRequest,
+    RequestInit, Response as WebResponse, WebSocket,
 };
 
+/// A WebSocket proxy to connect to in place of a TCP socket, which browsers can't open.
+#[derive(Deserialize, Clone)]
+pub struct SocketProxy {
+    host: String,
+
+    port: u16,
+
+    #[serde(rename = "proxyUrl")]
+    proxy_url: String,
+}
+
 pub struct WebNavigatorBackend {
     allow_script_access: bool,
     upgrade_to_https: bool,
     base_url: Option<Url>,
+    socket_proxies: Vec<SocketProxy>,
 }
 
 impl WebNavigatorBackend {
@@ -24,6 +44,7 @@ impl WebNavigatorBackend {
         allow_script_access: bool,
         upgrade_to_https: bool,
         base_url: Option<String>,
+        socket_proxies: Vec<SocketProxy>,
     ) -> Self {
         let window = web_sys::window().expect("window()");
 
@@ -62,6 +83,7 @@ impl WebNavigatorBackend {
             allow_script_access,
             upgrade_to_https,
             base_url,
+            socket_proxies,
         }
     }
 
@@ -187,6 +209,17 @@ impl NavigatorBackend for WebNavigatorBackend {
                 init.body(Some(&datablob));
             }
 
+            if !request.headers().is_empty() {
+                let headers =
+                    Headers::new().map_err(|_| Error::FetchError("Got JS error".to_string()))?;
+                for (name, value) in request.headers() {
+                    headers
+                        .append(name, value)
+                        .map_err(|_| Error::FetchError(format!("Invalid header {name}")))?;
+                }
+                init.headers(&headers);
+            }
+
             let request = WebRequest::new_with_str_and_init(&url, &init)
                 .map_err(|_| Error::FetchError(format!("Unable to create request for {url}")))?;
 
@@ -199,10 +232,7 @@ impl NavigatorBackend for WebNavigatorBackend {
                 .dyn_into()
                 .map_err(|_| Error::FetchError("Fetch result wasn't a WebResponse".to_string()))?;
             if !response.ok() {
-                return Err(Error::FetchError(format!(
-                    "HTTP status is not ok, got {}",
-                    response.status_text()
-                )));
+                return Err(Error::HttpNotOk(response.status()));
             }
 
             let url = response.url();
@@ -222,7 +252,11 @@ impl NavigatorBackend for WebNavigatorBackend {
             })?;
             let body = Uint8Array::new(&body).to_vec();
 
-            Ok(Response { url, body })
+            Ok(Response {
+                url,
+                body,
+                status: response.status(),
+            })
         })
     }
 
@@ -240,4 +274,90 @@ impl NavigatorBackend for WebNavigatorBackend {
         }
         url
     }
+
+    fn connect_socket(
+        &mut self,
+        host: String,
+        port: u16,
+        // The browser enforces its own timeout on WebSocket connections.
+        _timeout: Duration,
+        handle: SocketHandle,
+        receiver: Receiver<Vec<u8>>,
+        sender: Sender<SocketAction>,
+    ) {
+        let Some(proxy) = self
+            .socket_proxies
+            .iter()
+            .find(|proxy| proxy.host == host && proxy.port == port)
+        else {
+            tracing::warn!("Missing WebSocket proxy for host {}, port {}", host, port);
+            let _ = sender.try_send(SocketAction::Connect(handle, ConnectionState::Failed));
+            return;
+        };
+
+        let socket = match WebSocket::new(&proxy.proxy_url) {
+            Ok(socket) => socket,
+            Err(e) => {
+                tracing::error!(
+                    "Could not open WebSocket proxy {}: {:?}",
+                    proxy.proxy_url,
+                    e
+                );
+                let _ = sender.try_send(SocketAction::Connect(handle, ConnectionState::Failed));
+                return;
+            }
+        };
+        socket.set_binary_type(BinaryType::Arraybuffer);
+
+        let connected = Rc::new(Cell::new(false));
+        let on_open = Closure::<dyn FnMut()>::new({
+            let sender = sender.clone();
+            let connected = connected.clone();
+            move || {
+                connected.set(true);
+                let _ = sender.try_send(SocketAction::Connect(handle, ConnectionState::Connected));
+            }
+        });
+        let on_message = Closure::<dyn FnMut(MessageEvent)>::new({
+            let sender = sender.clone();
+            move |event: MessageEvent| {
+                let data = if let Ok(buffer) = event.data().dyn_into::<ArrayBuffer>() {
+                    Uint8Array::new(&buffer).to_vec()
+                } else if let Some(text) = event.data().as_string() {
+                    text.into_bytes()
+                } else {
+                    return;
+                };
+                let _ = sender.try_send(SocketAction::Data(handle, data));
+            }
+        });
+        let on_close = Closure::<dyn FnMut()>::new(move || {
+            // A WebSocket that never opened is closed straight away instead.
+            let action = if connected.get() {
+                SocketAction::Close(handle)
+            } else {
+                SocketAction::Connect(handle, ConnectionState::Failed)
+            };
+            let _ = sender.try_send(action);
+        });
+        socket.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+        socket.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+        socket.set_onclose(Some(on_close.as_ref().unchecked_ref()));
+
+        spawn_local(async move {
+            // The receiver is closed once the socket is closed on the player's side.
+            while let Ok(data) = receiver.recv().await {
+                // If this fails, the WebSocket is closing, and `on_close` will report it.
+                if let Err(e) = socket.send_with_u8_array(&data) {
+                    tracing::warn!("Error writing to WebSocket proxy: {:?}", e);
+                }
+            }
+            socket.set_onopen(None);
+            socket.set_onmessage(None);
+            socket.set_onclose(None);
+            let _ = socket.close();
+            // The callbacks have to live for as long as the WebSocket can call them.
+            drop((on_open, on_message, on_close));
+        });
+    }
 }
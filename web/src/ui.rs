@@ -1,22 +1,30 @@
 use super::JavascriptPlayer;
-use ruffle_core::backend::ui::{FullscreenError, MouseCursor, UiBackend};
+use ruffle_core::backend::ui::{FontDefinition, FullscreenError, MouseCursor, UiBackend};
+use ruffle_render::bounding_box::BoundingBox;
 use ruffle_web_common::JsResult;
 use std::borrow::Cow;
-use web_sys::HtmlCanvasElement;
+use web_sys::{HtmlCanvasElement, HtmlInputElement};
 
 /// An implementation of `UiBackend` utilizing `web_sys` bindings to input APIs.
 pub struct WebUiBackend {
     js_player: JavascriptPlayer,
     canvas: HtmlCanvasElement,
+    /// The hidden input that text is typed into, for input methods and on-screen keyboards.
+    text_input: HtmlInputElement,
     cursor_visible: bool,
     cursor: MouseCursor,
 }
 
 impl WebUiBackend {
-    pub fn new(js_player: JavascriptPlayer, canvas: &HtmlCanvasElement) -> Self {
+    pub fn new(
+        js_player: JavascriptPlayer,
+        canvas: &HtmlCanvasElement,
+        text_input: &HtmlInputElement,
+    ) -> Self {
         Self {
             js_player,
             canvas: canvas.clone(),
+            text_input: text_input.clone(),
             cursor_visible: true,
             cursor: MouseCursor::Arrow,
         }
@@ -38,6 +46,22 @@ impl WebUiBackend {
             .set_property("cursor", cursor)
             .warn_on_error();
     }
+
+    /// Shows the hidden text input at a position over the canvas, in CSS pixels, and focuses it.
+    fn show_text_input(&self, left: f64, top: f64, height: f64) {
+        let style = self.text_input.style();
+        style
+            .set_property("left", &format!("{left}px"))
+            .warn_on_error();
+        style
+            .set_property("top", &format!("{top}px"))
+            .warn_on_error();
+        style
+            .set_property("height", &format!("{height}px"))
+            .warn_on_error();
+        style.set_property("display", "block").warn_on_error();
+        self.text_input.focus().warn_on_error();
+    }
 }
 
 impl UiBackend for WebUiBackend {
@@ -82,4 +106,43 @@ impl UiBackend for WebUiBackend {
     fn message(&self, message: &str) {
         self.js_player.display_message(message);
     }
+
+    fn load_device_font(
+        &self,
+        _name: &str,
+        _is_bold: bool,
+        _is_italic: bool,
+        _register: &mut dyn FnMut(FontDefinition),
+    ) {
+        // Browsers don't give pages the files of the fonts that they have, so device text
+        // is drawn with the fonts that were registered with the player.
+    }
+
+    fn set_text_input_area(&mut self, area: Option<BoundingBox>) {
+        if let Some(area) = area {
+            // The canvas is sized in device pixels, while the page is laid out in CSS pixels.
+            let scale =
+                f64::from(self.canvas.client_width()) / f64::from(self.canvas.width().max(1));
+            self.show_text_input(
+                area.x_min.to_pixels() * scale,
+                area.y_min.to_pixels() * scale,
+                area.height().to_pixels() * scale,
+            );
+        } else {
+            self.text_input.blur().warn_on_error();
+            self.text_input
+                .style()
+                .set_property("display", "none")
+                .warn_on_error();
+        }
+    }
+
+    fn request_soft_keyboard(&mut self) -> bool {
+        // Mobile browsers show their keyboard once an input is focused.
+        let display = self.text_input.style().get_property_value("display");
+        if display.map_or(false, |display| display == "none") {
+            self.show_text_input(0.0, 0.0, 1.0);
+        }
+        ruffle_web_common::is_mobile_or_tablet()
+    }
 }